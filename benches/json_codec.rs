@@ -0,0 +1,64 @@
+//! Criterion microbench for the JSON codec on the `POST /pets` path: parsing the request
+//! body and serializing the created pet, with [`SerdeJsonCodec`] and (with
+//! `--features simd-json`) `SimdJsonCodec`.
+//!
+//! ```text
+//! cargo bench --bench json_codec --features simd-json
+//! ```
+//!
+//! Criterion setup is infallible test data; the workspace still warns on `expect`/`unwrap`
+//! in application code — opt out here only.
+#![allow(clippy::expect_used)]
+#![allow(clippy::unwrap_used)]
+
+use brrtrouter::server::{JsonCodec, SerdeJsonCodec};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::Value;
+use std::hint::black_box;
+
+/// `POST /pets` body: a pet with a category, photo URLs and tags.
+fn add_pet_body() -> Vec<u8> {
+    let tags: Vec<Value> = (0..16)
+        .map(|i| serde_json::json!({ "id": i, "name": format!("tag-{i}") }))
+        .collect();
+    let photos: Vec<String> = (0..8)
+        .map(|i| format!("https://cdn.example.com/pets/doggie/{i}.png"))
+        .collect();
+    serde_json::to_vec(&serde_json::json!({
+        "id": 10,
+        "name": "doggie",
+        "category": { "id": 1, "name": "Dogs" },
+        "photoUrls": photos,
+        "tags": tags,
+        "status": "available",
+        "description": "A very good dog with a long description ".repeat(8),
+    }))
+    .unwrap()
+}
+
+fn bench_codec<C: JsonCodec>(c: &mut Criterion, name: &str) {
+    let body = add_pet_body();
+    let parsed = C::parse(&body).expect("valid body");
+
+    c.bench_function(&format!("{name}_parse_add_pet"), |b| {
+        b.iter(|| black_box(C::parse(black_box(&body))))
+    });
+    c.bench_function(&format!("{name}_serialize_pet"), |b| {
+        b.iter(|| black_box(C::to_vec(black_box(&parsed))))
+    });
+}
+
+fn bench_serde_json(c: &mut Criterion) {
+    bench_codec::<SerdeJsonCodec>(c, "serde_json");
+}
+
+#[cfg(feature = "simd-json")]
+fn bench_simd_json(c: &mut Criterion) {
+    bench_codec::<brrtrouter::server::json::SimdJsonCodec>(c, "simd_json");
+}
+
+#[cfg(feature = "simd-json")]
+criterion_group!(json_benches, bench_serde_json, bench_simd_json);
+#[cfg(not(feature = "simd-json"))]
+criterion_group!(json_benches, bench_serde_json);
+criterion_main!(json_benches);
@@ -120,6 +120,16 @@ fn bench_route_scalability(c: &mut Criterion) {
                 x_service: None,
                 x_brrtrouter_downstream_path: None,
                 x_brrtrouter_impl: None,
+                auth_optional: false,
+                forward_claims: None,
+                tags: Vec::new(),
+                deprecated: false,
+                websocket: false,
+                links: Vec::new(),
+                declared_statuses: Vec::new(),
+                schema_overrides: Default::default(),
+                created_location: None,
+                response_headers: Default::default(),
                 method: Method::GET,
                 path_pattern: Arc::from(format!("/api/v1/resource{i}/{{id}}").as_str()),
                 handler_name: Arc::from(format!("handler_{i}").as_str()),
@@ -130,6 +140,7 @@ fn bench_route_scalability(c: &mut Criterion) {
                 request_content_types: Vec::new(),
                 response_schema: None,
                 example: None,
+                examples: Vec::new(),
                 responses: std::collections::HashMap::new(),
                 security: Vec::new(),
                 example_name: "test".to_string(),
@@ -138,6 +149,8 @@ fn bench_route_scalability(c: &mut Criterion) {
                 sse: false,
                 estimated_request_body_bytes: None,
                 x_brrtrouter_stack_size: None,
+                x_brrtrouter_timeout_ms: None,
+                x_slow_threshold_ms: None,
                 cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
             });
         }
@@ -12,5 +12,5 @@ pub fn handle(_req: TypedHandlerRequest<Request>) -> Response {
         tx.send(format!("tick {i}"));
     }
     drop(tx);
-    Response(rx.collect())
+    Response(rx.correlate(_req.extensions.get()).collect())
 }
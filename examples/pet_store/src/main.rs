@@ -321,15 +321,16 @@ fn main() -> io::Result<()> {
     let router = std::sync::Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes.clone())));
     // Dump initial route table — ArcSwap load is infallible.
     router.load().dump_routes();
-    let dispatcher = std::sync::Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher));
-    let mut service = AppService::new(
-        router,
-        dispatcher,
-        schemes,
-        spec_path.clone(),
-        args.static_dir.clone(),
-        Some(args.doc_dir.clone()),
-    );
+    let mut builder = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(spec_path.clone())
+        .doc_dir(args.doc_dir.clone());
+    if let Some(dir) = &args.static_dir {
+        builder = builder.static_dir(dir);
+    }
+    let mut service = builder.build().map_err(io::Error::other)?;
 
     // Pre-compile all JSON schemas at startup for optimal performance
     let compiled_count = service.precompile_schemas(&routes);
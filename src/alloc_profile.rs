@@ -0,0 +1,252 @@
+//! Allocation counts per request phase (`alloc-profiling` feature).
+//!
+//! Measures how many heap allocations, and how many bytes, each phase of request handling
+//! makes, to put numbers on codec and zero-copy changes:
+//!
+//! | Phase | Covers |
+//! |---|---|
+//! | `parse` | reading the method, path, headers, query and body off the connection |
+//! | `validate` | security, parameter and request-body checks of a routed request |
+//! | `dispatch` | the handler coroutine: request conversion, the handler, typed response conversion |
+//! | `serialize` | writing the status, headers and body to the connection |
+//!
+//! Build with `--features alloc-profiling`: the crate then installs [`CountingAllocator`] as
+//! the global allocator, wrapping jemalloc when the `jemalloc` feature is also on. A binary
+//! that declares its own `#[global_allocator]` cannot be built with the feature.
+//!
+//! Totals are exported on `/metrics` as `brrtrouter_alloc_phase_allocations_total{phase}`,
+//! `brrtrouter_alloc_phase_bytes_total{phase}` and `brrtrouter_alloc_phase_samples_total{phase}`
+//! (phases measured), and read in code with [`snapshot`]. Without the feature, [`phase`]
+//! compiles to nothing and no allocator is provided.
+//!
+//! Counts are kept per OS thread. A phase during which its coroutine moved to another thread
+//! is not counted; one during which another coroutine ran on the same thread also counts that
+//! coroutine's allocations. Profile with a steady, modest load for stable averages.
+
+/// A measured stage of request handling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Reading the request off the connection
+    Parse,
+    /// Routing, security and request validation
+    Validate,
+    /// The handler coroutine
+    Dispatch,
+    /// Writing the response
+    Serialize,
+}
+
+impl Phase {
+    /// Every phase, in request order
+    pub const ALL: [Phase; 4] = [
+        Phase::Parse,
+        Phase::Validate,
+        Phase::Dispatch,
+        Phase::Serialize,
+    ];
+
+    /// Metric label (`parse`, `validate`, `dispatch`, `serialize`)
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Validate => "validate",
+            Phase::Dispatch => "dispatch",
+            Phase::Serialize => "serialize",
+        }
+    }
+}
+
+/// Totals recorded for one [`Phase`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStats {
+    /// Times the phase was measured
+    pub samples: u64,
+    /// Allocations (including reallocations) made during those samples
+    pub allocations: u64,
+    /// Bytes requested by those allocations
+    pub bytes: u64,
+}
+
+impl PhaseStats {
+    /// Average allocations per sample
+    #[must_use]
+    pub fn allocations_per_sample(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.allocations as f64 / self.samples as f64
+        }
+    }
+}
+
+/// Start measuring `phase`; the allocations until the guard drops are added to its totals.
+#[inline]
+#[must_use = "the phase is measured until the guard is dropped"]
+pub fn phase(phase: Phase) -> PhaseGuard {
+    #[cfg(feature = "alloc-profiling")]
+    {
+        PhaseGuard(counting::PhaseStart::now(phase))
+    }
+    #[cfg(not(feature = "alloc-profiling"))]
+    {
+        let _ = phase;
+        PhaseGuard(())
+    }
+}
+
+/// Measures one [`Phase`] until dropped (see [`phase`])
+#[derive(Debug)]
+pub struct PhaseGuard(
+    #[cfg(feature = "alloc-profiling")] counting::PhaseStart,
+    #[cfg(not(feature = "alloc-profiling"))] (),
+);
+
+#[cfg(feature = "alloc-profiling")]
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+#[cfg(feature = "alloc-profiling")]
+pub use counting::{reset, snapshot, CountingAllocator};
+
+#[cfg(feature = "alloc-profiling")]
+mod counting {
+    use super::{Phase, PhaseStats};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    thread_local! {
+        /// `(allocations, bytes)` made on this thread; const-initialized so the allocator can
+        /// touch it without allocating.
+        static THREAD: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+    }
+
+    /// `[samples, allocations, bytes]` per phase, indexed like [`Phase::ALL`]
+    static TOTALS: [[AtomicU64; 3]; 4] = [const { [const { AtomicU64::new(0) }; 3] }; 4];
+
+    fn record(bytes: usize) {
+        let _ = THREAD.try_with(|t| {
+            let (count, total) = t.get();
+            t.set((count + 1, total + bytes as u64));
+        });
+    }
+
+    /// Global allocator wrapper counting allocations per thread, for [`super::phase`]
+    ///
+    /// Installed by the crate under the `alloc-profiling` feature.
+    #[derive(Debug, Default)]
+    pub struct CountingAllocator<A = System>(A);
+
+    impl CountingAllocator<System> {
+        /// Count allocations made through the system allocator
+        #[must_use]
+        pub const fn system() -> Self {
+            Self(System)
+        }
+    }
+
+    impl<A> CountingAllocator<A> {
+        /// Count allocations made through `inner`
+        #[must_use]
+        pub const fn new(inner: A) -> Self {
+            Self(inner)
+        }
+    }
+
+    // SAFETY: every call is forwarded unchanged to the wrapped allocator; counting only
+    // touches a const-initialized thread-local and never allocates.
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            record(layout.size());
+            self.0.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            record(layout.size());
+            self.0.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            record(new_size);
+            self.0.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.0.dealloc(ptr, layout);
+        }
+    }
+
+    /// Thread and counters a phase started with
+    #[derive(Debug)]
+    pub struct PhaseStart {
+        phase: Phase,
+        thread: usize,
+        allocations: u64,
+        bytes: u64,
+    }
+
+    /// Identity of the current thread's counters (their address) and their values
+    fn current() -> (usize, u64, u64) {
+        THREAD
+            .try_with(|t| {
+                let (allocations, bytes) = t.get();
+                (t as *const _ as usize, allocations, bytes)
+            })
+            .unwrap_or((0, 0, 0))
+    }
+
+    impl PhaseStart {
+        pub(super) fn now(phase: Phase) -> Self {
+            let (thread, allocations, bytes) = current();
+            Self {
+                phase,
+                thread,
+                allocations,
+                bytes,
+            }
+        }
+
+        pub(super) fn finish(&self) {
+            let (thread, allocations, bytes) = current();
+            if thread == 0 || thread != self.thread {
+                return;
+            }
+            let totals = &TOTALS[self.phase as usize];
+            totals[0].fetch_add(1, Ordering::Relaxed);
+            totals[1].fetch_add(allocations - self.allocations, Ordering::Relaxed);
+            totals[2].fetch_add(bytes - self.bytes, Ordering::Relaxed);
+        }
+    }
+
+    /// Totals per phase since start (or the last [`reset`]), in [`Phase::ALL`] order
+    #[must_use]
+    pub fn snapshot() -> Vec<(Phase, PhaseStats)> {
+        Phase::ALL
+            .iter()
+            .map(|&phase| {
+                let totals = &TOTALS[phase as usize];
+                (
+                    phase,
+                    PhaseStats {
+                        samples: totals[0].load(Ordering::Relaxed),
+                        allocations: totals[1].load(Ordering::Relaxed),
+                        bytes: totals[2].load(Ordering::Relaxed),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Zero every phase's totals
+    pub fn reset() {
+        for totals in &TOTALS {
+            for counter in totals {
+                counter.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     load_spec,
     router::Router,
     server::{AppService, HttpServer},
+    spec::{LinkTarget, ResponseLink, RouteMeta, RouteSecurityPolicy},
 };
 use arc_swap::ArcSwap;
 use clap::{Parser, Subcommand, ValueEnum};
@@ -65,6 +66,29 @@ pub enum Commands {
         /// If not provided, will auto-detect alongside the OpenAPI spec
         #[arg(long)]
         dependencies_config: Option<PathBuf>,
+
+        /// Map string/integer formats to dedicated types: date-time → chrono::DateTime<Utc>,
+        /// date → chrono::NaiveDate, uuid → uuid::Uuid, byte → Base64Bytes, int64 → i64.
+        /// Adds chrono/uuid dependencies to the generated crate.
+        #[arg(long, default_value_t = false)]
+        rich_types: bool,
+
+        /// Also emit a multi-stage Dockerfile, docker-compose.yml (Prometheus/Grafana/Jaeger)
+        /// and observability/ configs. Port and healthcheck interval come from config.yaml
+        /// (`port`, `docker.healthcheck_interval_secs`).
+        #[arg(long, default_value_t = false)]
+        with_docker: bool,
+
+        /// Emit a Cargo workspace instead of a single crate: `{name}-types` (a library with
+        /// the schema types, for clients to depend on) and `{name}-server` (handlers,
+        /// controllers, main) depending on it
+        #[arg(long, default_value_t = false)]
+        workspace: bool,
+
+        /// Fail when a JSON request or response body, or a 2xx response, has no schema
+        /// (otherwise each such operation is printed as a warning)
+        #[arg(long, default_value_t = false)]
+        strict_schema: bool,
     },
     /// Generate implementation stubs in impl crate
     ///
@@ -98,6 +122,10 @@ pub enum Commands {
         /// Sync only: patch handler signature and Response struct literal to match spec; do not overwrite body. Only affects files that contain the user-owned sentinel.
         #[arg(long, default_value_t = false)]
         sync: bool,
+
+        /// Use the --rich-types mapping; must match how the gen crate was generated
+        #[arg(long, default_value_t = false)]
+        rich_types: bool,
     },
     /// Dry-run report for impl stub generation and registry (Tier 1 plan precursor)
     PlanImpl {
@@ -199,6 +227,40 @@ pub enum Commands {
         #[arg(long, default_value_t = false)]
         errors_only: bool,
     },
+    /// Validate an OpenAPI specification and report every structural issue
+    ///
+    /// Unlike loading the spec, which stops at the first problem, this collects all issues
+    /// (missing handler names, missing responses, unresolved `$ref`s, duplicate
+    /// operationIds, conflicting path templates, ...) with JSON-pointer locations. Exits with
+    /// an error when any of them is an error rather than a warning.
+    Validate {
+        /// Path to the OpenAPI specification file (YAML or JSON)
+        #[arg(short, long)]
+        spec: PathBuf,
+    },
+    /// Inspect routes and their security requirements
+    ///
+    /// Prints one row per operation: method, path, handler, and the schemes and scopes it
+    /// requires. OR-composed requirements are joined with `OR`, schemes within one
+    /// requirement with `AND`; routes without security are shown as `public`.
+    ///
+    /// With `--output json`, prints a JSON array with one object per operation instead
+    /// (method, path, handler, params, security, tags, deprecated, schema refs) for tooling.
+    ///
+    /// Operations whose `x-feature` is not enabled are left out, as the server leaves them out.
+    Inspect {
+        /// Path to the OpenAPI specification file (YAML or JSON)
+        #[arg(short, long)]
+        spec: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = InspectFormat::Text)]
+        output: InspectFormat,
+
+        /// Enabled features, comma-separated (default: `BRRTR_FEATURES`)
+        #[arg(long, value_delimiter = ',')]
+        features: Option<Vec<String>>,
+    },
     /// Run the server for a spec using echo handlers
     Serve {
         /// Path to the OpenAPI specification file (YAML or JSON)
@@ -215,6 +277,16 @@ pub enum Commands {
     },
 }
 
+/// Output format of `brrtrouter-gen inspect`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum InspectFormat {
+    /// Human-readable route/security table
+    #[default]
+    Text,
+    /// JSON array of routes, for scripts and other tooling
+    Json,
+}
+
 /// Specific parts of the generated project that can be selectively regenerated
 ///
 /// Used with the `--only` flag to limit code generation to specific components.
@@ -255,13 +327,26 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
             version,
             package_name,
             dependencies_config,
+            rich_types,
+            with_docker,
+            workspace,
+            strict_schema,
         } => {
             let spec_path = spec
                 .to_str()
                 .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in spec path"))?;
-            let (_routes, _slug) = load_spec(spec_path)?;
-            let scope = map_only_to_scope(only.as_deref());
-            let project_dir = crate::generator::generate_project_with_options(
+            let (routes, _slug) = load_spec(spec_path)?;
+            let missing = crate::spec::missing_schemas(&routes);
+            if *strict_schema && !missing.is_empty() {
+                return Err(crate::spec::MissingSchemaError(missing).into());
+            }
+            for route in &missing {
+                println!("⚠️  no schema: {route}");
+            }
+            let mut scope = map_only_to_scope(only.as_deref());
+            scope.docker = *with_docker;
+            scope.workspace = *workspace;
+            let project_dir = crate::generator::generate_project_with_type_options(
                 spec.as_path(),
                 output.as_deref(),
                 *force,
@@ -270,6 +355,9 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
                 Some(version.clone()),
                 package_name.as_deref(),
                 dependencies_config.as_deref(),
+                crate::generator::TypeOptions {
+                    rich_types: *rich_types,
+                },
             )
             .expect("failed to generate example project");
             // Format the newly generated project (single implementation: generator owns fmt)
@@ -285,14 +373,18 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
             path,
             force,
             sync,
+            rich_types,
         } => {
-            crate::generator::generate_impl_stubs(
+            crate::generator::generate_impl_stubs_with_type_options(
                 spec.as_path(),
                 output.as_path(),
                 component_name.as_deref(),
                 path.as_deref(),
                 *force,
                 *sync,
+                crate::generator::TypeOptions {
+                    rich_types: *rich_types,
+                },
             )?;
             // Format generated stubs using the same implementation as generate
             crate::generator::format_project(output.as_path())?;
@@ -398,11 +490,43 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
 
             Ok(())
         }
+        Commands::Validate { spec } => {
+            let issues = crate::spec::validate_spec(spec.as_path())?;
+            crate::validator::print_validation_report(&issues);
+            let errors = issues.iter().filter(|i| i.is_error()).count();
+            if errors > 0 {
+                return Err(anyhow::anyhow!("spec has {errors} error(s)").into());
+            }
+            Ok(())
+        }
+        Commands::Inspect {
+            spec,
+            output,
+            features,
+        } => {
+            let spec_path = spec
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in spec path"))?;
+            let (routes, _slug) = load_spec(spec_path)?;
+            let routes = inspected_routes(routes, features.as_deref());
+            match output {
+                InspectFormat::Text => print!("{}", render_security_matrix(&routes)),
+                InspectFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&render_routes_json(&routes))?
+                ),
+            }
+            Ok(())
+        }
         Commands::Serve { spec, watch, addr } => {
             let spec_path = spec
                 .to_str()
                 .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in spec path"))?;
             let (routes, schemes, _slug) = crate::spec::load_spec_full(spec_path)?;
+            let routes = crate::spec::enabled_routes(
+                routes,
+                &crate::runtime_config::enabled_features_from_env(),
+            );
             let router = Arc::new(ArcSwap::from_pointee(Router::new(routes.clone())));
             let mut dispatcher = Dispatcher::new();
             for r in &routes {
@@ -419,14 +543,12 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
                 dispatcher.add_route(r.clone(), tx);
             }
             let dispatcher = Arc::new(ArcSwap::from_pointee(dispatcher));
-            let mut service = AppService::new(
-                Arc::clone(&router),
-                Arc::clone(&dispatcher),
-                schemes,
-                spec.clone(),
-                None,
-                None,
-            );
+            let mut service = AppService::builder()
+                .shared_router(Arc::clone(&router))
+                .shared_dispatcher(Arc::clone(&dispatcher))
+                .security_schemes(schemes)
+                .spec_path(spec.clone())
+                .build()?;
             if *watch {
                 let watcher = watch_spec(
                     spec.clone(),
@@ -460,6 +582,188 @@ pub fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+/// Format a route's security policy as `Scheme[scope,...] AND ... OR ...`.
+fn format_security_policy(route: &RouteMeta) -> String {
+    match route.security_policy() {
+        RouteSecurityPolicy::Public => "public".to_string(),
+        RouteSecurityPolicy::Alternatives {
+            alternatives,
+            allows_anonymous,
+        } => {
+            let mut parts: Vec<String> = alternatives
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|s| {
+                            if s.scopes.is_empty() {
+                                s.scheme.clone()
+                            } else {
+                                format!("{}[{}]", s.scheme, s.scopes.join(","))
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" AND ")
+                })
+                .collect();
+            if allows_anonymous {
+                parts.push("anonymous".to_string());
+            }
+            parts.join(" OR ")
+        }
+    }
+}
+
+/// Routes `brrtrouter-gen inspect` lists: those enabled by `--features` when given, else by
+/// `BRRTR_FEATURES`.
+pub(crate) fn inspected_routes(
+    routes: Vec<RouteMeta>,
+    features: Option<&[String]>,
+) -> Vec<RouteMeta> {
+    let features = match features {
+        Some(names) => names
+            .iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        None => crate::runtime_config::enabled_features_from_env(),
+    };
+    crate::spec::enabled_routes(routes, &features)
+}
+
+/// Render the route → schemes → scopes table printed by `brrtrouter-gen inspect`.
+pub(crate) fn render_security_matrix(routes: &[RouteMeta]) -> String {
+    let rows: Vec<[String; 4]> = routes
+        .iter()
+        .map(|r| {
+            [
+                r.method.to_string(),
+                r.path_pattern.to_string(),
+                r.handler_name.to_string(),
+                format_security_policy(r),
+            ]
+        })
+        .collect();
+    let headers = ["METHOD", "PATH", "HANDLER", "SECURITY"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    let mut push_row = |cells: [&str; 4]| {
+        out.push_str(&format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}\n",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        ));
+    };
+    push_row(headers);
+    for row in &rows {
+        push_row([&row[0], &row[1], &row[2], &row[3]]);
+    }
+    out
+}
+
+/// Summarise a schema for `inspect --output json`: `{"ref": ...}` for a component
+/// schema, `{"type": "array", "items": ...}` for arrays, otherwise `{"type": ...}`.
+fn schema_summary(schema: &serde_json::Value) -> serde_json::Value {
+    if let Some(name) = schema.get("x-ref-name").and_then(|v| v.as_str()) {
+        return serde_json::json!({ "ref": format!("#/components/schemas/{name}") });
+    }
+    let ty = schema
+        .get("type")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    match schema.get("items") {
+        Some(items) if ty == "array" => {
+            serde_json::json!({ "type": ty, "items": schema_summary(items) })
+        }
+        _ => serde_json::json!({ "type": ty }),
+    }
+}
+
+/// Describe a response link for `inspect --output json`: `operation_id` or `operation_ref` as
+/// declared, and `target` (`{"method", "path"}`) when the linked operation is in the spec.
+fn link_json(link: &ResponseLink) -> serde_json::Value {
+    let mut entry = serde_json::json!({
+        "status": link.status,
+        "name": link.name,
+        "target": link.target_route.as_ref().map(|(method, path)| {
+            serde_json::json!({ "method": method.as_str(), "path": path.as_ref() })
+        }),
+        "parameters": link
+            .parameters
+            .iter()
+            .cloned()
+            .collect::<serde_json::Map<_, _>>(),
+        "request_body": link.request_body,
+        "description": link.description,
+    });
+    match &link.target {
+        LinkTarget::OperationId(id) => entry["operation_id"] = id.as_str().into(),
+        LinkTarget::OperationRef(reference) => entry["operation_ref"] = reference.as_str().into(),
+    }
+    entry
+}
+
+/// Build the JSON array printed by `brrtrouter-gen inspect --output json`.
+///
+/// `security` lists the OR-composed alternatives, each an AND-list of
+/// `{"scheme", "scopes"}`; it is empty for public routes, which (like routes with a `{}`
+/// alternative) have `allows_anonymous: true`.
+pub(crate) fn render_routes_json(routes: &[RouteMeta]) -> serde_json::Value {
+    let routes: Vec<serde_json::Value> = routes
+        .iter()
+        .map(|r| {
+            let params: Vec<serde_json::Value> = r
+                .parameters
+                .iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "name": p.name,
+                        "in": p.location.to_string().to_ascii_lowercase(),
+                        "required": p.required,
+                        "type": p
+                            .schema
+                            .as_ref()
+                            .and_then(|s| s.get("type"))
+                            .cloned()
+                            .unwrap_or(serde_json::Value::Null),
+                    })
+                })
+                .collect();
+            let (alternatives, allows_anonymous) = match r.security_policy() {
+                RouteSecurityPolicy::Public => (Vec::new(), true),
+                RouteSecurityPolicy::Alternatives {
+                    alternatives,
+                    allows_anonymous,
+                } => (alternatives, allows_anonymous),
+            };
+            serde_json::json!({
+                "method": r.method.as_str(),
+                "path": r.path_pattern.as_ref(),
+                "handler": r.handler_name.as_ref(),
+                "params": params,
+                "security": alternatives,
+                "allows_anonymous": allows_anonymous,
+                "tags": r.tags,
+                "deprecated": r.deprecated,
+                "request_schema": r.request_schema.as_ref().map(schema_summary),
+                "response_schema": r.response_schema.as_ref().map(schema_summary),
+                "links": r.links.iter().map(link_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(routes)
+}
+
 /// Convert CLI `--only` parts to a `GenerationScope` configuration
 ///
 /// If `only` is `None`, all parts are enabled. If `only` is provided,
@@ -476,6 +780,8 @@ fn map_only_to_scope(only: Option<&[OnlyPart]>) -> crate::generator::GenerationS
             registry: false,
             main: false,
             docs: false,
+            docker: false,
+            workspace: false,
         };
         for p in parts {
             match p {
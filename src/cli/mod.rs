@@ -28,20 +28,46 @@
 //!
 //! ### `validate`
 //!
-//! Validate an OpenAPI specification:
+//! Validate an OpenAPI specification, reporting every structural issue (not just the
+//! first) with its JSON-pointer location. Exits nonzero when any issue is an error:
 //!
 //! ```bash
 //! brrtrouter-gen validate --spec openapi.yaml
 //! ```
 //!
+//! ```text
+//! error [DuplicateOperationId] /paths/~1pets~1{id}/get/operationId: operationId 'get_pet' is already used at /paths/~1pet~1{id}/get
+//! warning [MissingResponseContent] /paths/~1pets/post/responses/201: Success response 201 declares no content
+//!
+//! 1 error(s), 1 warning(s)
+//! ```
+//!
 //! ### `inspect`
 //!
-//! Inspect routes and handlers in a specification:
+//! Inspect routes and handlers in a specification, including the security schemes and
+//! scopes each operation requires (an authorization matrix for audits):
 //!
 //! ```bash
 //! brrtrouter-gen inspect --spec openapi.yaml
 //! ```
 //!
+//! ```text
+//! METHOD  PATH        HANDLER    SECURITY
+//! GET     /health     health     public
+//! GET     /pets       list_pets  ApiKey OR OAuth[pets:read]
+//! ```
+//!
+//! `--output json` prints the same routes as a JSON array for tooling, with parameters,
+//! security alternatives, tags, deprecation, request/response schema refs, and response
+//! `links` (with the method and path of each linked operation):
+//!
+//! ```bash
+//! brrtrouter-gen inspect --spec openapi.yaml --output json
+//! ```
+//!
+//! Operations marked `x-feature` are listed only when their feature is enabled, by
+//! `--features admin,beta` or else `BRRTR_FEATURES`, as the server would serve them.
+//!
 //! ## Usage from Code
 //!
 //! ```rust,ignore
@@ -85,4 +111,4 @@ mod commands;
 #[cfg(test)]
 mod tests;
 
-pub use commands::{run_cli, Cli, Commands};
+pub use commands::{run_cli, Cli, Commands, InspectFormat};
@@ -65,6 +65,24 @@ fn test_all_commands_parse() {
             "out",
         ],
         vec!["brrtrouter-gen", "lint", "--spec", "test.yaml"],
+        vec!["brrtrouter-gen", "validate", "--spec", "test.yaml"],
+        vec!["brrtrouter-gen", "inspect", "--spec", "test.yaml"],
+        vec![
+            "brrtrouter-gen",
+            "inspect",
+            "--spec",
+            "test.yaml",
+            "--output",
+            "json",
+        ],
+        vec![
+            "brrtrouter-gen",
+            "inspect",
+            "--spec",
+            "test.yaml",
+            "--features",
+            "admin,beta",
+        ],
         vec!["brrtrouter-gen", "serve", "--spec", "test.yaml"],
     ];
 
@@ -73,3 +91,225 @@ fn test_all_commands_parse() {
         assert!(cli.is_ok(), "Failed to parse command: {:?}", args);
     }
 }
+
+#[test]
+fn test_inspect_security_matrix_distinguishes_public_and_or_requirements() {
+    let spec = r#"openapi: 3.1.0
+info:
+  title: Matrix
+  version: '1.0'
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+    OAuth:
+      type: oauth2
+      flows:
+        clientCredentials:
+          tokenUrl: https://auth.example.com/token
+          scopes:
+            pets:read: read pets
+paths:
+  /health:
+    get:
+      operationId: health
+      responses:
+        '200': { description: OK }
+  /pets:
+    get:
+      operationId: list_pets
+      security:
+        - ApiKey: []
+        - OAuth: [pets:read]
+      responses:
+        '200': { description: OK }
+  /me:
+    get:
+      operationId: me
+      security:
+        - OAuth: [pets:read]
+        - {}
+      responses:
+        '200': { description: OK }
+"#;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openapi.yaml");
+    std::fs::write(&path, spec).unwrap();
+    let (routes, _slug) = crate::load_spec(path.to_str().unwrap()).unwrap();
+
+    let table = super::commands::render_security_matrix(&routes);
+    let line_for = |handler: &str| {
+        table
+            .lines()
+            .find(|l| l.contains(handler))
+            .unwrap()
+            .to_string()
+    };
+    assert!(table.starts_with("METHOD"));
+    assert!(line_for("health").ends_with("public"));
+    assert!(line_for("list_pets").ends_with("ApiKey OR OAuth[pets:read]"));
+    assert!(line_for(" me ").ends_with("OAuth[pets:read] OR anonymous"));
+}
+
+#[test]
+fn test_inspect_json_describes_pet_store_routes() {
+    let (routes, _slug) = crate::load_spec("examples/pet_store/doc/openapi.yaml").unwrap();
+    let json = super::commands::render_routes_json(&routes);
+    let text = serde_json::to_string(&json).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+    let entries = parsed.as_array().unwrap();
+    assert_eq!(entries.len(), routes.len());
+    for entry in entries {
+        for key in [
+            "method",
+            "path",
+            "handler",
+            "params",
+            "security",
+            "allows_anonymous",
+            "tags",
+            "deprecated",
+            "request_schema",
+            "response_schema",
+            "links",
+        ] {
+            assert!(entry.get(key).is_some(), "{key} missing in {entry}");
+        }
+    }
+
+    let route = |handler: &str| {
+        entries
+            .iter()
+            .find(|e| e["handler"] == handler)
+            .unwrap_or_else(|| panic!("no route for {handler}"))
+    };
+
+    let list_pets = route("list_pets");
+    assert_eq!(list_pets["method"], "GET");
+    assert_eq!(list_pets["path"], "/pets");
+    let limit = list_pets["params"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == "limit")
+        .unwrap();
+    assert_eq!(limit["in"], "query");
+    assert_eq!(limit["required"], false);
+    assert_eq!(limit["type"], "integer");
+    assert_eq!(
+        list_pets["response_schema"],
+        serde_json::json!({"type": "array", "items": {"ref": "#/components/schemas/Pet"}})
+    );
+
+    let add_pet = route("add_pet");
+    assert_eq!(
+        add_pet["request_schema"]["ref"],
+        "#/components/schemas/CreatePetRequest"
+    );
+    assert!(!add_pet["security"].as_array().unwrap().is_empty());
+    assert!(add_pet["security"][0][0]["scheme"].is_string());
+}
+
+#[test]
+fn test_inspect_json_includes_response_links() {
+    let spec = r#"openapi: 3.1.0
+info:
+  title: Links
+  version: '1.0'
+paths:
+  /users/{id}:
+    get:
+      operationId: get_user
+      responses:
+        '200':
+          description: OK
+          links:
+            posts:
+              operationId: list_user_posts
+              parameters:
+                user_id: '$response.body#/id'
+  /users/{user_id}/posts:
+    get:
+      operationId: list_user_posts
+      responses:
+        '200': { description: OK }
+"#;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openapi.yaml");
+    std::fs::write(&path, spec).unwrap();
+    let (routes, _slug) = crate::load_spec(path.to_str().unwrap()).unwrap();
+
+    let json = super::commands::render_routes_json(&routes);
+    let get_user = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["handler"] == "get_user")
+        .unwrap();
+    assert_eq!(
+        get_user["links"],
+        serde_json::json!([{
+            "status": "200",
+            "name": "posts",
+            "operation_id": "list_user_posts",
+            "target": { "method": "GET", "path": "/users/{user_id}/posts" },
+            "parameters": { "user_id": "$response.body#/id" },
+            "request_body": null,
+            "description": null,
+        }])
+    );
+}
+
+#[test]
+fn test_inspect_leaves_out_routes_of_disabled_features() {
+    let spec = r#"
+openapi: 3.1.0
+info: { title: Admin, version: "1.0" }
+paths:
+  /health:
+    get:
+      operationId: health
+      responses:
+        '200': { description: OK }
+  /admin/users:
+    get:
+      operationId: list_users
+      x-feature: admin
+      responses:
+        '200': { description: OK }
+"#;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openapi.yaml");
+    std::fs::write(&path, spec).unwrap();
+    let (routes, _slug) = crate::load_spec(path.to_str().unwrap()).unwrap();
+
+    let Commands::Inspect { features, .. } = Cli::try_parse_from([
+        "brrtrouter-gen",
+        "inspect",
+        "--spec",
+        "openapi.yaml",
+        "--features",
+        "admin, beta",
+    ])
+    .unwrap()
+    .command
+    else {
+        panic!("expected inspect");
+    };
+    let enabled = super::commands::inspected_routes(routes.clone(), features.as_deref());
+    let table = super::commands::render_security_matrix(&enabled);
+    assert!(table.contains("list_users"), "{table}");
+
+    let disabled = super::commands::inspected_routes(routes, Some(&["beta".to_string()]));
+    let table = super::commands::render_security_matrix(&disabled);
+    assert!(table.contains("health"), "{table}");
+    assert!(!table.contains("list_users"), "{table}");
+    assert!(super::commands::render_routes_json(&disabled)
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|entry| entry["handler"] != "list_users"));
+}
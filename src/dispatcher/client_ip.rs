@@ -0,0 +1,162 @@
+//! Client address resolution from `Forwarded` (RFC 7239) / `X-Forwarded-For` chains.
+//!
+//! Proxies append the address they received a connection from, so the chain is walked from
+//! right (nearest hop) to left and the first address outside the trusted-proxy set is the
+//! client. Everything left of that address was written by an untrusted party and is ignored,
+//! which is what makes a client-supplied `X-Forwarded-For: 1.2.3.4` harmless.
+//!
+//! Headers are only consulted when the direct peer is itself a trusted proxy. When the
+//! transport does not report the peer (`HandlerRequest::peer_addr` is `None`, as with
+//! `may_minihttp` and Unix socket listeners), there is no way to tell a proxy from a client
+//! writing the headers itself, so they are never used and no address is resolved.
+//!
+//! Connections served through `HttpServer::start_with_websockets` or `start_unix` reach the
+//! HTTP server through a loopback relay. It reports the client's own address as the peer
+//! (none for Unix sockets) but passes client headers through unchanged, so the relay must
+//! never be treated as a trusted proxy: its loopback address vouches for nothing.
+
+use std::net::{IpAddr, Ipv6Addr};
+
+use ipnet::IpNet;
+
+use super::core::HeaderVec;
+use super::headers::HeaderLookup;
+
+fn is_trusted(ip: IpAddr, trusted: &[IpNet]) -> bool {
+    trusted.iter().any(|net| net.contains(&ip))
+}
+
+/// Parse one hop: `203.0.113.7`, `203.0.113.7:4711`, `2001:db8::1`, `[2001:db8::1]:4711`,
+/// optionally quoted (`Forwarded: for="[2001:db8::1]"`).
+fn parse_node(raw: &str) -> Option<IpAddr> {
+    let node = raw.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        let (addr, _port) = rest.split_once(']')?;
+        return addr.parse::<Ipv6Addr>().ok().map(IpAddr::V6);
+    }
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    let (addr, _port) = node.rsplit_once(':')?;
+    addr.parse().ok()
+}
+
+/// The `for=` parameter of one `Forwarded` element, if any.
+fn forwarded_for(element: &str) -> Option<&str> {
+    element.split(';').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        key.trim().eq_ignore_ascii_case("for").then_some(value)
+    })
+}
+
+/// Resolve the client address; see the module docs for the trust rules.
+pub(crate) fn resolve(
+    peer: Option<IpAddr>,
+    headers: &HeaderVec,
+    trusted: &[IpNet],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !is_trusted(peer, trusted) {
+        return Some(peer);
+    }
+
+    let use_forwarded = headers.get("forwarded").is_some();
+    let header = if use_forwarded {
+        "forwarded"
+    } else {
+        "x-forwarded-for"
+    };
+
+    // Later header lines were appended by nearer proxies, so walk lines and elements in reverse.
+    let hops = headers
+        .get_all(header)
+        .rev()
+        .flat_map(|v| v.rsplit(','))
+        .map(|element| {
+            if use_forwarded {
+                forwarded_for(element).and_then(parse_node)
+            } else {
+                parse_node(element)
+            }
+        });
+
+    let mut nearest = peer;
+    for hop in hops {
+        // `unknown`, obfuscated identifiers or garbage: nothing further left can be trusted.
+        let Some(ip) = hop else { break };
+        nearest = ip;
+        if !is_trusted(ip, trusted) {
+            break;
+        }
+    }
+    Some(nearest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderVec {
+        pairs
+            .iter()
+            .map(|(k, v)| (Arc::from(*k), (*v).to_string()))
+            .collect()
+    }
+
+    fn nets(list: &[&str]) -> Vec<IpNet> {
+        list.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parses_node_forms() {
+        assert_eq!(parse_node(" 203.0.113.7 "), "203.0.113.7".parse().ok());
+        assert_eq!(parse_node("203.0.113.7:4711"), "203.0.113.7".parse().ok());
+        assert_eq!(parse_node("2001:db8::1"), "2001:db8::1".parse().ok());
+        assert_eq!(
+            parse_node("\"[2001:db8::1]:4711\""),
+            "2001:db8::1".parse().ok()
+        );
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[test]
+    fn forwarded_header_takes_precedence() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let h = headers(&[
+            ("x-forwarded-for", "198.51.100.1"),
+            (
+                "Forwarded",
+                "for=192.0.2.60;proto=https, for=\"10.0.0.2:80\"",
+            ),
+        ]);
+        assert_eq!(
+            resolve(Some("10.0.0.1".parse().unwrap()), &h, &trusted),
+            "192.0.2.60".parse().ok()
+        );
+    }
+
+    #[test]
+    fn multiple_header_lines_form_one_chain() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let h = headers(&[
+            ("x-forwarded-for", "198.51.100.1"),
+            ("x-forwarded-for", "10.0.0.3"),
+        ]);
+        assert_eq!(
+            resolve(Some("10.0.0.1".parse().unwrap()), &h, &trusted),
+            "198.51.100.1".parse().ok()
+        );
+    }
+
+    #[test]
+    fn unparseable_hop_stops_the_walk() {
+        let trusted = nets(&["10.0.0.0/8"]);
+        let h = headers(&[("x-forwarded-for", "198.51.100.1, unknown, 10.0.0.3")]);
+        assert_eq!(
+            resolve(Some("10.0.0.1".parse().unwrap()), &h, &trusted),
+            "10.0.0.3".parse().ok()
+        );
+    }
+}
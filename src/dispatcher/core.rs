@@ -16,24 +16,38 @@
 #![deny(clippy::unnecessary_to_owned)]
 
 #[allow(unused_imports)]
+use super::deadline::Deadline;
+use super::headers::HeaderLookup;
+use super::panic_guard::{PanicGuard, PanicGuardStats, PanicPolicy};
+use super::params::DecodedParams;
+use super::stack_guard::{exhausted_response, StackWatch};
+use crate::alloc_profile::{self, Phase};
 use crate::echo::echo_handler;
 use crate::ids::RequestId;
-use crate::router::{ParamVec, RouteMatch};
+use crate::router::{ParamVec, RawPathParams, RouteMatch};
+use crate::security::AuthContext;
+use crate::server::multipart::{is_multipart_form_data, Multipart};
+use crate::server::websocket::{WebSocketChannel, WebSocketHandler, WebSocketRequest};
+use crate::server::{ProblemDetails, PROBLEM_JSON};
 use crate::spec::RouteMeta;
+use crate::sse::SseCorrelation;
 use crate::worker_pool::{WorkerPool, WorkerPoolConfig};
-use http::Method;
+use bytes::Bytes;
+use http::{Extensions, Method};
+use ipnet::IpNet;
 use may::coroutine;
 use may::sync::mpsc;
 use serde::Serialize;
 use serde_json::Value;
 use smallvec::SmallVec;
 use std::collections::HashMap;
+use std::net::IpAddr;
 #[allow(unused_imports)]
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use crate::middleware::Middleware;
+use crate::middleware::{Middleware, ResponseTransform};
 
 /// Maximum inline headers/cookies before heap allocation
 /// Most requests have ≤16 headers (JSF: no heap in hot path)
@@ -48,6 +62,9 @@ pub const MAX_INLINE_HEADERS: usize = 16;
 /// - `Arc::clone()` is O(1) atomic increment vs O(n) string copy
 /// - Values remain `String` as they're per-request data from the HTTP request
 /// - Matches the optimization pattern used in ParamVec (P0-1)
+///
+/// Look headers up with [`HeaderLookup::get`] / [`HeaderLookup::get_all`], which compare
+/// names case-insensitively.
 pub type HeaderVec = SmallVec<[(Arc<str>, String); MAX_INLINE_HEADERS]>;
 
 /// Generate a unique request ID for tracing (ULID string)
@@ -76,7 +93,9 @@ pub struct HandlerRequest {
     pub path: String,
     /// Name of the handler that should process this request
     pub handler_name: String,
-    /// Path parameters extracted from the URL (stack-allocated for ≤8 params)
+    /// Path parameters extracted from the URL, percent-decoded per the router's
+    /// [`PathDecoding`](crate::router::PathDecoding) (stack-allocated for ≤8 params); see
+    /// [`Self::get_raw_path_param`] for the undecoded text
     pub path_params: ParamVec,
     /// Query string parameters (stack-allocated for ≤8 params)
     pub query_params: ParamVec,
@@ -86,10 +105,18 @@ pub struct HandlerRequest {
     pub cookies: HeaderVec,
     /// Request body parsed as JSON (if present)
     pub body: Option<Value>,
+    /// Request body exactly as received (if present)
+    ///
+    /// Signatures over a payload (e.g. Stripe or GitHub webhooks) must be checked against
+    /// these bytes, not against `body` re-serialized; see
+    /// [`crate::security::verify_hmac_sha256`]. Cloning is a reference-count bump.
+    pub raw_body: Option<Bytes>,
     /// Decoded JWT claims (if request was authenticated with JWT)
     ///
     /// This field is populated when a JWT token is successfully validated.
     /// Contains the decoded claims from the JWT payload (e.g., `sub`, `email`, `scope`, etc.).
+    /// Other schemes set it to whatever their provider's `extract_claims` returns (an API key
+    /// with a configured principal yields `{"sub": principal}`); see also [`Self::auth_context`].
     ///
     /// # Example
     ///
@@ -109,6 +136,193 @@ pub struct HandlerRequest {
     pub reply_tx: mpsc::Sender<HandlerResponse>,
     /// Guard for tracking queue depth and applying backpressure (decrements on Drop)
     pub queue_guard: Option<Arc<QueueDepthGuard>>,
+    /// Address of the directly connected peer, when the transport reports it
+    ///
+    /// Set for connections relayed from `HttpServer::start_with_websockets`' front listener,
+    /// which reports the client to the HTTP server in its relay preface. `None` for
+    /// [`crate::server::HttpServer::start`] (`may_minihttp` does not expose the peer) and for
+    /// Unix socket listeners. Use [`HandlerRequest::client_ip`] for the originating client.
+    pub peer_addr: Option<IpAddr>,
+    /// Headers to attach to downstream calls made on behalf of this request (BFF)
+    ///
+    /// Filled from `jwt_claims` by [`crate::middleware::ClaimsForwardingMiddleware`]
+    /// (`x-forward-claims`); [`crate::http::proxy_untyped`] sends them with the proxied
+    /// request. Empty unless a middleware populates it.
+    pub downstream_headers: HeaderVec,
+    /// Identity from the security scheme that authenticated this request
+    ///
+    /// Set for every scheme (JWT, JWKS, SPIFFE, API key, ...) once validation succeeds;
+    /// `None` for unsecured routes and anonymous `x-auth-optional` requests.
+    pub auth_context: Option<AuthContext>,
+    /// When the request is abandoned: set from the route's `x-brrtrouter-timeout-ms` and the
+    /// `Request-Timeout` header; `None` without either. Cancellation is cooperative — see
+    /// [`Deadline`].
+    pub deadline: Option<Deadline>,
+    /// Values attached by middleware for the handler, keyed by type
+    ///
+    /// Insert from [`crate::middleware::Middleware::prepare`] and read in the handler; see
+    /// [`Self::extensions`]. The map travels with the request into the handler coroutine
+    /// (values are owned by the request, not shared between requests), so stored types only
+    /// need to be `Clone + Send + Sync + 'static`.
+    ///
+    /// Requests to `text/event-stream` routes arrive with an
+    /// [`SseCorrelation`](crate::sse::SseCorrelation) already inserted, and requests with a
+    /// percent-decoded path parameter with [`RawPathParams`]. A `multipart/form-data` body
+    /// arrives parsed as a [`Multipart`], and the route's declared parameters decoded as
+    /// [`DecodedParams`] (read them with [`Self::param_i64`] and friends).
+    pub extensions: Extensions,
+}
+
+/// Wait for the handler's reply until `deadline`, yielding the coroutine between polls.
+///
+/// `Ok(None)` when the deadline passed first; [`Deadline::is_expired`] then stays `true` for
+/// the handler too.
+fn recv_until(
+    reply_rx: &mpsc::Receiver<HandlerResponse>,
+    deadline: &Deadline,
+) -> Result<Option<HandlerResponse>, String> {
+    loop {
+        match reply_rx.try_recv() {
+            Ok(response) => return Ok(Some(response)),
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                return Err("reply channel disconnected".to_string())
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+        }
+        if deadline.is_expired() {
+            return Ok(None);
+        }
+        coroutine::sleep(deadline.remaining().min(Duration::from_millis(1)));
+    }
+}
+
+/// Spawn the coroutine of a [`Dispatcher::register_handler`] handler.
+///
+/// Takes `pending` first, then requests from `rx`. When the handler's panic guard requests a
+/// respawn, the next request and `rx` are handed to a fresh coroutine running a fresh clone of
+/// `handler_fn`, and this one exits. If that spawn fails the handler is gone (requests then
+/// fail to send), which is logged.
+///
+/// # Safety
+///
+/// See [`Dispatcher::register_handler`].
+unsafe fn spawn_handler_coroutine<F>(
+    name: String,
+    stack_size: usize,
+    rx: mpsc::Receiver<HandlerRequest>,
+    handler_fn: F,
+    guard: Arc<PanicGuard>,
+    pending: Option<HandlerRequest>,
+) -> std::io::Result<()>
+where
+    F: Fn(HandlerRequest) + Send + 'static + Clone,
+{
+    let spawned = coroutine::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            // H1: Handler coroutine start
+            debug!(
+                handler_name = %name,
+                stack_size = stack_size,
+                "Handler coroutine start"
+            );
+            let watch = StackWatch::start(&name, stack_size);
+
+            let mut pending = pending;
+            loop {
+                let mut req = match pending.take() {
+                    Some(req) => req,
+                    None => match rx.recv() {
+                        Ok(req) => req,
+                        Err(_) => break,
+                    },
+                };
+
+                if guard.take_respawn_request() {
+                    // SAFETY: same runtime requirements as the original registration.
+                    let respawned = unsafe {
+                        spawn_handler_coroutine(
+                            name.clone(),
+                            stack_size,
+                            rx,
+                            handler_fn.clone(),
+                            guard.clone(),
+                            Some(req),
+                        )
+                    };
+                    match respawned {
+                        Ok(()) => {
+                            guard.record_respawn();
+                            warn!(handler_name = %name, "Handler respawned after repeated panics");
+                        }
+                        Err(e) => error!(
+                            handler_name = %name,
+                            error = %e,
+                            "Failed to respawn handler coroutine - CRITICAL"
+                        ),
+                    }
+                    return;
+                }
+
+                let _dispatch_phase = alloc_profile::phase(Phase::Dispatch);
+                if let Some(watch) = &watch {
+                    watch.stamp(&mut req.extensions);
+                }
+
+                // Extract what we need for error handling
+                let reply_tx = req.reply_tx.clone();
+                let handler_name = req.handler_name.clone();
+                let request_id = req.request_id;
+
+                // H2: Handler execution start — per-request, demoted to debug (PRD 2.2).
+                debug!(
+                    request_id = %request_id,
+                    handler_name = %handler_name,
+                    path_params = ?req.path_params,
+                    query_params = ?req.query_params,
+                    "Handler execution start"
+                );
+
+                let execution_start = Instant::now();
+
+                if let Err(panic) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    handler_fn(req);
+                })) {
+                    if let Some(response) = exhausted_response(&*panic, &request_id) {
+                        let _ = reply_tx.send(response);
+                    } else {
+                        // H3: Handler panic caught - CRITICAL ERROR
+                        let panic_message = format!("{panic:?}");
+                        let backtrace = std::backtrace::Backtrace::capture();
+
+                        error!(
+                            request_id = %request_id,
+                            handler_name = %handler_name,
+                            panic_message = %panic_message,
+                            backtrace = %backtrace,
+                            "Handler panicked - CRITICAL"
+                        );
+
+                        // Send an error response if the handler panicked
+                        let error_response = HandlerResponse::handler_panic(&panic_message);
+                        let _ = reply_tx.send(error_response);
+                    }
+                } else {
+                    // H4: Handler execution complete — per-request, demoted to debug (PRD 2.2).
+                    let execution_time_ms = execution_start.elapsed().as_millis() as u64;
+                    debug!(
+                        request_id = %request_id,
+                        handler_name = %handler_name,
+                        execution_time_ms = execution_time_ms,
+                        "Handler execution complete"
+                    );
+                }
+                if let Some(watch) = &watch {
+                    watch.record();
+                }
+            }
+        });
+    spawned.map(|_| ())
 }
 
 /// Guard that decreases queue depth counter when request processing completes and it drops
@@ -136,10 +350,33 @@ impl HandlerRequest {
             .map(|(_, v)| v.as_str())
     }
 
+    /// Get a path parameter by name, as it appeared in the request path
+    ///
+    /// Same as [`Self::get_path_param`] unless the value was percent-decoded (e.g., `a%2Fb`
+    /// where `get_path_param` gives `a/b`). Use it when the value goes back into a URL.
+    #[inline]
+    #[must_use]
+    pub fn get_raw_path_param(&self, name: &str) -> Option<&str> {
+        self.raw_path_params()
+            .iter()
+            .rfind(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Path parameters as they appeared in the request path, in `path_params` order
+    #[inline]
+    #[must_use]
+    pub fn raw_path_params(&self) -> &ParamVec {
+        self.extensions
+            .get::<RawPathParams>()
+            .map_or(&self.path_params, |raw| &raw.0)
+    }
+
     /// Get a query parameter by name
     ///
-    /// Uses "last write wins" semantics: if duplicate query parameter names exist
-    /// (e.g., `?limit=10&limit=20`), returns the last occurrence.
+    /// Declared parameters are canonicalized before dispatch (`query.duplicates` in
+    /// config.yaml), so a repeated key leaves one occurrence unless the parameter collects
+    /// an array. For undeclared keys sent more than once this returns the last occurrence.
     #[inline]
     #[must_use]
     pub fn get_query_param(&self, name: &str) -> Option<&str> {
@@ -149,14 +386,36 @@ impl HandlerRequest {
             .map(|(_, v)| v.as_str())
     }
 
+    /// Every occurrence of a query parameter, in request order
+    ///
+    /// Decode them together with
+    /// [`decode_query_values`](crate::server::request::decode_query_values).
+    pub fn get_query_params<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.query_params
+            .iter()
+            .filter(move |(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_str())
+    }
+
     /// Get a header by name (case-insensitive per RFC 7230)
     #[inline]
     #[must_use]
     pub fn get_header(&self, name: &str) -> Option<&str> {
-        self.headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case(name))
-            .map(|(_, v)| v.as_str())
+        self.headers.get(name)
+    }
+
+    /// Originating client address, resolved through trusted proxies
+    ///
+    /// Walks `Forwarded` (or, if absent, `X-Forwarded-For`) from right to left and returns the
+    /// first address not in `trusted_proxies`, falling back to [`Self::peer_addr`]. The headers
+    /// are ignored unless the direct peer is a trusted proxy, so clients cannot spoof their
+    /// address by sending the headers themselves. When the peer is unknown (see
+    /// [`Self::peer_addr`]) the headers cannot be trusted either and this returns `None`.
+    /// Trusted proxies are usually taken from
+    /// [`crate::runtime_config::RuntimeConfig::trusted_proxies`].
+    #[must_use]
+    pub fn client_ip(&self, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+        super::client_ip::resolve(self.peer_addr, &self.headers, trusted_proxies)
     }
 
     /// Get a cookie by name
@@ -169,6 +428,31 @@ impl HandlerRequest {
             .map(|(_, v)| v.as_str())
     }
 
+    /// Values middleware attached to this request
+    ///
+    /// ```rust,no_run
+    /// use brrtrouter::dispatcher::HandlerRequest;
+    ///
+    /// #[derive(Clone)]
+    /// struct TenantId(String);
+    ///
+    /// fn handler(req: HandlerRequest) {
+    ///     let tenant = req.extensions().get::<TenantId>().map(|t| t.0.as_str());
+    /// }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Mutable access to [`Self::extensions`], e.g. from
+    /// [`crate::middleware::Middleware::prepare`]
+    #[inline]
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
     /// Convert path_params to HashMap for compatibility
     /// Note: This allocates - use get_path_param() in hot paths
     #[must_use]
@@ -200,6 +484,9 @@ impl HandlerRequest {
     }
 }
 
+/// Problem `type` of the 500 response sent when a handler panics.
+pub const HANDLER_PANIC_PROBLEM_TYPE: &str = "urn:brrtrouter:problem:handler-panic";
+
 /// Response data sent back from a handler coroutine
 ///
 /// Contains the HTTP status code, headers, and JSON body to be sent to the client.
@@ -245,17 +532,74 @@ impl HandlerResponse {
         }
     }
 
+    /// **201 Created** pointing at the new resource with a `Location` header
+    ///
+    /// `location` is sent as given: build it from the resource's `GET` route with
+    /// [`CreatedLocation::expand`](crate::spec::CreatedLocation::expand) so its id is
+    /// URL-encoded. Behind a base path, a relative location gets the prefix prepended.
+    #[must_use]
+    pub fn created(location: impl Into<String>, body: Value) -> Self {
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("location"), location.into()));
+        Self::new(201, headers, body)
+    }
+
+    /// **200** file download: `Content-Disposition: attachment` naming `filename`, and
+    /// `X-Content-Type-Options: nosniff` so browsers keep `content_type` instead of sniffing
+    /// one from the content
+    ///
+    /// A name with non-ASCII or special characters is sent RFC 6266 style: an ASCII
+    /// `filename` fallback (others replaced by `_`) plus the exact name as an RFC 5987
+    /// `filename*=UTF-8''...`. The body is sent as text, like any string body.
+    #[must_use]
+    pub fn attachment(filename: &str, content_type: &str, body: impl Into<String>) -> Self {
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("content-type"), content_type.to_string()));
+        headers.push((
+            Arc::from("content-disposition"),
+            attachment_disposition(filename),
+        ));
+        headers.push((Arc::from("x-content-type-options"), "nosniff".to_string()));
+        Self::new(200, headers, Value::String(body.into()))
+    }
+
     /// Create an error response
     ///
-    /// Unlike [`Self::json`], this pins `content-type: application/json`
+    /// Shorthand for a [`ProblemDetails`] with `message` as its `detail`; see [`Self::problem`].
+    #[must_use]
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::problem(&ProblemDetails::new(status).detail(message))
+    }
+
+    /// 500 response for a handler that panicked, recognised by the dispatcher's panic guards
+    /// through its problem type ([`HANDLER_PANIC_PROBLEM_TYPE`]).
+    #[must_use]
+    pub fn handler_panic(message: &str) -> Self {
+        Self::problem(
+            &ProblemDetails::new(500)
+                .problem_type(HANDLER_PANIC_PROBLEM_TYPE)
+                .detail(format!("Handler panicked: {message}")),
+        )
+    }
+
+    /// Whether this is a [`Self::handler_panic`] response.
+    #[must_use]
+    pub fn is_handler_panic(&self) -> bool {
+        self.status == 500
+            && self.body.get("type").and_then(Value::as_str) == Some(HANDLER_PANIC_PROBLEM_TYPE)
+    }
+
+    /// Create an RFC 9457 problem details response
+    ///
+    /// Unlike [`Self::json`], this pins `content-type: application/problem+json`
     /// explicitly: error payloads are terminal (middleware short-circuits,
     /// policy rejections) and are never subject to OpenAPI response
     /// content-type negotiation.
     #[must_use]
-    pub fn error(status: u16, message: &str) -> Self {
-        let mut resp = Self::json(status, serde_json::json!({ "error": message }));
+    pub fn problem(problem: &ProblemDetails) -> Self {
+        let mut resp = Self::json(problem.status, problem.to_value());
         resp.headers
-            .push((Arc::from("content-type"), "application/json".to_string()));
+            .push((Arc::from("content-type"), PROBLEM_JSON.to_string()));
         resp
     }
 
@@ -263,10 +607,7 @@ impl HandlerResponse {
     #[inline]
     #[must_use]
     pub fn get_header(&self, name: &str) -> Option<&str> {
-        self.headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case(name))
-            .map(|(_, v)| v.as_str())
+        self.headers.get(name)
     }
 
     /// Add or update a header
@@ -278,6 +619,31 @@ impl HandlerResponse {
     }
 }
 
+/// `Content-Disposition` value of an attachment named `filename`
+///
+/// `attachment; filename="<ascii>"`, plus `filename*=UTF-8''<percent-encoded>` unless the
+/// name is plain ASCII without quotes, backslashes or control characters.
+fn attachment_disposition(filename: &str) -> String {
+    let plain = |c: char| c.is_ascii() && !c.is_ascii_control() && !matches!(c, '"' | '\\');
+    let fallback: String = filename
+        .chars()
+        .map(|c| if plain(c) { c } else { '_' })
+        .collect();
+    if filename.chars().all(plain) {
+        return format!("attachment; filename=\"{fallback}\"");
+    }
+    // RFC 5987 `attr-char`s go through as is; every other byte is percent-encoded.
+    let mut encoded = String::with_capacity(filename.len() * 3);
+    for byte in filename.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
 /// Type alias for a channel sender that dispatches requests to a handler
 pub type HandlerSender = mpsc::Sender<HandlerRequest>;
 
@@ -342,8 +708,13 @@ where
                 stack_size = stack_size,
                 "Untyped handler coroutine start"
             );
+            let watch = StackWatch::start(&handler_name_for_logging, stack_size);
 
-            for req in rx.iter() {
+            for mut req in rx.iter() {
+                let _dispatch_phase = alloc_profile::phase(Phase::Dispatch);
+                if let Some(watch) = &watch {
+                    watch.stamp(&mut req.extensions);
+                }
                 let reply_tx = req.reply_tx.clone();
                 let h_name = req.handler_name.clone();
                 let request_id = req.request_id;
@@ -361,17 +732,17 @@ where
                     let response = handler_fn(req);
                     let _ = reply_tx.send(response);
                 })) {
-                    let panic_message = format!("{panic:?}");
-                    error!(
-                        request_id = %request_id,
-                        handler_name = %h_name,
-                        panic_message = %panic_message,
-                        "Untyped handler panicked - CRITICAL"
-                    );
-                    let error_response = HandlerResponse::error(
-                        500,
-                        &format!("Handler panicked: {}", panic_message),
-                    );
+                    let error_response =
+                        exhausted_response(&*panic, &request_id).unwrap_or_else(|| {
+                            let panic_message = format!("{panic:?}");
+                            error!(
+                                request_id = %request_id,
+                                handler_name = %h_name,
+                                panic_message = %panic_message,
+                                "Untyped handler panicked - CRITICAL"
+                            );
+                            HandlerResponse::handler_panic(&panic_message)
+                        });
                     let _ = reply_tx.send(error_response);
                 } else {
                     // Per-request — demoted to debug (PRD 2.2).
@@ -383,6 +754,9 @@ where
                         "Untyped handler execution complete"
                     );
                 }
+                if let Some(watch) = &watch {
+                    watch.record();
+                }
             }
         });
 
@@ -415,10 +789,16 @@ pub struct Dispatcher {
     pub worker_pools: HashMap<String, Arc<WorkerPool>>,
     /// Ordered list of middleware to apply to requests/responses
     pub middlewares: Vec<Arc<dyn Middleware>>,
+    /// Ordered response body transforms, applied before middleware `after` hooks
+    pub response_transforms: Vec<Arc<dyn ResponseTransform>>,
     /// Map of handler names to active queue limit tracker
     pub queue_depths: HashMap<String, std::sync::Arc<std::sync::atomic::AtomicUsize>>,
     /// Global backpressure bound for standard queues
     pub queue_bound: usize,
+    /// Per-handler panic counters and policies (see [`Self::set_panic_policy`])
+    pub(crate) panic_guards: HashMap<String, Arc<PanicGuard>>,
+    /// Map of handler names to WebSocket handlers (routes marked `x-websocket: true`)
+    pub websocket_handlers: HashMap<String, WebSocketHandler>,
 }
 
 impl Default for Dispatcher {
@@ -442,8 +822,11 @@ impl Dispatcher {
             handlers: HashMap::new(),
             worker_pools: HashMap::new(),
             middlewares: Vec::new(),
+            response_transforms: Vec::new(),
             queue_depths: HashMap::new(),
             queue_bound,
+            panic_guards: HashMap::new(),
+            websocket_handlers: HashMap::new(),
         }
     }
 
@@ -493,6 +876,33 @@ impl Dispatcher {
         self.middlewares.push(mw);
     }
 
+    /// Add a response body transform
+    ///
+    /// Transforms run in the order they're added, after the handler (or a middleware
+    /// short-circuit) and before any middleware `after` hook, so compression and response
+    /// schema validation both see the transformed body.
+    ///
+    /// # Arguments
+    ///
+    /// * `transform` - Transform implementation to add
+    pub fn add_response_transform(&mut self, transform: Arc<dyn ResponseTransform>) {
+        self.response_transforms.push(transform);
+    }
+
+    /// Register the handler for a WebSocket route (`x-websocket: true`)
+    ///
+    /// Runs once per accepted connection, on that connection's coroutine, after the upgrade
+    /// request passed security and parameter validation. The connection is closed when it
+    /// returns. Only served by
+    /// [`HttpServer::start_with_websockets`](crate::server::HttpServer::start_with_websockets).
+    pub fn register_websocket_handler<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(WebSocketRequest, WebSocketChannel) + Send + Sync + 'static,
+    {
+        self.websocket_handlers
+            .insert(name.to_string(), Arc::new(handler));
+    }
+
     /// Registers a handler function that will process incoming requests with the given name.
     ///
     /// Spawns a coroutine that processes requests from a channel. The handler is automatically
@@ -520,12 +930,29 @@ impl Dispatcher {
     /// Handler panics are caught and converted to 500 error responses automatically.
     ///
     pub unsafe fn register_handler<F>(&mut self, name: &str, handler_fn: F)
+    where
+        F: Fn(HandlerRequest) + Send + 'static + Clone,
+    {
+        // A spawn failure is logged and leaves the handler unregistered.
+        // SAFETY: same contract as this function.
+        let _ = unsafe { self.spawn_registered_handler(name, handler_fn) };
+    }
+
+    /// [`Self::register_handler`], returning the error when the coroutine cannot be spawned.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Self::register_handler`].
+    pub(super) unsafe fn spawn_registered_handler<F>(
+        &mut self,
+        name: &str,
+        handler_fn: F,
+    ) -> std::io::Result<()>
     where
         F: Fn(HandlerRequest) + Send + 'static + Clone,
     {
         let (tx, rx) = mpsc::channel::<HandlerRequest>();
         let name = name.to_string();
-        let handler_name_for_logging = name.clone();
 
         // Use a larger default stack size to prevent stack overflows
         // 64KB is more reasonable for complex handlers
@@ -540,6 +967,14 @@ impl Dispatcher {
             })
             .unwrap_or(0x8000); // 32KB default
 
+        // Keeps a policy set before registration.
+        let guard = self
+            .panic_guards
+            .entry(name.clone())
+            .or_insert_with(|| Arc::new(PanicGuard::new()))
+            .clone();
+        guard.set_respawnable();
+
         // SAFETY: may::coroutine::Builder::spawn() is marked unsafe by the may runtime.
         // The unsafety comes from the coroutine runtime's requirements, not from this function's logic.
         // We ensure safety by:
@@ -547,68 +982,14 @@ impl Dispatcher {
         // - The handler function is Send + 'static, ensuring no dangling references
         // - Error handling is done via the reply channel, not panics
         let spawn_result = unsafe {
-            coroutine::Builder::new()
-                .stack_size(stack_size)
-                .spawn(move || {
-                    // H1: Handler coroutine start
-                    debug!(
-                        handler_name = %handler_name_for_logging,
-                        stack_size = stack_size,
-                        "Handler coroutine start"
-                    );
-
-                    for req in rx.iter() {
-                        // Extract what we need for error handling
-                        let reply_tx = req.reply_tx.clone();
-                        let handler_name = req.handler_name.clone();
-                        let request_id = req.request_id;
-
-                        // H2: Handler execution start — per-request, demoted to debug (PRD 2.2).
-                        debug!(
-                            request_id = %request_id,
-                            handler_name = %handler_name,
-                            path_params = ?req.path_params,
-                            query_params = ?req.query_params,
-                            "Handler execution start"
-                        );
-
-                        let execution_start = Instant::now();
-
-                        if let Err(panic) =
-                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                                handler_fn(req);
-                            }))
-                        {
-                            // H3: Handler panic caught - CRITICAL ERROR
-                            let panic_message = format!("{panic:?}");
-                            let backtrace = std::backtrace::Backtrace::capture();
-
-                            error!(
-                                request_id = %request_id,
-                                handler_name = %handler_name,
-                                panic_message = %panic_message,
-                                backtrace = %backtrace,
-                                "Handler panicked - CRITICAL"
-                            );
-
-                            // Send an error response if the handler panicked
-                            let error_response = HandlerResponse::error(
-                                500,
-                                &format!("Handler panicked: {}", panic_message),
-                            );
-                            let _ = reply_tx.send(error_response);
-                        } else {
-                            // H4: Handler execution complete — per-request, demoted to debug (PRD 2.2).
-                            let execution_time_ms = execution_start.elapsed().as_millis() as u64;
-                            debug!(
-                                request_id = %request_id,
-                                handler_name = %handler_name,
-                                execution_time_ms = execution_time_ms,
-                                "Handler execution complete"
-                            );
-                        }
-                    }
-                })
+            spawn_handler_coroutine(
+                name.clone(),
+                stack_size,
+                rx,
+                handler_fn,
+                guard.clone(),
+                None,
+            )
         };
 
         // Handle coroutine spawn failures gracefully
@@ -621,10 +1002,11 @@ impl Dispatcher {
             );
             // Return early without registering the handler
             // This prevents crashes when resources are exhausted
-            return;
+            return Err(e);
         }
 
         self.handlers.insert(name, tx);
+        Ok(())
     }
 
     /// Register a handler with a worker pool for parallel request processing
@@ -743,6 +1125,67 @@ impl Dispatcher {
         cookies: HeaderVec,
         request_id: String,
         jwt_claims: Option<Value>,
+    ) -> Option<HandlerResponse> {
+        self.dispatch_request(
+            route_match,
+            body,
+            None,
+            headers,
+            cookies,
+            request_id,
+            jwt_claims,
+            None,
+            None,
+        )
+    }
+
+    /// Dispatch an authenticated request, exposing `auth_context` to the handler
+    ///
+    /// [`HandlerRequest::jwt_claims`] is set from the context's claims (when the provider
+    /// returned any), so existing handlers reading `jwt_claims` keep working. `raw_body` is
+    /// the body as received (see [`HandlerRequest::raw_body`]) and `peer_addr` the
+    /// connection's peer, when known (see [`HandlerRequest::peer_addr`]).
+    #[allow(clippy::too_many_arguments)]
+    pub fn dispatch_with_auth(
+        &self,
+        route_match: RouteMatch,
+        body: Option<Value>,
+        raw_body: Option<Bytes>,
+        headers: HeaderVec,
+        cookies: HeaderVec,
+        request_id: String,
+        auth_context: Option<AuthContext>,
+        peer_addr: Option<IpAddr>,
+    ) -> Option<HandlerResponse> {
+        let jwt_claims = auth_context
+            .as_ref()
+            .filter(|ctx| !ctx.claims.is_null())
+            .map(|ctx| ctx.claims.clone());
+        self.dispatch_request(
+            route_match,
+            body,
+            raw_body,
+            headers,
+            cookies,
+            request_id,
+            jwt_claims,
+            auth_context,
+            peer_addr,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dispatch_request(
+        &self,
+        route_match: RouteMatch,
+        body: Option<Value>,
+        raw_body: Option<Bytes>,
+        headers: HeaderVec,
+        cookies: HeaderVec,
+        request_id: String,
+        jwt_claims: Option<Value>,
+        auth_context: Option<AuthContext>,
+        peer_addr: Option<IpAddr>,
     ) -> Option<HandlerResponse> {
         let (reply_tx, reply_rx) = mpsc::channel();
 
@@ -767,6 +1210,18 @@ impl Dispatcher {
             }
         };
 
+        let deadline = Deadline::for_request(route_match.route.x_brrtrouter_timeout_ms, &headers);
+        let is_sse = route_match.route.sse;
+        let raw_path_params = route_match.raw_path_params;
+        let decoded_params = (!route_match.route.parameters.is_empty()).then(|| {
+            DecodedParams::decode(
+                &route_match.route.parameters,
+                &route_match.path_params,
+                &route_match.query_params,
+                &headers,
+                &cookies,
+            )
+        });
         let mut request = HandlerRequest {
             request_id: request_id.parse().unwrap_or_else(|_| RequestId::new()),
             method: route_match.route.method.clone(),
@@ -778,10 +1233,34 @@ impl Dispatcher {
             headers,
             cookies,
             body,
+            raw_body,
             jwt_claims,
             reply_tx,
             queue_guard: None,
+            peer_addr,
+            downstream_headers: HeaderVec::new(),
+            auth_context,
+            deadline,
+            extensions: Extensions::new(),
         };
+        if is_sse {
+            let correlation = SseCorrelation::from_request(&request);
+            request.extensions.insert(correlation);
+        }
+        if !raw_path_params.is_empty() {
+            request.extensions.insert(RawPathParams(raw_path_params));
+        }
+        if let Some(decoded_params) = decoded_params {
+            request.extensions.insert(decoded_params);
+        }
+        if let Some(multipart) = request.raw_body.as_ref().and_then(|raw| {
+            let content_type = request.headers.get("content-type")?;
+            is_multipart_form_data(content_type)
+                .then(|| Multipart::parse(raw, content_type).ok())
+                .flatten()
+        }) {
+            request.extensions.insert(multipart);
+        }
 
         // D4: Middleware before execution
         let middleware_count = self.middlewares.len();
@@ -791,6 +1270,10 @@ impl Dispatcher {
             "Middleware before execution"
         );
 
+        for mw in &self.middlewares {
+            mw.prepare(&mut request);
+        }
+
         let mut early_resp: Option<HandlerResponse> = None;
         for (idx, mw) in self.middlewares.iter().enumerate() {
             if early_resp.is_none() {
@@ -820,6 +1303,26 @@ impl Dispatcher {
                 "Request dispatched to handler"
             );
 
+            // Repeatedly panicking handler isolated by its panic policy
+            let panic_guard = self.panic_guards.get(&request.handler_name);
+            if let Some(open_for) = panic_guard.and_then(|guard| guard.open_for()) {
+                warn!(
+                    request_id = %request_id,
+                    handler_name = %request.handler_name,
+                    "Handler breaker open after repeated panics - returning 503"
+                );
+                let mut resp = HandlerResponse::error(
+                    503,
+                    &format!(
+                        "Handler '{}' is temporarily disabled after repeated panics",
+                        request.handler_name
+                    ),
+                );
+                let retry_after = open_for.as_secs() + u64::from(open_for.subsec_nanos() > 0);
+                resp.set_header("retry-after", retry_after.to_string());
+                return Some(resp);
+            }
+
             let start = Instant::now();
 
             // Check if this handler has a worker pool with backpressure
@@ -884,10 +1387,29 @@ impl Dispatcher {
             );
 
             // Receive response with timeout detection
-            // Note: may::sync::mpsc doesn't have recv_timeout, so we use recv()
-            // and rely on handler-side timeouts and panic recovery
-            let r = match reply_rx.recv() {
-                Ok(response) => {
+            // Note: may::sync::mpsc doesn't have recv_timeout, so without a deadline we use
+            // recv() and rely on panic recovery; with one we poll until it passes.
+            let received = match &request.deadline {
+                Some(deadline) => recv_until(&reply_rx, deadline),
+                None => reply_rx.recv().map(Some).map_err(|e| e.to_string()),
+            };
+            let r = match received {
+                Ok(None) => {
+                    warn!(
+                        request_id = %request_id,
+                        handler_name = %request.handler_name,
+                        elapsed_ms = start.elapsed().as_millis() as u64,
+                        "Request deadline passed - answering 504"
+                    );
+                    HandlerResponse::error(
+                        504,
+                        &format!(
+                            "Handler '{}' did not respond before the request deadline",
+                            request.handler_name
+                        ),
+                    )
+                }
+                Ok(Some(response)) => {
                     // Per-request — demoted to debug (PRD 2.2).
                     let elapsed = start.elapsed();
                     debug!(
@@ -921,9 +1443,18 @@ impl Dispatcher {
                     ));
                 }
             };
+            if r.is_handler_panic() {
+                if let Some(guard) = panic_guard {
+                    guard.record_panic();
+                }
+            }
             (r, start.elapsed())
         };
 
+        for transform in &self.response_transforms {
+            transform.transform(&request, resp.status, &mut resp.body);
+        }
+
         // D5: Middleware after execution
         debug!(
             request_id = %request_id,
@@ -940,6 +1471,29 @@ impl Dispatcher {
         Some(resp)
     }
 
+    /// Isolate a handler that panics repeatedly (see [`PanicPolicy`]).
+    ///
+    /// May be set before or after the handler is registered. [`PanicAction::Respawn`] only
+    /// takes effect for handlers registered with [`Self::register_handler`]; for others panics
+    /// are just counted.
+    ///
+    /// [`PanicAction::Respawn`]: super::PanicAction::Respawn
+    pub fn set_panic_policy(&mut self, handler_name: &str, policy: PanicPolicy) {
+        self.panic_guards
+            .entry(handler_name.to_string())
+            .or_insert_with(|| Arc::new(PanicGuard::new()))
+            .set_policy(policy);
+    }
+
+    /// Panic counters and breaker state of every handler with a panic guard.
+    #[must_use]
+    pub fn panic_guard_stats(&self) -> HashMap<String, PanicGuardStats> {
+        self.panic_guards
+            .iter()
+            .map(|(name, guard)| (name.clone(), guard.stats()))
+            .collect()
+    }
+
     /// Get metrics for all worker pools
     ///
     /// Returns a map of handler names to their worker pool metrics.
@@ -978,8 +1532,13 @@ mod handler_response_tests {
             "403 errors must not serialize to bare JSON null"
         );
         assert_eq!(
-            r.body.get("error").and_then(Value::as_str),
+            r.body.get("detail").and_then(Value::as_str),
             Some("Origin not allowed by CORS policy")
         );
+        assert_eq!(r.body["title"], "Forbidden");
+        assert_eq!(
+            r.get_header("content-type"),
+            Some("application/problem+json")
+        );
     }
 }
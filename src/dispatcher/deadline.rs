@@ -0,0 +1,131 @@
+//! Request deadlines observable from handler coroutines.
+//!
+//! A route with `x-brrtrouter-timeout-ms`, or a request carrying a `Request-Timeout`
+//! header (seconds, fractions allowed), gets a [`Deadline`] on
+//! [`HandlerRequest::deadline`](super::HandlerRequest::deadline); with both, the earlier one
+//! wins. When it passes, the dispatcher stops waiting and answers `504 Gateway Timeout`.
+//!
+//! Cancellation is **cooperative**: the handler coroutine is not interrupted. A handler doing
+//! slow or downstream work should poll [`Deadline::is_expired`] (or hand
+//! [`Deadline::remaining`] to its client as a timeout) and return early once the client has
+//! already been answered; its late reply is discarded.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::headers::HeaderLookup;
+use super::HeaderVec;
+
+/// Header a client uses to bound how long it will wait, in seconds.
+pub const REQUEST_TIMEOUT_HEADER: &str = "request-timeout";
+
+/// Point in time after which a request is abandoned, plus a cancellation flag.
+///
+/// Clones share the flag, so a clone handed to a downstream client sees the cancellation.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    at: Instant,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Deadline {
+    /// Deadline `timeout` from now.
+    #[must_use]
+    pub fn after(timeout: Duration) -> Self {
+        Self {
+            at: Instant::now() + timeout,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Deadline of a request: the earlier of the route timeout and the `Request-Timeout`
+    /// header; `None` when neither is set (or the header is not a non-negative number).
+    pub(crate) fn for_request(route_timeout_ms: Option<u64>, headers: &HeaderVec) -> Option<Self> {
+        let header_timeout = headers
+            .get(REQUEST_TIMEOUT_HEADER)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+        let route_timeout = route_timeout_ms.map(Duration::from_millis);
+        let timeout = match (route_timeout, header_timeout) {
+            (Some(route), Some(header)) => route.min(header),
+            (route, header) => route.or(header)?,
+        };
+        Some(Self::after(timeout))
+    }
+
+    /// The instant the request is abandoned at.
+    #[must_use]
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// Time left before the deadline; zero once it passed or the request was cancelled.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        if self.cancelled.load(Ordering::Acquire) {
+            return Duration::ZERO;
+        }
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline passed or the request was cancelled; once `true`, stays `true`.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        if self.cancelled.load(Ordering::Acquire) {
+            return true;
+        }
+        if Instant::now() >= self.at {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+
+    /// Abandon the request now (the dispatcher does this when it answers 504).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(value: &str) -> HeaderVec {
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("Request-Timeout"), value.to_string()));
+        headers
+    }
+
+    #[test]
+    fn for_request_takes_the_earlier_timeout() {
+        assert!(Deadline::for_request(None, &HeaderVec::new()).is_none());
+        assert!(Deadline::for_request(None, &headers("soon")).is_none());
+        assert!(Deadline::for_request(None, &headers("-1")).is_none());
+
+        let route_only = Deadline::for_request(Some(60_000), &headers("nope")).unwrap();
+        assert!(route_only.remaining() > Duration::from_secs(30));
+
+        let header_wins = Deadline::for_request(Some(60_000), &headers(" 0.5 ")).unwrap();
+        assert!(header_wins.remaining() <= Duration::from_millis(500));
+
+        let route_wins = Deadline::for_request(Some(100), &headers("30")).unwrap();
+        assert!(route_wins.remaining() <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn expiry_and_cancellation_are_shared_by_clones() {
+        let deadline = Deadline::after(Duration::from_secs(60));
+        let downstream = deadline.clone();
+        assert!(!downstream.is_expired());
+
+        deadline.cancel();
+        assert!(downstream.is_expired());
+        assert_eq!(downstream.remaining(), Duration::ZERO);
+
+        let passed = Deadline::after(Duration::ZERO);
+        assert!(passed.is_expired());
+        assert!(passed.is_expired());
+    }
+}
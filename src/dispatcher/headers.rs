@@ -0,0 +1,107 @@
+//! Case-insensitive header access on [`HeaderVec`].
+//!
+//! Header names are compared with ASCII case folding (RFC 9110 §5.1), so `Content-Type`,
+//! `content-type` and `CONTENT-TYPE` all match regardless of how the name was stored. Request
+//! parsing, middleware and security providers look headers up through [`HeaderLookup`] rather
+//! than comparing names themselves.
+//!
+//! Cookie jars are `HeaderVec`s too, but cookie names are case-sensitive; look those up with
+//! an exact comparison instead.
+
+use std::iter::FusedIterator;
+use std::sync::Arc;
+
+use super::HeaderVec;
+
+/// Case-insensitive lookup of header values by name.
+pub trait HeaderLookup {
+    /// Value of the first header named `name`, compared case-insensitively.
+    fn get(&self, name: &str) -> Option<&str>;
+
+    /// Values of every header named `name`, in order, for multi-valued headers such as
+    /// `Cookie` or `Forwarded` that may be sent on several lines.
+    fn get_all<'a>(&'a self, name: &'a str) -> HeaderValues<'a>;
+}
+
+impl HeaderLookup for HeaderVec {
+    #[inline]
+    fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[inline]
+    fn get_all<'a>(&'a self, name: &'a str) -> HeaderValues<'a> {
+        HeaderValues {
+            headers: self.iter(),
+            name,
+        }
+    }
+}
+
+/// Iterator over the values of one header name; see [`HeaderLookup::get_all`].
+#[derive(Debug, Clone)]
+pub struct HeaderValues<'a> {
+    headers: std::slice::Iter<'a, (Arc<str>, String)>,
+    name: &'a str,
+}
+
+impl<'a> Iterator for HeaderValues<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.name;
+        self.headers
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl DoubleEndedIterator for HeaderValues<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let name = self.name;
+        self.headers
+            .rfind(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl FusedIterator for HeaderValues<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderVec {
+        pairs
+            .iter()
+            .map(|(k, v)| (Arc::from(*k), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn get_ignores_ascii_case() {
+        let h = headers(&[("Content-Type", "application/json"), ("x-api-key", "k")]);
+        assert_eq!(h.get("content-type"), Some("application/json"));
+        assert_eq!(h.get("CONTENT-TYPE"), Some("application/json"));
+        assert_eq!(h.get("X-Api-Key"), Some("k"));
+        assert_eq!(h.get("accept"), None);
+    }
+
+    #[test]
+    fn get_all_returns_every_line_in_order() {
+        let h = headers(&[
+            ("Cookie", "a=1"),
+            ("host", "example.com"),
+            ("cookie", "b=2"),
+            ("COOKIE", "c=3"),
+        ]);
+        let values: Vec<&str> = h.get_all("cookie").collect();
+        assert_eq!(values, ["a=1", "b=2", "c=3"]);
+        let reversed: Vec<&str> = h.get_all("Cookie").rev().collect();
+        assert_eq!(reversed, ["c=3", "b=2", "a=1"]);
+        assert_eq!(h.get("cookie"), Some("a=1"));
+        assert_eq!(h.get_all("forwarded").count(), 0);
+    }
+}
@@ -20,26 +20,30 @@
 //! - Requests are sent to handlers via MPSC channels
 //! - Handlers process requests and send responses back via one-shot channels
 //! - Stack size is configurable via `BRRTR_STACK_SIZE` environment variable
+//! - `BRRTR_STACK_GUARD=on` tracks each handler's peak stack use and lets deep code check
+//!   its remaining stack instead of overflowing (see [`StackBudget`])
 //!
 //! ## Handler Registration
 //!
 //! Handlers are registered with the dispatcher at startup:
 //!
 //! ```rust,ignore
-//! use brrtrouter::dispatcher::Dispatcher;
-//! use brrtrouter::server::{HandlerRequest, HandlerResponse};
+//! use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
 //!
+//! let (routes, _slug) = brrtrouter::load_spec("openapi.yaml")?;
 //! let mut dispatcher = Dispatcher::new();
 //!
-//! // Register a handler coroutine
-//! dispatcher.register("get_pet", |req: HandlerRequest| {
-//!     // Process request
-//!     HandlerResponse::ok_json(serde_json::json!({
-//!         "id": req.path_params.get("id")
-//!     }))
-//! });
+//! // Register a handler coroutine; fails for a name no route uses or one already taken
+//! dispatcher.register(&routes, "get_pet", |req: HandlerRequest| {
+//!     let id = req.get_path_param("id").map(str::to_string);
+//!     let _ = req.reply_tx.send(HandlerResponse::json(200, serde_json::json!({ "id": id })));
+//! })?;
 //! ```
 //!
+//! [`Dispatcher::register_from_spec`] registers a set at once and reports routes left
+//! without a handler (see [`registration`]). Generated registries use the unchecked
+//! `unsafe` [`Dispatcher::register_handler`].
+//!
 //! ## Request Flow
 //!
 //! 1. Router matches incoming request → route metadata
@@ -62,9 +66,26 @@
 //! - Stack size should be tuned based on handler complexity
 //! - Default stack size is 1MB per coroutine
 
+mod client_ip;
 mod core;
+mod deadline;
+mod headers;
+mod panic_guard;
+mod params;
+pub mod registration;
+pub mod stack_guard;
 
 pub use core::{
     generate_request_id, spawn_untyped_with_stack_size_and_name, Dispatcher, HandlerRequest,
-    HandlerResponse, HandlerSender, HeaderVec, MAX_INLINE_HEADERS,
+    HandlerResponse, HandlerSender, HeaderVec, HANDLER_PANIC_PROBLEM_TYPE, MAX_INLINE_HEADERS,
+};
+pub use deadline::{Deadline, REQUEST_TIMEOUT_HEADER};
+pub use headers::{HeaderLookup, HeaderValues};
+pub use http::Extensions;
+pub use panic_guard::{PanicAction, PanicGuardStats, PanicPolicy};
+pub use params::{DecodedParams, ParamTypeError};
+pub use registration::{HandlerFn, RegistrationError};
+pub(crate) use stack_guard::{exhausted_response, StackWatch};
+pub use stack_guard::{
+    stack_guard_enabled, stack_watermarks, StackBudget, StackExhausted, STACK_GUARD_ENV,
 };
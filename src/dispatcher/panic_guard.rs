@@ -0,0 +1,272 @@
+//! Per-handler panic isolation.
+//!
+//! Every handler panic is already caught and answered with a 500. A handler that keeps
+//! panicking (e.g. poisoned shared state) can additionally be isolated with a
+//! [`PanicPolicy`] set through [`Dispatcher::set_panic_policy`](super::Dispatcher::set_panic_policy):
+//! once it panics `threshold` times within `window`, the dispatcher either
+//!
+//! - trips a breaker ([`PanicAction::TripBreaker`]): requests for the handler get
+//!   `503 Service Unavailable` with `Retry-After` for `cooldown`, without reaching it; after
+//!   the cooldown requests flow again and the count starts over, or
+//! - respawns the handler ([`PanicAction::Respawn`]): the coroutine is replaced by a fresh
+//!   one running a fresh clone of the registered closure, before it takes the next request.
+//!   State shared behind an `Arc` is not reset. Only handlers registered with
+//!   [`Dispatcher::register_handler`](super::Dispatcher::register_handler) can be respawned;
+//!   for others the policy only counts panics.
+//!
+//! Breaker state and counters are exported by the metrics endpoint
+//! (`brrtrouter_handler_breaker_open`, `brrtrouter_handler_panics_total`, ...).
+
+use crate::lock_poison;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const STATE_LOCK: &str = "dispatcher.panic_guard";
+
+/// What to do with a handler that panicked too often.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicAction {
+    /// Answer 503 for the handler during `cooldown` without calling it.
+    TripBreaker {
+        /// How long the breaker stays open
+        cooldown: Duration,
+    },
+    /// Replace the handler coroutine with a fresh one.
+    Respawn,
+}
+
+/// Panic threshold of a handler and what happens when it is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanicPolicy {
+    /// Panics within `window` that trigger `action` (at least 1)
+    pub threshold: u32,
+    /// Sliding window the panics are counted in
+    pub window: Duration,
+    /// Action taken when the threshold is reached
+    pub action: PanicAction,
+}
+
+impl PanicPolicy {
+    /// Trip a breaker for `cooldown` after `threshold` panics within `window`.
+    #[must_use]
+    pub fn trip_breaker(threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            action: PanicAction::TripBreaker { cooldown },
+        }
+    }
+
+    /// Respawn the handler coroutine after `threshold` panics within `window`.
+    #[must_use]
+    pub fn respawn(threshold: u32, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            action: PanicAction::Respawn,
+        }
+    }
+}
+
+/// Panic counters and breaker state of one handler, as exported to metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PanicGuardStats {
+    /// Whether the breaker is currently open (requests answered with 503)
+    pub breaker_open: bool,
+    /// Panics observed since registration
+    pub panics: u64,
+    /// Times the breaker tripped
+    pub trips: u64,
+    /// Times the handler coroutine was respawned
+    pub respawns: u64,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    policy: Option<PanicPolicy>,
+    recent: VecDeque<Instant>,
+}
+
+/// Panic bookkeeping of one handler, shared by the dispatcher and (for respawnable
+/// handlers) the handler coroutine.
+#[derive(Debug)]
+pub(crate) struct PanicGuard {
+    state: Mutex<GuardState>,
+    created: Instant,
+    /// Breaker open until this many ms after `created`; 0 when closed.
+    open_until_ms: AtomicU64,
+    respawnable: AtomicBool,
+    respawn_requested: AtomicBool,
+    panics: AtomicU64,
+    trips: AtomicU64,
+    respawns: AtomicU64,
+}
+
+impl PanicGuard {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Mutex::new(GuardState::default()),
+            created: Instant::now(),
+            open_until_ms: AtomicU64::new(0),
+            respawnable: AtomicBool::new(false),
+            respawn_requested: AtomicBool::new(false),
+            panics: AtomicU64::new(0),
+            trips: AtomicU64::new(0),
+            respawns: AtomicU64::new(0),
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        u64::try_from(self.created.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    pub(crate) fn set_policy(&self, policy: PanicPolicy) {
+        let mut state = lock_poison::lock(&self.state, STATE_LOCK);
+        state.policy = Some(policy);
+        state.recent.clear();
+    }
+
+    /// Mark the handler as running in a coroutine that honours respawn requests.
+    pub(crate) fn set_respawnable(&self) {
+        self.respawnable.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_respawnable(&self) -> bool {
+        self.respawnable.load(Ordering::Relaxed)
+    }
+
+    /// Time left while the breaker is open; `None` when requests may reach the handler.
+    pub(crate) fn open_for(&self) -> Option<Duration> {
+        let until = self.open_until_ms.load(Ordering::Acquire);
+        if until == 0 {
+            return None;
+        }
+        let now = self.now_ms();
+        (now < until).then(|| Duration::from_millis(until - now))
+    }
+
+    /// Count a panic and apply the policy when its threshold is reached.
+    pub(crate) fn record_panic(&self) {
+        self.panics.fetch_add(1, Ordering::Relaxed);
+        let mut state = lock_poison::lock(&self.state, STATE_LOCK);
+        let Some(policy) = state.policy else {
+            return;
+        };
+        let now = Instant::now();
+        state.recent.push_back(now);
+        while state
+            .recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > policy.window)
+        {
+            state.recent.pop_front();
+        }
+        if state.recent.len() < policy.threshold.max(1) as usize {
+            return;
+        }
+        state.recent.clear();
+        match policy.action {
+            PanicAction::TripBreaker { cooldown } => {
+                let cooldown_ms = u64::try_from(cooldown.as_millis()).unwrap_or(u64::MAX);
+                self.open_until_ms.store(
+                    self.now_ms().saturating_add(cooldown_ms.max(1)),
+                    Ordering::Release,
+                );
+                self.trips.fetch_add(1, Ordering::Relaxed);
+            }
+            PanicAction::Respawn => {
+                if self.is_respawnable() {
+                    self.respawn_requested.store(true, Ordering::Release);
+                }
+            }
+        }
+    }
+
+    /// Consume a pending respawn request (called by the handler coroutine).
+    pub(crate) fn take_respawn_request(&self) -> bool {
+        self.respawn_requested.swap(false, Ordering::AcqRel)
+    }
+
+    pub(crate) fn record_respawn(&self) {
+        self.respawns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> PanicGuardStats {
+        PanicGuardStats {
+            breaker_open: self.open_for().is_some(),
+            panics: self.panics.load(Ordering::Relaxed),
+            trips: self.trips.load(Ordering::Relaxed),
+            respawns: self.respawns.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaker_trips_at_threshold_and_closes_after_cooldown() {
+        let guard = PanicGuard::new();
+        guard.record_panic();
+        assert_eq!(
+            guard.stats().panics,
+            1,
+            "panics are counted without a policy"
+        );
+
+        guard.set_policy(PanicPolicy::trip_breaker(
+            2,
+            Duration::from_secs(60),
+            Duration::from_millis(30),
+        ));
+        guard.record_panic();
+        assert!(guard.open_for().is_none());
+        guard.record_panic();
+        assert!(guard.open_for().is_some());
+        assert_eq!(
+            guard.stats(),
+            PanicGuardStats {
+                breaker_open: true,
+                panics: 3,
+                trips: 1,
+                respawns: 0
+            }
+        );
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(guard.open_for().is_none());
+        guard.record_panic();
+        assert!(guard.open_for().is_none(), "the count starts over");
+    }
+
+    #[test]
+    fn panics_outside_the_window_do_not_count() {
+        let guard = PanicGuard::new();
+        guard.set_policy(PanicPolicy::trip_breaker(
+            2,
+            Duration::from_millis(10),
+            Duration::from_secs(60),
+        ));
+        guard.record_panic();
+        std::thread::sleep(Duration::from_millis(20));
+        guard.record_panic();
+        assert!(guard.open_for().is_none());
+    }
+
+    #[test]
+    fn respawn_is_requested_only_for_respawnable_handlers() {
+        let guard = PanicGuard::new();
+        guard.set_policy(PanicPolicy::respawn(1, Duration::from_secs(60)));
+        guard.record_panic();
+        assert!(!guard.take_respawn_request());
+
+        guard.set_respawnable();
+        guard.record_panic();
+        assert!(guard.take_respawn_request());
+        assert!(!guard.take_respawn_request());
+        assert!(guard.open_for().is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! Declared parameters decoded to their schema types.
+//!
+//! Before dispatch, every path, query, header and cookie parameter the route declares is
+//! decoded once with [`decode_query_values`], the same `string → integer/number/boolean/array`
+//! coercion parameter validation applies, with omitted optional parameters already holding
+//! their schema `default`. Untyped handlers read the results through
+//! [`HandlerRequest::param_i64`], [`HandlerRequest::param_bool`],
+//! [`HandlerRequest::param_str`] and [`HandlerRequest::param_array`] instead of parsing the
+//! raw strings again.
+
+use serde_json::Value;
+
+use super::core::HandlerRequest;
+use super::headers::HeaderLookup;
+use super::HeaderVec;
+use crate::router::ParamVec;
+use crate::server::request::decode_query_values;
+use crate::spec::{ParameterLocation, ParameterMeta};
+
+/// Decoded values of a route's declared parameters, in declaration order
+///
+/// Inserted into [`HandlerRequest::extensions`] for routes that declare parameters; only
+/// parameters the request carries (or that took a default) are present.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedParams(pub Vec<(String, Value)>);
+
+impl DecodedParams {
+    /// Decode the `params` present in the request
+    ///
+    /// Query and header values must already be canonicalized and hold their defaults, as
+    /// they are when the service validates parameters.
+    pub fn decode(
+        params: &[ParameterMeta],
+        path_params: &ParamVec,
+        query_params: &ParamVec,
+        headers: &HeaderVec,
+        cookies: &HeaderVec,
+    ) -> Self {
+        let mut decoded = Vec::new();
+        for param in params {
+            let value = match param.location {
+                ParameterLocation::Path => decode_query_values(
+                    path_params
+                        .iter()
+                        .rfind(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str()),
+                    param.schema.as_ref(),
+                    param.style,
+                    param.explode,
+                ),
+                ParameterLocation::Query => decode_query_values(
+                    query_params
+                        .iter()
+                        .filter(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str()),
+                    param.schema.as_ref(),
+                    param.style,
+                    param.explode,
+                ),
+                ParameterLocation::Header => decode_query_values(
+                    headers.get(&param.name),
+                    param.schema.as_ref(),
+                    param.style,
+                    param.explode,
+                ),
+                ParameterLocation::Cookie => decode_query_values(
+                    cookies
+                        .iter()
+                        .find(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str()),
+                    param.schema.as_ref(),
+                    param.style,
+                    param.explode,
+                ),
+            };
+            if let Some(value) = value {
+                decoded.push((param.name.clone(), value));
+            }
+        }
+        Self(decoded)
+    }
+
+    /// Decoded value of the parameter `name`
+    ///
+    /// When a name is declared in several locations, the first declaration present wins
+    /// (path-level parameters come before operation-level ones).
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+}
+
+/// A decoded parameter does not have the type an accessor asked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamTypeError {
+    /// Parameter name
+    pub name: String,
+    /// Type the accessor reads (`integer`, `boolean`, `string` or `array`)
+    pub expected: &'static str,
+    /// The decoded value
+    pub value: Value,
+}
+
+impl std::fmt::Display for ParamTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let article = if matches!(self.expected, "integer" | "array") {
+            "an"
+        } else {
+            "a"
+        };
+        write!(
+            f,
+            "parameter `{}` is not {article} {}: {}",
+            self.name, self.expected, self.value
+        )
+    }
+}
+
+impl std::error::Error for ParamTypeError {}
+
+impl HandlerRequest {
+    /// Decoded value of the declared parameter `name` (see [`DecodedParams`])
+    ///
+    /// `None` when the route does not declare it or the request omits it without a default.
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<&Value> {
+        self.extensions.get::<DecodedParams>()?.get(name)
+    }
+
+    /// The declared `type: integer` parameter `name`
+    ///
+    /// # Errors
+    ///
+    /// [`ParamTypeError`] when the decoded value is not an integer that fits in `i64`.
+    pub fn param_i64(&self, name: &str) -> Result<Option<i64>, ParamTypeError> {
+        self.typed_param(name, "integer", Value::as_i64)
+    }
+
+    /// The declared `type: boolean` parameter `name`
+    ///
+    /// # Errors
+    ///
+    /// [`ParamTypeError`] when the decoded value is not a boolean.
+    pub fn param_bool(&self, name: &str) -> Result<Option<bool>, ParamTypeError> {
+        self.typed_param(name, "boolean", Value::as_bool)
+    }
+
+    /// The declared string parameter `name` (also any parameter declared without a type)
+    ///
+    /// # Errors
+    ///
+    /// [`ParamTypeError`] when the parameter decoded to another type, e.g. an integer.
+    pub fn param_str(&self, name: &str) -> Result<Option<&str>, ParamTypeError> {
+        self.typed_param(name, "string", Value::as_str)
+    }
+
+    /// The declared `type: array` parameter `name`, its items decoded per `items`
+    ///
+    /// # Errors
+    ///
+    /// [`ParamTypeError`] when the decoded value is not an array.
+    pub fn param_array(&self, name: &str) -> Result<Option<&[Value]>, ParamTypeError> {
+        self.typed_param(name, "array", |v| v.as_array().map(Vec::as_slice))
+    }
+
+    fn typed_param<'a, T>(
+        &'a self,
+        name: &str,
+        expected: &'static str,
+        read: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Result<Option<T>, ParamTypeError> {
+        let Some(value) = self.param(name) else {
+            return Ok(None);
+        };
+        read(value).map(Some).ok_or_else(|| ParamTypeError {
+            name: name.to_string(),
+            expected,
+            value: value.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn param(name: &str, location: ParameterLocation, schema: Value) -> ParameterMeta {
+        ParameterMeta {
+            name: name.to_string(),
+            location,
+            required: false,
+            schema: Some(schema),
+            style: None,
+            explode: None,
+            deprecated: false,
+        }
+    }
+
+    fn pairs<C: FromIterator<(Arc<str>, String)>>(items: &[(&str, &str)]) -> C {
+        items
+            .iter()
+            .map(|(k, v)| (Arc::from(*k), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn decodes_each_location_by_schema() {
+        let params = [
+            param("id", ParameterLocation::Path, json!({"type": "integer"})),
+            param(
+                "tags",
+                ParameterLocation::Query,
+                json!({"type": "array", "items": {"type": "string"}}),
+            ),
+            param(
+                "x-dry-run",
+                ParameterLocation::Header,
+                json!({"type": "boolean"}),
+            ),
+            param(
+                "session",
+                ParameterLocation::Cookie,
+                json!({"type": "string"}),
+            ),
+            param(
+                "absent",
+                ParameterLocation::Query,
+                json!({"type": "integer"}),
+            ),
+        ];
+        let decoded = DecodedParams::decode(
+            &params,
+            &pairs(&[("id", "7")]),
+            &pairs(&[("tags", "a,b")]),
+            &pairs(&[("X-Dry-Run", "true")]),
+            &pairs(&[("session", "abc")]),
+        );
+        assert_eq!(decoded.get("id"), Some(&json!(7)));
+        assert_eq!(decoded.get("tags"), Some(&json!(["a", "b"])));
+        assert_eq!(decoded.get("x-dry-run"), Some(&json!(true)));
+        assert_eq!(decoded.get("session"), Some(&json!("abc")));
+        assert_eq!(decoded.get("absent"), None);
+    }
+
+    #[test]
+    fn type_error_names_the_parameter() {
+        let err = ParamTypeError {
+            name: "limit".to_string(),
+            expected: "integer",
+            value: json!("ten"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "parameter `limit` is not an integer: \"ten\""
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Checked handler registration.
+//!
+//! [`Dispatcher::register_handler`] is `unsafe`, silently replaces a handler registered under
+//! the same name and accepts names no route uses, so a typo surfaces as a `500` on the
+//! first request. [`Dispatcher::register`] checks the name against the spec's routes and
+//! refuses duplicates; [`Dispatcher::register_from_spec`] registers a whole set and reports
+//! the routes still left without a handler. Generated registries keep the unchecked fast path.
+//!
+//! ```rust,ignore
+//! use brrtrouter::dispatcher::{Dispatcher, HandlerFn, HandlerRequest, HandlerResponse};
+//! use std::sync::Arc;
+//!
+//! let (routes, _slug) = brrtrouter::load_spec("openapi.yaml")?;
+//! let mut dispatcher = Dispatcher::new();
+//! let list_pets: HandlerFn = Arc::new(|req: HandlerRequest| {
+//!     let _ = req.reply_tx.send(HandlerResponse::json(200, serde_json::json!([])));
+//! });
+//! dispatcher.register_from_spec(&routes, [("list_pets", list_pets)])?;
+//! ```
+
+use super::core::{Dispatcher, HandlerRequest};
+use crate::spec::RouteMeta;
+use std::sync::Arc;
+
+/// Type-erased handler for [`Dispatcher::register_from_spec`].
+pub type HandlerFn = Arc<dyn Fn(HandlerRequest) + Send + Sync>;
+
+/// Why [`Dispatcher::register`] or [`Dispatcher::register_from_spec`] refused a handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistrationError {
+    /// A handler (plain, worker pool or WebSocket) is already registered under this name.
+    Duplicate(String),
+    /// No route in the spec has this handler name.
+    UnknownRoute(String),
+    /// Handler names of routes left without a handler, in route order.
+    MissingHandlers(Vec<String>),
+    /// The handler coroutine could not be spawned.
+    Spawn {
+        /// Handler name.
+        name: String,
+        /// The spawn error.
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate(name) => write!(f, "handler `{name}` is already registered"),
+            Self::UnknownRoute(name) => write!(f, "no route uses handler `{name}`"),
+            Self::MissingHandlers(names) => {
+                write!(f, "routes without a handler: {}", names.join(", "))
+            }
+            Self::Spawn { name, reason } => {
+                write!(f, "failed to spawn handler `{name}`: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistrationError {}
+
+impl Dispatcher {
+    /// Register `handler_fn` for the routes named `name`, like [`Self::register_handler`].
+    ///
+    /// Must be called once the `may` runtime is configured (stack size, workers), as for
+    /// every handler registration.
+    ///
+    /// # Errors
+    ///
+    /// [`RegistrationError::UnknownRoute`] when no route in `routes` uses `name`,
+    /// [`RegistrationError::Duplicate`] when a handler is already registered under it, and
+    /// [`RegistrationError::Spawn`] when its coroutine cannot be started. Nothing is
+    /// registered on error.
+    pub fn register<F>(
+        &mut self,
+        routes: &[RouteMeta],
+        name: &str,
+        handler_fn: F,
+    ) -> Result<(), RegistrationError>
+    where
+        F: Fn(HandlerRequest) + Send + 'static + Clone,
+    {
+        if !routes.iter().any(|route| &*route.handler_name == name) {
+            return Err(RegistrationError::UnknownRoute(name.to_string()));
+        }
+        if self.has_handler(name) {
+            return Err(RegistrationError::Duplicate(name.to_string()));
+        }
+        // SAFETY: `register_handler`'s unsafety is `may`'s coroutine spawn, whose
+        // requirement (a configured runtime) is documented above; the name checks leave
+        // nothing else for the caller to uphold.
+        unsafe { self.spawn_registered_handler(name, handler_fn) }.map_err(|e| {
+            RegistrationError::Spawn {
+                name: name.to_string(),
+                reason: e.to_string(),
+            }
+        })
+    }
+
+    /// [`Self::register`] every `(name, handler)` pair, then check that every route in
+    /// `routes` has a handler.
+    ///
+    /// # Errors
+    ///
+    /// The first error from [`Self::register`], after which the remaining pairs are not
+    /// registered; otherwise [`RegistrationError::MissingHandlers`] listing
+    /// [`Self::unhandled_routes`] when any are left. Handlers registered before the error
+    /// stay registered.
+    pub fn register_from_spec<'a, I>(
+        &mut self,
+        routes: &[RouteMeta],
+        handlers: I,
+    ) -> Result<(), RegistrationError>
+    where
+        I: IntoIterator<Item = (&'a str, HandlerFn)>,
+    {
+        for (name, handler) in handlers {
+            self.register(routes, name, move |req| handler(req))?;
+        }
+        let missing = self.unhandled_routes(routes);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(RegistrationError::MissingHandlers(missing))
+        }
+    }
+
+    /// Handler names of `routes` with no registered handler (plain, worker pool or
+    /// WebSocket), in route order and without repeats.
+    #[must_use]
+    pub fn unhandled_routes(&self, routes: &[RouteMeta]) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        for route in routes {
+            let name = &*route.handler_name;
+            if !self.has_handler(name) && !missing.iter().any(|m| m == name) {
+                missing.push(name.to_string());
+            }
+        }
+        missing
+    }
+
+    fn has_handler(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+            || self.worker_pools.contains_key(name)
+            || self.websocket_handlers.contains_key(name)
+    }
+}
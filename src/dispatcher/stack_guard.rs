@@ -0,0 +1,309 @@
+//! Coroutine stack high-water marks and exhaustion reporting.
+//!
+//! Handler coroutines run on fixed-size stacks (`BRRTR_STACK_SIZE`, per-handler
+//! `BRRTR_STACK_SIZE__<HANDLER>`). `may` puts a guard page below each stack, so recursing
+//! past its end is a segmentation fault that takes the whole process down, not a panic.
+//! With `BRRTR_STACK_GUARD=on`, handler coroutines get two tools short of that:
+//!
+//! - **High-water marks.** When a handler coroutine starts, the unused part of its stack is
+//!   filled with a pattern. After each request the dispatcher finds how far down the pattern
+//!   was overwritten and keeps the peak per handler ([`stack_watermarks`]). The metrics
+//!   endpoint exports it as `brrtrouter_coroutine_stack_high_water_bytes{handler}` next to
+//!   `brrtrouter_coroutine_stack_size_bytes{handler}`. A handler whose peak passes 90% of its
+//!   stack logs one warning naming the variable to raise.
+//! - **Budget checks.** Every request carries a [`StackBudget`] in its extensions. Recursive
+//!   code calls [`StackBudget::ensure`] to get a [`StackExhausted`] error, or
+//!   [`StackBudget::check`] to panic with one. Such a panic is logged as an error and
+//!   answered `500` with a hint to raise the stack size, and the coroutine keeps serving.
+//!
+//! ```rust,ignore
+//! fn walk(node: &Node, budget: Option<&StackBudget>) -> usize {
+//!     if let Some(budget) = budget {
+//!         budget.check(4 * 1024); // panics with StackExhausted below 4 KiB left
+//!     }
+//!     1 + node.children.iter().map(|c| walk(c, budget)).sum::<usize>()
+//! }
+//!
+//! let count = walk(&tree, req.extensions.get::<StackBudget>());
+//! ```
+//!
+//! ## Platform limitations
+//!
+//! - A real overflow into the guard page is still fatal: a signal handler cannot unwind a
+//!   coroutine safely. Only code that checks its budget gets a `500` instead.
+//! - Stacks are assumed to grow downwards, as on every platform `may` supports (x86-64,
+//!   AArch64).
+//! - Sizes are measured from the handler coroutine's entry, so the few hundred bytes the
+//!   runtime uses above it are not counted, and the bottom 4 KiB of the stack are never
+//!   painted: a peak within 4 KiB of the stack size reads as that bound.
+//! - Painting touches every page of the stack once, so each watched coroutine keeps its
+//!   full stack resident. Only coroutines spawned by [`Dispatcher::register_handler`],
+//!   [`Dispatcher::register_typed_with_stack_size`] and
+//!   [`spawn_untyped_with_stack_size_and_name`] are watched; worker pool workers are not.
+//!
+//! [`Dispatcher::register_handler`]: super::Dispatcher::register_handler
+//! [`Dispatcher::register_typed_with_stack_size`]: super::Dispatcher::register_typed_with_stack_size
+//! [`spawn_untyped_with_stack_size_and_name`]: super::spawn_untyped_with_stack_size_and_name
+
+use super::core::{HandlerResponse, HANDLER_PANIC_PROBLEM_TYPE};
+use crate::server::ProblemDetails;
+use dashmap::DashMap;
+use http::Extensions;
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// Environment variable enabling stack watermarks and budgets (`on` / `off`, default off).
+pub const STACK_GUARD_ENV: &str = "BRRTR_STACK_GUARD";
+
+/// Word written over the unused stack; an arbitrary value unlikely to appear in real frames.
+const PAINT: u64 = 0xB77E_57AC_C0DE_5AFE;
+/// Bytes below the painting frame left alone (its own locals and the x86-64 red zone).
+const FRAME_SLACK: usize = 1024;
+/// Bytes at the bottom of the stack left alone, so painting never reaches the guard page
+/// even when the runtime's own frames above the handler are larger than expected.
+const BOTTOM_SLACK: usize = 4096;
+/// Share of the stack (in percent) whose use triggers the one-time warning.
+const WARN_PERCENT: usize = 90;
+
+static WATERMARKS: Lazy<DashMap<String, Arc<Watermark>>> = Lazy::new(DashMap::new);
+
+/// Peak stack use of one handler.
+#[derive(Debug, Default)]
+struct Watermark {
+    stack_size: AtomicUsize,
+    high_water: AtomicUsize,
+    warned: AtomicBool,
+}
+
+/// Whether `BRRTR_STACK_GUARD` is on; read when each handler coroutine is spawned.
+#[must_use]
+pub fn stack_guard_enabled() -> bool {
+    std::env::var(STACK_GUARD_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// `(handler, stack size, peak bytes used)` of every watched handler, sorted by handler.
+#[must_use]
+pub fn stack_watermarks() -> Vec<(String, usize, usize)> {
+    let mut marks: Vec<_> = WATERMARKS
+        .iter()
+        .map(|entry| {
+            let mark = entry.value();
+            (
+                entry.key().clone(),
+                mark.stack_size.load(Ordering::Relaxed),
+                mark.high_water.load(Ordering::Relaxed),
+            )
+        })
+        .collect();
+    marks.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    marks
+}
+
+/// Stack left to the handler coroutine serving a request.
+///
+/// Found in [`HandlerRequest::extensions`](super::HandlerRequest::extensions) (and the typed
+/// request's `extensions`) when `BRRTR_STACK_GUARD` is on. Only meaningful on the handler
+/// coroutine itself: hand it to code running there, not to other coroutines or threads.
+#[derive(Debug, Clone)]
+pub struct StackBudget {
+    handler: Arc<str>,
+    top: usize,
+    stack_size: usize,
+}
+
+impl StackBudget {
+    /// Stack size of the handler coroutine in bytes
+    #[must_use]
+    pub fn stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    /// Bytes left below the caller's frame (approximate)
+    #[inline(never)]
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        let marker = 0u8;
+        let here = std::hint::black_box(std::ptr::addr_of!(marker)) as usize;
+        self.stack_size
+            .saturating_sub(self.top.saturating_sub(here))
+    }
+
+    /// `Err` when fewer than `reserve` bytes are left.
+    pub fn ensure(&self, reserve: usize) -> Result<(), StackExhausted> {
+        let remaining = self.remaining();
+        if remaining < reserve {
+            return Err(StackExhausted {
+                handler: self.handler.to_string(),
+                stack_size: self.stack_size,
+                remaining,
+                reserve,
+            });
+        }
+        Ok(())
+    }
+
+    /// Panic with [`StackExhausted`] when fewer than `reserve` bytes are left.
+    ///
+    /// The dispatcher answers the panic with a `500` naming the variable to raise.
+    pub fn check(&self, reserve: usize) {
+        if let Err(exhausted) = self.ensure(reserve) {
+            std::panic::panic_any(exhausted);
+        }
+    }
+}
+
+/// A handler ran short of coroutine stack (see [`StackBudget::ensure`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackExhausted {
+    /// Handler whose coroutine ran short
+    pub handler: String,
+    /// Its stack size in bytes
+    pub stack_size: usize,
+    /// Bytes left when checked
+    pub remaining: usize,
+    /// Bytes the check asked for
+    pub reserve: usize,
+}
+
+impl StackExhausted {
+    /// Variable that raises this handler's stack size
+    #[must_use]
+    pub fn env_var(&self) -> String {
+        format!("BRRTR_STACK_SIZE__{}", self.handler.to_uppercase())
+    }
+}
+
+impl fmt::Display for StackExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "handler '{}' exhausted its {}-byte coroutine stack ({} bytes left, {} needed); \
+             raise {} or BRRTR_STACK_SIZE",
+            self.handler,
+            self.stack_size,
+            self.remaining,
+            self.reserve,
+            self.env_var()
+        )
+    }
+}
+
+impl std::error::Error for StackExhausted {}
+
+/// `500` for a handler that panicked with [`StackExhausted`], logged as an error; `None`
+/// for any other panic payload.
+pub(crate) fn exhausted_response(
+    panic: &(dyn Any + Send),
+    request_id: &dyn fmt::Display,
+) -> Option<HandlerResponse> {
+    let exhausted = panic.downcast_ref::<StackExhausted>()?;
+    error!(
+        request_id = %request_id,
+        handler_name = %exhausted.handler,
+        stack_size = exhausted.stack_size,
+        remaining = exhausted.remaining,
+        hint = %exhausted.env_var(),
+        "Handler coroutine stack exhausted"
+    );
+    Some(HandlerResponse::problem(
+        &ProblemDetails::new(500)
+            .problem_type(HANDLER_PANIC_PROBLEM_TYPE)
+            .title("Handler stack exhausted")
+            .detail(exhausted.to_string())
+            .extension("stack_size", exhausted.stack_size)
+            .extension("hint", format!("raise {}", exhausted.env_var())),
+    ))
+}
+
+/// Watch over one handler coroutine's stack; created first thing on that coroutine.
+pub(crate) struct StackWatch {
+    budget: StackBudget,
+    /// Painted address range; `None` when the stack is too small to paint
+    painted: Option<(usize, usize)>,
+    mark: Arc<Watermark>,
+}
+
+impl StackWatch {
+    /// Paint the unused stack below the caller. `None` when `BRRTR_STACK_GUARD` is off or
+    /// this is not a coroutine.
+    #[inline(never)]
+    pub(crate) fn start(handler: &str, stack_size: usize) -> Option<Self> {
+        if !stack_guard_enabled() || !may::coroutine::is_coroutine() {
+            return None;
+        }
+        let marker = 0u8;
+        let top = std::hint::black_box(std::ptr::addr_of!(marker)) as usize;
+        let word = std::mem::size_of::<u64>();
+        let floor = (top.saturating_sub(stack_size) + BOTTOM_SLACK).next_multiple_of(word);
+        let ceiling = top.saturating_sub(FRAME_SLACK) & !(word - 1);
+        let painted = (floor < ceiling).then(|| {
+            let mut addr = floor;
+            while addr < ceiling {
+                // SAFETY: `[floor, ceiling)` lies inside this coroutine's stack, below the
+                // current frame (FRAME_SLACK) and above the guard page (BOTTOM_SLACK), and
+                // nothing lives there yet; later frames simply overwrite the pattern.
+                unsafe { std::ptr::write_volatile(addr as *mut u64, PAINT) };
+                addr += word;
+            }
+            (floor, ceiling)
+        });
+
+        let mark = WATERMARKS
+            .entry(handler.to_string())
+            .or_default()
+            .value()
+            .clone();
+        mark.stack_size.store(stack_size, Ordering::Relaxed);
+        Some(Self {
+            budget: StackBudget {
+                handler: Arc::from(handler),
+                top,
+                stack_size,
+            },
+            painted,
+            mark,
+        })
+    }
+
+    /// Attach the budget to a request about to run on this coroutine.
+    pub(crate) fn stamp(&self, extensions: &mut Extensions) {
+        extensions.insert(self.budget.clone());
+    }
+
+    /// Update the handler's peak after a request; call on the watched coroutine.
+    pub(crate) fn record(&self) {
+        let Some((floor, ceiling)) = self.painted else {
+            return;
+        };
+        let word = std::mem::size_of::<u64>();
+        let mut addr = floor;
+        // SAFETY: reads the region painted in `start`, which is below the current frame.
+        while addr < ceiling && unsafe { std::ptr::read_volatile(addr as *const u64) } == PAINT {
+            addr += word;
+        }
+        let used = self.budget.top.saturating_sub(addr);
+        let previous = self.mark.high_water.fetch_max(used, Ordering::Relaxed);
+        let stack_size = self.budget.stack_size;
+        if used > previous
+            && used * 100 >= stack_size * WARN_PERCENT
+            && !self.mark.warned.swap(true, Ordering::Relaxed)
+        {
+            warn!(
+                handler_name = %self.budget.handler,
+                stack_size = stack_size,
+                high_water_bytes = used,
+                hint = %format!("raise BRRTR_STACK_SIZE__{}", self.budget.handler.to_uppercase()),
+                "Handler coroutine stack nearly exhausted"
+            );
+        }
+    }
+}
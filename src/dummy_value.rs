@@ -15,7 +15,7 @@ pub fn dummy_value(ty: &str) -> askama::Result<String> {
 
     let value = match inner_ty {
         "String" => "\"example\".to_string()".to_string(),
-        "i32" => "42".to_string(),
+        "i32" | "i64" | "i128" => "42".to_string(),
         // NOT 3.14: clippy::approx_constant (≈ PI) is DENY-by-default since
         // rust 1.97, so 3.14 makes every consumer's generated stubs fail
         // `cargo clippy`. 1.5 is exact in binary floating point and near no
@@ -73,6 +73,29 @@ mod tests {
         assert_eq!(dummy_value("i32").unwrap(), "42");
     }
 
+    #[test]
+    fn test_i64() {
+        assert_eq!(dummy_value("i64").unwrap(), "42");
+    }
+
+    #[test]
+    fn test_i128() {
+        assert_eq!(dummy_value("i128").unwrap(), "42");
+    }
+
+    #[test]
+    fn test_rich_types_use_default() {
+        // chrono / uuid / Base64Bytes all implement Default.
+        for ty in [
+            "chrono::DateTime<chrono::Utc>",
+            "chrono::NaiveDate",
+            "uuid::Uuid",
+            "brrtrouter::typed::Base64Bytes",
+        ] {
+            assert_eq!(dummy_value(ty).unwrap(), "Default::default()", "{ty}");
+        }
+    }
+
     #[test]
     fn test_f64() {
         assert_eq!(dummy_value("f64").unwrap(), "1.5");
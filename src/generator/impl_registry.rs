@@ -393,6 +393,7 @@ mod tests {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses: HashMap::new(),
             security: vec![],
             example_name: String::new(),
@@ -402,10 +403,25 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: impl_flag,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
         }
     }
 
@@ -51,6 +51,19 @@
 //!         └── *.rs            # One file per operation
 //! ```
 //!
+//! With `--workspace` ([`GenerationScope::workspace`]) the output is a Cargo workspace
+//! instead, so clients can depend on the API types without the server:
+//!
+//! ```text
+//! my-service/
+//! ├── Cargo.toml              # [workspace] members
+//! ├── my-service-types/
+//! │   ├── Cargo.toml          # serde (+ chrono / uuid / ... when the types use them)
+//! │   └── src/lib.rs          # Every type generated from the spec's schemas
+//! └── my-service-server/      # The project above; handlers import `my_service_types::*`
+//!                             # and `handlers/types.rs` re-exports that crate
+//! ```
+//!
 //! ## Usage
 //!
 //! ### CLI Usage
@@ -107,6 +120,7 @@
 //! - `registry.rs.txt` - **Gen** crate mock handler registration (`register_from_spec`)
 //! - `impl_registry.rs.txt` - **Impl** crate business-logic registration (`register_impl`)
 //! - `Cargo.toml.txt` - Cargo manifest template
+//! - `Dockerfile.txt`, `docker-compose.yml.txt` - Container files (`--with-docker`)
 //!
 //! Modify these templates to customize code generation.
 
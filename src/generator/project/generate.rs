@@ -4,19 +4,24 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::spec::{load_spec, resolve_schema_ref, RouteMeta};
+use crate::spec::{
+    invalid_response_examples, load_spec, read_spec_document, resolve_schema_ref, RouteMeta,
+};
 use oas3;
 use oas3::OpenApiV3Spec;
 
 use crate::generator::schema::{
-    collect_component_schemas, extract_fields, is_named_type, parameter_to_field,
-    process_schema_type_with_spec, spec_uses_rust_decimal, to_camel_case, unique_handler_name,
+    collect_component_schemas_with_options, extract_fields_with_options, is_named_type,
+    process_schema_type_with_options, request_fields_with_options, spec_uses_rust_decimal,
+    spec_uses_type, to_camel_case, unique_handler_name, TypeOptions,
 };
 use crate::generator::stack_size::compute_stack_size;
 use crate::generator::templates::{
-    write_brrtrouter_dependencies_starter, write_controller, write_handler, write_lib_rs,
-    write_main_rs_with_options, write_mod_rs, write_openapi_index, write_registry_rs,
-    write_static_index, write_types_rs, RegistryEntry,
+    cargo_pkg_name_to_rust_ident, write_brrtrouter_dependencies_starter, write_controller,
+    write_docker_files, write_handler_with_types_path, write_lib_rs, write_main_rs_with_options,
+    write_mod_rs, write_openapi_index, write_registry_rs, write_static_index, write_types_crate,
+    write_types_reexport, write_types_rs, write_websocket_controller, write_workspace_cargo_toml,
+    DockerOptions, RegistryEntry, DEFAULT_TYPES_PATH,
 };
 
 use anyhow::Context;
@@ -67,6 +72,13 @@ pub struct GenerationScope {
     pub main: bool,
     /// Generate documentation files (OpenAPI spec, HTML docs)
     pub docs: bool,
+    /// Generate `Dockerfile`, `docker-compose.yml` and `observability/` configs
+    /// (opt-in via `--with-docker`; not part of [`GenerationScope::all`])
+    pub docker: bool,
+    /// Emit a Cargo workspace: a `{name}-types` library crate with the schema types and a
+    /// `{name}-server` crate (handlers, controllers, main) depending on it
+    /// (opt-in via `--workspace`; not part of [`GenerationScope::all`])
+    pub workspace: bool,
 }
 
 impl GenerationScope {
@@ -79,6 +91,8 @@ impl GenerationScope {
             registry: true,
             main: true,
             docs: true,
+            docker: false,
+            workspace: false,
         }
     }
 }
@@ -142,6 +156,41 @@ pub fn generate_project_with_options(
     version: Option<String>,
     package_name: Option<&str>,
     dependencies_config_path: Option<&Path>,
+) -> anyhow::Result<PathBuf> {
+    generate_project_with_type_options(
+        spec_path,
+        output_dir,
+        force,
+        dry_run,
+        scope,
+        version,
+        package_name,
+        dependencies_config_path,
+        TypeOptions::default(),
+    )
+}
+
+/// [`generate_project_with_options`] with explicit [`TypeOptions`] for schema → Rust type
+/// mapping (`brrtrouter-gen generate --rich-types`).
+///
+/// With `rich_types`, a spec that maps to `chrono` / `uuid` types and has no
+/// `brrtrouter-dependencies.toml` gets a starter config listing them, so the generated
+/// `Cargo.toml` includes the dependencies.
+///
+/// # Errors
+///
+/// Returns an error if spec loading, code generation, or file I/O fails.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_project_with_type_options(
+    spec_path: &Path,
+    output_dir: Option<&Path>,
+    force: bool,
+    dry_run: bool,
+    scope: &GenerationScope,
+    version: Option<String>,
+    package_name: Option<&str>,
+    dependencies_config_path: Option<&Path>,
+    type_options: TypeOptions,
 ) -> anyhow::Result<PathBuf> {
     let mut created: Vec<String> = Vec::new();
     let mut updated: Vec<String> = Vec::new();
@@ -150,9 +199,38 @@ pub fn generate_project_with_options(
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in spec path"))?;
     let (mut routes, slug) = load_spec(spec_str)?;
-    let base_dir = output_dir
+    let root_dir = output_dir
         .map(|p| p.to_path_buf())
         .unwrap_or_else(|| Path::new("examples").join(&slug));
+    // With `--workspace` the service is one member crate and the types another; the root
+    // manifest is written first so workspace detection below stops at it.
+    let cargo_package_name = package_name.unwrap_or(&slug);
+    let types_crate = scope
+        .workspace
+        .then(|| format!("{cargo_package_name}-types"));
+    let (base_dir, server_package) = if scope.workspace {
+        let server_package = format!("{cargo_package_name}-server");
+        (root_dir.join(&server_package), server_package)
+    } else {
+        (root_dir.clone(), cargo_package_name.to_string())
+    };
+    let types_module = types_crate.as_deref().map_or_else(
+        || DEFAULT_TYPES_PATH.to_string(),
+        cargo_pkg_name_to_rust_ident,
+    );
+    if let Some(types_crate) = &types_crate {
+        let cargo_path = root_dir.join("Cargo.toml");
+        let existed = cargo_path.exists();
+        if !dry_run {
+            fs::create_dir_all(&root_dir)?;
+            write_workspace_cargo_toml(&root_dir, &[types_crate.clone(), server_package.clone()])?;
+        }
+        if existed {
+            updated.push(format!("workspace: {cargo_path:?}"));
+        } else {
+            created.push(format!("workspace: {cargo_path:?}"));
+        }
+    }
     let src_dir = base_dir.join("src");
     let handler_dir = src_dir.join("handlers");
     let controller_dir = src_dir.join("controllers");
@@ -221,44 +299,58 @@ pub fn generate_project_with_options(
         println!("🔎 Dry-run/only: skipping OpenAPI spec copy");
     }
 
-    let mut schema_types = collect_component_schemas(spec_path)?;
+    let mut schema_types = collect_component_schemas_with_options(spec_path, type_options)?;
 
     // Load spec once for resolving $ref in request/response schemas
-    let spec: oas3::OpenApiV3Spec = if spec_path.extension().map(|s| s == "yaml").unwrap_or(false) {
-        serde_yaml::from_str(&std::fs::read_to_string(spec_path)?)?
-    } else {
-        serde_json::from_str(&std::fs::read_to_string(spec_path)?)?
-    };
+    let spec = read_spec_document(spec_path)?;
 
     // Process request/response schemas with spec context for $ref resolution
     // Do this before the main loop so we have all types available
     for route in routes.iter() {
         if let Some(schema) = &route.request_schema {
             let name = format!("{}Request", route.handler_name);
-            process_schema_type_with_spec(&name, schema, &mut schema_types, Some(&spec));
+            process_schema_type_with_options(
+                &name,
+                schema,
+                &mut schema_types,
+                Some(&spec),
+                type_options,
+            );
         }
         if let Some(schema) = &route.response_schema {
             let name = format!("{}Response", route.handler_name);
-            process_schema_type_with_spec(&name, schema, &mut schema_types, Some(&spec));
+            process_schema_type_with_options(
+                &name,
+                schema,
+                &mut schema_types,
+                Some(&spec),
+                type_options,
+            );
         }
     }
 
     // Resolve dependencies config: use explicit path, or auto-detect alongside spec.
-    // If spec uses decimal/money and no config exists, create brrtrouter-dependencies.toml
-    // so gen produces it from the spec instead of requiring manual creation.
+    // If generated types need extra crates (decimal/money, or chrono/uuid with --rich-types)
+    // and no config exists, create brrtrouter-dependencies.toml so gen produces it from the
+    // spec instead of requiring manual creation.
     let mut config_path = crate::generator::dependencies_config::resolve_config_path(
         dependencies_config_path,
         spec_path,
     );
-    if config_path.is_none() && spec_uses_rust_decimal(&schema_types) {
+    let uses_decimal = spec_uses_rust_decimal(&schema_types);
+    let uses_chrono = spec_uses_type(&schema_types, "chrono::");
+    let uses_uuid = spec_uses_type(&schema_types, "uuid::Uuid");
+    if config_path.is_none() && (uses_decimal || uses_chrono || uses_uuid) {
         if let Some(ref default_path) =
             crate::generator::dependencies_config::default_config_path(spec_path)
         {
             write_brrtrouter_dependencies_starter(
                 default_path,
-                true, // starter includes rust_decimal when spec uses decimal/money
+                uses_decimal,
+                uses_chrono,
+                uses_uuid,
             )?;
-            println!("✅ Created brrtrouter-dependencies.toml (spec uses decimal/money)");
+            println!("✅ Created brrtrouter-dependencies.toml (generated types need extra crates)");
             config_path = Some(default_path.clone());
         }
     }
@@ -267,6 +359,19 @@ pub fn generate_project_with_options(
     } else {
         None
     };
+    if let Some(ref config) = deps_config {
+        for (dep, used) in [("chrono", uses_chrono), ("uuid", uses_uuid)] {
+            if used
+                && !config.dependencies.contains_key(dep)
+                && !config.conditional.contains_key(dep)
+            {
+                println!(
+                    "⚠️  Generated types use `{dep}` (--rich-types) but brrtrouter-dependencies.toml \
+does not list it; add `{dep} = {{ version = \"…\", features = [\"serde\"] }}`"
+                );
+            }
+        }
+    }
 
     let mut seen = HashSet::new();
     let mut modules_handlers = Vec::new();
@@ -274,15 +379,16 @@ pub fn generate_project_with_options(
     let mut registry_entries = Vec::new();
 
     for route in routes.iter_mut() {
+        for warning in invalid_response_examples(route) {
+            println!("⚠️  {warning}");
+        }
         let handler = unique_handler_name(&mut seen, &route.handler_name);
         // JSF P0-2: Convert to Arc<str>
         route.handler_name = Arc::from(handler.as_str());
 
-        let mut request_fields = route.request_schema.as_ref().map_or(vec![], extract_fields);
-        for param in &route.parameters {
-            request_fields.push(parameter_to_field(param));
-        }
-        let response_fields = extract_fields(&resolved_response_schema_json(&spec, route));
+        let request_fields = request_fields_with_options(route, type_options);
+        let response_fields =
+            extract_fields_with_options(&resolved_response_schema_json(&spec, route), type_options);
 
         let mut imports = BTreeSet::new();
         for field in request_fields.iter().chain(response_fields.iter()) {
@@ -309,7 +415,7 @@ pub fn generate_project_with_options(
                     created.push(format!("handler: {handler_path:?}"));
                 }
             } else {
-                write_handler(
+                write_handler_with_types_path(
                     &handler_path,
                     &handler,
                     &request_fields,
@@ -319,6 +425,9 @@ pub fn generate_project_with_options(
                     route.sse,
                     route.x_service.is_some() && route.x_brrtrouter_downstream_path.is_some(),
                     route.needs_http_json_return_type(),
+                    &route.typed_success_statuses(),
+                    route.created_location.as_ref(),
+                    &types_module,
                     force,
                 )?;
                 if existed && force {
@@ -344,6 +453,15 @@ pub fn generate_project_with_options(
                 } else {
                     created.push(format!("controller: {controller_path:?}"));
                 }
+            } else if route.websocket {
+                write_websocket_controller(&controller_path, &handler, force)?;
+                if existed && force {
+                    updated.push(format!("controller: {controller_path:?}"));
+                } else if !existed {
+                    created.push(format!("controller: {controller_path:?}"));
+                } else {
+                    skipped.push(format!("controller: skip existing → {controller_path:?}"));
+                }
             } else {
                 write_controller(
                     &controller_path,
@@ -351,6 +469,7 @@ pub fn generate_project_with_options(
                     &controller_struct,
                     &response_fields,
                     route.example.clone(),
+                    &route.examples,
                     route.sse,
                     force,
                     route.x_service.clone(),
@@ -384,6 +503,7 @@ pub fn generate_project_with_options(
             parameters: route.parameters.clone(),
             stack_size_bytes,
             is_proxy: route.x_service.is_some() && route.x_brrtrouter_downstream_path.is_some(),
+            is_websocket: route.websocket,
         });
     }
 
@@ -437,7 +557,7 @@ pub fn generate_project_with_options(
                     if !found {
                         for route in &routes {
                             if let Some(schema) = &route.request_schema {
-                                let fields = extract_fields(schema);
+                                let fields = extract_fields_with_options(schema, type_options);
                                 for field in fields {
                                     if field.ty.contains(&cond_dep.detect) {
                                         detected_conditional_deps.insert(dep_name.clone());
@@ -450,7 +570,7 @@ pub fn generate_project_with_options(
                                 break;
                             }
                             if let Some(schema) = &route.response_schema {
-                                let fields = extract_fields(schema);
+                                let fields = extract_fields_with_options(schema, type_options);
                                 for field in fields {
                                     if field.ty.contains(&cond_dep.detect) {
                                         detected_conditional_deps.insert(dep_name.clone());
@@ -467,17 +587,36 @@ pub fn generate_project_with_options(
                 }
             }
 
-            let cargo_package_name = package_name.unwrap_or(&slug);
-            if deps_config.is_none() && version.is_none() {
-                crate::generator::templates::write_cargo_toml(&base_dir, cargo_package_name)?;
+            // The server depends on the types crate like on any other configured dependency.
+            let mut server_deps_config = deps_config.clone();
+            if let Some(types_crate) = &types_crate {
+                server_deps_config
+                    .get_or_insert_with(Default::default)
+                    .dependencies
+                    .insert(
+                        types_crate.clone(),
+                        crate::generator::DependencySpec::Full {
+                            version: None,
+                            package: None,
+                            path: Some(format!("../{types_crate}")),
+                            git: None,
+                            branch: None,
+                            features: None,
+                            default_features: None,
+                            workspace: None,
+                        },
+                    );
+            }
+            if server_deps_config.is_none() && version.is_none() {
+                crate::generator::templates::write_cargo_toml(&base_dir, &server_package)?;
             } else {
                 crate::generator::templates::write_cargo_toml_with_options(
                     &base_dir,
-                    cargo_package_name,
+                    &server_package,
                     use_workspace_deps,
                     None,
                     version.clone(),
-                    deps_config.as_ref(),
+                    server_deps_config.as_ref(),
                     Some(&detected_conditional_deps),
                 )?;
             }
@@ -545,6 +684,29 @@ pub fn generate_project_with_options(
     } else {
         println!("🔎 Dry-run/only: skipping docs/static generation");
     }
+    if scope.docker {
+        let dockerfile_path = base_dir.join("Dockerfile");
+        let dockerfile_existed = dockerfile_path.exists();
+        if dockerfile_existed && !force {
+            skipped.push(format!(
+                "docker: skip existing → {dockerfile_path:?} (use --force to overwrite)"
+            ));
+        } else if dry_run {
+            if dockerfile_existed {
+                updated.push(format!("docker: {dockerfile_path:?}"));
+            } else {
+                created.push(format!("docker: {dockerfile_path:?}"));
+            }
+        } else {
+            let options = DockerOptions::from_config_file(&config_dir.join("config.yaml"))?;
+            write_docker_files(&base_dir, &server_package, &options)?;
+            if dockerfile_existed {
+                updated.push(format!("docker: {dockerfile_path:?}"));
+            } else {
+                created.push(format!("docker: {dockerfile_path:?}"));
+            }
+        }
+    }
     if scope.types {
         let types_path = handler_dir.join("types.rs");
         let types_existed = types_path.exists();
@@ -557,7 +719,18 @@ pub fn generate_project_with_options(
                 created.push(format!("types: {types_path:?}"));
             }
         } else {
-            write_types_rs(&handler_dir, &schema_types)?;
+            if let Some(types_crate) = &types_crate {
+                write_types_crate(
+                    &root_dir.join(types_crate),
+                    types_crate,
+                    version.clone(),
+                    &schema_types,
+                    deps_config.as_ref(),
+                )?;
+                write_types_reexport(&handler_dir, types_crate)?;
+            } else {
+                write_types_rs(&handler_dir, &schema_types)?;
+            }
             if types_existed && force {
                 updated.push(format!("types: {types_path:?}"));
             } else if !types_existed {
@@ -649,7 +822,7 @@ pub fn generate_project_with_options(
         }
     }
     println!("──────────────────────────────────────────────────\n");
-    Ok(base_dir)
+    Ok(root_dir)
 }
 
 /// Sentinels that mark a handler as user-owned; when present, --force does not overwrite the body.
@@ -711,6 +884,32 @@ pub fn generate_impl_stubs(
     handler_name: Option<&str>,
     force: bool,
     sync: bool,
+) -> anyhow::Result<()> {
+    generate_impl_stubs_with_type_options(
+        spec_path,
+        impl_output_dir,
+        component_name,
+        handler_name,
+        force,
+        sync,
+        TypeOptions::default(),
+    )
+}
+
+/// [`generate_impl_stubs`] with explicit [`TypeOptions`]; must match the options the gen
+/// crate was generated with so stub literals type-check against its structs.
+///
+/// # Errors
+///
+/// Returns an error if spec loading, stub generation, or file I/O fails.
+pub fn generate_impl_stubs_with_type_options(
+    spec_path: &Path,
+    impl_output_dir: &Path,
+    component_name: Option<&str>,
+    handler_name: Option<&str>,
+    force: bool,
+    sync: bool,
+    type_options: TypeOptions,
 ) -> anyhow::Result<()> {
     use crate::generator::templates::{
         update_impl_mod_rs, write_impl_cargo_toml, write_impl_controller_stub, write_impl_main_rs,
@@ -724,15 +923,7 @@ pub fn generate_impl_stubs(
     let (routes, _slug) = load_spec(spec_str)?;
 
     // Load OpenAPI spec again for $ref resolution when building response fields (ensures full Response in stubs)
-    let spec: OpenApiV3Spec = if spec_path
-        .extension()
-        .map(|e| e == "yaml" || e == "yml")
-        .unwrap_or(false)
-    {
-        serde_yaml::from_str(&std::fs::read_to_string(spec_path)?)?
-    } else {
-        serde_json::from_str(&std::fs::read_to_string(spec_path)?)?
-    };
+    let spec = read_spec_document(spec_path)?;
 
     // Determine component name: use provided name, or derive from output directory
     let component_name = if let Some(name) = component_name {
@@ -821,7 +1012,10 @@ pub fn generate_impl_stubs(
                 .iter()
                 .find(|r| r.handler_name.as_ref() == handler.as_str())
                 .ok_or_else(|| anyhow::anyhow!("Handler not found in spec: {}", handler))?;
-            let response_fields = extract_fields(&resolved_response_schema_json(&spec, route));
+            let response_fields = extract_fields_with_options(
+                &resolved_response_schema_json(&spec, route),
+                type_options,
+            );
             if let Ok(new_content) = crate::generator::templates::sync_impl_stub_response(
                 &content,
                 &response_fields,
@@ -867,12 +1061,10 @@ pub fn generate_impl_stubs(
         }
 
         // Extract fields and types
-        let mut request_fields = route.request_schema.as_ref().map_or(vec![], extract_fields);
-        for param in &route.parameters {
-            request_fields.push(parameter_to_field(param));
-        }
+        let request_fields = request_fields_with_options(route, type_options);
         // Resolve response schema if it's a bare $ref so extract_fields gets full properties (full Response in stub)
-        let response_fields = extract_fields(&resolved_response_schema_json(&spec, route));
+        let response_fields =
+            extract_fields_with_options(&resolved_response_schema_json(&spec, route), type_options);
 
         let mut imports = BTreeSet::new();
         for field in request_fields.iter().chain(response_fields.iter()) {
@@ -3,5 +3,6 @@ mod generate;
 
 pub use format::format_project;
 pub use generate::{
-    generate_impl_stubs, generate_project_from_spec, generate_project_with_options, GenerationScope,
+    generate_impl_stubs, generate_impl_stubs_with_type_options, generate_project_from_spec,
+    generate_project_with_options, generate_project_with_type_options, GenerationScope,
 };
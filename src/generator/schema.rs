@@ -1,5 +1,5 @@
 use crate::dummy_value;
-use crate::spec::{resolve_schema_ref, ParameterMeta};
+use crate::spec::{resolve_schema_ref, ParameterMeta, RouteMeta};
 use oas3;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
@@ -40,10 +40,108 @@ pub struct FieldDef {
     pub ty: String,
     /// Whether the field is optional (`Option<T>`)
     pub optional: bool,
+    /// Whether the schema allows `null` (`type: [T, "null"]`, `nullable: true` or a `oneOf`
+    /// with a null schema); such fields are `Option<T>` and send `None` as `null`
+    pub nullable: bool,
     /// Example value as a Rust literal
     pub value: String,
 }
 
+/// Type-mapping options for schema → Rust type conversion
+///
+/// The default mapping only uses `std` and `serde_json` types (plus `rust_decimal` for
+/// `format: decimal | money`), so generated crates need no extra dependencies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypeOptions {
+    /// Map string and integer `format`s to dedicated types (`--rich-types`):
+    ///
+    /// - `date-time` → `chrono::DateTime<chrono::Utc>`, `date` → `chrono::NaiveDate`
+    /// - `uuid` → `uuid::Uuid`
+    /// - `byte` → `brrtrouter::typed::Base64Bytes`
+    /// - `int64` → `i64`, `int32` → `i32`
+    ///
+    /// Generated crates then depend on `chrono` / `uuid` (with their `serde` features).
+    pub rich_types: bool,
+}
+
+/// Rust type for a primitive (`string` / `integer` / `number` / `boolean`) schema, or `None`
+/// for anything else.
+fn primitive_type(schema: &Value, options: TypeOptions) -> Option<String> {
+    let format = schema.get("format").and_then(|f| f.as_str());
+    let ty = match schema_type(schema)? {
+        "string" if options.rich_types => match format {
+            Some("date-time") => "chrono::DateTime<chrono::Utc>",
+            Some("date") => "chrono::NaiveDate",
+            Some("uuid") => "uuid::Uuid",
+            Some("byte") => "brrtrouter::typed::Base64Bytes",
+            _ => "String",
+        },
+        "string" => "String",
+        // Beyond i64 (e.g. 20-digit IDs); exact on the wire, and through `serde_json::Value`
+        // with the `arbitrary-precision` feature.
+        "integer" if matches!(format, Some("bigint") | Some("int128")) => "i128",
+        "integer" if options.rich_types && format == Some("int64") => "i64",
+        "integer" => "i32",
+        // Format-based type differentiation for number types
+        // number (no format) → f64 (mathematical numbers)
+        // number format:decimal → rust_decimal::Decimal (general decimals)
+        // number format:money → rust_decimal::Decimal; Money has a lifetime parameter
+        // incompatible with owned Deserialize, so convert in business logic.
+        "number" => match format {
+            Some("money") | Some("decimal") => "rust_decimal::Decimal",
+            _ => "f64",
+        },
+        "boolean" => "bool",
+        _ => return None,
+    };
+    Some(ty.to_string())
+}
+
+/// The schema's `type`, ignoring `"null"` in an OpenAPI 3.1 type array
+/// (`type: [integer, "null"]` is `integer`). `None` when absent or a union of several
+/// non-null types.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(ty) => Some(ty.as_str()),
+        Value::Array(types) => {
+            let mut non_null = types
+                .iter()
+                .filter_map(Value::as_str)
+                .filter(|t| *t != "null");
+            match (non_null.next(), non_null.next()) {
+                (Some(ty), None) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether `schema` allows `null`: a `"null"` member in its `type` array, OpenAPI 3.0
+/// `nullable: true`, or a `oneOf` with a `type: null` schema.
+pub fn is_nullable_schema(schema: &Value) -> bool {
+    let type_array_has_null = schema
+        .get("type")
+        .and_then(Value::as_array)
+        .is_some_and(|types| types.iter().any(|t| t == "null"));
+    let one_of_has_null = schema
+        .get("oneOf")
+        .and_then(Value::as_array)
+        .is_some_and(|variants| {
+            variants
+                .iter()
+                .any(|v| v.get("type").and_then(Value::as_str) == Some("null"))
+        });
+    type_array_has_null
+        || one_of_has_null
+        || schema.get("nullable").and_then(Value::as_bool) == Some(true)
+}
+
+/// True for the string-backed `--rich-types` targets, which parse from their wire string.
+fn is_rich_string_type(ty: &str) -> bool {
+    ty.starts_with("chrono::") || ty == "uuid::Uuid" || ty == "brrtrouter::typed::Base64Bytes"
+}
+
 /// Convert a snake_case string to CamelCase
 ///
 /// Used for generating Rust struct names from OpenAPI schema names.
@@ -268,12 +366,18 @@ pub(crate) fn unique_handler_name(seen: &mut HashSet<String>, name: &str) -> Str
 ///
 /// A Rust expression string (e.g., `"example".to_string()`, `42i64`, `vec![]`)
 pub fn rust_literal_for_example(field: &FieldDef, example: &Value) -> String {
+    if field.optional && example.is_null() {
+        return "None".to_string();
+    }
     let literal = match example {
         // Simple string conversion - check if target type is Value or String
         Value::String(s) => {
             if field.ty == "serde_json::Value" || field.ty == "Value" {
                 // Target is serde_json::Value, wrap as Value::String
                 format!("serde_json::Value::String({s:?}.to_string())")
+            } else if is_rich_string_type(&field.ty) {
+                // `--rich-types` target (date-time, uuid, byte): parse from the wire string
+                format!("{s:?}.parse().unwrap_or_default()")
             } else {
                 // Target is Rust String, use .to_string()
                 format!("{s:?}.to_string()")
@@ -538,15 +642,41 @@ pub fn process_schema_type_with_spec(
     schema: &Value,
     types: &mut HashMap<String, TypeDefinition>,
     spec: Option<&oas3::OpenApiV3Spec>,
+) {
+    process_schema_type_with_options(name, schema, types, spec, TypeOptions::default());
+}
+
+/// Process an OpenAPI schema with spec context and explicit [`TypeOptions`]
+pub fn process_schema_type_with_options(
+    name: &str,
+    schema: &Value,
+    types: &mut HashMap<String, TypeDefinition>,
+    spec: Option<&oas3::OpenApiV3Spec>,
+    options: TypeOptions,
+) {
+    process_schema_type_visiting(name, schema, types, spec, options, &mut Vec::new());
+}
+
+/// [`process_schema_type_with_options`] tracking the schemas currently being processed, so a
+/// `$ref` back into one of them (`Tree.children -> Tree`) is not followed again.
+fn process_schema_type_visiting(
+    name: &str,
+    schema: &Value,
+    types: &mut HashMap<String, TypeDefinition>,
+    spec: Option<&oas3::OpenApiV3Spec>,
+    options: TypeOptions,
+    visiting: &mut Vec<String>,
 ) {
     let name = to_camel_case(name);
-    if types.contains_key(&name) {
+    if types.contains_key(&name) || visiting.contains(&name) {
         return;
     }
 
     // First, recursively collect all referenced types from this schema
     if let Some(spec_ref) = spec {
-        collect_referenced_types(schema, spec_ref, types);
+        visiting.push(name.clone());
+        collect_referenced_types(schema, spec_ref, types, options, visiting);
+        visiting.pop();
     }
 
     let enum_variants = extract_string_enum_variants(schema);
@@ -562,7 +692,7 @@ pub fn process_schema_type_with_spec(
         return;
     }
 
-    let fields = extract_fields(schema);
+    let fields = extract_fields_with_options(schema, options);
     if !fields.is_empty() {
         types.insert(
             name.clone(),
@@ -577,14 +707,16 @@ pub fn process_schema_type_with_spec(
 
 /// Returns true if any generated type uses rust_decimal::Decimal (from OpenAPI format: decimal | money).
 pub fn spec_uses_rust_decimal(types: &HashMap<String, TypeDefinition>) -> bool {
-    for type_def in types.values() {
-        for field in &type_def.fields {
-            if field.ty.contains("rust_decimal::Decimal") {
-                return true;
-            }
-        }
-    }
-    false
+    spec_uses_type(types, "rust_decimal::Decimal")
+}
+
+/// Returns true if any field type of a generated type contains `pattern`
+/// (e.g. `"chrono::"`, `"uuid::Uuid"`).
+pub fn spec_uses_type(types: &HashMap<String, TypeDefinition>, pattern: &str) -> bool {
+    types
+        .values()
+        .flat_map(|type_def| &type_def.fields)
+        .any(|field| field.ty.contains(pattern))
 }
 
 /// Recursively collect all types referenced via $ref in a schema
@@ -595,23 +727,29 @@ fn collect_referenced_types(
     schema: &Value,
     spec: &oas3::OpenApiV3Spec,
     types: &mut HashMap<String, TypeDefinition>,
+    options: TypeOptions,
+    visiting: &mut Vec<String>,
 ) {
     // Check if this schema itself is a $ref
     if let Some(ref_path) = schema.get("$ref").and_then(|v| v.as_str()) {
         if let Some(schema_name) = ref_path.strip_prefix("#/components/schemas/") {
             let camel_name = to_camel_case(schema_name);
-            if !types.contains_key(&camel_name) {
+            // A schema still being processed is a reference cycle; it is broken later by
+            // `break_reference_cycles`, not by following the `$ref` again.
+            if !types.contains_key(&camel_name) && !visiting.contains(&camel_name) {
                 // Resolve the referenced schema and process it
                 if let Some(components) = spec.components.as_ref() {
                     if let Some(schema_obj) = components.schemas.get(schema_name) {
                         match schema_obj {
                             oas3::spec::ObjectOrReference::Object(obj) => {
                                 let json = serde_json::to_value(obj).unwrap_or_default();
-                                process_schema_type_with_spec(
+                                process_schema_type_visiting(
                                     schema_name,
                                     &json,
                                     types,
                                     Some(spec),
+                                    options,
+                                    visiting,
                                 );
                             }
                             oas3::spec::ObjectOrReference::Ref {
@@ -620,11 +758,13 @@ fn collect_referenced_types(
                             } => {
                                 if let Some(resolved) = resolve_schema_ref(spec, nested_ref) {
                                     let json = serde_json::to_value(resolved).unwrap_or_default();
-                                    process_schema_type_with_spec(
+                                    process_schema_type_visiting(
                                         schema_name,
                                         &json,
                                         types,
                                         Some(spec),
+                                        options,
+                                        visiting,
                                     );
                                 }
                             }
@@ -638,26 +778,26 @@ fn collect_referenced_types(
     // Recursively check properties for $ref
     if let Some(props) = schema.get("properties").and_then(|p| p.as_object()) {
         for (_prop_name, prop_schema) in props {
-            collect_referenced_types(prop_schema, spec, types);
+            collect_referenced_types(prop_schema, spec, types, options, visiting);
         }
     }
 
     // Check items for arrays
     if let Some(items) = schema.get("items") {
-        collect_referenced_types(items, spec, types);
+        collect_referenced_types(items, spec, types, options, visiting);
     }
 
     // Check oneOf variants
     if let Some(one_of) = schema.get("oneOf").and_then(|v| v.as_array()) {
         for variant in one_of {
-            collect_referenced_types(variant, spec, types);
+            collect_referenced_types(variant, spec, types, options, visiting);
         }
     }
 
     // Check allOf variants
     if let Some(all_of) = schema.get("allOf").and_then(|v| v.as_array()) {
         for variant in all_of {
-            collect_referenced_types(variant, spec, types);
+            collect_referenced_types(variant, spec, types, options, visiting);
         }
     }
 }
@@ -686,6 +826,10 @@ fn collect_referenced_types(
 /// - Set `nullable_oneof = true` to wrap the type in `Option<T>` later
 /// - Fallback to `serde_json::Value` if we can't determine the inner type
 ///
+/// OpenAPI 3.1 type arrays (`type: [integer, "null"]`) and 3.0 `nullable: true` are
+/// handled the same way (see [`is_nullable_schema`]): the field is `Option<T>` even when
+/// required, and is marked [`FieldDef::nullable`] so `None` is sent as `null`.
+///
 /// ## 4. Type Resolution Priority
 /// For each property, we resolve the Rust type in this order:
 /// 1. oneOf inferred type (if present)
@@ -702,22 +846,26 @@ fn collect_referenced_types(
 ///
 /// A vector of field definitions that can be used to generate a Rust struct
 pub fn extract_fields(schema: &Value) -> Vec<FieldDef> {
+    extract_fields_with_options(schema, TypeOptions::default())
+}
+
+/// [`extract_fields`] with explicit [`TypeOptions`]
+pub fn extract_fields_with_options(schema: &Value, options: TypeOptions) -> Vec<FieldDef> {
     let mut fields = vec![];
 
     // Special case: if schema is itself an array, return a single "items" field
-    if let Some(schema_type) = schema.get("type").and_then(|t| t.as_str()) {
-        if schema_type == "array" {
-            if let Some(items) = schema.get("items") {
-                let ty = schema_to_type(items);
-                fields.push(FieldDef {
-                    name: "items".to_string(),
-                    original_name: "items".to_string(),
-                    ty: format!("Vec<{ty}>"),
-                    optional: false,
-                    value: "vec![]".to_string(),
-                });
-                return fields;
-            }
+    if schema_type(schema) == Some("array") {
+        if let Some(items) = schema.get("items") {
+            let ty = schema_to_type_with_options(items, options);
+            fields.push(FieldDef {
+                name: "items".to_string(),
+                original_name: "items".to_string(),
+                ty: format!("Vec<{ty}>"),
+                optional: false,
+                nullable: false,
+                value: "vec![]".to_string(),
+            });
+            return fields;
         }
     }
 
@@ -749,7 +897,7 @@ pub fn extract_fields(schema: &Value) -> Vec<FieldDef> {
                             has_null = true;
                         } else {
                             // This is the actual type (not null)
-                            inner_ty = Some(schema_to_type(variant));
+                            inner_ty = Some(schema_to_type_with_options(variant, options));
                         }
                     }
                     (
@@ -778,41 +926,27 @@ pub fn extract_fields(schema: &Value) -> Vec<FieldDef> {
                 }
             } else {
                 // Priority 4: Use inline type definition
-                match prop.get("type").and_then(|t| t.as_str()) {
-                    Some("string") => "String".to_string(),
-                    Some("integer") => "i32".to_string(),
-                    Some("number") => {
-                        // Format-based type differentiation for number types
-                        // number (no format) → f64 (mathematical numbers)
-                        // number format:decimal → rust_decimal::Decimal (general decimals)
-                        // number format:money → rusty_money::Money (financial amounts)
-                        match prop.get("format").and_then(|f| f.as_str()) {
-                            // For API serialization, use Decimal for money (Money has lifetime parameter incompatible with owned Deserialize)
-                            // Money types should be used in entities, converted from Decimal in business logic
-                            Some("money") => "rust_decimal::Decimal".to_string(),
-                            Some("decimal") => "rust_decimal::Decimal".to_string(),
-                            _ => "f64".to_string(), // Default to f64 for mathematical numbers
-                        }
-                    }
-                    Some("boolean") => "bool".to_string(),
+                match schema_type(prop) {
                     Some("array") => {
                         if let Some(items) = prop.get("items") {
                             // Recursively determine array element type
-                            format!("Vec<{}>", schema_to_type(items))
+                            format!("Vec<{}>", schema_to_type_with_options(items, options))
                         } else {
                             // No items schema, use Value
                             "Vec<serde_json::Value>".to_string()
                         }
                     }
-                    // Priority 5: Fallback for unknown or missing types
-                    _ => "serde_json::Value".to_string(),
+                    // Primitives, then Priority 5: fallback for unknown or missing types
+                    _ => primitive_type(prop, options)
+                        .unwrap_or_else(|| "serde_json::Value".to_string()),
                 }
             };
 
             // Determine if field is optional:
             // - Not in required array, OR
-            // - Has oneOf with null variant
-            let optional = !required.contains(name) || nullable_oneof;
+            // - Allows null (oneOf with null variant, `type: [T, "null"]`, `nullable: true`)
+            let nullable = nullable_oneof || is_nullable_schema(prop);
+            let optional = !required.contains(name) || nullable;
 
             // Generate a dummy value for this field
             // If optional, wrap in Some(...), otherwise use value directly
@@ -829,6 +963,7 @@ pub fn extract_fields(schema: &Value) -> Vec<FieldDef> {
                 original_name: name.clone(), // Original JSON name for #[serde(rename)]
                 ty,
                 optional,
+                nullable,
                 value,
             });
         }
@@ -855,6 +990,11 @@ pub fn extract_fields(schema: &Value) -> Vec<FieldDef> {
 ///
 /// A Rust type string (e.g., `String`, `Vec<Pet>`, `Option<i64>`)
 pub fn schema_to_type(schema: &Value) -> String {
+    schema_to_type_with_options(schema, TypeOptions::default())
+}
+
+/// [`schema_to_type`] with explicit [`TypeOptions`] (e.g. `format`-aware rich types)
+pub fn schema_to_type_with_options(schema: &Value, options: TypeOptions) -> String {
     if let Some(name) = schema.get("x-ref-name").and_then(|v| v.as_str()) {
         return to_camel_case(name);
     }
@@ -864,53 +1004,17 @@ pub fn schema_to_type(schema: &Value) -> String {
         }
         return "serde_json::Value".to_string();
     }
-    match schema.get("type").and_then(|t| t.as_str()) {
-        Some("string") => "String".to_string(),
-        Some("integer") => "i32".to_string(),
-        Some("number") => {
-            // Format-based type differentiation for number types
-            // number (no format) → f64 (mathematical numbers)
-            // number format:decimal → rust_decimal::Decimal (general decimals)
-            // number format:money → rusty_money::Money (financial amounts)
-            match schema.get("format").and_then(|f| f.as_str()) {
-                // For API serialization, use Decimal for money (Money has lifetime parameter incompatible with owned Deserialize)
-                // Money types should be used in entities, converted from Decimal in business logic
-                Some("money") => "rust_decimal::Decimal".to_string(),
-                Some("decimal") => "rust_decimal::Decimal".to_string(),
-                _ => "f64".to_string(), // Default to f64 for mathematical numbers
-            }
-        }
-        Some("boolean") => "bool".to_string(),
-        Some("array") => {
-            if let Some(items) = schema.get("items") {
-                if let Some(item_ty) = items.get("type").and_then(|v| v.as_str()) {
-                    let inner = match item_ty {
-                        "string" => "String".to_string(),
-                        "integer" => "i32".to_string(),
-                        "number" => {
-                            // Check format for array items too
-                            match items.get("format").and_then(|f| f.as_str()) {
-                                // For API serialization, use Decimal for money (Money has lifetime parameter incompatible with owned Deserialize)
-                                Some("money") => "rust_decimal::Decimal".to_string(),
-                                Some("decimal") => "rust_decimal::Decimal".to_string(),
-                                _ => "f64".to_string(),
-                            }
-                        }
-                        "boolean" => "bool".to_string(),
-                        _ => schema_to_type(items),
-                    };
-                    return format!("Vec<{inner}>");
-                }
-                if let Some(item_ref) = items.get("$ref").and_then(|v| v.as_str()) {
-                    if let Some(name) = item_ref.strip_prefix("#/components/schemas/") {
-                        return format!("Vec<{}>", to_camel_case(name));
-                    }
-                }
-                return format!("Vec<{}>", schema_to_type(items));
-            }
-            "Vec<serde_json::Value>".to_string()
-        }
-        _ => "serde_json::Value".to_string(),
+    match schema_type(schema) {
+        Some("array") => match schema.get("items") {
+            // Primitive item types win over an `x-ref-name` hint on the items.
+            Some(items) => format!(
+                "Vec<{}>",
+                primitive_type(items, options)
+                    .unwrap_or_else(|| schema_to_type_with_options(items, options))
+            ),
+            None => "Vec<serde_json::Value>".to_string(),
+        },
+        _ => primitive_type(schema, options).unwrap_or_else(|| "serde_json::Value".to_string()),
     }
 }
 
@@ -927,12 +1031,18 @@ pub fn schema_to_type(schema: &Value) -> String {
 ///
 /// A field definition with the parameter's name, type, and a default value
 pub fn parameter_to_field(param: &ParameterMeta) -> FieldDef {
+    parameter_to_field_with_options(param, TypeOptions::default())
+}
+
+/// [`parameter_to_field`] with explicit [`TypeOptions`]
+pub fn parameter_to_field_with_options(param: &ParameterMeta, options: TypeOptions) -> FieldDef {
     let ty = param
         .schema
         .as_ref()
-        .map(schema_to_type)
+        .map(|schema| schema_to_type_with_options(schema, options))
         .unwrap_or_else(|| "String".to_string());
-    let optional = !param.required;
+    let nullable = param.schema.as_ref().is_some_and(is_nullable_schema);
+    let optional = !param.required || nullable;
     let value = dummy_value::dummy_value(&ty)
         .map(|v| if optional { format!("Some({v})") } else { v })
         .unwrap_or_else(|_| "Default::default()".to_string());
@@ -941,10 +1051,31 @@ pub fn parameter_to_field(param: &ParameterMeta) -> FieldDef {
         original_name: param.name.clone(),
         ty,
         optional,
+        nullable,
         value,
     }
 }
 
+/// Fields of a route's generated `Request`: the request body's properties, then its parameters
+///
+/// When the operation's `requestBody` is not `required`, the body may be absent, so every body
+/// field is `Option<T>` and an empty request converts with all of them `None`.
+pub fn request_fields_with_options(route: &RouteMeta, options: TypeOptions) -> Vec<FieldDef> {
+    let mut fields = route.request_schema.as_ref().map_or(vec![], |schema| {
+        extract_fields_with_options(schema, options)
+    });
+    if !route.request_body_required {
+        for field in fields.iter_mut().filter(|f| !f.optional) {
+            field.optional = true;
+            field.value = format!("Some({})", field.value);
+        }
+    }
+    for param in &route.parameters {
+        fields.push(parameter_to_field_with_options(param, options));
+    }
+    fields
+}
+
 /// Collect all component schemas from an OpenAPI specification
 ///
 /// Parses the spec file and extracts all schema definitions from `components.schemas`,
@@ -965,11 +1096,19 @@ pub fn parameter_to_field(param: &ParameterMeta) -> FieldDef {
 pub fn collect_component_schemas(
     spec_path: &std::path::Path,
 ) -> anyhow::Result<HashMap<String, TypeDefinition>> {
-    let spec: oas3::OpenApiV3Spec = if spec_path.extension().map(|s| s == "yaml").unwrap_or(false) {
-        serde_yaml::from_str(&std::fs::read_to_string(spec_path)?)?
-    } else {
-        serde_json::from_str(&std::fs::read_to_string(spec_path)?)?
-    };
+    collect_component_schemas_with_options(spec_path, TypeOptions::default())
+}
+
+/// [`collect_component_schemas`] with explicit [`TypeOptions`]
+///
+/// # Errors
+///
+/// Returns an error if the spec file cannot be read or parsed.
+pub fn collect_component_schemas_with_options(
+    spec_path: &std::path::Path,
+    options: TypeOptions,
+) -> anyhow::Result<HashMap<String, TypeDefinition>> {
+    let spec = crate::spec::read_spec_document(spec_path)?;
     let mut types = HashMap::new();
     if let Some(components) = spec.components.as_ref() {
         for (name, schema) in &components.schemas {
@@ -977,17 +1116,151 @@ pub fn collect_component_schemas(
                 oas3::spec::ObjectOrReference::Object(obj) => {
                     let json = serde_json::to_value(obj).unwrap_or_default();
                     // Pass spec context to recursively collect referenced types
-                    process_schema_type_with_spec(name, &json, &mut types, Some(&spec));
+                    process_schema_type_with_options(name, &json, &mut types, Some(&spec), options);
                 }
                 oas3::spec::ObjectOrReference::Ref { ref_path, .. } => {
                     if let Some(resolved) = resolve_schema_ref(&spec, ref_path) {
                         let json = serde_json::to_value(resolved).unwrap_or_default();
                         // Pass spec context to recursively collect referenced types
-                        process_schema_type_with_spec(name, &json, &mut types, Some(&spec));
+                        process_schema_type_with_options(
+                            name,
+                            &json,
+                            &mut types,
+                            Some(&spec),
+                            options,
+                        );
                     }
                 }
             }
         }
     }
+    break_reference_cycles(&mut types)?;
     Ok(types)
 }
+
+/// The named type a field holds by value (`Tree` or `Option<Tree>`), if it is one of `types`.
+///
+/// `Vec<T>` and maps already allocate on the heap, so they never make a type infinitely sized.
+fn by_value_target<'a>(
+    field: &'a FieldDef,
+    types: &HashMap<String, TypeDefinition>,
+) -> Option<&'a str> {
+    types
+        .get(field.ty.as_str())
+        .filter(|target| !target.fields.is_empty())
+        .map(|_| field.ty.as_str())
+}
+
+/// Find a cycle through by-value fields, following only fields accepted by `follow`.
+///
+/// Returns the type names along the cycle, starting and ending with the same name.
+fn find_cycle(
+    types: &HashMap<String, TypeDefinition>,
+    follow: impl Fn(&FieldDef) -> bool,
+) -> Option<Vec<String>> {
+    fn visit(
+        name: &str,
+        types: &HashMap<String, TypeDefinition>,
+        follow: &dyn Fn(&FieldDef) -> bool,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|n| n == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_string());
+            return Some(cycle);
+        }
+        if done.contains(name) {
+            return None;
+        }
+        path.push(name.to_string());
+        let fields = types
+            .get(name)
+            .map(|t| t.fields.as_slice())
+            .unwrap_or_default();
+        for field in fields.iter().filter(|f| follow(f)) {
+            if let Some(target) = by_value_target(field, types) {
+                if let Some(cycle) = visit(target, types, follow, path, done) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        done.insert(name.to_string());
+        None
+    }
+
+    let mut names: Vec<&String> = types.keys().collect();
+    names.sort();
+    let mut done = HashSet::new();
+    names
+        .into_iter()
+        .find_map(|name| visit(name, types, &follow, &mut Vec::new(), &mut done))
+}
+
+/// Break `$ref` cycles between generated types so they compile as finite Rust structs.
+///
+/// Every by-value field that points back into its own cycle (`Node.next -> Node`,
+/// `A.b -> B -> A`) becomes `Box<T>`; optional fields render as `Option<Box<T>>`. Fields
+/// through `Vec<T>` are left alone.
+///
+/// # Errors
+///
+/// A cycle made only of required, non-nullable fields describes a value that can never end
+/// (every `A` must contain a `B` that must contain an `A`, ...) and is rejected with an error
+/// naming the schemas involved.
+pub fn break_reference_cycles(types: &mut HashMap<String, TypeDefinition>) -> anyhow::Result<()> {
+    if let Some(cycle) = find_cycle(types, |field| !field.optional) {
+        anyhow::bail!(
+            "schema reference cycle {} cannot be represented: every field along it is required, \
+             so a value would never end; make one of them optional, nullable or an array",
+            cycle.join(" -> ")
+        );
+    }
+
+    let mut boxed = Vec::new();
+    let mut names: Vec<String> = types.keys().cloned().collect();
+    names.sort();
+    for name in &names {
+        let Some(type_def) = types.get(name) else {
+            continue;
+        };
+        for (index, field) in type_def.fields.iter().enumerate() {
+            let Some(target) = by_value_target(field, types) else {
+                continue;
+            };
+            if reaches(types, target, name) {
+                boxed.push((name.clone(), index));
+            }
+        }
+    }
+    for (name, index) in boxed {
+        if let Some(field) = types.get_mut(&name).and_then(|t| t.fields.get_mut(index)) {
+            field.ty = format!("Box<{}>", field.ty);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `to` is reachable from `from` through by-value fields.
+fn reaches(types: &HashMap<String, TypeDefinition>, from: &str, to: &str) -> bool {
+    let mut seen = HashSet::new();
+    let mut stack = vec![from];
+    while let Some(name) = stack.pop() {
+        if name == to {
+            return true;
+        }
+        if !seen.insert(name) {
+            continue;
+        }
+        if let Some(type_def) = types.get(name) {
+            stack.extend(
+                type_def
+                    .fields
+                    .iter()
+                    .filter_map(|field| by_value_target(field, types)),
+            );
+        }
+    }
+    false
+}
@@ -256,6 +256,19 @@ mod tests {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method: Method::GET,
             path_pattern: Arc::from("/test"),
             handler_name: Arc::from("test_handler"),
@@ -265,6 +278,7 @@ mod tests {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses: HashMap::new(),
             security: vec![],
             example_name: "".to_string(),
@@ -274,6 +288,8 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
         }
     }
@@ -301,6 +317,7 @@ mod tests {
                 schema: None,
                 style: None,
                 explode: None,
+                deprecated: false,
             });
         }
         let stack_size = compute_stack_size(&route);
@@ -321,6 +338,7 @@ mod tests {
                 schema: None,
                 style: None,
                 explode: None,
+                deprecated: false,
             });
         }
         let stack_size = compute_stack_size(&route);
@@ -341,6 +359,7 @@ mod tests {
                 schema: None,
                 style: None,
                 explode: None,
+                deprecated: false,
             });
         }
         let stack_size = compute_stack_size(&route);
@@ -515,6 +534,7 @@ mod tests {
                 schema: None,
                 style: None,
                 explode: None,
+                deprecated: false,
             });
         }
 
@@ -590,6 +610,7 @@ mod tests {
                 schema: None,
                 style: None,
                 explode: None,
+                deprecated: false,
             });
         }
         route.sse = true;
@@ -8,7 +8,7 @@ use std::path::{Path, PathBuf};
 use super::schema::{
     is_named_type, rust_literal_for_example, to_camel_case, FieldDef, TypeDefinition,
 };
-use crate::spec::{ParameterMeta, RouteMeta};
+use crate::spec::{CreatedLocation, ParameterMeta, RouteMeta};
 
 /// Cargo `package` names may contain `-`. Rust `use` paths use `_` for those segments
 /// (see rustc crate naming). Example: `market-data_service_api` → `market_data_service_api`.
@@ -34,6 +34,9 @@ pub struct RegistryEntry {
     pub stack_size_bytes: usize,
     /// Whether this handler executes as a pure bytes-stream proxy (JSF rule isolation)
     pub is_proxy: bool,
+    /// Whether this is a WebSocket route (`x-websocket: true`), registered with
+    /// `Dispatcher::register_websocket_handler` instead of a handler coroutine
+    pub is_websocket: bool,
 }
 
 /// Parameters for writing implementation controller stub files
@@ -129,12 +132,105 @@ pub struct CargoTomlTemplateData {
 #[template(path = "config.yaml", escape = "none")]
 pub struct ConfigYamlTemplate;
 
+/// Container settings for the `--with-docker` files
+///
+/// Read from the project's `config/config.yaml` by [`DockerOptions::from_config_file`]:
+/// the top-level `port` (the same key the generated `main.rs` binds to) and the
+/// generator-only `docker.healthcheck_interval_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DockerOptions {
+    /// Port the container listens on (`EXPOSE`, `PORT`, healthcheck and compose mapping)
+    pub port: u16,
+    /// `HEALTHCHECK --interval` in seconds
+    pub healthcheck_interval_secs: u64,
+}
+
+impl Default for DockerOptions {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            healthcheck_interval_secs: 30,
+        }
+    }
+}
+
+impl DockerOptions {
+    /// Read options from a `config.yaml`; missing file or keys keep the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but is not valid YAML.
+    pub fn from_config_file(path: &Path) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let Ok(raw) = fs::read_to_string(path) else {
+            return Ok(options);
+        };
+        let config: serde_yaml::Value = serde_yaml::from_str(&raw)?;
+        if let Some(port) = config
+            .get("port")
+            .and_then(serde_yaml::Value::as_u64)
+            .and_then(|p| u16::try_from(p).ok())
+        {
+            options.port = port;
+        }
+        if let Some(secs) = config
+            .get("docker")
+            .and_then(|d| d.get("healthcheck_interval_secs"))
+            .and_then(serde_yaml::Value::as_u64)
+        {
+            options.healthcheck_interval_secs = secs;
+        }
+        Ok(options)
+    }
+}
+
+/// Template data for the generated multi-stage `Dockerfile`
+#[derive(Template)]
+#[template(path = "Dockerfile.txt", escape = "none")]
+pub struct DockerfileTemplateData {
+    /// Cargo package (and binary) name
+    pub name: String,
+    /// Container port
+    pub port: u16,
+    /// `HEALTHCHECK --interval` in seconds
+    pub healthcheck_interval_secs: u64,
+}
+
+/// Template data for the generated `docker-compose.yml` (service + Prometheus/Grafana/Jaeger)
+#[derive(Template)]
+#[template(path = "docker-compose.yml.txt", escape = "none")]
+pub struct DockerComposeTemplateData {
+    /// Cargo package name, also the compose service name
+    pub name: String,
+    /// Container port
+    pub port: u16,
+}
+
+/// Template data for `observability/prometheus.yml` used by the compose stack
+#[derive(Template)]
+#[template(path = "prometheus.yml.txt", escape = "none")]
+pub struct PrometheusConfigTemplateData {
+    /// Compose service name to scrape
+    pub name: String,
+    /// Container port serving `/metrics`
+    pub port: u16,
+}
+
+/// Template for `observability/grafana-datasources.yml` used by the compose stack
+#[derive(Template)]
+#[template(path = "grafana-datasources.yml.txt", escape = "none")]
+pub struct GrafanaDatasourcesTemplate;
+
 /// Template data for generating brrtrouter-dependencies.toml starter
 #[derive(Template)]
 #[template(path = "brrtrouter-dependencies.toml.txt", escape = "none")]
 pub struct BrrtrouterDependenciesTomlTemplate {
     /// Include conditional rust_decimal when spec uses format: decimal | format: money
     pub conditional_rust_decimal: bool,
+    /// Include conditional chrono when `--rich-types` maps format: date-time | date
+    pub conditional_chrono: bool,
+    /// Include conditional uuid when `--rich-types` maps format: uuid
+    pub conditional_uuid: bool,
 }
 
 /// Template data for generating main.rs entry point
@@ -189,6 +285,26 @@ pub struct TypesTemplateData {
     pub types: BTreeMap<String, TypeDefinition>,
 }
 
+/// Template data for the `-types` crate manifest of a `--workspace` project
+#[derive(Template)]
+#[template(path = "types_cargo.toml.txt", escape = "none")]
+pub struct TypesCargoTomlTemplateData {
+    /// Package name (`{name}-types`)
+    pub name: String,
+    /// Version for [package].version
+    pub version: String,
+    /// Dependencies the generated types need beyond `serde` / `serde_json`
+    pub dependencies: Vec<FormattedDependency>,
+}
+
+/// Template data for the root manifest of a `--workspace` project
+#[derive(Template)]
+#[template(path = "workspace_cargo.toml.txt", escape = "none")]
+pub struct WorkspaceCargoTomlTemplateData {
+    /// Member crate directories, relative to the workspace root
+    pub members: Vec<String>,
+}
+
 /// Template data for generating a handler module
 ///
 /// Contains all information needed to generate request/response types and a handler skeleton.
@@ -207,6 +323,9 @@ pub struct HandlerTemplateData {
     pub response_array_type: String,
     /// Types to import (e.g., custom types from handler_types)
     pub imports: Vec<String>,
+    /// Module `imports` come from: `crate::handlers::types`, or the `-types` crate of a
+    /// `--workspace` project
+    pub types_path: String,
     /// Route parameters
     pub parameters: Vec<ParameterMeta>,
     /// Whether this handler uses Server-Sent Events
@@ -215,6 +334,47 @@ pub struct HandlerTemplateData {
     pub is_proxy: bool,
     /// Emit `HttpJson<Response>` when OpenAPI defines non-2xx JSON response schemas (BR-3)
     pub uses_http_json: bool,
+    /// Variants of the generated `DeclaredResponse` enum; empty when none is emitted
+    pub declared_success: Vec<DeclaredSuccess>,
+    /// `GET` route template of the created resource, for the generated `location` helper
+    pub location_template: Option<String>,
+    /// Parameter of `location_template` holding the created resource's id
+    pub location_param: String,
+}
+
+/// A declared success status rendered as a `DeclaredResponse` variant
+#[derive(Debug, Clone)]
+pub struct DeclaredSuccess {
+    /// `brrtrouter::typed` wrapper (and variant) name, e.g. `Created`
+    pub variant: &'static str,
+    /// Whether the wrapper carries a `Response` body (all but `NoContent`)
+    pub has_body: bool,
+}
+
+impl DeclaredSuccess {
+    /// The typed wrapper for `status`, if there is one
+    pub fn for_status(status: u16) -> Option<Self> {
+        let variant = match status {
+            200 => "Ok",
+            201 => "Created",
+            202 => "Accepted",
+            204 => "NoContent",
+            _ => return None,
+        };
+        Some(Self {
+            variant,
+            has_body: status != 204,
+        })
+    }
+}
+
+/// A named response example rendered into a mock controller
+#[derive(Debug, Clone)]
+pub struct NamedExample {
+    /// Example name as a Rust string literal (matched against `?__example=`)
+    pub name_literal: String,
+    /// Example value as pretty-printed JSON
+    pub json: String,
 }
 
 /// Template data for generating a controller module
@@ -235,6 +395,8 @@ pub struct ControllerTemplateData {
     pub has_example: bool,
     /// Example response as JSON string
     pub example_json: String,
+    /// Named examples selectable with `?__example=<name>` (empty without an `examples` map)
+    pub named_examples: Vec<NamedExample>,
     /// Whether the response is an array
     pub response_is_array: bool,
     /// Array literal for response (if array)
@@ -268,6 +430,10 @@ pub struct ControllerTemplateData {
 /// * `imports` - Types to import
 /// * `params` - Route parameters
 /// * `sse` - Whether to use Server-Sent Events
+/// * `declared_success` - Statuses for the `DeclaredResponse` enum (see
+///   [`crate::spec::RouteMeta::typed_success_statuses`])
+/// * `created_location` - Where created resources are read back, for the `location` helper
+///   (see [`crate::spec::RouteMeta::created_location`])
 /// * `force` - Overwrite existing file
 ///
 /// # Errors
@@ -284,6 +450,50 @@ pub fn write_handler(
     sse: bool,
     is_proxy: bool,
     uses_http_json: bool,
+    declared_success: &[u16],
+    created_location: Option<&CreatedLocation>,
+    force: bool,
+) -> anyhow::Result<()> {
+    write_handler_with_types_path(
+        path,
+        handler,
+        req,
+        res,
+        imports,
+        params,
+        sse,
+        is_proxy,
+        uses_http_json,
+        declared_success,
+        created_location,
+        DEFAULT_TYPES_PATH,
+        force,
+    )
+}
+
+/// Where generated handlers import schema types from in a single-crate project
+pub const DEFAULT_TYPES_PATH: &str = "crate::handlers::types";
+
+/// [`write_handler`] importing schema types from `types_path` instead of
+/// [`DEFAULT_TYPES_PATH`] (the `-types` crate of a `--workspace` project)
+///
+/// # Errors
+///
+/// Returns an error if file writing fails
+#[allow(clippy::too_many_arguments)]
+pub fn write_handler_with_types_path(
+    path: &Path,
+    handler: &str,
+    req: &[FieldDef],
+    res: &[FieldDef],
+    imports: &BTreeSet<String>,
+    params: &[ParameterMeta],
+    sse: bool,
+    is_proxy: bool,
+    uses_http_json: bool,
+    declared_success: &[u16],
+    created_location: Option<&CreatedLocation>,
+    types_path: &str,
     force: bool,
 ) -> anyhow::Result<()> {
     if path.exists() && !force {
@@ -297,10 +507,19 @@ pub fn write_handler(
         response_is_array: res.len() == 1 && res[0].name == "items",
         response_array_type: res.first().map(|f| f.ty.clone()).unwrap_or_default(),
         imports: imports.iter().cloned().collect(),
+        types_path: types_path.to_string(),
         parameters: params.to_vec(),
         sse,
         is_proxy,
         uses_http_json,
+        declared_success: declared_success
+            .iter()
+            .filter_map(|&status| DeclaredSuccess::for_status(status))
+            .collect(),
+        location_template: created_location.map(|c| c.template.to_string()),
+        location_param: created_location
+            .map(|c| c.param.clone())
+            .unwrap_or_default(),
     }
     .render()?;
     fs::write(path, rendered)?;
@@ -320,18 +539,21 @@ pub fn write_handler(
 /// * `struct_name` - Controller struct name
 /// * `res` - Response struct fields
 /// * `example` - Example response from OpenAPI spec
+/// * `named_examples` - Named examples (`examples` map), selectable with `?__example=<name>`
 /// * `sse` - Whether to use Server-Sent Events
 /// * `force` - Overwrite existing file
 ///
 /// # Errors
 ///
 /// Returns an error if file writing fails
+#[allow(clippy::too_many_arguments)]
 pub fn write_controller(
     path: &Path,
     handler: &str,
     struct_name: &str,
     res: &[FieldDef],
     example: Option<Value>,
+    named_examples: &[(String, Value)],
     sse: bool,
     force: bool,
     downstream_service: Option<String>,
@@ -373,6 +595,7 @@ pub fn write_controller(
                 original_name: field.original_name.clone(),
                 ty: field.ty.clone(),
                 optional: field.optional,
+                nullable: field.nullable,
                 value, // Use enriched value with actual example data
             }
         })
@@ -421,6 +644,7 @@ pub fn write_controller(
                     original_name: "items".to_string(),
                     ty: res[0].ty.clone(), // Vec<T> where T is the element type
                     optional: false,
+                    nullable: false,
                     value: String::new(), // Not used for this purpose
                 };
                 // Convert entire JSON array to Rust vec![] literal
@@ -444,6 +668,15 @@ pub fn write_controller(
         // Not an array response, no array literal needed
         String::new()
     };
+    let named_examples = named_examples
+        .iter()
+        .filter_map(|(name, value)| {
+            Some(NamedExample {
+                name_literal: format!("{name:?}"),
+                json: serde_json::to_string_pretty(value).ok()?,
+            })
+        })
+        .collect();
     let is_untyped_proxy = downstream_service.is_some() && downstream_path.is_some();
     let proxy_service = downstream_service.unwrap_or_default();
     let proxy_path = downstream_path.unwrap_or_default();
@@ -454,6 +687,7 @@ pub fn write_controller(
         example: example_pretty,
         has_example: example.is_some(),
         example_json,
+        named_examples,
         response_is_array,
         response_array_literal: array_literal,
         imports: imports.iter().cloned().collect(),
@@ -469,6 +703,38 @@ pub fn write_controller(
     Ok(())
 }
 
+/// Template data for generating a WebSocket controller module (`x-websocket: true`)
+#[derive(Template)]
+#[template(path = "websocket_controller.rs.txt", escape = "none")]
+pub struct WebSocketControllerTemplateData {
+    /// Handler function name
+    pub handler_name: String,
+}
+
+/// Write an echo controller for a WebSocket route
+///
+/// # Arguments
+///
+/// * `path` - Output file path
+/// * `handler` - Handler function name
+/// * `force` - Overwrite existing file
+///
+/// # Errors
+///
+/// Returns an error if file writing fails
+pub fn write_websocket_controller(path: &Path, handler: &str, force: bool) -> anyhow::Result<()> {
+    if path.exists() && !force {
+        println!("⚠️  Skipping existing controller file: {path:?}");
+        return Ok(());
+    }
+    let context = WebSocketControllerTemplateData {
+        handler_name: handler.to_string(),
+    };
+    fs::write(path, context.render()?)?;
+    println!("✅ Generated controller: {path:?}");
+    Ok(())
+}
+
 /// Write a mod.rs file with module declarations (internal helper)
 ///
 /// Generates a `mod.rs` file that declares all submodules in a directory.
@@ -570,6 +836,113 @@ pub(crate) fn write_types_rs(
     Ok(())
 }
 
+/// Write the root Cargo.toml of a `--workspace` project, listing `members`
+///
+/// # Errors
+///
+/// Returns an error if template rendering or file writing fails
+pub(crate) fn write_workspace_cargo_toml(base: &Path, members: &[String]) -> anyhow::Result<()> {
+    let rendered = WorkspaceCargoTomlTemplateData {
+        members: members.to_vec(),
+    }
+    .render()?;
+    fs::write(base.join("Cargo.toml"), rendered)?;
+    println!("✅ Wrote workspace Cargo.toml");
+    Ok(())
+}
+
+/// Write the `-types` library crate of a `--workspace` project (internal helper)
+///
+/// `src/lib.rs` holds every generated type definition; `Cargo.toml` depends on `serde` and
+/// `serde_json`, on `brrtrouter` when a type uses `brrtrouter::typed` (e.g. `Base64Bytes`),
+/// and on the `[conditional]` dependencies of `deps_config` whose `detect` pattern the types
+/// use.
+///
+/// # Arguments
+///
+/// * `dir` - Crate directory (`{name}-types/`)
+/// * `name` - Package name
+/// * `version` - Optional version string (defaults to "0.1.0" if None)
+/// * `types` - Map of type names to their definitions
+/// * `deps_config` - Optional dependencies configuration from brrtrouter-dependencies.toml
+///
+/// # Errors
+///
+/// Returns an error if a detected dependency is configured as `workspace = true`, or if
+/// template rendering or file writing fails
+pub(crate) fn write_types_crate(
+    dir: &Path,
+    name: &str,
+    version: Option<String>,
+    types: &HashMap<String, TypeDefinition>,
+    deps_config: Option<&crate::generator::DependenciesConfig>,
+) -> anyhow::Result<()> {
+    let src_dir = dir.join("src");
+    fs::create_dir_all(&src_dir)?;
+
+    let mut dependencies = Vec::new();
+    if crate::generator::schema::spec_uses_type(types, "brrtrouter::") {
+        let path = brrtrouter_relative_path(dir, None);
+        dependencies.push(FormattedDependency {
+            name: "brrtrouter".to_string(),
+            spec: format!(r#"{{ path = "{}" }}"#, path.to_string_lossy()),
+        });
+    }
+    if let Some(config) = deps_config {
+        let mut conditional: Vec<_> = config.conditional.iter().collect();
+        conditional.sort_by(|a, b| a.0.cmp(b.0));
+        for (dep_name, cond_dep) in conditional {
+            if !crate::generator::schema::spec_uses_type(types, &cond_dep.detect) {
+                continue;
+            }
+            let spec = cond_dep.to_spec();
+            ensure_dep_spec_allows_non_workspace(dep_name, &spec, false)?;
+            dependencies.push(FormattedDependency {
+                name: dep_name.clone(),
+                spec: format_dependency_spec(dep_name, &spec, false),
+            });
+        }
+    }
+
+    let rendered = TypesCargoTomlTemplateData {
+        name: name.to_string(),
+        version: version.unwrap_or_else(|| "0.1.0".to_string()),
+        dependencies,
+    }
+    .render()?;
+    fs::write(dir.join("Cargo.toml"), rendered)?;
+
+    let mut sorted = BTreeMap::new();
+    for (type_name, def) in types {
+        sorted.insert(type_name.clone(), def.clone());
+    }
+    let rendered = TypesTemplateData { types: sorted }.render()?;
+    let lib_path = src_dir.join("lib.rs");
+    fs::write(&lib_path, rendered)?;
+    println!("✅ Generated types crate → {lib_path:?}");
+    Ok(())
+}
+
+/// Write `handlers/types.rs` of a `--workspace` server crate: a re-export of the `-types`
+/// crate, so controllers and impl crates keep importing `handlers::types::*`
+///
+/// # Errors
+///
+/// Returns an error if file writing fails
+pub(crate) fn write_types_reexport(dir: &Path, types_crate: &str) -> anyhow::Result<()> {
+    let path = dir.join("types.rs");
+    let rendered = format!(
+        "// ⚠️ WARNING: This file is auto-generated by BRRTRouter\n\
+// ⚠️ DO NOT MODIFY - Changes will be overwritten on next generation\n\
+// Types live in the `{types_crate}` crate; re-exported for controllers and impl crates.\n\
+pub use {}::*;\n",
+        cargo_pkg_name_to_rust_ident(types_crate)
+    );
+    fs::write(&path, rendered)?;
+    println!("✅ Generated types.rs → {path:?}");
+    Ok(())
+}
+
 /// Write the Cargo.toml file for the generated project (internal helper)
 ///
 /// Generates the Cargo.toml manifest with project name and dependencies.
@@ -889,6 +1262,101 @@ pub(crate) fn detect_workspace_with_brrtrouter_deps(output_dir: &Path) -> bool {
     false
 }
 
+/// Relative path from `base` to the BRRTRouter checkout: `brrtrouter_root` when given, else
+/// the nearest ancestor whose Cargo.toml is the `brrtrouter` package, else `../..`
+fn brrtrouter_relative_path(base: &Path, brrtrouter_root: Option<&Path>) -> PathBuf {
+    // Calculate relative path from output directory to BRRTRouter
+    let brrtrouter_base = brrtrouter_root
+        .map(|p| p.to_path_buf())
+        .or_else(|| {
+            // Try to find BRRTRouter by looking for its Cargo.toml in parent directories
+            // This handles the case where we're generating from within BRRTRouter
+            let mut current = base;
+            loop {
+                let cargo_toml = current.join("Cargo.toml");
+                if cargo_toml.exists() {
+                    if let Ok(content) = std::fs::read_to_string(&cargo_toml) {
+                        if content.contains("name = \"brrtrouter\"") {
+                            return Some(current.to_path_buf());
+                        }
+                    }
+                }
+                match current.parent() {
+                    Some(parent) => current = parent,
+                    None => break,
+                }
+            }
+            None
+        })
+        .unwrap_or_else(|| {
+            // Default fallback: assume BRRTRouter is at ../.. (petstore case)
+            base.parent()
+                .and_then(|p| p.parent())
+                .unwrap_or(base)
+                .to_path_buf()
+        });
+
+    // Calculate relative path from base to brrtrouter_base
+    // Ensure both paths are absolute for reliable calculation
+    let base_abs = if base.is_absolute() {
+        base.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(base)
+    };
+    let brrtrouter_abs = if brrtrouter_base.is_absolute() {
+        brrtrouter_base // Already a PathBuf, no need to clone
+    } else {
+        std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(brrtrouter_base)
+    };
+
+    let base_canon = base_abs.canonicalize().unwrap_or(base_abs);
+    let brrtrouter_canon = brrtrouter_abs.canonicalize().unwrap_or(brrtrouter_abs);
+
+    // Calculate relative path from base to brrtrouter_base
+    // base is typically a subdirectory of brrtrouter_base (e.g., examples/pet_store is under BRRTRouter root)
+    if let Ok(rel) = base_canon.strip_prefix(&brrtrouter_canon) {
+        // base is a subdirectory of brrtrouter_base (normal case)
+        // Count depth and build ../.. path
+        let depth = rel.components().count();
+        let mut rel_path = PathBuf::new();
+        for _ in 0..depth {
+            rel_path.push("..");
+        }
+        rel_path
+    } else if let Ok(rel) = brrtrouter_canon.strip_prefix(&base_canon) {
+        // brrtrouter_base is a subdirectory of base (unusual, but handle it)
+        rel.to_path_buf()
+    } else {
+        // No direct relationship - calculate manually
+        // Find common prefix
+        let base_parts: Vec<_> = base_canon.components().collect();
+        let brrtrouter_parts: Vec<_> = brrtrouter_canon.components().collect();
+        let mut common_len = 0;
+        let min_len = base_parts.len().min(brrtrouter_parts.len());
+        for i in 0..min_len {
+            if base_parts[i] == brrtrouter_parts[i] {
+                common_len += 1;
+            } else {
+                break;
+            }
+        }
+        // Build path: go up (base_depth - common_len) times, then down (brrtrouter_parts - common_len)
+        let mut rel_path = PathBuf::new();
+        let up_levels = base_parts.len() - common_len;
+        for _ in 0..up_levels {
+            rel_path.push("..");
+        }
+        for part in brrtrouter_parts.iter().skip(common_len) {
+            rel_path.push(part);
+        }
+        rel_path
+    }
+}
+
 /// Write Cargo.toml with options
 ///
 /// # Arguments
@@ -912,97 +1380,7 @@ pub(crate) fn write_cargo_toml_with_options(
     let (brrtrouter_path, brrtrouter_macros_path) = if use_workspace_deps {
         (String::new(), String::new())
     } else {
-        // Calculate relative path from output directory to BRRTRouter
-        let brrtrouter_base = brrtrouter_root
-            .map(|p| p.to_path_buf())
-            .or_else(|| {
-                // Try to find BRRTRouter by looking for its Cargo.toml in parent directories
-                // This handles the case where we're generating from within BRRTRouter
-                let mut current = base;
-                loop {
-                    let cargo_toml = current.join("Cargo.toml");
-                    if cargo_toml.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&cargo_toml) {
-                            if content.contains("name = \"brrtrouter\"") {
-                                return Some(current.to_path_buf());
-                            }
-                        }
-                    }
-                    match current.parent() {
-                        Some(parent) => current = parent,
-                        None => break,
-                    }
-                }
-                None
-            })
-            .unwrap_or_else(|| {
-                // Default fallback: assume BRRTRouter is at ../.. (petstore case)
-                base.parent()
-                    .and_then(|p| p.parent())
-                    .unwrap_or(base)
-                    .to_path_buf()
-            });
-
-        // Calculate relative path from base to brrtrouter_base
-        // Ensure both paths are absolute for reliable calculation
-        let base_abs = if base.is_absolute() {
-            base.to_path_buf()
-        } else {
-            std::env::current_dir()
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join(base)
-        };
-        let brrtrouter_abs = if brrtrouter_base.is_absolute() {
-            brrtrouter_base // Already a PathBuf, no need to clone
-        } else {
-            std::env::current_dir()
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join(brrtrouter_base)
-        };
-
-        let base_canon = base_abs.canonicalize().unwrap_or(base_abs);
-        let brrtrouter_canon = brrtrouter_abs.canonicalize().unwrap_or(brrtrouter_abs);
-
-        // Calculate relative path from base to brrtrouter_base
-        // base is typically a subdirectory of brrtrouter_base (e.g., examples/pet_store is under BRRTRouter root)
-        let rel_path = if let Ok(rel) = base_canon.strip_prefix(&brrtrouter_canon) {
-            // base is a subdirectory of brrtrouter_base (normal case)
-            // Count depth and build ../.. path
-            let depth = rel.components().count();
-            let mut rel_path = PathBuf::new();
-            for _ in 0..depth {
-                rel_path.push("..");
-            }
-            rel_path
-        } else if let Ok(rel) = brrtrouter_canon.strip_prefix(&base_canon) {
-            // brrtrouter_base is a subdirectory of base (unusual, but handle it)
-            rel.to_path_buf()
-        } else {
-            // No direct relationship - calculate manually
-            // Find common prefix
-            let base_parts: Vec<_> = base_canon.components().collect();
-            let brrtrouter_parts: Vec<_> = brrtrouter_canon.components().collect();
-            let mut common_len = 0;
-            let min_len = base_parts.len().min(brrtrouter_parts.len());
-            for i in 0..min_len {
-                if base_parts[i] == brrtrouter_parts[i] {
-                    common_len += 1;
-                } else {
-                    break;
-                }
-            }
-            // Build path: go up (base_depth - common_len) times, then down (brrtrouter_parts - common_len)
-            let mut rel_path = PathBuf::new();
-            let up_levels = base_parts.len() - common_len;
-            for _ in 0..up_levels {
-                rel_path.push("..");
-            }
-            for part in brrtrouter_parts.iter().skip(common_len) {
-                rel_path.push(part);
-            }
-            rel_path
-        };
-
+        let rel_path = brrtrouter_relative_path(base, brrtrouter_root);
         let rel_path_str = rel_path.to_string_lossy().to_string();
         let macros_path = rel_path.join("brrtrouter_macros");
         let macros_path_str = macros_path.to_string_lossy().to_string();
@@ -1179,12 +1557,59 @@ pub fn write_default_config(dir: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Write `Dockerfile`, `docker-compose.yml` and the `observability/` configs it mounts
+///
+/// # Arguments
+///
+/// * `base` - Project root (next to `Cargo.toml`)
+/// * `name` - Cargo package name (binary and compose service name)
+/// * `options` - Port and healthcheck interval
+///
+/// # Errors
+///
+/// Returns an error if rendering or file writing fails
+pub fn write_docker_files(base: &Path, name: &str, options: &DockerOptions) -> anyhow::Result<()> {
+    let dockerfile = DockerfileTemplateData {
+        name: name.to_string(),
+        port: options.port,
+        healthcheck_interval_secs: options.healthcheck_interval_secs,
+    }
+    .render()?;
+    fs::write(base.join("Dockerfile"), dockerfile)?;
+
+    let compose = DockerComposeTemplateData {
+        name: name.to_string(),
+        port: options.port,
+    }
+    .render()?;
+    fs::write(base.join("docker-compose.yml"), compose)?;
+
+    let observability = base.join("observability");
+    fs::create_dir_all(&observability)?;
+    let prometheus = PrometheusConfigTemplateData {
+        name: name.to_string(),
+        port: options.port,
+    }
+    .render()?;
+    fs::write(observability.join("prometheus.yml"), prometheus)?;
+    fs::write(
+        observability.join("grafana-datasources.yml"),
+        GrafanaDatasourcesTemplate.render()?,
+    )?;
+    println!("✅ Wrote Dockerfile, docker-compose.yml and observability/ configs");
+    Ok(())
+}
+
 /// Render brrtrouter-dependencies.toml starter content from the Askama template.
 pub fn render_brrtrouter_dependencies_starter(
     conditional_rust_decimal: bool,
+    conditional_chrono: bool,
+    conditional_uuid: bool,
 ) -> askama::Result<String> {
     BrrtrouterDependenciesTomlTemplate {
         conditional_rust_decimal,
+        conditional_chrono,
+        conditional_uuid,
     }
     .render()
 }
@@ -1194,8 +1619,14 @@ pub fn render_brrtrouter_dependencies_starter(
 pub fn write_brrtrouter_dependencies_starter(
     path: &Path,
     conditional_rust_decimal: bool,
+    conditional_chrono: bool,
+    conditional_uuid: bool,
 ) -> anyhow::Result<()> {
-    let content = render_brrtrouter_dependencies_starter(conditional_rust_decimal)?;
+    let content = render_brrtrouter_dependencies_starter(
+        conditional_rust_decimal,
+        conditional_chrono,
+        conditional_uuid,
+    )?;
     crate::generator::dependencies_config::write_dependencies_config_if_missing(path, &content)?;
     Ok(())
 }
@@ -1291,6 +1722,7 @@ pub fn write_impl_controller_stub(params: ImplControllerStubParams) -> anyhow::R
                 original_name: field.original_name.clone(),
                 ty: field.ty.clone(),
                 optional: field.optional,
+                nullable: field.nullable,
                 value,
             }
         })
@@ -1308,6 +1740,7 @@ pub fn write_impl_controller_stub(params: ImplControllerStubParams) -> anyhow::R
                     original_name: "items".to_string(),
                     ty: params.res_fields[0].ty.clone(),
                     optional: false,
+                    nullable: false,
                     value: String::new(),
                 };
                 rust_literal_for_example(&items_field, ex)
@@ -1499,6 +1932,7 @@ pub fn sync_impl_stub_response(
                 original_name: field.original_name.clone(),
                 ty: field.ty.clone(),
                 optional: field.optional,
+                nullable: field.nullable,
                 value,
             }
         })
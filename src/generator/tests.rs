@@ -293,6 +293,7 @@ fn test_parameter_to_field() {
         schema: Some(json!({"type": "string"})),
         style: None,
         explode: None,
+        deprecated: false,
     };
 
     let field = parameter_to_field(&param);
@@ -312,6 +313,7 @@ fn test_parameter_to_field_optional() {
         schema: Some(json!({"type": "integer"})),
         style: None,
         explode: None,
+        deprecated: false,
     };
 
     let field = parameter_to_field(&param);
@@ -331,6 +333,7 @@ fn test_parameter_to_field_no_schema() {
         schema: None,
         style: None,
         explode: None,
+        deprecated: false,
     };
 
     let field = parameter_to_field(&param);
@@ -346,6 +349,7 @@ fn test_rust_literal_for_example_string() {
         original_name: "name".to_string(),
         ty: "String".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -361,6 +365,7 @@ fn test_rust_literal_for_example_optional_string() {
         original_name: "nickname".to_string(),
         ty: "String".to_string(),
         optional: true,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -376,6 +381,7 @@ fn test_rust_literal_for_example_number() {
         original_name: "age".to_string(),
         ty: "i32".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -391,6 +397,7 @@ fn test_rust_literal_for_example_boolean() {
         original_name: "active".to_string(),
         ty: "bool".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -406,6 +413,7 @@ fn test_rust_literal_for_example_array() {
         original_name: "tags".to_string(),
         ty: "Vec<String>".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -424,6 +432,7 @@ fn test_rust_literal_for_example_array_numbers() {
         original_name: "scores".to_string(),
         ty: "Vec<i32>".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -439,6 +448,7 @@ fn test_rust_literal_for_example_json_value() {
         original_name: "metadata".to_string(),
         ty: "serde_json::Value".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -454,6 +464,7 @@ fn test_rust_literal_for_example_named_type() {
         original_name: "user".to_string(),
         ty: "User".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -549,6 +560,75 @@ fn test_write_types_rs_preserves_optional_component_fields() {
     fs::remove_dir_all(dir).unwrap();
 }
 
+#[test]
+fn test_write_types_rs_serializes_nullable_none_as_null() {
+    let schema = json!({
+        "type": "object",
+        "required": ["age"],
+        "properties": {
+            "age": { "type": ["integer", "null"] },
+            "breed": { "type": "string", "nullable": true },
+            "name": { "type": "string" }
+        }
+    });
+    let mut types = std::collections::HashMap::new();
+    process_schema_type("pet", &schema, &mut types);
+    let dir = temp_dir();
+
+    write_types_rs(&dir, &types).unwrap();
+
+    let generated = fs::read_to_string(dir.join("types.rs")).unwrap();
+    let compact: String = generated.split_whitespace().collect::<Vec<_>>().join(" ");
+    assert!(compact.contains("pub age: Option<i32>,"), "{generated}");
+    assert!(
+        compact.contains("pub breed: Option<String>,"),
+        "{generated}"
+    );
+    assert!(
+        compact.contains(
+            "#[serde(skip_serializing_if = \"Option::is_none\")] pub name: Option<String>,"
+        ),
+        "{generated}"
+    );
+    assert_eq!(
+        compact.matches("skip_serializing_if").count(),
+        1,
+        "{generated}"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Shape `handler_types.rs.txt` renders for the schema of
+/// [`test_write_types_rs_serializes_nullable_none_as_null`].
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct NullablePet {
+    age: Option<i32>,
+    breed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[test]
+fn test_nullable_fields_round_trip_present_null_and_absent() {
+    let round_trip = |wire: serde_json::Value| {
+        let pet: NullablePet = serde_json::from_value(wire).unwrap();
+        (serde_json::to_value(&pet).unwrap(), pet)
+    };
+
+    let (wire, pet) = round_trip(json!({"age": 3, "breed": "collie", "name": "Rex"}));
+    assert_eq!(pet.age, Some(3));
+    assert_eq!(wire, json!({"age": 3, "breed": "collie", "name": "Rex"}));
+
+    let (wire, pet) = round_trip(json!({"age": null, "breed": null, "name": null}));
+    assert_eq!(pet.age, None);
+    assert_eq!(wire, json!({"age": null, "breed": null}));
+
+    // Absent nullable fields come back as `null`, which the schema also accepts.
+    let (wire, pet) = round_trip(json!({}));
+    assert_eq!(pet.breed, None);
+    assert_eq!(wire, json!({"age": null, "breed": null}));
+}
+
 #[test]
 fn test_process_schema_type_deduplicates_sanitized_enum_variants() {
     let schema = json!({"type": "string", "enum": ["FOO-BAR", "foo_bar"]});
@@ -597,6 +677,7 @@ fn test_field_def_construction() {
         original_name: "test_field".to_string(),
         ty: "String".to_string(),
         optional: true,
+        nullable: false,
         value: "default_value".to_string(),
     };
 
@@ -614,6 +695,7 @@ fn test_type_definition_construction() {
             original_name: "id".to_string(),
             ty: "i32".to_string(),
             optional: false,
+            nullable: false,
             value: "0".to_string(),
         },
         FieldDef {
@@ -621,6 +703,7 @@ fn test_type_definition_construction() {
             original_name: "name".to_string(),
             ty: "String".to_string(),
             optional: false,
+            nullable: false,
             value: "String::new()".to_string(),
         },
     ];
@@ -766,6 +849,141 @@ fn test_schema_to_type_decimal_array() {
     );
 }
 
+#[test]
+fn test_schema_to_type_bigint_maps_to_i128() {
+    // Like decimal, bigint needs no extra crate, so it applies without --rich-types.
+    for (schema, expected) in [
+        (json!({"type": "integer", "format": "bigint"}), "i128"),
+        (json!({"type": "integer", "format": "int128"}), "i128"),
+        (
+            json!({"type": "array", "items": {"type": "integer", "format": "bigint"}}),
+            "Vec<i128>",
+        ),
+    ] {
+        assert_eq!(schema_to_type(&schema), expected, "{schema}");
+    }
+    let field = FieldDef {
+        name: "id".to_string(),
+        original_name: "id".to_string(),
+        ty: "i128".to_string(),
+        optional: false,
+        nullable: false,
+        value: String::new(),
+    };
+    let example: serde_json::Value = serde_json::from_str("12345678901234567890").unwrap();
+    assert_eq!(
+        rust_literal_for_example(&field, &example),
+        "12345678901234567890"
+    );
+}
+
+const RICH: TypeOptions = TypeOptions { rich_types: true };
+
+#[test]
+fn test_schema_to_type_formats_default_mapping_unchanged() {
+    // Without --rich-types, formats do not change the type (no extra dependencies).
+    for (schema, expected) in [
+        (json!({"type": "string", "format": "date-time"}), "String"),
+        (json!({"type": "string", "format": "uuid"}), "String"),
+        (json!({"type": "string", "format": "byte"}), "String"),
+        (json!({"type": "integer", "format": "int64"}), "i32"),
+    ] {
+        assert_eq!(schema_to_type(&schema), expected, "{schema}");
+    }
+}
+
+#[test]
+fn test_schema_to_type_rich_types() {
+    for (schema, expected) in [
+        (
+            json!({"type": "string", "format": "date-time"}),
+            "chrono::DateTime<chrono::Utc>",
+        ),
+        (
+            json!({"type": "string", "format": "date"}),
+            "chrono::NaiveDate",
+        ),
+        (json!({"type": "string", "format": "uuid"}), "uuid::Uuid"),
+        (
+            json!({"type": "string", "format": "byte"}),
+            "brrtrouter::typed::Base64Bytes",
+        ),
+        (json!({"type": "string", "format": "email"}), "String"),
+        (json!({"type": "integer", "format": "int64"}), "i64"),
+        (json!({"type": "integer", "format": "int32"}), "i32"),
+        (json!({"type": "integer"}), "i32"),
+        (
+            json!({"type": "number", "format": "decimal"}),
+            "rust_decimal::Decimal",
+        ),
+        (
+            json!({"type": "array", "items": {"type": "string", "format": "uuid"}}),
+            "Vec<uuid::Uuid>",
+        ),
+    ] {
+        assert_eq!(
+            schema_to_type_with_options(&schema, RICH),
+            expected,
+            "{schema}"
+        );
+    }
+}
+
+#[test]
+fn test_extract_fields_rich_types() {
+    let schema = json!({
+        "type": "object",
+        "required": ["id", "created_at"],
+        "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "created_at": {"type": "string", "format": "date-time"},
+            "birthday": {"type": "string", "format": "date"},
+            "count": {"type": "integer", "format": "int64"},
+            "avatar": {"type": "string", "format": "byte"}
+        }
+    });
+    let fields = extract_fields_with_options(&schema, RICH);
+    let ty = |name: &str| {
+        let f = fields.iter().find(|f| f.name == name).unwrap();
+        (f.ty.as_str(), f.optional)
+    };
+    assert_eq!(ty("id"), ("uuid::Uuid", false));
+    assert_eq!(ty("created_at"), ("chrono::DateTime<chrono::Utc>", false));
+    assert_eq!(ty("birthday"), ("chrono::NaiveDate", true));
+    assert_eq!(ty("count"), ("i64", true));
+    assert_eq!(ty("avatar"), ("brrtrouter::typed::Base64Bytes", true));
+
+    // Default options keep the plain mapping for the same schema.
+    let plain = extract_fields(&schema);
+    assert!(plain.iter().all(|f| f.ty == "String" || f.ty == "i32"));
+}
+
+#[test]
+fn test_rust_literal_for_rich_string_example_parses() {
+    let field = FieldDef {
+        name: "created_at".to_string(),
+        original_name: "created_at".to_string(),
+        ty: "chrono::DateTime<chrono::Utc>".to_string(),
+        optional: true,
+        nullable: false,
+        value: String::new(),
+    };
+    assert_eq!(
+        rust_literal_for_example(&field, &json!("2024-05-01T12:00:00Z")),
+        "Some(\"2024-05-01T12:00:00Z\".parse().unwrap_or_default())"
+    );
+}
+
+#[test]
+fn test_dependencies_starter_lists_rich_type_crates() {
+    let content = render_brrtrouter_dependencies_starter(false, true, true).unwrap();
+    assert!(content.contains("chrono = { detect = \"chrono::\""));
+    assert!(content.contains("uuid = { detect = \"uuid::Uuid\""));
+    assert!(!content.contains("rust_decimal"));
+    let config: DependenciesConfig = toml::from_str(&content).unwrap();
+    assert!(config.conditional.contains_key("chrono"));
+}
+
 #[test]
 fn test_extract_fields_with_money_usd() {
     // Test extracting fields with money type (API uses Decimal for money; Money has lifetime issues with serde)
@@ -8,7 +8,8 @@
 //!
 //! Hot reload watches the OpenAPI specification file for changes and:
 //! - Detects file modifications using filesystem watchers
-//! - Reloads and parses the updated specification
+//! - Reloads and parses the updated specification, dropping routes of disabled `x-feature`s
+//!   (`BRRTR_FEATURES`, read again on every reload)
 //! - Rebuilds the router with new routes
 //! - Updates the dispatcher with new handler mappings
 //! - Calls custom reload hooks for application-specific updates
@@ -153,6 +154,10 @@ where
 
                     match spec::load_spec(spec_path_str) {
                         Ok((routes, _spec)) => {
+                            let routes = spec::enabled_routes(
+                                routes,
+                                &crate::runtime_config::enabled_features_from_env(),
+                            );
                             let routes_count = routes.len();
                             let route_paths: Vec<String> = routes
                                 .iter()
@@ -161,7 +166,9 @@ where
 
                             // Build new router and publish atomically (PRD Phase 1).
                             // ArcSwap::store is infallible — no lock poisoning.
-                            let new_router = Router::new(routes.clone());
+                            // Keep the running router's path decoding policy.
+                            let new_router = Router::new(routes.clone())
+                                .with_path_decoding(router.load().path_decoding());
                             router.store(Arc::new(new_router));
 
                             // Update validator cache with new spec version and hash
@@ -1,12 +1,15 @@
 //! Bounded HTTP fetches through `may_minihttp::client` for both HTTP and rustls-backed HTTPS.
 
-use std::io::Read;
+use std::io::{Read, Write};
+use std::sync::Arc;
 use std::time::Duration;
 
 use http_legacy::{Method, Uri};
 use may_minihttp::client::HttpClient;
 use url::Url;
 
+use super::OutboundTls;
+
 /// Options for outbound GET requests from security providers.
 #[derive(Debug, Clone)]
 pub struct HttpFetchOptions {
@@ -16,6 +19,8 @@ pub struct HttpFetchOptions {
     pub max_body_bytes: usize,
     /// Extra request headers (name, value).
     pub extra_headers: Vec<(String, String)>,
+    /// Trust for `https://` URLs; `None` uses the system trust store.
+    pub tls: Option<Arc<OutboundTls>>,
 }
 
 impl Default for HttpFetchOptions {
@@ -24,6 +29,7 @@ impl Default for HttpFetchOptions {
             timeout: Duration::from_millis(500),
             max_body_bytes: 256 * 1024,
             extra_headers: Vec::new(),
+            tls: None,
         }
     }
 }
@@ -89,9 +95,10 @@ pub fn fetch_get_full(
     options: &HttpFetchOptions,
 ) -> Result<HttpGetResponse, HttpFetchError> {
     let parsed = Url::parse(url).map_err(|e| HttpFetchError::InvalidUrl(e.to_string()))?;
-    match parsed.scheme() {
-        "http" | "https" => fetch_get_full_via_client(&parsed, options),
-        other => Err(HttpFetchError::InvalidUrl(format!(
+    match (parsed.scheme(), options.tls.as_deref()) {
+        ("https", Some(tls)) => fetch_via_custom_tls(&parsed, Method::GET, None, options, tls),
+        ("http" | "https", _) => fetch_get_full_via_client(&parsed, options),
+        (other, _) => Err(HttpFetchError::InvalidUrl(format!(
             "unsupported scheme: {other}"
         ))),
     }
@@ -139,9 +146,13 @@ pub fn fetch_post(
     options: &HttpFetchOptions,
 ) -> Result<(u16, Vec<u8>), HttpFetchError> {
     let parsed = Url::parse(url).map_err(|e| HttpFetchError::InvalidUrl(e.to_string()))?;
-    match parsed.scheme() {
-        "http" | "https" => fetch_post_via_client(&parsed, body, options),
-        other => Err(HttpFetchError::InvalidUrl(format!(
+    match (parsed.scheme(), options.tls.as_deref()) {
+        ("https", Some(tls)) => {
+            fetch_via_custom_tls(&parsed, Method::POST, Some(body), options, tls)
+                .map(|r| (r.status, r.body))
+        }
+        ("http" | "https", _) => fetch_post_via_client(&parsed, body, options),
+        (other, _) => Err(HttpFetchError::InvalidUrl(format!(
             "unsupported scheme: {other}"
         ))),
     }
@@ -226,6 +237,165 @@ fn fetch_get_full_via_client(
     })
 }
 
+/// Upper bound on response status line + headers read over a custom-TLS connection.
+const MAX_TLS_RESPONSE_HEAD_BYTES: usize = 16 * 1024;
+
+/// One request over a rustls connection built from `tls` (the `may_minihttp` client only
+/// knows the system trust store). Sends `Connection: close` and does not follow redirects.
+fn fetch_via_custom_tls(
+    url: &Url,
+    method: Method,
+    body: Option<&[u8]>,
+    options: &HttpFetchOptions,
+    tls: &OutboundTls,
+) -> Result<HttpGetResponse, HttpFetchError> {
+    let server_name = match url.host() {
+        Some(url::Host::Domain(host)) => rustls::pki_types::ServerName::try_from(host.to_string())
+            .map_err(|e| HttpFetchError::InvalidUrl(e.to_string()))?,
+        Some(url::Host::Ipv4(ip)) => rustls::pki_types::ServerName::from(std::net::IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => rustls::pki_types::ServerName::from(std::net::IpAddr::V6(ip)),
+        None => return Err(HttpFetchError::InvalidUrl("missing host".to_string())),
+    };
+    let addrs = url
+        .socket_addrs(|| Some(443))
+        .map_err(|e| HttpFetchError::Connect(e.to_string()))?;
+    let tcp = may::net::TcpStream::connect(&addrs[..])
+        .map_err(|e| HttpFetchError::Connect(e.to_string()))?;
+    tcp.set_read_timeout(Some(options.timeout))
+        .and_then(|()| tcp.set_write_timeout(Some(options.timeout)))
+        .map_err(|e| HttpFetchError::Connect(e.to_string()))?;
+    let connection = rustls::ClientConnection::new(tls.client_config(), server_name)
+        .map_err(|e| HttpFetchError::Tls(e.to_string()))?;
+    let mut stream = rustls::StreamOwned::new(connection, tcp);
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let mut head = format!(
+        "{method} {} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n",
+        request_uri_for_may_minihttp(url)?
+    );
+    // Same validation as `apply_extra_headers`: no CR/LF can reach the request head.
+    for (name, value) in &options.extra_headers {
+        if http_legacy::HeaderName::try_from(name.as_str()).is_ok()
+            && http_legacy::HeaderValue::from_str(value).is_ok()
+        {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+    }
+    if let Some(body) = body {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    head.push_str("\r\n");
+    // The handshake runs on first write, so certificate rejections surface here.
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|()| stream.write_all(body.unwrap_or_default()))
+        .and_then(|()| stream.flush())
+        .map_err(|e| HttpFetchError::Tls(e.to_string()))?;
+
+    let raw = read_until_close(
+        &mut stream,
+        MAX_TLS_RESPONSE_HEAD_BYTES + options.max_body_bytes,
+    )?;
+    parse_http_response(&raw, options.max_body_bytes)
+}
+
+/// Read until the peer closes. A close without TLS `close_notify` still ends the response.
+fn read_until_close(reader: &mut impl Read, limit: usize) -> Result<Vec<u8>, HttpFetchError> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                buf.extend_from_slice(&chunk[..n]);
+                if buf.len() > limit {
+                    return Err(HttpFetchError::BodyTooLarge);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && !buf.is_empty() => break,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(HttpFetchError::Read(e.to_string())),
+        }
+    }
+    Ok(buf)
+}
+
+/// Parse a complete HTTP/1.1 response (status, `Location`, `Content-Length` or chunked body).
+fn parse_http_response(raw: &[u8], max_body: usize) -> Result<HttpGetResponse, HttpFetchError> {
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| HttpFetchError::Response("incomplete response head".to_string()))?;
+    let head =
+        std::str::from_utf8(&raw[..split]).map_err(|e| HttpFetchError::Response(e.to_string()))?;
+    let body = &raw[split + 4..];
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| HttpFetchError::Response("malformed status line".to_string()))?;
+    let mut location = None;
+    let mut content_length = None;
+    let mut chunked = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("location") {
+            location = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse::<usize>().ok();
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+        }
+    }
+    let body = if chunked {
+        decode_chunked(body)?
+    } else if let Some(len) = content_length {
+        body.get(..len)
+            .ok_or_else(|| {
+                HttpFetchError::Read("response body shorter than Content-Length".to_string())
+            })?
+            .to_vec()
+    } else {
+        body.to_vec()
+    };
+    if body.len() > max_body {
+        return Err(HttpFetchError::BodyTooLarge);
+    }
+    Ok(HttpGetResponse {
+        status,
+        location,
+        body,
+    })
+}
+
+/// Decode a `Transfer-Encoding: chunked` body (trailers are ignored).
+fn decode_chunked(mut input: &[u8]) -> Result<Vec<u8>, HttpFetchError> {
+    let malformed = || HttpFetchError::Read("malformed chunked body".to_string());
+    let mut out = Vec::new();
+    loop {
+        let line_end = input
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(malformed)?;
+        let size_line = std::str::from_utf8(&input[..line_end]).map_err(|_| malformed())?;
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16).map_err(|_| malformed())?;
+        input = &input[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        out.extend_from_slice(input.get(..size).ok_or_else(malformed)?);
+        input = input.get(size + 2..).ok_or_else(malformed)?;
+    }
+}
+
 fn read_bounded_body(reader: &mut impl Read, max_body: usize) -> Result<Vec<u8>, HttpFetchError> {
     let mut buf = Vec::new();
     reader
@@ -243,6 +413,24 @@ fn read_bounded_body(reader: &mut impl Read, max_body: usize) -> Result<Vec<u8>,
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_http_response_handles_length_and_chunked_bodies() {
+        let raw = b"HTTP/1.1 302 Found\r\nLocation: /next\r\nContent-Length: 2\r\n\r\nokEXTRA";
+        let resp = parse_http_response(raw, 1024).unwrap();
+        assert_eq!(resp.status, 302);
+        assert_eq!(resp.location.as_deref(), Some("/next"));
+        assert_eq!(resp.body, b"ok");
+
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n2;x=y\r\n:1\r\n1\r\n}\r\n0\r\n\r\n";
+        let resp = parse_http_response(raw, 1024).unwrap();
+        assert_eq!(resp.body, br#"{"a":1}"#);
+
+        assert_eq!(
+            parse_http_response(b"HTTP/1.1 200 OK\r\n\r\n12345", 3).unwrap_err(),
+            HttpFetchError::BodyTooLarge
+        );
+    }
+
     #[test]
     fn read_bounded_body_respects_limit() {
         let data = b"12345";
@@ -2,10 +2,12 @@
 //!
 //! Uses [`may_minihttp::client::HttpClient`] for HTTP and rustls-backed HTTPS.
 //! Replaces `reqwest::blocking` in the request hot path so fetches run on `may::net::TcpStream`
-//! without a separate tokio runtime.
+//! without a separate tokio runtime. [`OutboundTls`] swaps the system trust store for a private
+//! CA bundle (and optional SPKI pins) on `https://` fetches.
 
 mod fetch;
 mod proxy;
+mod tls;
 
 pub use fetch::{
     fetch_get, fetch_get_full, fetch_get_text_with_retry, fetch_post, HttpFetchError,
@@ -15,3 +17,4 @@ pub use proxy::{
     client_pool_key, downstream_host, downstream_http_port, proxy_untyped, resolve_path_template,
     skip_forward_request_header, skip_forward_response_header, ProxyError,
 };
+pub use tls::{OutboundTls, OutboundTlsBuilder, OutboundTlsError};
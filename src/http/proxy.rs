@@ -14,7 +14,7 @@ use http_legacy::{Method, Uri};
 use may_minihttp::client::HttpClient;
 use serde_json::Value;
 
-use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderVec};
+use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderLookup, HeaderVec};
 use crate::router::ParamVec;
 
 const DEFAULT_DOWNSTREAM_PORT: u16 = 8080;
@@ -191,7 +191,10 @@ fn proxy_untyped_inner(
     downstream_service: &str,
     path_template: &str,
 ) -> Result<HandlerResponse, ProxyError> {
-    let resolved_path = resolve_path_template(path_template, &req.path_params, &req.query_params);
+    // Raw path params: a decoded `a/b` must reach the downstream as `a%2Fb`, not as two
+    // segments.
+    let resolved_path =
+        resolve_path_template(path_template, req.raw_path_params(), &req.query_params);
     let host = downstream_host(downstream_service);
     let port = downstream_http_port();
 
@@ -226,6 +229,16 @@ fn proxy_untyped_inner(
         }
     }
 
+    // Claim-derived headers (`x-forward-claims`) override any inbound header of the same name.
+    for (hk, hv) in &req.downstream_headers {
+        if let (Ok(hname), Ok(hval)) = (
+            http_legacy::header::HeaderName::from_bytes(hk.as_bytes()),
+            http_legacy::header::HeaderValue::from_str(hv.as_str()),
+        ) {
+            proxy_req.headers_mut().insert(hname, hval);
+        }
+    }
+
     if proxy_req
         .headers()
         .get(http_legacy::header::ACCEPT)
@@ -267,10 +280,7 @@ fn proxy_untyped_inner(
             out_headers.push((Arc::from(name.as_str()), s.to_string()));
         }
     }
-    if !out_headers
-        .iter()
-        .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
-    {
+    if out_headers.get("content-type").is_none() {
         out_headers.push((
             Arc::from("content-type"),
             content_type.unwrap_or("application/json").to_string(),
@@ -307,6 +317,12 @@ mod tests {
             jwt_claims: None,
             reply_tx: tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         }
     }
 
@@ -0,0 +1,362 @@
+//! Custom trust for outbound HTTPS: a private CA bundle and optional SPKI pins.
+//!
+//! Security providers fetch JWKS documents and verify API keys over HTTPS using the system
+//! trust store by default. In locked-down environments those endpoints are signed by an
+//! internal CA instead; [`OutboundTls`] carries a rustls client configuration that trusts
+//! exactly that bundle and, optionally, only certificates whose public key matches a pin.
+//!
+//! ```rust,no_run
+//! use brrtrouter::http::OutboundTls;
+//! use brrtrouter::security::JwksBearerProvider;
+//!
+//! let tls = OutboundTls::builder()
+//!     .ca_bundle_file("/etc/ssl/internal-ca.pem")
+//!     .pin_spki_sha256("sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")
+//!     .build()
+//!     .expect("invalid outbound TLS configuration");
+//! let provider = JwksBearerProvider::new("https://auth.internal/.well-known/jwks.json").tls(tls);
+//! ```
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+
+/// Errors building an [`OutboundTls`]; all are configuration mistakes reported at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutboundTlsError {
+    /// No CA bundle was configured; pins alone cannot anchor trust.
+    MissingCaBundle,
+    /// The CA bundle file could not be read.
+    ReadCaBundle { path: PathBuf, message: String },
+    /// The CA bundle is not valid PEM.
+    InvalidPem(String),
+    /// The CA bundle holds no certificates.
+    EmptyCaBundle,
+    /// A certificate in the bundle cannot be used as a trust anchor.
+    InvalidCaCertificate(String),
+    /// A pin is not a base64 SHA-256 digest (optionally prefixed with `sha256/`).
+    InvalidPin(String),
+    /// rustls rejected the resulting configuration.
+    Config(String),
+}
+
+impl std::fmt::Display for OutboundTlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingCaBundle => write!(f, "no CA bundle configured"),
+            Self::ReadCaBundle { path, message } => {
+                write!(f, "cannot read CA bundle {}: {message}", path.display())
+            }
+            Self::InvalidPem(msg) => write!(f, "invalid PEM in CA bundle: {msg}"),
+            Self::EmptyCaBundle => write!(f, "CA bundle contains no certificates"),
+            Self::InvalidCaCertificate(msg) => write!(f, "invalid CA certificate: {msg}"),
+            Self::InvalidPin(pin) => write!(
+                f,
+                "invalid SPKI pin {pin:?}: expected base64 SHA-256, optionally prefixed with sha256/"
+            ),
+            Self::Config(msg) => write!(f, "TLS configuration: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for OutboundTlsError {}
+
+/// Client TLS configuration for outbound provider HTTPS.
+///
+/// Attach it to a provider (`JwksBearerProvider::tls`, `RemoteApiKeyProvider::tls`) or set
+/// [`super::HttpFetchOptions::tls`] directly. Cheap to clone.
+#[derive(Debug, Clone)]
+pub struct OutboundTls {
+    config: Arc<ClientConfig>,
+}
+
+impl OutboundTls {
+    /// Start building from a CA bundle and optional SPKI pins.
+    pub fn builder() -> OutboundTlsBuilder {
+        OutboundTlsBuilder::default()
+    }
+
+    /// Use a rustls client configuration built elsewhere (custom verifier, client certs, ...).
+    pub fn from_client_config(config: Arc<ClientConfig>) -> Self {
+        Self { config }
+    }
+
+    /// The rustls client configuration used for each connection.
+    pub fn client_config(&self) -> Arc<ClientConfig> {
+        self.config.clone()
+    }
+}
+
+/// Builder for [`OutboundTls`]; inputs are only validated by [`OutboundTlsBuilder::build`].
+#[derive(Debug, Clone, Default)]
+pub struct OutboundTlsBuilder {
+    ca_pem: Vec<u8>,
+    ca_files: Vec<PathBuf>,
+    pins: Vec<String>,
+}
+
+impl OutboundTlsBuilder {
+    /// Trust the CA certificates in a PEM bundle. May be called more than once.
+    pub fn ca_bundle_pem(mut self, pem: impl AsRef<[u8]>) -> Self {
+        self.ca_pem.extend_from_slice(pem.as_ref());
+        self.ca_pem.push(b'\n');
+        self
+    }
+
+    /// Trust the CA certificates in a PEM file, read by [`OutboundTlsBuilder::build`].
+    pub fn ca_bundle_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_files.push(path.into());
+        self
+    }
+
+    /// Additionally require a certificate in the server's chain whose SubjectPublicKeyInfo
+    /// hashes to this base64 SHA-256 digest (`sha256/...` prefix optional). With several
+    /// pins, any one matching is enough.
+    pub fn pin_spki_sha256(mut self, pin: impl Into<String>) -> Self {
+        self.pins.push(pin.into());
+        self
+    }
+
+    /// Validate the bundle and pins and build the client configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutboundTlsError`] when no CA is configured, a file cannot be read, the PEM
+    /// holds no usable certificate, or a pin is malformed.
+    pub fn build(self) -> Result<OutboundTls, OutboundTlsError> {
+        let mut pem = self.ca_pem;
+        for path in &self.ca_files {
+            let bytes = std::fs::read(path).map_err(|e| OutboundTlsError::ReadCaBundle {
+                path: path.clone(),
+                message: e.to_string(),
+            })?;
+            pem.extend_from_slice(&bytes);
+            pem.push(b'\n');
+        }
+        if pem.iter().all(u8::is_ascii_whitespace) {
+            return Err(OutboundTlsError::MissingCaBundle);
+        }
+
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_slice_iter(&pem) {
+            let cert = cert.map_err(|e| OutboundTlsError::InvalidPem(e.to_string()))?;
+            roots
+                .add(cert)
+                .map_err(|e| OutboundTlsError::InvalidCaCertificate(e.to_string()))?;
+        }
+        if roots.is_empty() {
+            return Err(OutboundTlsError::EmptyCaBundle);
+        }
+        let pins = self
+            .pins
+            .iter()
+            .map(|pin| parse_pin(pin).ok_or_else(|| OutboundTlsError::InvalidPin(pin.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| OutboundTlsError::Config(e.to_string()))?;
+        let config = if pins.is_empty() {
+            builder.with_root_certificates(roots).with_no_client_auth()
+        } else {
+            let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| OutboundTlsError::Config(e.to_string()))?;
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedVerifier { inner, pins }))
+                .with_no_client_auth()
+        };
+        Ok(OutboundTls {
+            config: Arc::new(config),
+        })
+    }
+}
+
+/// Decode `sha256/<base64>` or `<base64>` into a 32-byte digest.
+fn parse_pin(pin: &str) -> Option<[u8; 32]> {
+    let encoded = pin.trim();
+    let encoded = encoded.strip_prefix("sha256/").unwrap_or(encoded);
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// Chain validation against the configured roots, then an SPKI pin check.
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .filter_map(|cert| subject_public_key_info(cert.as_ref()))
+            .any(|spki| {
+                let digest = Sha256::digest(spki);
+                self.pins.iter().any(|pin| pin[..] == digest[..])
+            });
+        if pinned {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Split one DER element off `input`: `(element, rest)`.
+fn der_split(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    let first = usize::from(*input.get(1)?);
+    let (header, len) = if first < 0x80 {
+        (2, first)
+    } else {
+        let count = first & 0x7f;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = input.get(2..2 + count)?;
+        let len = bytes
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        (2 + count, len)
+    };
+    let end = header.checked_add(len)?;
+    (end <= input.len()).then(|| input.split_at(end))
+}
+
+/// The contents of a DER SEQUENCE element.
+fn der_sequence_contents(element: &[u8]) -> Option<&[u8]> {
+    if element.first() != Some(&0x30) {
+        return None;
+    }
+    let header = if element.get(1)? & 0x80 == 0 {
+        2
+    } else {
+        2 + usize::from(element.get(1)? & 0x7f)
+    };
+    element.get(header..)
+}
+
+/// The DER `SubjectPublicKeyInfo` of an X.509 certificate (the bytes SPKI pins hash).
+fn subject_public_key_info(cert: &[u8]) -> Option<&[u8]> {
+    let (certificate, _) = der_split(cert)?;
+    let (tbs, _) = der_split(der_sequence_contents(certificate)?)?;
+    let mut rest = der_sequence_contents(tbs)?;
+    // `version` is an optional `[0] EXPLICIT` element.
+    if rest.first() == Some(&0xa0) {
+        rest = der_split(rest)?.1;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        rest = der_split(rest)?.1;
+    }
+    let (spki, _) = der_split(rest)?;
+    (spki.first() == Some(&0x30)).then_some(spki)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_requires_a_ca_bundle() {
+        let err = OutboundTls::builder()
+            .pin_spki_sha256("sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, OutboundTlsError::MissingCaBundle);
+    }
+
+    #[test]
+    fn build_rejects_pem_without_certificates() {
+        let err = OutboundTls::builder()
+            .ca_bundle_pem("not a certificate")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, OutboundTlsError::EmptyCaBundle);
+    }
+
+    #[test]
+    fn build_reports_unreadable_bundle_files() {
+        let err = OutboundTls::builder()
+            .ca_bundle_file("/nonexistent/ca.pem")
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(err, OutboundTlsError::ReadCaBundle { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parse_pin_accepts_prefixed_and_bare_base64() {
+        let bare = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+        assert!(parse_pin(bare).is_some());
+        assert_eq!(parse_pin(&format!("sha256/{bare}")), parse_pin(bare));
+        assert!(parse_pin("sha256/dG9vIHNob3J0").is_none());
+        assert!(parse_pin("sha256/!!").is_none());
+    }
+
+    #[test]
+    fn der_split_handles_long_form_lengths() {
+        let mut element = vec![0x04, 0x81, 0x80];
+        element.extend_from_slice(&[0u8; 0x80]);
+        element.push(0xff);
+        let (head, rest) = der_split(&element).unwrap();
+        assert_eq!(head.len(), 3 + 0x80);
+        assert_eq!(rest, [0xff]);
+        assert!(der_split(&[0x04, 0x05, 0x00]).is_none());
+    }
+}
@@ -73,6 +73,7 @@
 //! - **[`hot_reload`]** - Live reloading of OpenAPI specifications
 //! - **[`sse`]** - Server-Sent Events support
 //! - **[`static_files`]** - Static file serving utilities
+//! - **`testing`** (feature `testing`) - Signed test JWTs and a mock JWKS endpoint
 //!
 //! ### Code Generation Flow
 //!
@@ -254,18 +255,20 @@
 //! ## Quick Start
 //!
 //! ```ignore
-//! // Example: Basic server setup (API has evolved - see examples/pet_store for current approach)
-//! use brrtrouter::{load_spec, router::Router, server::AppService};
+//! // Example: Basic server setup (see examples/pet_store for a full service)
+//! use brrtrouter::{dispatcher::Dispatcher, load_spec_full, router::Router, server::AppService};
 //!
 //! // Load your OpenAPI specification
-//! let spec = load_spec("openapi.yaml").expect("Failed to load spec");
-//!
-//! // Create a router from the spec
-//! let router = Router::from_spec(&spec);
-//!
-//! // Create and start the service
-//! let service = AppService::new(router, spec);
-//! // service.start("0.0.0.0:8080");
+//! let (routes, schemes, _slug) = load_spec_full("openapi.yaml").expect("Failed to load spec");
+//!
+//! // Create the service from a router over the spec's routes and a dispatcher
+//! let service = AppService::builder()
+//!     .router(Router::new(routes))
+//!     .dispatcher(Dispatcher::new())
+//!     .security_schemes(schemes)
+//!     .spec_path("openapi.yaml")
+//!     .build()?;
+//! // HttpServer(service).start("0.0.0.0:8080")?;
 //! ```
 //!
 //! ## Features
@@ -651,7 +654,8 @@
 //! **Handler Metrics:**
 //! - `handler_invocations_total` - Number of times each handler was called
 //! - `handler_errors_total` - Handler errors and panics by handler name
-//! - `handler_duration_seconds` - Handler execution time
+//! - `brrtrouter_handler_duration_seconds{path}` - Handler execution time histogram per route template
+//! - `brrtrouter_slow_requests_total{path}` - Requests slower than `BRRTR_SLOW_REQUEST_MS` (default 1000) or the route's `x-slow-threshold-ms`
 //!
 //! **Security Metrics:**
 //! - `auth_attempts_total` - Authentication attempts by scheme and result
@@ -664,7 +668,7 @@
 //! - `dispatcher_queue_depth` - Pending requests in dispatcher queues
 //!
 //! **Validation Metrics:**
-//! - `request_validation_failures_total` - Request validation errors by type
+//! - `brrtrouter_request_validation_failures_total{path,category}` - Rejected requests by route template and failure category (`missing_required`, `type_mismatch`, `format`, `enum`, `additional_property`, `other`)
 //! - `response_validation_failures_total` - Response validation errors by endpoint
 //!
 //! ### OpenTelemetry Tracing
@@ -848,17 +852,27 @@
 //! # HELP http_requests_total Total HTTP requests
 //! # TYPE http_requests_total counter
 //! http_requests_total{method="GET",path="/pets",status="200"} 42
-//! http_requests_total{method="GET",path="/pets/:id",status="200"} 18
+//! http_requests_total{method="GET",path="/pets/{id}",status="200"} 18
 //! http_requests_total{method="POST",path="/pets",status="201"} 5
 //! http_requests_total{method="GET",path="/health",status="200"} 120
 //! ```
 
 // Use jemalloc as the global allocator when the feature is enabled
 // This provides accurate heap statistics via tikv-jemalloc-ctl
-#[cfg(feature = "jemalloc")]
+#[cfg(all(feature = "jemalloc", not(feature = "alloc-profiling")))]
 #[global_allocator]
 static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
+// Count allocations per request phase (alloc_profile), over jemalloc when both are enabled
+#[cfg(all(feature = "alloc-profiling", feature = "jemalloc"))]
+#[global_allocator]
+static ALLOC: alloc_profile::CountingAllocator<tikv_jemallocator::Jemalloc> =
+    alloc_profile::CountingAllocator::new(tikv_jemallocator::Jemalloc);
+#[cfg(all(feature = "alloc-profiling", not(feature = "jemalloc")))]
+#[global_allocator]
+static ALLOC: alloc_profile::CountingAllocator = alloc_profile::CountingAllocator::system();
+
+pub mod alloc_profile;
 pub mod cli;
 
 #[doc(hidden)]
@@ -874,6 +888,7 @@ pub mod linter;
 #[cfg(test)]
 #[path = "linter/tests.rs"]
 mod linter_tests;
+pub mod lock_poison;
 pub mod middleware;
 pub mod otel;
 pub mod router;
@@ -884,12 +899,16 @@ pub mod server;
 pub mod spec;
 pub mod sse;
 pub mod static_files;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod typed;
 pub mod validator;
 pub mod validator_cache;
 pub mod worker_pool;
 
-pub use security::{BearerJwtProvider, OAuth2Provider, SecurityProvider, SecurityRequest};
+pub use security::{
+    AuthOutcome, BearerJwtProvider, OAuth2Provider, SecurityProvider, SecurityRequest,
+};
 pub use spec::{
     load_spec, load_spec_from_spec, load_spec_full, ParameterLocation, ParameterMeta,
     ParameterStyle, RouteMeta, SecurityRequirement, SecurityScheme,
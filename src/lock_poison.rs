@@ -0,0 +1,167 @@
+//! Lock-poison policy: log, count, recover.
+//!
+//! A `std::sync` lock is poisoned when a thread panics while holding it. Treating that as
+//! "lock unavailable" turns one panic into permanently degraded behaviour (a JWKS cache that
+//! never refreshes, a validator cache that is never read) with no signal. Every shared lock in
+//! BRRTRouter goes through this module instead:
+//!
+//! 1. the poison is logged at `error` with the lock's name,
+//! 2. `brrtrouter_lock_poison_total{lock="<name>"}` is incremented (see
+//!    [`crate::server::metrics_endpoint`]),
+//! 3. the poison flag is cleared and the guard is handed back, so the next caller does not
+//!    see the poison again.
+//!
+//! # Where recovery is safe
+//!
+//! Recovery hands out data that a panicking thread may have left half-updated, so it is only
+//! used where every critical section writes whole values:
+//!
+//! - **Router / dispatcher**: live behind `ArcSwap` (see `hot_reload`), not a lock, and
+//!   cannot be poisoned at all. The dispatcher's per-handler panic guard state is a set of
+//!   counters and a timestamp, each assigned in one step.
+//! - **JWKS key cache, OIDC discovery cache, SPIFFE JWKS cache**: a `(fetched_at, keys)` pair
+//!   or a map entry replaced wholesale after a successful fetch.
+//! - **Validator cache**: compiled validators inserted or cleared as a whole; a lost entry is
+//!   recompiled on the next request.
+//! - **Revocation set, remote API key cache, memory statistics**: single inserts/assignments.
+//! - **JWT claims cache** ([`write_or_reset`]): results of earlier validations. Rather than
+//!   trusting entries written around the panic, the cache is emptied and tokens are validated
+//!   again.
+//!
+//! New locks holding multi-step invariants should not use [`read`]/[`write`]; surface the
+//! poison with [`record_poison`] and fail the operation instead.
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+static POISON_COUNTS: Lazy<DashMap<&'static str, AtomicU64>> = Lazy::new(DashMap::new);
+
+/// Log a poisoned lock and count it under `lock`.
+///
+/// [`read`], [`write`] and [`lock`] call this; use it directly for locks that must not recover.
+pub fn record_poison(lock: &'static str) {
+    tracing::error!(
+        lock,
+        "lock poisoned by a panic in another thread; recovering its last written state"
+    );
+    POISON_COUNTS
+        .entry(lock)
+        .or_default()
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+/// Poison events per lock name, sorted by name (the `brrtrouter_lock_poison_total` series).
+pub fn lock_poison_counts() -> Vec<(&'static str, u64)> {
+    let mut counts: Vec<_> = POISON_COUNTS
+        .iter()
+        .map(|entry| (*entry.key(), entry.value().load(Ordering::Relaxed)))
+        .collect();
+    counts.sort_unstable_by_key(|(name, _)| *name);
+    counts
+}
+
+/// Poison events recorded for `lock` since startup.
+pub fn lock_poison_total(lock: &str) -> u64 {
+    POISON_COUNTS
+        .get(lock)
+        .map_or(0, |count| count.load(Ordering::Relaxed))
+}
+
+/// Acquire a read guard, recovering (and recording) a poisoned lock.
+pub fn read<'a, T>(lock: &'a RwLock<T>, name: &'static str) -> RwLockReadGuard<'a, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        record_poison(name);
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Acquire a write guard, recovering (and recording) a poisoned lock.
+pub fn write<'a, T>(lock: &'a RwLock<T>, name: &'static str) -> RwLockWriteGuard<'a, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        record_poison(name);
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Like [`write`], but runs `reset` on the data before returning a recovered guard, for
+/// caches whose entries should not outlive a panic.
+pub fn write_or_reset<'a, T>(
+    lock: &'a RwLock<T>,
+    name: &'static str,
+    reset: impl FnOnce(&mut T),
+) -> RwLockWriteGuard<'a, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        record_poison(name);
+        lock.clear_poison();
+        let mut guard = poisoned.into_inner();
+        reset(&mut guard);
+        guard
+    })
+}
+
+/// Acquire a mutex guard, recovering (and recording) a poisoned mutex.
+pub fn lock<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        record_poison(name);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poison<T: Send + Sync>(lock: &RwLock<T>) {
+        std::thread::scope(|s| {
+            let _ = s
+                .spawn(|| {
+                    let _guard = lock.write();
+                    panic!("poison the lock");
+                })
+                .join();
+        });
+        assert!(lock.is_poisoned());
+    }
+
+    #[test]
+    fn read_recovers_counts_and_clears_poison() {
+        let lock = RwLock::new(7);
+        poison(&lock);
+        assert_eq!(*read(&lock, "test.read"), 7);
+        assert!(!lock.is_poisoned());
+        assert_eq!(*read(&lock, "test.read"), 7);
+        assert_eq!(lock_poison_total("test.read"), 1);
+    }
+
+    #[test]
+    fn write_or_reset_resets_only_after_poison() {
+        let lock = RwLock::new(vec![1, 2]);
+        write_or_reset(&lock, "test.reset", Vec::clear).push(3);
+        assert_eq!(*read(&lock, "test.reset"), [1, 2, 3]);
+        poison(&lock);
+        assert!(write_or_reset(&lock, "test.reset", Vec::clear).is_empty());
+        assert_eq!(lock_poison_total("test.reset"), 1);
+    }
+
+    #[test]
+    fn mutex_recovers() {
+        let mutex = Mutex::new(1);
+        std::thread::scope(|s| {
+            let _ = s
+                .spawn(|| {
+                    let _guard = mutex.lock();
+                    panic!("poison the mutex");
+                })
+                .join();
+        });
+        *lock(&mutex, "test.mutex") += 1;
+        assert_eq!(*lock(&mutex, "test.mutex"), 2);
+        assert_eq!(lock_poison_total("test.mutex"), 1);
+        assert!(lock_poison_counts().contains(&("test.mutex", 1)));
+    }
+}
@@ -0,0 +1,402 @@
+//! Opt-in request/response body logging with redaction, for debugging selected routes.
+//!
+//! ```yaml
+//! middleware:
+//!   - name: body_logging
+//!     enabled: true
+//!     routes: [create_user, login]   # handler names (operationId)
+//!     level: debug
+//!     redact: [/password, /token, /user/ssn]
+//! ```
+//!
+//! Only JSON bodies are logged, each as one event on the `brrtrouter::body` target with the
+//! handler name and request id. Every [`BodyLoggingConfig::redact`] JSON pointer that resolves
+//! is replaced with [`REDACTED`] before the body is serialized. Bodies that serialize to more
+//! than `max_body_bytes`, and non-JSON request bodies, are logged as their size only. SSE and
+//! WebSocket routes are never logged: their bodies are streams.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::middleware::Middleware;
+use crate::spec::RouteMeta;
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "***";
+
+/// Default [`BodyLoggingConfig::max_body_bytes`].
+pub const DEFAULT_MAX_LOGGED_BODY_BYTES: usize = 4096;
+
+/// Level body events are logged at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyLogLevel {
+    /// `TRACE`
+    Trace,
+    /// `DEBUG` (default)
+    #[default]
+    Debug,
+    /// `INFO`
+    Info,
+    /// `WARN`
+    Warn,
+}
+
+/// Settings for [`BodyLoggingMiddleware`] (`middleware: - name: body_logging`).
+///
+/// | Field | Default | Meaning |
+/// |-------|---------|---------|
+/// | `enabled` | `false` | Log anything at all |
+/// | `routes` | empty | Handler names whose bodies are logged |
+/// | `level` | `debug` | `trace`, `debug`, `info` or `warn` |
+/// | `request` | `true` | Log request bodies |
+/// | `response` | `true` | Log response bodies |
+/// | `redact` | empty | JSON pointers replaced with `***` (e.g. `/password`) |
+/// | `max_body_bytes` | `4096` | Larger bodies are logged as their size only |
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BodyLoggingConfig {
+    /// Log bodies; off by default so the entry can stay in config between debugging sessions.
+    pub enabled: bool,
+    /// Handler names (`operationId`) to log; routes not listed are never logged.
+    pub routes: Vec<String>,
+    /// Level of the body events.
+    pub level: BodyLogLevel,
+    /// Log request bodies.
+    pub request: bool,
+    /// Log response bodies.
+    pub response: bool,
+    /// JSON pointers (RFC 6901) whose values are replaced with [`REDACTED`].
+    pub redact: Vec<String>,
+    /// Largest serialized body logged in full.
+    pub max_body_bytes: usize,
+}
+
+impl Default for BodyLoggingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            routes: Vec::new(),
+            level: BodyLogLevel::Debug,
+            request: true,
+            response: true,
+            redact: Vec::new(),
+            max_body_bytes: DEFAULT_MAX_LOGGED_BODY_BYTES,
+        }
+    }
+}
+
+/// Middleware logging the bodies of the routes listed in [`BodyLoggingConfig::routes`].
+pub struct BodyLoggingMiddleware {
+    /// Handler names to log (empty when disabled)
+    routes: HashSet<String>,
+    level: BodyLogLevel,
+    request: bool,
+    response: bool,
+    redact: Vec<String>,
+    max_body_bytes: usize,
+}
+
+impl BodyLoggingMiddleware {
+    /// Build the middleware. Listed routes that are SSE or WebSocket operations are skipped
+    /// with a warning, as are names that match no route.
+    pub fn new(config: &BodyLoggingConfig, routes: &[RouteMeta]) -> Self {
+        let enabled = if config.enabled {
+            config
+                .routes
+                .iter()
+                .filter(|name| {
+                    match routes.iter().find(|r| r.handler_name.as_ref() == name.as_str()) {
+                        Some(route) if route.sse || route.websocket => {
+                            tracing::warn!(handler = %name, "body logging skips streaming route");
+                            false
+                        }
+                        Some(_) => true,
+                        None => {
+                            tracing::warn!(handler = %name, "body logging route matches no operation");
+                            false
+                        }
+                    }
+                })
+                .cloned()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Self {
+            routes: enabled,
+            level: config.level,
+            request: config.request,
+            response: config.response,
+            redact: config.redact.clone(),
+            max_body_bytes: config.max_body_bytes,
+        }
+    }
+
+    fn logs(&self, handler: &str) -> bool {
+        !self.routes.is_empty() && self.routes.contains(handler) && level_enabled(self.level)
+    }
+
+    /// Redacted, size-limited rendering of `body`.
+    fn render(&self, body: &Value) -> String {
+        let rendered = if self.redact.is_empty() {
+            serde_json::to_string(body)
+        } else {
+            let mut redacted = body.clone();
+            for pointer in &self.redact {
+                if let Some(value) = redacted.pointer_mut(pointer) {
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+            serde_json::to_string(&redacted)
+        };
+        match rendered {
+            Ok(s) if s.len() <= self.max_body_bytes => s,
+            Ok(s) => format!("<{} bytes, over max_body_bytes>", s.len()),
+            Err(e) => format!("<unserializable: {e}>"),
+        }
+    }
+}
+
+fn level_enabled(level: BodyLogLevel) -> bool {
+    match level {
+        BodyLogLevel::Trace => tracing::enabled!(target: "brrtrouter::body", tracing::Level::TRACE),
+        BodyLogLevel::Debug => tracing::enabled!(target: "brrtrouter::body", tracing::Level::DEBUG),
+        BodyLogLevel::Info => tracing::enabled!(target: "brrtrouter::body", tracing::Level::INFO),
+        BodyLogLevel::Warn => tracing::enabled!(target: "brrtrouter::body", tracing::Level::WARN),
+    }
+}
+
+/// One body event at `level`; `status` is set for responses.
+fn emit(level: BodyLogLevel, req: &HandlerRequest, status: Option<u16>, body: &str) {
+    let kind = if status.is_some() {
+        "response"
+    } else {
+        "request"
+    };
+    macro_rules! at {
+        ($event:ident) => {
+            tracing::$event!(
+                target: "brrtrouter::body",
+                handler = %req.handler_name,
+                request_id = %req.request_id,
+                status = ?status,
+                body,
+                "{kind} body"
+            )
+        };
+    }
+    match level {
+        BodyLogLevel::Trace => at!(trace),
+        BodyLogLevel::Debug => at!(debug),
+        BodyLogLevel::Info => at!(info),
+        BodyLogLevel::Warn => at!(warn),
+    }
+}
+
+impl Middleware for BodyLoggingMiddleware {
+    fn before(&self, req: &HandlerRequest) -> Option<HandlerResponse> {
+        if !self.request || !self.logs(&req.handler_name) {
+            return None;
+        }
+        let body = match (&req.body, &req.raw_body) {
+            (Some(body), _) => self.render(body),
+            (None, Some(raw)) if !raw.is_empty() => format!("<{} bytes, not JSON>", raw.len()),
+            _ => return None,
+        };
+        emit(self.level, req, None, &body);
+        None
+    }
+
+    fn after(&self, req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        if !self.response || !self.logs(&req.handler_name) {
+            return;
+        }
+        if res
+            .get_header("content-type")
+            .is_some_and(|ct| ct.starts_with("text/event-stream"))
+        {
+            return;
+        }
+        emit(self.level, req, Some(res.status), &self.render(&res.body));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::HeaderVec;
+    use crate::ids::RequestId;
+    use crate::router::ParamVec;
+    use http::Method;
+    use may::sync::mpsc;
+    use serde_json::json;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn request(handler: &str, body: Value) -> HandlerRequest {
+        let (tx, _rx) = mpsc::channel();
+        HandlerRequest {
+            request_id: RequestId::new(),
+            method: Method::POST,
+            path: "/login".to_string(),
+            handler_name: handler.to_string(),
+            path_params: ParamVec::new(),
+            query_params: ParamVec::new(),
+            headers: HeaderVec::new(),
+            cookies: HeaderVec::new(),
+            body: Some(body),
+            jwt_claims: None,
+            reply_tx: tx,
+            queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
+        }
+    }
+
+    fn middleware(config: BodyLoggingConfig) -> BodyLoggingMiddleware {
+        BodyLoggingMiddleware {
+            routes: if config.enabled {
+                config.routes.iter().cloned().collect()
+            } else {
+                HashSet::new()
+            },
+            level: config.level,
+            request: config.request,
+            response: config.response,
+            redact: config.redact.clone(),
+            max_body_bytes: config.max_body_bytes,
+        }
+    }
+
+    /// Run `f` with a DEBUG subscriber and return everything it logged.
+    fn capture(f: impl FnOnce()) -> String {
+        let out = Captured::default();
+        let writer = out.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let bytes = out.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn login_config() -> BodyLoggingConfig {
+        BodyLoggingConfig {
+            enabled: true,
+            routes: vec!["login".to_string()],
+            redact: vec!["/password".to_string(), "/session/token".to_string()],
+            ..BodyLoggingConfig::default()
+        }
+    }
+
+    #[test]
+    fn redacted_fields_never_reach_the_log() {
+        let mw = middleware(login_config());
+        let req = request("login", json!({ "user": "ada", "password": "hunter2" }));
+        let mut res = HandlerResponse::json(
+            200,
+            json!({ "session": { "token": "s3cr3t-token", "expires": 60 } }),
+        );
+        let log = capture(|| {
+            assert!(mw.before(&req).is_none());
+            mw.after(&req, &mut res, Duration::ZERO);
+        });
+
+        assert!(log.contains("request body"), "{log}");
+        assert!(log.contains("response body"), "{log}");
+        assert!(log.contains("ada"), "{log}");
+        assert!(log.contains("expires"), "{log}");
+        assert!(log.contains(REDACTED), "{log}");
+        assert!(!log.contains("hunter2"), "{log}");
+        assert!(!log.contains("s3cr3t-token"), "{log}");
+        // Redaction never touches the body the handler and client see.
+        assert_eq!(res.body["session"]["token"], "s3cr3t-token");
+    }
+
+    #[test]
+    fn unlisted_disabled_streaming_and_oversized_bodies_are_not_logged() {
+        let req = request("signup", json!({ "password": "hunter2" }));
+        let log = capture(|| {
+            middleware(login_config()).before(&req);
+        });
+        assert!(log.is_empty(), "{log}");
+
+        let req = request("login", json!({ "password": "hunter2" }));
+        let log = capture(|| {
+            middleware(BodyLoggingConfig {
+                enabled: false,
+                ..login_config()
+            })
+            .before(&req);
+        });
+        assert!(log.is_empty(), "{log}");
+
+        let mut res = HandlerResponse::json(200, json!("data: {\"password\":\"hunter2\"}\n\n"));
+        res.set_header("content-type", "text/event-stream".to_string());
+        let log = capture(|| middleware(login_config()).after(&req, &mut res, Duration::ZERO));
+        assert!(log.is_empty(), "{log}");
+
+        let req = request("login", json!({ "note": "x".repeat(64) }));
+        let log = capture(|| {
+            middleware(BodyLoggingConfig {
+                max_body_bytes: 16,
+                ..login_config()
+            })
+            .before(&req);
+        });
+        assert!(log.contains("over max_body_bytes"), "{log}");
+        assert!(!log.contains("xxxx"), "{log}");
+    }
+
+    #[test]
+    fn streaming_and_unknown_routes_are_dropped_at_construction() {
+        let spec: oas3::OpenApiV3Spec = serde_yaml::from_str(
+            r#"
+openapi: 3.1.0
+info: { title: Body, version: "1" }
+paths:
+  /events:
+    get:
+      operationId: events
+      x-sse: true
+      responses: { "200": { description: ok } }
+  /login:
+    post:
+      operationId: login
+      responses: { "200": { description: ok } }
+"#,
+        )
+        .unwrap();
+        let routes = crate::spec::load_spec_from_spec(spec).unwrap();
+        let config = BodyLoggingConfig {
+            routes: vec!["events".into(), "login".into(), "missing".into()],
+            ..login_config()
+        };
+        let mw = BodyLoggingMiddleware::new(&config, &routes);
+        assert_eq!(mw.routes, HashSet::from(["login".to_string()]));
+    }
+}
@@ -0,0 +1,291 @@
+//! Declarative JWT claim forwarding for BFF routes.
+//!
+//! Maps claims from [`HandlerRequest::jwt_claims`] to headers in
+//! [`HandlerRequest::downstream_headers`], which [`crate::http::proxy_untyped`] (and handlers
+//! making their own downstream calls) send to the downstream service:
+//!
+//! ```yaml
+//! # config.yaml — default for every route
+//! middleware:
+//!   - name: forward_claims
+//!     claims:
+//!       sub: X-User-Id
+//!       org_id: X-Org-Id
+//! ```
+//!
+//! ```yaml
+//! # OpenAPI operation — replaces the default for this route (`false` disables it)
+//! x-forward-claims:
+//!   sub: X-Customer-Id
+//! ```
+//!
+//! Claims that are absent, or are not a string, number or boolean, are skipped. Inbound
+//! request headers with a mapped name are removed first, so a client cannot supply
+//! `X-User-Id` itself and have it reach the downstream service when the claim is missing.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::dispatcher::HandlerRequest;
+use crate::middleware::Middleware;
+use crate::spec::RouteMeta;
+
+/// Settings for [`ClaimsForwardingMiddleware`] (`middleware: - name: forward_claims`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClaimsForwardingConfig {
+    /// Default claim → header mapping for routes without `x-forward-claims`.
+    pub claims: BTreeMap<String, String>,
+}
+
+/// Claim name → downstream header name.
+type ClaimMapping = Arc<[(String, Arc<str>)]>;
+
+/// Whether `name` is a valid HTTP header name.
+pub(crate) fn is_valid_header_name(name: &str) -> bool {
+    http::HeaderName::from_bytes(name.as_bytes()).is_ok()
+}
+
+fn mapping<'a>(
+    pairs: impl IntoIterator<Item = (&'a String, &'a String)>,
+    handler: &str,
+) -> ClaimMapping {
+    pairs
+        .into_iter()
+        .filter(|(claim, header)| {
+            let valid = is_valid_header_name(header);
+            if !valid {
+                tracing::warn!(handler, claim = %claim, header = %header, "ignoring invalid forward-claims header name");
+            }
+            valid
+        })
+        .map(|(claim, header)| (claim.clone(), Arc::from(header.as_str())))
+        .collect()
+}
+
+/// Header value for a scalar claim; `None` for absent, structured or unsafe values.
+fn claim_header_value(claims: &Value, claim: &str) -> Option<String> {
+    let value = match claims.get(claim)? {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    // Never let a claim smuggle extra header lines downstream.
+    (!value.chars().any(|c| c.is_control())).then_some(value)
+}
+
+/// Middleware filling [`HandlerRequest::downstream_headers`] from JWT claims.
+pub struct ClaimsForwardingMiddleware {
+    default: ClaimMapping,
+    /// handler name → route override from `x-forward-claims`
+    routes: HashMap<String, ClaimMapping>,
+}
+
+impl ClaimsForwardingMiddleware {
+    /// Build the middleware from the config default and per-route `x-forward-claims`.
+    pub fn new(config: &ClaimsForwardingConfig, routes: &[RouteMeta]) -> Self {
+        Self::with_overrides(
+            config,
+            routes.iter().filter_map(|route| {
+                route
+                    .forward_claims
+                    .as_deref()
+                    .map(|pairs| (route.handler_name.as_ref(), pairs))
+            }),
+        )
+    }
+
+    fn with_overrides<'a>(
+        config: &ClaimsForwardingConfig,
+        overrides: impl IntoIterator<Item = (&'a str, &'a [(String, String)])>,
+    ) -> Self {
+        let routes = overrides
+            .into_iter()
+            .map(|(handler, pairs)| {
+                (
+                    handler.to_string(),
+                    mapping(pairs.iter().map(|(c, h)| (c, h)), handler),
+                )
+            })
+            .collect();
+        Self {
+            default: mapping(&config.claims, "*"),
+            routes,
+        }
+    }
+
+    fn mapping_for(&self, handler: &str) -> &[(String, Arc<str>)] {
+        self.routes.get(handler).unwrap_or(&self.default)
+    }
+}
+
+impl Middleware for ClaimsForwardingMiddleware {
+    fn prepare(&self, req: &mut HandlerRequest) {
+        let mapping = self.mapping_for(&req.handler_name);
+        if mapping.is_empty() {
+            return;
+        }
+        req.headers
+            .retain(|(name, _)| !mapping.iter().any(|(_, h)| h.eq_ignore_ascii_case(name)));
+        let Some(claims) = req.jwt_claims.as_ref() else {
+            return;
+        };
+        for (claim, header) in mapping {
+            if let Some(value) = claim_header_value(claims, claim) {
+                req.downstream_headers.push((header.clone(), value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::HeaderVec;
+    use crate::ids::RequestId;
+    use crate::router::ParamVec;
+    use http::Method;
+    use may::sync::mpsc;
+    use serde_json::json;
+
+    fn request(handler: &str, claims: Option<Value>, headers: &[(&str, &str)]) -> HandlerRequest {
+        let (tx, _rx) = mpsc::channel();
+        HandlerRequest {
+            request_id: RequestId::new(),
+            method: Method::GET,
+            path: "/orders".to_string(),
+            handler_name: handler.to_string(),
+            path_params: ParamVec::new(),
+            query_params: ParamVec::new(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (Arc::from(*k), (*v).to_string()))
+                .collect(),
+            cookies: HeaderVec::new(),
+            body: None,
+            jwt_claims: claims,
+            reply_tx: tx,
+            queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
+        }
+    }
+
+    fn middleware(overrides: &[(&str, Vec<(String, String)>)]) -> ClaimsForwardingMiddleware {
+        let config = ClaimsForwardingConfig {
+            claims: BTreeMap::from([
+                ("sub".to_string(), "X-User-Id".to_string()),
+                ("org_id".to_string(), "X-Org-Id".to_string()),
+            ]),
+        };
+        ClaimsForwardingMiddleware::with_overrides(
+            &config,
+            overrides
+                .iter()
+                .map(|(handler, pairs)| (*handler, pairs.as_slice())),
+        )
+    }
+
+    fn downstream(req: &HandlerRequest) -> Vec<(String, String)> {
+        req.downstream_headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn default_mapping_forwards_present_claims() {
+        let mw = middleware(&[]);
+        let mut req = request(
+            "list_orders",
+            Some(json!({"sub": "u-1", "org_id": 42})),
+            &[],
+        );
+        mw.prepare(&mut req);
+        assert_eq!(
+            downstream(&req),
+            vec![
+                ("X-Org-Id".to_string(), "42".to_string()),
+                ("X-User-Id".to_string(), "u-1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn absent_claims_and_anonymous_requests_are_skipped() {
+        let mw = middleware(&[]);
+        let mut req = request(
+            "list_orders",
+            Some(json!({"sub": "u-1", "roles": ["a"]})),
+            &[],
+        );
+        mw.prepare(&mut req);
+        assert_eq!(
+            downstream(&req),
+            vec![("X-User-Id".to_string(), "u-1".to_string())]
+        );
+
+        let mut req = request("list_orders", None, &[]);
+        mw.prepare(&mut req);
+        assert!(req.downstream_headers.is_empty());
+    }
+
+    #[test]
+    fn route_override_replaces_or_disables_default() {
+        let mw = middleware(&[
+            (
+                "get_customer",
+                vec![("sub".to_string(), "X-Customer-Id".to_string())],
+            ),
+            ("public_feed", Vec::new()),
+        ]);
+        let claims = json!({"sub": "u-1", "org_id": "o-1"});
+
+        let mut req = request("get_customer", Some(claims.clone()), &[]);
+        mw.prepare(&mut req);
+        assert_eq!(
+            downstream(&req),
+            vec![("X-Customer-Id".to_string(), "u-1".to_string())]
+        );
+
+        let mut req = request("public_feed", Some(claims), &[]);
+        mw.prepare(&mut req);
+        assert!(req.downstream_headers.is_empty());
+    }
+
+    #[test]
+    fn spoofed_inbound_headers_are_removed() {
+        let mw = middleware(&[]);
+        let mut req = request(
+            "list_orders",
+            Some(json!({"org_id": "o-1"})),
+            &[("x-user-id", "admin"), ("accept", "application/json")],
+        );
+        mw.prepare(&mut req);
+        assert_eq!(req.get_header("x-user-id"), None);
+        assert_eq!(req.get_header("accept"), Some("application/json"));
+        assert_eq!(
+            downstream(&req),
+            vec![("X-Org-Id".to_string(), "o-1".to_string())]
+        );
+    }
+
+    #[test]
+    fn control_characters_in_claims_are_not_forwarded() {
+        let mw = middleware(&[]);
+        let mut req = request(
+            "list_orders",
+            Some(json!({"sub": "u-1\r\nX-Admin: true"})),
+            &[],
+        );
+        mw.prepare(&mut req);
+        assert!(req.downstream_headers.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! gzip response compression negotiated from `Accept-Encoding`.
+//!
+//! Response bodies are still JSON values when middleware runs, so this middleware only
+//! negotiates: it marks eligible responses with `Content-Encoding: gzip` and
+//! `Vary: Accept-Encoding`. [`crate::server::response::write_handler_response`] performs the
+//! encoding after serialization, falling back to the identity encoding if gzip fails.
+
+use std::io::Write;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::middleware::{merge_vary_field_value, Middleware};
+use crate::server::response::response_status_allows_body;
+
+/// Settings for [`CompressionMiddleware`] (`middleware: - name: compression`).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionConfig {
+    /// Smallest serialized body (bytes) worth compressing; default 1024.
+    pub min_size_bytes: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            min_size_bytes: 1024,
+        }
+    }
+}
+
+/// Middleware that opts eligible responses into gzip encoding.
+#[derive(Debug, Clone, Default)]
+pub struct CompressionMiddleware {
+    min_size_bytes: usize,
+}
+
+impl CompressionMiddleware {
+    /// Build the middleware from its settings.
+    pub fn new(config: &CompressionConfig) -> Self {
+        Self {
+            min_size_bytes: config.min_size_bytes,
+        }
+    }
+}
+
+/// Whether an `Accept-Encoding` value accepts gzip (explicitly or via `*`) with a non-zero q.
+pub(crate) fn accepts_gzip(accept_encoding: &str) -> bool {
+    accepts_encoding(accept_encoding, "gzip")
+}
+
+/// Whether an `Accept-Encoding` value accepts `encoding` with a non-zero q. An entry naming
+/// `encoding` decides over `*` (RFC 9110 §12.5.3), so `gzip;q=0, *` refuses gzip.
+pub(crate) fn accepts_encoding(accept_encoding: &str, encoding: &str) -> bool {
+    let mut explicit = None;
+    let mut wildcard = None;
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let coding = parts.next().unwrap_or("").trim();
+        let slot = if coding.eq_ignore_ascii_case(encoding) {
+            &mut explicit
+        } else if coding == "*" {
+            &mut wildcard
+        } else {
+            continue;
+        };
+        let refused = parts.any(|p| {
+            p.trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        *slot = Some(!refused);
+    }
+    explicit.or(wildcard).unwrap_or(false)
+}
+
+/// `io::Write` sink that only counts bytes, for sizing JSON bodies without allocating.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn body_len(body: &Value) -> usize {
+    match body {
+        Value::Null => 0,
+        Value::String(s) => s.len(),
+        other => {
+            let mut counter = ByteCounter(0);
+            match serde_json::to_writer(&mut counter, other) {
+                Ok(()) => counter.0,
+                Err(_) => 0,
+            }
+        }
+    }
+}
+
+impl Middleware for CompressionMiddleware {
+    fn after(&self, req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        if !response_status_allows_body(res.status) || res.get_header("content-encoding").is_some()
+        {
+            return;
+        }
+        if res
+            .get_header("content-type")
+            .is_some_and(|ct| ct.starts_with("text/event-stream"))
+        {
+            return;
+        }
+        if !req.get_header("accept-encoding").is_some_and(accepts_gzip) {
+            return;
+        }
+        if body_len(&res.body) < self.min_size_bytes {
+            return;
+        }
+        let vary = merge_vary_field_value(res.get_header("vary"), &["Accept-Encoding"]);
+        res.set_header("vary", vary);
+        res.set_header("content-encoding", "gzip".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_encoding_negotiation() {
+        assert!(accepts_gzip("gzip"));
+        assert!(accepts_gzip("br, GZIP;q=0.5"));
+        assert!(accepts_gzip("*"));
+        assert!(!accepts_gzip("gzip;q=0"));
+        assert!(!accepts_gzip("br, deflate"));
+        assert!(!accepts_gzip(""));
+        assert!(accepts_encoding("gzip, br;q=0.8", "br"));
+        assert!(!accepts_encoding("gzip, br;q=0", "br"));
+        // An explicit q=0 overrides the wildcard, in either order
+        assert!(!accepts_gzip("gzip;q=0, *"));
+        assert!(!accepts_gzip("*, gzip;q=0"));
+        assert!(accepts_gzip("*;q=0, gzip"));
+        assert!(!accepts_gzip("br, *;q=0"));
+    }
+
+    #[test]
+    fn body_len_counts_serialized_json() {
+        assert_eq!(body_len(&serde_json::json!({"a": 1})), 7);
+        assert_eq!(body_len(&Value::String("hello".into())), 5);
+        assert_eq!(body_len(&Value::Null), 0);
+    }
+}
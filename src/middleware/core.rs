@@ -27,6 +27,13 @@ use crate::dispatcher::{HandlerRequest, HandlerResponse};
 /// }
 /// ```
 pub trait Middleware: Send + Sync {
+    /// Called with mutable access before any `before` hook runs
+    ///
+    /// Use this to derive request context (e.g. [`HandlerRequest::downstream_headers`], or
+    /// typed values for the handler via [`HandlerRequest::extensions_mut`]);
+    /// short-circuiting belongs in [`Self::before`].
+    fn prepare(&self, _req: &mut HandlerRequest) {}
+
     /// Called before the request is sent to the handler
     ///
     /// # Arguments
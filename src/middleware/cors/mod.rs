@@ -52,7 +52,7 @@ use http::Method;
 use regex::Regex;
 use tracing::{debug, warn};
 
-use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderVec};
+use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderLookup, HeaderVec};
 use crate::middleware::{MetricsMiddleware, Middleware};
 
 /// First comma-separated value from `X-Forwarded-*` headers (typical reverse-proxy chains).
@@ -78,12 +78,7 @@ fn host_has_explicit_port(host: &str) -> bool {
 /// proxies strip or validate these headers).
 fn effective_server_authority(req: &HandlerRequest, trust_forwarded: bool) -> Option<Cow<'_, str>> {
     if trust_forwarded {
-        let forwarded_vals: Vec<&str> = req
-            .headers
-            .iter()
-            .filter(|(k, _)| k.eq_ignore_ascii_case("forwarded"))
-            .map(|(_, v)| v.as_str())
-            .collect();
+        let forwarded_vals: Vec<&str> = req.headers.get_all("forwarded").collect();
         if let Some(auth) = forwarded::authority_from_forwarded_field_values(&forwarded_vals) {
             return Some(Cow::Owned(auth));
         }
@@ -1277,6 +1272,12 @@ mod cors_middleware_tests {
             jwt_claims: None,
             reply_tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         }
     }
 
@@ -1321,7 +1322,7 @@ mod cors_middleware_tests {
             "403 CORS rejection must not use Value::Null body (UI showed 4-byte null)"
         );
         assert_eq!(
-            early.body.get("error").and_then(|v| v.as_str()),
+            early.body.get("detail").and_then(|v| v.as_str()),
             Some("Origin not allowed by CORS policy")
         );
     }
@@ -1344,7 +1345,7 @@ mod cors_middleware_tests {
         assert_eq!(early.status, 403);
         assert!(!early.body.is_null());
         assert_eq!(
-            early.body.get("error").and_then(|v| v.as_str()),
+            early.body.get("detail").and_then(|v| v.as_str()),
             Some("Origin not allowed by CORS policy")
         );
     }
@@ -1384,7 +1385,7 @@ mod cors_middleware_tests {
         assert_eq!(early.status, 403);
         assert!(!early.body.is_null());
         assert_eq!(
-            early.body.get("error").and_then(|v| v.as_str()),
+            early.body.get("detail").and_then(|v| v.as_str()),
             Some("Origin not allowed by CORS policy")
         );
         assert!(
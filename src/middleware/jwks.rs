@@ -158,6 +158,12 @@ mod tests {
             jwt_claims: None,
             reply_tx: tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         }
     }
 
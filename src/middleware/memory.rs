@@ -10,8 +10,15 @@ use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::lock_poison;
 use crate::middleware::Middleware;
 
+// Lock names reported in `brrtrouter_lock_poison_total`.
+const CURRENT_LOCK: &str = "memory.current";
+const PEAK_LOCK: &str = "memory.peak";
+const LAST_MEASUREMENT_LOCK: &str = "memory.last_measurement";
+const HANDLER_MEMORY_LOCK: &str = "memory.handler_stats";
+
 /// Get current process memory statistics
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MemoryStats {
@@ -139,12 +146,11 @@ impl MemoryMiddleware {
         let stats = MemoryStats::current();
 
         // Update current stats (recover from poisoned lock if needed)
-        if let Ok(mut current) = self.current.write() {
-            *current = stats;
-        }
+        *lock_poison::write(&self.current, CURRENT_LOCK) = stats;
 
         // Update peak if necessary (recover from poisoned lock if needed)
-        if let Ok(mut peak) = self.peak.write() {
+        {
+            let mut peak = lock_poison::write(&self.peak, PEAK_LOCK);
             if stats.rss_bytes > peak.rss_bytes {
                 peak.rss_bytes = stats.rss_bytes;
             }
@@ -161,19 +167,17 @@ impl MemoryMiddleware {
         self.measurements.fetch_add(1, Ordering::Relaxed);
 
         // Update last measurement time
-        if let Ok(mut last) = self.last_measurement.write() {
-            *last = Instant::now();
-        }
+        *lock_poison::write(&self.last_measurement, LAST_MEASUREMENT_LOCK) = Instant::now();
     }
 
     /// Get current memory statistics
     pub fn current_stats(&self) -> MemoryStats {
-        self.current.read().map(|g| *g).unwrap_or_default()
+        *lock_poison::read(&self.current, CURRENT_LOCK)
     }
 
     /// Get peak memory statistics
     pub fn peak_stats(&self) -> MemoryStats {
-        self.peak.read().map(|g| *g).unwrap_or_default()
+        *lock_poison::read(&self.peak, PEAK_LOCK)
     }
 
     /// Get memory growth since baseline
@@ -242,9 +246,7 @@ impl MemoryMiddleware {
         ));
 
         // Per-handler invocation counts (we can't accurately measure per-handler memory)
-        let Ok(handler_stats) = self.handler_memory.read() else {
-            return output; // Return what we have if lock poisoned
-        };
+        let handler_stats = lock_poison::read(&self.handler_memory, HANDLER_MEMORY_LOCK);
         if !handler_stats.is_empty() {
             output.push_str("# HELP handler_invocations_total Number of invocations per handler\n");
             output.push_str("# TYPE handler_invocations_total counter\n");
@@ -329,14 +331,11 @@ impl Middleware for MemoryMiddleware {
         // Update overall memory stats after handler execution
         self.update();
 
-        // Track handler invocation count (skip if lock poisoned)
-        if let Ok(mut handler_stats) = self.handler_memory.write() {
-            let stats = handler_stats
-                .entry(req.handler_name.clone())
-                .or_insert_with(HandlerMemoryStats::default);
-
-            stats.invocations += 1;
-        }
+        // Track handler invocation count (recover from poisoned lock if needed)
+        lock_poison::write(&self.handler_memory, HANDLER_MEMORY_LOCK)
+            .entry(req.handler_name.clone())
+            .or_insert_with(HandlerMemoryStats::default)
+            .invocations += 1;
 
         // NOTE: We cannot accurately attribute memory to specific handlers
         // without request-scoped allocator tracking. We only track invocation
@@ -1,12 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use arc_swap::ArcSwap;
 use dashmap::DashMap;
+use http::Method;
+use serde_json::{json, Map, Value};
+use smallvec::SmallVec;
+use tracing::warn;
 
 use super::Middleware;
 use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::router::route_label;
+use crate::spec::RouteMeta;
 
 /// Histogram buckets for latency tracking (in seconds)
 /// Buckets: 1ms, 5ms, 10ms, 50ms, 100ms, 500ms, 1s, 5s, 10s, +Inf
@@ -21,6 +28,10 @@ const HISTOGRAM_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0
 /// misconfigured callers that record raw URLs.
 const DEFAULT_PATH_METRICS_CAP: usize = 4096;
 
+/// Default slow-request threshold in milliseconds; overridable via `BRRTR_SLOW_REQUEST_MS`
+/// (`0` disables slow-request detection) and per route via `x-slow-threshold-ms`.
+pub const DEFAULT_SLOW_REQUEST_MS: u64 = 1000;
+
 /// Overflow bucket label used once the path-metrics soft cap is exceeded.
 /// Exposed on `/metrics` as `path="__other"` on the per-path series.
 pub(crate) const PATH_OVERFLOW_LABEL: &str = "__other";
@@ -35,6 +46,12 @@ struct HistogramMetric {
     count: AtomicU64,
 }
 
+impl Default for HistogramMetric {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HistogramMetric {
     fn new() -> Self {
         let mut buckets = Vec::with_capacity(HISTOGRAM_BUCKETS.len() + 1);
@@ -93,6 +110,10 @@ struct PathMetrics {
     total_latency_ns: AtomicU64,
     max_latency_ns: AtomicU64,
     min_latency_ns: AtomicU64,
+    /// Handler duration distribution for this path
+    histogram: HistogramMetric,
+    /// Requests slower than the path's slow-request threshold
+    slow_count: AtomicU64,
 }
 
 impl PathMetrics {
@@ -102,10 +123,16 @@ impl PathMetrics {
             total_latency_ns: AtomicU64::new(0),
             max_latency_ns: AtomicU64::new(0),
             min_latency_ns: AtomicU64::new(u64::MAX), // Start high for min
+            histogram: HistogramMetric::new(),
+            slow_count: AtomicU64::new(0),
         }
     }
 
-    fn record(&self, latency_ns: u64) {
+    fn record(&self, latency_ns: u64, slow: bool) {
+        self.histogram.observe(latency_ns as f64 / 1_000_000_000.0);
+        if slow {
+            self.slow_count.fetch_add(1, Ordering::Relaxed);
+        }
         self.count.fetch_add(1, Ordering::Relaxed);
         self.total_latency_ns
             .fetch_add(latency_ns, Ordering::Relaxed);
@@ -140,6 +167,58 @@ impl PathMetrics {
     }
 }
 
+/// Coarse reason a request failed schema validation: the `category` label of
+/// `brrtrouter_request_validation_failures_total`.
+///
+/// Derived from the violated keyword only, never from the offending field name or
+/// value, so the series count stays bounded by routes × categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ValidationFailureCategory {
+    /// A `required` property or required parameter is absent
+    MissingRequired,
+    /// A value has the wrong JSON type (`type`)
+    TypeMismatch,
+    /// A string does not match its `format`
+    Format,
+    /// A value is not one of the allowed `enum` / `const` values
+    Enum,
+    /// An object has a property rejected by `additionalProperties` / `unevaluatedProperties`
+    AdditionalProperty,
+    /// Any other keyword (`minimum`, `pattern`, `maxLength`, `oneOf`, ...)
+    Other,
+}
+
+impl ValidationFailureCategory {
+    /// Prometheus label value.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingRequired => "missing_required",
+            Self::TypeMismatch => "type_mismatch",
+            Self::Format => "format",
+            Self::Enum => "enum",
+            Self::AdditionalProperty => "additional_property",
+            Self::Other => "other",
+        }
+    }
+
+    /// Category of a JSON Schema validation error.
+    #[must_use]
+    pub fn from_error_kind(kind: &jsonschema::error::ValidationErrorKind) -> Self {
+        use jsonschema::error::ValidationErrorKind as Kind;
+        match kind {
+            Kind::Required { .. } => Self::MissingRequired,
+            Kind::Type { .. } => Self::TypeMismatch,
+            Kind::Format { .. } => Self::Format,
+            Kind::Enum { .. } | Kind::Constant { .. } => Self::Enum,
+            Kind::AdditionalProperties { .. } | Kind::UnevaluatedProperties { .. } => {
+                Self::AdditionalProperty
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
 /// Middleware for collecting Prometheus-compatible metrics
 ///
 /// Tracks request counts, latency, stack usage, authentication failures, and (when linked from
@@ -155,11 +234,14 @@ impl PathMetrics {
 /// - Stack size and usage (for coroutine monitoring)
 /// - Top-level request count (non-handler requests like /health, /metrics)
 /// - Authentication failure count
+/// - Request validation failures by route and [`ValidationFailureCategory`]
 /// - CORS: `brrtrouter_cors_origin_rejections_total`, `brrtrouter_cors_preflight_denials_total`,
 ///   `brrtrouter_cors_route_disabled_total`
 ///   (incremented by [`CorsMiddleware`](crate::middleware::CorsMiddleware) via [`inc_cors_origin_rejection`](MetricsMiddleware::inc_cors_origin_rejection),
 ///   [`inc_cors_preflight_denial`](MetricsMiddleware::inc_cors_preflight_denial), and [`inc_cors_route_disabled`](MetricsMiddleware::inc_cors_route_disabled))
-/// - Per-path metrics (count, latency, min/max)
+/// - Per-path metrics (count, latency, min/max, duration histogram)
+/// - Slow requests per path: handler latency above [`Self::with_slow_threshold`] (or the
+///   route's `x-slow-threshold-ms`), optionally logged as a warning with the request id
 ///
 /// ## Performance Optimizations
 ///
@@ -206,6 +288,18 @@ pub struct MetricsMiddleware {
     cors_preflight_denials: AtomicUsize,
     /// CORS: route has `x-cors: false` / [`RouteCorsPolicy::Disabled`](crate::middleware::RouteCorsPolicy::Disabled) — one increment per request (no CORS headers)
     cors_route_disabled: AtomicUsize,
+    /// Request validation failures per (route template, category). Keys are bounded by the
+    /// spec's routes, so no soft cap applies.
+    validation_failures: DashMap<(String, ValidationFailureCategory), AtomicUsize>,
+    /// Default slow-request threshold; zero disables slow-request detection.
+    slow_threshold: Duration,
+    /// Log a warning for every slow request.
+    log_slow_requests: bool,
+    /// Per-route overrides from `x-slow-threshold-ms`, keyed by path template. Replaced
+    /// wholesale by [`MetricsMiddleware::set_route_slow_thresholds`]; usually empty.
+    route_slow_thresholds: ArcSwap<HashMap<String, SmallVec<[(Method, Duration); 2]>>>,
+    /// Route templates never recorded (see [`MetricsMiddleware::with_excluded_paths`]).
+    excluded_paths: HashSet<String>,
 }
 
 /// Default initialization for metrics middleware
@@ -221,6 +315,10 @@ impl Default for MetricsMiddleware {
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_PATH_METRICS_CAP);
+        let slow_threshold_ms = std::env::var("BRRTR_SLOW_REQUEST_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_SLOW_REQUEST_MS);
         Self {
             request_count: AtomicUsize::new(0),
             total_latency_ns: AtomicU64::new(0),
@@ -241,6 +339,11 @@ impl Default for MetricsMiddleware {
             cors_origin_rejections: AtomicUsize::new(0),
             cors_preflight_denials: AtomicUsize::new(0),
             cors_route_disabled: AtomicUsize::new(0),
+            validation_failures: DashMap::new(),
+            slow_threshold: Duration::from_millis(slow_threshold_ms),
+            log_slow_requests: true,
+            route_slow_thresholds: ArcSwap::from_pointee(HashMap::new()),
+            excluded_paths: HashSet::new(),
         }
     }
 }
@@ -251,6 +354,75 @@ impl MetricsMiddleware {
         Self::default()
     }
 
+    /// Count requests whose handler latency exceeds `threshold` as slow
+    /// (`Duration::ZERO` disables). Defaults to `BRRTR_SLOW_REQUEST_MS`, else
+    /// [`DEFAULT_SLOW_REQUEST_MS`].
+    #[must_use]
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    /// Log a warning (with request id, route and latency) for each slow request
+    /// (default `true`).
+    #[must_use]
+    pub fn with_slow_request_logging(mut self, enabled: bool) -> Self {
+        self.log_slow_requests = enabled;
+        self
+    }
+
+    /// Never record requests to these route templates (e.g. `/internal/ping`), so they
+    /// appear neither on `/metrics` nor in [`Self::snapshot`]. Templates are compared by
+    /// their [`route_label`].
+    #[must_use]
+    pub fn with_excluded_paths<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_paths = paths
+            .into_iter()
+            .map(|path| {
+                let path: String = path.into();
+                route_label(&path).into_owned()
+            })
+            .collect();
+        self
+    }
+
+    /// Whether requests to the route template `path` are left out of the metrics
+    #[must_use]
+    pub fn is_excluded(&self, path: &str) -> bool {
+        !self.excluded_paths.is_empty() && self.excluded_paths.contains(path)
+    }
+
+    /// Take per-route slow-request thresholds from the routes' `x-slow-threshold-ms`,
+    /// replacing any set before. Routes without the extension use the default threshold.
+    pub fn set_route_slow_thresholds<'a>(&self, routes: impl IntoIterator<Item = &'a RouteMeta>) {
+        let mut thresholds: HashMap<String, SmallVec<[(Method, Duration); 2]>> = HashMap::new();
+        for route in routes {
+            if let Some(ms) = route.x_slow_threshold_ms {
+                thresholds
+                    .entry(route_label(&route.path_pattern).into_owned())
+                    .or_default()
+                    .push((route.method.clone(), Duration::from_millis(ms)));
+            }
+        }
+        self.route_slow_thresholds.store(Arc::new(thresholds));
+    }
+
+    /// Slow-request threshold for `method` on the route template `path`.
+    fn slow_threshold_for(&self, method: &Method, path: &str) -> Duration {
+        let overrides = self.route_slow_thresholds.load();
+        if overrides.is_empty() {
+            return self.slow_threshold;
+        }
+        overrides
+            .get(path)
+            .and_then(|routes| routes.iter().find(|(m, _)| m == method))
+            .map_or(self.slow_threshold, |(_, threshold)| *threshold)
+    }
+
     /// Get the total number of requests processed
     pub fn request_count(&self) -> usize {
         self.request_count.load(Ordering::Relaxed)
@@ -307,6 +479,22 @@ impl MetricsMiddleware {
         self.auth_failures.load(Ordering::Relaxed)
     }
 
+    /// Count a request to `route` (its path template) that failed validation with `category`
+    pub fn inc_validation_failure(&self, route: &str, category: ValidationFailureCategory) {
+        self.validation_failures
+            .entry((route.to_string(), category))
+            .or_insert_with(|| AtomicUsize::new(0))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Request validation failures keyed by (route template, category)
+    pub fn validation_failure_stats(&self) -> HashMap<(String, ValidationFailureCategory), usize> {
+        self.validation_failures
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().load(Ordering::Relaxed)))
+            .collect()
+    }
+
     /// Increment the connection close counter
     pub fn inc_connection_close(&self) {
         self.connection_closes.fetch_add(1, Ordering::Relaxed);
@@ -396,9 +584,14 @@ impl MetricsMiddleware {
     /// serialized concurrent dispatchers on the hot path and caused a
     /// measured −62 % throughput regression at 2000 concurrent users.
     pub(crate) fn record_path_metrics(&self, path: &str, latency_ns: u64) {
+        self.record_path(path, latency_ns, false);
+    }
+
+    /// [`Self::record_path_metrics`], also counting the request as slow when `slow`.
+    fn record_path(&self, path: &str, latency_ns: u64, slow: bool) {
         // Fast path: read-locked lookup.
         if let Some(m) = self.path_metrics.get(path) {
-            m.record(latency_ns);
+            m.record(latency_ns, slow);
             return;
         }
         // Slow path: path not registered yet. Consult the soft cap.
@@ -409,7 +602,7 @@ impl MetricsMiddleware {
                 .entry(PATH_OVERFLOW_LABEL.to_string())
                 .or_insert_with(|| Arc::new(PathMetrics::new()))
                 .clone();
-            over.record(latency_ns);
+            over.record(latency_ns, slow);
             return;
         }
         // Idempotent insert. `entry` auto-handles a race with another thread
@@ -426,7 +619,7 @@ impl MetricsMiddleware {
         if inserted {
             self.path_count.fetch_add(1, Ordering::Relaxed);
         }
-        m.record(latency_ns);
+        m.record(latency_ns, slow);
     }
 
     /// Read the current overflow counter (PRD Phase 0.3).
@@ -454,6 +647,36 @@ impl MetricsMiddleware {
             .collect()
     }
 
+    /// Per-path handler duration histograms for Prometheus export
+    ///
+    /// Maps path -> (cumulative bucket counts, sum_ns, count); buckets as in
+    /// [`Self::histogram_data`].
+    pub fn path_histograms(&self) -> HashMap<String, (Vec<u64>, u64, u64)> {
+        self.path_metrics
+            .iter()
+            .map(|entry| {
+                let h = &entry.value().histogram;
+                (
+                    entry.key().clone(),
+                    (h.get_buckets(), h.get_sum_ns(), h.get_count()),
+                )
+            })
+            .collect()
+    }
+
+    /// Slow requests per path (see [`Self::with_slow_threshold`])
+    pub fn slow_request_stats(&self) -> HashMap<String, u64> {
+        self.path_metrics
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key().clone(),
+                    entry.value().slow_count.load(Ordering::Relaxed),
+                )
+            })
+            .collect()
+    }
+
     /// Get the current number of active (in-flight) requests
     pub fn active_requests(&self) -> i64 {
         self.active_requests.load(Ordering::Relaxed)
@@ -488,6 +711,97 @@ impl MetricsMiddleware {
         HISTOGRAM_BUCKETS
     }
 
+    /// Current values of every metric as JSON, for dashboards that do not scrape
+    /// Prometheus text (served on `/metrics.json` when enabled)
+    ///
+    /// Reads the same counters as `/metrics`; taking a snapshot records nothing. Shape:
+    ///
+    /// ```json
+    /// {
+    ///   "counters": { "requests_total": 3, "auth_failures_total": 0, ... },
+    ///   "gauges": { "active_requests": 0, "average_latency_seconds": 0.0012, ... },
+    ///   "histograms": { "request_duration_seconds": { "buckets": [{ "le": 0.001, "count": 1 }, ...], "sum": 0.0036, "count": 3 } },
+    ///   "paths": { "/pets": { "requests_total": 3, "statuses": { "200": 3 }, "latency_seconds": { "avg": .., "min": .., "max": .. }, "slow_requests_total": 0, "duration_seconds": { .. } } },
+    ///   "validation_failures": [{ "path": "/pets", "category": "body", "count": 1 }]
+    /// }
+    /// ```
+    ///
+    /// Bucket counts are cumulative, as in Prometheus; the last bucket has `"le": "+Inf"`.
+    #[must_use]
+    pub fn snapshot(&self) -> Value {
+        let (stack_size, used_stack) = self.stack_usage();
+        let (buckets, sum_ns, count) = self.histogram_data();
+
+        let mut paths = Map::new();
+        let histograms = self.path_histograms();
+        let slow = self.slow_request_stats();
+        for (path, (requests, avg_ns, min_ns, max_ns)) in self.path_stats() {
+            let duration = histograms
+                .get(&path)
+                .map(|(buckets, sum_ns, count)| histogram_json(buckets, *sum_ns, *count));
+            paths.insert(
+                path.clone(),
+                json!({
+                    "requests_total": requests,
+                    "statuses": {},
+                    "latency_seconds": {
+                        "avg": nanos_to_secs(avg_ns),
+                        "min": nanos_to_secs(min_ns),
+                        "max": nanos_to_secs(max_ns),
+                    },
+                    "slow_requests_total": slow.get(&path).copied().unwrap_or(0),
+                    "duration_seconds": duration,
+                }),
+            );
+        }
+        for ((path, status), count) in self.status_stats() {
+            let entry = paths
+                .entry(path)
+                .or_insert_with(|| json!({ "statuses": {} }));
+            entry["statuses"][status.to_string()] = json!(count);
+        }
+
+        let mut validation_failures: Vec<_> = self
+            .validation_failure_stats()
+            .into_iter()
+            .map(|((path, category), count)| (path, category.as_str(), count))
+            .collect();
+        validation_failures.sort_unstable();
+
+        json!({
+            "counters": {
+                "requests_total": self.request_count(),
+                "top_level_requests_total": self.top_level_request_count(),
+                "auth_failures_total": self.auth_failures(),
+                "connection_closes_total": self.connection_closes(),
+                "connection_errors_total": self.connection_errors(),
+                "cors_origin_rejections_total": self.cors_origin_rejections(),
+                "cors_preflight_denials_total": self.cors_preflight_denials(),
+                "cors_route_disabled_total": self.cors_route_disabled(),
+                "metrics_path_overflow_total": self.path_overflow_total(),
+            },
+            "gauges": {
+                "active_requests": self.active_requests(),
+                "average_latency_seconds": self.average_latency().as_secs_f64(),
+                "connection_health_ratio": self.connection_health_ratio(),
+                "coroutine_stack_bytes": stack_size,
+                "coroutine_stack_used_bytes": used_stack,
+            },
+            "histograms": {
+                "request_duration_seconds": histogram_json(&buckets, sum_ns, count),
+            },
+            "paths": paths,
+            "validation_failures": validation_failures
+                .into_iter()
+                .map(|(path, category, count)| json!({
+                    "path": path,
+                    "category": category.as_str(),
+                    "count": count,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
     /// Pre-register paths at service startup
     ///
     /// This method allows pre-registering known paths to avoid on-the-fly
@@ -509,6 +823,9 @@ impl MetricsMiddleware {
     pub fn pre_register_paths<S: AsRef<str>>(&self, paths: &[S]) {
         for path in paths {
             let path_str = path.as_ref();
+            if self.is_excluded(path_str) {
+                continue;
+            }
             self.path_metrics
                 .entry(path_str.to_string())
                 .or_insert_with(|| Arc::new(PathMetrics::new()));
@@ -550,6 +867,21 @@ impl MetricsMiddleware {
     }
 }
 
+fn nanos_to_secs(ns: u64) -> f64 {
+    ns as f64 / 1_000_000_000.0
+}
+
+/// A histogram as `{buckets: [{le, count}], sum, count}`, with the `+Inf` bucket last
+fn histogram_json(buckets: &[u64], sum_ns: u64, count: u64) -> Value {
+    let mut entries: Vec<Value> = HISTOGRAM_BUCKETS
+        .iter()
+        .zip(buckets)
+        .map(|(le, n)| json!({ "le": le, "count": n }))
+        .collect();
+    entries.push(json!({ "le": "+Inf", "count": count }));
+    json!({ "buckets": entries, "sum": nanos_to_secs(sum_ns), "count": count })
+}
+
 /// Metrics collection middleware implementation
 ///
 /// Automatically tracks request statistics using atomic operations for thread-safety.
@@ -578,12 +910,15 @@ impl Middleware for MetricsMiddleware {
     ///
     /// # Arguments
     ///
-    /// * `_req` - The incoming request (unused)
+    /// * `req` - The incoming request (checked against the excluded paths)
     ///
     /// # Returns
     ///
     /// Always returns `None` (never blocks requests)
-    fn before(&self, _req: &HandlerRequest) -> Option<HandlerResponse> {
+    fn before(&self, req: &HandlerRequest) -> Option<HandlerResponse> {
+        if self.is_excluded(&route_label(&req.path)) {
+            return None;
+        }
         self.request_count.fetch_add(1, Ordering::Relaxed);
         self.active_requests.fetch_add(1, Ordering::Relaxed);
         None
@@ -611,6 +946,10 @@ impl Middleware for MetricsMiddleware {
     /// - If not in coroutine: Records global stack size from May config
     /// - Used stack is always 0 (May doesn't expose actual usage)
     fn after(&self, req: &HandlerRequest, res: &mut HandlerResponse, latency: Duration) {
+        let path = route_label(&req.path);
+        if self.is_excluded(&path) {
+            return;
+        }
         // Decrement active requests
         self.active_requests.fetch_sub(1, Ordering::Relaxed);
 
@@ -620,11 +959,24 @@ impl Middleware for MetricsMiddleware {
         self.total_latency_ns
             .fetch_add(latency_ns, Ordering::Relaxed);
 
-        // Record per-path metrics
-        self.record_path_metrics(&req.path, latency_ns);
+        // Record per-path metrics, including slow-request detection
+        let threshold = self.slow_threshold_for(&req.method, &path);
+        let slow = !threshold.is_zero() && latency > threshold;
+        self.record_path(&path, latency_ns, slow);
+        if slow && self.log_slow_requests {
+            warn!(
+                request_id = %req.request_id,
+                method = %req.method,
+                path = %path,
+                status = res.status,
+                latency_ms = latency.as_millis() as u64,
+                threshold_ms = threshold.as_millis() as u64,
+                "Slow request"
+            );
+        }
 
         // Record status code metrics
-        self.record_status(&req.path, res.status);
+        self.record_status(&path, res.status);
 
         // Record duration histogram (for percentiles)
         self.duration_histogram.observe(latency_secs);
@@ -648,6 +1000,39 @@ impl Middleware for MetricsMiddleware {
 mod tests {
     use super::*;
 
+    #[test]
+    fn slow_threshold_uses_route_override_for_its_method_only() {
+        let metrics = MetricsMiddleware::new().with_slow_threshold(Duration::from_millis(500));
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "/reports".to_string(),
+            SmallVec::from_elem((Method::POST, Duration::from_millis(50)), 1),
+        );
+        metrics.route_slow_thresholds.store(Arc::new(overrides));
+
+        assert_eq!(
+            metrics.slow_threshold_for(&Method::POST, "/reports"),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            metrics.slow_threshold_for(&Method::GET, "/reports"),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            metrics.slow_threshold_for(&Method::POST, "/other"),
+            Duration::from_millis(500)
+        );
+
+        metrics.record_path("/reports", 80_000_000, true);
+        metrics.record_path("/reports", 1_000_000, false);
+        assert_eq!(metrics.slow_request_stats()["/reports"], 1);
+        let (buckets, _, count) = &metrics.path_histograms()["/reports"];
+        assert_eq!(*count, 2);
+        // 1ms lands in the first bucket, 80ms first in the 100ms bucket
+        assert_eq!(buckets[0], 1);
+        assert_eq!(buckets[4], 2);
+    }
+
     #[test]
     fn test_record_path_metrics_same_path() {
         let metrics = MetricsMiddleware::new();
@@ -696,6 +1081,31 @@ mod tests {
         assert_eq!(metrics.path_overflow_total(), 2);
     }
 
+    #[test]
+    fn validation_failures_are_keyed_by_route_and_category() {
+        let metrics = MetricsMiddleware::new();
+        metrics.inc_validation_failure("/pets", ValidationFailureCategory::MissingRequired);
+        metrics.inc_validation_failure("/pets", ValidationFailureCategory::MissingRequired);
+        metrics.inc_validation_failure("/pets/{id}", ValidationFailureCategory::Enum);
+        let stats = metrics.validation_failure_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[&(
+                "/pets".to_string(),
+                ValidationFailureCategory::MissingRequired
+            )],
+            2
+        );
+        assert_eq!(
+            stats[&("/pets/{id}".to_string(), ValidationFailureCategory::Enum)],
+            1
+        );
+        assert_eq!(
+            ValidationFailureCategory::AdditionalProperty.as_str(),
+            "additional_property"
+        );
+    }
+
     /// Disabling the cap (cap = 0) preserves the pre-Phase-0.3 behavior.
     #[test]
     fn record_path_metrics_cap_zero_disables_overflow() {
@@ -32,6 +32,21 @@
 //! - **[`CorsMiddleware`]** - Handles CORS headers and preflight requests
 //! - **[`MetricsMiddleware`]** - Collects Prometheus metrics
 //! - **[`TracingMiddleware`]** - Adds distributed tracing spans
+//! - **[`CompressionMiddleware`]** - Negotiates gzip response encoding
+//! - **[`RateLimitMiddleware`]** - Fixed-window rate limiting (`429` + `Retry-After`)
+//! - **[`SecurityHeadersMiddleware`]** - Adds `nosniff`, frame, referrer, HSTS and CSP headers
+//! - **[`ClaimsForwardingMiddleware`]** - Maps JWT claims to downstream headers (`x-forward-claims`)
+//! - **[`BodyLoggingMiddleware`]** - Logs redacted request/response bodies of selected routes
+//! - **[`ResponseCacheMiddleware`]** - Serves cached responses of selected `GET` routes
+//!
+//! Services built from `config.yaml` can list these under `middleware:`; see
+//! [`crate::server::build_middleware_chain`].
+//!
+//! ## Response transforms
+//!
+//! [`ResponseTransform`] is a narrower post-handler hook for rewriting the JSON body (e.g.
+//! [`FieldRedaction`] for PII). Transforms run before every `after` hook and before response
+//! validation; see the `transform` module docs for the full ordering.
 //!
 //! ## Creating Custom Middleware
 //!
@@ -74,29 +89,47 @@
 //! ```rust,ignore
 //! use brrtrouter::middleware::{AuthMiddleware, CorsMiddleware, MetricsMiddleware};
 //! use brrtrouter::server::AppService;
+//! use std::sync::Arc;
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! # let spec = brrtrouter::spec::load_spec("examples/openapi.yaml")?;
-//! # let router = brrtrouter::router::Router::from_spec(&spec);
-//! let mut service = AppService::new(router, spec);
-//!
-//! // Register middleware in order
-//! service.add_middleware(CorsMiddleware::new());
-//! service.add_middleware(AuthMiddleware::new());
-//! service.add_middleware(MetricsMiddleware::new());
+//! # let (routes, _slug) = brrtrouter::spec::load_spec("examples/openapi.yaml")?;
+//! # let router = brrtrouter::router::Router::new(routes);
+//! let service = AppService::builder()
+//!     .router(router)
+//!     .dispatcher(brrtrouter::dispatcher::Dispatcher::new())
+//!     .spec_path("examples/openapi.yaml")
+//!     // Register middleware in order
+//!     .middleware(Arc::new(CorsMiddleware::new()))
+//!     .middleware(Arc::new(AuthMiddleware::new()))
+//!     .middleware(Arc::new(MetricsMiddleware::new()))
+//!     .build()?;
 //! # Ok(())
 //! # }
 //! ```
 
 mod auth;
+mod body_logging;
+mod claims_forwarding;
+mod compression;
 mod core;
 mod cors;
 pub mod jwks;
 pub mod memory;
 mod metrics;
+mod rate_limit;
+mod response_cache;
+mod security_headers;
 mod tracing;
+mod transform;
 
 pub use auth::AuthMiddleware;
+pub use body_logging::{
+    BodyLogLevel, BodyLoggingConfig, BodyLoggingMiddleware, DEFAULT_MAX_LOGGED_BODY_BYTES, REDACTED,
+};
+pub(crate) use claims_forwarding::is_valid_header_name;
+pub use claims_forwarding::{ClaimsForwardingConfig, ClaimsForwardingMiddleware};
+pub(crate) use compression::accepts_encoding;
+pub use compression::{CompressionConfig, CompressionMiddleware};
 pub use core::Middleware;
 pub use cors::{
     build_route_cors_map, extract_route_cors_config, merge_route_policies_with_global_origins,
@@ -105,5 +138,9 @@ pub use cors::{
 };
 pub use jwks::JwksHeadersMiddleware;
 pub use memory::MemoryMiddleware;
-pub use metrics::MetricsMiddleware;
+pub use metrics::{MetricsMiddleware, ValidationFailureCategory, DEFAULT_SLOW_REQUEST_MS};
+pub use rate_limit::{RateLimitConfig, RateLimitMiddleware};
+pub use response_cache::{ResponseCacheConfig, ResponseCacheMiddleware};
+pub use security_headers::{SecurityHeadersConfig, SecurityHeadersMiddleware};
 pub use tracing::TracingMiddleware;
+pub use transform::{claims_have_scope, FieldRedaction, ResponseTransform};
@@ -0,0 +1,156 @@
+//! Fixed-window request rate limiting.
+//!
+//! Requests are counted per key in windows of `window_secs`. The key is the value of
+//! `key_header` or a single shared bucket when no header is configured or the request does
+//! not carry it. For list-valued headers such as `X-Forwarded-For` the last element is used:
+//! it was appended by the nearest proxy, whereas everything to its left is whatever the
+//! client sent. Requests over the limit short-circuit with `429 Too Many Requests` and a
+//! `Retry-After` header.
+//!
+//! At most `max_tracked_keys` keys are tracked. When the map is full, keys from past windows
+//! are dropped (at most once per window); if it is still full, requests with new keys share
+//! the shared bucket until the next window, so rotating keys cannot grow memory.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::middleware::Middleware;
+
+/// Bucket key used when no per-client key is available.
+const SHARED_BUCKET: &str = "";
+
+/// Settings for [`RateLimitMiddleware`] (`middleware: - name: rate_limit`).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Requests allowed per key in each window (must be at least 1).
+    pub requests_per_window: u32,
+    /// Window length in seconds (default 1, must be at least 1).
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// Request header identifying the client (e.g. `x-api-key`); unset = one shared bucket.
+    #[serde(default)]
+    pub key_header: Option<String>,
+    /// Upper bound on tracked keys; further keys share one bucket until the next window.
+    #[serde(default = "default_max_tracked_keys")]
+    pub max_tracked_keys: usize,
+}
+
+fn default_window_secs() -> u64 {
+    1
+}
+
+fn default_max_tracked_keys() -> usize {
+    10_000
+}
+
+/// Middleware enforcing [`RateLimitConfig`].
+pub struct RateLimitMiddleware {
+    limit: u32,
+    window: Duration,
+    key_header: Option<String>,
+    max_tracked_keys: usize,
+    started: Instant,
+    /// key → (window index, requests seen in that window)
+    buckets: DashMap<String, (u64, u32)>,
+    /// Window index + 1 of the last stale-key prune (0 = never)
+    pruned: AtomicU64,
+}
+
+impl RateLimitMiddleware {
+    /// Build the middleware. Zero values for `requests_per_window` / `window_secs` are
+    /// clamped to 1; [`crate::server::build_middleware_chain`] rejects them up front.
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            limit: config.requests_per_window.max(1),
+            window: Duration::from_secs(config.window_secs.max(1)),
+            key_header: config.key_header.clone(),
+            max_tracked_keys: config.max_tracked_keys.max(1),
+            started: Instant::now(),
+            buckets: DashMap::new(),
+            pruned: AtomicU64::new(0),
+        }
+    }
+
+    fn bucket_key<'r>(&self, req: &'r HandlerRequest) -> &'r str {
+        self.key_header
+            .as_deref()
+            .and_then(|name| req.get_header(name))
+            .and_then(|v| v.rsplit(',').next())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or(SHARED_BUCKET)
+    }
+
+    /// Count one request for `key`; returns seconds until the window resets when over limit.
+    fn hit(&self, key: &str) -> Option<u64> {
+        let elapsed = self.started.elapsed();
+        let window_secs = self.window.as_secs();
+        let window = elapsed.as_secs() / window_secs;
+
+        let bump = |(entry_window, count): &mut (u64, u32)| {
+            if *entry_window != window {
+                *entry_window = window;
+                *count = 0;
+            }
+            *count = count.saturating_add(1);
+            *count
+        };
+        if let Some(mut entry) = self.buckets.get_mut(key) {
+            let count = bump(entry.value_mut());
+            drop(entry);
+            return self.over_limit(count, window, elapsed);
+        }
+        let key = if self.has_room(window) {
+            key
+        } else {
+            SHARED_BUCKET
+        };
+        let count = bump(
+            self.buckets
+                .entry(key.to_string())
+                .or_insert((window, 0))
+                .value_mut(),
+        );
+        self.over_limit(count, window, elapsed)
+    }
+
+    /// Whether a new key may be tracked, dropping keys from past windows first when full.
+    fn has_room(&self, window: u64) -> bool {
+        if self.buckets.len() < self.max_tracked_keys {
+            return true;
+        }
+        // One O(n) prune per window, not one per new key.
+        if self.pruned.swap(window + 1, Ordering::Relaxed) != window + 1 {
+            self.buckets.retain(|_, (w, _)| *w == window);
+        }
+        self.buckets.len() < self.max_tracked_keys
+    }
+
+    fn over_limit(&self, count: u32, window: u64, elapsed: Duration) -> Option<u64> {
+        let window_secs = self.window.as_secs();
+        if count > self.limit {
+            Some(
+                ((window + 1) * window_secs)
+                    .saturating_sub(elapsed.as_secs())
+                    .max(1),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl Middleware for RateLimitMiddleware {
+    fn before(&self, req: &HandlerRequest) -> Option<HandlerResponse> {
+        let retry_after = self.hit(self.bucket_key(req))?;
+        let mut resp = HandlerResponse::error(429, "Too Many Requests");
+        resp.headers
+            .push((Arc::from("retry-after"), retry_after.to_string()));
+        Some(resp)
+    }
+}
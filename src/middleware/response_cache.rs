@@ -0,0 +1,256 @@
+//! In-process caching of whole handler responses for selected `GET` and `HEAD` routes.
+//!
+//! ```yaml
+//! middleware:
+//!   - name: response_cache
+//!     routes: [list_pets, get_pet]   # handler names (operationId)
+//!     ttl_secs: 30
+//!     max_entries: 1000
+//!     query_params: [limit, offset]  # default: every query parameter
+//!     vary_headers: [accept]
+//! ```
+//!
+//! Responses are keyed by method, handler, path parameters, the selected query parameters,
+//! the values of `vary_headers` and the authenticated identity (scheme, subject and scopes),
+//! so one caller's response is never served to another; authenticated requests whose provider
+//! reports no subject are not cached at all. The least recently used entry is
+//! dropped once `max_entries` are held, and an entry older than `ttl_secs` is never served.
+//!
+//! A hit is answered from `before`, so the handler is not invoked. Authentication runs in
+//! the service before dispatch: a request failing it never reaches the cache, hit or not.
+//! Only `200` responses are stored, and never one marked `Cache-Control: no-store` or
+//! carrying `Set-Cookie`. Responses of cached routes carry `X-Cache: HIT` with `Age` (whole
+//! seconds since the response was stored) or `X-Cache: MISS`.
+//!
+//! Register it before `compression` so entries hold the uncompressed response and are
+//! encoded per request. SSE and WebSocket routes are never cached: their bodies are streams.
+
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use http::Method;
+use lru::LruCache;
+use serde_json::json;
+
+use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderLookup};
+use crate::lock_poison;
+use crate::middleware::Middleware;
+use crate::spec::RouteMeta;
+
+// Lock name reported in `brrtrouter_lock_poison_total`.
+const ENTRIES_LOCK: &str = "response_cache.entries";
+
+/// Settings for [`ResponseCacheMiddleware`] (`middleware: - name: response_cache`).
+///
+/// | Field | Default | Meaning |
+/// |-------|---------|---------|
+/// | `routes` | empty | Handler names whose responses are cached |
+/// | `ttl_secs` | `60` | How long a stored response is served |
+/// | `max_entries` | `1024` | Responses held before the least recently used is dropped |
+/// | `query_params` | all | Query parameters that select the entry |
+/// | `vary_headers` | `[accept]` | Request headers that select the entry |
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResponseCacheConfig {
+    /// Handler names (`operationId`) to cache; routes not listed are never cached.
+    pub routes: Vec<String>,
+    /// Seconds a stored response is served (must be at least 1).
+    pub ttl_secs: u64,
+    /// Upper bound on stored responses (must be at least 1).
+    pub max_entries: usize,
+    /// Query parameters that are part of the key; unset = every query parameter.
+    pub query_params: Option<Vec<String>>,
+    /// Request headers whose values are part of the key (case-insensitive).
+    pub vary_headers: Vec<String>,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            ttl_secs: 60,
+            max_entries: 1024,
+            query_params: None,
+            vary_headers: vec!["accept".to_string()],
+        }
+    }
+}
+
+/// Key of a cacheable request, attached in `prepare`
+#[derive(Debug, Clone)]
+struct CacheKey(Arc<str>);
+
+/// A stored response and when it was stored
+struct Entry {
+    response: HandlerResponse,
+    stored: Instant,
+}
+
+/// Middleware caching the responses of the routes listed in [`ResponseCacheConfig::routes`].
+pub struct ResponseCacheMiddleware {
+    routes: HashSet<String>,
+    ttl: Duration,
+    query_params: Option<Vec<String>>,
+    vary_headers: Vec<String>,
+    entries: Mutex<LruCache<Arc<str>, Entry>>,
+}
+
+impl ResponseCacheMiddleware {
+    /// Build the middleware. Listed routes that are SSE or WebSocket operations are skipped
+    /// with a warning, as are names that match no route. Zero `ttl_secs` / `max_entries` are
+    /// clamped to 1; [`crate::server::build_middleware_chain`] rejects them up front.
+    pub fn new(config: &ResponseCacheConfig, routes: &[RouteMeta]) -> Self {
+        let cached = config
+            .routes
+            .iter()
+            .filter(|name| {
+                match routes.iter().find(|r| r.handler_name.as_ref() == name.as_str()) {
+                    Some(route) if route.sse || route.websocket => {
+                        tracing::warn!(handler = %name, "response cache skips streaming route");
+                        false
+                    }
+                    Some(_) => true,
+                    None => {
+                        tracing::warn!(handler = %name, "response cache route matches no operation");
+                        false
+                    }
+                }
+            })
+            .cloned()
+            .collect();
+        Self {
+            routes: cached,
+            ttl: Duration::from_secs(config.ttl_secs.max(1)),
+            query_params: config.query_params.clone(),
+            vary_headers: config.vary_headers.clone(),
+            entries: Mutex::new(LruCache::new(
+                NonZeroUsize::new(config.max_entries).unwrap_or(NonZeroUsize::MIN),
+            )),
+        }
+    }
+
+    /// Serve stored responses for `ttl` instead of `ttl_secs` (e.g. sub-second in tests)
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Number of responses currently stored, expired ones included until they are looked up
+    #[must_use]
+    pub fn len(&self) -> usize {
+        lock_poison::lock(&self.entries, ENTRIES_LOCK).len()
+    }
+
+    /// Whether no response is stored
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn caches(&self, req: &HandlerRequest) -> bool {
+        (req.method == Method::GET || req.method == Method::HEAD)
+            && self.routes.contains(&req.handler_name)
+            // Callers whose provider names no subject cannot be told apart.
+            && req
+                .auth_context
+                .as_ref()
+                .is_none_or(|ctx| ctx.subject.is_some())
+    }
+
+    fn key(&self, req: &HandlerRequest) -> Arc<str> {
+        let mut path: Vec<(&str, &str)> = req
+            .path_params
+            .iter()
+            .map(|(k, v)| (k.as_ref(), v.as_str()))
+            .collect();
+        path.sort_unstable();
+        let mut query: Vec<(&str, &str)> = req
+            .query_params
+            .iter()
+            .filter(|(k, _)| {
+                self.query_params
+                    .as_ref()
+                    .is_none_or(|names| names.iter().any(|n| n == k.as_ref()))
+            })
+            .map(|(k, v)| (k.as_ref(), v.as_str()))
+            .collect();
+        query.sort_unstable();
+        let headers: Vec<Vec<&str>> = self
+            .vary_headers
+            .iter()
+            .map(|name| req.headers.get_all(name).collect())
+            .collect();
+        let identity = req.auth_context.as_ref().map(|ctx| {
+            let mut scopes: Vec<&str> = ctx.scopes.iter().map(String::as_str).collect();
+            scopes.sort_unstable();
+            json!([ctx.scheme, ctx.subject, scopes])
+        });
+        // JSON keeps the parts unambiguous whatever characters the values contain.
+        Arc::from(
+            json!([
+                req.method.as_str(),
+                req.handler_name,
+                path,
+                query,
+                headers,
+                identity
+            ])
+            .to_string(),
+        )
+    }
+}
+
+/// Whether `res` may be stored: a `200` without `no-store` or `Set-Cookie`
+fn storable(res: &HandlerResponse) -> bool {
+    res.status == 200
+        && res.headers.get("set-cookie").is_none()
+        && !res.headers.get_all("cache-control").any(|v| {
+            v.split(',')
+                .any(|d| d.trim().eq_ignore_ascii_case("no-store"))
+        })
+}
+
+impl Middleware for ResponseCacheMiddleware {
+    fn prepare(&self, req: &mut HandlerRequest) {
+        if self.caches(req) {
+            let key = self.key(req);
+            req.extensions_mut().insert(CacheKey(key));
+        }
+    }
+
+    fn before(&self, req: &HandlerRequest) -> Option<HandlerResponse> {
+        let key = &req.extensions().get::<CacheKey>()?.0;
+        let mut entries = lock_poison::lock(&self.entries, ENTRIES_LOCK);
+        let age = entries.get(key)?.stored.elapsed();
+        if age >= self.ttl {
+            entries.pop(key);
+            return None;
+        }
+        let mut resp = entries.peek(key)?.response.clone();
+        drop(entries);
+        resp.set_header("age", age.as_secs().to_string());
+        resp.set_header("x-cache", "HIT".to_string());
+        Some(resp)
+    }
+
+    fn after(&self, req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        let Some(CacheKey(key)) = req.extensions().get::<CacheKey>() else {
+            return;
+        };
+        if res.get_header("x-cache").is_some() {
+            // Served from the cache by `before`
+            return;
+        }
+        if storable(res) {
+            let entry = Entry {
+                response: res.clone(),
+                stored: Instant::now(),
+            };
+            lock_poison::lock(&self.entries, ENTRIES_LOCK).put(key.clone(), entry);
+        }
+        res.set_header("x-cache", "MISS".to_string());
+    }
+}
@@ -0,0 +1,100 @@
+//! Baseline browser security headers for every response.
+//!
+//! Headers are rendered once at construction time; `after` only checks whether the handler
+//! already set a header (handler values always win) and pushes the precomputed pair.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::middleware::Middleware;
+
+/// Settings for [`SecurityHeadersMiddleware`] (`middleware: - name: security_headers`).
+///
+/// | Field | Default | Header |
+/// |-------|---------|--------|
+/// | `content_type_options` | `true` | `X-Content-Type-Options: nosniff` |
+/// | `frame_options` | `"DENY"` | `X-Frame-Options` (empty string disables) |
+/// | `referrer_policy` | `"no-referrer"` | `Referrer-Policy` (empty string disables) |
+/// | `hsts_max_age_secs` | unset | `Strict-Transport-Security: max-age=N` |
+/// | `hsts_include_subdomains` | `false` | appends `; includeSubDomains` to HSTS |
+/// | `content_security_policy` | unset | `Content-Security-Policy` |
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityHeadersConfig {
+    /// Send `X-Content-Type-Options: nosniff`.
+    pub content_type_options: bool,
+    /// `X-Frame-Options` value; empty disables the header.
+    pub frame_options: String,
+    /// `Referrer-Policy` value; empty disables the header.
+    pub referrer_policy: String,
+    /// HSTS `max-age`; only set this when the service is reached over HTTPS.
+    pub hsts_max_age_secs: Option<u64>,
+    /// Append `includeSubDomains` to the HSTS header.
+    pub hsts_include_subdomains: bool,
+    /// `Content-Security-Policy` value.
+    pub content_security_policy: Option<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_type_options: true,
+            frame_options: "DENY".to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            hsts_max_age_secs: None,
+            hsts_include_subdomains: false,
+            content_security_policy: None,
+        }
+    }
+}
+
+/// Middleware that adds security headers to responses that do not already carry them.
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersMiddleware {
+    headers: Vec<(Arc<str>, String)>,
+}
+
+impl SecurityHeadersMiddleware {
+    /// Build the middleware, rendering every enabled header once.
+    pub fn new(config: &SecurityHeadersConfig) -> Self {
+        let mut headers: Vec<(Arc<str>, String)> = Vec::new();
+        if config.content_type_options {
+            headers.push((Arc::from("x-content-type-options"), "nosniff".to_string()));
+        }
+        if !config.frame_options.is_empty() {
+            headers.push((Arc::from("x-frame-options"), config.frame_options.clone()));
+        }
+        if !config.referrer_policy.is_empty() {
+            headers.push((Arc::from("referrer-policy"), config.referrer_policy.clone()));
+        }
+        if let Some(max_age) = config.hsts_max_age_secs {
+            let value = if config.hsts_include_subdomains {
+                format!("max-age={max_age}; includeSubDomains")
+            } else {
+                format!("max-age={max_age}")
+            };
+            headers.push((Arc::from("strict-transport-security"), value));
+        }
+        if let Some(csp) = config.content_security_policy.as_ref() {
+            headers.push((Arc::from("content-security-policy"), csp.clone()));
+        }
+        Self { headers }
+    }
+}
+
+impl Default for SecurityHeadersMiddleware {
+    fn default() -> Self {
+        Self::new(&SecurityHeadersConfig::default())
+    }
+}
+
+impl Middleware for SecurityHeadersMiddleware {
+    fn after(&self, _req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        for (name, value) in &self.headers {
+            if res.get_header(name).is_none() {
+                res.headers.push((Arc::clone(name), value.clone()));
+            }
+        }
+    }
+}
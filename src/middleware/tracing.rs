@@ -4,6 +4,7 @@ use tracing::info_span;
 
 use super::Middleware;
 use crate::dispatcher::{HandlerRequest, HandlerResponse};
+use crate::router::route_label;
 
 /// Middleware for distributed tracing using the `tracing` crate
 ///
@@ -23,7 +24,7 @@ pub struct TracingMiddleware;
 ///
 /// 1. **`http_request`** span (in `before()`):
 ///    - `method`: HTTP method (GET, POST, etc.)
-///    - `path`: Request path
+///    - `path`: Route template of the request ([`route_label`])
 ///    - `handler`: Handler function name
 ///
 /// 2. **`http_response`** span (in `after()`):
@@ -72,7 +73,7 @@ impl Middleware for TracingMiddleware {
         let span = info_span!(
             "http_request",
             method = ?req.method,
-            path = %req.path,
+            path = %route_label(&req.path),
             handler = %req.handler_name
         );
 
@@ -105,7 +106,7 @@ impl Middleware for TracingMiddleware {
         let span = info_span!(
             "http_response",
             method = ?req.method,
-            path = %req.path,
+            path = %route_label(&req.path),
             handler = %req.handler_name,
             status = res.status,
             latency_ms = latency.as_millis() as u64
@@ -0,0 +1,124 @@
+//! Response body transforms (field filtering / redaction).
+//!
+//! A [`ResponseTransform`] sees the handler's response body as a parsed
+//! `serde_json::Value` and may mutate it in place. Transforms are registered on the
+//! dispatcher with [`crate::dispatcher::Dispatcher::add_response_transform`].
+//!
+//! ## Ordering
+//!
+//! 1. Handler (or a middleware `before` short-circuit) produces the `HandlerResponse`.
+//! 2. Response transforms run, in registration order.
+//! 3. [`Middleware::after`](crate::middleware::Middleware::after) hooks run, in registration
+//!    order — so [`CompressionMiddleware`](crate::middleware::CompressionMiddleware) sizes the
+//!    *transformed* body.
+//! 4. `AppService` validates the body against the OpenAPI response schema, so redacted output
+//!    must still satisfy the schema (redact optional properties only).
+//! 5. The response writer serializes the body and applies gzip encoding last.
+
+use serde_json::Value;
+
+use crate::dispatcher::HandlerRequest;
+
+/// Post-handler hook that may rewrite the JSON response body.
+///
+/// # Example
+///
+/// ```rust
+/// use brrtrouter::dispatcher::HandlerRequest;
+/// use brrtrouter::middleware::ResponseTransform;
+/// use serde_json::Value;
+///
+/// struct StripInternalIds;
+///
+/// impl ResponseTransform for StripInternalIds {
+///     fn transform(&self, _req: &HandlerRequest, _status: u16, body: &mut Value) {
+///         if let Some(obj) = body.as_object_mut() {
+///             obj.remove("internal_id");
+///         }
+///     }
+/// }
+/// ```
+pub trait ResponseTransform: Send + Sync {
+    /// Mutate `body` for the response to `req` with HTTP `status`.
+    fn transform(&self, req: &HandlerRequest, status: u16, body: &mut Value);
+}
+
+/// Whether JWT `claims` grant `scope`.
+///
+/// Reads the space-delimited `scope` claim (RFC 8693 §4.2) and the `scp` claim, which some
+/// identity providers emit as an array or a space-delimited string.
+#[must_use]
+pub fn claims_have_scope(claims: &Value, scope: &str) -> bool {
+    let in_str = |v: &Value| {
+        v.as_str()
+            .is_some_and(|s| s.split_whitespace().any(|s| s == scope))
+    };
+    claims.get("scope").is_some_and(in_str)
+        || claims.get("scp").is_some_and(|scp| match scp {
+            Value::Array(items) => items.iter().any(|i| i.as_str() == Some(scope)),
+            other => in_str(other),
+        })
+}
+
+/// Removes named fields (at any depth) unless the caller's token has an exempting scope.
+///
+/// ```rust
+/// use brrtrouter::middleware::FieldRedaction;
+///
+/// // Drop `ssn` and `email` everywhere in the body unless the token carries `pii:read`.
+/// let redaction = FieldRedaction::new(["ssn", "email"]).unless_scope("pii:read");
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldRedaction {
+    fields: Vec<String>,
+    exempt_scope: Option<String>,
+}
+
+impl FieldRedaction {
+    /// Redact `fields` from every object in the response body.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+            exempt_scope: None,
+        }
+    }
+
+    /// Skip redaction when the request's JWT claims grant `scope`.
+    #[must_use]
+    pub fn unless_scope(mut self, scope: impl Into<String>) -> Self {
+        self.exempt_scope = Some(scope.into());
+        self
+    }
+
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|k, _| !self.fields.iter().any(|f| f == k));
+                for v in map.values_mut() {
+                    self.redact(v);
+                }
+            }
+            Value::Array(items) => {
+                for v in items {
+                    self.redact(v);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ResponseTransform for FieldRedaction {
+    fn transform(&self, req: &HandlerRequest, _status: u16, body: &mut Value) {
+        if let (Some(scope), Some(claims)) = (self.exempt_scope.as_deref(), &req.jwt_claims) {
+            if claims_have_scope(claims, scope) {
+                return;
+            }
+        }
+        self.redact(body);
+    }
+}
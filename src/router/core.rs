@@ -22,6 +22,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info};
 
+use super::decode::PathDecoding;
 use super::radix::RadixRouter;
 
 /// Maximum number of path/query parameters before heap allocation.
@@ -53,9 +54,14 @@ pub type ParamVec = SmallVec<[(Arc<str>, String); MAX_INLINE_PARAMS]>;
 pub struct RouteMatch {
     /// The matched route metadata from the OpenAPI spec (Arc to avoid expensive clones)
     pub route: std::sync::Arc<RouteMeta>,
-    /// Path parameters extracted from the URL (e.g., `{id}` → `{"id": "123"}`)
+    /// Path parameters extracted from the URL (e.g., `{id}` → `{"id": "123"}`),
+    /// percent-decoded per the router's [`PathDecoding`]
     /// Stack-allocated for ≤8 params (JSF: no heap in hot path)
     pub path_params: ParamVec,
+    /// Path parameters as they appeared in the request path (e.g., `{"id": "a%2Fb"}` for
+    /// `id = "a/b"`), in the same order as `path_params`; left empty (no allocation)
+    /// when no value was percent-decoded
+    pub raw_path_params: ParamVec,
     /// Name of the handler that should process this request
     pub handler_name: String,
     /// Query string parameters (populated by the server)
@@ -84,6 +90,24 @@ impl RouteMatch {
             .map(|(_, v)| v.as_str())
     }
 
+    /// Get a path parameter by name, as it appeared in the request path
+    ///
+    /// Same as [`Self::get_path_param`] unless the value was percent-decoded, in which
+    /// case this is the text before decoding (e.g., `a%2Fb` for `a/b`).
+    #[inline]
+    #[must_use]
+    pub fn get_raw_path_param(&self, name: &str) -> Option<&str> {
+        let params = if self.raw_path_params.is_empty() {
+            &self.path_params
+        } else {
+            &self.raw_path_params
+        };
+        params
+            .iter()
+            .rfind(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_str())
+    }
+
     /// Get a query parameter by name
     ///
     /// Uses "last write wins" semantics: if duplicate query parameter names exist
@@ -124,6 +148,52 @@ impl RouteMatch {
     }
 }
 
+/// Path parameters as they appeared in the request path, for a request where at least one
+/// value was percent-decoded
+///
+/// The dispatcher inserts it into [`HandlerRequest::extensions`] from
+/// [`RouteMatch::raw_path_params`]; read it with
+/// [`HandlerRequest::get_raw_path_param`].
+///
+/// [`HandlerRequest::extensions`]: crate::dispatcher::HandlerRequest::extensions
+/// [`HandlerRequest::get_raw_path_param`]: crate::dispatcher::HandlerRequest::get_raw_path_param
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPathParams(pub ParamVec);
+
+/// HTTP methods a route can be registered for; routes with other methods are dropped.
+const SUPPORTED_METHODS: [Method; 8] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+    Method::OPTIONS,
+    Method::HEAD,
+    Method::TRACE,
+];
+
+/// `routes` plus a `HEAD` route for each `GET` operation whose path declares no `HEAD`
+///
+/// RFC 9110 requires `HEAD` wherever `GET` is served. The derived route is the `GET` one with
+/// its method set to `HEAD`, so the `GET` handler answers it (see [`HeadStrategy`]). Only
+/// the radix tree holds derived routes; [`Router::dump_routes`] lists the spec's own.
+///
+/// [`HeadStrategy`]: crate::spec::HeadStrategy
+fn with_derived_head(routes: &[RouteMeta]) -> Vec<RouteMeta> {
+    let mut all = routes.to_vec();
+    for route in routes.iter().filter(|r| r.method == Method::GET) {
+        let declared = routes
+            .iter()
+            .any(|r| r.method == Method::HEAD && r.path_pattern == route.path_pattern);
+        if !declared {
+            let mut head = route.clone();
+            head.method = Method::HEAD;
+            all.push(head);
+        }
+    }
+    all
+}
+
 /// Router that matches HTTP requests to handlers using radix tree
 ///
 /// Uses a radix tree (compact prefix tree) for O(k) route matching where k is the
@@ -164,20 +234,9 @@ impl Router {
     #[must_use]
     pub fn new(routes: Vec<RouteMeta>) -> Self {
         // Filter out routes that are not HTTP methods we care about
-        let supported_methods = [
-            Method::GET,
-            Method::POST,
-            Method::PUT,
-            Method::DELETE,
-            Method::PATCH,
-            Method::OPTIONS,
-            Method::HEAD,
-            Method::TRACE,
-        ];
-
         let routes: Vec<RouteMeta> = routes
             .into_iter()
-            .filter(|r| supported_methods.contains(&r.method))
+            .filter(|r| SUPPORTED_METHODS.contains(&r.method))
             .collect();
 
         if routes.is_empty() {
@@ -195,7 +254,7 @@ impl Router {
             .unwrap_or_default();
 
         // Create the radix tree router for fast O(k) lookups
-        let radix_router = RadixRouter::new(routes.clone());
+        let radix_router = RadixRouter::new(with_derived_head(&routes));
 
         // Also build the legacy regex-based routes for compatibility
         // (though we'll primarily use the radix tree)
@@ -231,6 +290,20 @@ impl Router {
         }
     }
 
+    /// Percent-decode request path segments per `decoding` (default
+    /// [`PathDecoding::Segment`]).
+    #[must_use]
+    pub fn with_path_decoding(mut self, decoding: PathDecoding) -> Self {
+        self.radix_router = self.radix_router.with_path_decoding(decoding);
+        self
+    }
+
+    /// Percent-decoding applied to request path segments
+    #[must_use]
+    pub fn path_decoding(&self) -> PathDecoding {
+        self.radix_router.path_decoding()
+    }
+
     /// Print all registered routes to stdout
     ///
     /// Useful for debugging and verifying that routes are loaded correctly.
@@ -249,6 +322,39 @@ impl Router {
         }
     }
 
+    /// All registered routes as JSON, for structured logs
+    ///
+    /// One object per route: `method`, `path` (with the base path), `handler`, and `auth`
+    /// — the route's security alternatives as `{scheme: scopes}` objects, empty for a public
+    /// route. The JSON counterpart of [`Router::dump_routes`].
+    #[must_use]
+    pub fn dump_routes_json(&self) -> serde_json::Value {
+        let routes = self
+            .routes
+            .iter()
+            .map(|(method, _re, meta, _params)| {
+                let auth: Vec<serde_json::Value> = meta
+                    .security
+                    .iter()
+                    .map(|req| {
+                        req.0
+                            .iter()
+                            .map(|(scheme, scopes)| (scheme.clone(), serde_json::json!(scopes)))
+                            .collect::<serde_json::Map<_, _>>()
+                            .into()
+                    })
+                    .collect();
+                serde_json::json!({
+                    "method": method.as_str(),
+                    "path": format!("{}{}", self.base_path, meta.path_pattern),
+                    "handler": meta.handler_name.as_ref(),
+                    "auth": auth,
+                })
+            })
+            .collect();
+        serde_json::Value::Array(routes)
+    }
+
     /// Match an HTTP request to a route using radix tree
     ///
     /// Uses the radix tree for O(k) route matching where k is the path length.
@@ -288,11 +394,11 @@ impl Router {
         let match_start = std::time::Instant::now();
 
         // Use radix tree for O(k) lookup
-        let result = self.radix_router.route(method.clone(), path);
+        let result = self.radix_router.route_with_raw(method.clone(), path);
 
         let match_duration = match_start.elapsed();
 
-        if let Some((route, params)) = result {
+        if let Some((route, params, raw_params)) = result {
             // RT3: Route matched
             // JSF P0-2: Convert Arc<str> to String for RouteMatch
             let handler_name = route.handler_name.to_string();
@@ -341,6 +447,7 @@ impl Router {
             return Some(RouteMatch {
                 route,
                 path_params: params,
+                raw_path_params: raw_params,
                 handler_name,
                 query_params: Default::default(),
             });
@@ -363,6 +470,19 @@ impl Router {
 
         None
     }
+    /// Methods that have a route for `path` (GET, POST, PUT, DELETE, PATCH, OPTIONS, HEAD, TRACE order).
+    ///
+    /// Empty when no route matches the path at all. The server calls this only after
+    /// [`Self::route`] missed, to answer 405 (with `Allow`) instead of 404.
+    #[must_use]
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        SUPPORTED_METHODS
+            .iter()
+            .filter(|method| self.radix_router.route((*method).clone(), path).is_some())
+            .cloned()
+            .collect()
+    }
+
     /// Get all registered path patterns for metrics pre-registration
     ///
     /// Returns a list of all path patterns (with base path prepended) that are
@@ -390,6 +510,13 @@ impl Router {
             .collect()
     }
 
+    /// Metadata of every registered route, in registration order.
+    pub fn routes(&self) -> impl Iterator<Item = &RouteMeta> {
+        self.routes
+            .iter()
+            .map(|(_method, _regex, meta, _params)| meta.as_ref())
+    }
+
     /// Convert an OpenAPI path pattern to a regex and extract parameter names
     ///
     /// Transforms path patterns like `/users/{id}` into regex patterns like
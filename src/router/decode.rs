@@ -0,0 +1,57 @@
+//! Percent-decoding of request path segments before matching.
+
+use std::borrow::Cow;
+
+/// How request path segments are percent-decoded before matching (config.yaml
+/// `http.path_decoding`).
+///
+/// With [`PathDecoding::Segment`] (the default) the path is split on literal `/` first and
+/// each segment is then percent-decoded on its own, so:
+///
+/// - `/pets/a%2Fb` is two segments and matches `/pets/{id}` with `id = "a/b"` — an encoded
+///   slash never creates a new segment and cannot reach a sibling route or a parent
+///   directory;
+/// - `/p%65ts/1` matches the static segment `pets`;
+/// - `/pets/%7Bid%7D` matches `/pets/{id}` with `id = "{id}"`; route templates are never
+///   re-read from request data;
+/// - every segment is decoded exactly once: `/pets/rex%2520` gives `id = "rex%20"`.
+///
+/// A decoded value may contain `/`, `..` or other bytes that are significant in a path;
+/// handlers that build file paths or downstream URLs from it must treat it as data. The
+/// undecoded text of each parameter stays available through
+/// [`RouteMatch::get_raw_path_param`](super::RouteMatch::get_raw_path_param) and
+/// [`HandlerRequest::get_raw_path_param`](crate::dispatcher::HandlerRequest::get_raw_path_param).
+///
+/// A segment whose escapes do not decode to UTF-8 is matched as sent. [`PathDecoding::Raw`]
+/// turns decoding off: segments are compared and extracted exactly as sent.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PathDecoding {
+    /// Split on `/`, then decode each segment once
+    #[default]
+    Segment,
+    /// Match and extract segments exactly as sent
+    Raw,
+}
+
+impl PathDecoding {
+    /// Config name (`segment`, `raw`)
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Segment => "segment",
+            Self::Raw => "raw",
+        }
+    }
+
+    /// `segment` as this policy matches it; borrowed unless it was decoded.
+    #[inline]
+    pub(crate) fn apply(self, segment: &str) -> Cow<'_, str> {
+        if self == Self::Raw || !segment.contains('%') {
+            return Cow::Borrowed(segment);
+        }
+        urlencoding::decode(segment).unwrap_or(Cow::Borrowed(segment))
+    }
+}
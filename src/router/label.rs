@@ -0,0 +1,72 @@
+//! Route labels: the one spelling of a matched route used for metrics and tracing.
+//!
+//! The metrics `path` label, the `http.route` span field and the [`TracingMiddleware`] `path`
+//! all come from [`route_label`] applied to the matched [`RouteMeta::path_pattern`], never
+//! from the request path, so `/pets/123` and `/pets/456` are both `/pets/{id}` and the label
+//! set stays bounded by the number of routes.
+//!
+//! [`TracingMiddleware`]: crate::middleware::TracingMiddleware
+//! [`RouteMeta::path_pattern`]: crate::spec::RouteMeta::path_pattern
+
+use std::borrow::Cow;
+
+/// RFC 6570 operators that may open a template expression (`{+rest}`, `{;id}`, `{/path}`)
+const OPERATORS: &[char] = &['+', '#', '.', '/', ';', '?', '&', '=', ',', '!', '@', '|'];
+
+/// Label of the route template `template`
+///
+/// Every `{...}` expression is reduced to `{name}`: RFC 6570 operators and modifiers are
+/// dropped, so wildcard-style `{+rest}`, `{rest*}` and `{/rest*}` all render as `{rest}`, and
+/// matrix-style `{;id}` as `{id}`. Static segments, trailing slashes and plain `{id}`
+/// expressions are kept as written; the common case borrows `template` unchanged.
+///
+/// ```
+/// use brrtrouter::router::route_label;
+///
+/// assert_eq!(route_label("/pets/{id}"), "/pets/{id}");
+/// assert_eq!(route_label("/files/{+rest}"), "/files/{rest}");
+/// assert_eq!(route_label("/files/{rest*}"), "/files/{rest}");
+/// assert_eq!(route_label("/colors/{;color}"), "/colors/{color}");
+/// ```
+#[must_use]
+pub fn route_label(template: &str) -> Cow<'_, str> {
+    let canonical = expressions(template).all(|expr| expression_name(expr) == expr);
+    if canonical {
+        return Cow::Borrowed(template);
+    }
+    let mut label = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some((before, expr, after)) = split_expression(rest) {
+        label.push_str(before);
+        label.push('{');
+        label.push_str(expression_name(expr));
+        label.push('}');
+        rest = after;
+    }
+    label.push_str(rest);
+    Cow::Owned(label)
+}
+
+/// The text between each `{` and its `}` in `template`
+fn expressions(template: &str) -> impl Iterator<Item = &str> {
+    let mut rest = template;
+    std::iter::from_fn(move || {
+        let (_, expr, after) = split_expression(rest)?;
+        rest = after;
+        Some(expr)
+    })
+}
+
+/// `text` split around its first complete `{...}` expression
+fn split_expression(text: &str) -> Option<(&str, &str, &str)> {
+    let open = text.find('{')?;
+    let close = open + text[open..].find('}')?;
+    Some((&text[..open], &text[open + 1..close], &text[close + 1..]))
+}
+
+/// Variable name of an expression: no leading operator, `:N` prefix or trailing `*`
+fn expression_name(expr: &str) -> &str {
+    let name = expr.trim_start_matches(OPERATORS);
+    let name = name.split_once(':').map_or(name, |(name, _)| name);
+    name.trim_end_matches('*')
+}
@@ -9,7 +9,8 @@
 //! The router is responsible for:
 //! - Building routing tables from OpenAPI path specifications
 //! - Matching incoming HTTP requests to registered routes
-//! - Extracting path parameters from matched routes
+//! - Extracting path parameters from matched routes, percent-decoded per segment
+//!   ([`PathDecoding`])
 //! - Providing route metadata for downstream processing
 //!
 //! ## Architecture
@@ -64,10 +65,14 @@
 //! with regex matching, particularly for applications with many routes.
 
 mod core;
+mod decode;
+mod label;
 #[cfg(test)]
 mod performance_tests;
 mod radix;
 #[cfg(test)]
 mod tests;
 
-pub use core::{ParamVec, RouteMatch, Router, MAX_INLINE_PARAMS};
+pub use core::{ParamVec, RawPathParams, RouteMatch, Router, MAX_INLINE_PARAMS};
+pub use decode::PathDecoding;
+pub use label::route_label;
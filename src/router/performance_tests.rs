@@ -16,6 +16,19 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         x_service: None,
         x_brrtrouter_downstream_path: None,
         x_brrtrouter_impl: None,
+        auth_optional: false,
+        forward_claims: None,
+        tags: Vec::new(),
+        deprecated: false,
+        websocket: false,
+        links: Vec::new(),
+        declared_statuses: Vec::new(),
+        schema_overrides: Default::default(),
+        created_location: None,
+        response_headers: Default::default(),
+        upload_limits: Default::default(),
+        feature: None,
+        head_strategy: Default::default(),
         method,
         path_pattern: Arc::from(path),
         handler_name: Arc::from(handler),
@@ -26,6 +39,7 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         request_content_types: Vec::new(),
         response_schema: None,
         example: None,
+        examples: Vec::new(),
         responses: HashMap::new(),
         security: Vec::new(),
         example_name: "test_example".to_string(),
@@ -34,6 +48,8 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         sse: false,
         estimated_request_body_bytes: None,
         x_brrtrouter_stack_size: None,
+        x_brrtrouter_timeout_ms: None,
+        x_slow_threshold_ms: None,
         cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
     }
 }
@@ -63,6 +63,7 @@ use std::borrow::Cow;
 use std::sync::Arc;
 
 use super::core::ParamVec;
+use super::decode::PathDecoding;
 use crate::spec::RouteMeta;
 
 /// Lazy path-segment iterator (PRD Phase R.2).
@@ -310,19 +311,25 @@ impl RadixNode {
         &self,
         mut segments: PathCursor<'_>,
         method: &Method,
+        decoding: PathDecoding,
         params: &mut ParamVec,
+        raw_params: &mut ParamVec,
     ) -> Option<Arc<RouteMeta>> {
-        let Some(segment) = segments.next_segment() else {
+        let Some(raw_segment) = segments.next_segment() else {
             // End of path — check if this node has a route for the method.
             // O(1) array index (PRD Phase R.1) in place of the pre-R.1 HashMap.
             return self.routes.get(method).cloned();
         };
+        // Decoded after splitting, so `%2F` stays inside its segment. Borrowed (no
+        // allocation) unless the segment contains an escape.
+        let segment = decoding.apply(raw_segment);
+        let decoded = matches!(segment, Cow::Owned(_));
 
         // First, try exact match with static children. Pass `segments` by
         // value — its `Copy` impl gives each branch an independent cursor.
         for child in &self.children {
             if child.segment == segment {
-                if let Some(route) = child.search(segments, method, params) {
+                if let Some(route) = child.search(segments, method, decoding, params, raw_params) {
                     return Some(route);
                 }
             }
@@ -334,12 +341,26 @@ impl RadixNode {
             if let Some(ref param_name) = param_child.param_name {
                 // JSF Optimization (P0): Arc::clone() is O(1) atomic increment
                 // vs O(n) string copy for param names.
+                // `raw_params` stays empty until a value is decoded; from then on it runs
+                // parallel to `params`, starting with copies of the earlier (undecoded) values.
+                if decoded && raw_params.is_empty() {
+                    raw_params.extend(params.iter().cloned());
+                }
+                let track_raw = !raw_params.is_empty();
                 params.push((Arc::clone(param_name), segment.to_string()));
-                if let Some(route) = param_child.search(segments, method, params) {
+                if track_raw {
+                    raw_params.push((Arc::clone(param_name), raw_segment.to_string()));
+                }
+                if let Some(route) =
+                    param_child.search(segments, method, decoding, params, raw_params)
+                {
                     return Some(route);
                 }
                 // Backtrack.
                 params.pop();
+                if track_raw {
+                    raw_params.pop();
+                }
             }
         }
 
@@ -368,6 +389,8 @@ pub struct RadixRouter {
     /// Base path prefix for all routes (e.g., `/api/v1`)
     #[allow(dead_code)]
     base_path: String,
+    /// Percent-decoding applied to each request segment before matching
+    decoding: PathDecoding,
 }
 
 impl RadixRouter {
@@ -421,7 +444,23 @@ impl RadixRouter {
             root.insert(&segments, method, Arc::new(route));
         }
 
-        Self { root, base_path }
+        Self {
+            root,
+            base_path,
+            decoding: PathDecoding::default(),
+        }
+    }
+
+    /// Use `decoding` for request segments (default [`PathDecoding::Segment`]).
+    #[must_use]
+    pub fn with_path_decoding(mut self, decoding: PathDecoding) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    /// Percent-decoding applied to request segments
+    pub fn path_decoding(&self) -> PathDecoding {
+        self.decoding
     }
 
     /// Match an HTTP request to a route
@@ -451,10 +490,25 @@ impl RadixRouter {
     /// never touches segments 3..N.
     #[inline]
     pub fn route(&self, method: Method, path: &str) -> Option<(Arc<RouteMeta>, ParamVec)> {
+        self.route_with_raw(method, path)
+            .map(|(route, params, _)| (route, params))
+    }
+
+    /// [`Self::route`], also returning the parameters as they appeared in the path:
+    /// parallel to the decoded ones, or empty when no value was percent-decoded.
+    #[inline]
+    pub fn route_with_raw(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Option<(Arc<RouteMeta>, ParamVec, ParamVec)> {
         let cursor = PathCursor::new(path);
         let mut params = ParamVec::new();
-        let route = self.root.search(cursor, &method, &mut params)?;
-        Some((route, params))
+        let mut raw_params = ParamVec::new();
+        let route =
+            self.root
+                .search(cursor, &method, self.decoding, &mut params, &mut raw_params)?;
+        Some((route, params, raw_params))
     }
 }
 
@@ -479,6 +533,19 @@ mod tests {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method,
             path_pattern: Arc::from(path),
             handler_name: Arc::from(handler),
@@ -489,6 +556,7 @@ mod tests {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses: HashMap::new(),
             security: Vec::new(),
             example_name: "test_example".to_string(),
@@ -497,6 +565,8 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
         }
     }
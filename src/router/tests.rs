@@ -14,6 +14,19 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         x_service: None,
         x_brrtrouter_downstream_path: None,
         x_brrtrouter_impl: None,
+        auth_optional: false,
+        forward_claims: None,
+        tags: Vec::new(),
+        deprecated: false,
+        websocket: false,
+        links: Vec::new(),
+        declared_statuses: Vec::new(),
+        schema_overrides: Default::default(),
+        created_location: None,
+        response_headers: Default::default(),
+        upload_limits: Default::default(),
+        feature: None,
+        head_strategy: Default::default(),
         method,
         path_pattern: Arc::from(path),
         handler_name: Arc::from(handler),
@@ -24,6 +37,7 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         request_content_types: Vec::new(),
         response_schema: None,
         example: None,
+        examples: Vec::new(),
         responses: HashMap::new(),
         security: Vec::new(),
         example_name: "test_example".to_string(),
@@ -32,6 +46,8 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         sse: false,
         estimated_request_body_bytes: None,
         x_brrtrouter_stack_size: None,
+        x_brrtrouter_timeout_ms: None,
+        x_slow_threshold_ms: None,
         cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
     }
 }
@@ -304,6 +320,43 @@ fn test_router_different_param_names_same_position() {
     assert!(match3.get_path_param("user_id").is_none());
 }
 
+#[test]
+fn test_allowed_methods() {
+    let routes = vec![
+        create_route_meta(Method::GET, "/users", "list_users"),
+        create_route_meta(Method::POST, "/users", "create_user"),
+        create_route_meta(Method::DELETE, "/users/{id}", "delete_user"),
+    ];
+    let router = Router::new(routes);
+
+    assert_eq!(
+        router.allowed_methods("/users"),
+        vec![Method::GET, Method::POST, Method::HEAD]
+    );
+    assert_eq!(router.allowed_methods("/users/7"), vec![Method::DELETE]);
+    assert!(router.allowed_methods("/nope").is_empty());
+}
+
+#[test]
+fn test_head_is_derived_from_get() {
+    let routes = vec![
+        create_route_meta(Method::GET, "/users/{id}", "get_user"),
+        create_route_meta(Method::GET, "/pets", "list_pets"),
+        create_route_meta(Method::HEAD, "/pets", "head_pets"),
+    ];
+    let router = Router::new(routes);
+
+    let derived = router.route(Method::HEAD, "/users/7").unwrap();
+    assert_eq!(derived.handler_name, "get_user");
+    assert_eq!(derived.route.method, Method::HEAD);
+    assert_eq!(derived.get_path_param("id"), Some("7"));
+    // A declared HEAD operation is kept
+    let declared = router.route(Method::HEAD, "/pets").unwrap();
+    assert_eq!(declared.handler_name, "head_pets");
+    // Derived routes are not spec routes
+    assert_eq!(router.dump_routes_json().as_array().unwrap().len(), 3);
+}
+
 #[test]
 fn test_get_all_path_patterns() {
     // Test that we can extract all path patterns from a router
@@ -27,10 +27,27 @@
 //! - Typical handler uses ~3.5 KB; 32 KB provides 4x safety margin
 //! - Tune based on your handler complexity and concurrency needs
 //!
-//! ### `BRRTR_MAY_WORKERS`
+//! ### `BRRTR_STACK_GUARD`
+//!
+//! Tracks the peak stack use of each handler coroutine
+//! (`brrtrouter_coroutine_stack_high_water_bytes{handler}` on the metrics endpoint) and puts
+//! a [`StackBudget`](crate::dispatcher::StackBudget) in every request's extensions, so deep
+//! code can answer `500` instead of overflowing its stack. Read when handlers are
+//! registered; see [`crate::dispatcher::stack_guard`] for platform limitations.
+//!
+//! Default: `off`
+//!
+//! ### `BRRTR_WORKERS` (alias `BRRTR_MAY_WORKERS`)
 //!
 //! Sets the **may** scheduler worker thread count. Must be called before the first `go!` /
-//! coroutine (see `may::config::set_workers`).
+//! coroutine (see `may::config::set_workers`). Overrides config.yaml `runtime.worker_threads`
+//! (see [`RuntimeConfig::with_worker_threads`]). Lower it to match a container CPU limit or to
+//! pin the server to a set of cores; raise it when handlers block.
+//!
+//! **Interaction with `BRRTR_STACK_SIZE`:** coroutine stacks are allocated per in-flight
+//! request, not per worker, so memory is roughly `concurrent requests × stack size` whatever
+//! the worker count. Workers are OS threads; they cap how many coroutines run at once and
+//! each costs one thread stack.
 //!
 //! **Why this matters with Lifeguard + `may_postgres`:** HTTP handlers run on may workers and
 //! block on `PooledLifeExecutor` reply channels. Pool threads run queries that schedule
@@ -39,8 +56,10 @@
 //!
 //! Default when unset: `max(32, available_parallelism + DB_POOL_MAX + 16)` where `DB_POOL_MAX`
 //! defaults to `10` if unset (matching typical Lifeguard pool sizing).
+//! Setting it to the core count is only safe when no handler blocks on work that itself needs
+//! a may worker.
 //!
-//! Override example: `export BRRTR_MAY_WORKERS=64`
+//! Override example: `export BRRTR_WORKERS=64`
 //!
 //! ### `BRRTR_SCHEMA_CACHE`
 //!
@@ -55,6 +74,49 @@
 //! - Reduces memory allocations for validation
 //! - Can be disabled for debugging or if issues arise
 //!
+//! ### `BRRTR_TRUSTED_PROXIES`
+//!
+//! Comma-separated CIDRs or addresses of reverse proxies / load balancers whose
+//! `Forwarded` / `X-Forwarded-For` entries are trusted by
+//! [`HandlerRequest::client_ip`](crate::dispatcher::HandlerRequest::client_ip).
+//! Invalid entries are logged and skipped.
+//!
+//! Default: empty (forwarding headers are never trusted)
+//!
+//! Example: `export BRRTR_TRUSTED_PROXIES=10.0.0.0/8,fd00::/8,192.0.2.10`
+//!
+//! ### `BRRTR_RESPONSE_VALIDATION`
+//!
+//! What to do when a handler's JSON body does not match the response schema declared for its
+//! status (array items and nested objects included). Accepts `fail`, `warn`, `off`.
+//!
+//! Default: `fail` (answer 500 with the JSON pointer of the first violation)
+//!
+//! ### `BRRTR_PRETTY_JSON`
+//!
+//! Indent JSON response bodies (handler responses and problem details). Accepts `on`, `off`,
+//! `true`, `false`, `1`, `0`. Indentation only changes the bytes on the wire: `Content-Length`
+//! and gzip encoding follow the indented body.
+//!
+//! Default: `off` (compact JSON)
+//!
+//! ### `BRRTR_DEV_MODE`
+//!
+//! Enables development conveniences that should not be reachable in production. Currently:
+//! a `__pretty=1` (or `__pretty=true`) query parameter indents that request's JSON response,
+//! whatever `BRRTR_PRETTY_JSON` says.
+//!
+//! Default: `off`
+//!
+//! ### `BRRTR_FEATURES`
+//!
+//! Comma-separated features to enable (e.g. `admin,beta`). An operation marked
+//! `x-feature: admin` is only served while `admin` is enabled; otherwise its route is left
+//! out of the router (answered `404`) and needs no handler. See
+//! [`crate::spec::enabled_routes`].
+//!
+//! Default: none (only routes without `x-feature` are served)
+//!
 //! ## Usage
 //!
 //! ```rust
@@ -90,13 +152,43 @@
 //! - Complex logic: `0x8000` (32 KB)
 //! - Deep recursion: `0x10000` (64 KB)
 
+use std::collections::BTreeSet;
 use std::env;
 
+use ipnet::IpNet;
+
+/// Handling of handler responses that violate their declared response schema.
+///
+/// The same mode applies to an explicit non-200 success status (e.g. `typed::Created`) that
+/// the operation does not declare.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseValidationMode {
+    /// Replace the body with a 500 problem listing the violations (default)
+    #[default]
+    Fail,
+    /// Log the violations and send the handler's response unchanged
+    Warn,
+    /// Skip response validation entirely
+    Off,
+}
+
+impl ResponseValidationMode {
+    /// Parse `fail` / `warn` / `off` (case-insensitive); anything else is `None`.
+    pub fn parse(val: &str) -> Option<Self> {
+        match val.trim().to_ascii_lowercase().as_str() {
+            "fail" | "strict" => Some(Self::Fail),
+            "warn" | "log" => Some(Self::Warn),
+            "off" | "false" | "0" | "no" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
 /// Runtime configuration loaded from environment variables.
 ///
 /// Load this at startup using [`RuntimeConfig::from_env()`] to configure
 /// the coroutine runtime behavior.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     /// Stack size for coroutines in bytes (default: 32 KB / 0x8000)
     /// Optimal for typical handlers (~3.5 KB used) with 4x safety margin
@@ -106,6 +198,19 @@ pub struct RuntimeConfig {
     pub schema_cache_enabled: bool,
     /// May scheduler worker threads (`may::config().set_workers`). Minimum 2 when applied.
     pub may_workers: usize,
+    /// Proxies whose forwarding headers are trusted for client IP resolution (default: none)
+    pub trusted_proxies: Vec<IpNet>,
+    /// Response schema enforcement (default: [`ResponseValidationMode::Fail`])
+    pub response_validation: ResponseValidationMode,
+    /// Declared response header enforcement (default: [`ResponseValidationMode::Off`]); see
+    /// [`crate::server::response_headers`]
+    pub response_header_validation: ResponseValidationMode,
+    /// Indent JSON response bodies (default: false)
+    pub pretty_json: bool,
+    /// Development conveniences such as the `?__pretty=1` override (default: false)
+    pub dev_mode: bool,
+    /// Features whose `x-feature` routes are served (default: none)
+    pub enabled_features: BTreeSet<String>,
 }
 
 impl RuntimeConfig {
@@ -159,25 +264,179 @@ impl RuntimeConfig {
             Err(_) => true, // Default to enabled
         };
 
-        let cpus = std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(8)
-            .max(1);
-        let db_pool_max = env::var("DB_POOL_MAX")
-            .ok()
-            .and_then(|s| s.parse::<usize>().ok())
-            .filter(|&n| n >= 1)
-            .unwrap_or(10);
-        let default_may_workers = (cpus + db_pool_max + 16).max(32);
-        let may_workers = match env::var("BRRTR_MAY_WORKERS") {
-            Ok(val) => val.parse::<usize>().unwrap_or(default_may_workers).max(2),
-            Err(_) => default_may_workers.max(2),
+        let may_workers = workers_from_env()
+            .unwrap_or_else(default_may_workers)
+            .max(2);
+
+        let trusted_proxies = env::var("BRRTR_TRUSTED_PROXIES")
+            .map(|val| parse_trusted_proxies(&val))
+            .unwrap_or_default();
+
+        let response_validation = match env::var("BRRTR_RESPONSE_VALIDATION") {
+            Ok(val) => ResponseValidationMode::parse(&val).unwrap_or_else(|| {
+                tracing::warn!(value = %val, "ignoring invalid BRRTR_RESPONSE_VALIDATION");
+                ResponseValidationMode::default()
+            }),
+            Err(_) => ResponseValidationMode::default(),
+        };
+
+        let response_header_validation = match env::var("BRRTR_RESPONSE_HEADER_VALIDATION") {
+            Ok(val) => ResponseValidationMode::parse(&val).unwrap_or_else(|| {
+                tracing::warn!(value = %val, "ignoring invalid BRRTR_RESPONSE_HEADER_VALIDATION");
+                ResponseValidationMode::Off
+            }),
+            Err(_) => ResponseValidationMode::Off,
         };
 
         RuntimeConfig {
             stack_size,
             schema_cache_enabled,
             may_workers,
+            trusted_proxies,
+            response_validation,
+            response_header_validation,
+            pretty_json: env_flag("BRRTR_PRETTY_JSON"),
+            dev_mode: env_flag("BRRTR_DEV_MODE"),
+            enabled_features: enabled_features_from_env(),
         }
     }
+
+    /// Apply config.yaml `runtime.worker_threads`.
+    ///
+    /// `BRRTR_WORKERS` / `BRRTR_MAY_WORKERS` still win, so a deployment can override the
+    /// file (e.g. to match a container CPU limit). Values below 2 are raised to 2.
+    #[must_use]
+    pub fn with_worker_threads(mut self, worker_threads: Option<usize>) -> Self {
+        if let (Some(n), None) = (worker_threads, workers_from_env()) {
+            self.may_workers = n.max(2);
+        }
+        self
+    }
+}
+
+/// Whether boolean variable `name` is set to `1` / `true` / `yes` / `on` (case-insensitive).
+fn env_flag(name: &str) -> bool {
+    env::var(name).map(|v| parse_flag(&v)).unwrap_or(false)
+}
+
+fn parse_flag(val: &str) -> bool {
+    matches!(
+        val.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// Features named in `BRRTR_FEATURES`; empty when unset.
+pub fn enabled_features_from_env() -> BTreeSet<String> {
+    env::var("BRRTR_FEATURES")
+        .map(|val| parse_features(&val))
+        .unwrap_or_default()
+}
+
+/// Parse a comma-separated feature list, trimming names and skipping empty entries.
+fn parse_features(val: &str) -> BTreeSet<String> {
+    val.split(',')
+        .map(str::trim)
+        .filter(|feature| !feature.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `BRRTR_WORKERS`, else the older `BRRTR_MAY_WORKERS`; unparsable values count as unset.
+fn workers_from_env() -> Option<usize> {
+    ["BRRTR_WORKERS", "BRRTR_MAY_WORKERS"]
+        .iter()
+        .find_map(|name| env::var(name).ok())
+        .and_then(|val| val.trim().parse::<usize>().ok())
+}
+
+/// `max(32, available_parallelism + DB_POOL_MAX + 16)`; see the module docs.
+fn default_may_workers() -> usize {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(8)
+        .max(1);
+    let db_pool_max = env::var("DB_POOL_MAX")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&n| n >= 1)
+        .unwrap_or(10);
+    (cpus + db_pool_max + 16).max(32)
+}
+
+/// Parse a comma-separated list of CIDRs or bare addresses, skipping invalid entries.
+fn parse_trusted_proxies(val: &str) -> Vec<IpNet> {
+    val.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .parse::<IpNet>()
+                .ok()
+                .or_else(|| entry.parse::<std::net::IpAddr>().ok().map(IpNet::from));
+            if parsed.is_none() {
+                tracing::warn!(entry, "ignoring invalid BRRTR_TRUSTED_PROXIES entry");
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trusted_proxies_accept_cidrs_and_addresses() {
+        let nets = parse_trusted_proxies(" 10.0.0.0/8, 192.0.2.10 ,bogus,,fd00::/8");
+        assert_eq!(nets.len(), 3);
+        assert!(nets[1].contains(&"192.0.2.10".parse::<std::net::IpAddr>().unwrap()));
+        assert_eq!(nets[1].prefix_len(), 32);
+    }
+
+    #[test]
+    fn response_validation_mode_parses() {
+        assert_eq!(
+            ResponseValidationMode::parse(" WARN "),
+            Some(ResponseValidationMode::Warn)
+        );
+        assert_eq!(
+            ResponseValidationMode::parse("off"),
+            Some(ResponseValidationMode::Off)
+        );
+        assert_eq!(
+            ResponseValidationMode::parse("fail"),
+            Some(ResponseValidationMode::Fail)
+        );
+        assert_eq!(ResponseValidationMode::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn flags_parse() {
+        assert!(parse_flag(" ON "));
+        assert!(parse_flag("1"));
+        assert!(parse_flag("true"));
+        assert!(!parse_flag("off"));
+        assert!(!parse_flag(""));
+    }
+
+    #[test]
+    fn features_parse() {
+        let features = parse_features(" admin,,beta , admin");
+        assert_eq!(features.into_iter().collect::<Vec<_>>(), ["admin", "beta"]);
+        assert!(parse_features(" , ").is_empty());
+    }
+
+    #[test]
+    fn config_worker_threads_apply_when_env_is_unset() {
+        // BRRTR_WORKERS / BRRTR_MAY_WORKERS are not set by the test suite.
+        let config = RuntimeConfig::from_env();
+        let default_workers = config.may_workers;
+        assert_eq!(config.clone().with_worker_threads(Some(6)).may_workers, 6);
+        assert_eq!(config.clone().with_worker_threads(Some(1)).may_workers, 2);
+        assert_eq!(
+            config.with_worker_threads(None).may_workers,
+            default_workers
+        );
+    }
 }
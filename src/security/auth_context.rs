@@ -0,0 +1,86 @@
+//! Uniform identity view of an authenticated request.
+
+use serde_json::Value;
+
+/// Identity established by the security scheme that authenticated a request.
+///
+/// Built by the server from [`SecurityProvider::extract_claims`](super::SecurityProvider::extract_claims)
+/// after [`SecurityProvider::validate`](super::SecurityProvider::validate) succeeds, whatever
+/// the scheme (JWT, JWKS, SPIFFE, API key, ...), and exposed to handlers as
+/// [`HandlerRequest::auth_context`](crate::dispatcher::HandlerRequest::auth_context).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthContext {
+    /// Name of the security scheme (`components.securitySchemes` key) that authenticated the request
+    pub scheme: String,
+    /// Authenticated principal: the `sub` claim (SPIFFE ID, API-key principal, user id)
+    pub subject: Option<String>,
+    /// Granted scopes from the `scope` (space separated), `scp` or `scopes` claim
+    pub scopes: Vec<String>,
+    /// Raw claims returned by the provider; `Value::Null` when it returned none
+    pub claims: Value,
+}
+
+impl AuthContext {
+    /// Build the context for `scheme` from the claims a provider extracted.
+    pub fn from_claims(scheme: impl Into<String>, claims: Option<Value>) -> Self {
+        let claims = claims.unwrap_or(Value::Null);
+        let subject = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let scopes = ["scope", "scp", "scopes"]
+            .iter()
+            .find_map(|key| claims.get(key))
+            .map(|value| match value {
+                Value::String(s) => s.split_whitespace().map(str::to_string).collect(),
+                Value::Array(items) => items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .unwrap_or_default();
+        Self {
+            scheme: scheme.into(),
+            subject,
+            scopes,
+            claims,
+        }
+    }
+
+    /// Whether `scope` was granted.
+    #[must_use]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthContext;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn jwt_claims_yield_subject_and_space_separated_scopes() {
+        let ctx = AuthContext::from_claims(
+            "BearerAuth",
+            Some(json!({"sub": "user-1", "scope": "read write"})),
+        );
+        assert_eq!(ctx.scheme, "BearerAuth");
+        assert_eq!(ctx.subject.as_deref(), Some("user-1"));
+        assert_eq!(ctx.scopes, vec!["read", "write"]);
+        assert!(ctx.has_scope("write"));
+    }
+
+    #[test]
+    fn scp_array_and_missing_claims() {
+        let ctx = AuthContext::from_claims("Oidc", Some(json!({"scp": ["a", "b"]})));
+        assert_eq!(ctx.subject, None);
+        assert_eq!(ctx.scopes, vec!["a", "b"]);
+
+        let ctx = AuthContext::from_claims("ApiKeyAuth", None);
+        assert_eq!(ctx.claims, Value::Null);
+        assert!(ctx.scopes.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::security::{SecurityProvider, SecurityRequest};
+use crate::security::{AuthOutcome, SecurityProvider, SecurityRequest};
 use crate::spec::SecurityScheme;
 use base64::{engine::general_purpose, Engine as _};
 use serde_json::Value;
@@ -48,14 +48,15 @@ impl BearerJwtProvider {
             .and_then(|h| h.strip_prefix("Bearer "))
     }
 
-    pub(crate) fn validate_token(&self, token: &str, scopes: &[String]) -> bool {
+    /// Signature and payload checks, then the payload's `scope` claim against `scopes`.
+    pub(crate) fn check_token(&self, token: &str, scopes: &[String]) -> AuthOutcome {
         let mut parts = token.split('.');
         let header = parts.next();
         let payload = parts.next();
         let sig = parts.next();
         if header.is_none() || payload.is_none() || sig != Some(self.signature.as_str()) {
             debug!("BearerJWT token validation failed: malformed token or invalid signature");
-            return false;
+            return AuthOutcome::Unauthenticated;
         }
         // Safe to unwrap here because we checked is_none() above
         let payload_str = payload.expect("payload already validated as Some");
@@ -66,7 +67,7 @@ impl BearerJwtProvider {
                     "BearerJWT token validation failed: invalid base64 payload - {:?}",
                     e
                 );
-                return false;
+                return AuthOutcome::Unauthenticated;
             }
         };
         let json: Value = match serde_json::from_slice(&payload_bytes) {
@@ -76,23 +77,22 @@ impl BearerJwtProvider {
                     "BearerJWT token validation failed: invalid JSON payload - {:?}",
                     e
                 );
-                return false;
+                return AuthOutcome::Unauthenticated;
             }
         };
         let token_scopes = json.get("scope").and_then(|v| v.as_str()).unwrap_or("");
-        let has_all_scopes = scopes
-            .iter()
-            .all(|s| token_scopes.split_whitespace().any(|ts| ts == s));
+        let outcome = AuthOutcome::from_scope_claim(scopes, token_scopes);
 
-        if !has_all_scopes {
+        if let AuthOutcome::InsufficientScope { missing } = &outcome {
             warn!(
-                "BearerJWT validation failed: missing required scopes (token: {:?}, required: {:?})",
+                "BearerJWT validation failed: missing required scopes (token: {:?}, required: {:?}, missing: {:?})",
                 token_scopes,
-                scopes
+                scopes,
+                missing
             );
         }
 
-        has_all_scopes
+        outcome
     }
 }
 
@@ -153,25 +153,36 @@ impl SecurityProvider for BearerJwtProvider {
     /// - `true` - Token is valid and contains required scopes
     /// - `false` - Token missing, invalid signature, or missing scopes
     fn validate(&self, scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool {
+        self.check(scheme, scopes, req).is_granted()
+    }
+
+    /// Like [`Self::validate`]; a valid token without every scope is
+    /// [`AuthOutcome::InsufficientScope`] naming the missing ones.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
         match scheme {
             SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => {}
             _ => {
                 debug!("BearerJWT validation failed: unsupported security scheme");
-                return false;
+                return AuthOutcome::Unauthenticated;
             }
         }
         let token = match self.extract_token(req) {
             Some(t) => t,
             None => {
                 debug!("BearerJWT validation failed: missing token (no Authorization header or cookie)");
-                return false;
+                return AuthOutcome::Unauthenticated;
             }
         };
-        let result = self.validate_token(token, scopes);
-        if result {
+        let outcome = self.check_token(token, scopes);
+        if outcome.is_granted() {
             debug!("BearerJWT validation succeeded: token valid");
         }
-        result
+        outcome
     }
 }
 
@@ -189,6 +200,6 @@ mod tests {
     fn pet_store_e2e_style_token_validates_with_default_sig() {
         let p = BearerJwtProvider::new("sig");
         let tok = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.sig";
-        assert!(p.validate_token(tok, &[]));
+        assert!(p.check_token(tok, &[]).is_granted());
     }
 }
@@ -0,0 +1,248 @@
+//! OpenID Connect discovery for [`JwksBearerProvider`].
+//!
+//! Fetches `{issuer}/.well-known/openid-configuration` (OpenID Connect Discovery 1.0 §4),
+//! reads `issuer` and `jwks_uri`, and configures a provider from them. The discovered
+//! `issuer` must be identical to the configured issuer URL (§4.3) to prevent mix-up
+//! attacks where one identity provider advertises another's keys.
+//!
+//! Discovery documents are cached process-wide per issuer URL so multiple schemes that
+//! share an identity provider only fetch the document once per TTL.
+
+use super::{jwks_http_host_allowed, JwksBearerProvider};
+use crate::lock_poison;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Well-known path appended to the issuer URL (OpenID Connect Discovery 1.0 §4).
+pub(crate) const OIDC_DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// How long a fetched discovery document is reused before it is fetched again.
+const DISCOVERY_CACHE_TTL: Duration = Duration::from_secs(3600);
+const DISCOVERY_CACHE_LOCK: &str = "jwks.discovery_cache";
+
+static DISCOVERY_CACHE: Lazy<RwLock<HashMap<String, (Instant, OidcDiscoveryDocument)>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Subset of the OpenID provider metadata used to configure JWT validation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OidcDiscoveryDocument {
+    /// Issuer identifier; must match the issuer URL used for discovery.
+    pub issuer: String,
+    /// URL of the provider's JSON Web Key Set.
+    pub jwks_uri: String,
+    /// Signing algorithms the provider advertises for ID tokens (informational).
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// Errors from OpenID Connect discovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OidcDiscoveryError {
+    /// Issuer URL or discovered `jwks_uri` could not be parsed.
+    InvalidUrl(String),
+    /// URL is not HTTPS (HTTP is only allowed for loopback and `*.svc.cluster.local`).
+    InsecureUrl(String),
+    /// Discovery document could not be fetched.
+    Fetch(String),
+    /// Discovery document is not valid JSON or is missing required members.
+    InvalidDocument(String),
+    /// Discovered `issuer` differs from the configured issuer URL.
+    IssuerMismatch {
+        /// Issuer URL passed to discovery.
+        expected: String,
+        /// `issuer` value returned by the discovery document.
+        discovered: String,
+    },
+}
+
+impl std::fmt::Display for OidcDiscoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUrl(msg) => write!(f, "OIDC discovery: invalid URL: {msg}"),
+            Self::InsecureUrl(url) => write!(
+                f,
+                "OIDC discovery: URL must use HTTPS (HTTP only allowed for localhost/127.0.0.1 or *.svc.cluster.local). Got: {url}"
+            ),
+            Self::Fetch(msg) => write!(f, "OIDC discovery: fetch failed: {msg}"),
+            Self::InvalidDocument(msg) => write!(f, "OIDC discovery: invalid document: {msg}"),
+            Self::IssuerMismatch {
+                expected,
+                discovered,
+            } => write!(
+                f,
+                "OIDC discovery: issuer mismatch (expected {expected}, discovered {discovered})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OidcDiscoveryError {}
+
+/// Apply the same transport rules as [`JwksBearerProvider::new`] without panicking.
+fn check_url(url: &str) -> Result<(), OidcDiscoveryError> {
+    let parsed =
+        Url::parse(url).map_err(|e| OidcDiscoveryError::InvalidUrl(format!("{url}: {e}")))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if parsed.host_str().is_some_and(jwks_http_host_allowed) => Ok(()),
+        _ => Err(OidcDiscoveryError::InsecureUrl(url.to_string())),
+    }
+}
+
+/// Build the discovery URL for an issuer (trailing slashes are not duplicated).
+pub(crate) fn discovery_url(issuer_url: &str) -> String {
+    format!(
+        "{}{}",
+        issuer_url.trim_end_matches('/'),
+        OIDC_DISCOVERY_PATH
+    )
+}
+
+/// Fetch (or reuse a cached) discovery document and validate it against `issuer_url`.
+///
+/// # Errors
+///
+/// Returns [`OidcDiscoveryError`] when either URL is insecure, the fetch fails, the document
+/// is malformed, or the discovered issuer does not exactly match `issuer_url`.
+pub(crate) fn discover(
+    issuer_url: &str,
+    tls: Option<&Arc<crate::http::OutboundTls>>,
+) -> Result<OidcDiscoveryDocument, OidcDiscoveryError> {
+    check_url(issuer_url)?;
+
+    if let Some((fetched_at, doc)) =
+        lock_poison::read(&DISCOVERY_CACHE, DISCOVERY_CACHE_LOCK).get(issuer_url)
+    {
+        if fetched_at.elapsed() < DISCOVERY_CACHE_TTL {
+            return Ok(doc.clone());
+        }
+    }
+
+    let url = discovery_url(issuer_url);
+    let options = crate::http::HttpFetchOptions {
+        timeout: Duration::from_secs(2),
+        max_body_bytes: 64 * 1024,
+        extra_headers: vec![("Accept".into(), "application/json".into())],
+        tls: tls.cloned(),
+    };
+    let (status, body) = crate::http::fetch_get(&url, &options)
+        .map_err(|e| OidcDiscoveryError::Fetch(format!("{url}: {e}")))?;
+    if !(200..300).contains(&status) {
+        return Err(OidcDiscoveryError::Fetch(format!(
+            "{url}: HTTP status {status}"
+        )));
+    }
+    let doc: OidcDiscoveryDocument = serde_json::from_slice(&body)
+        .map_err(|e| OidcDiscoveryError::InvalidDocument(e.to_string()))?;
+
+    // OpenID Connect Discovery 1.0 §4.3: the returned issuer MUST be identical to the
+    // issuer URL that was used to build the discovery URL.
+    if doc.issuer != issuer_url {
+        return Err(OidcDiscoveryError::IssuerMismatch {
+            expected: issuer_url.to_string(),
+            discovered: doc.issuer,
+        });
+    }
+    check_url(&doc.jwks_uri)?;
+
+    lock_poison::write(&DISCOVERY_CACHE, DISCOVERY_CACHE_LOCK)
+        .insert(issuer_url.to_string(), (Instant::now(), doc.clone()));
+    Ok(doc)
+}
+
+/// Drop all cached discovery documents so the next discovery refetches them.
+pub fn clear_discovery_cache() {
+    lock_poison::write(&DISCOVERY_CACHE, DISCOVERY_CACHE_LOCK).clear();
+}
+
+impl JwksBearerProvider {
+    /// Create a provider from OpenID Connect discovery.
+    ///
+    /// Fetches `{issuer_url}/.well-known/openid-configuration`, verifies that the discovered
+    /// `issuer` is identical to `issuer_url`, and configures the provider with the discovered
+    /// `jwks_uri` and `issuer`. The audience is not discoverable; chain `.audience()` as usual.
+    ///
+    /// ```rust,no_run
+    /// use brrtrouter::security::JwksBearerProvider;
+    ///
+    /// let provider = JwksBearerProvider::from_oidc_discovery("https://auth.example.com")
+    ///     .expect("OIDC discovery failed")
+    ///     .audience("my-api");
+    /// ```
+    ///
+    /// # Security
+    ///
+    /// Both the issuer URL and the discovered `jwks_uri` must use HTTPS; HTTP is limited to
+    /// loopback and in-cluster `.svc.cluster.local` hosts, matching [`JwksBearerProvider::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OidcDiscoveryError`] instead of panicking so callers can retry or fall back
+    /// to explicit configuration.
+    pub fn from_oidc_discovery(issuer_url: impl AsRef<str>) -> Result<Self, OidcDiscoveryError> {
+        let doc = discover(issuer_url.as_ref(), None)?;
+        let mut provider = Self::new(doc.jwks_uri.clone()).issuer(doc.issuer.clone());
+        provider.oidc_discovery = Some(doc);
+        Ok(provider)
+    }
+
+    /// [`JwksBearerProvider::from_oidc_discovery`] for an issuer behind a private CA: the
+    /// discovery fetch and every JWKS fetch verify against `tls` (see [`JwksBearerProvider::tls`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OidcDiscoveryError`] as [`JwksBearerProvider::from_oidc_discovery`] does; a
+    /// certificate the custom trust rejects is a [`OidcDiscoveryError::Fetch`].
+    pub fn from_oidc_discovery_with_tls(
+        issuer_url: impl AsRef<str>,
+        tls: crate::http::OutboundTls,
+    ) -> Result<Self, OidcDiscoveryError> {
+        let tls = Arc::new(tls);
+        let doc = discover(issuer_url.as_ref(), Some(&tls))?;
+        let mut provider = Self::new(doc.jwks_uri.clone()).issuer(doc.issuer.clone());
+        provider.outbound_tls.store(Some(tls));
+        provider.oidc_discovery = Some(doc);
+        Ok(provider)
+    }
+
+    /// Discovery document used to configure this provider, if it was built via
+    /// [`JwksBearerProvider::from_oidc_discovery`].
+    pub fn oidc_discovery(&self) -> Option<&OidcDiscoveryDocument> {
+        self.oidc_discovery.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discovery_url_does_not_double_slash() {
+        assert_eq!(
+            discovery_url("https://auth.example.com/"),
+            "https://auth.example.com/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            discovery_url("https://auth.example.com/realms/main"),
+            "https://auth.example.com/realms/main/.well-known/openid-configuration"
+        );
+    }
+
+    #[test]
+    fn check_url_rejects_plain_http_for_public_hosts() {
+        assert!(check_url("https://auth.example.com").is_ok());
+        assert!(check_url("http://localhost:8080").is_ok());
+        assert!(matches!(
+            check_url("http://localhost.attacker.com"),
+            Err(OidcDiscoveryError::InsecureUrl(_))
+        ));
+        assert!(matches!(
+            check_url("not a url"),
+            Err(OidcDiscoveryError::InvalidUrl(_))
+        ));
+    }
+}
@@ -1,9 +1,14 @@
+mod discovery;
 mod jwt_logger;
 mod validation;
 
+pub use discovery::{clear_discovery_cache, OidcDiscoveryDocument, OidcDiscoveryError};
 pub use jwt_logger::{DecisionSource, JwtLogFields, JwtStructuredLogger};
 
-use crate::security::{CacheStats, SecurityProvider, SecurityRequest};
+use crate::lock_poison;
+use crate::security::{
+    AuthOutcome, CacheStats, RevocationChecker, SecurityProvider, SecurityRequest,
+};
 use crate::spec::SecurityScheme;
 use base64::Engine as _;
 use lru::LruCache;
@@ -15,6 +20,7 @@ use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use tracing::{debug, warn, Level};
 use url::Url;
+use validation::ValidationError;
 
 // Algorithms supported by jsonwebtoken's rust_crypto backend. Each provider should configure
 // the smallest issuer-specific subset with `allowed_algorithms`; this full set remains the
@@ -34,6 +40,59 @@ pub(super) const SUPPORTED_ALGORITHMS: &[jsonwebtoken::Algorithm] = &[
     jsonwebtoken::Algorithm::EdDSA,
 ];
 
+/// Type of a JWKS key, which fixes the header `alg`s it may verify
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum KeyKind {
+    /// `oct` HMAC secret: HS256, HS384, HS512
+    Oct,
+    /// `RSA` public key: RS* and PS*
+    Rsa,
+    /// `EC` public key: only the ES* algorithm of its curve
+    Ec(jsonwebtoken::Algorithm),
+    /// `OKP` Ed25519 public key: EdDSA
+    Okp,
+}
+
+impl KeyKind {
+    /// Whether a token whose header declares `alg` may be verified with this key
+    ///
+    /// SECURITY: the key's type decides, never the token. Without this an `HS256` token
+    /// could be checked against an RSA key's public material (algorithm confusion).
+    pub(super) fn verifies(self, alg: jsonwebtoken::Algorithm) -> bool {
+        use jsonwebtoken::Algorithm::*;
+        match self {
+            KeyKind::Oct => matches!(alg, HS256 | HS384 | HS512),
+            KeyKind::Rsa => matches!(alg, RS256 | RS384 | RS512 | PS256 | PS384 | PS512),
+            KeyKind::Ec(curve_alg) => alg == curve_alg,
+            KeyKind::Okp => alg == EdDSA,
+        }
+    }
+
+    /// The JWK `kty` this kind was parsed from
+    pub(super) fn kty(self) -> &'static str {
+        match self {
+            KeyKind::Oct => "oct",
+            KeyKind::Rsa => "RSA",
+            KeyKind::Ec(_) => "EC",
+            KeyKind::Okp => "OKP",
+        }
+    }
+}
+
+/// A JWKS key: its decoding key and the type it was published with
+#[derive(Clone)]
+pub(super) struct JwkKey {
+    pub(super) decoding: jsonwebtoken::DecodingKey,
+    pub(super) kind: KeyKind,
+}
+
+// Lock names reported in `brrtrouter_lock_poison_total`.
+pub(super) const KEYS_LOCK: &str = "jwks.keys";
+pub(super) const REFRESH_LOCK: &str = "jwks.refresh";
+const CLAIMS_CACHE_LOCK: &str = "jwks.claims_cache";
+const BACKGROUND_HANDLE_LOCK: &str = "jwks.background_handle";
+const UNKNOWN_KID_LOCK: &str = "jwks.unknown_kid_refresh";
+
 /// Dynamic status of a cryptographically valid JWT.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JwtTokenStatus {
@@ -82,17 +141,19 @@ pub struct JwksBearerProvider {
     pub(super) iss: Option<String>,
     pub(super) aud: Option<String>,
     pub(super) leeway_secs: u64,
+    pub(super) reject_future_iat: bool,
     /// Algorithms accepted for this provider. This is configuration, not token input.
     pub(super) allowed_algorithms: Vec<jsonwebtoken::Algorithm>,
     token_status_checker: Option<Arc<dyn JwtTokenStatusChecker>>,
+    revocation_checker: Option<Arc<dyn RevocationChecker>>,
     cache_ttl: Duration,
     // P1: Shared cache_ttl for background thread to read current value
     // Stored as milliseconds (u64) in AtomicU64 for lock-free reads
     // Using milliseconds preserves sub-second precision (e.g., 100ms TTL)
     cache_ttl_millis: Arc<std::sync::atomic::AtomicU64>,
     // P1: Background refresh - use Arc<RwLock> for lock-free reads
-    // kid -> DecodingKey
-    cache: Arc<RwLock<(Instant, HashMap<String, jsonwebtoken::DecodingKey>)>>,
+    // kid -> key and its type
+    cache: Arc<RwLock<(Instant, HashMap<String, JwkKey>)>>,
     // P2: Debounce JWKS refresh to prevent concurrent HTTP requests
     refresh_in_progress: Arc<AtomicBool>,
     // P1: Condition variable to notify waiting threads when refresh completes
@@ -102,6 +163,8 @@ pub struct JwksBearerProvider {
     // immediately. A cooldown prevents attacker-controlled kids from causing a fetch storm.
     unknown_kid_refresh_cooldown: Duration,
     last_unknown_kid_refresh: Mutex<Option<Instant>>,
+    // Tokens without a `kid` are verified with the JWKS's only key, if it has exactly one.
+    allow_missing_kid: bool,
     // P1: Background refresh task handle for lifecycle management
     background_handle: Option<Arc<RwLock<Option<JoinHandle<()>>>>>,
     // P1: Shutdown flag for graceful background thread termination
@@ -110,7 +173,9 @@ pub struct JwksBearerProvider {
     // Uses LRU cache with Arc<str> keys to prevent memory leaks and avoid allocations
     // P1: RwLock for explicit read/write separation (LruCache::get() requires &mut for LRU updates)
     // SECURITY: Cache key includes kid (key ID) so cache invalidates on key rotation
-    // Format: "token|kid" -> (exp_timestamp_with_leeway, decoded_claims, kid)
+    // Format: "token|kid" -> (exp_timestamp_with_leeway, decoded_claims, kid); a token
+    // without a kid is keyed "token#" and stores the kid of the sole key that verified it
+    // (see `claims_cache_key`)
     pub(super) claims_cache:
         std::sync::RwLock<LruCache<Arc<str>, (i64, serde_json::Value, String)>>,
     claims_cache_size: usize,
@@ -125,6 +190,11 @@ pub struct JwksBearerProvider {
     jwks_poisoning_rejected: AtomicU64,
     // Story 9.6: Structured JWT logging for audit trail
     pub(super) structured_logger: JwtStructuredLogger,
+    // Set when the provider was configured via OpenID Connect discovery
+    oidc_discovery: Option<OidcDiscoveryDocument>,
+    /// Trust for `https://` JWKS fetches; shared with the background refresh thread like
+    /// `cache_ttl_millis`, so [`JwksBearerProvider::tls`] applies to refreshes already scheduled.
+    outbound_tls: Arc<arc_swap::ArcSwapOption<crate::http::OutboundTls>>,
 }
 
 impl JwksBearerProvider {
@@ -198,8 +268,10 @@ impl JwksBearerProvider {
             iss: None,
             aud: None,
             leeway_secs: 30,
+            reject_future_iat: false,
             allowed_algorithms: SUPPORTED_ALGORITHMS.to_vec(),
             token_status_checker: None,
+            revocation_checker: None,
             cache_ttl: Duration::from_secs(300),
             cache_ttl_millis: cache_ttl_millis.clone(),
             cache: cache.clone(),
@@ -207,6 +279,7 @@ impl JwksBearerProvider {
             refresh_complete: refresh_complete.clone(),
             unknown_kid_refresh_cooldown: Duration::from_secs(1),
             last_unknown_kid_refresh: Mutex::new(None),
+            allow_missing_kid: true,
             claims_cache: std::sync::RwLock::new(LruCache::new(
                 NonZeroUsize::new(1000).expect("claims_cache_size must be > 0"),
             )),
@@ -221,6 +294,8 @@ impl JwksBearerProvider {
             jwks_fetch_success: AtomicU64::new(0),
             jwks_fetch_failure: AtomicU64::new(0),
             jwks_poisoning_rejected: AtomicU64::new(0),
+            oidc_discovery: None,
+            outbound_tls: Arc::new(arc_swap::ArcSwapOption::empty()),
         };
 
         // Start background refresh task
@@ -255,11 +330,21 @@ impl JwksBearerProvider {
     /// Configure leeway for time-based claims validation
     ///
     /// Allows some clock skew between client and server when validating exp, nbf, and iat claims.
+    /// A token is accepted until `exp + leeway` and from `nbf - leeway`. Default: 30 seconds.
     pub fn leeway(mut self, secs: u64) -> Self {
         self.leeway_secs = secs;
         self
     }
 
+    /// Reject tokens whose `iat` (issued-at) is later than now + leeway.
+    ///
+    /// Off by default (lenient): a future `iat` usually means issuer clock skew, and `exp`/`nbf`
+    /// already bound the token's lifetime. Enable it when the issuer's clock is trusted.
+    pub fn reject_future_iat(mut self, reject: bool) -> Self {
+        self.reject_future_iat = reject;
+        self
+    }
+
     /// Restrict JWT algorithms accepted by this provider.
     ///
     /// The token header is untrusted input. Consumers SHOULD configure the smallest set that
@@ -270,19 +355,34 @@ impl JwksBearerProvider {
     ///
     /// Panics when `algorithms` is empty or contains an unsupported algorithm. This builder is
     /// intended for startup configuration, where an invalid security policy must fail fast.
+    /// Use [`Self::check_allowed_algorithms`] first when the list comes from configuration.
     #[allow(clippy::panic)]
     pub fn allowed_algorithms(mut self, algorithms: &[jsonwebtoken::Algorithm]) -> Self {
+        if let Err(e) = Self::check_allowed_algorithms(algorithms) {
+            panic!("{e}");
+        }
+        self.allowed_algorithms = algorithms.to_vec();
+        self
+    }
+
+    /// Check a list for [`Self::allowed_algorithms`] without panicking.
+    ///
+    /// # Errors
+    ///
+    /// Says the list is empty, or names its first unsupported algorithm.
+    pub fn check_allowed_algorithms(algorithms: &[jsonwebtoken::Algorithm]) -> Result<(), String> {
         if algorithms.is_empty() {
-            panic!("allowed_algorithms must contain at least one algorithm");
+            return Err("allowed_algorithms must contain at least one algorithm".to_string());
         }
-        if let Some(unsupported) = algorithms
+        match algorithms
             .iter()
             .find(|algorithm| !SUPPORTED_ALGORITHMS.contains(algorithm))
         {
-            panic!("unsupported JWT algorithm in allowed_algorithms: {unsupported:?}");
+            Some(unsupported) => Err(format!(
+                "unsupported JWT algorithm in allowed_algorithms: {unsupported:?}"
+            )),
+            None => Ok(()),
         }
-        self.allowed_algorithms = algorithms.to_vec();
-        self
     }
 
     /// Configure the minimum interval between forced JWKS refreshes for unknown key IDs.
@@ -294,6 +394,16 @@ impl JwksBearerProvider {
         self
     }
 
+    /// Accept tokens without a `kid` header when the JWKS holds exactly one key (default
+    /// `true`).
+    ///
+    /// Such a token is verified with that key. With several keys the choice would be a guess,
+    /// so the token is rejected as ambiguous; with `false` every token must name its key.
+    pub fn allow_missing_kid(mut self, allow: bool) -> Self {
+        self.allow_missing_kid = allow;
+        self
+    }
+
     /// Attach a dynamic denylist/version checker.
     ///
     /// The checker runs after cryptographic and standard-claim validation on both cache hits and
@@ -303,6 +413,37 @@ impl JwksBearerProvider {
         self
     }
 
+    /// Reject tokens whose `jti` the checker reports as revoked.
+    ///
+    /// Consulted after signature and claims validation, on claims-cache hits as well as
+    /// misses: a revoked token is never cached, and a cached one is evicted the first time it
+    /// is presented after revocation. Tokens without a `jti` are not affected.
+    ///
+    /// ```rust
+    /// use brrtrouter::security::{InMemoryRevocationChecker, JwksBearerProvider};
+    /// use std::sync::Arc;
+    ///
+    /// let revoked = Arc::new(InMemoryRevocationChecker::new());
+    /// let provider = JwksBearerProvider::new("https://auth.example.com/.well-known/jwks.json")
+    ///     .revocation_checker(revoked.clone());
+    /// revoked.revoke("compromised-jti");
+    /// # provider.stop_background_refresh();
+    /// ```
+    pub fn revocation_checker(mut self, checker: Arc<dyn RevocationChecker>) -> Self {
+        self.revocation_checker = Some(checker);
+        self
+    }
+
+    pub(super) fn is_revoked(&self, claims: &serde_json::Value) -> bool {
+        match (
+            &self.revocation_checker,
+            claims.get("jti").and_then(|v| v.as_str()),
+        ) {
+            (Some(checker), Some(jti)) => checker.is_revoked(jti),
+            _ => false,
+        }
+    }
+
     pub(super) fn algorithm_allowed(&self, algorithm: jsonwebtoken::Algorithm) -> bool {
         self.allowed_algorithms.contains(&algorithm)
     }
@@ -330,6 +471,17 @@ impl JwksBearerProvider {
         self
     }
 
+    /// Verify `https://` JWKS fetches against a private CA bundle (and optional SPKI pins)
+    /// instead of the system trust store
+    ///
+    /// Build the [`crate::http::OutboundTls`] at startup so a bad bundle or pin fails there.
+    /// Also applies to the background refresh thread, which may already have attempted its
+    /// first fetch with the default trust.
+    pub fn tls(self, tls: crate::http::OutboundTls) -> Self {
+        self.outbound_tls.store(Some(Arc::new(tls)));
+        self
+    }
+
     /// Configure the cookie name used to read the token.
     ///
     /// If set, tokens will be extracted from cookies in addition to the Authorization header.
@@ -362,10 +514,7 @@ impl JwksBearerProvider {
         }
         self.claims_cache_size = size;
         {
-            let mut guard = self
-                .claims_cache
-                .write()
-                .expect("Claims cache RwLock poisoned - critical error");
+            let mut guard = lock_poison::write(&self.claims_cache, CLAIMS_CACHE_LOCK);
             *guard = LruCache::new(NonZeroUsize::new(size).unwrap());
         }
         self
@@ -375,9 +524,7 @@ impl JwksBearerProvider {
     ///
     /// Useful for testing, key rotation, or security incidents where tokens need to be invalidated.
     pub fn clear_claims_cache(&self) {
-        if let Ok(mut guard) = self.claims_cache.write() {
-            guard.clear();
-        }
+        self.claims_cache_guard().clear();
     }
 
     /// Invalidate a specific token from the claims cache.
@@ -393,10 +540,10 @@ impl JwksBearerProvider {
     ///
     /// # Note
     ///
-    /// If the token cannot be parsed (missing or invalid header), this method
-    /// will log a warning and return without invalidating. Tokens without valid
-    /// headers are not cached, so this is safe. For manual invalidation with a
-    /// known key ID, use `invalidate_token_with_kid()`.
+    /// If the token cannot be parsed (invalid header), this method will log a
+    /// warning and return without invalidating. Tokens without valid headers are
+    /// not cached, so this is safe. For manual invalidation with a known key ID,
+    /// use `invalidate_token_with_kid()`.
     pub fn invalidate_token(&self, token: &str) {
         // SECURITY: Cache key format is "token|kid", so we need to extract kid from token
         // Parse the token header to get the kid
@@ -412,19 +559,8 @@ impl JwksBearerProvider {
             }
         };
 
-        let kid = match header.kid {
-            Some(k) => k,
-            None => {
-                warn!(
-                    "JWT invalidation failed: missing 'kid' (key ID) in token header. \
-                     Tokens without kids are not cached, skipping invalidation."
-                );
-                return;
-            }
-        };
-
-        // Use the more precise invalidation method with the extracted kid
-        self.invalidate_token_with_kid(token, &kid);
+        let token_key = claims_cache_key(token, header.kid.as_deref());
+        self.claims_cache_guard().pop(&token_key);
     }
 
     /// Invalidate a specific token with a specific key ID from the claims cache.
@@ -436,11 +572,8 @@ impl JwksBearerProvider {
     /// * `token` - The JWT token string to invalidate
     /// * `kid` - The key ID to invalidate
     pub fn invalidate_token_with_kid(&self, token: &str, kid: &str) {
-        // SECURITY: Cache key format is "token|kid"
-        let token_key: Arc<str> = Arc::from(format!("{}|{}", token, kid));
-        if let Ok(mut guard) = self.claims_cache.write() {
-            guard.pop(&token_key);
-        }
+        let token_key = claims_cache_key(token, Some(kid));
+        self.claims_cache_guard().pop(&token_key);
     }
 
     /// Get cache statistics for observability and tuning.
@@ -456,11 +589,7 @@ impl JwksBearerProvider {
     /// - `size`: Current number of entries in cache
     /// - `capacity`: Maximum cache capacity
     pub fn cache_stats(&self) -> CacheStats {
-        let cache_size = self
-            .claims_cache
-            .read()
-            .map(|guard| guard.len())
-            .unwrap_or(0);
+        let cache_size = self.claims_cache_guard().len();
         CacheStats {
             hits: self.cache_hits.load(Ordering::Relaxed),
             misses: self.cache_misses.load(Ordering::Relaxed),
@@ -488,7 +617,7 @@ impl JwksBearerProvider {
     /// This ensures validation threads never block on HTTP requests.
     fn start_background_refresh_internal(
         &self,
-        cache: Arc<RwLock<(Instant, HashMap<String, jsonwebtoken::DecodingKey>)>>,
+        cache: Arc<RwLock<(Instant, HashMap<String, JwkKey>)>>,
         refresh_in_progress: Arc<AtomicBool>,
         refresh_complete: Arc<(Mutex<()>, Condvar)>,
         shutdown: Arc<AtomicBool>,
@@ -496,6 +625,7 @@ impl JwksBearerProvider {
         cache_ttl_millis: Arc<std::sync::atomic::AtomicU64>,
     ) {
         let jwks_url = self.jwks_url.clone();
+        let outbound_tls = self.outbound_tls.clone();
         let cache_ttl_millis = cache_ttl_millis;
 
         let handle = thread::spawn(move || {
@@ -506,6 +636,7 @@ impl JwksBearerProvider {
                 Self::refresh_jwks_internal(
                     &cache,
                     &jwks_url,
+                    &outbound_tls,
                     &refresh_in_progress,
                     &refresh_complete,
                     false, // Background thread claims the refresh itself
@@ -570,6 +701,7 @@ impl JwksBearerProvider {
                     Self::refresh_jwks_internal(
                         &cache,
                         &jwks_url,
+                        &outbound_tls,
                         &refresh_in_progress,
                         &refresh_complete,
                         false, // Background thread claims the refresh itself
@@ -582,6 +714,7 @@ impl JwksBearerProvider {
                     Self::refresh_jwks_internal(
                         &cache,
                         &jwks_url,
+                        &outbound_tls,
                         &refresh_in_progress,
                         &refresh_complete,
                         false, // Background thread claims the refresh itself
@@ -591,9 +724,7 @@ impl JwksBearerProvider {
             }
         });
 
-        if let Ok(mut guard) = handle_lock.write() {
-            *guard = Some(handle);
-        }
+        *lock_poison::write(handle_lock, BACKGROUND_HANDLE_LOCK) = Some(handle);
     }
 
     /// Stop the background refresh task
@@ -605,11 +736,10 @@ impl JwksBearerProvider {
 
         // Wait for thread to finish
         if let Some(handle_lock) = &self.background_handle {
-            if let Ok(mut guard) = handle_lock.write() {
-                if let Some(handle) = guard.take() {
-                    // Wait for thread to finish (will exit when shutdown flag is set)
-                    let _ = handle.join();
-                }
+            let handle = lock_poison::write(handle_lock, BACKGROUND_HANDLE_LOCK).take();
+            if let Some(handle) = handle {
+                // Wait for thread to finish (will exit when shutdown flag is set)
+                let _ = handle.join();
             }
         }
     }
@@ -620,8 +750,9 @@ impl JwksBearerProvider {
     /// * `already_claimed` - If true, the caller has already atomically claimed the refresh
     ///   (set refresh_in_progress to true). If false, this method will atomically claim it.
     fn refresh_jwks_internal(
-        cache: &Arc<RwLock<(Instant, HashMap<String, jsonwebtoken::DecodingKey>)>>,
+        cache: &Arc<RwLock<(Instant, HashMap<String, JwkKey>)>>,
         jwks_url: &str,
+        outbound_tls: &arc_swap::ArcSwapOption<crate::http::OutboundTls>,
         refresh_in_progress: &Arc<AtomicBool>,
         refresh_complete: &Arc<(Mutex<()>, Condvar)>,
         already_claimed: bool,
@@ -642,6 +773,7 @@ impl JwksBearerProvider {
             timeout: Duration::from_millis(200),
             max_body_bytes: 256 * 1024,
             extra_headers: Vec::new(),
+            tls: outbound_tls.load_full(),
         };
         let body = match crate::http::fetch_get_text_with_retry(jwks_url, &fetch_options, 2) {
             Some(b) => b,
@@ -649,7 +781,7 @@ impl JwksBearerProvider {
                 refresh_in_progress.store(false, Ordering::Release);
                 // Notify waiting threads even on failure so they don't wait forever
                 let (lock, cvar) = &**refresh_complete;
-                let _guard = lock.lock().unwrap();
+                let _guard = lock_poison::lock(lock, REFRESH_LOCK);
                 cvar.notify_all();
                 return;
             }
@@ -661,13 +793,13 @@ impl JwksBearerProvider {
                 refresh_in_progress.store(false, Ordering::Release);
                 // Notify waiting threads even on failure so they don't wait forever
                 let (lock, cvar) = &**refresh_complete;
-                let _guard = lock.lock().unwrap();
+                let _guard = lock_poison::lock(lock, REFRESH_LOCK);
                 cvar.notify_all();
                 return;
             }
         };
 
-        let mut new_map: HashMap<String, jsonwebtoken::DecodingKey> = HashMap::new();
+        let mut new_map: HashMap<String, JwkKey> = HashMap::new();
         if let Some(keys) = parsed.get("keys").and_then(|v| v.as_array()) {
             for k in keys {
                 let kid = k.get("kid").and_then(|v| v.as_str()).unwrap_or("");
@@ -691,7 +823,12 @@ impl JwksBearerProvider {
                                 Ok(secret) => {
                                     new_map.insert(
                                         kid.to_string(),
-                                        jsonwebtoken::DecodingKey::from_secret(&secret),
+                                        JwkKey {
+                                            decoding: jsonwebtoken::DecodingKey::from_secret(
+                                                &secret,
+                                            ),
+                                            kind: KeyKind::Oct,
+                                        },
                                     );
                                 }
                                 Err(e) => {
@@ -725,7 +862,13 @@ impl JwksBearerProvider {
                             (Some(n), Some(e)) => {
                                 if let Ok(dk) = jsonwebtoken::DecodingKey::from_rsa_components(n, e)
                                 {
-                                    new_map.insert(kid.to_string(), dk);
+                                    new_map.insert(
+                                        kid.to_string(),
+                                        JwkKey {
+                                            decoding: dk,
+                                            kind: KeyKind::Rsa,
+                                        },
+                                    );
                                 } else {
                                     tracing::warn!(
                                         kid,
@@ -757,7 +900,18 @@ impl JwksBearerProvider {
                             (Some(x), Some(y)) => {
                                 if let Ok(dk) = jsonwebtoken::DecodingKey::from_ec_components(x, y)
                                 {
-                                    new_map.insert(kid.to_string(), dk);
+                                    let curve_alg = if alg == "ES256" {
+                                        jsonwebtoken::Algorithm::ES256
+                                    } else {
+                                        jsonwebtoken::Algorithm::ES384
+                                    };
+                                    new_map.insert(
+                                        kid.to_string(),
+                                        JwkKey {
+                                            decoding: dk,
+                                            kind: KeyKind::Ec(curve_alg),
+                                        },
+                                    );
                                 } else {
                                     tracing::warn!(
                                         kid,
@@ -800,7 +954,13 @@ impl JwksBearerProvider {
                     match k.get("x").and_then(|v| v.as_str()) {
                         Some(x) => match jsonwebtoken::DecodingKey::from_ed_components(x) {
                             Ok(dk) => {
-                                new_map.insert(kid.to_string(), dk);
+                                new_map.insert(
+                                    kid.to_string(),
+                                    JwkKey {
+                                        decoding: dk,
+                                        kind: KeyKind::Okp,
+                                    },
+                                );
                             }
                             Err(e) => {
                                 tracing::warn!(kid, kty, crv, error = %e, "JWKS: OKP key 'x' invalid; rejected");
@@ -836,16 +996,14 @@ impl JwksBearerProvider {
         let key_count = new_map.len();
         let refresh_duration = refresh_start.elapsed();
 
-        if let Ok(mut guard) = cache.write() {
-            *guard = (Instant::now(), new_map);
-        }
+        *lock_poison::write(cache, KEYS_LOCK) = (Instant::now(), new_map);
 
         refresh_in_progress.store(false, Ordering::Release);
 
         // Notify all waiting threads that refresh has completed
         // This wakes them immediately instead of waiting for their next poll
         let (lock, cvar) = &**refresh_complete;
-        let _guard = lock.lock().unwrap();
+        let _guard = lock_poison::lock(lock, REFRESH_LOCK);
         cvar.notify_all();
 
         debug!(
@@ -874,12 +1032,8 @@ impl JwksBearerProvider {
         // traffic. Failed refreshes leave the old timestamp in place, so TTL expiry still
         // triggers another attempt.
         let (needs_refresh, is_empty) = {
-            if let Ok(guard) = self.cache.read() {
-                (guard.0.elapsed() >= current_cache_ttl, guard.1.is_empty())
-            } else {
-                // Lock poisoned, skip refresh
-                return;
-            }
+            let guard = lock_poison::read(&self.cache, KEYS_LOCK);
+            (guard.0.elapsed() >= current_cache_ttl, guard.1.is_empty())
         };
 
         if !needs_refresh {
@@ -890,14 +1044,7 @@ impl JwksBearerProvider {
         // After that, background refresh will keep it updated
         if is_empty {
             // Record cache timestamp BEFORE refresh to detect if refresh succeeded
-            let cache_timestamp_before = {
-                if let Ok(guard) = self.cache.read() {
-                    guard.0
-                } else {
-                    // Lock poisoned, give up
-                    return;
-                }
-            };
+            let cache_timestamp_before = lock_poison::read(&self.cache, KEYS_LOCK).0;
 
             // Try to claim the refresh - if we win, do blocking refresh
             // If we lose, another thread is refreshing - wait for it with exponential backoff
@@ -910,6 +1057,7 @@ impl JwksBearerProvider {
                 Self::refresh_jwks_internal(
                     &self.cache,
                     &self.jwks_url,
+                    &self.outbound_tls,
                     &self.refresh_in_progress,
                     &self.refresh_complete,
                     true, // We already claimed the flag
@@ -917,25 +1065,11 @@ impl JwksBearerProvider {
 
                 // Check if refresh succeeded by comparing timestamps
                 // If timestamp unchanged, refresh failed - retry
-                let cache_timestamp_after = {
-                    if let Ok(guard) = self.cache.read() {
-                        guard.0
-                    } else {
-                        // Lock poisoned, give up
-                        return;
-                    }
-                };
+                let cache_timestamp_after = lock_poison::read(&self.cache, KEYS_LOCK).0;
 
                 // Only retry if timestamp unchanged (refresh failed) AND cache still empty
                 if cache_timestamp_after == cache_timestamp_before {
-                    let still_empty = {
-                        if let Ok(guard) = self.cache.read() {
-                            guard.1.is_empty()
-                        } else {
-                            // Lock poisoned, give up
-                            return;
-                        }
-                    };
+                    let still_empty = lock_poison::read(&self.cache, KEYS_LOCK).1.is_empty();
 
                     if still_empty {
                         // Refresh failed - retry once
@@ -947,6 +1081,7 @@ impl JwksBearerProvider {
                             Self::refresh_jwks_internal(
                                 &self.cache,
                                 &self.jwks_url,
+                                &self.outbound_tls,
                                 &self.refresh_in_progress,
                                 &self.refresh_complete,
                                 true, // We already claimed the flag
@@ -959,18 +1094,11 @@ impl JwksBearerProvider {
                 // This is more efficient than polling - threads are woken immediately when refresh completes
 
                 // Record cache timestamp BEFORE waiting to detect if refresh succeeded
-                let cache_timestamp_before = {
-                    if let Ok(guard) = self.cache.read() {
-                        guard.0
-                    } else {
-                        // Lock poisoned, give up
-                        return;
-                    }
-                };
+                let cache_timestamp_before = lock_poison::read(&self.cache, KEYS_LOCK).0;
 
                 let timeout = Duration::from_secs(2); // Allow 2s for refresh (400ms max + buffer for network issues)
                 let (lock, cvar) = &*self.refresh_complete;
-                let guard = lock.lock().unwrap();
+                let guard = lock_poison::lock(lock, REFRESH_LOCK);
 
                 // Wait for refresh to complete, with timeout
                 // The condvar will wake us immediately when refresh_in_progress becomes false
@@ -995,26 +1123,12 @@ impl JwksBearerProvider {
                 {
                     // Timeout - refresh may have failed, check if cache timestamp was updated
                     // If timestamp unchanged, refresh failed and we should retry
-                    let cache_timestamp_after = {
-                        if let Ok(guard) = self.cache.read() {
-                            guard.0
-                        } else {
-                            // Lock poisoned, give up
-                            return;
-                        }
-                    };
+                    let cache_timestamp_after = lock_poison::read(&self.cache, KEYS_LOCK).0;
 
                     // If timestamp didn't change, refresh failed (cache not updated)
                     // Only retry if cache is still empty AND timestamp unchanged
                     if cache_timestamp_after == cache_timestamp_before {
-                        let still_empty = {
-                            if let Ok(guard) = self.cache.read() {
-                                guard.1.is_empty()
-                            } else {
-                                // Lock poisoned, give up
-                                return;
-                            }
-                        };
+                        let still_empty = lock_poison::read(&self.cache, KEYS_LOCK).1.is_empty();
 
                         if still_empty {
                             // Cache timestamp unchanged and still empty - refresh failed, retry
@@ -1027,6 +1141,7 @@ impl JwksBearerProvider {
                                 Self::refresh_jwks_internal(
                                     &self.cache,
                                     &self.jwks_url,
+                                    &self.outbound_tls,
                                     &self.refresh_in_progress,
                                     &self.refresh_complete,
                                     true, // We already claimed the flag
@@ -1041,25 +1156,11 @@ impl JwksBearerProvider {
                 // Refresh completed (flag cleared) - check if refresh succeeded by comparing timestamps
                 // If timestamp changed, refresh succeeded (even if empty keys) - don't retry
                 // If timestamp unchanged, refresh failed - retry
-                let cache_timestamp_after = {
-                    if let Ok(guard) = self.cache.read() {
-                        guard.0
-                    } else {
-                        // Lock poisoned, give up
-                        return;
-                    }
-                };
+                let cache_timestamp_after = lock_poison::read(&self.cache, KEYS_LOCK).0;
 
                 // Only retry if timestamp unchanged (refresh failed) AND cache still empty
                 if cache_timestamp_after == cache_timestamp_before {
-                    let still_empty = {
-                        if let Ok(guard) = self.cache.read() {
-                            guard.1.is_empty()
-                        } else {
-                            // Lock poisoned, give up
-                            return;
-                        }
-                    };
+                    let still_empty = lock_poison::read(&self.cache, KEYS_LOCK).1.is_empty();
 
                     if still_empty {
                         // Cache timestamp unchanged and still empty - refresh failed, retry
@@ -1072,6 +1173,7 @@ impl JwksBearerProvider {
                             Self::refresh_jwks_internal(
                                 &self.cache,
                                 &self.jwks_url,
+                                &self.outbound_tls,
                                 &self.refresh_in_progress,
                                 &self.refresh_complete,
                                 true, // We already claimed the flag
@@ -1107,6 +1209,7 @@ impl JwksBearerProvider {
             // handle that here. If refresh_jwks_internal fails, it also clears the flag.
             let cache = self.cache.clone();
             let jwks_url = self.jwks_url.clone();
+            let outbound_tls = self.outbound_tls.clone();
             // Clone Arc references - one set for the spawned thread, one for error handling
             let refresh_in_progress_thread = self.refresh_in_progress.clone();
             let refresh_complete_thread = self.refresh_complete.clone();
@@ -1122,6 +1225,7 @@ impl JwksBearerProvider {
                 Self::refresh_jwks_internal(
                     &cache,
                     &jwks_url,
+                    &outbound_tls,
                     &refresh_in_progress_thread,
                     &refresh_complete_thread,
                     true,
@@ -1136,7 +1240,7 @@ impl JwksBearerProvider {
                     refresh_in_progress_error.store(false, Ordering::Release);
                     // Notify any waiting threads that refresh won't happen
                     let (lock, cvar) = &*refresh_complete_error;
-                    let _guard = lock.lock().unwrap();
+                    let _guard = lock_poison::lock(lock, REFRESH_LOCK);
                     cvar.notify_all();
                 }
             }
@@ -1148,29 +1252,33 @@ impl JwksBearerProvider {
     /// The refresh is globally coalesced by `refresh_in_progress` and rate-limited by
     /// `unknown_kid_refresh_cooldown`. A caller that loses the refresh race waits for the
     /// in-flight refresh instead of starting another network request.
+    ///
+    /// The refresh is claimed under the cooldown lock: a concurrent miss that finds the
+    /// cooldown running is guaranteed to see the refresh that started it still in flight (or
+    /// already finished), so it never gives up on a key that refresh is about to cache.
     fn refresh_jwks_for_unknown_kid(&self) {
-        let should_start = match self.last_unknown_kid_refresh.lock() {
-            Ok(mut last_refresh) => {
-                let cooldown_elapsed = last_refresh
-                    .as_ref()
-                    .is_none_or(|instant| instant.elapsed() >= self.unknown_kid_refresh_cooldown);
-                if cooldown_elapsed {
-                    *last_refresh = Some(Instant::now());
-                }
-                cooldown_elapsed
+        let should_start = {
+            let mut last_refresh =
+                lock_poison::lock(&self.last_unknown_kid_refresh, UNKNOWN_KID_LOCK);
+            let cooldown_elapsed = last_refresh
+                .as_ref()
+                .is_none_or(|instant| instant.elapsed() >= self.unknown_kid_refresh_cooldown);
+            let claimed = cooldown_elapsed
+                && self
+                    .refresh_in_progress
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok();
+            if claimed {
+                *last_refresh = Some(Instant::now());
             }
-            Err(_) => false,
+            claimed
         };
 
-        if should_start
-            && self
-                .refresh_in_progress
-                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-                .is_ok()
-        {
+        if should_start {
             Self::refresh_jwks_internal(
                 &self.cache,
                 &self.jwks_url,
+                &self.outbound_tls,
                 &self.refresh_in_progress,
                 &self.refresh_complete,
                 true,
@@ -1180,19 +1288,26 @@ impl JwksBearerProvider {
 
         if self.refresh_in_progress.load(Ordering::Acquire) {
             let (lock, cvar) = &*self.refresh_complete;
-            if let Ok(guard) = lock.lock() {
-                let _ = cvar.wait_timeout_while(guard, Duration::from_secs(2), |_| {
-                    self.refresh_in_progress.load(Ordering::Acquire)
-                });
-            }
+            let guard = lock_poison::lock(lock, REFRESH_LOCK);
+            let _ = cvar.wait_timeout_while(guard, Duration::from_secs(2), |_| {
+                self.refresh_in_progress.load(Ordering::Acquire)
+            });
         }
     }
 
+    /// Write guard on the claims cache. A poisoned cache is emptied rather than trusted, so
+    /// tokens cached around the panic are validated again.
+    pub(super) fn claims_cache_guard(
+        &self,
+    ) -> std::sync::RwLockWriteGuard<'_, LruCache<Arc<str>, (i64, serde_json::Value, String)>> {
+        lock_poison::write_or_reset(&self.claims_cache, CLAIMS_CACHE_LOCK, LruCache::clear)
+    }
+
     /// Get decoding key for a given key ID (kid)
     ///
     /// P1: Non-blocking - uses lock-free reads (RwLock) and triggers refresh in background.
     /// If refresh fails, uses stale cache (graceful degradation).
-    pub(super) fn get_key_for(&self, kid: &str) -> Option<jsonwebtoken::DecodingKey> {
+    pub(super) fn get_key_for(&self, kid: &str) -> Option<JwkKey> {
         let span = tracing::span!(Level::DEBUG, "jwks_cache", kid = kid,);
         let _guard = span.enter();
 
@@ -1200,39 +1315,67 @@ impl JwksBearerProvider {
         self.refresh_jwks_if_needed();
 
         // Lock-free read (RwLock allows concurrent reads)
-        let cache_hit = if let Ok(guard) = self.cache.read() {
-            let result = guard.1.get(kid).cloned();
-            span.record("cache_hit", result.is_some());
-            result.is_some()
-        } else {
-            span.record("cache_hit", false);
-            false
-        };
+        let cache_hit = lock_poison::read(&self.cache, KEYS_LOCK)
+            .1
+            .contains_key(kid);
+        span.record("cache_hit", cache_hit);
 
         if cache_hit {
             span.record("cache_hit", true);
             // Record cache age from the cache timestamp
-            if let Ok(guard) = self.cache.read() {
-                let age = guard.0.elapsed();
-                span.record("cache_age_seconds", age.as_secs_f64());
-            }
+            let age = lock_poison::read(&self.cache, KEYS_LOCK).0.elapsed();
+            span.record("cache_age_seconds", age.as_secs_f64());
         }
 
         // Re-read to return the actual key.
-        if let Ok(guard) = self.cache.read() {
-            if let Some(key) = guard.1.get(kid).cloned() {
-                return Some(key);
-            }
+        if let Some(key) = lock_poison::read(&self.cache, KEYS_LOCK)
+            .1
+            .get(kid)
+            .cloned()
+        {
+            return Some(key);
         }
 
         // A cache can be fresh while the issuer has just rotated to a new kid. Force a
         // coalesced, rate-limited refresh rather than rejecting until the normal TTL expires.
         self.refresh_jwks_for_unknown_kid();
 
-        self.cache
-            .read()
-            .ok()
-            .and_then(|guard| guard.1.get(kid).cloned())
+        lock_poison::read(&self.cache, KEYS_LOCK)
+            .1
+            .get(kid)
+            .cloned()
+    }
+
+    /// Key for a token without a `kid`: the JWKS's only key, with its kid (`""` when the
+    /// key has none).
+    ///
+    /// Fails with [`ValidationError::MissingKeyId`] when the fallback is disabled or the
+    /// JWKS is empty, and [`ValidationError::AmbiguousKeyId`] when it holds several keys.
+    pub(super) fn sole_key(&self) -> Result<(String, JwkKey), ValidationError> {
+        if !self.allow_missing_kid {
+            return Err(ValidationError::MissingKeyId);
+        }
+        self.refresh_jwks_if_needed();
+        let cache = lock_poison::read(&self.cache, KEYS_LOCK);
+        let mut keys = cache.1.iter();
+        match (keys.next(), keys.next()) {
+            (Some((kid, key)), None) => Ok((kid.clone(), key.clone())),
+            (None, _) => Err(ValidationError::MissingKeyId),
+            _ => Err(ValidationError::AmbiguousKeyId {
+                keys: cache.1.len(),
+            }),
+        }
+    }
+}
+
+/// Claims-cache key for `token`: `token|kid`, or `token#` for a token without a `kid`.
+///
+/// The different separator keeps a kid-less entry apart from any `token|kid` entry, whatever
+/// key it was verified with.
+pub(super) fn claims_cache_key(token: &str, kid: Option<&str>) -> Arc<str> {
+    match kid {
+        Some(kid) => Arc::from(format!("{token}|{kid}")),
+        None => Arc::from(format!("{token}#")),
     }
 }
 
@@ -1328,6 +1471,17 @@ impl SecurityProvider for JwksBearerProvider {
     /// 5. Check `iss`, `aud`, `exp` claims
     /// 6. Verify scopes
     fn validate(&self, scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool {
+        self.check(scheme, scopes, req).is_granted()
+    }
+
+    /// Validate like [`Self::validate`], reporting a valid token that lacks required
+    /// scopes as [`AuthOutcome::InsufficientScope`] with the missing ones.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
         // Story 9.5: token.validation span — child of jwt_validation (if available)
         // Records token_size_bytes, header_size_bytes, token_version, result
         let token = self.extract_token(req);
@@ -1346,17 +1500,25 @@ impl SecurityProvider for JwksBearerProvider {
         );
         let _guard = span.enter();
 
-        let result = validation::validate_token_impl(self, scheme, scopes, req);
+        let result = validation::check_token_impl(self, scheme, scopes, req);
 
         // Record result in span attribute
-        span.record("result", if result { "valid" } else { "invalid" });
+        span.record(
+            "result",
+            if result.is_granted() {
+                "valid"
+            } else {
+                "invalid"
+            },
+        );
 
         // Record token version from claims if available
         if let Some(t) = &token {
             if let Ok(header) = jsonwebtoken::decode_header(t) {
                 if let Some(kid) = &header.kid {
                     // Try to get token version from claims cache
-                    if let Ok(mut cache) = self.claims_cache.write() {
+                    {
+                        let mut cache = self.claims_cache_guard();
                         let cache_key: Arc<str> = Arc::from(format!("{}|{}", t, kid));
                         if let Some((_, claims, _)) = cache.get::<Arc<str>>(&cache_key.into()) {
                             if let Some(ver) = claims.get("ver") {
@@ -1371,7 +1533,7 @@ impl SecurityProvider for JwksBearerProvider {
         }
 
         // Structured log on validation failure (WARN level)
-        if !result {
+        if !result.is_granted() {
             tracing::warn!(
                 event = "token_validation_failed",
                 token_size_bytes = token_size,
@@ -7,13 +7,15 @@
 //! All JWT fields (issuer, subject, client_id, session_id, jti, token_version,
 //! actor_subject) are extracted from claims and logged with appropriate log levels.
 
-use crate::security::jwks_bearer::{DecisionSource, JwtLogFields, JwtTokenStatus};
-use crate::security::SecurityRequest;
+use crate::security::jwks_bearer::{
+    claims_cache_key, DecisionSource, JwtLogFields, JwtTokenStatus,
+};
+use crate::security::{AuthOutcome, SecurityRequest};
 use crate::spec::SecurityScheme;
+use base64::Engine as _;
 use jsonwebtoken;
 use serde_json::Value;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
 use tracing::{debug, warn};
 
 /// Internal error types for JWT validation
@@ -32,10 +34,14 @@ pub(super) enum ValidationError {
     MissingKeyId,
     /// Key not found in JWKS for the given kid
     MissingKey { kid: String },
+    /// Token has no 'kid' and the JWKS holds several keys it could name
+    AmbiguousKeyId { keys: usize },
     /// Token signature is invalid
     InvalidSignature,
     /// Token has expired
     ExpiredToken { exp: i64, now: i64 },
+    /// Token `nbf` (or `iat`, when future `iat` is rejected) is ahead of now + leeway
+    NotYetValid { claim: &'static str },
     /// Token issuer doesn't match expected value
     InvalidIssuer {
         expected: Option<String>,
@@ -50,6 +56,10 @@ pub(super) enum ValidationError {
     MissingRequiredClaim { claim: String },
     /// Token uses an unsupported algorithm
     UnsupportedAlgorithm { alg: String },
+    /// Token header declares `alg: none`: an unsigned token
+    UnsecuredToken,
+    /// Token `alg` cannot be verified with the type of key its kid names
+    KeyAlgorithmMismatch { alg: String, kty: &'static str },
     /// Token has wrong or missing typ claim (RFC 9068)
     InvalidTokenType {
         expected: String,
@@ -84,12 +94,16 @@ impl ValidationError {
             ValidationError::InvalidTokenFormat { .. } => "invalid token format",
             ValidationError::MissingKeyId => "missing key ID",
             ValidationError::MissingKey { .. } => "key not found in JWKS",
+            ValidationError::AmbiguousKeyId { .. } => "ambiguous key ID",
             ValidationError::InvalidSignature => "invalid signature",
             ValidationError::ExpiredToken { .. } => "token expired",
+            ValidationError::NotYetValid { .. } => "token not yet valid",
             ValidationError::InvalidIssuer { .. } => "invalid issuer",
             ValidationError::InvalidAudience { .. } => "invalid audience",
             ValidationError::MissingRequiredClaim { .. } => "missing required claim",
             ValidationError::UnsupportedAlgorithm { .. } => "unsupported algorithm",
+            ValidationError::UnsecuredToken => "unsecured token",
+            ValidationError::KeyAlgorithmMismatch { .. } => "algorithm does not match key type",
             ValidationError::InvalidTokenType { .. } => "invalid token type",
             ValidationError::JwksFetchError { .. } => "JWKS fetch failed",
             ValidationError::InsufficientScopes { .. } => "insufficient scopes",
@@ -110,8 +124,10 @@ impl ValidationError {
             }
             ValidationError::MissingKeyId => "missing_kid".to_string(),
             ValidationError::MissingKey { kid } => format!("key_not_found: {}", kid),
+            ValidationError::AmbiguousKeyId { keys } => format!("ambiguous_kid: {} keys", keys),
             ValidationError::InvalidSignature => "invalid_signature".to_string(),
             ValidationError::ExpiredToken { exp: _, now: _ } => "token_expired".to_string(),
+            ValidationError::NotYetValid { claim } => format!("token_not_yet_valid: {}", claim),
             ValidationError::InvalidIssuer {
                 expected: _,
                 got: _,
@@ -126,6 +142,10 @@ impl ValidationError {
             ValidationError::UnsupportedAlgorithm { alg } => {
                 format!("unsupported_algorithm: {}", alg)
             }
+            ValidationError::UnsecuredToken => "unsecured_token".to_string(),
+            ValidationError::KeyAlgorithmMismatch { alg, kty } => {
+                format!("key_algorithm_mismatch: {} with {} key", alg, kty)
+            }
             ValidationError::InvalidTokenType {
                 expected: _,
                 got: _,
@@ -158,6 +178,12 @@ impl ValidationError {
                     kid
                 );
             }
+            ValidationError::AmbiguousKeyId { keys } => {
+                warn!(
+                    "JWT validation failed: token has no 'kid' and the JWKS holds {} keys",
+                    keys
+                );
+            }
             ValidationError::InvalidSignature => {
                 warn!("JWT validation failed: invalid signature");
             }
@@ -167,6 +193,12 @@ impl ValidationError {
                     exp, now
                 );
             }
+            ValidationError::NotYetValid { claim } => {
+                warn!(
+                    "JWT validation failed: '{}' is in the future beyond the allowed leeway",
+                    claim
+                );
+            }
             ValidationError::InvalidIssuer { expected, got } => {
                 warn!(
                     "JWT validation failed: invalid issuer (expected: {:?}, got: {:?})",
@@ -185,6 +217,15 @@ impl ValidationError {
             ValidationError::UnsupportedAlgorithm { alg } => {
                 warn!("JWT validation failed: unsupported algorithm '{}'", alg);
             }
+            ValidationError::UnsecuredToken => {
+                warn!("JWT validation failed: token declares alg 'none' (unsigned)");
+            }
+            ValidationError::KeyAlgorithmMismatch { alg, kty } => {
+                warn!(
+                    "JWT validation failed: algorithm '{}' cannot be verified with a '{}' key",
+                    alg, kty
+                );
+            }
             ValidationError::InvalidTokenType { expected, got } => {
                 warn!(
                     "JWT validation failed: invalid token type (expected: '{}', got: {:?})",
@@ -225,22 +266,71 @@ impl ValidationError {
     }
 }
 
+/// Signature and standard-claim policy for tokens signed with `alg`.
+///
+/// `exp` is required; `exp` and `nbf` (when present) are both checked with `leeway_secs` of
+/// clock skew in either direction.
+fn jwt_validation(
+    provider: &super::JwksBearerProvider,
+    alg: jsonwebtoken::Algorithm,
+) -> jsonwebtoken::Validation {
+    let mut validation = jsonwebtoken::Validation::new(alg);
+    validation.validate_exp = true;
+    validation.validate_nbf = true;
+    validation.set_required_spec_claims(&["exp"]);
+    validation.leeway = provider.leeway_secs;
+    if let Some(ref iss) = provider.iss {
+        validation.set_issuer(&[iss]);
+    }
+    if let Some(ref aud) = provider.aud {
+        validation.set_audience(&[aud]);
+    }
+    validation
+}
+
+/// With [`super::JwksBearerProvider::reject_future_iat`], reject `iat` beyond now + leeway.
+///
+/// `jsonwebtoken` does not check `iat`; a non-numeric `iat` is left to the issuer's contract.
+fn validate_iat(
+    provider: &super::JwksBearerProvider,
+    claims: &Value,
+    now: i64,
+) -> Result<(), ValidationError> {
+    if !provider.reject_future_iat {
+        return Ok(());
+    }
+    match claims.get("iat").and_then(Value::as_i64) {
+        Some(iat) if iat > now + provider.leeway_secs as i64 => {
+            Err(ValidationError::NotYetValid { claim: "iat" })
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Internal helper to validate a JWT token
 ///
-/// Returns `bool` for backward compatibility, but uses structured error types
-/// internally for better observability via logging.
+/// Maps the structured error types to an [`AuthOutcome`], logging failures for
+/// observability: only [`ValidationError::InsufficientScopes`] is reported as
+/// [`AuthOutcome::InsufficientScope`], every other failure is unauthenticated.
 /// Story 9.6: Structured JWT logging is called at all decision points.
-pub(super) fn validate_token_impl(
+pub(super) fn check_token_impl(
     provider: &super::JwksBearerProvider,
     scheme: &SecurityScheme,
     scopes: &[String],
     req: &SecurityRequest,
-) -> bool {
+) -> AuthOutcome {
     match validate_token_internal(provider, scheme, scopes, req) {
-        Ok(valid) => valid,
+        Ok(valid) => valid.into(),
         Err(e) => {
             e.log();
-            false
+            match e {
+                ValidationError::InsufficientScopes { required, got } => {
+                    AuthOutcome::InsufficientScope {
+                        missing: required.into_iter().filter(|s| !got.contains(s)).collect(),
+                    }
+                }
+                _ => AuthOutcome::Unauthenticated,
+            }
         }
     }
 }
@@ -321,6 +411,28 @@ fn validate_dynamic_token_status(
     Err(error)
 }
 
+/// Reject a token whose `jti` the configured [`crate::security::RevocationChecker`] reports.
+fn validate_not_revoked(
+    provider: &super::JwksBearerProvider,
+    token: &str,
+    claims: &Value,
+) -> Result<(), ValidationError> {
+    if !provider.is_revoked(claims) {
+        return Ok(());
+    }
+    let error = ValidationError::TokenRevoked;
+    let reason = error.error_reason();
+    extract_and_log_jwt_fields(
+        provider,
+        token,
+        claims,
+        DecisionSource::Denylist,
+        "denied",
+        Some(&reason),
+    );
+    Err(error)
+}
+
 /// Internal validation with structured error types
 fn validate_token_internal(
     provider: &super::JwksBearerProvider,
@@ -349,6 +461,11 @@ fn validate_token_internal(
         None => return Err(ValidationError::MissingToken),
     };
 
+    // SECURITY: Reject unsigned tokens by name, before anything else reads the header
+    if declares_alg_none(token) {
+        return Err(ValidationError::UnsecuredToken);
+    }
+
     // SECURITY: Parse header FIRST to get kid before cache lookup
     // This ensures cache key includes kid, so cache invalidates on key rotation
     let header = match jsonwebtoken::decode_header(token) {
@@ -360,10 +477,11 @@ fn validate_token_internal(
         }
     };
 
-    let kid = match header.kid {
-        Some(k) => k,
-        None => return Err(ValidationError::MissingKeyId),
-    };
+    // A token without a kid is verified with the JWKS's sole key (`allow_missing_kid`).
+    let header_kid = header.kid.clone();
+    if header_kid.is_none() && !provider.allow_missing_kid {
+        return Err(ValidationError::MissingKeyId);
+    }
 
     // SECURITY: Enforce JWT typ claim (RFC 9068) - reject type confusion attacks
     // This check must occur AFTER header parsing but BEFORE any trust decision
@@ -391,7 +509,7 @@ fn validate_token_internal(
 
     // SECURITY: Include kid in cache key so cache invalidates on key rotation
     // Format: "token|kid" ensures different cache entries for same token with different keys
-    let token_key: Arc<str> = Arc::from(format!("{}|{}", token, kid));
+    let token_key = claims_cache_key(token, header_kid.as_deref());
 
     // Check claims cache AFTER parsing header (we need kid for cache key)
     // SECURITY: On cache hit, verify key still exists in JWKS before using cached claims
@@ -400,39 +518,29 @@ fn validate_token_internal(
         // CRITICAL: Clone all needed values and release lock before calling get_key_for
         // get_key_for() can trigger HTTP requests (up to 400ms) via refresh_jwks_if_needed(),
         // which would block all other threads from accessing the claims cache
-        let cached_data = {
-            if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                if let Some((exp_timestamp_with_leeway, cached_claims, cached_kid)) =
-                    cache_guard.get(&token_key)
-                {
-                    // Clone all values while holding the lock
-                    Some((
-                        *exp_timestamp_with_leeway,
-                        cached_claims.clone(),
-                        cached_kid.clone(),
-                    ))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        };
+        // Clone all values while holding the lock; the guard is dropped at the end of the statement
+        let cached_data = provider.claims_cache_guard().get(&token_key).map(
+            |(exp_timestamp_with_leeway, cached_claims, cached_kid)| {
+                (
+                    *exp_timestamp_with_leeway,
+                    cached_claims.clone(),
+                    cached_kid.clone(),
+                )
+            },
+        );
 
         if let Some((exp_timestamp_clone, cached_claims_clone, cached_kid_clone)) = cached_data {
             // Lock is now released - safe to call get_key_for which may trigger HTTP requests
             // SECURITY: Verify the key still exists in JWKS (key rotation check)
             // If key was rotated, this will return None and we'll re-validate
-            if provider.get_key_for(&cached_kid_clone).is_none() {
+            if !cached_key_current(provider, header_kid.as_deref(), &cached_kid_clone) {
                 // Key no longer exists (rotated/revoked), remove from cache
                 debug!(
                     "JWT cache: key '{}' no longer in JWKS, invalidating cache entry",
                     cached_kid_clone
                 );
                 // Re-acquire lock to remove cache entry
-                if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                    cache_guard.pop(&token_key);
-                }
+                provider.claims_cache_guard().pop(&token_key);
                 // Fall through to full validation below
             } else {
                 // Key still exists, check expiration
@@ -446,6 +554,11 @@ fn validate_token_internal(
                     provider.cache_hits.fetch_add(1, Ordering::Relaxed);
 
                     validate_dynamic_token_status(provider, token, &cached_claims_clone)?;
+                    if let Err(e) = validate_not_revoked(provider, token, &cached_claims_clone) {
+                        // Revoked after it was cached: drop the entry so it is never served again
+                        provider.claims_cache_guard().pop(&token_key);
+                        return Err(e);
+                    }
 
                     // Story 9.6: Log cache-hit validation (decision_source = jwt_claims)
                     let token_scopes = cached_claims_clone
@@ -490,9 +603,7 @@ fn validate_token_internal(
                     // Token expired, remove from cache
                     debug!("JWT cache: token expired, removing from cache");
                     // Re-acquire lock to remove expired entry
-                    if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                        cache_guard.pop(&token_key);
-                    }
+                    provider.claims_cache_guard().pop(&token_key);
                 }
             }
         }
@@ -509,24 +620,19 @@ fn validate_token_internal(
         .as_secs() as i64;
 
     // Get key for validation (will trigger JWKS refresh if needed)
-    let key = match provider.get_key_for(&kid) {
-        Some(k) => k,
-        None => return Err(ValidationError::MissingKey { kid: kid.clone() }),
-    };
+    let (kid, key) = key_for_token(provider, header_kid)?;
 
-    let selected_alg = header.alg;
-    let mut validation = jsonwebtoken::Validation::new(selected_alg);
-    validation.validate_exp = true;
-    validation.set_required_spec_claims(&["exp"]);
-    validation.leeway = provider.leeway_secs;
-    if let Some(ref iss) = provider.iss {
-        validation.set_issuer(&[iss]);
-    }
-    if let Some(ref aud) = provider.aud {
-        validation.set_audience(&[aud]);
+    // SECURITY: The key's type, not the token, decides which algorithms it verifies
+    if !key.kind.verifies(header.alg) {
+        return Err(ValidationError::KeyAlgorithmMismatch {
+            alg: format!("{:?}", header.alg),
+            kty: key.kind.kty(),
+        });
     }
+
+    let validation = jwt_validation(provider, header.alg);
     let data: Result<jsonwebtoken::TokenData<Value>, jsonwebtoken::errors::Error> =
-        jsonwebtoken::decode(token, &key, &validation);
+        jsonwebtoken::decode(token, &key.decoding, &validation);
     let claims = match data {
         Ok(d) => d.claims,
         Err(e) => {
@@ -541,6 +647,9 @@ fn validate_token_internal(
                     let exp = now; // Default to now if we can't extract
                     ValidationError::ExpiredToken { exp, now }
                 }
+                jsonwebtoken::errors::ErrorKind::ImmatureSignature => {
+                    ValidationError::NotYetValid { claim: "nbf" }
+                }
                 jsonwebtoken::errors::ErrorKind::InvalidSignature => {
                     ValidationError::InvalidSignature
                 }
@@ -577,7 +686,10 @@ fn validate_token_internal(
         }
     };
 
+    validate_iat(provider, &claims, now)?;
     validate_dynamic_token_status(provider, token, &claims)?;
+    // Checked before caching so revoked tokens never enter the claims cache
+    validate_not_revoked(provider, token, &claims)?;
 
     // P0: Store decoded claims in cache with leeway applied to expiration
     // Extract exp claim to determine cache validity
@@ -589,7 +701,8 @@ fn validate_token_internal(
             // Only cache if token hasn't expired (with leeway)
             // SECURITY: Store kid with cached claims so we can verify key existence on cache hits
             if now < exp_timestamp_with_leeway {
-                if let Ok(mut cache_guard) = provider.claims_cache.write() {
+                {
+                    let mut cache_guard = provider.claims_cache_guard();
                     // P0: Use Arc<str> key (already created above with kid included)
                     // P1: Write lock for cache insert (eviction may occur)
                     // P2: Track evictions correctly - LruCache::put() returns Some(old_value) when
@@ -651,6 +764,51 @@ fn validate_token_internal(
     }
 }
 
+/// Key that verifies a token whose header names `kid`, with the kid it is cached under
+///
+/// A token without a kid falls back to [`super::JwksBearerProvider::sole_key`].
+fn key_for_token(
+    provider: &super::JwksBearerProvider,
+    kid: Option<String>,
+) -> Result<(String, super::JwkKey), ValidationError> {
+    match kid {
+        Some(kid) => match provider.get_key_for(&kid) {
+            Some(key) => Ok((kid, key)),
+            None => Err(ValidationError::MissingKey { kid }),
+        },
+        None => provider.sole_key(),
+    }
+}
+
+/// Whether the token's header declares `alg: none` (in any case)
+///
+/// `jsonwebtoken` has no `none` algorithm, so such a header would otherwise only fail to
+/// parse; naming it makes the rejection explicit and independent of the library.
+fn declares_alg_none(token: &str) -> bool {
+    let encoded = token.split('.').next().unwrap_or("");
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .ok()
+        .and_then(|json| serde_json::from_slice::<Value>(&json).ok())
+        .and_then(|header| header.get("alg")?.as_str().map(str::to_owned))
+        .is_some_and(|alg| alg.eq_ignore_ascii_case("none"))
+}
+
+/// Whether the key a cached entry was verified with (`cached_kid`) still verifies the token
+///
+/// For a token without a kid that key must also still be the JWKS's only one: once another
+/// key is added the token is ambiguous and is validated again.
+fn cached_key_current(
+    provider: &super::JwksBearerProvider,
+    header_kid: Option<&str>,
+    cached_kid: &str,
+) -> bool {
+    match header_kid {
+        Some(_) => provider.get_key_for(cached_kid).is_some(),
+        None => provider.sole_key().is_ok_and(|(kid, _)| kid == cached_kid),
+    }
+}
+
 /// Internal helper to extract JWT claims
 pub(super) fn extract_claims_impl(
     provider: &super::JwksBearerProvider,
@@ -668,6 +826,10 @@ pub(super) fn extract_claims_impl(
 
     let token = provider.extract_token(req)?;
 
+    if declares_alg_none(token) {
+        return None;
+    }
+
     // Parse header to get kid for cache key
     let header = match jsonwebtoken::decode_header(token) {
         Ok(h) => h,
@@ -685,49 +847,42 @@ pub(super) fn extract_claims_impl(
         return None;
     }
 
-    let kid = header.kid?;
+    let header_kid = header.kid;
+    if header_kid.is_none() && !provider.allow_missing_kid {
+        return None;
+    }
 
     // Check cache first
     // SECURITY: On cache hit, verify key still exists in JWKS before using cached claims
     // This ensures tokens are invalidated when keys are rotated/revoked
-    let token_key: Arc<str> = Arc::from(format!("{}|{}", token, kid));
+    let token_key = claims_cache_key(token, header_kid.as_deref());
     {
         // CRITICAL: Clone all needed values and release lock before calling get_key_for
         // get_key_for() can trigger HTTP requests (up to 400ms) via refresh_jwks_if_needed(),
         // which would block all other threads from accessing the claims cache
-        let cached_data = {
-            if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                if let Some((exp_timestamp_with_leeway, cached_claims, cached_kid)) =
-                    cache_guard.get(&token_key)
-                {
-                    // Clone all values while holding the lock
-                    Some((
-                        *exp_timestamp_with_leeway,
-                        cached_claims.clone(),
-                        cached_kid.clone(),
-                    ))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        };
+        // Clone all values while holding the lock; the guard is dropped at the end of the statement
+        let cached_data = provider.claims_cache_guard().get(&token_key).map(
+            |(exp_timestamp_with_leeway, cached_claims, cached_kid)| {
+                (
+                    *exp_timestamp_with_leeway,
+                    cached_claims.clone(),
+                    cached_kid.clone(),
+                )
+            },
+        );
 
         if let Some((exp_timestamp_clone, cached_claims_clone, cached_kid_clone)) = cached_data {
             // Lock is now released - safe to call get_key_for which may trigger HTTP requests
             // SECURITY: Verify the key still exists in JWKS (key rotation check)
             // If key was rotated, this will return None and we'll re-validate
-            if provider.get_key_for(&cached_kid_clone).is_none() {
+            if !cached_key_current(provider, header_kid.as_deref(), &cached_kid_clone) {
                 // Key no longer exists (rotated/revoked), remove from cache
                 debug!(
                     "JWT cache: key '{}' no longer in JWKS, invalidating cache entry for claims extraction",
                     cached_kid_clone
                 );
                 // Re-acquire lock to remove cache entry
-                if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                    cache_guard.pop(&token_key);
-                }
+                provider.claims_cache_guard().pop(&token_key);
                 // Fall through to full validation below
             } else {
                 // Key still exists, check expiration
@@ -744,9 +899,7 @@ pub(super) fn extract_claims_impl(
                     // Token expired, remove from cache
                     debug!("JWT cache: token expired for claims extraction, removing from cache");
                     // Re-acquire lock to remove expired entry
-                    if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                        cache_guard.pop(&token_key);
-                    }
+                    provider.claims_cache_guard().pop(&token_key);
                 }
             }
         }
@@ -762,24 +915,14 @@ pub(super) fn extract_claims_impl(
         .as_secs() as i64;
 
     // Get key for validation
-    let key = match provider.get_key_for(&kid) {
-        Some(k) => k,
-        None => return None,
-    };
-
-    let selected_alg = header.alg;
-    let mut validation = jsonwebtoken::Validation::new(selected_alg);
-    validation.validate_exp = true;
-    validation.set_required_spec_claims(&["exp"]);
-    validation.leeway = provider.leeway_secs;
-    if let Some(ref iss) = provider.iss {
-        validation.set_issuer(&[iss]);
-    }
-    if let Some(ref aud) = provider.aud {
-        validation.set_audience(&[aud]);
+    let (kid, key) = key_for_token(provider, header_kid).ok()?;
+    if !key.kind.verifies(header.alg) {
+        return None;
     }
+
+    let validation = jwt_validation(provider, header.alg);
     let data: Result<jsonwebtoken::TokenData<Value>, jsonwebtoken::errors::Error> =
-        jsonwebtoken::decode(token, &key, &validation);
+        jsonwebtoken::decode(token, &key.decoding, &validation);
     let claims = match data {
         Ok(d) => d.claims,
         Err(e) => {
@@ -787,15 +930,18 @@ pub(super) fn extract_claims_impl(
             return None;
         }
     };
+    if validate_iat(provider, &claims, now).is_err() || provider.is_revoked(&claims) {
+        return None;
+    }
 
     // Store in cache
     if let Some(exp_value) = claims.get("exp") {
         if let Some(exp_timestamp) = exp_value.as_i64() {
             let exp_timestamp_with_leeway = exp_timestamp + provider.leeway_secs as i64;
             if now < exp_timestamp_with_leeway {
-                if let Ok(mut cache_guard) = provider.claims_cache.write() {
-                    cache_guard.put(token_key, (exp_timestamp_with_leeway, claims.clone(), kid));
-                }
+                provider
+                    .claims_cache_guard()
+                    .put(token_key, (exp_timestamp_with_leeway, claims.clone(), kid));
             }
         }
     }
@@ -896,6 +1042,15 @@ mod tests {
 
         let e = ValidationError::ExpiredToken { exp: 100, now: 200 };
         assert_eq!(e.error_reason(), "token_expired");
+
+        let e = ValidationError::KeyAlgorithmMismatch {
+            alg: "HS256".to_string(),
+            kty: "RSA",
+        };
+        assert_eq!(
+            e.error_reason(),
+            "key_algorithm_mismatch: HS256 with RSA key"
+        );
     }
 
     /// Unit: JwtLogFields from empty claims returns all None.
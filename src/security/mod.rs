@@ -19,9 +19,11 @@
 //!
 //! 1. Request arrives with credentials (header, cookie, query param)
 //! 2. Router determines which security scheme(s) are required for the route
-//! 3. Appropriate [`SecurityProvider`] is invoked to validate credentials
-//! 4. If validation succeeds, request proceeds to handler
-//! 5. If validation fails, 401/403 response is returned
+//! 3. Appropriate [`SecurityProvider`] is invoked to check credentials
+//! 4. If the check succeeds ([`AuthOutcome::Granted`]), request proceeds to handler
+//! 5. Missing or invalid credentials ([`AuthOutcome::Unauthenticated`]) return 401; valid
+//!    credentials lacking scopes ([`AuthOutcome::InsufficientScope`]) return 403 with the
+//!    missing scope names in the problem's `missing_scopes`
 //!
 //! ## Security Providers
 //!
@@ -87,6 +89,17 @@
 //!     .jwks_url("https://spiffe.example.com/.well-known/jwks.json"); // REQUIRED
 //! ```
 //!
+//! With OpenID Connect discovery, the JWKS URL and issuer are read from
+//! `{issuer}/.well-known/openid-configuration` (the discovered issuer must match):
+//!
+//! ```rust,no_run
+//! use brrtrouter::security::JwksBearerProvider;
+//!
+//! let provider = JwksBearerProvider::from_oidc_discovery("https://auth.example.com")
+//!     .expect("OIDC discovery failed")
+//!     .audience("my-api");
+//! ```
+//!
 //! **Note**: `JwksBearerProvider` and `SpiffeProvider` are independent:
 //! - Use `JwksBearerProvider` for standard JWKS-based JWT validation (no SPIFFE)
 //! - Use `SpiffeProvider` for SPIFFE SVID validation (requires JWKS for signature verification)
@@ -242,7 +255,7 @@
 //! service.register_security_provider("bearerAuth", Arc::new(jwt_provider));
 //! ```
 
-use crate::dispatcher::HeaderVec;
+use crate::dispatcher::{HeaderLookup, HeaderVec};
 use crate::router::ParamVec;
 use crate::spec::SecurityScheme;
 use serde_json::Value;
@@ -299,10 +312,7 @@ impl<'a> SecurityRequest<'a> {
     /// Get a header by name (case-insensitive)
     #[inline]
     pub fn get_header(&self, name: &str) -> Option<&str> {
-        self.headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case(name))
-            .map(|(_, v)| v.as_str())
+        self.headers.get(name)
     }
 
     /// Get a query parameter by name
@@ -324,6 +334,58 @@ impl<'a> SecurityRequest<'a> {
     }
 }
 
+/// Result of [`SecurityProvider::check`].
+///
+/// Tells a client that must (re-)authenticate (`401`) apart from one that is authenticated
+/// but not allowed (`403`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthOutcome {
+    /// Valid credentials carrying every required scope
+    Granted,
+    /// No credentials, or credentials that did not validate
+    Unauthenticated,
+    /// Valid credentials lacking some required scopes
+    InsufficientScope {
+        /// Required scopes the credentials do not carry, in requirement order
+        missing: Vec<String>,
+    },
+}
+
+impl AuthOutcome {
+    /// Whether the request is authenticated and authorized.
+    #[must_use]
+    pub fn is_granted(&self) -> bool {
+        matches!(self, Self::Granted)
+    }
+
+    /// Outcome for valid credentials granting the whitespace-separated scopes of `granted`
+    /// (an OAuth2 `scope` claim): `Granted` when it covers `required`.
+    #[must_use]
+    pub fn from_scope_claim(required: &[String], granted: &str) -> Self {
+        let missing: Vec<String> = required
+            .iter()
+            .filter(|scope| !granted.split_whitespace().any(|g| g == scope.as_str()))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            Self::Granted
+        } else {
+            Self::InsufficientScope { missing }
+        }
+    }
+}
+
+impl From<bool> for AuthOutcome {
+    /// `Granted` or `Unauthenticated`, for providers without scopes.
+    fn from(valid: bool) -> Self {
+        if valid {
+            Self::Granted
+        } else {
+            Self::Unauthenticated
+        }
+    }
+}
+
 /// Trait for implementing security validation providers.
 ///
 /// Implement this trait to create custom authentication/authorization logic
@@ -342,6 +404,34 @@ pub trait SecurityProvider: Send + Sync {
     /// `true` if the request is authenticated and authorized, `false` otherwise
     fn validate(&self, scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool;
 
+    /// Validate like [`Self::validate`], telling missing or invalid credentials apart from
+    /// valid credentials that lack scopes.
+    ///
+    /// The server calls this to answer `401` ([`AuthOutcome::Unauthenticated`]) or `403`
+    /// ([`AuthOutcome::InsufficientScope`]).
+    ///
+    /// # Default Implementation
+    ///
+    /// When [`Self::validate`] fails with scopes, validates again without them; if that
+    /// succeeds, every required scope is reported missing. Providers that can read the
+    /// granted scopes should override this to name only the missing ones.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        if self.validate(scheme, scopes, req) {
+            AuthOutcome::Granted
+        } else if !scopes.is_empty() && self.validate(scheme, &[], req) {
+            AuthOutcome::InsufficientScope {
+                missing: scopes.to_vec(),
+            }
+        } else {
+            AuthOutcome::Unauthenticated
+        }
+    }
+
     /// Extract claims from a validated request (optional).
     ///
     /// This method is called after `validate()` returns `true` to extract any
@@ -376,14 +466,17 @@ pub trait SecurityProvider: Send + Sync {
 }
 
 // Re-export all providers
+pub use auth_context::AuthContext;
 pub use bearer_jwt::BearerJwtProvider;
-pub use jwks_bearer::{JwksBearerProvider, JwtTokenStatus, JwtTokenStatusChecker};
+pub use jwks_bearer::{
+    clear_discovery_cache, JwksBearerProvider, JwtTokenStatus, JwtTokenStatusChecker,
+    OidcDiscoveryDocument, OidcDiscoveryError,
+};
 pub use oauth2::OAuth2Provider;
 pub use remote_api_key::RemoteApiKeyProvider;
-pub use spiffe::{
-    InMemoryRevocationChecker, NoOpRevocationChecker, RevocationChecker, SpiffeConfigError,
-    SpiffeProvider,
-};
+pub use revocation::{InMemoryRevocationChecker, NoOpRevocationChecker, RevocationChecker};
+pub use spiffe::{SpiffeConfigError, SpiffeProvider};
+pub use webhook::{hmac_sha256, verify_hmac_sha256};
 
 // Decision types (Story 9.4 — shadow decision observability)
 pub mod decision;
@@ -393,8 +486,11 @@ pub mod shadow;
 pub mod jwt_auth;
 
 // Provider modules
+mod auth_context;
 mod bearer_jwt;
 mod jwks_bearer;
 mod oauth2;
 mod remote_api_key;
+mod revocation;
 mod spiffe;
+mod webhook;
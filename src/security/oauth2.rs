@@ -1,4 +1,4 @@
-use crate::security::{BearerJwtProvider, SecurityProvider, SecurityRequest};
+use crate::security::{AuthOutcome, BearerJwtProvider, SecurityProvider, SecurityRequest};
 use crate::spec::SecurityScheme;
 
 /// OAuth2 provider using the same simple JWT validation as `BearerJwtProvider`.
@@ -94,19 +94,30 @@ impl SecurityProvider for OAuth2Provider {
     /// - `true` - Token is valid and contains required scopes
     /// - `false` - Token missing, invalid, or missing scopes
     fn validate(&self, scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool {
+        self.check(scheme, scopes, req).is_granted()
+    }
+
+    /// Like [`Self::validate`]; a valid token without every scope is
+    /// [`AuthOutcome::InsufficientScope`] naming the missing ones.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
         match scheme {
             SecurityScheme::OAuth2 { .. } => {}
-            _ => return false,
+            _ => return AuthOutcome::Unauthenticated,
         }
         let token = match self.extract_token(req) {
             Some(t) => t,
-            None => return false,
+            None => return AuthOutcome::Unauthenticated,
         };
         // Reuse BearerJwtProvider logic
         let helper = BearerJwtProvider {
             signature: self.signature.clone(),
             cookie_name: None,
         };
-        helper.validate_token(token, scopes)
+        helper.check_token(token, scopes)
     }
 }
@@ -1,8 +1,12 @@
-use crate::security::{SecurityProvider, SecurityRequest};
+use crate::lock_poison;
+use crate::security::{AuthOutcome, SecurityProvider, SecurityRequest};
 use crate::spec::SecurityScheme;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+const CACHE_LOCK: &str = "remote_api_key.cache";
+
 /// Remote API key verification provider with simple caching.
 pub struct RemoteApiKeyProvider {
     verify_url: String,
@@ -10,6 +14,7 @@ pub struct RemoteApiKeyProvider {
     cache_ttl: Duration,
     cache: std::sync::Mutex<HashMap<String, (Instant, bool)>>,
     header_name: String,
+    tls: Option<Arc<crate::http::OutboundTls>>,
 }
 
 impl RemoteApiKeyProvider {
@@ -28,6 +33,7 @@ impl RemoteApiKeyProvider {
             cache_ttl: Duration::from_secs(60),
             cache: std::sync::Mutex::new(HashMap::new()),
             header_name: "x-api-key".to_string(),
+            tls: None,
         }
     }
 
@@ -55,6 +61,15 @@ impl RemoteApiKeyProvider {
         self
     }
 
+    /// Verify `https://` calls against a private CA bundle (and optional SPKI pins) instead of
+    /// the system trust store
+    ///
+    /// Build the [`crate::http::OutboundTls`] at startup so a bad bundle or pin fails there.
+    pub fn tls(mut self, tls: crate::http::OutboundTls) -> Self {
+        self.tls = Some(Arc::new(tls));
+        self
+    }
+
     fn extract_key<'a>(&self, req: &'a SecurityRequest, header_name: &str) -> Option<&'a str> {
         // Prefer named header, also accept Authorization: Bearer <key>
         req.get_header(header_name).or_else(|| {
@@ -159,13 +174,7 @@ impl SecurityProvider for RemoteApiKeyProvider {
             None => return false,
         };
         // Cache lookup
-        if let Some((ts, ok)) = self
-            .cache
-            .lock()
-            .expect("API key cache Mutex poisoned - critical error")
-            .get(key)
-            .cloned()
-        {
+        if let Some((ts, ok)) = lock_poison::lock(&self.cache, CACHE_LOCK).get(key).cloned() {
             if ts.elapsed() < self.cache_ttl {
                 return ok;
             }
@@ -175,14 +184,22 @@ impl SecurityProvider for RemoteApiKeyProvider {
             timeout: Duration::from_millis(self.timeout_ms),
             max_body_bytes: 4096,
             extra_headers: vec![("X-API-Key".to_string(), key.to_string())],
+            tls: self.tls.clone(),
         };
         let ok = crate::http::fetch_get(&self.verify_url, &options)
             .map(|(status, _)| (200..300).contains(&status))
             .unwrap_or(false);
-        self.cache
-            .lock()
-            .expect("API key cache Mutex poisoned - critical error")
-            .insert(key.to_string(), (Instant::now(), ok));
+        lock_poison::lock(&self.cache, CACHE_LOCK).insert(key.to_string(), (Instant::now(), ok));
         ok
     }
+
+    /// API keys carry no scopes: `Granted` or `Unauthenticated`, verified once.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        self.validate(scheme, scopes, req).into()
+    }
 }
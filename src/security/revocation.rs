@@ -1,10 +1,14 @@
-//! Token revocation checking for SPIFFE SVIDs
+//! Token revocation checking shared by JWT-based providers
 //!
 //! This module provides interfaces and implementations for checking if a JWT ID (jti)
 //! has been revoked. Supports multiple backends: in-memory, Redis, database, and external services.
+//! Consulted by [`super::SpiffeProvider`] and [`super::JwksBearerProvider`].
 
+use crate::lock_poison;
 use std::sync::Arc;
 
+const REVOKED_LOCK: &str = "revocation.revoked";
+
 /// Trait for checking if a token has been revoked
 ///
 /// Implementations can use various backends:
@@ -45,23 +49,17 @@ impl InMemoryRevocationChecker {
 
     /// Revoke a JWT ID
     pub fn revoke(&self, jti: &str) {
-        if let Ok(mut guard) = self.revoked.write() {
-            guard.insert(jti.to_string());
-        }
+        lock_poison::write(&self.revoked, REVOKED_LOCK).insert(jti.to_string());
     }
 
     /// Unrevoke a JWT ID (for testing)
     pub fn unrevoke(&self, jti: &str) {
-        if let Ok(mut guard) = self.revoked.write() {
-            guard.remove(jti);
-        }
+        lock_poison::write(&self.revoked, REVOKED_LOCK).remove(jti);
     }
 
     /// Clear all revocations (for testing)
     pub fn clear(&self) {
-        if let Ok(mut guard) = self.revoked.write() {
-            guard.clear();
-        }
+        lock_poison::write(&self.revoked, REVOKED_LOCK).clear();
     }
 }
 
@@ -73,10 +71,7 @@ impl Default for InMemoryRevocationChecker {
 
 impl RevocationChecker for InMemoryRevocationChecker {
     fn is_revoked(&self, jti: &str) -> bool {
-        self.revoked
-            .read()
-            .map(|guard| guard.contains(jti))
-            .unwrap_or(false)
+        lock_poison::read(&self.revoked, REVOKED_LOCK).contains(jti)
     }
 }
 
@@ -44,10 +44,10 @@
 //! For Windows enterprise environments, SPIFFE IDs can be mapped to Windows user accounts
 //! and integrated with Active Directory for seamless single sign-on.
 
-mod revocation;
 mod validation;
 
-use crate::security::{SecurityProvider, SecurityRequest};
+use crate::lock_poison;
+use crate::security::{AuthOutcome, RevocationChecker, SecurityProvider, SecurityRequest};
 use crate::spec::SecurityScheme;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -59,7 +59,9 @@ use std::time::{Duration, Instant};
 use tracing::debug;
 use url::Url;
 
-pub use revocation::{InMemoryRevocationChecker, NoOpRevocationChecker, RevocationChecker};
+// Lock names reported in `brrtrouter_lock_poison_total`.
+const JWKS_CACHE_LOCK: &str = "spiffe.jwks_keys";
+const JWKS_REFRESH_LOCK: &str = "spiffe.jwks_refresh";
 
 /// Configuration error for SPIFFE provider
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -367,13 +369,7 @@ impl SpiffeProvider {
         };
 
         // Check if cache is empty - if so, we need to wait for refresh
-        let is_empty = {
-            if let Ok(guard) = cache.read() {
-                guard.1.is_empty()
-            } else {
-                return None;
-            }
-        };
+        let is_empty = lock_poison::read(cache, JWKS_CACHE_LOCK).1.is_empty();
 
         // Trigger refresh if needed
         self.refresh_jwks_if_needed();
@@ -381,16 +377,15 @@ impl SpiffeProvider {
         // If cache was empty, wait for refresh to complete
         if is_empty {
             let (lock, cvar) = &**refresh_complete;
-            let guard = lock.lock().unwrap();
+            let guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
             let _ = cvar.wait_timeout(guard, Duration::from_secs(5));
         }
 
         // Read from cache
-        if let Ok(guard) = cache.read() {
-            guard.1.get(kid).cloned()
-        } else {
-            None
-        }
+        lock_poison::read(cache, JWKS_CACHE_LOCK)
+            .1
+            .get(kid)
+            .cloned()
     }
 
     /// Get decoding key only (for backward compatibility with existing code)
@@ -417,14 +412,11 @@ impl SpiffeProvider {
 
         // Check if refresh is needed
         let (needs_refresh, is_empty) = {
-            if let Ok(guard) = cache.read() {
-                (
-                    guard.0.elapsed() >= self.jwks_cache_ttl || guard.1.is_empty(),
-                    guard.1.is_empty(),
-                )
-            } else {
-                return;
-            }
+            let guard = lock_poison::read(cache, JWKS_CACHE_LOCK);
+            (
+                guard.0.elapsed() >= self.jwks_cache_ttl || guard.1.is_empty(),
+                guard.1.is_empty(),
+            )
         };
 
         if !needs_refresh {
@@ -450,7 +442,7 @@ impl SpiffeProvider {
             } else {
                 // Another thread is refreshing - wait for it
                 let (lock, cvar) = &**refresh_complete;
-                let guard = lock.lock().unwrap();
+                let guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
                 let _ = cvar.wait_timeout(guard, Duration::from_secs(5));
                 return;
             }
@@ -491,7 +483,7 @@ impl SpiffeProvider {
                 // Clear flag on spawn failure
                 refresh_in_progress_err.store(false, Ordering::Release);
                 let (lock, cvar) = &*refresh_complete_err;
-                let _guard = lock.lock().unwrap();
+                let _guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
                 cvar.notify_all();
                 debug!("Failed to spawn SPIFFE JWKS refresh thread: {}", e);
             });
@@ -720,6 +712,16 @@ impl SecurityProvider for SpiffeProvider {
         validation::validate_svid_impl(self, scheme, scopes, req)
     }
 
+    /// SVIDs carry no scopes: `Granted` or `Unauthenticated`, validated once.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        self.validate(scheme, scopes, req).into()
+    }
+
     /// Extract SPIFFE claims from a validated request.
     ///
     /// Returns the decoded JWT claims from a validated SPIFFE SVID, including:
@@ -7,6 +7,7 @@
 //! - JWT signature verification (if JWKS configured)
 //! - Expiration checking
 
+use crate::lock_poison;
 use crate::security::SecurityRequest;
 use crate::spec::SecurityScheme;
 use regex::Regex;
@@ -17,7 +18,7 @@ use std::sync::{Arc, Condvar, Mutex, RwLock};
 use std::time::{Duration, Instant};
 use tracing::{debug, warn};
 
-use super::SpiffeProvider;
+use super::{SpiffeProvider, JWKS_CACHE_LOCK, JWKS_REFRESH_LOCK};
 
 /// SPIFFE ID format regex: `spiffe://trust-domain/path`
 /// Trust domain: alphanumeric, dots, hyphens, underscores
@@ -318,13 +319,14 @@ pub(super) fn refresh_jwks_internal(
         timeout: Duration::from_millis(200),
         max_body_bytes: 256 * 1024,
         extra_headers: Vec::new(),
+        tls: None,
     };
     let body = match crate::http::fetch_get_text_with_retry(jwks_url, &fetch_options, 2) {
         Some(b) => b,
         None => {
             refresh_in_progress.store(false, Ordering::Release);
             let (lock, cvar) = &**refresh_complete;
-            let _guard = lock.lock().unwrap();
+            let _guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
             cvar.notify_all();
             return;
         }
@@ -335,7 +337,7 @@ pub(super) fn refresh_jwks_internal(
         Err(_) => {
             refresh_in_progress.store(false, Ordering::Release);
             let (lock, cvar) = &**refresh_complete;
-            let _guard = lock.lock().unwrap();
+            let _guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
             cvar.notify_all();
             return;
         }
@@ -467,14 +469,12 @@ pub(super) fn refresh_jwks_internal(
     let key_count = new_map.len();
     let refresh_duration = refresh_start.elapsed();
 
-    if let Ok(mut guard) = cache.write() {
-        *guard = (Instant::now(), new_map);
-    }
+    *lock_poison::write(cache, JWKS_CACHE_LOCK) = (Instant::now(), new_map);
 
     refresh_in_progress.store(false, Ordering::Release);
 
     let (lock, cvar) = &**refresh_complete;
-    let _guard = lock.lock().unwrap();
+    let _guard = lock_poison::lock(lock, JWKS_REFRESH_LOCK);
     cvar.notify_all();
 
     debug!(
@@ -0,0 +1,110 @@
+//! HMAC-SHA256 signatures over raw request bodies (webhooks)
+//!
+//! Webhook senders such as GitHub (`X-Hub-Signature-256: sha256=<hex>`) or Stripe
+//! (`Stripe-Signature: t=<ts>,v1=<hex>` over `"<ts>.<body>"`) sign the exact bytes they sent.
+//! Verify against [`HandlerRequest::raw_body`](crate::dispatcher::HandlerRequest::raw_body);
+//! the parsed JSON body re-serializes differently (key order, whitespace).
+//!
+//! ```rust
+//! use brrtrouter::dispatcher::{HandlerRequest, HeaderLookup};
+//! use brrtrouter::security::verify_hmac_sha256;
+//!
+//! fn is_from_github(req: &HandlerRequest, secret: &[u8]) -> bool {
+//!     match (&req.raw_body, req.headers.get("x-hub-signature-256")) {
+//!         (Some(body), Some(signature)) => verify_hmac_sha256(secret, body, signature),
+//!         _ => false,
+//!     }
+//! }
+//! ```
+
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn keyed(key: &[u8]) -> HmacSha256 {
+    <HmacSha256 as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length")
+}
+
+/// HMAC-SHA256 (RFC 2104) of `payload` under `key`.
+#[must_use]
+pub fn hmac_sha256(key: &[u8], payload: &[u8]) -> [u8; 32] {
+    let mut mac = keyed(key);
+    mac.update(payload);
+    mac.finalize().into_bytes().into()
+}
+
+/// Check a hex-encoded HMAC-SHA256 `signature` of `payload` under `secret`.
+///
+/// A `sha256=` prefix (GitHub style) is accepted and hex digits may be upper or lower case.
+/// The comparison takes the same time wherever the first mismatch is. Malformed signatures
+/// verify as `false`.
+#[must_use]
+pub fn verify_hmac_sha256(secret: &[u8], payload: &[u8], signature: &str) -> bool {
+    let hex = signature.trim();
+    let hex = hex.strip_prefix("sha256=").unwrap_or(hex).as_bytes();
+    if hex.len() != 64 {
+        return false;
+    }
+    let mut presented = [0u8; 32];
+    for (byte, pair) in presented.iter_mut().zip(hex.chunks_exact(2)) {
+        match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(hi), Some(lo)) => *byte = (hi << 4) | lo,
+            _ => return false,
+        }
+    }
+    let mut mac = keyed(secret);
+    mac.update(payload);
+    mac.verify_slice(&presented).is_ok()
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn matches_rfc_4231_vectors() {
+        // Test case 2
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: key longer than the block size
+        assert_eq!(
+            hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_tampered_and_malformed_signatures() {
+        let sig = hex(&hmac_sha256(b"secret", b"payload"));
+        assert!(verify_hmac_sha256(b"secret", b"payload", &sig));
+        assert!(verify_hmac_sha256(
+            b"secret",
+            b"payload",
+            &format!("sha256={}", sig.to_uppercase())
+        ));
+        assert!(!verify_hmac_sha256(b"secret", b"payload!", &sig));
+        assert!(!verify_hmac_sha256(b"other", b"payload", &sig));
+        assert!(!verify_hmac_sha256(b"secret", b"payload", &sig[..62]));
+        assert!(!verify_hmac_sha256(b"secret", b"payload", &"zz".repeat(32)));
+    }
+}
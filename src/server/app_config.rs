@@ -6,7 +6,12 @@
 use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use super::default_providers::DefaultProviderPolicy;
+use super::fallback::ErrorFormat;
+use super::request::DuplicateQueryPolicy;
+use crate::spec::SchemaDialect;
 
 /// Top-level service configuration (`config/config.yaml`).
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -16,6 +21,212 @@ pub struct AppConfig {
     pub security: Option<SecurityConfig>,
     pub http: Option<HttpConfig>,
     pub cors: Option<CorsConfig>,
+    /// Ordered middleware chain; see [`super::build_middleware_chain`]. Unset = CORS only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub middleware: Option<Vec<MiddlewareEntry>>,
+    /// Static file serving options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub static_files: Option<StaticFilesConfig>,
+    /// Coroutine runtime options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<RuntimeSettings>,
+    /// Batch endpoint; see [`super::batch`]. Unset = disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<BatchConfig>,
+    /// WebSocket endpoints (`x-websocket: true` operations); see [`super::websocket`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<WebSocketConfig>,
+    /// Query string handling. Unset = first occurrence of a repeated key wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryConfig>,
+    /// Framework 404 / 405 / 500 rendering. Unset = negotiated on `Accept`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<ErrorsConfig>,
+    /// Spec checks at startup. Unset = operations without a schema are only warned about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationConfig>,
+    /// Metrics exports. Unset = Prometheus `/metrics` only, every route recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsConfig>,
+}
+
+/// `batch:` section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchConfig {
+    /// Serve the endpoint (default `false`).
+    pub enabled: bool,
+    /// Path answered for `POST` (default `/batch`).
+    pub path: String,
+    /// Most sub-requests in one batch; larger batches get `413` (default
+    /// [`super::batch::DEFAULT_MAX_BATCH_REQUESTS`]).
+    pub max_requests: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "/batch".to_string(),
+            max_requests: super::batch::DEFAULT_MAX_BATCH_REQUESTS,
+        }
+    }
+}
+
+/// `metrics:` section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Also serve a JSON snapshot of the metrics (default `false`); see
+    /// [`MetricsMiddleware::snapshot`](crate::middleware::MetricsMiddleware::snapshot).
+    pub json: bool,
+    /// Path answered for `GET` when `json` is on (default `/metrics.json`).
+    pub json_path: String,
+    /// Route templates (`/internal/ping`) never recorded, in either export.
+    pub exclude_paths: Vec<String>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            json: false,
+            json_path: "/metrics.json".to_string(),
+            exclude_paths: Vec::new(),
+        }
+    }
+}
+
+/// `websocket:` section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebSocketConfig {
+    /// Largest message (reassembled from its fragments) a client may send; larger ones close
+    /// the connection with `1009` (default [`super::websocket::DEFAULT_MAX_MESSAGE_BYTES`]).
+    pub max_message_bytes: usize,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: super::websocket::DEFAULT_MAX_MESSAGE_BYTES,
+        }
+    }
+}
+
+/// `query:` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryConfig {
+    /// Which occurrence of a repeated query key a declared parameter gets: `first`
+    /// (default), `last` or `array`. Exploded array parameters always collect every
+    /// occurrence; see [`DuplicateQueryPolicy::for_param`].
+    pub duplicates: DuplicateQueryPolicy,
+    /// Reject a repeated scalar query parameter with `400` instead of picking one.
+    pub strict_duplicates: bool,
+}
+
+/// `errors:` section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorsConfig {
+    /// `negotiate` (default): an HTML page when `Accept` prefers `text/html`, problem details
+    /// otherwise; `json`: always problem details.
+    pub format: ErrorFormat,
+    /// MiniJinja template replacing the built-in HTML page; see
+    /// [`HtmlErrorPage`](super::fallback::HtmlErrorPage).
+    pub html_template: Option<PathBuf>,
+}
+
+/// `validation:` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidationConfig {
+    /// Refuse to start when a JSON request or response body, or a 2xx response, has no
+    /// schema to validate against (see [`crate::spec::check_schemas`]). Off: one warning
+    /// per such route.
+    pub strict_schema: bool,
+    /// JSON Schema flavour of the spec's schemas: `2020-12` (default) or `openapi-3.0`
+    /// (boolean `exclusiveMinimum`, no `type` arrays); see [`crate::spec::SchemaDialect`].
+    pub schema_dialect: SchemaDialect,
+}
+
+/// `runtime:` section; environment variables override it (see [`crate::runtime_config`]).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeSettings {
+    /// may scheduler worker threads (`BRRTR_WORKERS` overrides). Unset = the
+    /// [`RuntimeConfig`](crate::runtime_config::RuntimeConfig) default.
+    pub worker_threads: Option<usize>,
+}
+
+impl AppConfig {
+    /// `runtime.worker_threads`, if configured.
+    #[must_use]
+    pub fn worker_threads(&self) -> Option<usize> {
+        self.runtime.as_ref().and_then(|r| r.worker_threads)
+    }
+
+    /// `validation.strict_schema` (default `false`).
+    #[must_use]
+    pub fn strict_schema(&self) -> bool {
+        self.validation.is_some_and(|v| v.strict_schema)
+    }
+
+    /// Sources for schemes `security:` does not configure: [`DefaultProviderPolicy::strict`]
+    /// with `security.strict_providers`, else [`DefaultProviderPolicy::lenient`].
+    #[must_use]
+    pub fn default_provider_policy(&self) -> DefaultProviderPolicy {
+        if self.security.as_ref().is_some_and(|s| s.strict_providers) {
+            DefaultProviderPolicy::strict()
+        } else {
+            DefaultProviderPolicy::lenient()
+        }
+    }
+
+    /// `metrics.json_path` when `metrics.json` is on.
+    #[must_use]
+    pub fn metrics_json_path(&self) -> Option<String> {
+        self.metrics
+            .as_ref()
+            .filter(|m| m.json)
+            .map(|m| m.json_path.clone())
+    }
+
+    /// `metrics.exclude_paths` (default none).
+    #[must_use]
+    pub fn metrics_excluded_paths(&self) -> &[String] {
+        self.metrics.as_ref().map_or(&[], |m| &m.exclude_paths)
+    }
+
+    /// `validation.schema_dialect` (default `2020-12`).
+    #[must_use]
+    pub fn schema_dialect(&self) -> SchemaDialect {
+        self.validation
+            .map(|v| v.schema_dialect)
+            .unwrap_or_default()
+    }
+}
+
+/// `static_files:` section.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StaticFilesConfig {
+    /// Extension → MIME type entries added to, or overriding, the built-in table
+    /// (e.g. `glb: model/gltf-binary`).
+    pub mime_types: HashMap<String, String>,
+}
+
+/// One entry of the ordered `middleware:` list.
+///
+/// `name` selects the middleware; every other key of the entry is that middleware's
+/// settings (see [`super::middleware_setup`] for the schema of each).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MiddlewareEntry {
+    /// `cors`, `compression`, `rate_limit` or `security_headers`.
+    pub name: String,
+    /// Per-middleware settings (all keys except `name`).
+    #[serde(flatten)]
+    pub settings: serde_yaml::Mapping,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -26,12 +237,18 @@ pub struct SecurityConfig {
     pub oauth2: Option<OAuth2Config>,
     pub jwks: Option<HashMap<String, JwksConfig>>,
     pub propelauth: Option<PropelAuthConfig>,
+    /// Leave the mock providers (`test123`, signature `sig`) out: a scheme with no configured
+    /// provider and no `BRRTR_*` variable stops startup instead.
+    #[serde(default)]
+    pub strict_providers: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct ApiKeyConfig {
     pub key: Option<String>,
     pub header_name: Option<String>,
+    /// Principal the key identifies; surfaced as `sub` in `HandlerRequest::auth_context`.
+    pub principal: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -40,6 +257,37 @@ pub struct RemoteApiKeyConfig {
     pub timeout_ms: Option<u64>,
     pub header_name: Option<String>,
     pub cache_ttl_secs: Option<u64>,
+    /// Private trust for `verify_url` instead of the system store.
+    pub tls: Option<OutboundTlsConfig>,
+}
+
+/// `tls:` under a `jwks.<scheme>` or `remote_api_keys.<scheme>` entry.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundTlsConfig {
+    /// PEM bundle of the CA certificates to trust (required).
+    pub ca_bundle: Option<std::path::PathBuf>,
+    /// SPKI pins (`sha256/<base64>`); when set, one must match a certificate in the chain.
+    pub spki_sha256: Vec<String>,
+}
+
+impl OutboundTlsConfig {
+    /// Build the client configuration this section describes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::http::OutboundTlsError`] for a missing or unreadable bundle or a
+    /// malformed pin.
+    pub fn build(&self) -> Result<crate::http::OutboundTls, crate::http::OutboundTlsError> {
+        let mut builder = crate::http::OutboundTls::builder();
+        if let Some(path) = &self.ca_bundle {
+            builder = builder.ca_bundle_file(path);
+        }
+        for pin in &self.spki_sha256 {
+            builder = builder.pin_spki_sha256(pin);
+        }
+        builder.build()
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -60,7 +308,17 @@ pub struct JwksConfig {
     pub iss: Option<String>,
     pub aud: Option<String>,
     pub leeway_secs: Option<u64>,
+    /// Reject tokens with `iat` later than now + leeway (default `false`).
+    pub reject_future_iat: Option<bool>,
+    /// Verify tokens without a `kid` with the JWKS's only key; ambiguous with several keys
+    /// (default `true`).
+    pub allow_missing_kid: Option<bool>,
+    /// Header `alg`s accepted (e.g. `[RS256]`); other tokens are rejected before key lookup.
+    /// Must not be empty. Default: every algorithm BRRTRouter supports, HMAC included.
+    pub allowed_algorithms: Option<Vec<jsonwebtoken::Algorithm>>,
     pub cache_ttl_secs: Option<u64>,
+    /// Private trust for `jwks_url` instead of the system store.
+    pub tls: Option<OutboundTlsConfig>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -75,9 +333,93 @@ pub struct PropelAuthConfig {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct HttpConfig {
+    /// Send HTTP/1.1 keep-alive headers (default `true`).
     pub keep_alive: Option<bool>,
+    /// Idle timeout advertised via `Keep-Alive: timeout=` (default 5).
     pub timeout_secs: Option<u64>,
+    /// Responses per connection before `Connection: close` (default and `0`: unlimited).
     pub max_requests: Option<u64>,
+    /// Longest request target (path and query string) in bytes; longer get `414` (default 8 KiB).
+    pub max_uri_bytes: Option<usize>,
+    /// Maximum request header lines; more get `431` (default 100).
+    pub max_headers: Option<usize>,
+    /// Maximum total bytes of request header names and values; more get `431` (default 32 KiB).
+    pub max_header_bytes: Option<usize>,
+    /// `Server` header value sent with every response (default `BRRTRouter`).
+    pub server_header: Option<String>,
+    /// Send no `Server` header at all (default `false`).
+    pub hide_server_header: Option<bool>,
+    /// Largest request body accepted, by `Content-Length`; larger get `413` (default unlimited).
+    pub max_body_bytes: Option<usize>,
+    /// Seconds a request body may take to arrive; slower bodies get `408` (default 30).
+    pub body_read_timeout_secs: Option<u64>,
+    /// Deepest array/object nesting of a JSON body; deeper bodies get `400` (default 64).
+    pub max_json_depth: Option<usize>,
+    /// Answer `Expect: 100-continue` before the body is sent (default `false`).
+    pub expect_continue: Option<bool>,
+    /// Path prefix stripped before routing, e.g. `/service-a` behind a gateway (default none).
+    pub base_path: Option<String>,
+    /// Also answer `/health` and `/metrics` without `base_path` (default `true`).
+    pub base_path_unprefixed_builtins: Option<bool>,
+    /// Oldest HTTP version served, `1.0` or `1.1`; older requests get `505` (default `1.0`).
+    pub min_version: Option<super::HttpVersion>,
+    /// Percent-decoding of path segments before routing, `segment` or `raw` (default
+    /// `segment`: split on `/`, then decode each segment once, so `%2F` stays in its segment).
+    pub path_decoding: Option<crate::router::PathDecoding>,
+    /// Paths with duplicate slashes (`//pets`): `off`, `normalize` or `redirect` (default
+    /// `off`); see [`super::path_normalize`].
+    pub duplicate_slashes: Option<super::DuplicateSlashes>,
+    /// Status of `duplicate_slashes: redirect` responses, `301` or `308` (default `308`).
+    pub duplicate_slashes_redirect: Option<u16>,
+}
+
+impl HttpConfig {
+    /// Connection policy described by this section.
+    pub fn connection_config(&self) -> super::ConnectionConfig {
+        super::ConnectionConfig {
+            keep_alive: self.keep_alive.unwrap_or(true),
+            idle_timeout: std::time::Duration::from_secs(self.timeout_secs.unwrap_or(5)),
+            max_requests_per_connection: self.max_requests.filter(|&max| max != 0),
+        }
+    }
+
+    /// `Server` header value described by this section; `None` when it is hidden.
+    pub fn server_header(&self) -> Option<&str> {
+        if self.hide_server_header.unwrap_or(false) {
+            return None;
+        }
+        Some(
+            self.server_header
+                .as_deref()
+                .unwrap_or(super::DEFAULT_SERVER_HEADER),
+        )
+    }
+
+    /// Base path described by this section; `None` when unset or `/`.
+    pub fn base_path(&self) -> Option<super::BasePath> {
+        let base = super::BasePath::new(self.base_path.as_deref()?)?;
+        Some(base.unprefixed_builtins(self.base_path_unprefixed_builtins.unwrap_or(true)))
+    }
+
+    /// Path decoding described by this section.
+    pub fn path_decoding(&self) -> crate::router::PathDecoding {
+        self.path_decoding.unwrap_or_default()
+    }
+
+    /// Duplicate-slash handling described by this section.
+    pub fn path_normalization(&self) -> super::PathNormalization {
+        super::PathNormalization::new(self.duplicate_slashes.unwrap_or_default())
+            .with_redirect_status(self.duplicate_slashes_redirect.unwrap_or(308))
+    }
+
+    /// Request header limits described by this section.
+    pub fn header_limits(&self) -> super::HeaderLimits {
+        let defaults = super::HeaderLimits::default();
+        super::HeaderLimits {
+            max_headers: self.max_headers.unwrap_or(defaults.max_headers),
+            max_header_bytes: self.max_header_bytes.unwrap_or(defaults.max_header_bytes),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
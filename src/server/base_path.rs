@@ -0,0 +1,172 @@
+//! Mounting the service under a path prefix.
+//!
+//! Behind a gateway that forwards `/service-a/...` unchanged, the spec's paths are only
+//! reachable under that prefix. [`AppService::with_base_path`] (or config.yaml
+//! `http.base_path`) makes the service strip it before routing, so the spec and generated
+//! handlers stay prefix-free:
+//!
+//! - `/service-a/pets/1` routes as `/pets/1`, and `/service-a` alone as `/`;
+//! - any other path (`/pets/1`, `/service-ab/pets`) gets `404`;
+//! - `/health` and `/metrics` answer both with and without the prefix, so probes and
+//!   scrapers configured against the pod keep working; [`BasePath::unprefixed_builtins`]
+//!   turns the unprefixed forms off;
+//! - relative `Location`, `Content-Location` and `Link` targets in handler responses
+//!   (`/pets/1`, `</pets?page=2>; rel="next"`) get the prefix prepended, and `/docs` loads
+//!   the spec from the prefixed `/openapi.yaml`.
+//!
+//! Paths in batch sub-requests and handler path parameters are the unprefixed ones.
+//!
+//! [`AppService::with_base_path`]: super::AppService::with_base_path
+
+use crate::dispatcher::HeaderVec;
+use std::sync::Arc;
+
+/// Response headers whose absolute-path targets are prefixed.
+const LOCATION_HEADERS: [&str; 2] = ["location", "content-location"];
+
+/// Path prefix the service is mounted under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasePath {
+    prefix: Arc<str>,
+    unprefixed_builtins: bool,
+}
+
+impl BasePath {
+    /// Prefix `prefix`, normalised to one leading and no trailing `/` (`service-a/` becomes
+    /// `/service-a`). `None` when nothing is left, i.e. the root.
+    #[must_use]
+    pub fn new(prefix: &str) -> Option<Self> {
+        let trimmed = prefix.trim().trim_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+        Some(Self {
+            prefix: Arc::from(format!("/{trimmed}")),
+            unprefixed_builtins: true,
+        })
+    }
+
+    /// Also answer `/health` and `/metrics` without the prefix (default `true`).
+    #[must_use]
+    pub fn unprefixed_builtins(mut self, allow: bool) -> Self {
+        self.unprefixed_builtins = allow;
+        self
+    }
+
+    /// The normalised prefix, e.g. `/service-a`
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Whether `/health` and `/metrics` are answered without the prefix
+    #[must_use]
+    pub fn allows_unprefixed_builtins(&self) -> bool {
+        self.unprefixed_builtins
+    }
+
+    /// `path` with the prefix removed; `None` when it is not under the prefix.
+    ///
+    /// The prefix must end at a segment boundary: `/service-ab` is not under `/service-a`.
+    #[must_use]
+    pub fn strip<'a>(&self, path: &'a str) -> Option<&'a str> {
+        match path.strip_prefix(&*self.prefix)? {
+            "" => Some("/"),
+            rest if rest.starts_with('/') => Some(rest),
+            _ => None,
+        }
+    }
+
+    /// `path` (an absolute path such as `/pets/1`) as seen from outside the gateway.
+    #[must_use]
+    pub fn prepend(&self, path: &str) -> String {
+        format!("{}{path}", self.prefix)
+    }
+
+    /// Prefix the absolute-path targets of `Location`, `Content-Location` and `Link`.
+    pub(crate) fn rewrite_headers(&self, headers: &mut HeaderVec) {
+        for (name, value) in headers.iter_mut() {
+            if LOCATION_HEADERS
+                .iter()
+                .any(|h| name.eq_ignore_ascii_case(h))
+            {
+                if is_absolute_path(value) {
+                    *value = self.prepend(value);
+                }
+            } else if name.eq_ignore_ascii_case("link") {
+                *value = self.rewrite_link(value);
+            }
+        }
+    }
+
+    /// Prefix each `<target>` of a `Link` value that is an absolute path.
+    fn rewrite_link(&self, value: &str) -> String {
+        let mut out = String::with_capacity(value.len() + 4 * self.prefix.len());
+        let mut rest = value;
+        while let Some(open) = rest.find('<') {
+            out.push_str(&rest[..=open]);
+            rest = &rest[open + 1..];
+            if is_absolute_path(rest) {
+                out.push_str(&self.prefix);
+            }
+            let Some(close) = rest.find('>') else {
+                break;
+            };
+            out.push_str(&rest[..=close]);
+            rest = &rest[close + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// `/path` but not a scheme-relative `//host/path`.
+fn is_absolute_path(target: &str) -> bool {
+    target.starts_with('/') && !target.starts_with("//")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::HeaderLookup;
+
+    #[test]
+    fn strip_stops_at_segment_boundaries() {
+        let base = BasePath::new("service-a/").unwrap();
+        assert_eq!(base.prefix(), "/service-a");
+        assert_eq!(base.strip("/service-a"), Some("/"));
+        assert_eq!(base.strip("/service-a/"), Some("/"));
+        assert_eq!(base.strip("/service-a/pets/1"), Some("/pets/1"));
+        assert_eq!(base.strip("/service-ab/pets"), None);
+        assert_eq!(base.strip("/pets"), None);
+        assert!(BasePath::new("/").is_none());
+    }
+
+    #[test]
+    fn rewrite_prefixes_relative_targets_only() {
+        let base = BasePath::new("/api").unwrap();
+        let mut headers: HeaderVec = vec![
+            (Arc::from("location"), "/pets/1".to_string()),
+            (
+                Arc::from("content-location"),
+                "https://x.test/pets".to_string(),
+            ),
+            (
+                Arc::from("link"),
+                r#"</pets?page=2>; rel="next", <//cdn.test/a>; rel="x", <https://x.test/>"#
+                    .to_string(),
+            ),
+            (Arc::from("x-other"), "/pets".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        base.rewrite_headers(&mut headers);
+        assert_eq!(headers.get("location"), Some("/api/pets/1"));
+        assert_eq!(headers.get("content-location"), Some("https://x.test/pets"));
+        assert_eq!(
+            headers.get("link"),
+            Some(r#"</api/pets?page=2>; rel="next", <//cdn.test/a>; rel="x", <https://x.test/>"#)
+        );
+        assert_eq!(headers.get("x-other"), Some("/pets"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Batch endpoint: several API calls in one round trip.
+//!
+//! Enabled by config.yaml `batch:` ([`BatchConfig`]). A `POST` to its path (default `/batch`)
+//! carries a JSON array of sub-requests:
+//!
+//! ```json
+//! [
+//!   {"method": "GET", "path": "/pets?limit=2"},
+//!   {"method": "POST", "path": "/pets", "body": {"name": "Rex"}},
+//!   {"method": "GET", "path": "/admin/stats", "headers": {"authorization": "Bearer other"}}
+//! ]
+//! ```
+//!
+//! and is answered `200` with one `{status, headers, body}` object per sub-request, in order.
+//! Each sub-request goes through routing, authentication, parameter and body validation, the
+//! handler and response validation exactly like a request of its own, on its own coroutine,
+//! so the batch takes about as long as its slowest member.
+//!
+//! Sub-requests inherit the outer request's headers (credentials, cookies, tenant) except
+//! framing headers and `x-request-id`; a header set on the sub-request replaces the inherited
+//! one, so a sub-request can carry its own credentials. A body, when present, is treated as
+//! JSON. Only spec operations are reachable: built-in endpoints (`/health`, `/metrics`, docs,
+//! static files) and nested batches are not. A malformed entry fails alone with a `400`
+//! problem; the batch as a whole is rejected only when the body is not an array (`400`) or
+//! holds more than `max_requests` entries (`413`).
+
+use super::app_config::BatchConfig;
+use super::header_intern::intern_header_name;
+use super::request::{parse_cookies, parse_query_params};
+use super::response::ProblemDetails;
+use super::service::{upgrade_required_problem, AppService, RouteOutcome, RoutedRequest};
+use crate::dispatcher::{HeaderLookup, HeaderVec};
+use crate::ids::RequestId;
+use bytes::Bytes;
+use http::Method;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// Default [`BatchConfig::max_requests`].
+pub const DEFAULT_MAX_BATCH_REQUESTS: usize = 20;
+
+/// Outer request headers a sub-request never inherits: they describe the batch request
+/// itself, not the calls inside it.
+const NOT_INHERITED: [&str; 9] = [
+    "content-type",
+    "content-length",
+    "content-encoding",
+    "transfer-encoding",
+    "accept-encoding",
+    "connection",
+    "keep-alive",
+    "expect",
+    "x-request-id",
+];
+
+/// Answer a batch request; `headers` and `body` are the outer request's.
+pub(crate) fn handle_batch(
+    service: &AppService,
+    config: &BatchConfig,
+    headers: &HeaderVec,
+    body: Option<Value>,
+) -> RouteOutcome {
+    let entries = match body {
+        Some(Value::Array(entries)) => entries,
+        _ => {
+            return RouteOutcome::problem(
+                ProblemDetails::new(400)
+                    .detail("Batch body must be a JSON array of sub-requests")
+                    .instance(config.path.as_str()),
+            )
+        }
+    };
+    if entries.len() > config.max_requests {
+        return RouteOutcome::problem(
+            ProblemDetails::new(413)
+                .detail(format!(
+                    "Batch of {} sub-requests exceeds the limit of {}",
+                    entries.len(),
+                    config.max_requests
+                ))
+                .instance(config.path.as_str())
+                .extension("max_requests", config.max_requests),
+        );
+    }
+
+    let running: Vec<Running> = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| match sub_request(config, headers, entry) {
+            Ok(req) => spawn(service, req),
+            Err(problem) => Running::Done(outcome_json(RouteOutcome::problem(
+                problem.extension("index", index),
+            ))),
+        })
+        .collect();
+
+    let results = running
+        .into_iter()
+        .map(|running| match running {
+            Running::Done(result) => result,
+            Running::Spawned(handle) => handle.join().unwrap_or_else(|_| {
+                outcome_json(RouteOutcome::problem(
+                    ProblemDetails::new(500).detail("Sub-request failed"),
+                ))
+            }),
+        })
+        .collect();
+
+    let mut response_headers = HeaderVec::new();
+    response_headers.push((Arc::from("content-type"), "application/json".to_string()));
+    RouteOutcome::Handler {
+        status: 200,
+        body: Value::Array(results),
+        is_sse: false,
+        headers: response_headers,
+    }
+}
+
+enum Running {
+    Done(Value),
+    Spawned(may::coroutine::JoinHandle<Value>),
+}
+
+/// Run `req` on its own coroutine.
+fn spawn(service: &AppService, req: RoutedRequest) -> Running {
+    let service = service.clone();
+    // SAFETY: `may::coroutine::Builder::spawn` is unsafe because the coroutine must not block
+    // the worker thread or rely on thread-local storage; sub-requests take the same path as
+    // top-level requests, which already run on may coroutines.
+    let spawned = unsafe {
+        may::coroutine::Builder::new()
+            .name("brrtrouter-batch".to_string())
+            .spawn(move || outcome_json(run(&service, req)))
+    };
+    match spawned {
+        Ok(handle) => Running::Spawned(handle),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to spawn batch sub-request");
+            Running::Done(outcome_json(RouteOutcome::problem(
+                ProblemDetails::new(503).detail("Sub-request could not be scheduled"),
+            )))
+        }
+    }
+}
+
+fn run(service: &AppService, req: RoutedRequest) -> RouteOutcome {
+    let route = service.router.load().route(req.method.clone(), &req.path);
+    match route {
+        Some(route_match) => service.handle_route(route_match, req),
+        None => service.handle_unrouted(
+            &req.method,
+            &req.path,
+            &req.headers,
+            &req.cookies,
+            &req.query_params,
+            req.request_id,
+        ),
+    }
+}
+
+/// Build a sub-request from one batch entry, or the problem describing why it is malformed.
+fn sub_request(
+    config: &BatchConfig,
+    outer_headers: &HeaderVec,
+    entry: Value,
+) -> Result<RoutedRequest, ProblemDetails> {
+    let Value::Object(mut entry) = entry else {
+        return Err(ProblemDetails::new(400).detail("Sub-request must be an object"));
+    };
+    let method = entry
+        .get("method")
+        .and_then(Value::as_str)
+        .and_then(|m| m.to_ascii_uppercase().parse::<Method>().ok())
+        .ok_or_else(|| ProblemDetails::new(400).detail("Sub-request needs a valid 'method'"))?;
+    let target = entry
+        .get("path")
+        .and_then(Value::as_str)
+        .filter(|p| p.starts_with('/'))
+        .ok_or_else(|| {
+            ProblemDetails::new(400).detail("Sub-request needs a 'path' starting with '/'")
+        })?;
+    let path = target.split('?').next().unwrap_or("/").to_string();
+    if path == config.path {
+        return Err(ProblemDetails::new(400)
+            .detail("Batches cannot be nested")
+            .instance(path));
+    }
+    let query_params = parse_query_params(target);
+
+    let mut headers: HeaderVec = outer_headers
+        .iter()
+        .filter(|(name, _)| !NOT_INHERITED.iter().any(|n| name.eq_ignore_ascii_case(n)))
+        .cloned()
+        .collect();
+    match entry.get("headers") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(own)) => {
+            for (name, value) in own {
+                let Some(value) = value.as_str() else {
+                    return Err(ProblemDetails::new(400)
+                        .detail(format!("Sub-request header '{name}' must be a string")));
+                };
+                headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+                headers.push((intern_header_name(name.as_bytes()), value.to_string()));
+            }
+        }
+        Some(_) => {
+            return Err(ProblemDetails::new(400).detail("Sub-request 'headers' must be an object"))
+        }
+    }
+
+    let body = entry.remove("body").filter(|b| !b.is_null());
+    let raw_body = match &body {
+        Some(body) => {
+            if headers.get("content-type").is_none() {
+                headers.push((
+                    intern_header_name(b"content-type"),
+                    "application/json".to_string(),
+                ));
+            }
+            Some(Bytes::from(serde_json::to_vec(body).map_err(|e| {
+                ProblemDetails::new(400).detail(format!("Sub-request body: {e}"))
+            })?))
+        }
+        None => None,
+    };
+    let request_id = RequestId::from_header_or_new(headers.get("x-request-id"));
+    let cookies = parse_cookies(&headers);
+
+    Ok(RoutedRequest {
+        method,
+        path,
+        headers,
+        cookies,
+        query_params,
+        body,
+        body_size_bytes: raw_body.as_ref().map_or(0, Bytes::len),
+        raw_body,
+        request_id,
+        upgrade: false,
+        expect_continue: false,
+    })
+}
+
+/// `{status, headers, body}` for one sub-request. Repeated header names are joined with
+/// `, `; `content-length` and `content-encoding` are dropped (the body is embedded as JSON).
+fn outcome_json(outcome: RouteOutcome) -> Value {
+    let (status, headers, body) = match outcome {
+        RouteOutcome::Problem {
+            problem,
+            mut headers,
+        } => {
+            headers.push((
+                Arc::from("content-type"),
+                super::response::PROBLEM_JSON.to_string(),
+            ));
+            (problem.status, headers, problem.to_value())
+        }
+        RouteOutcome::Handler {
+            status,
+            body,
+            headers,
+            ..
+        } => (status, headers, body),
+        // Sub-requests are never upgrades, so a WebSocket route already answered `426`;
+        // nor `Expect: 100-continue` heads.
+        RouteOutcome::Upgrade(_) | RouteOutcome::Continue => {
+            return outcome_json(RouteOutcome::problem(upgrade_required_problem()));
+        }
+    };
+    let mut header_map = Map::new();
+    for (name, value) in &headers {
+        let name = name.to_ascii_lowercase();
+        if name == "content-length" || name == "content-encoding" {
+            continue;
+        }
+        match header_map.get_mut(&name) {
+            Some(Value::String(existing)) => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            _ => {
+                header_map.insert(name, Value::String(value.clone()));
+            }
+        }
+    }
+    json!({ "status": status, "headers": header_map, "body": body })
+}
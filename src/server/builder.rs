@@ -0,0 +1,212 @@
+//! [`AppServiceBuilder`]: an [`AppService`] from named settings rather than positional
+//! arguments.
+//!
+//! ```rust,ignore
+//! let service = AppService::builder()
+//!     .router(Router::new(routes))
+//!     .dispatcher(dispatcher)
+//!     .security_schemes(schemes)
+//!     .spec_path("openapi.yaml")
+//!     .doc_dir("doc")
+//!     .middleware(Arc::new(MetricsMiddleware::new()))
+//!     .config(&app_config)
+//!     .base_path("/service-a")
+//!     .build()?;
+//! ```
+//!
+//! The router, dispatcher and spec path are required; [`AppServiceBuilder::build`] names
+//! the first one missing. Everything else starts from the same defaults as a service built
+//! with [`AppService::new`], and the `set_*` methods on the built service still apply.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use super::app_config::AppConfig;
+use super::base_path::BasePath;
+use super::service::{AppService, SharedDispatcher, SharedRouter};
+use crate::dispatcher::Dispatcher;
+use crate::middleware::Middleware;
+use crate::router::Router;
+use crate::security::SecurityProvider;
+use crate::spec::SecurityScheme;
+
+/// Why [`AppServiceBuilder::build`] could not build a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppServiceBuildError {
+    /// A required setting was never given: `router`, `dispatcher` or `spec_path`
+    Missing(&'static str),
+    /// The config's `errors:` section could not be applied (unreadable or invalid template)
+    InvalidErrorsConfig(String),
+}
+
+impl std::fmt::Display for AppServiceBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(setting) => write!(f, "AppServiceBuilder: `{setting}` is required"),
+            Self::InvalidErrorsConfig(reason) => {
+                write!(f, "invalid errors configuration: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppServiceBuildError {}
+
+/// Builder for [`AppService`]; start with [`AppService::builder`].
+#[derive(Default)]
+pub struct AppServiceBuilder {
+    router: Option<SharedRouter>,
+    dispatcher: Option<SharedDispatcher>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    security_schemes: HashMap<String, SecurityScheme>,
+    security_providers: Vec<(String, Arc<dyn SecurityProvider>)>,
+    spec_path: Option<PathBuf>,
+    static_dir: Option<PathBuf>,
+    doc_dir: Option<PathBuf>,
+    config: Option<AppConfig>,
+    base_path: Option<String>,
+}
+
+impl AppServiceBuilder {
+    /// Route requests with `router` (required, or [`Self::shared_router`]).
+    #[must_use]
+    pub fn router(self, router: Router) -> Self {
+        self.shared_router(Arc::new(ArcSwap::from_pointee(router)))
+    }
+
+    /// Route requests with a router shared with a hot-reload watcher.
+    #[must_use]
+    pub fn shared_router(mut self, router: SharedRouter) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Dispatch requests to `dispatcher`'s handlers (required, or
+    /// [`Self::shared_dispatcher`]).
+    #[must_use]
+    pub fn dispatcher(self, dispatcher: Dispatcher) -> Self {
+        self.shared_dispatcher(Arc::new(ArcSwap::from_pointee(dispatcher)))
+    }
+
+    /// Dispatch requests through a dispatcher shared with a hot-reload watcher.
+    #[must_use]
+    pub fn shared_dispatcher(mut self, dispatcher: SharedDispatcher) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Add a middleware to the dispatcher, after those it already has. Middleware run in
+    /// the order they are added.
+    #[must_use]
+    pub fn middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Security schemes from the spec's `components.securitySchemes` (default none).
+    #[must_use]
+    pub fn security_schemes(mut self, schemes: HashMap<String, SecurityScheme>) -> Self {
+        self.security_schemes = schemes;
+        self
+    }
+
+    /// Validate credentials for the scheme `name` with `provider`; see
+    /// [`AppService::register_security_provider`].
+    #[must_use]
+    pub fn security_provider(mut self, name: &str, provider: Arc<dyn SecurityProvider>) -> Self {
+        self.security_providers.push((name.to_string(), provider));
+        self
+    }
+
+    /// The OpenAPI spec file, served on `/openapi.yaml` (required).
+    #[must_use]
+    pub fn spec_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spec_path = Some(path.into());
+        self
+    }
+
+    /// Serve static files from `dir` (default none).
+    #[must_use]
+    pub fn static_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.static_dir = Some(dir.into());
+        self
+    }
+
+    /// Serve the documentation UI from `dir` (default none).
+    #[must_use]
+    pub fn doc_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.doc_dir = Some(dir.into());
+        self
+    }
+
+    /// Apply config.yaml's `http:`, `batch:`, `websocket:`, `query:` and `errors:` sections.
+    /// Security providers and the middleware chain are not built from it: see
+    /// [`super::security_setup::register_security_from_config`] and
+    /// [`super::build_middleware_chain`].
+    #[must_use]
+    pub fn config(mut self, config: &AppConfig) -> Self {
+        self.config = Some(config.clone());
+        self
+    }
+
+    /// Mount the service under `prefix`, overriding config.yaml `http.base_path`; see
+    /// [`AppService::with_base_path`].
+    #[must_use]
+    pub fn base_path(mut self, prefix: &str) -> Self {
+        self.base_path = Some(prefix.to_string());
+        self
+    }
+
+    /// Build the service.
+    ///
+    /// # Errors
+    ///
+    /// [`AppServiceBuildError::Missing`] when the router, dispatcher or spec path was not
+    /// given, [`AppServiceBuildError::InvalidErrorsConfig`] when the config's `errors:`
+    /// template cannot be loaded.
+    pub fn build(self) -> Result<AppService, AppServiceBuildError> {
+        let router = self.router.ok_or(AppServiceBuildError::Missing("router"))?;
+        let dispatcher = self
+            .dispatcher
+            .ok_or(AppServiceBuildError::Missing("dispatcher"))?;
+        let spec_path = self
+            .spec_path
+            .ok_or(AppServiceBuildError::Missing("spec_path"))?;
+
+        if !self.middleware.is_empty() {
+            let mut with_middleware = Dispatcher::clone(&dispatcher.load());
+            for middleware in self.middleware {
+                with_middleware.add_middleware(middleware);
+            }
+            dispatcher.store(Arc::new(with_middleware));
+        }
+
+        let mut service = AppService::from_parts(
+            router,
+            dispatcher,
+            self.security_schemes,
+            spec_path,
+            self.static_dir,
+            self.doc_dir,
+        );
+        for (name, provider) in self.security_providers {
+            service.register_security_provider(&name, provider);
+        }
+        if let Some(config) = self.config {
+            service.apply_http_config(&config.http.unwrap_or_default());
+            service.set_batch(config.batch);
+            service.set_websocket(config.websocket);
+            service.set_query(config.query);
+            service
+                .set_errors(config.errors)
+                .map_err(|e| AppServiceBuildError::InvalidErrorsConfig(e.to_string()))?;
+        }
+        if let Some(prefix) = self.base_path {
+            service.set_base_path(BasePath::new(&prefix));
+        }
+        Ok(service)
+    }
+}
@@ -0,0 +1,218 @@
+//! Per-connection HTTP/1.1 keep-alive policy.
+//!
+//! `may_minihttp` clones the [`super::AppService`] once per accepted connection, so a clone's
+//! request counter counts requests on that connection. [`ConnectionConfig`] uses it to end a
+//! connection after `max_requests_per_connection` responses (`Connection: close`) and
+//! advertises `idle_timeout` through the `Keep-Alive` header.
+//!
+//! ## HTTP/1.0
+//!
+//! HTTP/1.0 connections close after each response unless the client sends
+//! `Connection: keep-alive`, and HTTP/1.1 ones close when the client sends
+//! `Connection: close`; such responses carry `Connection: close` whatever the policy.
+//! [`super::AppService::set_min_http_version`] rejects older requests with
+//! `505 HTTP Version Not Supported` instead.
+//!
+//! ## Limits
+//!
+//! The accept loop and per-stream socket handling of [`super::HttpServer::start`] live in
+//! `may_minihttp`, which does not expose per-stream read/write/idle timeouts; enforcing them
+//! there needs support in the fork. On those connections `idle_timeout` is only a hint for
+//! clients, load balancers and proxies (which all honor `Keep-Alive: timeout=`).
+//!
+//! Connections BRRTRouter accepts itself ([`super::HttpServer::start_unix`] and
+//! [`super::HttpServer::start_with_websockets`]) are relayed, and the relay does enforce it:
+//! a connection silent for `idle_timeout` between requests, or whose client accepts no
+//! response bytes for that long, is closed. A zero `idle_timeout` disables this.
+//!
+//! Either way it is not per-route: it applies between requests, when no route is known
+//! yet, so a slow handler holds its connection until it responds. There is no handler-level
+//! request timeout to interact with.
+//!
+//! Separate read and write timeouts and per-route overrides (a longer timeout for SSE) are
+//! not implemented: `idle_timeout` is the only socket timeout, and it bounds both directions
+//! of a relayed connection.
+
+use std::fmt;
+use std::time::Duration;
+
+/// HTTP version of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum HttpVersion {
+    /// `HTTP/1.0`: connections close after each response unless the client asks otherwise
+    #[default]
+    Http10,
+    /// `HTTP/1.1`: connections stay open unless either side sends `Connection: close`
+    Http11,
+}
+
+impl HttpVersion {
+    /// Version from the minor number of an `HTTP/1.x` request line
+    pub(crate) fn from_minor(minor: u8) -> Self {
+        if minor == 0 {
+            Self::Http10
+        } else {
+            Self::Http11
+        }
+    }
+
+    /// Whether a request of this version keeps its connection open, given its `Connection`
+    /// header.
+    pub fn keeps_alive(self, connection: Option<&str>) -> bool {
+        let has = |token: &str| {
+            connection.is_some_and(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)))
+        };
+        match self {
+            Self::Http10 => has("keep-alive"),
+            Self::Http11 => !has("close"),
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Http10 => "HTTP/1.0",
+            Self::Http11 => "HTTP/1.1",
+        })
+    }
+}
+
+impl std::str::FromStr for HttpVersion {
+    type Err = String;
+
+    /// `1.0` / `1.1`, with or without the `HTTP/` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let number = s
+            .get(..5)
+            .filter(|p| p.eq_ignore_ascii_case("HTTP/"))
+            .map_or(s, |_| &s[5..]);
+        match number {
+            "1.0" => Ok(Self::Http10),
+            "1.1" => Ok(Self::Http11),
+            _ => Err(format!(
+                "unsupported HTTP version '{s}' (expected 1.0 or 1.1)"
+            )),
+        }
+    }
+}
+
+impl serde::Serialize for HttpVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::Http10 => "1.0",
+            Self::Http11 => "1.1",
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HttpVersion {
+    /// A string (`"1.1"`, `"HTTP/1.1"`) or a number (`1.1`, as YAML reads it unquoted)
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Text(String),
+            Number(f64),
+        }
+        let text = match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => text,
+            Repr::Number(number) => format!("{number:.1}"),
+        };
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Keep-alive settings applied to every connection served by an [`super::AppService`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionConfig {
+    /// Send `Connection: keep-alive`; when `false` no keep-alive headers are sent.
+    pub keep_alive: bool,
+    /// Idle time between requests advertised via `Keep-Alive: timeout=` (and enforced on
+    /// relayed connections, see the module docs).
+    pub idle_timeout: Duration,
+    /// Responses served on one connection before it is closed (`None`, the default, =
+    /// unlimited).
+    pub max_requests_per_connection: Option<u64>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            idle_timeout: Duration::from_secs(5),
+            max_requests_per_connection: None,
+        }
+    }
+}
+
+impl ConnectionConfig {
+    /// Precomputed `Keep-Alive: …` header line, or `None` when keep-alive is disabled.
+    pub(crate) fn keep_alive_header(&self) -> Option<Box<str>> {
+        if !self.keep_alive {
+            return None;
+        }
+        let timeout = self.idle_timeout.as_secs();
+        Some(
+            match self.max_requests_per_connection {
+                Some(max) => format!("Keep-Alive: timeout={timeout}, max={max}"),
+                None => format!("Keep-Alive: timeout={timeout}"),
+            }
+            .into_boxed_str(),
+        )
+    }
+
+    /// Whether the `served`-th response on a connection must close it.
+    #[inline]
+    pub(crate) fn closes_after(&self, served: u64) -> bool {
+        self.max_requests_per_connection
+            .is_some_and(|max| served >= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_10_closes_unless_asked_to_keep_alive() {
+        assert!(!HttpVersion::Http10.keeps_alive(None));
+        assert!(HttpVersion::Http10.keeps_alive(Some("Keep-Alive")));
+        assert!(HttpVersion::Http11.keeps_alive(None));
+        assert!(!HttpVersion::Http11.keeps_alive(Some("upgrade, close")));
+        assert_eq!("HTTP/1.1".parse(), Ok(HttpVersion::Http11));
+        assert_eq!(
+            serde_yaml::from_str::<HttpVersion>("1.0").unwrap(),
+            HttpVersion::Http10
+        );
+        assert!("2".parse::<HttpVersion>().is_err());
+    }
+
+    #[test]
+    fn header_reflects_limits() {
+        let cfg = ConnectionConfig {
+            keep_alive: true,
+            idle_timeout: Duration::from_secs(30),
+            max_requests_per_connection: None,
+        };
+        assert_eq!(
+            cfg.keep_alive_header().as_deref(),
+            Some("Keep-Alive: timeout=30")
+        );
+        assert!(!cfg.closes_after(u64::MAX));
+
+        let limited = ConnectionConfig {
+            max_requests_per_connection: Some(2),
+            ..cfg
+        };
+        assert!(!limited.closes_after(1));
+        assert!(limited.closes_after(2));
+
+        let disabled = ConnectionConfig {
+            keep_alive: false,
+            ..ConnectionConfig::default()
+        };
+        assert!(disabled.keep_alive_header().is_none());
+    }
+}
@@ -0,0 +1,184 @@
+//! Where [`AppService::register_default_security_providers_from_env`] finds a provider for
+//! each security scheme.
+//!
+//! A [`DefaultProviderPolicy`] lists, per [`SchemeKind`], the [`ProviderSource`]s to try in
+//! order; the first that yields a provider wins:
+//!
+//! | Source | `apiKey` | `http` bearer | `oauth2` |
+//! |---|---|---|---|
+//! | `config` | a provider already registered for the scheme (config.yaml, or by hand) | same | same |
+//! | `env` | `BRRTR_API_KEY__<SCHEME_NAME>`, then `BRRTR_API_KEY` | `BRRTR_BEARER_SIGNATURE` | `BRRTR_OAUTH2_SIGNATURE` |
+//! | `mock` | the `test_api_key` argument, else `test123` | signature `sig` | signature `sig` |
+//!
+//! [`DefaultProviderPolicy::lenient`] (the default) tries config, env, then mock, so every
+//! scheme ends up with a provider. [`DefaultProviderPolicy::strict`] leaves the mock out: a
+//! scheme no real source covers is reported as a [`MissingProviderError`] at startup rather
+//! than accepting a well-known development key. Schemes of other kinds (`http` basic,
+//! `openIdConnect`, ...) are left to explicit registration.
+//!
+//! [`AppService::register_default_security_providers_from_env`]: super::AppService::register_default_security_providers_from_env
+
+use crate::spec::SecurityScheme;
+
+/// A place a default security provider can come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderSource {
+    /// A provider already registered for the scheme
+    Config,
+    /// Keys and signatures from `BRRTR_*` environment variables
+    Env,
+    /// Development keys that accept well-known credentials
+    Mock,
+}
+
+impl ProviderSource {
+    /// Name used in logs and errors (`config`, `env`, `mock`)
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Env => "env",
+            Self::Mock => "mock",
+        }
+    }
+}
+
+/// The security scheme types that get default providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemeKind {
+    /// `type: apiKey`
+    ApiKey,
+    /// `type: http`, `scheme: bearer`
+    Bearer,
+    /// `type: oauth2`
+    OAuth2,
+}
+
+impl SchemeKind {
+    /// Kind of `scheme`; `None` for schemes without default providers
+    #[must_use]
+    pub fn of(scheme: &SecurityScheme) -> Option<Self> {
+        match scheme {
+            SecurityScheme::ApiKey { .. } => Some(Self::ApiKey),
+            SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => {
+                Some(Self::Bearer)
+            }
+            SecurityScheme::OAuth2 { .. } => Some(Self::OAuth2),
+            _ => None,
+        }
+    }
+
+    /// Name used in logs and errors (`apiKey`, `bearer`, `oauth2`)
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ApiKey => "apiKey",
+            Self::Bearer => "bearer",
+            Self::OAuth2 => "oauth2",
+        }
+    }
+}
+
+/// Source order per scheme kind for default security providers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultProviderPolicy {
+    /// Sources tried for `apiKey` schemes
+    pub api_key: Vec<ProviderSource>,
+    /// Sources tried for `http` bearer schemes
+    pub bearer: Vec<ProviderSource>,
+    /// Sources tried for `oauth2` schemes
+    pub oauth2: Vec<ProviderSource>,
+}
+
+impl Default for DefaultProviderPolicy {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+impl DefaultProviderPolicy {
+    /// Config, then env, then mock for every kind; never leaves a scheme without a provider
+    #[must_use]
+    pub fn lenient() -> Self {
+        let order = vec![
+            ProviderSource::Config,
+            ProviderSource::Env,
+            ProviderSource::Mock,
+        ];
+        Self {
+            api_key: order.clone(),
+            bearer: order.clone(),
+            oauth2: order,
+        }
+    }
+
+    /// Config, then env; a scheme neither covers is an error instead of a mock
+    #[must_use]
+    pub fn strict() -> Self {
+        Self::lenient().without_mock()
+    }
+
+    /// Try `order` for schemes of `kind`
+    #[must_use]
+    pub fn with_order(mut self, kind: SchemeKind, order: &[ProviderSource]) -> Self {
+        *self.order_mut(kind) = order.to_vec();
+        self
+    }
+
+    /// Drop the mock source for every kind
+    #[must_use]
+    pub fn without_mock(mut self) -> Self {
+        for order in [&mut self.api_key, &mut self.bearer, &mut self.oauth2] {
+            order.retain(|source| *source != ProviderSource::Mock);
+        }
+        self
+    }
+
+    /// Sources tried for schemes of `kind`, in order
+    #[must_use]
+    pub fn order(&self, kind: SchemeKind) -> &[ProviderSource] {
+        match kind {
+            SchemeKind::ApiKey => &self.api_key,
+            SchemeKind::Bearer => &self.bearer,
+            SchemeKind::OAuth2 => &self.oauth2,
+        }
+    }
+
+    /// Whether no kind falls back to the mock source
+    #[must_use]
+    pub fn is_strict(&self) -> bool {
+        [SchemeKind::ApiKey, SchemeKind::Bearer, SchemeKind::OAuth2]
+            .into_iter()
+            .all(|kind| !self.order(kind).contains(&ProviderSource::Mock))
+    }
+
+    fn order_mut(&mut self, kind: SchemeKind) -> &mut Vec<ProviderSource> {
+        match kind {
+            SchemeKind::ApiKey => &mut self.api_key,
+            SchemeKind::Bearer => &mut self.bearer,
+            SchemeKind::OAuth2 => &mut self.oauth2,
+        }
+    }
+}
+
+/// Security schemes no source of a [`DefaultProviderPolicy`] provided for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingProviderError {
+    /// Name and kind of each scheme left without a provider, sorted by name
+    pub schemes: Vec<(String, SchemeKind)>,
+}
+
+impl std::fmt::Display for MissingProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("no security provider for ")?;
+        for (i, (name, kind)) in self.schemes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{name}' ({})", kind.as_str())?;
+        }
+        f.write_str(": configure it under security: or set its BRRTR_* environment variable")
+    }
+}
+
+impl std::error::Error for MissingProviderError {}
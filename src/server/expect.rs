@@ -0,0 +1,127 @@
+//! `Expect: 100-continue` (RFC 9110 §10.1.1).
+//!
+//! A client uploading a large body can send the request head alone with
+//! `Expect: 100-continue` and wait for `100 Continue` before sending the body, so a request
+//! the server would reject anyway costs no upload. `may_minihttp` reads the whole request
+//! before the service sees it, so the front listener of
+//! [`HttpServer::start_with_websockets`] answers the expectation instead, when
+//! [`AppService::set_expect_continue`] is on:
+//!
+//! - any expectation other than `100-continue` is rejected with `417 Expectation Failed`;
+//! - a `Content-Length` over [`AppService::set_max_body_bytes`] gets `413`;
+//! - a routed request runs through authentication, parameter and content-type validation
+//!   without a body, and any rejection (`401`, `403`, `400`, `415`, ...) is answered as usual.
+//!
+//! A rejection closes the connection, so a client that sends the body anyway does not have
+//! it read. When every check passes, `100 Continue` is written and the request is relayed
+//! without its `Expect` header to the HTTP server, which validates the body and runs the
+//! handler as for any other request. Requests no route matches (built-in endpoints, static
+//! files, the batch endpoint, paths outside the base path) are only checked for size.
+//!
+//! The front listener only parses the first request head of a connection, so while expect
+//! handling is on every relayed connection is closed after its first response
+//! (`Connection: close`); a client's next request opens a new connection and is inspected
+//! in turn.
+//!
+//! [`HttpServer::start_with_websockets`]: super::HttpServer::start_with_websockets
+//! [`AppService::set_expect_continue`]: super::AppService::set_expect_continue
+//! [`AppService::set_max_body_bytes`]: super::AppService::set_max_body_bytes
+
+use super::request::parse_request_head;
+use super::response::ProblemDetails;
+use super::service::{body_too_large, AppService, RouteOutcome, RoutedRequest};
+use super::websocket::Head;
+use crate::ids::RequestId;
+
+/// Interim response sent when the request may proceed.
+pub(crate) const CONTINUE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\n";
+
+/// Run the checks that need no body on a head carrying `Expect`. `None` means answer
+/// [`CONTINUE`]; otherwise the rejection to send before closing the connection.
+pub(crate) fn preflight(service: &AppService, head: &Head<'_>) -> Option<RouteOutcome> {
+    let expectation = head.header("expect").unwrap_or_default();
+    if !expectation.eq_ignore_ascii_case("100-continue") {
+        let problem =
+            ProblemDetails::new(417).detail(format!("Unsupported expectation '{expectation}'"));
+        return Some(RouteOutcome::problem(problem));
+    }
+    let content_length = match head.header("content-length") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(len) => Some(len),
+            Err(_) => {
+                let problem = ProblemDetails::new(400).detail("Invalid Content-Length");
+                return Some(RouteOutcome::problem(problem));
+            }
+        },
+        None => None,
+    };
+    if let Some(problem) = service
+        .max_body_bytes
+        .zip(content_length)
+        .and_then(|(max, len)| body_too_large(len, max))
+    {
+        return Some(RouteOutcome::problem(problem));
+    }
+
+    let headers = head.headers.iter().map(|(n, v)| (*n, v.as_bytes()));
+    let parsed = match parse_request_head(head.method, head.target, headers) {
+        Ok(parsed) => parsed,
+        Err(method) => {
+            let problem = ProblemDetails::new(400).detail(format!("Invalid HTTP method: {method}"));
+            return Some(RouteOutcome::problem(problem));
+        }
+    };
+    let path = service.strip_base_path(parsed.path).ok()?;
+    let route_match = service.router.load().route(parsed.method.clone(), &path)?;
+    // A chunked body has no length yet but is still checked against the content types.
+    let body_size_bytes =
+        content_length.unwrap_or_else(|| usize::from(head.header("transfer-encoding").is_some()));
+    let request_id = RequestId::from_header_or_new(parsed.headers.get("x-request-id"));
+    match service.handle_route(
+        route_match,
+        RoutedRequest {
+            method: parsed.method,
+            path,
+            headers: parsed.headers,
+            cookies: parsed.cookies,
+            query_params: parsed.query_params,
+            body: None,
+            raw_body: None,
+            body_size_bytes,
+            request_id,
+            upgrade: false,
+            expect_continue: true,
+        },
+    ) {
+        RouteOutcome::Continue => None,
+        rejection => Some(rejection),
+    }
+}
+
+/// `head` (the request line and headers up to and including the blank line) without its
+/// `Expect` header lines, so the HTTP server does not answer the expectation again.
+pub(crate) fn strip_expect(head: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(head.len());
+    for line in head.split_inclusive(|&b| b == b'\n') {
+        let is_expect = line.len() > 7 && line[..7].eq_ignore_ascii_case(b"expect:");
+        if !is_expect {
+            out.extend_from_slice(line);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_expect_keeps_other_headers() {
+        let head = b"POST /upload HTTP/1.1\r\nHost: x\r\nEXPECT: 100-continue\r\n\
+                     Content-Length: 3\r\n\r\n";
+        assert_eq!(
+            strip_expect(head),
+            b"POST /upload HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\n\r\n"
+        );
+    }
+}
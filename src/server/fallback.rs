@@ -0,0 +1,301 @@
+//! Custom fallback responses for framework-generated errors.
+//!
+//! When no route matches (404), the path matches but not the method (405), or the framework
+//! itself fails a request (500: handler not registered or unreachable, schema configuration
+//! error, response validation in enforce mode), [`super::AppService`] normally writes an
+//! RFC 7807 problem. A fallback handler registered with [`super::AppService::on_not_found`],
+//! [`on_method_not_allowed`](super::AppService::on_method_not_allowed) or
+//! [`on_internal_error`](super::AppService::on_internal_error) replaces that response, e.g.
+//! with an HTML 404 page for browsers or a branded error envelope. It receives the request
+//! and the default problem, and its response still passes through every middleware's
+//! [`after`](crate::middleware::Middleware::after) hook.
+//!
+//! Without a handler for the status, the error is content-negotiated ([`ErrorFormat`]): a
+//! request whose `Accept` prefers `text/html` over JSON (a browser) gets an
+//! [`HtmlErrorPage`], everything else the problem. The page is a MiniJinja template like
+//! the templated files of [`crate::static_files`] and can be replaced (config.yaml
+//! `errors.html_template`); `errors.format: json` always writes the problem.
+
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use http::Method;
+use may::sync::mpsc;
+
+use super::response::ProblemDetails;
+use super::service::SharedDispatcher;
+use crate::dispatcher::{HandlerRequest, HandlerResponse, HeaderVec};
+use crate::ids::RequestId;
+use crate::router::ParamVec;
+use serde_json::Value;
+
+/// Builds the response for a framework error from the request and the default problem.
+///
+/// The request has an empty `handler_name` and no `body`.
+pub type FallbackHandler =
+    Arc<dyn Fn(&HandlerRequest, &ProblemDetails) -> HandlerResponse + Send + Sync>;
+
+/// How framework 404 / 405 / 500s without a fallback handler are written (config.yaml
+/// `errors.format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorFormat {
+    /// [`HtmlErrorPage`] when `Accept` prefers `text/html` over JSON, the problem otherwise
+    #[default]
+    Negotiate,
+    /// Always the problem, for pure APIs
+    Json,
+}
+
+/// Built-in template of [`HtmlErrorPage`].
+pub const DEFAULT_HTML_ERROR_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{ status }} {{ title }}</title>
+<style>body{font-family:system-ui,sans-serif;max-width:40rem;margin:4rem auto;padding:0 1rem;color:#222}p{color:#555}</style>
+</head>
+<body>
+<h1>{{ status }} {{ title }}</h1>
+{% if detail %}<p>{{ detail }}</p>{% endif %}
+</body>
+</html>
+"#;
+
+/// Template name; its `.html` suffix turns on MiniJinja's HTML autoescaping.
+const TEMPLATE_NAME: &str = "error.html";
+
+/// HTML page for framework errors requested by browsers.
+///
+/// A MiniJinja template rendered with the problem's members as context (`status`, `title`,
+/// `type`, `detail`, `instance` and any extensions such as `method`); values are
+/// HTML-escaped.
+#[derive(Debug, Clone)]
+pub struct HtmlErrorPage {
+    template: String,
+}
+
+impl Default for HtmlErrorPage {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_HTML_ERROR_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl HtmlErrorPage {
+    /// Page from a template source.
+    ///
+    /// # Errors
+    ///
+    /// Returns the MiniJinja syntax error when `template` does not compile.
+    pub fn new(template: impl Into<String>) -> Result<Self, minijinja::Error> {
+        let template = template.into();
+        minijinja::Environment::new().add_template(TEMPLATE_NAME, &template)?;
+        Ok(Self { template })
+    }
+
+    /// Page from a template file (config.yaml `errors.html_template`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the file cannot be read or does not compile.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let template = std::fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        Self::new(template).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })
+    }
+
+    /// Render the page for `problem`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the MiniJinja error when rendering fails (e.g. a filter error).
+    pub fn render(&self, problem: &ProblemDetails) -> Result<String, minijinja::Error> {
+        let mut env = minijinja::Environment::new();
+        env.add_template(TEMPLATE_NAME, &self.template)?;
+        env.get_template(TEMPLATE_NAME)?.render(problem.to_value())
+    }
+
+    /// `text/html` response for `problem`; `None` (the problem is written instead) when
+    /// rendering fails.
+    fn response(&self, problem: &ProblemDetails) -> Option<HandlerResponse> {
+        match self.render(problem) {
+            Ok(html) => {
+                let mut headers = HeaderVec::new();
+                headers.push((
+                    Arc::from("content-type"),
+                    "text/html; charset=utf-8".to_string(),
+                ));
+                Some(HandlerResponse::new(
+                    problem.status,
+                    headers,
+                    Value::String(html),
+                ))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, status = problem.status, "HTML error page failed to render");
+                None
+            }
+        }
+    }
+}
+
+/// Whether an `Accept` value ranks `text/html` above JSON.
+///
+/// Browsers (`text/html,application/xhtml+xml,...,*/*;q=0.8`) do; `*/*`, JSON media types,
+/// ties and a missing header do not.
+pub(crate) fn prefers_html(accept: &str) -> bool {
+    let mut html = 0.0_f32;
+    let mut json = 0.0_f32;
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let range = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let q = parts
+            .find_map(|p| {
+                p.trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+            })
+            .unwrap_or(1.0);
+        match range.as_str() {
+            "text/html" | "application/xhtml+xml" => html = html.max(q),
+            "application/json" | "application/problem+json" | "application/*" => {
+                json = json.max(q);
+            }
+            _ => {}
+        }
+    }
+    html > 0.0 && html > json
+}
+
+/// Fallback handlers of an [`super::AppService`], by framework error status.
+#[derive(Clone, Default)]
+pub struct FallbackHandlers {
+    /// No route for the path (404)
+    pub not_found: Option<FallbackHandler>,
+    /// The path has routes, but none for the method (405)
+    pub method_not_allowed: Option<FallbackHandler>,
+    /// The framework failed the request (500)
+    pub internal_error: Option<FallbackHandler>,
+    /// Page for browsers when no handler is registered for the status; `None` always writes
+    /// the problem ([`ErrorFormat::Json`]).
+    pub html_page: Option<Arc<HtmlErrorPage>>,
+}
+
+impl FallbackHandlers {
+    fn for_status(&self, status: u16) -> Option<&FallbackHandler> {
+        match status {
+            404 => self.not_found.as_ref(),
+            405 => self.method_not_allowed.as_ref(),
+            500 => self.internal_error.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether a fallback is registered for `status`.
+    pub(crate) fn handles(&self, status: u16) -> bool {
+        self.for_status(status).is_some()
+    }
+
+    /// Response from the fallback registered for `problem.status`, or the HTML page when
+    /// there is none and the request prefers HTML, after middleware `after` hooks ran on it;
+    /// `None` when the problem should be written as is.
+    pub(crate) fn respond(
+        &self,
+        dispatcher: &SharedDispatcher,
+        problem: &ProblemDetails,
+        request: impl FnOnce() -> HandlerRequest,
+    ) -> Option<HandlerResponse> {
+        if !matches!(problem.status, 404 | 405 | 500) {
+            return None;
+        }
+        let handler = self.for_status(problem.status);
+        if handler.is_none() && self.html_page.is_none() {
+            return None;
+        }
+        let request = request();
+        let start = Instant::now();
+        let mut resp = match (handler, &self.html_page) {
+            (Some(handler), _) => handler(&request, problem),
+            (None, Some(page)) if request.get_header("accept").is_some_and(prefers_html) => {
+                page.response(problem)?
+            }
+            _ => return None,
+        };
+        let latency = start.elapsed();
+        for mw in &dispatcher.load().middlewares {
+            mw.after(&request, &mut resp, latency);
+        }
+        Some(resp)
+    }
+}
+
+/// Request handed to a fallback handler.
+pub(crate) fn fallback_request(
+    method: &Method,
+    path: &str,
+    headers: &HeaderVec,
+    cookies: &HeaderVec,
+    query_params: &ParamVec,
+    request_id: RequestId,
+    peer_addr: Option<IpAddr>,
+) -> HandlerRequest {
+    let (reply_tx, _reply_rx) = mpsc::channel();
+    HandlerRequest {
+        request_id,
+        method: method.clone(),
+        path: path.to_string(),
+        handler_name: String::new(),
+        path_params: ParamVec::new(),
+        query_params: query_params.clone(),
+        headers: headers.clone(),
+        cookies: cookies.clone(),
+        body: None,
+        jwt_claims: None,
+        reply_tx,
+        queue_guard: None,
+        peer_addr,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_html_only_when_html_outranks_json() {
+        assert!(prefers_html(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        ));
+        assert!(prefers_html("application/json;q=0.5, text/html"));
+        assert!(!prefers_html("*/*"));
+        assert!(!prefers_html("application/json"));
+        assert!(!prefers_html("application/problem+json, text/html"));
+        assert!(!prefers_html("text/html;q=0"));
+    }
+
+    #[test]
+    fn html_page_escapes_problem_members() {
+        let problem = ProblemDetails::new(404).detail("No route for GET /<script>");
+        let html = HtmlErrorPage::default().render(&problem).unwrap();
+        assert!(html.contains("<title>404 Not Found</title>"), "{html}");
+        assert!(html.contains("&lt;script&gt;"), "{html}");
+        assert!(!html.contains("<script>"), "{html}");
+        assert!(HtmlErrorPage::new("{% if %}").is_err());
+    }
+}
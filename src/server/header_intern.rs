@@ -64,8 +64,8 @@ static INTERNED: Lazy<Vec<(&'static [u8], Arc<str>)>> = Lazy::new(|| {
 ///
 /// * Hit (~95 %+ of real HTTP traffic): returns a shared `Arc` with one
 ///   atomic refcount bump. Zero heap allocation.
-/// * Miss: falls back to `Arc::from(lowercased)` — identical behaviour to
-///   the pre-intern path, so any custom header still works.
+/// * Miss: one `Arc::from(lowercased)` allocation (names up to 64 bytes are
+///   lowercased on the stack), so any custom header still works.
 ///
 /// Case-insensitive ASCII comparison, matching RFC 9110 §5.1.
 #[inline]
@@ -76,14 +76,23 @@ pub fn intern_header_name(raw_name: &[u8]) -> Arc<str> {
             return Arc::clone(arc);
         }
     }
-    // Miss — allocate lowercased owned string, then Arc<str>. This mirrors the
-    // pre-Phase-2.1 behaviour for any non-standard header name.
-    let mut lower = Vec::with_capacity(raw_name.len());
-    lower.extend(raw_name.iter().map(|b| b.to_ascii_lowercase()));
+    // Miss — lowercase (on the stack for names up to 64 bytes), then one
+    // Arc<str> allocation. Any non-standard header name still works.
+    let mut stack = [0u8; 64];
+    let mut heap = Vec::new();
+    let lower: &mut [u8] = if raw_name.len() <= stack.len() {
+        &mut stack[..raw_name.len()]
+    } else {
+        heap.resize(raw_name.len(), 0);
+        &mut heap
+    };
+    for (dst, src) in lower.iter_mut().zip(raw_name) {
+        *dst = src.to_ascii_lowercase();
+    }
     // SAFETY: `raw_name` was a valid UTF-8 header name (httparse rejects non-
     // ASCII names) and `to_ascii_lowercase` only touches ASCII letters, so the
     // result remains valid UTF-8.
-    let lower_str = unsafe { std::str::from_utf8_unchecked(&lower) };
+    let lower_str = unsafe { std::str::from_utf8_unchecked(lower) };
     Arc::from(lower_str)
 }
 
@@ -133,6 +142,13 @@ mod tests {
         assert!(!Arc::ptr_eq(&a, &b));
     }
 
+    #[test]
+    fn intern_miss_lowercases_names_longer_than_the_stack_buffer() {
+        let raw = format!("X-{}", "Long".repeat(20));
+        let arc = intern_header_name(raw.as_bytes());
+        assert_eq!(&*arc, raw.to_ascii_lowercase());
+    }
+
     #[test]
     fn intern_handles_every_canonical_name() {
         for name in COMMON_HEADER_NAMES {
@@ -5,6 +5,12 @@ use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::thread;
 use std::time::Duration;
 
+use super::relay;
+#[cfg(unix)]
+use super::unix_socket::UnixBinding;
+use super::websocket::WebSocketBinding;
+use super::AppService;
+
 /// Wrapper around may_minihttp's HTTP server
 ///
 /// Provides a typed interface for starting and managing HTTP servers.
@@ -18,6 +24,9 @@ pub struct HttpServer<T>(pub T);
 pub struct ServerHandle {
     addr: SocketAddr,
     handle: JoinHandle<()>,
+    #[cfg(unix)]
+    unix: Option<UnixBinding>,
+    websocket: Option<WebSocketBinding>,
 }
 
 impl ServerHandle {
@@ -47,7 +56,8 @@ impl ServerHandle {
 
     /// Wait for the server to be ready to accept connections
     ///
-    /// Polls the server address by attempting TCP connections until successful.
+    /// Polls the server address by attempting TCP connections until successful (and, for
+    /// [`HttpServer::start_unix`], connections to the socket file).
     /// Useful in tests to ensure the server is fully started before sending requests.
     ///
     /// # Returns
@@ -59,7 +69,7 @@ impl ServerHandle {
     /// Returns `TimedOut` error if the server doesn't become ready within ~250ms (50 attempts × 5ms).
     pub fn wait_ready(&self) -> io::Result<()> {
         for _ in 0..50 {
-            if TcpStream::connect(self.addr).is_ok() {
+            if TcpStream::connect(self.addr).is_ok() && self.unix_ready() {
                 return Ok(());
             }
             thread::sleep(Duration::from_millis(5));
@@ -67,11 +77,38 @@ impl ServerHandle {
         Err(io::Error::new(io::ErrorKind::TimedOut, "server not ready"))
     }
 
+    #[cfg(unix)]
+    fn unix_ready(&self) -> bool {
+        self.unix
+            .as_ref()
+            .is_none_or(|u| std::os::unix::net::UnixStream::connect(u.path()).is_ok())
+    }
+
+    #[cfg(not(unix))]
+    fn unix_ready(&self) -> bool {
+        true
+    }
+
+    /// Socket file path when started with [`HttpServer::start_unix`].
+    #[cfg(unix)]
+    #[must_use]
+    pub fn unix_path(&self) -> Option<&std::path::Path> {
+        self.unix.as_ref().map(UnixBinding::path)
+    }
+
     /// Stop the server gracefully
     ///
     /// Cancels the server coroutine and waits for it to finish.
-    /// Consumes the handle, preventing further operations.
+    /// Consumes the handle, preventing further operations. A Unix socket or WebSocket
+    /// listener stops accepting first and a socket file is removed.
     pub fn stop(self) {
+        if let Some(websocket) = self.websocket {
+            websocket.shutdown();
+        }
+        #[cfg(unix)]
+        if let Some(unix) = self.unix {
+            unix.shutdown();
+        }
         // SAFETY: may::CoroutineHandle::coroutine().cancel() is marked unsafe by the may runtime.
         // This is safe because:
         // - We're in Drop, so the server is shutting down
@@ -158,6 +195,89 @@ impl<T: HttpService + Clone + Send + Sync + 'static> HttpServer<T> {
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid address"))?;
         // Use HttpServerWithHeaders<_, 32> to handle modern API gateway/proxy traffic
         let handle = HttpServerWithHeaders::<_, 32>(self.0).start(addr)?;
-        Ok(ServerHandle { addr, handle })
+        Ok(ServerHandle {
+            addr,
+            handle,
+            #[cfg(unix)]
+            unix: None,
+            websocket: None,
+        })
+    }
+}
+
+impl HttpServer<AppService> {
+    /// Start the HTTP server on a Unix domain socket at `path`
+    ///
+    /// `may_minihttp` only accepts TCP, so the service is served on an ephemeral `127.0.0.1`
+    /// port and each socket connection is relayed byte-for-byte to it. Handlers, middleware,
+    /// health and metrics behave as over TCP, except that requests have no peer address:
+    /// [`crate::dispatcher::HandlerRequest::client_ip`] resolves nothing and `X-Forwarded-*`
+    /// headers reach handlers unverified.
+    ///
+    /// Filesystem permissions on the socket file (created with the process umask) decide who
+    /// can connect. The loopback port only serves connections opened by the relay, which
+    /// present a secret generated here; others are answered `403` and closed.
+    ///
+    /// A stale socket file at `path` is removed; [`ServerHandle::stop`] removes the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AddrInUse` if another process is serving `path`, `AlreadyExists` if `path` is
+    /// not a socket, or any error binding the socket or the loopback port.
+    #[cfg(unix)]
+    pub fn start_unix<P: AsRef<std::path::Path>>(self, path: P) -> io::Result<ServerHandle> {
+        let (mut server, upstream) = relay::start_upstream(self.0)?;
+        match UnixBinding::bind(path.as_ref(), upstream) {
+            Ok(unix) => {
+                server.unix = Some(unix);
+                Ok(server)
+            }
+            Err(e) => {
+                server.stop();
+                Err(e)
+            }
+        }
+    }
+
+    /// Start the HTTP server on `addr` with WebSocket routes (`x-websocket: true`) enabled
+    ///
+    /// `may_minihttp` cannot hand a connection over after `101 Switching Protocols`, so `addr`
+    /// is accepted by a front listener: WebSocket handshakes are served there (see
+    /// [`super::websocket`]) and every other connection is relayed byte-for-byte to the
+    /// service on an ephemeral `127.0.0.1` port, as with `start_unix`. Only the relay's
+    /// connections, which present a secret generated here, are served on that port.
+    ///
+    /// Relaying costs an extra loopback connection per client connection and copies every
+    /// byte of plain HTTP traffic twice; use [`Self::start`] when neither WebSocket routes
+    /// nor `Expect: 100-continue` are needed. The relay reports each client's address, so
+    /// [`crate::dispatcher::HandlerRequest::peer_addr`] is set on requests served this way.
+    ///
+    /// The front listener also answers `Expect: 100-continue` when
+    /// [`AppService::set_expect_continue`] is on (see [`super::expect`]), so it is worth
+    /// starting with this method for large uploads even without WebSocket routes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is invalid or either port cannot be bound.
+    pub fn start_with_websockets<A: ToSocketAddrs>(self, addr: A) -> io::Result<ServerHandle> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid address"))?;
+        let listener = may::net::TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let service = self.0.clone();
+        let (mut server, upstream) = relay::start_upstream(self.0)?;
+        match WebSocketBinding::bind(listener, upstream, service) {
+            Ok(websocket) => {
+                server.addr = addr;
+                server.websocket = Some(websocket);
+                Ok(server)
+            }
+            Err(e) => {
+                server.stop();
+                Err(e)
+            }
+        }
     }
 }
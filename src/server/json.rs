@@ -0,0 +1,120 @@
+//! JSON codec for request bodies and response serialization.
+//!
+//! Request parsing and response writing in [`super`] go through [`DefaultJsonCodec`],
+//! selected at build time:
+//!
+//! | features | codec |
+//! |----------|-------|
+//! | (default) | [`SerdeJsonCodec`] |
+//! | `simd-json` | `SimdJsonCodec` (SIMD parsing and serialization via `simd-json`) |
+//! | `simd-json` + `arbitrary-precision` | [`SerdeJsonCodec`]: `simd-json` cannot keep numbers as text |
+//!
+//! Both produce a [`serde_json::Value`], so schema validation, handlers and middleware are
+//! unaffected by the choice. Compare them with `cargo bench --bench json_codec --features simd-json`.
+//! `SimdJsonCodec` copies every request body and goes through simd-json's serde bridge, and
+//! it has not been shown to be faster; see `docs/PERFORMANCE.md` before enabling it.
+//!
+//! Pretty-printed responses (`BRRTR_PRETTY_JSON`, or `?__pretty=1` with `BRRTR_DEV_MODE`; see
+//! [`crate::runtime_config`]) go through [`JsonCodec::to_vec_pretty`], which is `serde_json`
+//! for every codec. Object members are written in key order unless the `preserve-order`
+//! feature is enabled, which keeps serde struct field order (and request body member order).
+
+use serde_json::Value;
+
+/// Parses request bodies into and serializes responses from [`serde_json::Value`].
+pub trait JsonCodec {
+    /// Parse a JSON document; `None` when `raw` is not valid JSON.
+    fn parse(raw: &[u8]) -> Option<Value>;
+
+    /// Serialize `value` to JSON bytes.
+    ///
+    /// # Errors
+    ///
+    /// A description of why `value` could not be serialized.
+    fn to_vec(value: &Value) -> Result<Vec<u8>, String>;
+
+    /// Serialize `value` to indented JSON bytes.
+    ///
+    /// # Errors
+    ///
+    /// A description of why `value` could not be serialized.
+    fn to_vec_pretty(value: &Value) -> Result<Vec<u8>, String> {
+        serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+    }
+}
+
+/// [`JsonCodec`] backed by `serde_json`; the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeJsonCodec;
+
+impl JsonCodec for SerdeJsonCodec {
+    #[inline]
+    fn parse(raw: &[u8]) -> Option<Value> {
+        serde_json::from_slice(raw).ok()
+    }
+
+    #[inline]
+    fn to_vec(value: &Value) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(value).map_err(|e| e.to_string())
+    }
+}
+
+/// [`JsonCodec`] backed by `simd-json` (feature `simd-json`).
+#[cfg(feature = "simd-json")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimdJsonCodec;
+
+#[cfg(feature = "simd-json")]
+impl JsonCodec for SimdJsonCodec {
+    #[inline]
+    fn parse(raw: &[u8]) -> Option<Value> {
+        // simd-json rewrites its input in place; `raw` is also kept as the request's raw body.
+        let mut buf = raw.to_vec();
+        simd_json::serde::from_slice(&mut buf).ok()
+    }
+
+    #[inline]
+    fn to_vec(value: &Value) -> Result<Vec<u8>, String> {
+        simd_json::serde::to_vec(value).map_err(|e| e.to_string())
+    }
+}
+
+/// Codec used by request parsing and response writing; see the [module docs](self).
+#[cfg(not(all(feature = "simd-json", not(feature = "arbitrary-precision"))))]
+pub type DefaultJsonCodec = SerdeJsonCodec;
+
+/// Codec used by request parsing and response writing; see the [module docs](self).
+#[cfg(all(feature = "simd-json", not(feature = "arbitrary-precision")))]
+pub type DefaultJsonCodec = SimdJsonCodec;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn round_trip<C: JsonCodec>() {
+        let raw = br#"{"name":"Rex","tags":["a","b"],"age":3,"weight":4.5,"owner":null}"#;
+        let value = C::parse(raw).expect("valid json");
+        assert_eq!(
+            value,
+            json!({"name": "Rex", "tags": ["a", "b"], "age": 3, "weight": 4.5, "owner": null})
+        );
+        let bytes = C::to_vec(&value).expect("serializable");
+        assert_eq!(C::parse(&bytes), Some(value));
+        assert_eq!(C::parse(b"{\"name\":"), None);
+        let pretty = C::to_vec_pretty(&value).expect("serializable");
+        assert!(pretty.contains(&b'\n'));
+        assert_eq!(C::parse(&pretty), Some(value));
+    }
+
+    #[test]
+    fn serde_json_codec_round_trips() {
+        round_trip::<SerdeJsonCodec>();
+    }
+
+    #[cfg(feature = "simd-json")]
+    #[test]
+    fn simd_json_codec_round_trips() {
+        round_trip::<SimdJsonCodec>();
+    }
+}
@@ -0,0 +1,98 @@
+//! Request URI and header limits.
+//!
+//! [`uri_violation`] bounds the request target (path and query string): a longer one is
+//! answered with `414 URI Too Long` before it is percent-decoded, parsed or routed.
+//! [`HeaderLimits`] bounds how many header lines a request may carry and how many bytes
+//! their names and values may add up to. [`super::AppService`] checks them against the raw
+//! parsed headers, before any header is copied into a [`HeaderVec`](crate::dispatcher::HeaderVec),
+//! and answers `431 Request Header Fields Too Large` when either is exceeded.
+//!
+//! `may_minihttp` also caps the number of header lines it parses; requests beyond that cap are
+//! rejected by the HTTP layer before they reach the service.
+
+/// Default longest request target, path plus query string (8 KiB).
+pub const DEFAULT_MAX_URI_BYTES: usize = 8 * 1024;
+/// Default for [`HeaderLimits::max_headers`].
+pub const DEFAULT_MAX_HEADERS: usize = 100;
+/// Default for [`HeaderLimits::max_header_bytes`] (32 KiB).
+pub const DEFAULT_MAX_HEADER_BYTES: usize = 32 * 1024;
+
+/// Upper bounds on request headers, enforced before routing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderLimits {
+    /// Maximum number of header lines
+    pub max_headers: usize,
+    /// Maximum total size of header names and values, in bytes
+    pub max_header_bytes: usize,
+}
+
+impl Default for HeaderLimits {
+    fn default() -> Self {
+        Self {
+            max_headers: DEFAULT_MAX_HEADERS,
+            max_header_bytes: DEFAULT_MAX_HEADER_BYTES,
+        }
+    }
+}
+
+impl HeaderLimits {
+    /// Describe which limit `headers` (name and value lengths of each line) exceed, if any.
+    pub(crate) fn violation(
+        &self,
+        headers: impl ExactSizeIterator<Item = (usize, usize)>,
+    ) -> Option<String> {
+        let count = headers.len();
+        if count > self.max_headers {
+            return Some(format!(
+                "Request has {count} headers; at most {} are allowed",
+                self.max_headers
+            ));
+        }
+        let mut total = 0usize;
+        for (name, value) in headers {
+            total = total.saturating_add(name).saturating_add(value);
+            if total > self.max_header_bytes {
+                return Some(format!(
+                    "Request headers exceed {} bytes",
+                    self.max_header_bytes
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Describe how a request target of `len` bytes exceeds `max_uri_bytes`, if it does.
+pub(crate) fn uri_violation(len: usize, max_uri_bytes: usize) -> Option<String> {
+    (len > max_uri_bytes)
+        .then(|| format!("Request URI is {len} bytes; at most {max_uri_bytes} are allowed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn violation_reports_the_exceeded_limit() {
+        let limits = HeaderLimits {
+            max_headers: 2,
+            max_header_bytes: 20,
+        };
+        assert!(limits.violation([(4, 6), (6, 4)].into_iter()).is_none());
+        assert!(limits
+            .violation([(1, 1), (1, 1), (1, 1)].into_iter())
+            .is_some_and(|d| d.contains("3 headers")));
+        assert!(limits
+            .violation([(4, 6), (6, 5)].into_iter())
+            .is_some_and(|d| d.contains("20 bytes")));
+    }
+
+    #[test]
+    fn uri_violation_reports_the_length() {
+        assert!(uri_violation(8, 8).is_none());
+        assert_eq!(
+            uri_violation(9, 8).as_deref(),
+            Some("Request URI is 9 bytes; at most 8 are allowed")
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Middleware chain assembly from the `middleware:` section of `config.yaml`.
+//!
+//! The section is an ordered list; middleware runs in list order (`before` first-to-last,
+//! `after` in the same order as registered on the [`crate::dispatcher::Dispatcher`]).
+//! [`crate::middleware::MetricsMiddleware`] is not listed: services always register it
+//! first so it observes the full chain.
+//!
+//! ```yaml
+//! middleware:
+//!   - name: cors              # settings come from the top-level `cors:` section
+//!   - name: security_headers
+//!     hsts_max_age_secs: 31536000
+//!   - name: rate_limit
+//!     requests_per_window: 100
+//!     window_secs: 60
+//!     key_header: x-api-key
+//!   - name: compression
+//!     min_size_bytes: 1024
+//!   - name: forward_claims
+//!     claims:
+//!       sub: X-User-Id
+//!   - name: body_logging
+//!     enabled: true
+//!     routes: [login]
+//!     redact: [/password]
+//!   - name: response_cache
+//!     routes: [list_pets]
+//!     ttl_secs: 30
+//! ```
+//!
+//! | Name | Settings |
+//! |------|----------|
+//! | `body_logging` | [`BodyLoggingConfig`]: `enabled` (default false), `routes`, `level`, `request`, `response`, `redact` (JSON pointers), `max_body_bytes` (default 4096) |
+//! | `cors` | none — uses the top-level `cors:` section plus OpenAPI `x-cors` |
+//! | `compression` | [`CompressionConfig`]: `min_size_bytes` (default 1024) |
+//! | `forward_claims` | [`ClaimsForwardingConfig`]: `claims` (claim → downstream header; routes override with OpenAPI `x-forward-claims`) |
+//! | `rate_limit` | [`RateLimitConfig`]: `requests_per_window` (required), `window_secs` (default 1), `key_header`, `max_tracked_keys` (default 10000) |
+//! | `response_cache` | [`ResponseCacheConfig`]: `routes`, `ttl_secs` (default 60), `max_entries` (default 1024), `query_params` (default all), `vary_headers` (default `[accept]`) |
+//! | `security_headers` | [`SecurityHeadersConfig`]: `content_type_options`, `frame_options`, `referrer_policy`, `hsts_max_age_secs`, `hsts_include_subdomains`, `content_security_policy` |
+//!
+//! When the section is absent the chain is `[cors]`, matching services generated before
+//! the section existed. Unknown names, duplicate entries and invalid settings are startup
+//! errors — nothing is parsed on the request path.
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+
+use crate::middleware::{
+    BodyLoggingConfig, BodyLoggingMiddleware, ClaimsForwardingConfig, ClaimsForwardingMiddleware,
+    CompressionConfig, CompressionMiddleware, MetricsMiddleware, Middleware, RateLimitConfig,
+    RateLimitMiddleware, ResponseCacheConfig, ResponseCacheMiddleware, SecurityHeadersConfig,
+    SecurityHeadersMiddleware,
+};
+use crate::spec::RouteMeta;
+
+use super::app_config::{AppConfig, MiddlewareEntry};
+use super::cors_setup::build_cors_middleware;
+
+/// Middleware names accepted in the `middleware:` section.
+pub const KNOWN_MIDDLEWARE: &[&str] = &[
+    "body_logging",
+    "cors",
+    "compression",
+    "forward_claims",
+    "rate_limit",
+    "response_cache",
+    "security_headers",
+];
+
+/// Errors from validating the `middleware:` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiddlewareConfigError {
+    /// Entry `index` names a middleware that does not exist.
+    UnknownMiddleware {
+        /// Position in the `middleware:` list.
+        index: usize,
+        /// Name as written in the config.
+        name: String,
+    },
+    /// The same middleware is listed more than once.
+    DuplicateMiddleware(String),
+    /// Settings for a known middleware failed to parse or are out of range.
+    InvalidSettings {
+        /// Middleware name.
+        name: String,
+        /// Parse or validation error.
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for MiddlewareConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownMiddleware { index, name } => write!(
+                f,
+                "middleware[{index}]: unknown middleware `{name}` (expected one of: {})",
+                KNOWN_MIDDLEWARE.join(", ")
+            ),
+            Self::DuplicateMiddleware(name) => {
+                write!(f, "middleware `{name}` is listed more than once")
+            }
+            Self::InvalidSettings { name, reason } => {
+                write!(f, "middleware `{name}`: invalid settings: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MiddlewareConfigError {}
+
+/// A validated `middleware:` entry.
+#[derive(Debug, Clone, PartialEq)]
+enum MiddlewareSpec {
+    BodyLogging(BodyLoggingConfig),
+    Cors,
+    Compression(CompressionConfig),
+    ForwardClaims(ClaimsForwardingConfig),
+    RateLimit(RateLimitConfig),
+    ResponseCache(ResponseCacheConfig),
+    SecurityHeaders(SecurityHeadersConfig),
+}
+
+fn parse_settings<T: DeserializeOwned>(
+    entry: &MiddlewareEntry,
+) -> Result<T, MiddlewareConfigError> {
+    serde_yaml::from_value(serde_yaml::Value::Mapping(entry.settings.clone())).map_err(|e| {
+        MiddlewareConfigError::InvalidSettings {
+            name: entry.name.clone(),
+            reason: e.to_string(),
+        }
+    })
+}
+
+fn parse_entry(
+    index: usize,
+    entry: &MiddlewareEntry,
+) -> Result<MiddlewareSpec, MiddlewareConfigError> {
+    let invalid = |reason: &str| MiddlewareConfigError::InvalidSettings {
+        name: entry.name.clone(),
+        reason: reason.to_string(),
+    };
+    match entry.name.as_str() {
+        "body_logging" => {
+            let cfg: BodyLoggingConfig = parse_settings(entry)?;
+            if let Some(pointer) = cfg.redact.iter().find(|p| !p.starts_with('/')) {
+                return Err(invalid(&format!(
+                    "redact entry `{pointer}` is not a JSON pointer (must start with `/`)"
+                )));
+            }
+            Ok(MiddlewareSpec::BodyLogging(cfg))
+        }
+        "cors" => {
+            if !entry.settings.is_empty() {
+                return Err(invalid(
+                    "cors takes no inline settings; configure the top-level `cors:` section",
+                ));
+            }
+            Ok(MiddlewareSpec::Cors)
+        }
+        "compression" => parse_settings(entry).map(MiddlewareSpec::Compression),
+        "forward_claims" => {
+            let cfg: ClaimsForwardingConfig = parse_settings(entry)?;
+            if let Some(header) = cfg
+                .claims
+                .values()
+                .find(|h| !crate::middleware::is_valid_header_name(h))
+            {
+                return Err(invalid(&format!("`{header}` is not a valid header name")));
+            }
+            Ok(MiddlewareSpec::ForwardClaims(cfg))
+        }
+        "rate_limit" => {
+            let cfg: RateLimitConfig = parse_settings(entry)?;
+            if cfg.requests_per_window == 0 {
+                return Err(invalid("requests_per_window must be at least 1"));
+            }
+            if cfg.window_secs == 0 {
+                return Err(invalid("window_secs must be at least 1"));
+            }
+            Ok(MiddlewareSpec::RateLimit(cfg))
+        }
+        "response_cache" => {
+            let cfg: ResponseCacheConfig = parse_settings(entry)?;
+            if cfg.ttl_secs == 0 {
+                return Err(invalid("ttl_secs must be at least 1"));
+            }
+            if cfg.max_entries == 0 {
+                return Err(invalid("max_entries must be at least 1"));
+            }
+            Ok(MiddlewareSpec::ResponseCache(cfg))
+        }
+        "security_headers" => parse_settings(entry).map(MiddlewareSpec::SecurityHeaders),
+        other => Err(MiddlewareConfigError::UnknownMiddleware {
+            index,
+            name: other.to_string(),
+        }),
+    }
+}
+
+fn parse_middleware_config(
+    app_config: &AppConfig,
+) -> Result<Vec<MiddlewareSpec>, MiddlewareConfigError> {
+    let Some(entries) = app_config.middleware.as_ref() else {
+        return Ok(vec![MiddlewareSpec::Cors]);
+    };
+    let mut specs = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        if entries[..index].iter().any(|e| e.name == entry.name) {
+            return Err(MiddlewareConfigError::DuplicateMiddleware(
+                entry.name.clone(),
+            ));
+        }
+        specs.push(parse_entry(index, entry)?);
+    }
+    Ok(specs)
+}
+
+/// Validate the `middleware:` section without constructing anything.
+///
+/// # Errors
+///
+/// Returns the first unknown name, duplicate entry or invalid settings block.
+pub fn validate_middleware_config(app_config: &AppConfig) -> Result<(), MiddlewareConfigError> {
+    parse_middleware_config(app_config).map(|_| ())
+}
+
+/// Build the configured middleware chain, in order, ready for `Dispatcher::add_middleware`.
+///
+/// `metrics` is linked into CORS so `/metrics` exposes `brrtrouter_cors_*` counters; callers
+/// register it on the dispatcher themselves before this chain. A CORS entry whose policy
+/// fails to build is logged and skipped, as before this section existed.
+///
+/// # Errors
+///
+/// Returns [`MiddlewareConfigError`] for unknown names, duplicates or invalid settings; no
+/// middleware is constructed in that case.
+pub fn build_middleware_chain(
+    app_config: &AppConfig,
+    routes: &[RouteMeta],
+    metrics: Arc<MetricsMiddleware>,
+) -> Result<Vec<Arc<dyn Middleware>>, MiddlewareConfigError> {
+    let specs = parse_middleware_config(app_config)?;
+    let mut chain: Vec<Arc<dyn Middleware>> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        match spec {
+            MiddlewareSpec::BodyLogging(cfg) => {
+                chain.push(Arc::new(BodyLoggingMiddleware::new(&cfg, routes)));
+            }
+            MiddlewareSpec::Cors => {
+                if let Some(cors) = build_cors_middleware(app_config, routes, metrics.clone()) {
+                    chain.push(cors);
+                }
+            }
+            MiddlewareSpec::Compression(cfg) => {
+                chain.push(Arc::new(CompressionMiddleware::new(&cfg)));
+            }
+            MiddlewareSpec::ForwardClaims(cfg) => {
+                chain.push(Arc::new(ClaimsForwardingMiddleware::new(&cfg, routes)));
+            }
+            MiddlewareSpec::RateLimit(cfg) => {
+                chain.push(Arc::new(RateLimitMiddleware::new(&cfg)));
+            }
+            MiddlewareSpec::ResponseCache(cfg) => {
+                chain.push(Arc::new(ResponseCacheMiddleware::new(&cfg, routes)));
+            }
+            MiddlewareSpec::SecurityHeaders(cfg) => {
+                chain.push(Arc::new(SecurityHeadersMiddleware::new(&cfg)));
+            }
+        }
+    }
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> AppConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn absent_section_defaults_to_cors() {
+        let specs = parse_middleware_config(&AppConfig::default()).unwrap();
+        assert_eq!(specs, vec![MiddlewareSpec::Cors]);
+    }
+
+    #[test]
+    fn entries_parse_in_order_with_settings() {
+        let cfg = config(
+            "middleware:\n  - name: rate_limit\n    requests_per_window: 5\n  - name: security_headers\n    hsts_max_age_secs: 60\n  - name: compression\n",
+        );
+        let specs = parse_middleware_config(&cfg).unwrap();
+        assert_eq!(specs.len(), 3);
+        match &specs[0] {
+            MiddlewareSpec::RateLimit(rl) => {
+                assert_eq!(rl.requests_per_window, 5);
+                assert_eq!(rl.window_secs, 1);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        match &specs[1] {
+            MiddlewareSpec::SecurityHeaders(sh) => {
+                assert_eq!(sh.hsts_max_age_secs, Some(60));
+                assert_eq!(sh.frame_options, "DENY");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(
+            specs[2],
+            MiddlewareSpec::Compression(CompressionConfig::default())
+        );
+    }
+
+    #[test]
+    fn forward_claims_rejects_invalid_header_names() {
+        let cfg =
+            config("middleware:\n  - name: forward_claims\n    claims:\n      sub: X-User-Id\n");
+        match &parse_middleware_config(&cfg).unwrap()[0] {
+            MiddlewareSpec::ForwardClaims(fc) => {
+                assert_eq!(fc.claims.get("sub").map(String::as_str), Some("X-User-Id"));
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let cfg =
+            config("middleware:\n  - name: forward_claims\n    claims:\n      sub: \"X User\"\n");
+        assert!(matches!(
+            validate_middleware_config(&cfg),
+            Err(MiddlewareConfigError::InvalidSettings { .. })
+        ));
+    }
+
+    #[test]
+    fn body_logging_parses_and_rejects_non_pointer_redactions() {
+        let cfg = config(
+            "middleware:\n  - name: body_logging\n    enabled: true\n    routes: [login]\n    level: info\n    redact: [/password]\n",
+        );
+        match &parse_middleware_config(&cfg).unwrap()[0] {
+            MiddlewareSpec::BodyLogging(bl) => {
+                assert!(bl.enabled);
+                assert_eq!(bl.level, crate::middleware::BodyLogLevel::Info);
+                assert_eq!(bl.redact, ["/password"]);
+                assert_eq!(
+                    bl.max_body_bytes,
+                    crate::middleware::DEFAULT_MAX_LOGGED_BODY_BYTES
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let cfg = config("middleware:\n  - name: body_logging\n    redact: [password]\n");
+        assert!(matches!(
+            validate_middleware_config(&cfg),
+            Err(MiddlewareConfigError::InvalidSettings { .. })
+        ));
+    }
+
+    #[test]
+    fn response_cache_parses_and_rejects_zero_limits() {
+        let cfg = config(
+            "middleware:\n  - name: response_cache\n    routes: [list_pets]\n    ttl_secs: 30\n    query_params: [limit]\n",
+        );
+        match &parse_middleware_config(&cfg).unwrap()[0] {
+            MiddlewareSpec::ResponseCache(rc) => {
+                assert_eq!(rc.routes, ["list_pets"]);
+                assert_eq!(rc.ttl_secs, 30);
+                assert_eq!(rc.max_entries, 1024);
+                assert_eq!(rc.query_params.as_deref(), Some(&["limit".to_string()][..]));
+                assert_eq!(rc.vary_headers, ["accept"]);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        for zero in ["ttl_secs: 0", "max_entries: 0"] {
+            let cfg = config(&format!(
+                "middleware:\n  - name: response_cache\n    {zero}\n"
+            ));
+            assert!(matches!(
+                validate_middleware_config(&cfg),
+                Err(MiddlewareConfigError::InvalidSettings { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn unknown_name_is_rejected() {
+        let cfg = config("middleware:\n  - name: cors\n  - name: gzip\n");
+        assert_eq!(
+            validate_middleware_config(&cfg),
+            Err(MiddlewareConfigError::UnknownMiddleware {
+                index: 1,
+                name: "gzip".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn duplicates_and_bad_settings_are_rejected() {
+        let dup = config("middleware:\n  - name: cors\n  - name: cors\n");
+        assert_eq!(
+            validate_middleware_config(&dup),
+            Err(MiddlewareConfigError::DuplicateMiddleware(
+                "cors".to_string()
+            ))
+        );
+
+        let typo = config("middleware:\n  - name: compression\n    min_size: 10\n");
+        assert!(matches!(
+            validate_middleware_config(&typo),
+            Err(MiddlewareConfigError::InvalidSettings { .. })
+        ));
+
+        let zero = config("middleware:\n  - name: rate_limit\n    requests_per_window: 0\n");
+        assert!(matches!(
+            validate_middleware_config(&zero),
+            Err(MiddlewareConfigError::InvalidSettings { .. })
+        ));
+    }
+}
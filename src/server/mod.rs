@@ -61,14 +61,18 @@
 //! ## Example
 //!
 //! ```rust,ignore
+//! use brrtrouter::dispatcher::Dispatcher;
 //! use brrtrouter::server::AppService;
 //! use brrtrouter::router::Router;
 //! use brrtrouter::spec::load_spec;
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! let spec = load_spec("openapi.yaml")?;
-//! let router = Router::from_spec(&spec);
-//! let service = AppService::new(router, spec);
+//! let (routes, _slug) = load_spec("openapi.yaml")?;
+//! let service = AppService::builder()
+//!     .router(Router::new(routes))
+//!     .dispatcher(Dispatcher::new())
+//!     .spec_path("openapi.yaml")
+//!     .build()?;
 //!
 //! // Start server
 //! // service.start("0.0.0.0:8080")?;
@@ -78,26 +82,90 @@
 
 /// HTTP server implementation using may_minihttp
 pub mod app_config;
+/// Mounting the service under a path prefix
+pub mod base_path;
+/// Batch endpoint: several API calls in one request
+pub mod batch;
+/// `AppService` construction from named settings
+pub mod builder;
+/// Per-connection keep-alive policy
+pub mod connection;
 pub mod cors_setup;
+/// Source order for default security providers
+pub mod default_providers;
+/// `Expect: 100-continue` handling on the front listener
+pub mod expect;
+/// Custom 404 / 405 / 500 responses
+pub mod fallback;
 pub mod header_intern;
 pub mod http_server;
+/// Build-time selectable JSON codec (`serde_json` or `simd-json`)
+pub mod json;
+/// Request header count and size limits
+pub mod limits;
+/// Middleware chain assembly from config.yaml `middleware:`
+pub mod middleware_setup;
+/// `multipart/form-data` bodies and upload limits
+pub mod multipart;
+/// Duplicate-slash normalization and redirects
+pub mod path_normalize;
 /// Request parsing and parameter extraction
 pub mod request;
 /// Response building and serialization
 pub mod response;
+/// Handler response headers checked against the spec
+pub mod response_headers;
 /// Fix B: shared service bootstrap
 pub mod run_app;
+mod relay;
 /// Security provider registration from config.yaml
 pub mod security_setup;
 /// Core application service that handles requests
 pub mod service;
+/// Structured startup summary: config, address and route table
+pub mod startup;
+/// In-process requests through an `AppService` for tests
+pub mod test_client;
+#[cfg(unix)]
+mod unix_socket;
+/// WebSocket upgrades for `x-websocket` operations
+pub mod websocket;
 
-pub use request::{decode_param_value, parse_request, ParsedRequest};
+pub use request::{
+    decode_param_value, decode_query_values, parse_request, parse_request_head,
+    parse_request_with_limits, parse_request_with_timeout, DuplicateQueryPolicy, ParsedRequest,
+    RequestParseError, DEFAULT_BODY_READ_TIMEOUT, DEFAULT_MAX_JSON_DEPTH,
+};
 
 pub use app_config::{
-    load_app_config, ApiKeyConfig, AppConfig, BearerConfig, CorsConfig, HttpConfig, JwksConfig,
-    OAuth2Config, PropelAuthConfig, RemoteApiKeyConfig, SecurityConfig,
+    load_app_config, ApiKeyConfig, AppConfig, BatchConfig, BearerConfig, CorsConfig, ErrorsConfig,
+    HttpConfig, JwksConfig, MetricsConfig, MiddlewareEntry, OAuth2Config, OutboundTlsConfig,
+    PropelAuthConfig, QueryConfig, RemoteApiKeyConfig, RuntimeSettings, SecurityConfig,
+    StaticFilesConfig, ValidationConfig, WebSocketConfig,
+};
+pub use base_path::BasePath;
+pub use builder::{AppServiceBuildError, AppServiceBuilder};
+pub use connection::{ConnectionConfig, HttpVersion};
+pub use default_providers::{
+    DefaultProviderPolicy, MissingProviderError, ProviderSource, SchemeKind,
+};
+pub use fallback::{
+    ErrorFormat, FallbackHandler, FallbackHandlers, HtmlErrorPage, DEFAULT_HTML_ERROR_TEMPLATE,
 };
 pub use http_server::{HttpServer, ServerHandle};
+pub use json::{DefaultJsonCodec, JsonCodec, SerdeJsonCodec};
+pub use limits::{HeaderLimits, DEFAULT_MAX_URI_BYTES};
+pub use middleware_setup::{
+    build_middleware_chain, validate_middleware_config, MiddlewareConfigError, KNOWN_MIDDLEWARE,
+};
+pub use multipart::{Multipart, MultipartError, MultipartPart, UploadRejection};
+pub use path_normalize::{DuplicateSlashes, PathNormalization};
+pub use response::{write_problem, ProblemDetails, ProblemFieldError, PROBLEM_JSON};
+pub use response_headers::{HeaderReport, ResponseHeaderPolicy};
 pub use run_app::{RegisterHandlersFn, RunAppArgs, RunAppBuilder, RunAppHooks};
-pub use service::{health_endpoint, AppService};
+pub use service::{health_endpoint, AppService, DEFAULT_SERVER_HEADER, UNMATCHED_ROUTE};
+pub use startup::{sanitized_config, StartupSummary};
+pub use test_client::{TestClient, TestRequest, TestResponse};
+pub use websocket::{
+    Message, WebSocketChannel, WebSocketHandler, WebSocketRequest, WebSocketSender,
+};
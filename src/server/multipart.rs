@@ -0,0 +1,365 @@
+//! `multipart/form-data` bodies (RFC 7578) and the per-field upload limits of
+//! [`RouteMeta::upload_limits`](crate::spec::RouteMeta::upload_limits).
+//!
+//! The service checks a multipart request against its route's limits before dispatch —
+//! `413` for a part over `x-brrtrouter-max-bytes`, `415` for a part type the field does not
+//! accept — and the dispatcher hands the handler the parsed [`Multipart`] in
+//! [`HandlerRequest::extensions`](crate::dispatcher::HandlerRequest::extensions). Part data
+//! are slices of the request's raw body, not copies.
+
+use bytes::Bytes;
+
+use super::request::primary_content_type;
+use super::response::ProblemDetails;
+use crate::spec::UploadLimits;
+
+/// Content type of a part sent without a `Content-Type` header (RFC 7578 §4.4)
+pub const DEFAULT_PART_CONTENT_TYPE: &str = "text/plain";
+
+/// Why a `multipart/form-data` body could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultipartError {
+    /// The `Content-Type` header has no `boundary` parameter
+    MissingBoundary,
+    /// The body does not follow the multipart framing; the reason names what is wrong
+    Malformed(&'static str),
+}
+
+impl std::fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingBoundary => f.write_str("multipart Content-Type has no boundary"),
+            Self::Malformed(reason) => write!(f, "Malformed multipart body: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+/// One part of a `multipart/form-data` body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartPart {
+    /// Form field name (`Content-Disposition` `name`)
+    pub name: String,
+    /// Client-side file name (`Content-Disposition` `filename`); `None` for plain fields
+    pub filename: Option<String>,
+    /// The part's `Content-Type` header, as sent
+    pub content_type: Option<String>,
+    /// The part's content
+    pub data: Bytes,
+}
+
+impl MultipartPart {
+    /// The part's content type, [`DEFAULT_PART_CONTENT_TYPE`] when it sent none
+    pub fn content_type_or_default(&self) -> &str {
+        self.content_type
+            .as_deref()
+            .unwrap_or(DEFAULT_PART_CONTENT_TYPE)
+    }
+
+    /// Whether the part is a file (it has a `filename`)
+    pub fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+}
+
+/// A parsed `multipart/form-data` body, parts in the order sent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Multipart {
+    parts: Vec<MultipartPart>,
+}
+
+/// Whether `content_type` is `multipart/form-data`
+pub fn is_multipart_form_data(content_type: &str) -> bool {
+    primary_content_type(content_type).eq_ignore_ascii_case("multipart/form-data")
+}
+
+/// The `boundary` parameter of a multipart `Content-Type`, unquoted
+fn boundary(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("boundary") {
+            return None;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        (!value.is_empty()).then_some(value)
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|at| at + from)
+}
+
+/// A `Content-Disposition` parameter (`name`, `filename`), unquoted
+fn disposition_param(disposition: &str, param: &str) -> Option<String> {
+    let mut rest = disposition.split_once(';')?.1;
+    loop {
+        let (name, value) = rest.split_once('=')?;
+        let value = value.trim_start();
+        let (parsed, after) = if let Some(quoted) = value.strip_prefix('"') {
+            let mut out = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => out.extend(chars.next().map(|(_, escaped)| escaped)),
+                    '"' => {
+                        end = Some(i + 1);
+                        break;
+                    }
+                    _ => out.push(c),
+                }
+            }
+            let after = &quoted[end?..];
+            (out, after.split_once(';').map_or("", |(_, next)| next))
+        } else {
+            let (token, after) = value.split_once(';').unwrap_or((value, ""));
+            (token.trim().to_string(), after)
+        };
+        if name.trim().eq_ignore_ascii_case(param) {
+            return Some(parsed);
+        }
+        rest = after;
+    }
+}
+
+impl Multipart {
+    /// Parse `body`, sent as `content_type` (`multipart/form-data; boundary=...`)
+    ///
+    /// Parts without a `Content-Disposition: form-data` name are skipped, as are the
+    /// preamble and epilogue.
+    ///
+    /// # Errors
+    ///
+    /// [`MultipartError::MissingBoundary`] without a boundary parameter,
+    /// [`MultipartError::Malformed`] when the framing is broken (no closing delimiter, a part
+    /// without a blank line after its headers, non-UTF-8 headers).
+    pub fn parse(body: &Bytes, content_type: &str) -> Result<Self, MultipartError> {
+        let boundary = boundary(content_type).ok_or(MultipartError::MissingBoundary)?;
+        let delimiter = format!("--{boundary}");
+        let delimiter = delimiter.as_bytes();
+        // Every delimiter after the first starts a line
+        let next_delimiter = format!("\r\n--{boundary}");
+        let next_delimiter = next_delimiter.as_bytes();
+
+        let mut at = find(body, delimiter, 0)
+            .ok_or(MultipartError::Malformed("no boundary delimiter"))?
+            + delimiter.len();
+        let mut parts = Vec::new();
+        loop {
+            if body.get(at..at + 2) == Some(b"--") {
+                return Ok(Self { parts });
+            }
+            while matches!(body.get(at), Some(b' ' | b'\t')) {
+                at += 1;
+            }
+            if body.get(at..at + 2) != Some(b"\r\n") {
+                return Err(MultipartError::Malformed("delimiter not followed by CRLF"));
+            }
+            at += 2;
+
+            // From the CRLF just read, so a part without headers ends them at once
+            let headers_end = find(body, b"\r\n\r\n", at - 2)
+                .ok_or(MultipartError::Malformed("part headers not terminated"))?;
+            let headers = std::str::from_utf8(&body[at..headers_end.max(at)])
+                .map_err(|_| MultipartError::Malformed("part headers are not UTF-8"))?;
+            let data_start = headers_end + 4;
+            let data_end = find(body, next_delimiter, data_start)
+                .ok_or(MultipartError::Malformed("no closing boundary delimiter"))?;
+
+            let mut name = None;
+            let mut filename = None;
+            let mut part_type = None;
+            for line in headers.split("\r\n") {
+                let Some((header, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim();
+                if header.trim().eq_ignore_ascii_case("content-disposition") {
+                    name = disposition_param(value, "name");
+                    filename = disposition_param(value, "filename");
+                } else if header.trim().eq_ignore_ascii_case("content-type") {
+                    part_type = Some(value.to_string());
+                }
+            }
+            if let Some(name) = name {
+                parts.push(MultipartPart {
+                    name,
+                    filename,
+                    content_type: part_type,
+                    data: body.slice(data_start..data_end),
+                });
+            }
+            at = data_end + next_delimiter.len();
+        }
+    }
+
+    /// All parts, in the order sent
+    pub fn parts(&self) -> &[MultipartPart] {
+        &self.parts
+    }
+
+    /// The first part named `name`
+    pub fn part(&self, name: &str) -> Option<&MultipartPart> {
+        self.parts.iter().find(|part| part.name == name)
+    }
+
+    /// Every part named `name` (a repeated field, e.g. several files)
+    pub fn parts_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a MultipartPart> {
+        self.parts.iter().filter(move |part| part.name == name)
+    }
+
+    /// Check every part against the limits of its field
+    ///
+    /// # Errors
+    ///
+    /// The first [`UploadRejection`], in part order.
+    pub fn check_limits(&self, limits: &UploadLimits) -> Result<(), UploadRejection> {
+        for part in &self.parts {
+            let Some(limit) = limits.get(&part.name) else {
+                continue;
+            };
+            if let Some(max_bytes) = limit.max_bytes {
+                if part.data.len() as u64 > max_bytes {
+                    return Err(UploadRejection::TooLarge {
+                        field: part.name.clone(),
+                        size: part.data.len() as u64,
+                        max_bytes,
+                    });
+                }
+            }
+            if !limit.accepts_content_type(part.content_type_or_default()) {
+                return Err(UploadRejection::UnsupportedType {
+                    field: part.name.clone(),
+                    content_type: part.content_type_or_default().to_string(),
+                    accepted: limit.content_types.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A multipart part that breaks its field's [`UploadLimit`](crate::spec::UploadLimit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadRejection {
+    /// The part is larger than `x-brrtrouter-max-bytes`
+    TooLarge {
+        /// Form field name
+        field: String,
+        /// Part size in bytes
+        size: u64,
+        /// The field's limit
+        max_bytes: u64,
+    },
+    /// The part's content type is not one the field accepts
+    UnsupportedType {
+        /// Form field name
+        field: String,
+        /// The part's content type
+        content_type: String,
+        /// Content types the field accepts
+        accepted: Vec<String>,
+    },
+}
+
+impl UploadRejection {
+    /// Response for the client: `413` for a part too large, `415` for a type not accepted
+    #[must_use]
+    pub fn problem(&self) -> ProblemDetails {
+        match self {
+            Self::TooLarge {
+                field, max_bytes, ..
+            } => ProblemDetails::new(413)
+                .detail(self.to_string())
+                .extension("field", field.clone())
+                .extension("max_bytes", *max_bytes),
+            Self::UnsupportedType {
+                field, accepted, ..
+            } => ProblemDetails::new(415)
+                .detail(self.to_string())
+                .extension("field", field.clone())
+                .extension("accepted", accepted.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for UploadRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge {
+                field,
+                size,
+                max_bytes,
+            } => write!(
+                f,
+                "Upload '{field}' is {size} bytes; at most {max_bytes} are accepted"
+            ),
+            Self::UnsupportedType {
+                field,
+                content_type,
+                accepted,
+            } => write!(
+                f,
+                "Upload '{field}' has Content-Type '{content_type}'; accepted: {}",
+                accepted.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UploadRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CT: &str = "multipart/form-data; boundary=XyZ";
+
+    #[test]
+    fn parses_fields_and_files() {
+        let body = Bytes::from_static(
+            b"preamble\r\n--XyZ\r\n\
+              Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+              Cat\r\n\
+              --XyZ\r\n\
+              Content-Disposition: form-data; name=\"photo\"; filename=\"a \\\"b\\\".png\"\r\n\
+              Content-Type: image/png\r\n\r\n\
+              \x89PNG\r\n--X\r\n\
+              --XyZ--\r\nepilogue",
+        );
+        let multipart = Multipart::parse(&body, CT).unwrap();
+        assert_eq!(multipart.parts().len(), 2);
+        let title = multipart.part("title").unwrap();
+        assert_eq!(&title.data[..], b"Cat");
+        assert_eq!(title.content_type_or_default(), "text/plain");
+        assert!(!title.is_file());
+        let photo = multipart.part("photo").unwrap();
+        assert_eq!(photo.filename.as_deref(), Some("a \"b\".png"));
+        assert_eq!(photo.content_type.as_deref(), Some("image/png"));
+        assert_eq!(&photo.data[..], b"\x89PNG\r\n--X");
+    }
+
+    #[test]
+    fn rejects_broken_framing() {
+        let missing_close =
+            Bytes::from_static(b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue");
+        assert_eq!(
+            Multipart::parse(&missing_close, CT),
+            Err(MultipartError::Malformed("no closing boundary delimiter"))
+        );
+        assert_eq!(
+            Multipart::parse(&missing_close, "multipart/form-data"),
+            Err(MultipartError::MissingBoundary)
+        );
+        assert!(Multipart::parse(&Bytes::from_static(b"{}"), CT).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Duplicate slashes in request paths (`//pets`, `/pets//123`).
+//!
+//! The router skips empty segments, so such paths already reach `/pets` and `/pets/{id}`,
+//! but handlers, logs, metrics and the base path see them as sent. A [`PathNormalization`]
+//! (config.yaml `http.duplicate_slashes`) makes the canonical form explicit:
+//!
+//! - `off` (default): the path is left as sent;
+//! - `normalize`: runs of `/` are collapsed before routing, so `/pets//123` is handled,
+//!   logged and recorded as `/pets/123`;
+//! - `redirect`: the client is sent to the collapsed path with a `308 Permanent Redirect`
+//!   (or `301` with `http.duplicate_slashes_redirect: 301`), keeping the query string as sent.
+//!   `308` makes clients repeat the method and body; `301` lets them turn a `POST` into a
+//!   `GET`.
+//!
+//! Only duplicate slashes are touched: a trailing slash stays (`/pets//` becomes `/pets/`),
+//! and neither case nor percent-escapes change, since path parameters are case-sensitive.
+
+/// How paths with duplicate slashes are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateSlashes {
+    /// Leave the path as sent
+    #[default]
+    Off,
+    /// Collapse runs of `/` before routing
+    Normalize,
+    /// Redirect to the collapsed path
+    Redirect,
+}
+
+/// Duplicate-slash handling plus the status its redirects use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathNormalization {
+    /// What to do with a path containing `//`
+    pub duplicate_slashes: DuplicateSlashes,
+    redirect_status: u16,
+}
+
+impl Default for PathNormalization {
+    fn default() -> Self {
+        Self::new(DuplicateSlashes::Off)
+    }
+}
+
+impl PathNormalization {
+    /// Handle duplicate slashes per `duplicate_slashes`, redirecting with `308`
+    #[must_use]
+    pub fn new(duplicate_slashes: DuplicateSlashes) -> Self {
+        Self {
+            duplicate_slashes,
+            redirect_status: 308,
+        }
+    }
+
+    /// Redirect with `301` instead of `308`; any other `status` keeps `308`
+    #[must_use]
+    pub fn with_redirect_status(mut self, status: u16) -> Self {
+        self.redirect_status = if status == 301 { 301 } else { 308 };
+        self
+    }
+
+    /// Status of redirects to the canonical path (`301` or `308`)
+    #[must_use]
+    pub fn redirect_status(&self) -> u16 {
+        self.redirect_status
+    }
+
+    /// Where to redirect `target` (path and optional query string), if anywhere
+    ///
+    /// `Some` only in `redirect` mode for a path containing `//`; the query string is kept
+    /// byte for byte, so `//pets?next=//x` goes to `/pets?next=//x`.
+    #[must_use]
+    pub fn redirect_location(&self, target: &str) -> Option<String> {
+        if self.duplicate_slashes != DuplicateSlashes::Redirect {
+            return None;
+        }
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (target, None),
+        };
+        let mut location = collapse_slashes(path)?;
+        if let Some(query) = query {
+            location.push('?');
+            location.push_str(query);
+        }
+        Some(location)
+    }
+
+    /// `path` as routed: collapsed in `normalize` mode, unchanged otherwise
+    #[must_use]
+    pub fn normalize(&self, path: String) -> String {
+        match self.duplicate_slashes {
+            DuplicateSlashes::Normalize => collapse_slashes(&path).unwrap_or(path),
+            DuplicateSlashes::Off | DuplicateSlashes::Redirect => path,
+        }
+    }
+}
+
+/// `path` with every run of `/` collapsed to one; `None` when it has none
+#[must_use]
+pub fn collapse_slashes(path: &str) -> Option<String> {
+    if !path.contains("//") {
+        return None;
+    }
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        if !(c == '/' && out.ends_with('/')) {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
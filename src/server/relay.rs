@@ -0,0 +1,606 @@
+//! Loopback relay from listeners BRRTRouter accepts itself to the `may_minihttp` server.
+//!
+//! [`super::HttpServer::start_unix`] and [`super::HttpServer::start_with_websockets`] accept
+//! connections themselves, because `may_minihttp` keeps its accept loop private and only
+//! serves TCP. Connections they do not handle are relayed byte-for-byte to the HTTP server on
+//! an ephemeral `127.0.0.1` port.
+//!
+//! That port is reachable by every process on the host (or network namespace), which would
+//! bypass the Unix socket's filesystem permissions and any address-based controls on the
+//! public listener. The server behind a relay therefore only serves connections that open
+//! with the relay preface: an `OPTIONS *` request carrying [`RELAY_HEADER`] set to a random
+//! secret generated when the server starts, answered with `204`. A connection whose first
+//! request lacks the secret is answered `403` and closed.
+//!
+//! ## Cost
+//!
+//! Every relayed connection is a second hop: one extra loopback TCP connection and one more
+//! coroutine, the preface round trip before the first request, and each byte copied twice
+//! through user space in both directions. For
+//! [`super::HttpServer::start_with_websockets`] that is all plain HTTP traffic on the public
+//! port, not only upgrades, so prefer [`super::HttpServer::start`] when there are no
+//! WebSocket routes and `Expect: 100-continue` is off. Serving upgrades without the relay
+//! needs `may_minihttp` to hand a connection over after `101`.
+//!
+//! The HTTP server sees the relay's loopback connection, so the relay names the client in its
+//! preface ([`PEER_HEADER`]). The server only reads it from the preface that carries the
+//! secret and hands it to handlers as [`crate::dispatcher::HandlerRequest::peer_addr`], from
+//! which [`crate::dispatcher::HandlerRequest::client_ip`] resolves. Unix socket clients have
+//! no address, so their requests have none. Do not add `127.0.0.1` to the trusted proxies:
+//! the relay forwards whatever `X-Forwarded-*` headers the client wrote.
+//!
+//! ## `HEAD` responses
+//!
+//! `may_minihttp` frames every response with the length of the body it writes, so a `HEAD`
+//! response, written without one, carries `Content-Length: 0`. On relayed connections the
+//! server names the `GET` body's length in [`HEAD_LENGTH_HEADER`] and the relay moves it into
+//! `Content-Length`; connections served by [`super::HttpServer::start`] keep the `0`.
+//!
+//! ## Timeouts
+//!
+//! Relayed connections are the only ones whose sockets BRRTRouter owns, so they are the only
+//! ones on which [`super::ConnectionConfig::idle_timeout`] is enforced: a connection whose
+//! last transfer was a response (or that has sent nothing yet) and that stays silent in both
+//! directions for that long is closed, and so is one whose client accepts no response bytes
+//! for that long. A slow handler does not count as idle, because the request was the last
+//! transfer.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use may::net::TcpStream;
+use sha2::{Digest, Sha256};
+
+use super::limits::DEFAULT_MAX_HEADER_BYTES;
+use super::{AppService, HttpServer, ServerHandle};
+
+/// Request header carrying the relay secret in the preface.
+pub(crate) const RELAY_HEADER: &str = "x-brrtr-relay";
+
+/// Preface header carrying the relayed client's IP address, when it has one.
+pub(crate) const PEER_HEADER: &str = "x-brrtr-peer";
+
+/// Response header naming the `Content-Length` of a `HEAD` response, which the relay moves
+/// into `Content-Length` (see [`HeadFraming`]).
+pub(crate) const HEAD_LENGTH_HEADER: &str = "x-brrtr-head-length";
+
+/// Longest response head [`HeadFraming`] parses; the rest of a connection with a longer one
+/// is passed through unchanged.
+const MAX_RESPONSE_HEAD_BYTES: usize = 64 * 1024;
+
+/// A fresh random relay secret.
+pub(crate) fn new_token() -> Arc<str> {
+    // Two ULIDs carry 160 random bits.
+    Arc::from(format!("{}{}", ulid::Ulid::new(), ulid::Ulid::new()))
+}
+
+/// Whether `presented` is the relay secret, compared without an early exit.
+pub(crate) fn token_matches(presented: &[u8], token: &str) -> bool {
+    Sha256::digest(presented) == Sha256::digest(token.as_bytes())
+}
+
+/// The HTTP server a listener relays to.
+#[derive(Clone)]
+pub(crate) struct Upstream {
+    addr: SocketAddr,
+    token: Arc<str>,
+    idle_timeout: Option<Duration>,
+}
+
+impl Upstream {
+    /// The server at `addr`, which expects the relay secret `token`; relayed connections are
+    /// closed after `idle_timeout` without traffic (never when it is zero).
+    pub(crate) fn new(addr: SocketAddr, token: Arc<str>, idle_timeout: Duration) -> Self {
+        Self {
+            addr,
+            token,
+            idle_timeout: Some(idle_timeout).filter(|t| !t.is_zero()),
+        }
+    }
+
+    /// How long a relayed connection may stay silent, if limited.
+    pub(crate) fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+}
+
+/// Ports tried by [`start_upstream`] before giving up.
+const UPSTREAM_ATTEMPTS: usize = 8;
+
+/// Serve `service` on an ephemeral `127.0.0.1` port behind a fresh relay secret, once it
+/// accepts connections.
+pub(crate) fn start_upstream(service: AppService) -> io::Result<(ServerHandle, Upstream)> {
+    start_upstream_on(service, || {
+        std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()
+    })
+}
+
+/// [`start_upstream`] on ports chosen by `pick`.
+///
+/// `may_minihttp` binds an address rather than taking a listener, so a free port is picked
+/// first and another process may bind it before the server does. Binding is exclusive, so
+/// the server then fails with `AddrInUse` and the next port is tried; the secret is only
+/// ever sent to a port the server has bound.
+fn start_upstream_on(
+    mut service: AppService,
+    mut pick: impl FnMut() -> io::Result<SocketAddr>,
+) -> io::Result<(ServerHandle, Upstream)> {
+    let token = new_token();
+    service.relay_token = Some(Arc::clone(&token));
+    let idle_timeout = service.connection.idle_timeout;
+    for _ in 0..UPSTREAM_ATTEMPTS {
+        let addr = pick()?;
+        match HttpServer(service.clone()).start(addr) {
+            Ok(server) => {
+                if let Err(e) = server.wait_ready() {
+                    server.stop();
+                    return Err(e);
+                }
+                return Ok((server, Upstream::new(addr, token, idle_timeout)));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AddrInUse,
+        "no free loopback port for the relayed HTTP server",
+    ))
+}
+
+/// Open a connection to the HTTP server at `upstream` and send the relay preface, naming
+/// `peer` as the client.
+pub(crate) fn connect(
+    upstream: SocketAddr,
+    token: &str,
+    peer: Option<IpAddr>,
+) -> io::Result<TcpStream> {
+    let mut server = TcpStream::connect(upstream)?;
+    server.set_nodelay(true)?;
+    let mut preface =
+        format!("OPTIONS * HTTP/1.1\r\nHost: {upstream}\r\n{RELAY_HEADER}: {token}\r\n");
+    if let Some(peer) = peer {
+        preface.push_str(&format!("{PEER_HEADER}: {peer}\r\n"));
+    }
+    preface.push_str("\r\n");
+    server.write_all(preface.as_bytes())?;
+    // Nothing else has been sent yet, so everything read belongs to the bodiless answer.
+    let mut head = Vec::with_capacity(256);
+    let mut chunk = [0u8; 512];
+    while !head.ends_with(b"\r\n\r\n") {
+        let n = server.read(&mut chunk)?;
+        if n == 0 || head.len() + n > DEFAULT_MAX_HEADER_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "HTTP server closed or garbled the relay preface",
+            ));
+        }
+        head.extend_from_slice(&chunk[..n]);
+    }
+    if !head.starts_with(b"HTTP/1.1 204") {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "HTTP server rejected the relay preface",
+        ));
+    }
+    Ok(server)
+}
+
+/// The client side of a relayed connection.
+pub(crate) trait ClientStream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+    fn shutdown(&self, how: Shutdown) -> io::Result<()>;
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl ClientStream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        TcpStream::shutdown(self, how)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl ClientStream for may::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        may::os::unix::net::UnixStream::try_clone(self)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        may::os::unix::net::UnixStream::shutdown(self, how)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        may::os::unix::net::UnixStream::set_read_timeout(self, timeout)
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        may::os::unix::net::UnixStream::set_write_timeout(self, timeout)
+    }
+}
+
+/// When bytes last crossed a relayed connection, shared by its two copy halves.
+struct Activity {
+    start: Instant,
+    /// Milliseconds after `start` of the last transfer.
+    last_ms: AtomicU64,
+    /// Whether the last transfer was a response, i.e. the server is waiting for a request.
+    awaiting_request: AtomicBool,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_ms: AtomicU64::new(0),
+            awaiting_request: AtomicBool::new(true),
+        }
+    }
+
+    fn record(&self, response: bool) {
+        let now = u64::try_from(self.start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        self.last_ms.store(now, Ordering::Relaxed);
+        self.awaiting_request.store(response, Ordering::Relaxed);
+    }
+
+    fn idle_for(&self, timeout: Duration) -> bool {
+        let last = Duration::from_millis(self.last_ms.load(Ordering::Relaxed));
+        self.awaiting_request.load(Ordering::Relaxed)
+            && self.start.elapsed().saturating_sub(last) >= timeout
+    }
+}
+
+/// Copy `from` into `to` until `from` closes or the connection has been idle for `timeout`.
+///
+/// `from` must have `timeout` as its read timeout, so silence is noticed.
+fn pump(
+    mut from: impl Read,
+    mut to: impl Write,
+    activity: &Activity,
+    response: bool,
+    timeout: Option<Duration>,
+) -> io::Result<()> {
+    let mut buf = [0u8; 8192];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                to.write_all(&buf[..n])?;
+                activity.record(response);
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                if timeout.is_some_and(|t| activity.idle_for(t)) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "relayed connection idle",
+                    ));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Writes the HTTP server's responses to the client, framing `HEAD` responses.
+///
+/// `may_minihttp` takes `Content-Length` from the body it writes, so a `HEAD` response,
+/// which has none, would go out as `Content-Length: 0`. The server names the length the
+/// `GET` response would have in [`HEAD_LENGTH_HEADER`]; this writer puts it in the
+/// `Content-Length` of that head and drops the header. Everything else passes through
+/// unchanged, response bodies being skipped by their `Content-Length`.
+struct HeadFraming<W> {
+    inner: W,
+    /// The response head read so far.
+    head: Vec<u8>,
+    /// Bytes of the current response body not written yet.
+    body_left: usize,
+    /// A head could not be framed; copy the rest of the connection as is.
+    passthrough: bool,
+}
+
+impl<W: Write> HeadFraming<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            head: Vec::new(),
+            body_left: 0,
+            passthrough: false,
+        }
+    }
+
+    /// Write the complete head in `self.head`, reframed if it names a `HEAD` length.
+    fn write_head(&mut self) -> io::Result<()> {
+        let head = std::mem::take(&mut self.head);
+        let text = String::from_utf8_lossy(&head);
+        let mut content_length = None;
+        let mut head_length = None;
+        for line in text.split("\r\n") {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                } else if name.eq_ignore_ascii_case(HEAD_LENGTH_HEADER) {
+                    head_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+        let Some(content_length) = content_length else {
+            self.passthrough = true;
+            return self.inner.write_all(&head);
+        };
+        self.body_left = content_length;
+        match head_length {
+            // Only a bodyless response can be a `HEAD` response.
+            Some(length) if content_length == 0 => {
+                let mut framed = String::with_capacity(text.len());
+                for line in text.trim_end_matches("\r\n").split("\r\n") {
+                    match line.split_once(':') {
+                        Some((name, _)) if name.eq_ignore_ascii_case(HEAD_LENGTH_HEADER) => {
+                            continue
+                        }
+                        Some((name, _)) if name.eq_ignore_ascii_case("content-length") => {
+                            framed.push_str(&format!("{name}: {length}"));
+                        }
+                        _ => framed.push_str(line),
+                    }
+                    framed.push_str("\r\n");
+                }
+                framed.push_str("\r\n");
+                self.inner.write_all(framed.as_bytes())
+            }
+            _ => self.inner.write_all(&head),
+        }
+    }
+}
+
+impl<W: Write> Write for HeadFraming<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.passthrough {
+                self.inner.write_all(rest)?;
+                break;
+            }
+            if self.body_left > 0 {
+                let n = self.body_left.min(rest.len());
+                self.inner.write_all(&rest[..n])?;
+                self.body_left -= n;
+                rest = &rest[n..];
+                continue;
+            }
+            let mut taken = 0;
+            while taken < rest.len() && !self.head.ends_with(b"\r\n\r\n") {
+                self.head.push(rest[taken]);
+                taken += 1;
+            }
+            rest = &rest[taken..];
+            if self.head.ends_with(b"\r\n\r\n") {
+                self.write_head()?;
+            } else if self.head.len() > MAX_RESPONSE_HEAD_BYTES {
+                self.passthrough = true;
+                let head = std::mem::take(&mut self.head);
+                self.inner.write_all(&head)?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Forward `prefix` (already read from the client), then copy bytes both ways between the
+/// client, whose address is `peer`, and `upstream` until either side closes or the
+/// connection goes idle.
+pub(crate) fn relay<S: ClientStream>(
+    mut client: S,
+    upstream: &Upstream,
+    prefix: &[u8],
+    peer: Option<IpAddr>,
+) -> io::Result<()> {
+    let timeout = upstream.idle_timeout;
+    let mut server = connect(upstream.addr, &upstream.token, peer)?;
+    server.write_all(prefix)?;
+    let activity = Arc::new(Activity::new());
+    if !prefix.is_empty() {
+        activity.record(false);
+    }
+    let mut client_read = client.try_clone()?;
+    client_read.set_read_timeout(timeout)?;
+    client.set_write_timeout(timeout)?;
+    server.set_read_timeout(timeout)?;
+    let mut server_write = server.try_clone()?;
+    let upload_activity = Arc::clone(&activity);
+    // SAFETY: `may::coroutine::Builder::spawn` is unsafe because the coroutine must not block
+    // the worker thread or access thread-local storage; the copy only performs may-aware
+    // socket I/O.
+    let upload = unsafe {
+        may::coroutine::Builder::new().spawn(move || {
+            let _ = pump(
+                &mut client_read,
+                &mut server_write,
+                &upload_activity,
+                false,
+                timeout,
+            );
+            let _ = server_write.shutdown(Shutdown::Write);
+        })
+    }?;
+
+    let result = pump(
+        &mut server,
+        HeadFraming::new(&mut client),
+        &activity,
+        true,
+        timeout,
+    );
+    // The server closed (e.g. `Connection: close`) or the connection went idle; unblock the
+    // upload half as well.
+    let _ = client.shutdown(Shutdown::Both);
+    let _ = server.shutdown(Shutdown::Both);
+    let _ = upload.join();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatcher::Dispatcher;
+    use crate::router::Router;
+
+    /// Read until the end of a response head.
+    fn response_head(stream: &mut impl Read) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).unwrap();
+            assert!(n > 0, "closed before a response");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        String::from_utf8_lossy(&buf).to_string()
+    }
+
+    #[test]
+    fn relay_port_serves_only_connections_with_the_secret() {
+        may::config().set_stack_size(0x8000);
+        let token = new_token();
+        let mut service = AppService::builder()
+            .router(Router::new(Vec::new()))
+            .dispatcher(Dispatcher::new())
+            .spec_path("examples/openapi.yaml")
+            .build()
+            .unwrap();
+        service.relay_token = Some(Arc::clone(&token));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let handle = HttpServer(service).start(addr).unwrap();
+        handle.wait_ready().unwrap();
+
+        // Another local process connecting straight to the port is turned away.
+        let mut direct = std::net::TcpStream::connect(addr).unwrap();
+        direct
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        direct
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let head = response_head(&mut direct);
+        assert!(head.starts_with("HTTP/1.1 403"), "{head}");
+        assert!(head.contains("Connection: close"), "{head}");
+
+        assert_eq!(
+            connect(addr, "guessed", None).err().map(|e| e.kind()),
+            Some(io::ErrorKind::PermissionDenied)
+        );
+
+        let mut relayed = connect(addr, &token, None).unwrap();
+        relayed
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        relayed
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let head = response_head(&mut relayed);
+        assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+        handle.stop();
+    }
+
+    #[test]
+    fn upstream_skips_a_port_taken_before_the_server_binds_it() {
+        may::config().set_stack_size(0x8000);
+        let service = AppService::builder()
+            .router(Router::new(Vec::new()))
+            .dispatcher(Dispatcher::new())
+            .spec_path("examples/openapi.yaml")
+            .build()
+            .unwrap();
+        // Another process bound the first picked port after it was found free.
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut picks = vec![
+            std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap(),
+            taken.local_addr().unwrap(),
+        ];
+        let (handle, upstream) = start_upstream_on(service, || Ok(picks.pop().unwrap())).unwrap();
+        assert!(picks.is_empty());
+        assert_ne!(upstream.addr, taken.local_addr().unwrap());
+        // Ready on return: the preface is answered without waiting.
+        connect(upstream.addr, &upstream.token, None).unwrap();
+        handle.stop();
+    }
+
+    #[test]
+    fn tokens_are_random_and_compared_exactly() {
+        let token = new_token();
+        assert_ne!(token, new_token());
+        assert!(token_matches(token.as_bytes(), &token));
+        assert!(!token_matches(&token.as_bytes()[1..], &token));
+        assert!(!token_matches(b"", &token));
+    }
+
+    /// Feed `chunks` through [`HeadFraming`] and return what reaches the client.
+    fn framed(chunks: &[&[u8]]) -> String {
+        let mut out = Vec::new();
+        let mut writer = HeadFraming::new(&mut out);
+        for chunk in chunks {
+            writer.write_all(chunk).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn head_responses_get_the_named_content_length() {
+        let head = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nx-brrtr-head-length: 42\r\n\
+                    Content-Type: application/json\r\n\r\n";
+        let get = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]";
+        let expected = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: 42\r\nContent-Type: application/json\r\n\r\n{get}"
+        );
+        let whole = format!("{head}{get}");
+        assert_eq!(framed(&[whole.as_bytes()]), expected);
+        // Split anywhere, including inside the head terminator and the body.
+        for at in 1..whole.len() {
+            let (a, b) = whole.as_bytes().split_at(at);
+            assert_eq!(framed(&[a, b]), expected, "split at {at}");
+        }
+    }
+
+    #[test]
+    fn bodies_are_not_parsed_as_heads() {
+        // A body that looks like a head carrying the length header is passed through.
+        let body = "x-brrtr-head-length: 9\r\n\r\n";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        assert_eq!(framed(&[response.as_bytes()]), response);
+        // So is a header naming a length on a response with a body.
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nx-brrtr-head-length: 9\r\n\r\n{}";
+        assert_eq!(framed(&[response.as_bytes()]), response);
+    }
+}
@@ -10,14 +10,18 @@
 #![deny(clippy::format_push_string)]
 #![deny(clippy::unnecessary_to_owned)]
 
-use crate::dispatcher::HeaderVec;
+use super::json::{DefaultJsonCodec, JsonCodec};
+use super::response::ProblemDetails;
+use crate::dispatcher::{HeaderLookup, HeaderVec};
 use crate::router::ParamVec;
-use crate::spec::ParameterStyle;
+use crate::spec::{ParameterLocation, ParameterMeta, ParameterStyle};
+use bytes::Bytes;
 use http::Method;
 use may_minihttp::Request;
 use serde_json::{Map, Number, Value};
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::debug;
 use url::form_urlencoded::parse as parse_form_urlencoded;
 
@@ -46,16 +50,15 @@ pub struct ParsedRequest {
     /// Parsed request body as JSON: `application/json`, `application/x-www-form-urlencoded`,
     /// or a placeholder object for `multipart/form-data` (see `parse_request_body`).
     pub body: Option<serde_json::Value>,
+    /// Request body exactly as received, for signature verification; `None` when empty
+    pub raw_body: Option<Bytes>,
 }
 
 impl ParsedRequest {
     /// Get a header by name (case-insensitive)
     #[inline]
     pub fn get_header(&self, name: &str) -> Option<&str> {
-        self.headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case(name))
-            .map(|(_, v)| v.as_str())
+        self.headers.get(name)
     }
 
     /// Get a cookie by name
@@ -79,25 +82,21 @@ impl ParsedRequest {
 
 /// Extract cookies from headers, returning a stack-allocated SmallVec
 pub fn parse_cookies(headers: &HeaderVec) -> HeaderVec {
-    // Find cookie header using linear search (efficient for small collections)
-    let cookie_value = headers
-        .iter()
-        .find(|(k, _)| k.eq_ignore_ascii_case("cookie"))
-        .map(|(_, v)| v.as_str());
-
-    match cookie_value {
-        Some(c) => c
-            .split(';')
-            .filter_map(|pair| {
-                let mut parts = pair.trim().splitn(2, '=');
-                let name = parts.next()?.trim();
-                let value = parts.next().unwrap_or("").trim().to_string();
-                // JSF P2: Use Arc::from for cookie names (O(1) clone)
-                Some((Arc::from(name), value))
-            })
-            .collect(),
-        None => HeaderVec::new(),
-    }
+    // Clients may split cookies over several `Cookie` lines (e.g. HTTP/2 → HTTP/1.1 proxies)
+    headers
+        .get_all("cookie")
+        .flat_map(|c| c.split(';'))
+        .filter_map(|pair| {
+            let mut parts = pair.trim().splitn(2, '=');
+            let name = parts.next()?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = parts.next().unwrap_or("").trim().to_string();
+            // JSF P2: Use Arc::from for cookie names (O(1) clone)
+            Some((Arc::from(name), value))
+        })
+        .collect()
 }
 
 /// Parse query string parameters from a URL path
@@ -121,13 +120,230 @@ pub fn parse_query_params(path: &str) -> ParamVec {
         // JSF: Use Arc::from for param names (O(1) clone in hot path)
         // Values remain String as they're per-request data
         url::form_urlencoded::parse(query_str.as_bytes())
-            .map(|(k, v)| (Arc::from(k.as_ref()), v.to_string()))
+            .map(|(k, v)| (Arc::from(k.as_ref()), v.into_owned()))
             .collect()
     } else {
         ParamVec::new()
     }
 }
 
+/// How repeated query keys (`?status=open&status=closed`) are canonicalized
+///
+/// Configured with `query.duplicates` in `config.yaml` (default [`Self::First`]); see
+/// [`Self::for_param`] for when the parameter's own `explode` semantics take precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateQueryPolicy {
+    /// Keep the first occurrence
+    #[default]
+    First,
+    /// Keep the last occurrence
+    Last,
+    /// Keep every occurrence; the parameter's value is the array of all of them, validated
+    /// against the parameter schema (so a scalar schema rejects it)
+    Array,
+}
+
+impl DuplicateQueryPolicy {
+    /// Policy applied to `param` when `configured` is the service-wide default
+    ///
+    /// An exploded array parameter (`explode: true`, the default for `form`-style query
+    /// parameters) sends one key per item, so it always collects [`Self::Array`]. Every other
+    /// parameter, including arrays declared with `explode: false`, uses `configured`.
+    #[must_use]
+    pub fn for_param(param: &ParameterMeta, configured: Self) -> Self {
+        let explodes = param
+            .explode
+            .unwrap_or(matches!(param.style, None | Some(ParameterStyle::Form)));
+        if is_array_param(param) && explodes {
+            Self::Array
+        } else {
+            configured
+        }
+    }
+}
+
+fn is_array_param(param: &ParameterMeta) -> bool {
+    param
+        .schema
+        .as_ref()
+        .and_then(|s| s.get("type"))
+        .and_then(Value::as_str)
+        == Some("array")
+}
+
+/// Reduce repeated keys of the declared query parameters in `query` to what their
+/// [`DuplicateQueryPolicy::for_param`] keeps
+///
+/// Undeclared keys are left as sent. With `strict`, repeated scalar parameters are left
+/// untouched as well, so that parameter validation rejects them with `400`.
+pub fn canonicalize_query_params(
+    params: &[ParameterMeta],
+    query: &mut ParamVec,
+    configured: DuplicateQueryPolicy,
+    strict: bool,
+) {
+    for param in params {
+        if param.location != ParameterLocation::Query {
+            continue;
+        }
+        let name = param.name.as_str();
+        let count = query.iter().filter(|(k, _)| k.as_ref() == name).count();
+        if count < 2 || (strict && !is_array_param(param)) {
+            continue;
+        }
+        let keep = match DuplicateQueryPolicy::for_param(param, configured) {
+            DuplicateQueryPolicy::First => 1,
+            DuplicateQueryPolicy::Last => count,
+            DuplicateQueryPolicy::Array => continue,
+        };
+        let mut seen = 0;
+        query.retain(|(k, _)| {
+            if k.as_ref() != name {
+                return true;
+            }
+            seen += 1;
+            seen == keep
+        });
+    }
+}
+
+/// Fill in the schema `default` of each optional query and header parameter the request
+/// omits
+///
+/// Defaults go in as raw values, so handlers and parameter validation see them exactly as
+/// if the client had sent them: `limit: {default: 20}` reads as `?limit=20`. An array
+/// default becomes one occurrence per item for an exploded query parameter, otherwise one
+/// value joined by its style's delimiter. Required parameters get no default (a missing
+/// one is still a `400`), and object defaults are not applied.
+pub fn apply_param_defaults(
+    params: &[ParameterMeta],
+    query: &mut ParamVec,
+    headers: &mut HeaderVec,
+) {
+    for param in params {
+        if param.required {
+            continue;
+        }
+        let Some(default) = param.schema.as_ref().and_then(|s| s.get("default")) else {
+            continue;
+        };
+        match param.location {
+            ParameterLocation::Query => {
+                if query.iter().any(|(k, _)| k.as_ref() == param.name) {
+                    continue;
+                }
+                let explodes = param
+                    .explode
+                    .unwrap_or(matches!(param.style, None | Some(ParameterStyle::Form)));
+                match default {
+                    Value::Array(items) if explodes => {
+                        for item in items {
+                            if let Some(raw) = raw_param_value(item) {
+                                query.push((Arc::from(param.name.as_str()), raw));
+                            }
+                        }
+                    }
+                    _ => {
+                        let delimiter = match param.style {
+                            Some(ParameterStyle::SpaceDelimited) => " ",
+                            Some(ParameterStyle::PipeDelimited) => "|",
+                            _ => ",",
+                        };
+                        if let Some(raw) = raw_default(default, delimiter) {
+                            query.push((Arc::from(param.name.as_str()), raw));
+                        }
+                    }
+                }
+            }
+            ParameterLocation::Header => {
+                if headers.get(&param.name).is_some() {
+                    continue;
+                }
+                if let Some(raw) = raw_default(default, ",") {
+                    headers.push((Arc::from(param.name.to_ascii_lowercase()), raw));
+                }
+            }
+            ParameterLocation::Path | ParameterLocation::Cookie => {}
+        }
+    }
+}
+
+/// Names of the `deprecated: true` parameters the request sends, in declaration order
+///
+/// Check before [`apply_param_defaults`]: a filled-in default was not sent by the client.
+/// A deprecated path parameter is always sent, as the route matched.
+pub fn deprecated_params_sent<'a>(
+    params: &'a [ParameterMeta],
+    query: &ParamVec,
+    headers: &HeaderVec,
+    cookies: &HeaderVec,
+) -> Vec<&'a str> {
+    params
+        .iter()
+        .filter(|param| param.deprecated)
+        .filter(|param| match param.location {
+            ParameterLocation::Path => true,
+            ParameterLocation::Query => query.iter().any(|(k, _)| k.as_ref() == param.name),
+            ParameterLocation::Header => headers.get(&param.name).is_some(),
+            ParameterLocation::Cookie => cookies.get(&param.name).is_some(),
+        })
+        .map(|param| param.name.as_str())
+        .collect()
+}
+
+/// A scalar default as sent on the wire, or an array's items joined by `delimiter`
+fn raw_default(default: &Value, delimiter: &str) -> Option<String> {
+    match default {
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(raw_param_value)
+                .collect::<Vec<_>>()
+                .join(delimiter),
+        ),
+        scalar => raw_param_value(scalar),
+    }
+}
+
+fn raw_param_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Decode every occurrence of a query parameter into one value
+///
+/// A single occurrence decodes as [`decode_param_value`]. Several occurrences (kept by
+/// [`DuplicateQueryPolicy::Array`]) become one array: for an array schema the items of each
+/// occurrence are concatenated (`?tag=a&tag=b,c` → `["a","b","c"]`), otherwise the decoded
+/// occurrences are collected as they are. Returns `None` when there is no occurrence.
+pub fn decode_query_values<'a>(
+    values: impl IntoIterator<Item = &'a str>,
+    schema: Option<&Value>,
+    style: Option<ParameterStyle>,
+    explode: Option<bool>,
+) -> Option<Value> {
+    let mut values = values.into_iter().peekable();
+    let first = decode_param_value(values.next()?, schema, style, explode);
+    if values.peek().is_none() {
+        return Some(first);
+    }
+    let is_array = schema.and_then(|s| s.get("type")).and_then(Value::as_str) == Some("array");
+    let mut items = Vec::new();
+    let decoded = values.map(|raw| decode_param_value(raw, schema, style, explode));
+    for value in std::iter::once(first).chain(decoded) {
+        match value {
+            Value::Array(parts) if is_array => items.extend(parts),
+            other => items.push(other),
+        }
+    }
+    Some(Value::Array(items))
+}
+
 /// Decode a parameter value according to OpenAPI schema and style
 ///
 /// Converts string parameter values to their appropriate JSON types based on
@@ -155,13 +371,16 @@ pub fn decode_param_value(
     fn convert_primitive(val: &str, schema: Option<&Value>) -> Value {
         if let Some(ty) = schema.and_then(|s| s.get("type").and_then(|v| v.as_str())) {
             match ty {
+                // Integers beyond i64 fall back to a JSON number (exact with the
+                // `arbitrary-precision` feature) rather than failing `type: integer` as a string.
                 "integer" => val
                     .parse::<i64>()
                     .map(Value::from)
+                    .or_else(|_| serde_json::from_str::<serde_json::Number>(val).map(Value::Number))
                     .unwrap_or_else(|_| Value::String(val.to_string())),
-                "number" => val
-                    .parse::<f64>()
-                    .map(Value::from)
+                "number" => serde_json::from_str::<serde_json::Number>(val)
+                    .map(Value::Number)
+                    .or_else(|_| val.parse::<f64>().map(Value::from))
                     .unwrap_or_else(|_| Value::String(val.to_string())),
                 "boolean" => val
                     .parse::<bool>()
@@ -253,11 +472,11 @@ fn form_urlencoded_body_to_json(raw: &[u8]) -> Value {
 ///
 /// Supports `application/json`, `application/x-www-form-urlencoded`, and a minimal
 /// `multipart/form-data` placeholder so `request_body_required` routes receive `Some(body)`.
-fn parse_request_body(raw: &[u8], content_type: &str) -> Option<Value> {
+pub(crate) fn parse_request_body(raw: &[u8], content_type: &str) -> Option<Value> {
     let ct = primary_content_type(content_type);
     let ct_lower = ct.to_ascii_lowercase();
     if ct_lower == "application/json" || ct_lower.ends_with("+json") {
-        return serde_json::from_slice(raw).ok();
+        return DefaultJsonCodec::parse(raw);
     }
     if ct_lower == "application/x-www-form-urlencoded" {
         return Some(form_urlencoded_body_to_json(raw));
@@ -275,7 +494,190 @@ fn parse_request_body(raw: &[u8], content_type: &str) -> Option<Value> {
     if ct_lower == "multipart/form-data" {
         return Some(Value::Object(Map::new()));
     }
-    serde_json::from_slice(raw).ok()
+    DefaultJsonCodec::parse(raw)
+}
+
+/// Longest a request body may take to arrive before [`parse_request`] gives up on it
+pub const DEFAULT_BODY_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Deepest nesting of arrays and objects [`parse_request`] accepts in a JSON body
+pub const DEFAULT_MAX_JSON_DEPTH: usize = 64;
+
+/// Whether `parse_request_body` reads a body of `content_type` as JSON
+fn parses_as_json(content_type: &str) -> bool {
+    let ct = primary_content_type(content_type);
+    !ct.eq_ignore_ascii_case("application/x-www-form-urlencoded")
+        && !ct.eq_ignore_ascii_case("multipart/form-data")
+}
+
+/// Whether the JSON text `raw` nests arrays and objects more than `max_depth` deep
+///
+/// One pass over the bytes, skipping string contents, so a hostile body is measured without
+/// recursing into it. Malformed text is measured as far as it goes; the parser rejects it.
+fn json_depth_exceeds(raw: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in raw {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Reject a body read as JSON that nests deeper than `max_depth`, before it is parsed
+pub(crate) fn check_json_depth(
+    raw: &[u8],
+    content_type: &str,
+    max_depth: usize,
+) -> Result<(), RequestParseError> {
+    if parses_as_json(content_type) && json_depth_exceeds(raw, max_depth) {
+        return Err(RequestParseError::BodyTooDeep { max_depth });
+    }
+    Ok(())
+}
+
+/// Why [`parse_request`] rejected a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestParseError {
+    /// The request method could not be parsed
+    InvalidMethod(String),
+    /// The connection closed before the body was complete
+    IncompleteBody {
+        /// `Content-Length`, when the request declared one
+        expected: Option<usize>,
+        /// Bytes received before the connection closed
+        received: usize,
+    },
+    /// The body was not complete within the read timeout
+    BodyTimeout {
+        /// Bytes received before the timeout
+        received: usize,
+    },
+    /// The JSON body nests arrays and objects deeper than allowed
+    BodyTooDeep {
+        /// Deepest nesting allowed
+        max_depth: usize,
+    },
+}
+
+impl RequestParseError {
+    /// Response for the client: `408` for a timed-out body, `400` otherwise
+    #[must_use]
+    pub fn problem(&self) -> ProblemDetails {
+        match self {
+            Self::BodyTimeout { .. } => ProblemDetails::new(408).detail(self.to_string()),
+            _ => ProblemDetails::new(400).detail(self.to_string()),
+        }
+    }
+
+    /// Whether the body was cut short, leaving the connection unusable for another request
+    #[must_use]
+    pub fn is_body_error(&self) -> bool {
+        matches!(self, Self::IncompleteBody { .. } | Self::BodyTimeout { .. })
+    }
+}
+
+impl std::fmt::Display for RequestParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMethod(method) => write!(f, "Invalid HTTP method: {method}"),
+            Self::IncompleteBody {
+                expected: Some(expected),
+                received,
+            } => write!(
+                f,
+                "Incomplete request body: expected {expected} bytes, received {received}"
+            ),
+            Self::IncompleteBody {
+                expected: None,
+                received,
+            } => write!(
+                f,
+                "Incomplete request body: connection closed after {received} bytes"
+            ),
+            Self::BodyTimeout { received } => write!(
+                f,
+                "Request body not received in time ({received} bytes received)"
+            ),
+            Self::BodyTooDeep { max_depth } => {
+                write!(f, "Request body nests JSON deeper than {max_depth} levels")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RequestParseError {}
+
+/// Read a request body of `content_length` bytes (or until the end of the stream)
+///
+/// Fails rather than returning a short body: a stream that ends, or a read that fails,
+/// before `content_length` bytes is [`RequestParseError::IncompleteBody`]; a read that times
+/// out, or a body still incomplete once `timeout` has passed, is
+/// [`RequestParseError::BodyTimeout`].
+fn read_body<R: Read>(
+    mut reader: R,
+    content_length: Option<usize>,
+    timeout: Duration,
+) -> Result<Vec<u8>, RequestParseError> {
+    let start = Instant::now();
+    // Reserve up front only what a small body needs; a large declared length grows as read.
+    let mut raw = Vec::with_capacity(content_length.unwrap_or(0).min(64 * 1024));
+    let mut chunk = [0u8; 8 * 1024];
+    loop {
+        if content_length.is_some_and(|len| raw.len() >= len) {
+            break;
+        }
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => raw.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                return Err(RequestParseError::BodyTimeout {
+                    received: raw.len(),
+                });
+            }
+            // Without a length there is nothing to be short of until a byte has arrived.
+            Err(_) if content_length.is_none() && raw.is_empty() => break,
+            Err(e) => {
+                debug!(error = %e, received = raw.len(), "Request body read failed");
+                return Err(RequestParseError::IncompleteBody {
+                    expected: content_length,
+                    received: raw.len(),
+                });
+            }
+        }
+        if start.elapsed() >= timeout && content_length.is_some_and(|len| raw.len() < len) {
+            return Err(RequestParseError::BodyTimeout {
+                received: raw.len(),
+            });
+        }
+    }
+    match content_length {
+        Some(expected) if raw.len() < expected => Err(RequestParseError::IncompleteBody {
+            expected: Some(expected),
+            received: raw.len(),
+        }),
+        _ => Ok(raw),
+    }
 }
 
 /// Parse an incoming HTTP request into a ParsedRequest
@@ -294,124 +696,179 @@ fn parse_request_body(raw: &[u8], content_type: &str) -> Option<Value> {
 /// # JSF Compliance
 ///
 /// Uses SmallVec for headers, cookies, and query params to avoid heap
-/// allocation in the common case.
+/// allocation in the common case. The request line and headers are parsed by
+/// [`parse_request_head`].
 ///
 /// # Returns
 ///
-/// Returns `Ok(ParsedRequest)` if the request is valid, or `Err(invalid_method_string)`
-/// if the HTTP method is invalid and cannot be parsed.
-pub fn parse_request(req: Request) -> Result<ParsedRequest, String> {
+/// Returns `Ok(ParsedRequest)` if the request is valid, or a [`RequestParseError`] if the
+/// HTTP method cannot be parsed, the body is not received in full within
+/// [`DEFAULT_BODY_READ_TIMEOUT`] (see [`parse_request_with_timeout`]) or a JSON body nests
+/// deeper than [`DEFAULT_MAX_JSON_DEPTH`].
+pub fn parse_request(req: Request) -> Result<ParsedRequest, RequestParseError> {
+    parse_request_with_timeout(req, DEFAULT_BODY_READ_TIMEOUT)
+}
+
+/// [`parse_request`] with its own limit on how long the body may take to arrive
+///
+/// A body shorter than its `Content-Length` (the client closed the connection early) is
+/// [`RequestParseError::IncompleteBody`] and is never parsed. A body still incomplete after
+/// `body_timeout`, or whose read fails with a timeout, is [`RequestParseError::BodyTimeout`].
+/// The deadline is checked each time a read returns, so a client trickling bytes is cut off;
+/// a read that blocks outright is bounded by the connection's own read timeout.
+pub fn parse_request_with_timeout(
+    req: Request,
+    body_timeout: Duration,
+) -> Result<ParsedRequest, RequestParseError> {
+    parse_request_with_limits(req, body_timeout, DEFAULT_MAX_JSON_DEPTH)
+}
+
+/// [`parse_request_with_timeout`] with its own limit on JSON body nesting
+///
+/// A body read as JSON (any content type but form-urlencoded and multipart) whose arrays
+/// and objects nest deeper than `max_json_depth` is [`RequestParseError::BodyTooDeep`],
+/// found by a scan of the raw bytes before the parser recurses into it. The parser's own
+/// limit of 128 levels still applies above that.
+pub fn parse_request_with_limits(
+    req: Request,
+    body_timeout: Duration,
+    max_json_depth: usize,
+) -> Result<ParsedRequest, RequestParseError> {
+    // Everything the handler keeps is copied out of the connection buffer here;
+    // `req.body()` consumes the request.
+    let mut parsed = parse_request_head(
+        req.method(),
+        req.path(),
+        req.headers().iter().map(|h| (h.name, h.value)),
+    )
+    .map_err(RequestParseError::InvalidMethod)?;
+    // R2: HTTP request parsed — per-request, demoted to debug (PRD 2.2).
+    debug!(
+        method = %parsed.method,
+        path = %parsed.path,
+        http_version = ?req.version(),
+        headers_count = parsed.headers.len(),
+        "HTTP request parsed"
+    );
+
+    let headers = &parsed.headers;
+    // R5 & R6: Request body read and parsed (JSON, form-urlencoded, multipart)
+    let parse_start = std::time::Instant::now();
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.trim().parse::<usize>().ok());
+    let (body, raw_body) = {
+        let raw = read_body(req.body(), content_length, body_timeout)?;
+        let size = raw.len();
+        if size > 0 {
+            // Find content-type header using the HeaderVec helper
+            let content_type = headers.get("content-type").unwrap_or("");
+
+            // R5: Request body read — per-request, demoted to debug (PRD 2.2).
+            debug!(
+                content_length = size,
+                content_type = %content_type,
+                body_size_bytes = size,
+                "Request body read"
+            );
+
+            check_json_depth(&raw, content_type, max_json_depth)?;
+            let parsed = parse_request_body(&raw, content_type);
+            let parse_duration_ms = parse_start.elapsed().as_millis() as u64;
+
+            if let Some(ref json) = parsed {
+                debug!(
+                    parse_duration_ms = parse_duration_ms,
+                    body_fields = json.as_object().map(|o| o.len()),
+                    "Request body parsed"
+                );
+            } else {
+                debug!(
+                    parse_duration_ms = parse_duration_ms,
+                    "Request body not recognized or invalid JSON"
+                );
+            }
+
+            // The buffer moves into `Bytes`; nothing is copied.
+            (parsed, Some(Bytes::from(raw)))
+        } else {
+            (None, None)
+        }
+    };
+
+    parsed.body = body;
+    parsed.raw_body = raw_body;
+    Ok(parsed)
+}
+
+/// Parse the request line and headers of a request without its body
+///
+/// `method`, `target` (path plus query string) and the header `(name, value)` pairs borrow
+/// the connection buffer. Only what the handler keeps is copied: the path, header values,
+/// cookie and query pairs. Common header names are interned and need no allocation, and
+/// nothing is formatted for logging unless debug logging is enabled.
+///
+/// `body` and `raw_body` of the result are `None`; [`parse_request`] fills them.
+///
+/// # Errors
+///
+/// Returns `Err(method)` if the HTTP method is invalid.
+pub fn parse_request_head<'a, I>(
+    method: &str,
+    target: &str,
+    headers: I,
+) -> Result<ParsedRequest, String>
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
     // JSF P1: Parse method directly to Method enum (avoids String allocation)
     // Reject invalid HTTP methods instead of defaulting to GET (security fix)
-    let method_str = req.method();
-    let method = method_str.parse().map_err(|_| method_str.to_string())?;
-    let raw_path = req.path().to_string();
-    let path = raw_path.split('?').next().unwrap_or("/").to_string();
-    // JSF P1: Use static strings for HTTP version (avoids format! allocation)
-    // Note: may_minihttp version() returns a Debug-able type, but we can't match on it
-    // So we format once (acceptable as it's not in the hot path per-request allocation)
-    let http_version = format!("{:?}", req.version());
+    let method = method.parse().map_err(|_| method.to_string())?;
+    let path = target.split('?').next().unwrap_or("/").to_string();
 
     // R3: Headers extracted — using SmallVec for stack allocation.
     // PRD Phase 2.1: `intern_header_name` returns a shared `Arc<str>` for the
     // ~24 common HTTP header names (`content-type`, `authorization`, …)
     // without any heap allocation on the hit path, which is >95 % of traffic.
     // Falls back to the previous `Arc::from(lowercased)` on miss.
-    let headers: HeaderVec = req
-        .headers()
-        .iter()
-        .map(|h| {
+    let headers: HeaderVec = headers
+        .into_iter()
+        .map(|(name, value)| {
             (
-                super::header_intern::intern_header_name(h.name.as_bytes()),
-                String::from_utf8_lossy(h.value).to_string(),
+                super::header_intern::intern_header_name(name.as_bytes()),
+                String::from_utf8_lossy(value).into_owned(),
             )
         })
         .collect();
 
-    // JSF P2: Header names are now Arc<str>, so we get references to the Arc
-    let header_names: Vec<&Arc<str>> = headers.iter().map(|(k, _)| k).take(20).collect();
-    let header_count = headers.len();
-    let size_bytes: usize = headers.iter().map(|(k, v)| k.len() + v.len()).sum();
-
-    debug!(
-        header_count = header_count,
-        size_bytes = size_bytes,
-        header_names = ?header_names,
-        "Headers extracted"
-    );
-
     // R7: Cookies extracted
     let cookies = parse_cookies(&headers);
-    // JSF P2: Cookie names are now Arc<str>
-    let cookie_names: Vec<&Arc<str>> = cookies.iter().map(|(k, _)| k).collect();
-    debug!(
-        cookie_count = cookies.len(),
-        cookie_names = ?cookie_names,
-        "Cookies extracted"
-    );
 
     // R4: Query params parsed
-    let query_params = parse_query_params(&raw_path);
-    debug!(
-        param_count = query_params.len(),
-        query_params = ?query_params,
-        "Query params parsed"
-    );
-
-    // R5 & R6: Request body read and parsed (JSON, form-urlencoded, multipart)
-    let parse_start = std::time::Instant::now();
-    let body = {
-        let mut raw: Vec<u8> = Vec::new();
-        if let Ok(size) = req.body().read_to_end(&mut raw) {
-            if size > 0 {
-                // Find content-type header using the HeaderVec helper
-                let content_type = headers
-                    .iter()
-                    .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
-                    .map(|(_, v)| v.as_str())
-                    .unwrap_or("");
-
-                // R5: Request body read — per-request, demoted to debug (PRD 2.2).
-                debug!(
-                    content_length = size,
-                    content_type = %content_type,
-                    body_size_bytes = size,
-                    "Request body read"
-                );
+    let query_params = parse_query_params(target);
 
-                let parsed = parse_request_body(&raw, content_type);
-                let parse_duration_ms = parse_start.elapsed().as_millis() as u64;
-
-                if let Some(ref json) = parsed {
-                    debug!(
-                        parse_duration_ms = parse_duration_ms,
-                        body_fields = json.as_object().map(|o| o.len()),
-                        "Request body parsed"
-                    );
-                } else {
-                    debug!(
-                        parse_duration_ms = parse_duration_ms,
-                        "Request body not recognized or invalid JSON"
-                    );
-                }
-
-                parsed
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    };
-
-    // R2: HTTP request parsed — per-request, demoted to debug (PRD 2.2).
-    debug!(
-        method = %method,
-        path = %path,
-        http_version = %http_version,
-        headers_count = header_count,
-        "HTTP request parsed"
-    );
+    if tracing::enabled!(tracing::Level::DEBUG) {
+        // JSF P2: Header and cookie names are Arc<str>, so we log references to the Arc
+        let header_names: Vec<&Arc<str>> = headers.iter().map(|(k, _)| k).take(20).collect();
+        let size_bytes: usize = headers.iter().map(|(k, v)| k.len() + v.len()).sum();
+        debug!(
+            header_count = headers.len(),
+            size_bytes = size_bytes,
+            header_names = ?header_names,
+            "Headers extracted"
+        );
+        let cookie_names: Vec<&Arc<str>> = cookies.iter().map(|(k, _)| k).collect();
+        debug!(
+            cookie_count = cookies.len(),
+            cookie_names = ?cookie_names,
+            "Cookies extracted"
+        );
+        debug!(
+            param_count = query_params.len(),
+            query_params = ?query_params,
+            "Query params parsed"
+        );
+    }
 
     Ok(ParsedRequest {
         method,
@@ -419,9 +876,11 @@ pub fn parse_request(req: Request) -> Result<ParsedRequest, String> {
         headers,
         cookies,
         query_params,
-        body,
+        body: None,
+        raw_body: None,
     })
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,6 +936,17 @@ mod tests {
         assert_eq!(v["age"], 30);
     }
 
+    #[test]
+    fn test_json_depth_exceeds() {
+        assert!(!json_depth_exceeds(br#"{"a":[1,{"b":2}]}"#, 3));
+        assert!(json_depth_exceeds(br#"{"a":[1,{"b":2}]}"#, 2));
+        // Brackets in strings, escaped quotes included, are not nesting.
+        assert!(!json_depth_exceeds(br#"{"a":"\"[[[{{{"}"#, 1));
+        assert!(json_depth_exceeds(&[b'['; 10_000], 64));
+        assert!(check_json_depth(b"[[[", "application/json", 2).is_err());
+        assert!(check_json_depth(b"[[[", "application/x-www-form-urlencoded", 2).is_ok());
+    }
+
     #[test]
     fn test_parse_request_body_multipart_returns_empty_object() {
         // Multipart returns an empty JSON object placeholder so that the
@@ -508,6 +978,21 @@ mod tests {
         assert_eq!(v, json!([1, 2, 3]));
     }
 
+    #[test]
+    fn test_decode_param_integer_beyond_i64_stays_a_number() {
+        let schema = json!({"type": "integer", "format": "bigint"});
+        let v = decode_param_value("12345678901234567890123", Some(&schema), None, None);
+        assert!(v.is_number(), "{v:?}");
+        #[cfg(feature = "arbitrary-precision")]
+        assert_eq!(v.to_string(), "12345678901234567890123");
+
+        let schema = json!({"type": "number", "format": "decimal"});
+        let v = decode_param_value("1.50", Some(&schema), None, None);
+        assert!(v.is_number(), "{v:?}");
+        #[cfg(feature = "arbitrary-precision")]
+        assert_eq!(v.to_string(), "1.50");
+    }
+
     // Helper function to test HTTP method parsing logic
     // This mirrors the parsing logic in parse_request() to test method validation
     fn test_method_parsing(method_str: &str) -> Result<Method, String> {
@@ -608,4 +1093,192 @@ mod tests {
         // The important thing is that clearly invalid methods are rejected
         // If lowercase is accepted, that's fine - we're testing the rejection of invalid methods
     }
+
+    fn query_param(name: &str, schema: serde_json::Value, explode: Option<bool>) -> ParameterMeta {
+        ParameterMeta {
+            name: name.to_string(),
+            location: ParameterLocation::Query,
+            required: false,
+            schema: Some(schema),
+            style: None,
+            explode,
+            deprecated: false,
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_query_params_per_policy() {
+        let params = [
+            query_param("status", json!({"type": "string"}), None),
+            query_param("tag", json!({"type": "array"}), None),
+        ];
+        let values = |q: &ParamVec, name: &str| -> Vec<String> {
+            q.iter()
+                .filter(|(k, _)| k.as_ref() == name)
+                .map(|(_, v)| v.clone())
+                .collect()
+        };
+        let raw = parse_query_params("/t?status=open&tag=a&status=closed&other=1&other=2&tag=b");
+
+        let mut q = raw.clone();
+        canonicalize_query_params(&params, &mut q, DuplicateQueryPolicy::First, false);
+        assert_eq!(values(&q, "status"), ["open"]);
+        assert_eq!(values(&q, "tag"), ["a", "b"]);
+        assert_eq!(values(&q, "other"), ["1", "2"]);
+
+        let mut q = raw.clone();
+        canonicalize_query_params(&params, &mut q, DuplicateQueryPolicy::Last, false);
+        assert_eq!(values(&q, "status"), ["closed"]);
+
+        for (policy, strict) in [
+            (DuplicateQueryPolicy::Array, false),
+            (DuplicateQueryPolicy::First, true),
+        ] {
+            let mut q = raw.clone();
+            canonicalize_query_params(&params, &mut q, policy, strict);
+            assert_eq!(values(&q, "status"), ["open", "closed"]);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_policy_follows_explode() {
+        let array = json!({"type": "array"});
+        let exploded = query_param("ids", array.clone(), None);
+        let packed = query_param("ids", array, Some(false));
+        let scalar = query_param("id", json!({"type": "integer"}), Some(true));
+        let last = DuplicateQueryPolicy::Last;
+        assert_eq!(
+            DuplicateQueryPolicy::for_param(&exploded, last),
+            DuplicateQueryPolicy::Array
+        );
+        assert_eq!(DuplicateQueryPolicy::for_param(&packed, last), last);
+        assert_eq!(DuplicateQueryPolicy::for_param(&scalar, last), last);
+    }
+
+    #[test]
+    fn test_decode_query_values() {
+        let ints = json!({"type": "array", "items": {"type": "integer"}});
+        assert_eq!(decode_query_values([], Some(&ints), None, None), None);
+        assert_eq!(
+            decode_query_values(["1,2"], Some(&ints), None, None),
+            Some(json!([1, 2]))
+        );
+        assert_eq!(
+            decode_query_values(["1,2", "3"], Some(&ints), None, None),
+            Some(json!([1, 2, 3]))
+        );
+        let int = json!({"type": "integer"});
+        assert_eq!(
+            decode_query_values(["1", "2"], Some(&int), None, None),
+            Some(json!([1, 2]))
+        );
+    }
+
+    /// Yields `chunks` one read at a time, then fails every read with `end`
+    struct ScriptedReader {
+        chunks: Vec<&'static [u8]>,
+        pause: std::time::Duration,
+        end: std::io::ErrorKind,
+    }
+
+    impl Read for ScriptedReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            std::thread::sleep(self.pause);
+            if self.chunks.is_empty() {
+                return Err(self.end.into());
+            }
+            let chunk = self.chunks.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    fn reader(chunks: &[&'static [u8]], end: std::io::ErrorKind) -> ScriptedReader {
+        ScriptedReader {
+            chunks: chunks.to_vec(),
+            pause: std::time::Duration::ZERO,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_read_body_complete() {
+        let timeout = DEFAULT_BODY_READ_TIMEOUT;
+        let body = read_body(
+            reader(&[b"{\"a\":", b"1}"], std::io::ErrorKind::BrokenPipe),
+            Some(7),
+            timeout,
+        );
+        assert_eq!(body.unwrap(), b"{\"a\":1}");
+        // No length and no body: a closed stream is simply the end.
+        let body = read_body(reader(&[], std::io::ErrorKind::BrokenPipe), None, timeout);
+        assert_eq!(body.unwrap(), b"");
+    }
+
+    #[test]
+    fn test_read_body_short_read_is_incomplete() {
+        // Half of a JSON document that would otherwise parse as valid once closed early.
+        let err = read_body(
+            reader(&[b"{\"a\":1}"], std::io::ErrorKind::BrokenPipe),
+            Some(14),
+            DEFAULT_BODY_READ_TIMEOUT,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            RequestParseError::IncompleteBody {
+                expected: Some(14),
+                received: 7
+            }
+        );
+        assert_eq!(err.problem().status, 400);
+        assert_eq!(
+            err.to_string(),
+            "Incomplete request body: expected 14 bytes, received 7"
+        );
+        assert!(err.is_body_error());
+
+        let err = read_body(
+            reader(&[b"abc"], std::io::ErrorKind::UnexpectedEof),
+            Some(10),
+            DEFAULT_BODY_READ_TIMEOUT,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            RequestParseError::IncompleteBody { received: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_read_body_stalled_read_times_out() {
+        // A read that blocks until the socket's read timeout fires.
+        let err = read_body(
+            reader(&[b"ab"], std::io::ErrorKind::TimedOut),
+            Some(10),
+            DEFAULT_BODY_READ_TIMEOUT,
+        )
+        .unwrap_err();
+        assert_eq!(err, RequestParseError::BodyTimeout { received: 2 });
+        assert_eq!(err.problem().status, 408);
+
+        // A client trickling bytes is cut off at the deadline rather than waited on.
+        let start = std::time::Instant::now();
+        let trickle = ScriptedReader {
+            chunks: vec![&b"a"[..]; 1000],
+            pause: std::time::Duration::from_millis(10),
+            end: std::io::ErrorKind::BrokenPipe,
+        };
+        let err = read_body(trickle, Some(1000), std::time::Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(err, RequestParseError::BodyTimeout { received } if received < 1000));
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_invalid_method_error() {
+        let err = RequestParseError::InvalidMethod("G E T".to_string());
+        assert_eq!(err.to_string(), "Invalid HTTP method: G E T");
+        assert_eq!(err.problem().status, 400);
+        assert!(!err.is_body_error());
+    }
 }
@@ -1,6 +1,14 @@
+use super::json::{DefaultJsonCodec, JsonCodec};
 use crate::dispatcher::HeaderVec;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use may_minihttp::Response;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Media type of RFC 9457 (formerly RFC 7807) problem details.
+pub const PROBLEM_JSON: &str = "application/problem+json";
 
 /// Whether an HTTP status permits a response body.
 ///
@@ -22,7 +30,17 @@ fn status_reason(status: u16) -> &'static str {
         401 => "Unauthorized",
         403 => "Forbidden",
         404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Content",
+        426 => "Upgrade Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
         500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        505 => "HTTP Version Not Supported",
         _ => "OK",
     }
 }
@@ -45,6 +63,28 @@ pub fn write_handler_response(
     body: Value,
     is_sse: bool,
     headers: &HeaderVec,
+) {
+    write_handler_response_as(res, status, body, is_sse, headers, false, false);
+}
+
+/// [`write_handler_response`], with JSON bodies indented when `pretty` is set.
+///
+/// The body is serialized before gzip encoding and `Content-Length` is taken from the bytes
+/// written, so both hold for either layout.
+///
+/// With `head` set (a response to `HEAD`) the headers are those a `GET` response would carry
+/// and no body is written: a client reads none, so body bytes would be taken for the start
+/// of the next response on the connection. `may_minihttp` frames the response from the
+/// bytes written, so its `Content-Length` is `0` unless the connection is relayed, where the
+/// relay restores the computed length (see [`super::relay`]).
+pub(crate) fn write_handler_response_as(
+    res: &mut Response,
+    status: u16,
+    body: Value,
+    is_sse: bool,
+    headers: &HeaderVec,
+    pretty: bool,
+    head: bool,
 ) {
     let reason = status_reason(status);
     res.status_code(status as usize, reason);
@@ -55,8 +95,18 @@ pub fn write_handler_response(
     // second casing (`content-type` + `Content-Type`). Nginx treats that as a
     // duplicate header and can return 502 to the browser.
     let mut has_content_type = false;
+    // `Content-Encoding: gzip` (set by `CompressionMiddleware`) is a request to encode the
+    // serialized body; the header is only emitted once encoding succeeds.
+    let mut gzip = false;
     for (k, v) in headers {
-        if k.eq_ignore_ascii_case("content-length") {
+        // The relay frames `HEAD` responses from `HEAD_LENGTH_HEADER`; never take it from a handler.
+        if k.eq_ignore_ascii_case("content-length")
+            || k.eq_ignore_ascii_case(super::relay::HEAD_LENGTH_HEADER)
+        {
+            continue;
+        }
+        if k.eq_ignore_ascii_case("content-encoding") && v.trim().eq_ignore_ascii_case("gzip") {
+            gzip = !is_sse;
             continue;
         }
         if k.eq_ignore_ascii_case("content-type") {
@@ -78,19 +128,32 @@ pub fn write_handler_response(
     if !response_status_allows_body(status) {
         return;
     }
+    if head {
+        if !has_content_type {
+            res.header(if body.is_string() {
+                "Content-Type: text/plain"
+            } else {
+                "Content-Type: application/json"
+            });
+        }
+        if gzip {
+            res.header("Content-Encoding: gzip");
+        }
+        return;
+    }
     match body {
         Value::String(s) => {
             if !has_content_type {
                 res.header("Content-Type: text/plain");
             }
-            res.body_vec(s.into_bytes());
+            write_body(res, s.into_bytes(), gzip);
         }
-        other => match serde_json::to_vec(&other) {
+        other => match json_bytes(&other, pretty) {
             Ok(json_bytes) => {
                 if !has_content_type {
                     res.header("Content-Type: application/json");
                 }
-                res.body_vec(json_bytes);
+                write_body(res, json_bytes, gzip);
             }
             Err(e) => {
                 res.status_code(500, "Internal Server Error");
@@ -103,10 +166,228 @@ pub fn write_handler_response(
     }
 }
 
+/// Serialize `value` compactly, or indented when `pretty` is set.
+fn json_bytes(value: &Value, pretty: bool) -> Result<Vec<u8>, String> {
+    if pretty {
+        DefaultJsonCodec::to_vec_pretty(value)
+    } else {
+        DefaultJsonCodec::to_vec(value)
+    }
+}
+
+/// Write `bytes` as the body, gzip-encoding it when `gzip` is set.
+///
+/// Falls back to the identity encoding (no `Content-Encoding` header) if encoding fails.
+fn write_body(res: &mut Response, bytes: Vec<u8>, gzip: bool) {
+    if gzip {
+        if let Some(encoded) = gzip_encode(&bytes) {
+            res.header("Content-Encoding: gzip");
+            res.body_vec(encoded);
+            return;
+        }
+    }
+    res.body_vec(bytes);
+}
+
+fn gzip_encode(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 2), Compression::fast());
+    encoder
+        .write_all(bytes)
+        .and_then(|()| encoder.finish())
+        .ok()
+}
+
+/// Length of the body [`write_handler_response_as`] writes for this response: the value
+/// serialized the same way, gzip-encoded when `headers` ask for it. `0` for statuses that
+/// carry no body.
+pub(crate) fn handler_body_len(
+    status: u16,
+    body: &Value,
+    is_sse: bool,
+    headers: &HeaderVec,
+    pretty: bool,
+) -> usize {
+    if !response_status_allows_body(status) {
+        return 0;
+    }
+    let gzip = !is_sse
+        && headers.iter().any(|(k, v)| {
+            k.eq_ignore_ascii_case("content-encoding") && v.trim().eq_ignore_ascii_case("gzip")
+        });
+    let bytes = match body {
+        Value::String(s) if !gzip => return s.len(),
+        Value::String(s) => s.as_bytes().to_vec(),
+        other => match json_bytes(other, pretty) {
+            Ok(bytes) if !gzip => return bytes.len(),
+            Ok(bytes) => bytes,
+            Err(e) => return format!("Failed to serialize response: {}", e).len(),
+        },
+    };
+    gzip_encode(&bytes).map_or(bytes.len(), |encoded| encoded.len())
+}
+
+/// One entry of the `errors` extension: which part of the request was invalid and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemFieldError {
+    /// JSON Pointer fragment into the request body (e.g. `#/age`; `#` for the document root)
+    pub pointer: String,
+    /// Human-readable description of the violation
+    pub detail: String,
+    /// JSON Schema keyword the value violated (e.g. `maxItems`, `uniqueItems`), when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+}
+
+/// RFC 9457 problem details, the error envelope for every framework-produced error.
+///
+/// Serialized as `application/problem+json` by [`write_problem`] or via
+/// [`crate::dispatcher::HandlerResponse::problem`]. Typed handlers return the same envelope
+/// by returning `Result<T, ProblemDetails>` (see [`crate::typed::HandlerResponseOutput`]).
+///
+/// Deliberately not `Serialize` — that would make it a plain 200 body for typed handlers;
+/// use [`Self::to_value`] for the JSON object.
+///
+/// ```rust
+/// use brrtrouter::server::{ProblemDetails, ProblemFieldError};
+///
+/// let problem = ProblemDetails::new(422)
+///     .detail("Pet cannot be adopted twice")
+///     .instance("/pets/42/adoption")
+///     .extension("pet_id", 42)
+///     .errors(vec![ProblemFieldError {
+///         pointer: "#/adopter".to_string(),
+///         detail: "already adopted".to_string(),
+///         keyword: None,
+///     }]);
+/// assert_eq!(problem.title, "Unprocessable Content");
+/// assert_eq!(problem.to_value()["errors"][0]["pointer"], "#/adopter");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type (`about:blank` when the status code says it all)
+    pub problem_type: String,
+    /// Short summary of the problem type
+    pub title: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Explanation specific to this occurrence
+    pub detail: Option<String>,
+    /// URI reference identifying this occurrence (typically the request path)
+    pub instance: Option<String>,
+    /// Extension members, serialized alongside the standard members
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// Problem of type `about:blank` titled with the status code's reason phrase.
+    #[must_use]
+    pub fn new(status: u16) -> Self {
+        let title = http::StatusCode::from_u16(status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("Error");
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: title.to_string(),
+            status,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Set the problem type URI.
+    #[must_use]
+    pub fn problem_type(mut self, problem_type: impl Into<String>) -> Self {
+        self.problem_type = problem_type.into();
+        self
+    }
+
+    /// Override the title.
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the occurrence-specific detail.
+    #[must_use]
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the occurrence URI.
+    #[must_use]
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Add an extension member (standard member names are overridden on serialization).
+    #[must_use]
+    pub fn extension(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the `errors` extension listing invalid request fields.
+    #[must_use]
+    pub fn errors(mut self, errors: Vec<ProblemFieldError>) -> Self {
+        self.extensions.insert(
+            "errors".to_string(),
+            serde_json::to_value(errors).unwrap_or_default(),
+        );
+        self
+    }
+
+    /// Serialize to a JSON object; standard members win over same-named extensions.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        let mut map = self.extensions.clone();
+        map.insert("type".to_string(), Value::from(self.problem_type.as_str()));
+        map.insert("title".to_string(), Value::from(self.title.as_str()));
+        map.insert("status".to_string(), Value::from(self.status));
+        if let Some(detail) = &self.detail {
+            map.insert("detail".to_string(), Value::from(detail.as_str()));
+        }
+        if let Some(instance) = &self.instance {
+            map.insert("instance".to_string(), Value::from(instance.as_str()));
+        }
+        Value::Object(map)
+    }
+}
+
+/// Write `problem` as an `application/problem+json` response.
+pub fn write_problem(res: &mut Response, problem: &ProblemDetails) {
+    write_problem_as(res, problem, false, false);
+}
+
+/// [`write_problem`], indented when `pretty` is set; without a body when `head` is set (see
+/// [`write_handler_response_as`]).
+pub(crate) fn write_problem_as(
+    res: &mut Response,
+    problem: &ProblemDetails,
+    pretty: bool,
+    head: bool,
+) {
+    res.status_code(problem.status as usize, status_reason(problem.status));
+    res.header("Content-Type: application/problem+json");
+    if head {
+        return;
+    }
+    match json_bytes(&problem.to_value(), pretty) {
+        Ok(bytes) => res.body_vec(bytes),
+        Err(_) => res.body_vec(
+            br#"{"type":"about:blank","title":"Internal Server Error","status":500}"#.to_vec(),
+        ),
+    }
+}
+
 /// Write a JSON error response to the HTTP response object
 ///
-/// Used for validation errors, authentication failures, and other error conditions.
-/// Always sets content-type to `application/json`.
+/// Writes `body` verbatim with content-type `application/json`. Framework errors use
+/// [`write_problem`]; this remains for callers with their own error shape.
 ///
 /// # Arguments
 ///
@@ -119,7 +400,7 @@ pub fn write_json_error(res: &mut Response, status: u16, body: Value) {
     res.header("Content-Type: application/json");
     // Direct Vec<u8> — avoids the intermediate `String` allocation from
     // `to_string().into_bytes()`.
-    match serde_json::to_vec(&body) {
+    match DefaultJsonCodec::to_vec(&body) {
         Ok(bytes) => res.body_vec(bytes),
         Err(_) => res.body_vec(br#"{"error":"serialization failure"}"#.to_vec()),
     }
@@ -171,6 +452,23 @@ mod tests {
         }
     }
 
+    #[derive(Clone)]
+    struct ProblemService;
+
+    impl HttpService for ProblemService {
+        fn call(&mut self, _req: Request, res: &mut Response) -> std::io::Result<()> {
+            let problem = ProblemDetails::new(400)
+                .detail("Request validation failed")
+                .errors(vec![ProblemFieldError {
+                    pointer: "#/name".to_string(),
+                    detail: "\"name\" is a required property".to_string(),
+                    keyword: Some("required".to_string()),
+                }]);
+            write_problem(res, &problem);
+            Ok(())
+        }
+    }
+
     #[derive(Clone)]
     struct ForbiddenJsonService;
 
@@ -329,6 +627,45 @@ mod tests {
         (status, info, body)
     }
 
+    #[derive(Clone)]
+    struct GzipService;
+
+    impl HttpService for GzipService {
+        fn call(&mut self, _req: Request, res: &mut Response) -> std::io::Result<()> {
+            let mut headers: HeaderVec = HeaderVec::new();
+            headers.push((Arc::from("content-encoding"), "gzip".to_string()));
+            write_handler_response(res, 200, serde_json::json!({"ok": true}), false, &headers);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_handler_response_gzip_encodes_body() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let handle = HttpServer(GzipService).start(addr).unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        let mut buf = Vec::new();
+        let _ = stream.read_to_end(&mut buf);
+        unsafe { handle.coroutine().cancel() };
+
+        let header_end = buf.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let head = String::from_utf8_lossy(&buf[..header_end]);
+        assert_eq!(count_header(&head, "content-encoding"), 1);
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&buf[header_end..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "{\"ok\":true}");
+    }
+
     #[test]
     fn test_status_reason() {
         assert_eq!(status_reason(200), "OK");
@@ -353,6 +690,25 @@ mod tests {
         assert_eq!(body, "{\"ok\":true}");
     }
 
+    #[test]
+    fn test_write_problem() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let handle = HttpServer(ProblemService).start(addr).unwrap();
+        let resp = send_request(&addr, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        unsafe { handle.coroutine().cancel() };
+        let (status, info, body) = parse_parts(&resp);
+        assert_eq!(status, 400);
+        assert!(info.starts_with(PROBLEM_JSON), "{info}");
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["title"], "Bad Request");
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["errors"][0]["pointer"], "#/name");
+        assert!(body.get("instance").is_none());
+    }
+
     #[test]
     fn test_write_json_error() {
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
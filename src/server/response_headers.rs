@@ -0,0 +1,193 @@
+//! Handler response headers checked against the operation's declared response `headers`.
+//!
+//! With a [`ResponseHeaderPolicy`] in `Warn` or `Fail` mode (`BRRTR_RESPONSE_HEADER_VALIDATION`
+//! or [`AppService::set_response_header_validation`]), every handler response is checked
+//! against [`RouteMeta::response_headers`] for its status:
+//!
+//! - a header declared `required: true` must be present;
+//! - a declared header's value must match its `schema`, decoded like a `simple`-style header
+//!   parameter (`"42"` is an integer, `"a,b"` a two-item array);
+//! - a header the status does not declare is *undocumented*. It is only ever logged, never
+//!   failed, and standard headers ([`ALWAYS_ALLOWED`]) plus the policy's allowlist are exempt.
+//!
+//! Names are compared case-insensitively. In `Fail` mode missing or invalid headers turn the
+//! response into a `500` problem whose `errors` name each header; `Warn` logs and sends it.
+//! The default is `Off`: handlers that predate this check keep working unchanged.
+//!
+//! [`AppService::set_response_header_validation`]: super::AppService::set_response_header_validation
+//! [`RouteMeta::response_headers`]: crate::spec::RouteMeta::response_headers
+
+use std::sync::Arc;
+
+use crate::dispatcher::HeaderVec;
+use crate::runtime_config::ResponseValidationMode;
+use crate::spec::{ParameterStyle, RouteMeta};
+use crate::validator_cache::ValidatorCache;
+
+use super::request::decode_param_value;
+use super::response::ProblemFieldError;
+
+/// Headers never reported as undocumented: representation, caching and framework headers
+pub const ALWAYS_ALLOWED: &[&str] = &[
+    "accept-ranges",
+    "cache-control",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-length",
+    "content-range",
+    "content-type",
+    "date",
+    "etag",
+    "expires",
+    "last-modified",
+    "location",
+    "retry-after",
+    "set-cookie",
+    "vary",
+    "warning",
+    "x-content-type-options",
+    "x-request-id",
+];
+
+/// How handler response headers are checked against the spec
+#[derive(Debug, Clone)]
+pub struct ResponseHeaderPolicy {
+    /// `Fail`: missing or invalid declared headers become a `500`; `Warn`: logged; `Off`
+    /// (default): not checked
+    pub mode: ResponseValidationMode,
+    /// Extra lowercase header names never reported as undocumented
+    allowed: Arc<[String]>,
+}
+
+impl Default for ResponseHeaderPolicy {
+    fn default() -> Self {
+        Self::new(ResponseValidationMode::Off)
+    }
+}
+
+/// What a response's headers got wrong
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderReport {
+    /// Missing required and invalid declared headers, one entry each, by header name
+    pub errors: Vec<ProblemFieldError>,
+    /// Names of the offending headers in `errors`, as declared
+    pub invalid: Vec<String>,
+    /// Headers the status does not declare, as the handler named them
+    pub undocumented: Vec<String>,
+}
+
+impl HeaderReport {
+    /// Whether nothing was reported
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.undocumented.is_empty()
+    }
+}
+
+impl ResponseHeaderPolicy {
+    /// Check in `mode`, with only [`ALWAYS_ALLOWED`] exempt from the undocumented warning
+    #[must_use]
+    pub fn new(mode: ResponseValidationMode) -> Self {
+        Self {
+            mode,
+            allowed: Arc::from(Vec::new()),
+        }
+    }
+
+    /// Also exempt `names` from the undocumented warning (case-insensitive)
+    #[must_use]
+    pub fn allow<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut allowed = self.allowed.to_vec();
+        allowed.extend(names.into_iter().map(|n| n.as_ref().to_ascii_lowercase()));
+        self.allowed = allowed.into();
+        self
+    }
+
+    /// Whether `name` may be sent without being declared
+    #[must_use]
+    pub fn is_allowed(&self, name: &str) -> bool {
+        ALWAYS_ALLOWED.iter().any(|a| name.eq_ignore_ascii_case(a))
+            || self.allowed.iter().any(|a| name.eq_ignore_ascii_case(a))
+    }
+
+    /// Check `headers` of a `status` response from `route`; empty when the mode is `Off`
+    #[must_use]
+    pub fn check(
+        &self,
+        cache: &ValidatorCache,
+        route: &RouteMeta,
+        status: u16,
+        headers: &HeaderVec,
+    ) -> HeaderReport {
+        let mut report = HeaderReport::default();
+        if self.mode == ResponseValidationMode::Off {
+            return report;
+        }
+        let declared = route
+            .response_headers
+            .get(&status)
+            .map_or(&[][..], Vec::as_slice);
+
+        for spec in declared {
+            let values: Vec<&str> = headers
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case(&spec.name))
+                .map(|(_, v)| v.as_str())
+                .collect();
+            if values.is_empty() {
+                if spec.required {
+                    report.errors.push(ProblemFieldError {
+                        pointer: "#".to_string(),
+                        detail: format!("missing required header '{}'", spec.name),
+                        keyword: Some("required".to_string()),
+                    });
+                    report.invalid.push(spec.name.clone());
+                }
+                continue;
+            }
+            let Some(schema) = &spec.schema else {
+                continue;
+            };
+            let Some(validator) =
+                cache.get_or_compile(&route.handler_name, "response-header", Some(status), schema)
+            else {
+                continue;
+            };
+            // Repeated fields are one comma-separated value (RFC 9110 §5.3).
+            let value = decode_param_value(
+                &values.join(","),
+                Some(schema),
+                Some(ParameterStyle::Simple),
+                None,
+            );
+            if !validator.is_valid(&value) {
+                let errors = super::service::schema_field_errors(&validator, &value);
+                report
+                    .errors
+                    .extend(errors.into_iter().map(|e| ProblemFieldError {
+                        detail: format!("header '{}': {}", spec.name, e.detail),
+                        ..e
+                    }));
+                report.invalid.push(spec.name.clone());
+            }
+        }
+
+        for (name, _) in headers {
+            if !self.is_allowed(name)
+                && !declared.iter().any(|d| d.name.eq_ignore_ascii_case(name))
+                && !report
+                    .undocumented
+                    .iter()
+                    .any(|u| u.eq_ignore_ascii_case(name))
+            {
+                report.undocumented.push(name.to_string());
+            }
+        }
+        report
+    }
+}
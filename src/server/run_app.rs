@@ -10,9 +10,12 @@ use crate::router::Router;
 use crate::runtime_config::RuntimeConfig;
 use crate::spec::RouteMeta;
 
-use super::app_config::{load_app_config, AppConfig};
-use super::cors_setup::build_cors_middleware;
-use super::security_setup::register_security_from_config;
+use super::app_config::{load_app_config, AppConfig, HttpConfig};
+use super::middleware_setup::build_middleware_chain;
+use super::security_setup::{
+    check_jwks_algorithms, check_outbound_tls, register_security_from_config,
+};
+use super::startup::StartupSummary;
 use super::{AppService, HttpServer};
 
 /// Paths and flags passed from a slim service `main`.
@@ -74,7 +77,7 @@ impl RunAppBuilder {
         self
     }
 
-    /// Run the full service bootstrap: config, CORS, auth, HTTP server.
+    /// Run the full service bootstrap: config, middleware chain, auth, HTTP server.
     pub fn run(self) -> io::Result<()> {
         let args = self
             .args
@@ -84,13 +87,13 @@ impl RunAppBuilder {
             .ok_or_else(|| io::Error::other("RunAppBuilder: register handler required"))?;
         let hooks = self.hooks;
 
-        if let Err(e) = crate::otel::init_logging_with_config(&crate::otel::LogConfig::from_env()) {
+        let log_config = crate::otel::LogConfig::from_env();
+        if let Err(e) = crate::otel::init_logging_with_config(&log_config) {
             eprintln!("[logging][error] failed to init tracing subscriber: {e}");
         }
 
         let runtime = RuntimeConfig::from_env();
         may::config().set_stack_size(runtime.stack_size);
-        may::config().set_workers(runtime.may_workers);
 
         let spec_path = resolve_path(&args.manifest_dir, &args.spec);
         if args.hot_reload {
@@ -103,6 +106,12 @@ impl RunAppBuilder {
         }
 
         let app_config = load_app_config(&args.config)?;
+        check_outbound_tls(&app_config)
+            .and_then(|()| check_jwks_algorithms(&app_config))
+            .map_err(|e| io::Error::other(format!("invalid security configuration: {e}")))?;
+        // Still before the first coroutine: config.yaml may size the worker pool.
+        let runtime = runtime.with_worker_threads(app_config.worker_threads());
+        may::config().set_workers(runtime.may_workers);
         if let Some(cb) = hooks.on_config_loaded {
             cb(&app_config);
         }
@@ -110,65 +119,75 @@ impl RunAppBuilder {
         let spec_str = spec_path
             .to_str()
             .ok_or_else(|| io::Error::other("OpenAPI spec path contains invalid UTF-8"))?;
-        let (routes, schemes, _slug) = crate::spec::load_spec_full(spec_str)
-            .map_err(|e| io::Error::other(format!("failed to load OpenAPI spec: {e}")))?;
+        let (routes, schemes, _slug) =
+            crate::spec::load_spec_full_with_dialect(spec_str, app_config.schema_dialect())
+                .map_err(|e| io::Error::other(format!("failed to load OpenAPI spec: {e}")))?;
+        // Routes of disabled `x-feature`s are dropped before registration: they 404 and
+        // need no handler.
+        let routes = crate::spec::enabled_routes(routes, &runtime.enabled_features);
+        crate::spec::check_schemas(&routes, app_config.strict_schema())
+            .map_err(|e| io::Error::other(format!("invalid OpenAPI spec: {e}")))?;
 
         let mut dispatcher = Dispatcher::new();
-        let metrics = Arc::new(MetricsMiddleware::new());
+        let metrics = Arc::new(
+            MetricsMiddleware::new().with_excluded_paths(app_config.metrics_excluded_paths()),
+        );
         dispatcher.add_middleware(metrics.clone());
 
         let memory = Arc::new(crate::middleware::MemoryMiddleware::new());
         crate::middleware::memory::start_memory_monitor(memory.clone());
 
-        if let Some(cors) = build_cors_middleware(&app_config, &routes, metrics.clone()) {
-            dispatcher.add_middleware(cors);
+        let chain = build_middleware_chain(&app_config, &routes, metrics.clone())
+            .map_err(|e| io::Error::other(format!("invalid middleware configuration: {e}")))?;
+        for mw in chain {
+            dispatcher.add_middleware(mw);
         }
 
         unsafe {
             register(&mut dispatcher, &routes);
         }
 
-        let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes.clone())));
-        router.load().dump_routes();
-        let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher));
-        let mut service = AppService::new(
-            router,
-            dispatcher,
-            schemes,
-            spec_path.clone(),
-            args.static_dir.clone(),
-            Some(args.doc_dir.clone()),
-        );
+        let path_decoding = app_config
+            .http
+            .as_ref()
+            .map(HttpConfig::path_decoding)
+            .unwrap_or_default();
+        let router = Arc::new(arc_swap::ArcSwap::from_pointee(
+            Router::new(routes.clone()).with_path_decoding(path_decoding),
+        ));
+        let mut builder = AppService::builder()
+            .shared_router(router.clone())
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(spec_path.clone())
+            .doc_dir(args.doc_dir.clone())
+            .config(&app_config);
+        if let Some(dir) = &args.static_dir {
+            builder = builder.static_dir(dir);
+        }
+        let mut service = builder.build().map_err(io::Error::other)?;
+
+        if let Some(static_cfg) = &app_config.static_files {
+            service.static_files = service
+                .static_files
+                .take()
+                .map(|sf| sf.with_mime_types(&static_cfg.mime_types));
+        }
 
         let compiled_count = service.precompile_schemas(&routes);
         println!("[startup] precompiled {compiled_count} JSON schema validators");
 
         service.set_metrics_middleware(metrics);
+        service.set_metrics_json_path(app_config.metrics_json_path());
         if let Some(extra) = hooks.extra_prometheus {
             service.set_extra_prometheus(Some(extra));
         }
         service.set_memory_middleware(memory);
 
-        log_startup_context(
-            &args,
-            &spec_path,
-            &app_config,
-            runtime.stack_size,
-            runtime.may_workers,
-            routes.len(),
-        );
-
-        let (enable, timeout, max) = match app_config.http.as_ref() {
-            Some(http) => (
-                http.keep_alive.unwrap_or(true),
-                http.timeout_secs.unwrap_or(5),
-                http.max_requests.unwrap_or(1000),
-            ),
-            None => (true, 5, 1000),
-        };
-        service.set_keep_alive(enable, timeout, max);
+        log_startup_context(&args, runtime.stack_size, runtime.may_workers, routes.len());
 
-        register_security_from_config(&mut service, &app_config, args.test_api_key.as_deref());
+        register_security_from_config(&mut service, &app_config, args.test_api_key.as_deref())
+            .map_err(|e| io::Error::other(format!("invalid security configuration: {e}")))?;
 
         let port = app_config
             .port
@@ -193,8 +212,21 @@ impl RunAppBuilder {
             warm();
         }
 
-        let server = HttpServer(service).start(&addr).map_err(io::Error::other)?;
+        // WebSocket routes and `Expect: 100-continue` need the front listener
+        let server = if routes.iter().any(|r| r.websocket) || service.expect_continue {
+            HttpServer(service).start_with_websockets(&addr)
+        } else {
+            HttpServer(service).start(&addr)
+        }
+        .map_err(io::Error::other)?;
         println!("Server started successfully on {addr}");
+        StartupSummary::new(
+            spec_path.display().to_string(),
+            &addr,
+            &app_config,
+            &router.load(),
+        )
+        .log(log_config.format);
 
         server
             .run_until_shutdown()
@@ -212,13 +244,10 @@ fn resolve_path(manifest_dir: &Path, path: &Path) -> PathBuf {
 
 fn log_startup_context(
     args: &RunAppArgs,
-    spec_path: &Path,
-    app_config: &AppConfig,
     stack_size: usize,
     may_workers: usize,
     routes_count: usize,
 ) {
-    println!("[startup] spec_path={}", spec_path.display());
     if let Some(sd) = &args.static_dir {
         println!("[startup] static_dir={}", sd.display());
     }
@@ -227,8 +256,4 @@ fn log_startup_context(
         "[startup] stack_size={stack_size} may_workers={may_workers} routes_count={routes_count} hot_reload={}",
         args.hot_reload
     );
-    match serde_yaml::to_string(app_config) {
-        Ok(y) => println!("[config]\n{y}"),
-        Err(_) => println!("[config] <failed to serialize config>"),
-    }
 }
@@ -2,16 +2,20 @@
 
 use std::sync::Arc;
 
-use crate::security::{JwksBearerProvider, RemoteApiKeyProvider};
+use serde_json::Value;
+
+use crate::security::{AuthOutcome, JwksBearerProvider, RemoteApiKeyProvider};
 use crate::spec::SecurityScheme;
 use crate::{BearerJwtProvider, OAuth2Provider, SecurityProvider, SecurityRequest};
 
 use super::app_config::AppConfig;
+use super::default_providers::MissingProviderError;
 use super::service::AppService;
 
 struct StaticApiKeyProvider {
     key: String,
     header_override: Option<String>,
+    principal: Option<String>,
 }
 
 impl SecurityProvider for StaticApiKeyProvider {
@@ -31,36 +35,138 @@ impl SecurityProvider for StaticApiKeyProvider {
             _ => false,
         }
     }
+
+    /// API keys carry no scopes: `Granted` or `Unauthenticated`.
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        self.validate(scheme, scopes, req).into()
+    }
+
+    /// `{"sub": principal}` when `security.api_keys.<scheme>.principal` is configured.
+    fn extract_claims(&self, _scheme: &SecurityScheme, _req: &SecurityRequest) -> Option<Value> {
+        self.principal
+            .as_ref()
+            .map(|principal| serde_json::json!({ "sub": principal }))
+    }
+}
+
+/// Build every provider `tls:` section so a bad CA bundle or pin stops startup instead of
+/// surfacing as failed JWKS or verify fetches.
+///
+/// # Errors
+///
+/// Names the first invalid section, e.g. `security.jwks.BearerAuth.tls: no CA bundle configured`.
+pub fn check_outbound_tls(app_config: &AppConfig) -> Result<(), String> {
+    let Some(sec_cfg) = app_config.security.as_ref() else {
+        return Ok(());
+    };
+    let jwks = sec_cfg
+        .jwks
+        .iter()
+        .flatten()
+        .map(|(scheme, cfg)| ("jwks", scheme, &cfg.tls));
+    let remote = sec_cfg
+        .remote_api_keys
+        .iter()
+        .flatten()
+        .map(|(scheme, cfg)| ("remote_api_keys", scheme, &cfg.tls));
+    for (section, scheme, tls) in jwks.chain(remote) {
+        if let Some(tls) = tls {
+            tls.build()
+                .map_err(|e| format!("security.{section}.{scheme}.tls: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Check every `security.jwks.<scheme>.allowed_algorithms` list, so an empty or unsupported
+/// one stops startup instead of leaving the scheme without a provider.
+///
+/// # Errors
+///
+/// Names the first invalid list, e.g. `security.jwks.BearerAuth: allowed_algorithms must
+/// contain at least one algorithm`.
+pub fn check_jwks_algorithms(app_config: &AppConfig) -> Result<(), String> {
+    let jwks = app_config
+        .security
+        .iter()
+        .flat_map(|s| s.jwks.iter().flatten());
+    for (scheme, cfg) in jwks {
+        if let Some(algorithms) = cfg.allowed_algorithms.as_deref() {
+            JwksBearerProvider::check_allowed_algorithms(algorithms)
+                .map_err(|e| format!("security.jwks.{scheme}: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// What `security:` yielded for one scheme.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Configured {
+    /// A provider was registered.
+    Registered,
+    /// The scheme's section is invalid; it stays without a provider (requests fail closed).
+    Rejected,
+    /// No section covers the scheme.
+    Absent,
 }
 
 /// Register auth providers for each OpenAPI security scheme on the service.
+///
+/// Schemes `security:` configures get that provider. The rest go through
+/// [`AppService::register_default_security_providers_from_env`] with
+/// [`AppConfig::default_provider_policy`]: `BRRTR_*` variables, then (unless
+/// `security.strict_providers`) the mock `test_api_key` / `test123` key and `sig` signature.
+///
+/// # Errors
+///
+/// Under `security.strict_providers`, names every scheme neither config nor the environment
+/// covers. Covered schemes are registered either way.
 pub fn register_security_from_config(
     service: &mut AppService,
     app_config: &AppConfig,
     test_api_key: Option<&str>,
-) {
+) -> Result<(), MissingProviderError> {
     let sec_cfg = app_config.security.as_ref();
+    let mut rejected = Vec::new();
     for (scheme_name, scheme) in service.security_schemes.clone() {
-        match scheme {
-            SecurityScheme::ApiKey { .. } => {
-                register_api_key(service, sec_cfg, &scheme_name, test_api_key)
-            }
+        let configured = match scheme {
+            SecurityScheme::ApiKey { .. } => register_api_key(service, sec_cfg, &scheme_name),
             SecurityScheme::Http { ref scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => {
-                register_bearer(service, sec_cfg, &scheme_name);
+                register_bearer(service, sec_cfg, &scheme_name)
             }
             SecurityScheme::OAuth2 { .. } => register_oauth2(service, sec_cfg, &scheme_name),
-            _ => {}
+            _ => Configured::Absent,
+        };
+        if configured == Configured::Rejected {
+            rejected.push(scheme_name);
         }
     }
+
+    // Rejected schemes must not fall back to env or mock providers.
+    let schemes = std::mem::take(&mut service.security_schemes);
+    service.security_schemes = schemes
+        .iter()
+        .filter(|(name, _)| !rejected.contains(name))
+        .map(|(name, scheme)| (name.clone(), scheme.clone()))
+        .collect();
+    let result = service.register_default_security_providers_from_env(
+        test_api_key.map(str::to_string),
+        &app_config.default_provider_policy(),
+    );
+    service.security_schemes = schemes;
+    result
 }
 
 fn register_api_key(
     service: &mut AppService,
     sec_cfg: Option<&super::app_config::SecurityConfig>,
     scheme_name: &str,
-    test_api_key: Option<&str>,
-) {
-    let mut registered = false;
+) -> Configured {
     if let Some(cfgs) = sec_cfg.and_then(|s| s.remote_api_keys.as_ref()) {
         if let Some(cfg) = cfgs.get(scheme_name) {
             let mut provider = RemoteApiKeyProvider::new(&cfg.verify_url);
@@ -73,6 +179,19 @@ fn register_api_key(
             if let Some(ttl) = cfg.cache_ttl_secs {
                 provider = provider.cache_ttl(std::time::Duration::from_secs(ttl));
             }
+            if let Some(tls) = cfg.tls.as_ref() {
+                // Never fall back to the system trust store: leave the scheme unregistered
+                // (requests fail closed). `run_app` rejects this earlier via `check_outbound_tls`.
+                match tls.build() {
+                    Ok(tls) => provider = provider.tls(tls),
+                    Err(e) => {
+                        eprintln!(
+                            "[auth] skip RemoteApiKeyProvider scheme={scheme_name}: tls: {e}"
+                        );
+                        return Configured::Rejected;
+                    }
+                }
+            }
             println!(
                 "[auth] register RemoteApiKeyProvider scheme={scheme_name} url={} header={} timeout_ms={:?} ttl_s={:?}",
                 cfg.verify_url,
@@ -83,65 +202,52 @@ fn register_api_key(
                 cfg.cache_ttl_secs
             );
             service.register_security_provider(scheme_name, Arc::new(provider));
-            registered = true;
+            return Configured::Registered;
         }
     }
-    if !registered {
-        if let Some(cfgs) = sec_cfg.and_then(|s| s.api_keys.as_ref()) {
-            if let Some(cfg) = cfgs.get(scheme_name) {
-                if let Some(key) = cfg.key.clone() {
-                    println!(
-                        "[auth] register StaticApiKeyProvider scheme={scheme_name} header_override={:?} key_len={}",
-                        cfg.header_name,
-                        key.len()
-                    );
-                    service.register_security_provider(
-                        scheme_name,
-                        Arc::new(StaticApiKeyProvider {
-                            key,
-                            header_override: cfg.header_name.clone(),
-                        }),
-                    );
-                    registered = true;
-                }
+    if let Some(cfgs) = sec_cfg.and_then(|s| s.api_keys.as_ref()) {
+        if let Some(cfg) = cfgs.get(scheme_name) {
+            if let Some(key) = cfg.key.clone() {
+                println!(
+                    "[auth] register StaticApiKeyProvider scheme={scheme_name} header_override={:?} key_len={}",
+                    cfg.header_name,
+                    key.len()
+                );
+                service.register_security_provider(
+                    scheme_name,
+                    Arc::new(StaticApiKeyProvider {
+                        key,
+                        header_override: cfg.header_name.clone(),
+                        principal: cfg.principal.clone(),
+                    }),
+                );
+                return Configured::Registered;
             }
         }
     }
-    if !registered {
-        let fallback = std::env::var("BRRTR_API_KEY")
-            .ok()
-            .or_else(|| test_api_key.map(str::to_string))
-            .unwrap_or_else(|| "test123".to_string());
-        println!(
-            "[auth] register StaticApiKeyProvider scheme={scheme_name} from=fallback key_len={}",
-            fallback.len()
-        );
-        service.register_security_provider(
-            scheme_name,
-            Arc::new(StaticApiKeyProvider {
-                key: fallback,
-                header_override: None,
-            }),
-        );
-    }
+    Configured::Absent
 }
 
 fn register_bearer(
     service: &mut AppService,
     sec_cfg: Option<&super::app_config::SecurityConfig>,
     scheme_name: &str,
-) {
+) -> Configured {
     if register_jwks_from_propelauth(service, sec_cfg, scheme_name) {
-        return;
+        return Configured::Registered;
     }
-    if register_jwks_per_scheme(service, sec_cfg, scheme_name) {
-        return;
+    let jwks = register_jwks_per_scheme(service, sec_cfg, scheme_name);
+    if jwks != Configured::Absent {
+        return jwks;
     }
-    let sig = sec_cfg
+    // Env here too so `bearer.cookie_name` applies to BRRTR_BEARER_SIGNATURE
+    let Some(sig) = sec_cfg
         .and_then(|s| s.bearer.as_ref())
         .and_then(|b| b.signature.clone())
         .or_else(|| std::env::var("BRRTR_BEARER_SIGNATURE").ok())
-        .unwrap_or_else(|| "sig".into());
+    else {
+        return Configured::Absent;
+    };
     let sig_len = sig.len();
     let mut p = BearerJwtProvider::new(sig);
     let cookie_opt = sec_cfg
@@ -151,27 +257,32 @@ fn register_bearer(
         p = p.cookie_name(cookie);
     }
     println!(
-        "[auth] register BearerJwtProvider scheme={scheme_name} source=mock signature_len={sig_len} cookie={cookie_opt:?}"
+        "[auth] register BearerJwtProvider scheme={scheme_name} source=signature signature_len={sig_len} cookie={cookie_opt:?}"
     );
     service.register_security_provider(scheme_name, Arc::new(p));
+    Configured::Registered
 }
 
 fn register_oauth2(
     service: &mut AppService,
     sec_cfg: Option<&super::app_config::SecurityConfig>,
     scheme_name: &str,
-) {
+) -> Configured {
     if register_jwks_from_propelauth(service, sec_cfg, scheme_name) {
-        return;
+        return Configured::Registered;
     }
-    if register_jwks_per_scheme(service, sec_cfg, scheme_name) {
-        return;
+    let jwks = register_jwks_per_scheme(service, sec_cfg, scheme_name);
+    if jwks != Configured::Absent {
+        return jwks;
     }
-    let sig = sec_cfg
+    // Env here too so `oauth2.cookie_name` applies to BRRTR_OAUTH2_SIGNATURE
+    let Some(sig) = sec_cfg
         .and_then(|s| s.oauth2.as_ref())
         .and_then(|b| b.signature.clone())
         .or_else(|| std::env::var("BRRTR_OAUTH2_SIGNATURE").ok())
-        .unwrap_or_else(|| "sig".into());
+    else {
+        return Configured::Absent;
+    };
     let sig_len = sig.len();
     let mut p = OAuth2Provider::new(sig);
     let cookie_opt = sec_cfg
@@ -181,9 +292,10 @@ fn register_oauth2(
         p = p.cookie_name(cookie);
     }
     println!(
-        "[auth] register OAuth2Provider scheme={scheme_name} source=mock signature_len={sig_len} cookie={cookie_opt:?}"
+        "[auth] register OAuth2Provider scheme={scheme_name} source=signature signature_len={sig_len} cookie={cookie_opt:?}"
     );
     service.register_security_provider(scheme_name, Arc::new(p));
+    Configured::Registered
 }
 
 fn register_jwks_from_propelauth(
@@ -224,12 +336,12 @@ fn register_jwks_per_scheme(
     service: &mut AppService,
     sec_cfg: Option<&super::app_config::SecurityConfig>,
     scheme_name: &str,
-) -> bool {
+) -> Configured {
     let Some(jwks_map) = sec_cfg.and_then(|s| s.jwks.as_ref()) else {
-        return false;
+        return Configured::Absent;
     };
     let Some(jwks) = jwks_map.get(scheme_name) else {
-        return false;
+        return Configured::Absent;
     };
     let mut p = JwksBearerProvider::new(&jwks.jwks_url);
     if let Some(iss) = jwks.iss.as_deref() {
@@ -241,13 +353,37 @@ fn register_jwks_per_scheme(
     if let Some(leeway) = jwks.leeway_secs {
         p = p.leeway(leeway);
     }
+    if let Some(reject) = jwks.reject_future_iat {
+        p = p.reject_future_iat(reject);
+    }
+    if let Some(allow) = jwks.allow_missing_kid {
+        p = p.allow_missing_kid(allow);
+    }
+    if let Some(algorithms) = jwks.allowed_algorithms.as_deref() {
+        // Same fail-closed rule as `tls`: no provider rather than a panic or the default set.
+        if let Err(e) = JwksBearerProvider::check_allowed_algorithms(algorithms) {
+            eprintln!("[auth] skip JwksBearerProvider scheme={scheme_name}: {e}");
+            return Configured::Rejected;
+        }
+        p = p.allowed_algorithms(algorithms);
+    }
     if let Some(ttl) = jwks.cache_ttl_secs {
         p = p.cache_ttl(std::time::Duration::from_secs(ttl));
     }
+    if let Some(tls) = jwks.tls.as_ref() {
+        // Same fail-closed rule as remote API keys: no provider rather than the system trust.
+        match tls.build() {
+            Ok(tls) => p = p.tls(tls),
+            Err(e) => {
+                eprintln!("[auth] skip JwksBearerProvider scheme={scheme_name}: tls: {e}");
+                return Configured::Rejected;
+            }
+        }
+    }
     println!(
         "[auth] register JwksBearerProvider scheme={scheme_name} source=per-scheme jwks_url={} iss={:?} aud={:?}",
         jwks.jwks_url, jwks.iss, jwks.aud
     );
     service.register_security_provider(scheme_name, Arc::new(p));
-    true
+    Configured::Registered
 }
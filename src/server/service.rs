@@ -1,24 +1,50 @@
-use super::request::{parse_request, ParsedRequest};
-use super::response::{response_status_allows_body, write_handler_response, write_json_error};
-use crate::dispatcher::Dispatcher;
+use super::app_config::{BatchConfig, ErrorsConfig, HttpConfig, QueryConfig, WebSocketConfig};
+use super::base_path::BasePath;
+use super::builder::AppServiceBuilder;
+use super::connection::{ConnectionConfig, HttpVersion};
+use super::default_providers::{
+    DefaultProviderPolicy, MissingProviderError, ProviderSource, SchemeKind,
+};
+use super::fallback::{fallback_request, ErrorFormat, FallbackHandlers, HtmlErrorPage};
+use super::limits::{uri_violation, HeaderLimits, DEFAULT_MAX_URI_BYTES};
+use super::multipart::{is_multipart_form_data, Multipart};
+use super::path_normalize::PathNormalization;
+use super::relay;
+use super::request::{
+    apply_param_defaults, canonicalize_query_params, decode_query_values, deprecated_params_sent,
+    parse_request_with_limits, ParsedRequest, DEFAULT_BODY_READ_TIMEOUT, DEFAULT_MAX_JSON_DEPTH,
+};
+use super::response::{
+    handler_body_len, response_status_allows_body, write_handler_response,
+    write_handler_response_as, write_problem, write_problem_as, ProblemDetails, ProblemFieldError,
+};
+use super::response_headers::ResponseHeaderPolicy;
+use super::websocket::WebSocketRequest;
+use crate::alloc_profile::{self, Phase};
+use crate::dispatcher::{
+    Dispatcher, HandlerRequest, HandlerResponse, HeaderLookup, HeaderVec, PanicGuardStats,
+};
 use crate::ids::RequestId;
-use crate::middleware::MetricsMiddleware;
-use crate::router::Router;
+use crate::middleware::{MetricsMiddleware, ValidationFailureCategory};
+use crate::router::{route_label, ParamVec, RouteMatch, Router};
+use crate::runtime_config::ResponseValidationMode;
 use crate::sanitize::default_sanitizer;
-use crate::security::{SecurityProvider, SecurityRequest};
-use crate::spec::SecurityScheme;
+use crate::security::{AuthContext, AuthOutcome, SecurityProvider, SecurityRequest};
+use crate::spec::{HeadStrategy, ParameterLocation, SecurityScheme};
 use crate::static_files::StaticFiles;
 use crate::validator_cache::ValidatorCache;
 use arc_swap::ArcSwap;
 use http::Method;
 use may_minihttp::{HttpService, Request, Response};
-use serde_json::json;
+use serde_json::{json, Value};
+use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::io;
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Shared, lock-free-read router (PRD Phase 1).
 ///
@@ -36,13 +62,113 @@ pub type SharedRouter = Arc<ArcSwap<Router>>;
 pub type SharedDispatcher = Arc<ArcSwap<Dispatcher>>;
 use tracing::info;
 
+/// Default `Server` header value; see [`AppService::set_server_header`].
+pub const DEFAULT_SERVER_HEADER: &str = "BRRTRouter";
+
 /// Maximum JSON Schema validation errors collected per request/response (hot-path Phase 4).
-/// Bounds CPU and allocations on pathological invalid bodies; the HTTP `details` array is truncated accordingly.
+/// Bounds CPU and allocations on pathological invalid bodies; the problem `errors` array is truncated accordingly.
 ///
 /// Valid instances call `jsonschema::Validator::is_valid` first (cheaper than `iter_errors` when there are no errors);
-/// this limit applies only to the failure path when we build the `details` array.
+/// this limit applies only to the failure path when we build the `errors` array.
 const MAX_JSON_SCHEMA_ERRORS: usize = 64;
 
+/// Schema violations of `instance` as problem `errors` entries (JSON Pointer fragment + message).
+pub(super) fn schema_field_errors(
+    validator: &jsonschema::Validator,
+    instance: &serde_json::Value,
+) -> Vec<ProblemFieldError> {
+    schema_failures(validator, instance).0
+}
+
+/// `http.route` of the request span when no route matched.
+pub const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// Records `brrt.validation_us` (authentication, parameter, content-type and body checks) on
+/// the current request span when dropped, so a rejected request reports the time until its
+/// rejection.
+struct ValidationTimer(Instant);
+
+impl Drop for ValidationTimer {
+    fn drop(&mut self) {
+        tracing::Span::current().record("brrt.validation_us", self.0.elapsed().as_micros() as u64);
+    }
+}
+
+/// Distinct [`ValidationFailureCategory`]s of one failed validation (there are only six).
+type FailureCategories = SmallVec<[ValidationFailureCategory; 4]>;
+
+/// [`schema_field_errors`] plus the distinct failure categories, collected in the same pass.
+fn schema_failures(
+    validator: &jsonschema::Validator,
+    instance: &serde_json::Value,
+) -> (Vec<ProblemFieldError>, FailureCategories) {
+    let mut categories = FailureCategories::new();
+    let errors = validator
+        .iter_errors(instance)
+        .take(MAX_JSON_SCHEMA_ERRORS)
+        .map(|e| {
+            let category = ValidationFailureCategory::from_error_kind(e.kind());
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+            let pointer = format!("#{}", e.instance_path());
+            ProblemFieldError {
+                detail: array_violation_detail(e.kind(), &pointer).unwrap_or_else(|| e.to_string()),
+                keyword: schema_keyword(e.kind()).map(str::to_string),
+                pointer,
+            }
+        })
+        .collect();
+    (errors, categories)
+}
+
+/// JSON Schema keyword behind a validation error, for the problem `errors` entry.
+fn schema_keyword(kind: &jsonschema::error::ValidationErrorKind) -> Option<&'static str> {
+    use jsonschema::error::ValidationErrorKind as Kind;
+    Some(match kind {
+        Kind::Required { .. } => "required",
+        Kind::Type { .. } => "type",
+        Kind::Enum { .. } => "enum",
+        Kind::Constant { .. } => "const",
+        Kind::Format { .. } => "format",
+        Kind::AdditionalProperties { .. } => "additionalProperties",
+        Kind::MaxLength { .. } => "maxLength",
+        Kind::MinLength { .. } => "minLength",
+        Kind::Maximum { .. } => "maximum",
+        Kind::Minimum { .. } => "minimum",
+        Kind::Pattern { .. } => "pattern",
+        Kind::MaxItems { .. } => "maxItems",
+        Kind::MinItems { .. } => "minItems",
+        Kind::UniqueItems { .. } => "uniqueItems",
+        _ => return None,
+    })
+}
+
+/// Short `detail` for array size and uniqueness violations.
+///
+/// jsonschema's own message quotes the whole array, which for an oversized bulk request is as
+/// large as the request itself; this names the field (`pointer`) and the limit instead.
+fn array_violation_detail(
+    kind: &jsonschema::error::ValidationErrorKind,
+    pointer: &str,
+) -> Option<String> {
+    use jsonschema::error::ValidationErrorKind as Kind;
+    let field = match pointer.strip_prefix("#/") {
+        Some(path) => format!("'{path}'"),
+        None => "The value".to_string(),
+    };
+    match kind {
+        Kind::MaxItems { limit } => Some(format!(
+            "{field} has more than {limit} items (maxItems: {limit})"
+        )),
+        Kind::MinItems { limit } => Some(format!(
+            "{field} has fewer than {limit} items (minItems: {limit})"
+        )),
+        Kind::UniqueItems { .. } => Some(format!("{field} has duplicate items (uniqueItems)")),
+        _ => None,
+    }
+}
+
 fn response_schema_is_binary_string(s: &serde_json::Value) -> bool {
     s.get("type").and_then(|t| t.as_str()) == Some("string")
         && s.get("format").and_then(|f| f.as_str()) == Some("binary")
@@ -53,12 +179,16 @@ fn response_schema_is_binary_string(s: &serde_json::Value) -> bool {
 /// OpenAPI may list several `content` types for one status (e.g. `image/png` + `application/json`).
 /// We must never validate a JSON object against a `type: string` + `format: binary` schema.
 ///
-/// Prefer `application/json` on the status, then any non-binary schema (typically `type: object`),
-/// then the route default `response_schema` for **2xx only** if it is not a binary-only schema.
+/// An `x-response-schema` override for the status wins. Otherwise prefer `application/json` on
+/// the status, then any non-binary schema (typically `type: object`), then the route default
+/// `response_schema` for **2xx only** if it is not a binary-only schema.
 fn response_body_schema_for_status(
     route: &crate::spec::RouteMeta,
     status: u16,
 ) -> Option<&serde_json::Value> {
+    if let Some(s) = route.schema_overrides.response(status) {
+        return Some(s);
+    }
     if let Some(status_map) = route.responses.get(&status) {
         if let Some(s) = status_map
             .get("application/json")
@@ -147,6 +277,8 @@ pub struct AppService {
     pub metrics: Option<Arc<crate::middleware::MetricsMiddleware>>,
     /// Optional extra Prometheus text (e.g. Lifeguard `lifeguard_*` appended to `/metrics`).
     pub extra_prometheus: Option<Arc<dyn Fn() -> String + Send + Sync>>,
+    /// Path of the JSON metrics snapshot when enabled (config.yaml `metrics.json`)
+    pub metrics_json_path: Option<Arc<str>>,
     /// Optional memory tracking middleware
     pub memory: Option<Arc<crate::middleware::MemoryMiddleware>>,
     /// Path to the OpenAPI specification file
@@ -157,15 +289,74 @@ pub struct AppService {
     pub doc_files: Option<StaticFiles>,
     /// Optional file watcher for hot reloading
     pub watcher: Option<notify::RecommendedWatcher>,
-    /// Precomputed `Keep-Alive: …` header line, set once via [`set_keep_alive`].
+    /// Precomputed `Keep-Alive: …` header line, set once via [`set_connection_config`].
     /// Cloned (O(1) `Arc`-free `String::clone`? — just a `Box<str>` copy) per
     /// response; no `Box::leak` needed since `may_minihttp::Response::header`
     /// now accepts owned values.
     pub keep_alive_header: Option<Box<str>>,
+    /// Keep-alive policy for every connection (see [`super::connection`]).
+    pub connection: ConnectionConfig,
+    /// Responses served on this clone's connection.
+    ///
+    /// `may_minihttp` clones the service once per accepted connection and serves every
+    /// request on that connection with the clone, and [`Clone`] resets this counter, so it
+    /// counts requests per connection. A service shared across connections some other way
+    /// would pool their budgets.
+    pub connection_requests: u64,
+    /// Secret a relayed connection must present before it is served, set when the service
+    /// runs behind [`super::HttpServer::start_unix`] or
+    /// [`super::HttpServer::start_with_websockets`]; `None` serves every connection.
+    pub(crate) relay_token: Option<Arc<str>>,
+    /// Whether this clone's connection presented [`Self::relay_token`].
+    relay_admitted: bool,
+    /// Client address the relay reported for this clone's connection, passed to handlers as
+    /// [`crate::dispatcher::HandlerRequest::peer_addr`].
+    peer_addr: Option<IpAddr>,
+    /// Oldest HTTP version served; older requests get `505` (see [`super::connection`]).
+    pub min_http_version: HttpVersion,
+    /// Longest request target (path and query string); longer ones get `414` before parsing.
+    pub max_uri_bytes: usize,
+    /// Header count/size limits checked before routing (see [`super::limits`]).
+    pub header_limits: HeaderLimits,
+    /// Largest `Content-Length` accepted; larger requests get `413` before routing.
+    pub max_body_bytes: Option<usize>,
+    /// Longest a request body may take to arrive; slower bodies get `408`.
+    pub body_read_timeout: Duration,
+    /// Deepest array/object nesting of a JSON body; deeper bodies get `400` before parsing.
+    pub max_json_depth: usize,
+    /// Answer `Expect: 100-continue` after the checks that need no body (see
+    /// [`super::expect`]); only honoured behind [`super::HttpServer::start_with_websockets`].
+    pub expect_continue: bool,
+    /// Precomputed `Server: …` line sent with every response; `None` suppresses it.
+    pub server_header: Option<Box<str>>,
     /// JSON Schema validator cache for eliminating per-request compilation
     pub validator_cache: ValidatorCache,
     /// Pre-resolved security by handler name (populated after providers are registered).
     pub security_lookup: Arc<HashMap<String, Arc<ResolvedSecurity>>>,
+    /// Response schema enforcement, from `BRRTR_RESPONSE_VALIDATION` unless overridden via
+    /// [`Self::set_response_validation`].
+    pub response_validation: ResponseValidationMode,
+    /// Declared response header checks, from `BRRTR_RESPONSE_HEADER_VALIDATION` unless
+    /// overridden via [`Self::set_response_header_validation`] (see [`super::response_headers`]).
+    pub response_headers: ResponseHeaderPolicy,
+    /// Indent JSON response bodies, from `BRRTR_PRETTY_JSON` unless overridden via
+    /// [`Self::set_pretty_json`].
+    pub pretty_json: bool,
+    /// Honour the `?__pretty=1` override, from `BRRTR_DEV_MODE` unless overridden via
+    /// [`Self::set_dev_mode`].
+    pub dev_mode: bool,
+    /// Custom responses for framework 404 / 405 / 500s (see [`super::fallback`]).
+    pub fallbacks: FallbackHandlers,
+    /// Batch endpoint settings when enabled (see [`super::batch`]).
+    pub batch: Option<Arc<BatchConfig>>,
+    /// WebSocket limits, used by [`super::HttpServer::start_with_websockets`].
+    pub websocket: WebSocketConfig,
+    /// Repeated query key handling (see [`super::request::canonicalize_query_params`]).
+    pub query: QueryConfig,
+    /// Prefix stripped before routing when mounted behind a gateway (see [`super::base_path`]).
+    pub base_path: Option<BasePath>,
+    /// Duplicate-slash handling before routing (see [`super::path_normalize`]).
+    pub path_normalization: PathNormalization,
 }
 
 /// Clone implementation for `AppService`
@@ -203,19 +394,50 @@ impl Clone for AppService {
             security_providers: self.security_providers.clone(),
             metrics: self.metrics.clone(),
             extra_prometheus: self.extra_prometheus.clone(),
+            metrics_json_path: self.metrics_json_path.clone(),
             memory: self.memory.clone(),
             spec_path: self.spec_path.clone(),
             static_files: self.static_files.clone(),
             doc_files: self.doc_files.clone(),
             watcher: None,
             keep_alive_header: self.keep_alive_header.clone(),
+            connection: self.connection.clone(),
+            // A clone serves one new connection (see `connection_requests`).
+            connection_requests: 0,
+            relay_token: self.relay_token.clone(),
+            relay_admitted: false,
+            peer_addr: None,
+            min_http_version: self.min_http_version,
+            max_uri_bytes: self.max_uri_bytes,
+            header_limits: self.header_limits,
+            max_body_bytes: self.max_body_bytes,
+            body_read_timeout: self.body_read_timeout,
+            max_json_depth: self.max_json_depth,
+            expect_continue: self.expect_continue,
+            server_header: self.server_header.clone(),
             validator_cache: self.validator_cache.clone(),
             security_lookup: self.security_lookup.clone(),
+            response_validation: self.response_validation,
+            response_headers: self.response_headers.clone(),
+            pretty_json: self.pretty_json,
+            dev_mode: self.dev_mode,
+            fallbacks: self.fallbacks.clone(),
+            batch: self.batch.clone(),
+            websocket: self.websocket.clone(),
+            query: self.query,
+            base_path: self.base_path.clone(),
+            path_normalization: self.path_normalization,
         }
     }
 }
 
 impl AppService {
+    /// Start building a service from named settings; see [`AppServiceBuilder`].
+    #[must_use]
+    pub fn builder() -> AppServiceBuilder {
+        AppServiceBuilder::default()
+    }
+
     /// Create a new application service
     ///
     /// # Arguments
@@ -230,6 +452,7 @@ impl AppService {
     /// # Returns
     ///
     /// A new `AppService` ready to handle requests
+    #[deprecated(note = "use `AppService::builder()`, whose named settings can grow")]
     pub fn new(
         router: SharedRouter,
         dispatcher: SharedDispatcher,
@@ -237,6 +460,25 @@ impl AppService {
         spec_path: PathBuf,
         static_dir: Option<PathBuf>,
         doc_dir: Option<PathBuf>,
+    ) -> Self {
+        Self::from_parts(
+            router,
+            dispatcher,
+            security_schemes,
+            spec_path,
+            static_dir,
+            doc_dir,
+        )
+    }
+
+    /// The service [`AppService::new`] and [`AppServiceBuilder::build`] start from
+    pub(super) fn from_parts(
+        router: SharedRouter,
+        dispatcher: SharedDispatcher,
+        security_schemes: HashMap<String, SecurityScheme>,
+        spec_path: PathBuf,
+        static_dir: Option<PathBuf>,
+        doc_dir: Option<PathBuf>,
     ) -> Self {
         // Load runtime config to determine if caching is enabled
         let runtime_config = crate::runtime_config::RuntimeConfig::from_env();
@@ -249,15 +491,125 @@ impl AppService {
             security_providers: HashMap::new(),
             metrics: None,
             extra_prometheus: None,
+            metrics_json_path: None,
             memory: None,
             spec_path,
             static_files: static_dir.map(StaticFiles::new),
             doc_files: doc_dir.map(StaticFiles::new),
             watcher: None,
             keep_alive_header: None,
+            connection: ConnectionConfig {
+                keep_alive: false,
+                ..ConnectionConfig::default()
+            },
+            connection_requests: 0,
+            relay_token: None,
+            relay_admitted: false,
+            peer_addr: None,
+            min_http_version: HttpVersion::Http10,
+            max_uri_bytes: DEFAULT_MAX_URI_BYTES,
+            header_limits: HeaderLimits::default(),
+            max_body_bytes: None,
+            body_read_timeout: DEFAULT_BODY_READ_TIMEOUT,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            expect_continue: false,
+            server_header: Some(format!("Server: {DEFAULT_SERVER_HEADER}").into_boxed_str()),
             validator_cache,
             security_lookup: Arc::new(HashMap::new()),
+            response_validation: runtime_config.response_validation,
+            response_headers: ResponseHeaderPolicy::new(runtime_config.response_header_validation),
+            pretty_json: runtime_config.pretty_json,
+            dev_mode: runtime_config.dev_mode,
+            fallbacks: FallbackHandlers {
+                html_page: Some(Arc::new(HtmlErrorPage::default())),
+                ..FallbackHandlers::default()
+            },
+            batch: None,
+            websocket: WebSocketConfig::default(),
+            query: QueryConfig::default(),
+            base_path: None,
+            path_normalization: PathNormalization::default(),
+        }
+    }
+
+    /// Mount the service under `prefix` (see [`super::base_path`]): `/service-a/pets` routes
+    /// as `/pets`, and `/health` and `/metrics` also answer unprefixed.
+    #[must_use]
+    pub fn with_base_path(mut self, prefix: &str) -> Self {
+        self.set_base_path(BasePath::new(prefix));
+        self
+    }
+
+    /// Collapse or redirect paths with duplicate slashes (config.yaml
+    /// `http.duplicate_slashes`); see [`super::path_normalize`]. Off by default.
+    pub fn set_path_normalization(&mut self, normalization: PathNormalization) {
+        self.path_normalization = normalization;
+    }
+
+    /// Path prefix to strip before routing (config.yaml `http.base_path`); `None` serves
+    /// the spec's paths as they are.
+    pub fn set_base_path(&mut self, base_path: Option<BasePath>) {
+        self.base_path = base_path;
+    }
+
+    /// `path` with the base path removed, or `Err(path)` unchanged when it is outside the
+    /// base path. Without a base path every path is `Ok`.
+    pub(crate) fn strip_base_path(&self, path: String) -> Result<String, String> {
+        match self.base_path.as_ref().map(|base| base.strip(&path)) {
+            None => Ok(path),
+            Some(Some(stripped)) => Ok(stripped.to_string()),
+            Some(None) => Err(path),
+        }
+    }
+
+    /// Prefix the relative `Location` / `Link` targets of a routed request's outcome.
+    pub(crate) fn prefix_outcome(&self, mut outcome: RouteOutcome) -> RouteOutcome {
+        if let Some(base) = &self.base_path {
+            match &mut outcome {
+                RouteOutcome::Problem { headers, .. } | RouteOutcome::Handler { headers, .. } => {
+                    base.rewrite_headers(headers);
+                }
+                RouteOutcome::Upgrade(_) | RouteOutcome::Continue => {}
+            }
         }
+        outcome
+    }
+
+    /// Serve the batch endpoint described by `config`; `None` or `enabled: false` turns it off.
+    pub fn set_batch(&mut self, config: Option<BatchConfig>) {
+        self.batch = config.filter(|c| c.enabled).map(Arc::new);
+    }
+
+    /// WebSocket limits (config.yaml `websocket:`); `None` keeps the defaults.
+    pub fn set_websocket(&mut self, config: Option<WebSocketConfig>) {
+        self.websocket = config.unwrap_or_default();
+    }
+
+    /// Query string handling (config.yaml `query:`); `None` keeps the defaults.
+    pub fn set_query(&mut self, config: Option<QueryConfig>) {
+        self.query = config.unwrap_or_default();
+    }
+
+    /// Framework error rendering (config.yaml `errors:`); `None` keeps the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `html_template` cannot be read or does not compile.
+    pub fn set_errors(&mut self, config: Option<ErrorsConfig>) -> io::Result<()> {
+        let config = config.unwrap_or_default();
+        let page = match (config.format, &config.html_template) {
+            (ErrorFormat::Json, _) => None,
+            (ErrorFormat::Negotiate, Some(path)) => Some(HtmlErrorPage::from_file(path)?),
+            (ErrorFormat::Negotiate, None) => Some(HtmlErrorPage::default()),
+        };
+        self.set_html_error_page(page);
+        Ok(())
+    }
+
+    /// Page for framework 404 / 405 / 500s requested with `Accept: text/html` when no fallback
+    /// handler is registered for the status; `None` always writes the problem.
+    pub fn set_html_error_page(&mut self, page: Option<HtmlErrorPage>) {
+        self.fallbacks.html_page = page.map(Arc::new);
     }
 
     /// Register a security provider for authentication/authorization
@@ -296,10 +648,14 @@ impl AppService {
     }
 
     pub fn set_metrics_middleware(&mut self, metrics: Arc<MetricsMiddleware>) {
-        // Pre-register all known paths from the router. ArcSwap::load returns a
-        // `Guard<Arc<Router>>` which auto-derefs to `Router`.
+        // Pre-register all known paths from the router, by their route label. ArcSwap::load
+        // returns a `Guard<Arc<Router>>` which auto-derefs to `Router`.
         let router = self.router.load();
-        let paths = router.get_all_path_patterns();
+        let paths: Vec<String> = router
+            .get_all_path_patterns()
+            .into_iter()
+            .map(|path| route_label(&path).into_owned())
+            .collect();
         if !paths.is_empty() {
             info!(
                 count = paths.len(),
@@ -307,10 +663,17 @@ impl AppService {
             );
             metrics.pre_register_paths(&paths);
         }
+        metrics.set_route_slow_thresholds(router.routes());
 
         self.metrics = Some(metrics);
     }
 
+    /// Serve [`MetricsMiddleware::snapshot`] as JSON on `GET path` (e.g. `/metrics.json`);
+    /// `None` turns it off. Needs [`Self::set_metrics_middleware`], like `/metrics`.
+    pub fn set_metrics_json_path(&mut self, path: Option<String>) {
+        self.metrics_json_path = path.map(Arc::from);
+    }
+
     /// Set the memory tracking middleware
     ///
     /// Enables memory usage tracking and export to OpenTelemetry/Prometheus.
@@ -325,16 +688,290 @@ impl AppService {
     /// Configure HTTP/1.1 keep-alive headers to be sent on responses.
     /// If `enable` is false, keep-alive headers are not sent.
     ///
-    /// Note: may_minihttp requires header values with 'static lifetime; we therefore
-    /// allocate once and leak a single header string here to avoid per-request leaks.
+    /// Shorthand for [`Self::set_connection_config`] with `idle_timeout = timeout_secs` and
+    /// `max_requests_per_connection = max_requests`, where `0` means unlimited as in
+    /// `config.yaml`.
     pub fn set_keep_alive(&mut self, enable: bool, timeout_secs: u64, max_requests: u64) {
-        if enable {
-            self.keep_alive_header = Some(
-                format!("Keep-Alive: timeout={timeout_secs}, max={max_requests}").into_boxed_str(),
-            );
-        } else {
-            self.keep_alive_header = None;
+        self.set_connection_config(ConnectionConfig {
+            keep_alive: enable,
+            idle_timeout: Duration::from_secs(timeout_secs),
+            max_requests_per_connection: (max_requests != 0).then_some(max_requests),
+        });
+    }
+
+    /// Apply a per-connection keep-alive policy.
+    ///
+    /// Each connection answers with `Connection: keep-alive` and the precomputed `Keep-Alive`
+    /// header until it has served `max_requests_per_connection` responses; the last one carries
+    /// `Connection: close`. See [`super::connection`] for what is (and is not) enforced.
+    pub fn set_connection_config(&mut self, config: ConnectionConfig) {
+        self.keep_alive_header = config.keep_alive_header();
+        self.connection = config;
+    }
+
+    /// Answer requests older than `version` with `505 HTTP Version Not Supported`; the
+    /// default, HTTP/1.0, serves every version `may_minihttp` parses.
+    pub fn set_min_http_version(&mut self, version: HttpVersion) {
+        self.min_http_version = version;
+    }
+
+    /// Apply config.yaml `http:`: connection policy, request limits, `Server` header,
+    /// `Expect: 100-continue`, base path, path normalization and minimum HTTP version.
+    /// Settings `http` leaves unset take their defaults.
+    pub fn apply_http_config(&mut self, http: &HttpConfig) {
+        self.set_connection_config(http.connection_config());
+        self.set_header_limits(http.header_limits());
+        self.set_server_header(http.server_header());
+        self.set_max_body_bytes(http.max_body_bytes);
+        self.set_max_uri_bytes(http.max_uri_bytes.unwrap_or(DEFAULT_MAX_URI_BYTES));
+        self.set_body_read_timeout(
+            http.body_read_timeout_secs
+                .map_or(DEFAULT_BODY_READ_TIMEOUT, Duration::from_secs),
+        );
+        self.set_max_json_depth(http.max_json_depth.unwrap_or(DEFAULT_MAX_JSON_DEPTH));
+        self.set_expect_continue(http.expect_continue.unwrap_or(false));
+        self.set_base_path(http.base_path());
+        self.set_path_normalization(http.path_normalization());
+        self.set_min_http_version(http.min_version.unwrap_or_default());
+    }
+
+    /// Reject requests whose target (path and query string) is longer than `max` bytes with
+    /// `414 URI Too Long` before it is decoded or routed. Defaults to
+    /// [`DEFAULT_MAX_URI_BYTES`] (8 KiB).
+    pub fn set_max_uri_bytes(&mut self, max: usize) {
+        self.max_uri_bytes = max;
+    }
+
+    /// Limit the number and total size of request headers; requests over either limit get
+    /// `431 Request Header Fields Too Large` before they are routed.
+    pub fn set_header_limits(&mut self, limits: HeaderLimits) {
+        self.header_limits = limits;
+    }
+
+    /// Reject requests whose `Content-Length` exceeds `max` with `413 Content Too Large`
+    /// before they are routed; `None` (the default) accepts any length.
+    pub fn set_max_body_bytes(&mut self, max: Option<usize>) {
+        self.max_body_bytes = max;
+    }
+
+    /// Answer `408 Request Timeout` when a request body is still incomplete after `timeout`
+    /// (default [`DEFAULT_BODY_READ_TIMEOUT`]); a body cut short by the client closing the
+    /// connection gets `400` whatever the timeout.
+    pub fn set_body_read_timeout(&mut self, timeout: Duration) {
+        self.body_read_timeout = timeout;
+    }
+
+    /// Reject JSON bodies whose arrays and objects nest deeper than `max` with `400` before
+    /// they are parsed or validated, so a hostile body cannot exhaust a coroutine stack.
+    /// Defaults to [`DEFAULT_MAX_JSON_DEPTH`] (64).
+    pub fn set_max_json_depth(&mut self, max: usize) {
+        self.max_json_depth = max;
+    }
+
+    /// Answer `Expect: 100-continue` with `100 Continue` once the route, credentials,
+    /// parameters, content type and `Content-Length` pass, or reject the request before its
+    /// body is sent (see [`super::expect`]). Needs the front listener of
+    /// [`super::HttpServer::start_with_websockets`], whose connections then serve one request
+    /// each; off by default.
+    pub fn set_expect_continue(&mut self, enabled: bool) {
+        self.expect_continue = enabled;
+    }
+
+    /// Set the `Server` header sent with every response (handler, static, built-in and error
+    /// responses alike), or suppress it with `None`. Defaults to [`DEFAULT_SERVER_HEADER`].
+    /// Control characters are removed from `value`.
+    pub fn set_server_header(&mut self, value: Option<&str>) {
+        self.server_header = value.map(|v| {
+            let v: String = v.chars().filter(|c| !c.is_control()).collect();
+            format!("Server: {}", v.trim()).into_boxed_str()
+        });
+    }
+
+    /// Choose how handler responses that violate their response schema are handled.
+    pub fn set_response_validation(&mut self, mode: ResponseValidationMode) {
+        self.response_validation = mode;
+    }
+
+    /// Choose how handler responses missing a declared response header, or sending one that
+    /// violates its schema, are handled (see [`super::response_headers`]).
+    pub fn set_response_header_validation(&mut self, mode: ResponseValidationMode) {
+        self.response_headers.mode = mode;
+    }
+
+    /// Never warn about these response headers being undocumented, beyond
+    /// [`ALWAYS_ALLOWED`](super::response_headers::ALWAYS_ALLOWED).
+    pub fn allow_response_headers<I, S>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.response_headers = std::mem::take(&mut self.response_headers).allow(names);
+    }
+
+    /// Indent JSON response bodies (handler responses and problem details).
+    pub fn set_pretty_json(&mut self, pretty: bool) {
+        self.pretty_json = pretty;
+    }
+
+    /// Enable development conveniences: `?__pretty=1` indents that request's JSON response.
+    pub fn set_dev_mode(&mut self, dev_mode: bool) {
+        self.dev_mode = dev_mode;
+    }
+
+    /// Serve 404s (no route for the path) with `handler` instead of the default problem.
+    ///
+    /// The handler gets the request and the default problem and may, for example, return an
+    /// HTML page when `Accept` prefers `text/html`. Middleware `after` hooks run on its
+    /// response.
+    pub fn on_not_found<F>(&mut self, handler: F)
+    where
+        F: Fn(&HandlerRequest, &ProblemDetails) -> HandlerResponse + Send + Sync + 'static,
+    {
+        self.fallbacks.not_found = Some(Arc::new(handler));
+    }
+
+    /// Serve 405s (path routed, method not) with `handler`; `Allow` is added when it omits it.
+    pub fn on_method_not_allowed<F>(&mut self, handler: F)
+    where
+        F: Fn(&HandlerRequest, &ProblemDetails) -> HandlerResponse + Send + Sync + 'static,
+    {
+        self.fallbacks.method_not_allowed = Some(Arc::new(handler));
+    }
+
+    /// Serve framework 500s (handler not registered or unreachable, schema configuration
+    /// error, response validation failure) with `handler`. Handler-returned 500s are sent
+    /// unchanged.
+    pub fn on_internal_error<F>(&mut self, handler: F)
+    where
+        F: Fn(&HandlerRequest, &ProblemDetails) -> HandlerResponse + Send + Sync + 'static,
+    {
+        self.fallbacks.internal_error = Some(Arc::new(handler));
+    }
+
+    /// Count a rejected request once per failure category, labelled by its route template.
+    fn record_validation_failures(
+        &self,
+        route_match: &RouteMatch,
+        categories: &[ValidationFailureCategory],
+    ) {
+        if let Some(metrics) = &self.metrics {
+            for category in categories {
+                metrics.inc_validation_failure(
+                    &route_label(&route_match.route.path_pattern),
+                    *category,
+                );
+            }
+        }
+    }
+
+    /// Validate the route's declared parameters against their schemas.
+    ///
+    /// Raw values are decoded first (the same `string → integer/number/boolean` coercion
+    /// typed handlers see), so `minimum` / `maximum` / `multipleOf` apply to numbers while
+    /// `enum`, `const`, `pattern` and `minLength` / `maxLength` apply as written in the spec.
+    /// Header names match case-insensitively (RFC 9110); cookie and query names are
+    /// case-sensitive. Query keys are canonicalized first (see
+    /// [`canonicalize_query_params`]); occurrences that remain repeated are validated as
+    /// one array, except that a repeated scalar under `strict_duplicates` is rejected.
+    ///
+    /// A missing required parameter, or an invalid value, yields a 400 problem naming the
+    /// parameter and its location.
+    fn check_params(
+        &self,
+        route_match: &RouteMatch,
+        headers: &HeaderVec,
+        cookies: &HeaderVec,
+    ) -> Option<ProblemDetails> {
+        for param in &route_match.route.parameters {
+            let (location, values): (&str, SmallVec<[&str; 2]>) = match param.location {
+                ParameterLocation::Path => (
+                    "path",
+                    route_match
+                        .path_params
+                        .iter()
+                        .filter(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str())
+                        .collect(),
+                ),
+                ParameterLocation::Query => (
+                    "query",
+                    route_match
+                        .query_params
+                        .iter()
+                        .filter(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str())
+                        .collect(),
+                ),
+                ParameterLocation::Header => {
+                    ("header", headers.get(&param.name).into_iter().collect())
+                }
+                ParameterLocation::Cookie => (
+                    "cookie",
+                    cookies
+                        .iter()
+                        .find(|(k, _)| k.as_ref() == param.name)
+                        .map(|(_, v)| v.as_str())
+                        .into_iter()
+                        .collect(),
+                ),
+            };
+            let problem = |detail: String| {
+                ProblemDetails::new(400)
+                    .detail(detail)
+                    .extension("parameter", param.name.as_str())
+                    .extension("in", location)
+            };
+            if values.is_empty() {
+                if param.required {
+                    self.record_validation_failures(
+                        route_match,
+                        &[ValidationFailureCategory::MissingRequired],
+                    );
+                    return Some(problem(format!(
+                        "Missing required {location} parameter '{}'",
+                        param.name
+                    )));
+                }
+                continue;
+            }
+            let Some(schema) = &param.schema else {
+                continue;
+            };
+            let is_array = schema.get("type").and_then(Value::as_str) == Some("array");
+            if values.len() > 1 && !is_array && self.query.strict_duplicates {
+                self.record_validation_failures(
+                    route_match,
+                    &[ValidationFailureCategory::TypeMismatch],
+                );
+                return Some(problem(format!(
+                    "Repeated {location} parameter '{}' is not allowed",
+                    param.name
+                )));
+            }
+            let Some(validator) = self.validator_cache.get_or_compile(
+                &route_match.handler_name,
+                "parameter",
+                None,
+                schema,
+            ) else {
+                continue;
+            };
+            // Repeated keys left by canonicalization (exploded arrays, `duplicates: array`)
+            // are one multi-valued parameter.
+            let Some(value) = decode_query_values(values, Some(schema), param.style, param.explode)
+            else {
+                continue;
+            };
+            if !validator.is_valid(&value) {
+                let (field_errors, categories) = schema_failures(&validator, &value);
+                self.record_validation_failures(route_match, &categories);
+                return Some(
+                    problem(format!("Invalid {location} parameter '{}'", param.name))
+                        .errors(field_errors),
+                );
+            }
         }
+        None
     }
 
     /// Pre-compile and cache all JSON schemas from routes at startup
@@ -354,7 +991,11 @@ impl AppService {
     /// # Example
     ///
     /// ```rust,ignore
-    /// let mut service = AppService::new(router, dispatcher, schemes, spec_path, None, None);
+    /// let service = AppService::builder()
+    ///     .shared_router(router)
+    ///     .shared_dispatcher(dispatcher)
+    ///     .spec_path(spec_path)
+    ///     .build()?;
     /// let compiled = service.precompile_schemas(&routes);
     /// println!("Pre-compiled {} schemas", compiled);
     /// ```
@@ -368,11 +1009,14 @@ impl AppService {
     /// `security_providers.get()`) with pre-computed concrete references.
     /// Call this after [`register_default_security_providers_from_env`].
     ///
+    /// Routes naming a scheme the spec does not define (kept by
+    /// `BRRTR_UNDEFINED_SECURITY_SCHEMES=lenient`) are left to the per-request check, where
+    /// that requirement always fails (`401`).
+    ///
     /// # Safety
     ///
-    /// Panics if a route references a security scheme name that has no
-    /// corresponding scheme or provider registered. This is a server
-    /// configuration error.
+    /// Panics if a route references a defined security scheme that has no
+    /// provider registered. This is a server configuration error.
     pub fn resolve_security(&mut self, routes: &[crate::spec::RouteMeta]) {
         let mut lookup = HashMap::with_capacity(routes.len() * 2);
 
@@ -380,6 +1024,19 @@ impl AppService {
             if route.security.is_empty() {
                 continue;
             }
+            if let Some(scheme_name) = route
+                .security
+                .iter()
+                .flat_map(|req| req.0.keys())
+                .find(|name| !self.security_schemes.contains_key(name.as_str()))
+            {
+                warn!(
+                    handler = %route.handler_name,
+                    scheme_name = %scheme_name,
+                    "Security scheme not found; route fails closed"
+                );
+                continue;
+            }
 
             let requirements: Vec<Vec<ResolvedSecurityRequirement>> = route
                 .security
@@ -425,98 +1082,181 @@ impl AppService {
         );
     }
 
+    /// Build the [`AuthContext`] for a request whose `security[group_idx]` alternative validated.
+    ///
+    /// Calls [`SecurityProvider::extract_claims`] for each scheme of that group and uses the
+    /// first that returns claims; when none do, the context names the group's first scheme
+    /// without claims. `None` for the anonymous `{}` alternative.
+    fn auth_context_for(
+        &self,
+        route: &crate::spec::RouteMeta,
+        group_idx: usize,
+        sec_req: &SecurityRequest,
+    ) -> Option<AuthContext> {
+        let group = &route.security.get(group_idx)?.0;
+        let claims = group.iter().find_map(|(scheme_name, _)| {
+            let provider = self.security_providers.get(scheme_name)?;
+            let scheme = self.security_schemes.get(scheme_name)?;
+            provider
+                .extract_claims(scheme, sec_req)
+                .map(|claims| (scheme_name, claims))
+        });
+        match claims {
+            Some((scheme_name, claims)) => {
+                Some(AuthContext::from_claims(scheme_name.as_str(), Some(claims)))
+            }
+            None => group
+                .iter()
+                .next()
+                .map(|(scheme_name, _)| AuthContext::from_claims(scheme_name.as_str(), None)),
+        }
+    }
+
     /// Register default security providers based on loaded OpenAPI security schemes.
     ///
-    /// This wires ApiKey, Bearer, and OAuth2 providers using environment variables or a
-    /// provided test key for development. For ApiKey schemes, the following configuration
-    /// is used (in order): per-scheme env `BRRTR_API_KEY__<SCHEME_NAME>`, global env
-    /// `BRRTR_API_KEY`, then `test_api_key` argument, then fallback `"test123"`.
-    pub fn register_default_security_providers_from_env(&mut self, test_api_key: Option<String>) {
-        use std::sync::Arc as SyncArc;
-
-        struct ApiKeyProvider {
-            key: String,
-        }
-        impl SecurityProvider for ApiKeyProvider {
-            fn validate(
-                &self,
-                scheme: &SecurityScheme,
-                _scopes: &[String],
-                req: &SecurityRequest,
-            ) -> bool {
-                match scheme {
-                    SecurityScheme::ApiKey { name, location, .. } => match location.as_str() {
-                        "header" => {
-                            // Accept either the named header or Authorization: Bearer <key> for migration convenience
-                            let header_ok = req
-                                .get_header(&name.to_ascii_lowercase())
-                                .map(|v| v == self.key)
-                                .unwrap_or(false);
-                            let auth_ok = req
-                                .get_header("authorization")
-                                .and_then(|h| h.strip_prefix("Bearer "))
-                                .map(|v| v == self.key)
-                                .unwrap_or(false);
-                            header_ok || auth_ok
-                        }
-                        "query" => req.get_query(name).map(|v| v == self.key).unwrap_or(false),
-                        "cookie" => req.get_cookie(name).map(|v| v == self.key).unwrap_or(false),
-                        _ => false,
-                    },
-                    _ => false,
+    /// For each apiKey, bearer and OAuth2 scheme, `policy` lists where a provider may come
+    /// from, in order: a provider already registered for the scheme (`config`), `BRRTR_*`
+    /// environment variables (`env`), or development credentials (`mock`). For ApiKey
+    /// schemes `env` reads `BRRTR_API_KEY__<SCHEME_NAME>`, then `BRRTR_API_KEY`; `mock` uses
+    /// `test_api_key`, else `"test123"`. See [`super::default_providers`].
+    ///
+    /// # Errors
+    ///
+    /// Names every scheme no source of `policy` covered, e.g. under
+    /// [`DefaultProviderPolicy::strict`] when a scheme is neither configured nor set in the
+    /// environment. Schemes that were covered are registered either way.
+    pub fn register_default_security_providers_from_env(
+        &mut self,
+        test_api_key: Option<String>,
+        policy: &DefaultProviderPolicy,
+    ) -> Result<(), MissingProviderError> {
+        let mut schemes: Vec<_> = self.security_schemes.clone().into_iter().collect();
+        schemes.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut missing = Vec::new();
+        for (scheme_name, scheme) in schemes {
+            let Some(kind) = SchemeKind::of(&scheme) else {
+                continue;
+            };
+            let found = policy.order(kind).iter().find_map(|&source| {
+                self.default_provider(&scheme_name, kind, source, test_api_key.as_deref())
+                    .map(|provider| (source, provider))
+            });
+            match found {
+                Some((source, provider)) => {
+                    if source == ProviderSource::Mock {
+                        tracing::warn!(
+                            scheme = %scheme_name,
+                            kind = kind.as_str(),
+                            "Using mock security provider"
+                        );
+                    }
+                    self.register_security_provider(&scheme_name, provider);
                 }
+                None => missing.push((scheme_name, kind)),
+            }
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingProviderError { schemes: missing })
+        }
+    }
+
+    /// Provider for `scheme_name` from `source`, if that source has one
+    fn default_provider(
+        &self,
+        scheme_name: &str,
+        kind: SchemeKind,
+        source: ProviderSource,
+        test_api_key: Option<&str>,
+    ) -> Option<Arc<dyn SecurityProvider>> {
+        match (source, kind) {
+            (ProviderSource::Config, _) => self.security_providers.get(scheme_name).cloned(),
+            (ProviderSource::Env, SchemeKind::ApiKey) => {
+                // Per-scheme env: BRRTR_API_KEY__<SCHEME_NAME>
+                let env_key_name = format!(
+                    "BRRTR_API_KEY__{}",
+                    scheme_name
+                        .chars()
+                        .map(|c| if c.is_ascii_alphanumeric() {
+                            c.to_ascii_uppercase()
+                        } else {
+                            '_'
+                        })
+                        .collect::<String>()
+                );
+                std::env::var(&env_key_name)
+                    .or_else(|_| std::env::var("BRRTR_API_KEY"))
+                    .ok()
+                    .map(|key| Arc::new(DefaultApiKeyProvider { key }) as Arc<dyn SecurityProvider>)
+            }
+            (ProviderSource::Env, SchemeKind::Bearer) => {
+                std::env::var("BRRTR_BEARER_SIGNATURE").ok().map(|sig| {
+                    Arc::new(crate::security::BearerJwtProvider::new(sig))
+                        as Arc<dyn SecurityProvider>
+                })
+            }
+            (ProviderSource::Env, SchemeKind::OAuth2) => {
+                std::env::var("BRRTR_OAUTH2_SIGNATURE").ok().map(|sig| {
+                    Arc::new(crate::security::OAuth2Provider::new(sig)) as Arc<dyn SecurityProvider>
+                })
+            }
+            (ProviderSource::Mock, SchemeKind::ApiKey) => Some(Arc::new(DefaultApiKeyProvider {
+                key: test_api_key.unwrap_or("test123").to_string(),
+            })),
+            // Simple development providers; real validation can be plugged in by the user
+            (ProviderSource::Mock, SchemeKind::Bearer) => {
+                Some(Arc::new(crate::security::BearerJwtProvider::new("sig")))
+            }
+            (ProviderSource::Mock, SchemeKind::OAuth2) => {
+                Some(Arc::new(crate::security::OAuth2Provider::new("sig")))
             }
         }
+    }
+}
 
-        for (scheme_name, scheme) in self.security_schemes.clone() {
-            match scheme {
-                SecurityScheme::ApiKey { .. } => {
-                    // Per-scheme env: BRRTR_API_KEY__<SCHEME_NAME>
-                    let env_key_name = format!(
-                        "BRRTR_API_KEY__{}",
-                        scheme_name
-                            .chars()
-                            .map(|c| if c.is_ascii_alphanumeric() {
-                                c.to_ascii_uppercase()
-                            } else {
-                                '_'
-                            })
-                            .collect::<String>()
-                    );
-                    let key = std::env::var(&env_key_name)
-                        .ok()
-                        .or_else(|| std::env::var("BRRTR_API_KEY").ok())
-                        .or_else(|| test_api_key.clone())
-                        .unwrap_or_else(|| "test123".to_string());
-                    self.register_security_provider(
-                        &scheme_name,
-                        SyncArc::new(ApiKeyProvider { key }),
-                    );
-                }
-                SecurityScheme::Http { ref scheme, .. }
-                    if scheme.eq_ignore_ascii_case("bearer") =>
-                {
-                    // Simple development bearer provider; real validation can be plugged in by user
-                    let provider = crate::security::BearerJwtProvider::new(
-                        std::env::var("BRRTR_BEARER_SIGNATURE").unwrap_or_else(|_| "sig".into()),
-                    );
-                    self.register_security_provider(&scheme_name, SyncArc::new(provider));
-                }
-                SecurityScheme::OAuth2 { .. } => {
-                    let provider = crate::security::OAuth2Provider::new(
-                        std::env::var("BRRTR_OAUTH2_SIGNATURE").unwrap_or_else(|_| "sig".into()),
-                    );
-                    self.register_security_provider(&scheme_name, SyncArc::new(provider));
+/// API key provider wired by [`AppService::register_default_security_providers_from_env`]
+struct DefaultApiKeyProvider {
+    key: String,
+}
+
+impl SecurityProvider for DefaultApiKeyProvider {
+    fn validate(&self, scheme: &SecurityScheme, _scopes: &[String], req: &SecurityRequest) -> bool {
+        match scheme {
+            SecurityScheme::ApiKey { name, location, .. } => match location.as_str() {
+                "header" => {
+                    // Accept either the named header or Authorization: Bearer <key> for migration convenience
+                    let header_ok = req
+                        .get_header(&name.to_ascii_lowercase())
+                        .map(|v| v == self.key)
+                        .unwrap_or(false);
+                    let auth_ok = req
+                        .get_header("authorization")
+                        .and_then(|h| h.strip_prefix("Bearer "))
+                        .map(|v| v == self.key)
+                        .unwrap_or(false);
+                    header_ok || auth_ok
                 }
-                _ => {}
-            }
+                "query" => req.get_query(name).map(|v| v == self.key).unwrap_or(false),
+                "cookie" => req.get_cookie(name).map(|v| v == self.key).unwrap_or(false),
+                _ => false,
+            },
+            _ => false,
         }
     }
+
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        self.validate(scheme, scopes, req).into()
+    }
 }
 
 /// Basic health check endpoint returning `{ "status": "ok" }`.
 pub fn health_endpoint(res: &mut Response) -> io::Result<()> {
-    use crate::dispatcher::HeaderVec;
     write_handler_response(
         res,
         200,
@@ -629,6 +1369,19 @@ pub fn metrics_endpoint(
         metrics.auth_failures()
     );
 
+    body.push_str(
+        "# HELP brrtrouter_request_validation_failures_total Requests rejected by request validation, by route and failure category\n",
+    );
+    body.push_str("# TYPE brrtrouter_request_validation_failures_total counter\n");
+    for ((path, category), count) in metrics.validation_failure_stats() {
+        let escaped_path = escape_prometheus_label(&path);
+        let _ = writeln!(
+            body,
+            "brrtrouter_request_validation_failures_total{{path=\"{escaped_path}\",category=\"{}\"}} {count}",
+            category.as_str()
+        );
+    }
+
     // PRD Phase 0.3: visibility into the path-metrics soft cap (see
     // BRRTR_METRICS_PATH_MAX). Non-zero means one or more requests were
     // recorded against the `__other` bucket because the cap was reached.
@@ -758,6 +1511,172 @@ pub fn metrics_endpoint(
         }
     }
 
+    // Handler panic guards (breaker state, panic/trip/respawn counters)
+    if let Some(disp) = dispatcher {
+        let mut guards: Vec<_> = disp.panic_guard_stats().into_iter().collect();
+        guards.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        if !guards.is_empty() {
+            body.push_str("\n# Handler Panic Guards\n");
+            let series: [(&str, &str, &str, fn(&PanicGuardStats) -> u64); 4] = [
+                (
+                    "brrtrouter_handler_breaker_open",
+                    "gauge",
+                    "Whether the handler's panic breaker is open (1) or closed (0)",
+                    |s| u64::from(s.breaker_open),
+                ),
+                (
+                    "brrtrouter_handler_panics_total",
+                    "counter",
+                    "Handler panics caught by the dispatcher",
+                    |s| s.panics,
+                ),
+                (
+                    "brrtrouter_handler_breaker_trips_total",
+                    "counter",
+                    "Times the handler's panic breaker tripped",
+                    |s| s.trips,
+                ),
+                (
+                    "brrtrouter_handler_respawns_total",
+                    "counter",
+                    "Times the handler coroutine was respawned after repeated panics",
+                    |s| s.respawns,
+                ),
+            ];
+            for (name, kind, help, value) in series {
+                let _ = writeln!(body, "# HELP {name} {help}");
+                let _ = writeln!(body, "# TYPE {name} {kind}");
+                for (handler, stats) in &guards {
+                    let escaped_handler = escape_prometheus_label(handler);
+                    let _ = writeln!(
+                        body,
+                        "{name}{{handler=\"{escaped_handler}\"}} {}",
+                        value(stats)
+                    );
+                }
+            }
+        }
+    }
+
+    // Per-handler coroutine stack peaks (BRRTR_STACK_GUARD=on)
+    let watermarks = crate::dispatcher::stack_watermarks();
+    if !watermarks.is_empty() {
+        body.push_str("\n# Coroutine Stack Watermarks\n");
+        body.push_str(
+            "# HELP brrtrouter_coroutine_stack_size_bytes Stack size of the handler coroutine\n",
+        );
+        body.push_str("# TYPE brrtrouter_coroutine_stack_size_bytes gauge\n");
+        for (handler, size, _) in &watermarks {
+            let escaped_handler = escape_prometheus_label(handler);
+            let _ = writeln!(
+                body,
+                "brrtrouter_coroutine_stack_size_bytes{{handler=\"{escaped_handler}\"}} {size}",
+            );
+        }
+        body.push_str(
+            "# HELP brrtrouter_coroutine_stack_high_water_bytes Peak stack bytes used by the handler coroutine\n",
+        );
+        body.push_str("# TYPE brrtrouter_coroutine_stack_high_water_bytes gauge\n");
+        for (handler, _, peak) in &watermarks {
+            let escaped_handler = escape_prometheus_label(handler);
+            let _ = writeln!(
+                body,
+                "brrtrouter_coroutine_stack_high_water_bytes{{handler=\"{escaped_handler}\"}} {peak}",
+            );
+        }
+    }
+
+    // Allocations per request phase (`alloc-profiling` feature)
+    #[cfg(feature = "alloc-profiling")]
+    {
+        let phases = alloc_profile::snapshot();
+        body.push_str("\n# Allocation Profile\n");
+        let series: [(&str, &str, fn(&alloc_profile::PhaseStats) -> u64); 3] = [
+            (
+                "brrtrouter_alloc_phase_samples_total",
+                "Request phases measured for allocations",
+                |s| s.samples,
+            ),
+            (
+                "brrtrouter_alloc_phase_allocations_total",
+                "Heap allocations made during a request phase",
+                |s| s.allocations,
+            ),
+            (
+                "brrtrouter_alloc_phase_bytes_total",
+                "Heap bytes allocated during a request phase",
+                |s| s.bytes,
+            ),
+        ];
+        for (name, help, value) in series {
+            let _ = writeln!(body, "# HELP {name} {help}");
+            let _ = writeln!(body, "# TYPE {name} counter");
+            for (phase, stats) in &phases {
+                let _ = writeln!(
+                    body,
+                    "{name}{{phase=\"{}\"}} {}",
+                    phase.as_str(),
+                    value(stats)
+                );
+            }
+        }
+    }
+
+    // Per-route handler duration histograms and slow-request counters
+    let mut path_histograms: Vec<_> = metrics.path_histograms().into_iter().collect();
+    path_histograms.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    body.push_str(
+        "# HELP brrtrouter_handler_duration_seconds Handler duration per route template in seconds\n",
+    );
+    body.push_str("# TYPE brrtrouter_handler_duration_seconds histogram\n");
+    for (path, (buckets, sum_ns, count)) in &path_histograms {
+        let escaped_path = escape_prometheus_label(path);
+        for (boundary, bucket) in bucket_boundaries.iter().zip(buckets) {
+            let _ = writeln!(
+                body,
+                "brrtrouter_handler_duration_seconds_bucket{{path=\"{escaped_path}\",le=\"{boundary}\"}} {bucket}",
+            );
+        }
+        let _ = writeln!(
+            body,
+            "brrtrouter_handler_duration_seconds_bucket{{path=\"{escaped_path}\",le=\"+Inf\"}} {count}",
+        );
+        let sum_secs = *sum_ns as f64 / 1_000_000_000.0;
+        let _ = writeln!(
+            body,
+            "brrtrouter_handler_duration_seconds_sum{{path=\"{escaped_path}\"}} {sum_secs:.6}",
+        );
+        let _ = writeln!(
+            body,
+            "brrtrouter_handler_duration_seconds_count{{path=\"{escaped_path}\"}} {count}",
+        );
+    }
+
+    let mut slow_requests: Vec<_> = metrics.slow_request_stats().into_iter().collect();
+    slow_requests.sort_unstable();
+    body.push_str(
+        "# HELP brrtrouter_slow_requests_total Requests slower than the route's slow-request threshold\n",
+    );
+    body.push_str("# TYPE brrtrouter_slow_requests_total counter\n");
+    for (path, count) in &slow_requests {
+        let escaped_path = escape_prometheus_label(path);
+        let _ = writeln!(
+            body,
+            "brrtrouter_slow_requests_total{{path=\"{escaped_path}\"}} {count}",
+        );
+    }
+
+    body.push_str(
+        "# HELP brrtrouter_lock_poison_total Shared locks found poisoned by a panic and recovered, by lock\n",
+    );
+    body.push_str("# TYPE brrtrouter_lock_poison_total counter\n");
+    for (lock, count) in &crate::lock_poison::lock_poison_counts() {
+        let _ = writeln!(
+            body,
+            "brrtrouter_lock_poison_total{{lock=\"{lock}\"}} {count}"
+        );
+    }
+
     // Legacy per-path metrics (backward compatible)
     // Pre-escape all paths once to avoid repeated escaping
     let escaped_paths: HashMap<&String, String> = path_stats
@@ -819,7 +1738,6 @@ pub fn metrics_endpoint(
         body.push_str(&extra());
     }
 
-    use crate::dispatcher::HeaderVec;
     write_handler_response(
         res,
         200,
@@ -830,16 +1748,170 @@ pub fn metrics_endpoint(
     Ok(())
 }
 
-/// Streams the OpenAPI specification file as `text/yaml`.
-pub fn openapi_endpoint(res: &mut Response, spec_path: &Path) -> io::Result<()> {
-    match std::fs::read(spec_path) {
-        Ok(bytes) => {
-            res.status_code(200, "OK");
-            res.header("Content-Type: text/yaml");
-            res.body_vec(bytes);
-        }
+/// JSON metrics snapshot ([`MetricsMiddleware::snapshot`]), the same values `/metrics` exports.
+pub fn metrics_json_endpoint(res: &mut Response, metrics: &MetricsMiddleware) -> io::Result<()> {
+    write_handler_response(res, 200, metrics.snapshot(), false, &HeaderVec::new());
+    Ok(())
+}
+
+/// Whether the query asks for an indented response (`__pretty=1` / `__pretty=true`).
+fn pretty_query_override(query_params: &ParamVec) -> bool {
+    query_params
+        .iter()
+        .any(|(k, v)| &**k == "__pretty" && matches!(v.as_str(), "1" | "true"))
+}
+
+/// 413 problem when a declared body of `len` bytes exceeds `max`.
+pub(crate) fn body_too_large(len: usize, max: usize) -> Option<ProblemDetails> {
+    (len > max).then(|| {
+        ProblemDetails::new(413).detail(format!(
+            "Request body of {len} bytes exceeds the limit of {max} bytes"
+        ))
+    })
+}
+
+/// 404 problem for a path no route, built-in endpoint or static file serves.
+fn not_found_problem(method: &Method, path: &str) -> ProblemDetails {
+    ProblemDetails::new(404)
+        .detail(format!("No route for {method} {path}"))
+        .instance(path)
+}
+
+/// 405 problem for a path that has routes, but none for `method`.
+fn method_not_allowed_problem(method: &Method, path: &str) -> ProblemDetails {
+    ProblemDetails::new(405)
+        .detail(format!("Method {method} not allowed for {path}"))
+        .instance(path)
+}
+
+/// `Allow` header value for a path routed for `allowed`; `OPTIONS` is always answered, so
+/// it is listed even without an explicit OPTIONS operation.
+fn allow_methods(allowed: &[Method]) -> String {
+    let mut allow = String::new();
+    for (i, method) in allowed.iter().enumerate() {
+        if i > 0 {
+            allow.push_str(", ");
+        }
+        allow.push_str(method.as_str());
+    }
+    if !allowed.contains(&Method::OPTIONS) {
+        allow.push_str(", OPTIONS");
+    }
+    allow
+}
+
+/// `Content-Length` of a `HEAD` response: under [`HeadStrategy::Estimate`] the one the
+/// handler set, otherwise (or when it set none) the length of the body it returned, written
+/// as a `GET` response would be.
+fn head_content_length(
+    strategy: HeadStrategy,
+    status: u16,
+    body: &Value,
+    is_sse: bool,
+    headers: &HeaderVec,
+    pretty: bool,
+) -> usize {
+    let estimate = match strategy {
+        HeadStrategy::Estimate => headers
+            .get("content-length")
+            .and_then(|v| v.trim().parse::<usize>().ok()),
+        HeadStrategy::Compute => None,
+    };
+    estimate.unwrap_or_else(|| handler_body_len(status, body, is_sse, headers, pretty))
+}
+
+/// A parsed request on its way to [`AppService::handle_route`].
+pub(crate) struct RoutedRequest {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) headers: HeaderVec,
+    pub(crate) cookies: HeaderVec,
+    pub(crate) query_params: ParamVec,
+    pub(crate) body: Option<Value>,
+    pub(crate) raw_body: Option<bytes::Bytes>,
+    /// Declared (or actual) body length; non-zero bodies are checked against the declared
+    /// request content types.
+    pub(crate) body_size_bytes: usize,
+    pub(crate) request_id: RequestId,
+    /// A WebSocket opening handshake taken over by [`super::websocket`]; WebSocket routes
+    /// answer everything else with `426`.
+    pub(crate) upgrade: bool,
+    /// The head of an `Expect: 100-continue` request whose body is not sent yet: stop with
+    /// [`RouteOutcome::Continue`] after the content-type check (see [`super::expect`]).
+    pub(crate) expect_continue: bool,
+}
+
+/// Response decided for a request, before it is written to the connection or collected
+/// into a batch response.
+pub(crate) enum RouteOutcome {
+    /// Framework problem (`application/problem+json`) plus headers such as `WWW-Authenticate`.
+    Problem {
+        problem: ProblemDetails,
+        headers: HeaderVec,
+    },
+    /// Handler or fallback handler response.
+    Handler {
+        status: u16,
+        body: Value,
+        is_sse: bool,
+        headers: HeaderVec,
+    },
+    /// An authenticated, validated WebSocket handshake, to be completed by the caller.
+    Upgrade(Box<WebSocketRequest>),
+    /// An `Expect: 100-continue` head that passed every check needing no body.
+    Continue,
+}
+
+impl RouteOutcome {
+    pub(crate) fn problem(problem: ProblemDetails) -> Self {
+        Self::Problem {
+            problem,
+            headers: HeaderVec::new(),
+        }
+    }
+
+    /// The fallback handler's response when there is one, else `problem`.
+    pub(crate) fn fallback_or_problem(
+        fallback: Option<HandlerResponse>,
+        problem: ProblemDetails,
+    ) -> Self {
+        match fallback {
+            Some(hr) => Self::Handler {
+                status: hr.status,
+                body: hr.body,
+                is_sse: false,
+                headers: hr.headers,
+            },
+            None => Self::problem(problem),
+        }
+    }
+
+    pub(crate) fn with_header(mut self, name: &str, value: String) -> Self {
+        match &mut self {
+            Self::Problem { headers, .. } | Self::Handler { headers, .. } => {
+                headers.push((Arc::from(name), value));
+            }
+            Self::Upgrade(_) | Self::Continue => {}
+        }
+        self
+    }
+}
+
+/// `426` for a plain HTTP request to a WebSocket route.
+pub(crate) fn upgrade_required_problem() -> ProblemDetails {
+    ProblemDetails::new(426).detail("This operation requires a WebSocket upgrade")
+}
+
+/// Streams the OpenAPI specification file as `text/yaml`.
+pub fn openapi_endpoint(res: &mut Response, spec_path: &Path) -> io::Result<()> {
+    match std::fs::read(spec_path) {
+        Ok(bytes) => {
+            res.status_code(200, "OK");
+            res.header("Content-Type: text/yaml");
+            res.body_vec(bytes);
+        }
         Err(_) => {
-            write_json_error(res, 404, serde_json::json!({ "error": "Spec not found" }));
+            write_problem(res, &ProblemDetails::new(404).detail("Spec not found"));
         }
     }
     Ok(())
@@ -854,7 +1926,7 @@ pub fn swagger_ui_endpoint(res: &mut Response, docs: &StaticFiles) -> io::Result
             res.body_vec(bytes);
         }
         Err(_) => {
-            write_json_error(res, 404, serde_json::json!({ "error": "Docs not found" }));
+            write_problem(res, &ProblemDetails::new(404).detail("Docs not found"));
         }
     }
     Ok(())
@@ -932,7 +2004,7 @@ impl HttpService for AppService {
     /// This method is called from multiple coroutines concurrently.
     /// All shared state (Router, Dispatcher, etc.) uses Arc + Mutex/RwLock.
     fn call(&mut self, req: Request, res: &mut Response) -> io::Result<()> {
-        use tracing::{debug, error, info, info_span, warn, Span};
+        use tracing::{debug, info, info_span, warn, Span};
 
         /// Helper struct that logs request completion when dropped
         /// This ensures we log timing even if we return early
@@ -947,6 +2019,11 @@ impl HttpService for AppService {
             request_headers: String,
             /// Redacted response headers as JSON object string (set on respond_*).
             response_headers: Option<String>,
+            /// Indent JSON bodies (`pretty_json`, or `?__pretty=1` in dev mode).
+            pretty_json: bool,
+            /// Served through the relay, which frames `HEAD` responses from
+            /// [`relay::HEAD_LENGTH_HEADER`].
+            relayed: bool,
             span: Span,
         }
 
@@ -961,21 +2038,38 @@ impl HttpService for AppService {
                 self.response_headers = Some(default_sanitizer().headers_for_log(headers));
             }
 
-            fn respond_json_error(
-                &mut self,
-                res: &mut Response,
-                status: u16,
-                body: serde_json::Value,
-            ) {
-                self.record_http_status(status);
-                // JSON errors use default Content-Type only; still record empty map
+            /// Name the length a `GET` response would have had, for the relay to frame a
+            /// bodyless `HEAD` response with (`may_minihttp` sends `Content-Length: 0`).
+            fn head_length(&self, res: &mut Response, status: u16, length: impl FnOnce() -> usize) {
+                if self.relayed
+                    && self.method == Method::HEAD
+                    && response_status_allows_body(status)
+                {
+                    res.header(format!("{}: {}", relay::HEAD_LENGTH_HEADER, length()));
+                }
+            }
+
+            fn respond_problem(&mut self, res: &mut Response, problem: &ProblemDetails) {
+                let _serialize = alloc_profile::phase(Phase::Serialize);
+                self.record_http_status(problem.status);
+                // Problem responses use default Content-Type only; still record empty map
                 // so Discover always shows the field on completed requests.
                 if self.response_headers.is_none() {
                     self.response_headers = Some(
                         default_sanitizer().headers_for_log(&crate::dispatcher::HeaderVec::new()),
                     );
                 }
-                write_json_error(res, status, body);
+                write_problem_as(res, problem, self.pretty_json, self.method == Method::HEAD);
+                self.head_length(res, problem.status, || {
+                    let headers = crate::dispatcher::HeaderVec::new();
+                    handler_body_len(
+                        problem.status,
+                        &problem.to_value(),
+                        false,
+                        &headers,
+                        self.pretty_json,
+                    )
+                });
             }
 
             fn respond_handler(
@@ -986,9 +2080,47 @@ impl HttpService for AppService {
                 is_sse: bool,
                 headers: &crate::dispatcher::HeaderVec,
             ) {
+                let _serialize = alloc_profile::phase(Phase::Serialize);
                 self.record_http_status(status);
                 self.record_response_headers(headers);
-                write_handler_response(res, status, body, is_sse, headers);
+                // `HEAD` responses carry the computed `Content-Length` (see `handle_route`).
+                let head_length = headers
+                    .get("content-length")
+                    .and_then(|v| v.trim().parse::<usize>().ok());
+                write_handler_response_as(
+                    res,
+                    status,
+                    body,
+                    is_sse,
+                    headers,
+                    self.pretty_json,
+                    self.method == Method::HEAD,
+                );
+                if let Some(length) = head_length {
+                    self.head_length(res, status, || length);
+                }
+            }
+
+            fn respond(&mut self, res: &mut Response, outcome: RouteOutcome) {
+                match outcome {
+                    RouteOutcome::Problem { problem, headers } => {
+                        for (k, v) in &headers {
+                            res.header(format!("{k}: {v}"));
+                        }
+                        self.respond_problem(res, &problem);
+                    }
+                    RouteOutcome::Handler {
+                        status,
+                        body,
+                        is_sse,
+                        headers,
+                    } => self.respond_handler(res, status, body, is_sse, &headers),
+                    // Only `RoutedRequest::upgrade` / `expect_continue` requests produce
+                    // these; they never reach here.
+                    RouteOutcome::Upgrade(_) | RouteOutcome::Continue => {
+                        self.respond_problem(res, &upgrade_required_problem());
+                    }
+                }
             }
         }
 
@@ -1045,10 +2177,95 @@ impl HttpService for AppService {
             }
         }
 
+        // Behind a relay, only connections opened by the relay are served (see `super::relay`).
+        if let Some(token) = &self.relay_token {
+            if !self.relay_admitted {
+                let header = |name: &str| {
+                    req.headers()
+                        .iter()
+                        .find(|h| h.name.eq_ignore_ascii_case(name))
+                        .map(|h| h.value)
+                };
+                let presented =
+                    header(relay::RELAY_HEADER).is_some_and(|v| relay::token_matches(v, token));
+                if presented {
+                    self.relay_admitted = true;
+                    // Only trusted in the preface that carries the secret.
+                    self.peer_addr = header(relay::PEER_HEADER)
+                        .and_then(|v| std::str::from_utf8(v).ok())
+                        .and_then(|v| v.parse().ok());
+                    res.status_code(204, "No Content");
+                } else {
+                    warn!("Connection to the relay port without the relay secret");
+                    res.header("Connection: close");
+                    write_problem(
+                        res,
+                        &ProblemDetails::new(403).detail("Connect through the server's listener"),
+                    );
+                }
+                return Ok(());
+            }
+        }
+
         // Start timing immediately
         let request_start = std::time::Instant::now();
 
+        // Every response, including the early rejections below, carries the Server header.
+        if let Some(server) = &self.server_header {
+            res.header(server.clone());
+        }
+
+        let version = HttpVersion::from_minor(req.version());
+        if version < self.min_http_version {
+            warn!(version = %version, "Request HTTP version below minimum");
+            res.header("Connection: close");
+            write_problem(
+                res,
+                &ProblemDetails::new(505).detail(format!(
+                    "{version} is not supported; use {} or later",
+                    self.min_http_version
+                )),
+            );
+            return Ok(());
+        }
+
+        // Reject overlong URIs before they are decoded or routed
+        if let Some(detail) = uri_violation(req.path().len(), self.max_uri_bytes) {
+            warn!(detail = %detail, "Request URI over limit");
+            write_problem(res, &ProblemDetails::new(414).detail(detail));
+            return Ok(());
+        }
+
+        // Reject oversized header sections before copying them into a HeaderVec
+        if let Some(detail) = self
+            .header_limits
+            .violation(req.headers().iter().map(|h| (h.name.len(), h.value.len())))
+        {
+            warn!(detail = %detail, "Request headers over limit");
+            write_problem(res, &ProblemDetails::new(431).detail(detail));
+            return Ok(());
+        }
+
+        // Reject oversized bodies before they are parsed
+        if let Some(problem) = self.max_body_bytes.and_then(|max| {
+            req.headers()
+                .iter()
+                .find(|h| h.name.eq_ignore_ascii_case("content-length"))
+                .and_then(|h| std::str::from_utf8(h.value).ok())
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .and_then(|len| body_too_large(len, max))
+        }) {
+            warn!(detail = ?problem.detail, "Request body over limit");
+            write_problem(res, &problem);
+            return Ok(());
+        }
+
+        // Decided before parsing consumes the request: the redirect keeps the query string as
+        // sent.
+        let canonical_location = self.path_normalization.redirect_location(req.path());
+
         // Parse request and validate HTTP method
+        let parse_phase = alloc_profile::phase(Phase::Parse);
         let ParsedRequest {
             method,
             path,
@@ -1056,27 +2273,24 @@ impl HttpService for AppService {
             cookies,
             query_params,
             body,
-        } = match parse_request(req) {
+            raw_body,
+        } = match parse_request_with_limits(req, self.body_read_timeout, self.max_json_depth) {
             Ok(parsed) => parsed,
-            Err(invalid_method) => {
-                // Reject invalid HTTP methods with 400 Bad Request
-                write_json_error(
-                    res,
-                    400,
-                    serde_json::json!({
-                        "error": "Bad Request",
-                        "message": format!("Invalid HTTP method: {}", invalid_method)
-                    }),
-                );
+            Err(err) => {
+                // Invalid method: 400. Truncated body: 400, stalled body: 408; either way the
+                // rest of the stream cannot be trusted, so the connection is closed.
+                if err.is_body_error() {
+                    warn!(error = %err, "Request body incomplete");
+                    res.header("Connection: close");
+                }
+                write_problem(res, &err.problem());
                 return Ok(());
             }
         };
+        drop(parse_phase);
+        let path = self.path_normalization.normalize(path);
 
-        let tenant_id = headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case("x-tenant-id"))
-            .map(|(_, v)| v.as_str())
-            .unwrap_or("");
+        let tenant_id = headers.get("x-tenant-id").unwrap_or("");
 
         // Create a span for this request with key fields
         let span = info_span!(
@@ -1088,6 +2302,10 @@ impl HttpService for AppService {
             status = tracing::field::Empty,
             duration_ms = tracing::field::Empty,
             stack_used_kb = tracing::field::Empty,
+            "http.route" = tracing::field::Empty,
+            "brrt.handler" = tracing::field::Empty,
+            "brrt.route_match_us" = tracing::field::Empty,
+            "brrt.validation_us" = tracing::field::Empty,
         );
         let _enter = span.enter();
 
@@ -1097,9 +2315,8 @@ impl HttpService for AppService {
         // Calculate body size using Content-Length header if available
         // This avoids expensive JSON serialization in the hot path
         let body_size_bytes = headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
-            .and_then(|(_, v)| v.parse::<usize>().ok())
+            .get("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
             .unwrap_or(0);
 
         let total_size_bytes = header_size_bytes + body_size_bytes;
@@ -1121,6 +2338,9 @@ impl HttpService for AppService {
             http_status: None,
             request_headers: request_headers_log.clone(),
             response_headers: None,
+            pretty_json: self.pretty_json
+                || (self.dev_mode && pretty_query_override(&query_params)),
+            relayed: self.relay_admitted,
             span: span.clone(),
         };
 
@@ -1139,9 +2359,21 @@ impl HttpService for AppService {
 
         // Apply keep-alive headers early so all responses inherit them.
         // Owned `Box<str>` clone per response — freed with the response, no leak.
+        // An HTTP/1.0 client without `Connection: keep-alive` (or an HTTP/1.1 one sending
+        // `Connection: close`) gets `Connection: close` whatever the policy. With expect handling
+        // on, a relayed connection serves one request so the front listener sees every head.
+        let client_keeps_alive = version.keeps_alive(headers.get("connection"))
+            && !(self.expect_continue && self.relay_admitted);
         if let Some(ka) = &self.keep_alive_header {
-            res.header("Connection: keep-alive");
-            res.header(ka.clone());
+            self.connection_requests += 1;
+            if !client_keeps_alive || self.connection.closes_after(self.connection_requests) {
+                res.header("Connection: close");
+            } else {
+                res.header("Connection: keep-alive");
+                res.header(ka.clone());
+            }
+        } else if !client_keeps_alive {
+            res.header("Connection: close");
         }
 
         // Count every incoming request at top-level (even those short-circuited before dispatch)
@@ -1149,6 +2381,36 @@ impl HttpService for AppService {
             metrics.inc_top_level_request();
         }
 
+        if let Some(location) = canonical_location {
+            _request_logger.respond(res, self.canonical_path_redirect(location));
+            return Ok(());
+        }
+
+        // Behind a base path, only `/health` and the metrics exports may be reached without it.
+        let (path, outside_base_path) = match self.strip_base_path(path) {
+            Ok(path) => (path, false),
+            Err(path) => (path, true),
+        };
+        if outside_base_path
+            && !((matches!(path.as_str(), "/health" | "/metrics")
+                || self.metrics_json_path.as_deref() == Some(path.as_str()))
+                && self
+                    .base_path
+                    .as_ref()
+                    .is_some_and(BasePath::allows_unprefixed_builtins))
+        {
+            let outcome = self.handle_outside_base_path(
+                &method,
+                &path,
+                &headers,
+                &cookies,
+                &query_params,
+                RequestId::from_header_or_new(headers.get("x-request-id")),
+            );
+            _request_logger.respond(res, outcome);
+            return Ok(());
+        }
+
         if method == Method::GET && path == "/health" {
             _request_logger.record_http_status(200);
             return health_endpoint(res);
@@ -1172,14 +2434,18 @@ impl HttpService for AppService {
                     extra,
                 );
             } else {
-                _request_logger.respond_json_error(
-                    res,
-                    404,
-                    serde_json::json!({"error": "Not Found", "method": method.to_string(), "path": path}),
-                );
+                _request_logger.respond_problem(res, &not_found_problem(&method, &path));
                 return Ok(());
             }
         }
+        if method == Method::GET && self.metrics_json_path.as_deref() == Some(path.as_str()) {
+            if let Some(metrics) = &self.metrics {
+                _request_logger.record_http_status(200);
+                return metrics_json_endpoint(res, metrics);
+            }
+            _request_logger.respond_problem(res, &not_found_problem(&method, &path));
+            return Ok(());
+        }
         if method == Method::GET && path == "/openapi.yaml" {
             let status = if self.spec_path.exists() { 200 } else { 404 };
             _request_logger.record_http_status(status);
@@ -1187,8 +2453,12 @@ impl HttpService for AppService {
         }
         if method == Method::GET && path == "/docs" {
             if let Some(docs) = &self.doc_files {
+                let spec_url = self.base_path.as_ref().map_or_else(
+                    || "/openapi.yaml".to_string(),
+                    |b| b.prepend("/openapi.yaml"),
+                );
                 let status = if docs
-                    .load("index.html", Some(&json!({ "spec_url": "/openapi.yaml" })))
+                    .load("index.html", Some(&json!({ "spec_url": spec_url })))
                     .is_ok()
                 {
                     200
@@ -1198,11 +2468,8 @@ impl HttpService for AppService {
                 _request_logger.record_http_status(status);
                 return swagger_ui_endpoint(res, docs);
             } else {
-                _request_logger.respond_json_error(
-                    res,
-                    404,
-                    serde_json::json!({ "error": "Docs not configured" }),
-                );
+                _request_logger
+                    .respond_problem(res, &ProblemDetails::new(404).detail("Docs not configured"));
                 return Ok(());
             }
         }
@@ -1211,6 +2478,20 @@ impl HttpService for AppService {
             if let Some(sf) = &self.static_files {
                 let p = path.trim_start_matches('/');
                 let p = if p.is_empty() { "index.html" } else { p };
+                // Build-time `.br` / `.gz` siblings skip runtime compression entirely.
+                let precompressed = headers
+                    .get("accept-encoding")
+                    .and_then(|accept| sf.load_precompressed(p, accept));
+                if let Some((bytes, ct, encoding)) = precompressed {
+                    res.status_code(200, "OK");
+                    res.header(format!("Content-Type: {ct}"));
+                    res.header(format!("Content-Encoding: {encoding}"));
+                    res.header("Vary: Accept-Encoding");
+                    res.header("X-Content-Type-Options: nosniff");
+                    res.body_vec(bytes);
+                    _request_logger.record_http_status(200);
+                    return Ok(());
+                }
                 if let Ok((bytes, ct)) = sf.load(p, None) {
                     res.status_code(200, "OK");
                     // JSF P1: Pre-intern common Content-Type headers to avoid format! allocation
@@ -1238,6 +2519,8 @@ impl HttpService for AppService {
                         // Uncommon MIME — owned header, freed with the response.
                         res.header(format!("Content-Type: {ct}"));
                     }
+                    // The type comes from the file extension; browsers must not guess another.
+                    res.header("X-Content-Type-Options: nosniff");
                     res.body_vec(bytes);
                     _request_logger.record_http_status(200);
                     return Ok(());
@@ -1245,582 +2528,1076 @@ impl HttpService for AppService {
             }
         }
 
+        if method == Method::POST {
+            if let Some(batch) = self.batch.as_ref().filter(|b| b.path == path) {
+                let outcome = super::batch::handle_batch(self, batch, &headers, body);
+                _request_logger.respond(res, outcome);
+                return Ok(());
+            }
+        }
+
         // Determine/accept request id from headers; fallback to generated
-        let inbound_req_id = headers
-            .iter()
-            .find(|(k, _)| k.eq_ignore_ascii_case("x-request-id"))
-            .map(|(_, v)| v.as_str())
-            .filter(|s| !s.trim().is_empty());
+        let inbound_req_id = headers.get("x-request-id").filter(|s| !s.trim().is_empty());
         let canonical_req_id = RequestId::from_header_or_new(inbound_req_id);
 
         // Router lookup: lock-free ArcSwap load on the request path (PRD Phase 1).
         // No `RwLock::read()` → no reader queuing behind writers, no poison
         // surface. `load()` returns a `Guard<Arc<Router>>` that we auto-deref.
+        let lookup_start = Instant::now();
         let route_opt = self.router.load().route(method.clone(), &path);
-        if let Some(mut route_match) = route_opt {
-            route_match.query_params = query_params.clone();
-
+        span.record(
+            "brrt.route_match_us",
+            lookup_start.elapsed().as_micros() as u64,
+        );
+        match &route_opt {
+            Some(route_match) => {
+                span.record("http.route", &*route_label(&route_match.route.path_pattern));
+                span.record("brrt.handler", &*route_match.handler_name);
+            }
+            None => {
+                span.record("http.route", UNMATCHED_ROUTE);
+            }
+        }
+        let outcome = if let Some(route_match) = route_opt {
             // Update total_size_bytes with estimated body size if Content-Length was not available
             if body_size_bytes == 0 && body.is_some() {
                 if let Some(estimated) = route_match.route.estimated_request_body_bytes {
                     _request_logger.total_size_bytes = header_size_bytes + estimated;
                 }
             }
+            _request_logger.request_id = Some(canonical_req_id);
+            self.handle_route(
+                route_match,
+                RoutedRequest {
+                    method,
+                    path,
+                    headers,
+                    cookies,
+                    query_params,
+                    body,
+                    raw_body,
+                    body_size_bytes,
+                    request_id: canonical_req_id,
+                    upgrade: false,
+                    expect_continue: false,
+                },
+            )
+        } else {
+            self.handle_unrouted(
+                &method,
+                &path,
+                &headers,
+                &cookies,
+                &query_params,
+                canonical_req_id,
+            )
+        };
+        let outcome = self.prefix_outcome(outcome);
+        _request_logger.respond(res, outcome);
+        Ok(())
+    }
+}
 
-            // Perform security validation first
-            // JSF P2: Use pre-resolved security (security_lookup) to eliminate per-request
-            // HashMap lookups. Falls back to original per-request HashMap lookup if the
-            // pre-resolution table wasn't populated (backward-compatible with tests that
-            // don't call resolve_security at startup).
-            let use_preresolved = self
-                .security_lookup
-                .get(&route_match.handler_name)
-                .map(Arc::clone)
-                .filter(|rs| !rs.requirements.is_empty());
-
-            let mut authorized = route_match.route.security.is_empty();
-            let mut insufficient_scope = false;
-
-            // Helper to perform the actual auth check for both pre-resolved and raw paths
-            fn validate_requirements(
-                requirements: &[Vec<ResolvedSecurityRequirement>],
-                sec_req: &SecurityRequest,
-                insufficient_scope: &mut bool,
-            ) -> bool {
-                for req_group in requirements {
-                    let mut ok = true;
-                    for resolved in req_group {
-                        let auth_result = resolved.provider.validate(
-                            resolved.scheme.as_ref(),
-                            &resolved.scopes,
-                            sec_req,
-                        );
-
-                        if !auth_result {
-                            // Detect insufficient scope for Bearer/OAuth2: token valid but scopes missing
-                            match resolved.scheme.as_ref() {
-                                SecurityScheme::Http {
-                                    scheme: http_scheme,
-                                    ..
-                                } if http_scheme.eq_ignore_ascii_case("bearer") => {
-                                    if resolved.provider.validate(
-                                        resolved.scheme.as_ref(),
-                                        &[],
-                                        sec_req,
-                                    ) {
-                                        *insufficient_scope = true;
-                                    }
-                                }
-                                SecurityScheme::OAuth2 { .. } => {
-                                    if resolved.provider.validate(
-                                        resolved.scheme.as_ref(),
-                                        &[],
-                                        sec_req,
-                                    ) {
-                                        *insufficient_scope = true;
-                                    }
-                                }
-                                _ => {}
-                            }
-                            ok = false;
+impl AppService {
+    /// Run a routed request through authentication, parameter, content-type and body
+    /// validation, the handler, and response validation.
+    ///
+    /// Shared by [`HttpService::call`] and the batch endpoint (see [`super::batch`]); the
+    /// caller writes the returned outcome.
+    pub(crate) fn handle_route(
+        &self,
+        mut route_match: RouteMatch,
+        req: RoutedRequest,
+    ) -> RouteOutcome {
+        use tracing::{debug, error, warn};
+
+        let RoutedRequest {
+            method,
+            path,
+            mut headers,
+            mut cookies,
+            mut query_params,
+            body,
+            raw_body,
+            body_size_bytes,
+            request_id: canonical_req_id,
+            upgrade,
+            expect_continue,
+        } = req;
+        let validate_phase = alloc_profile::phase(Phase::Validate);
+        let validation_timer = ValidationTimer(Instant::now());
+        canonicalize_query_params(
+            &route_match.route.parameters,
+            &mut query_params,
+            self.query.duplicates,
+            self.query.strict_duplicates,
+        );
+        route_match.query_params = query_params.clone();
+
+        // Perform security validation first
+        // JSF P2: Use pre-resolved security (security_lookup) to eliminate per-request
+        // HashMap lookups. Falls back to original per-request HashMap lookup if the
+        // pre-resolution table wasn't populated (backward-compatible with tests that
+        // don't call resolve_security at startup).
+        let use_preresolved = self
+            .security_lookup
+            .get(&route_match.handler_name)
+            .map(Arc::clone)
+            .filter(|rs| !rs.requirements.is_empty());
+
+        let mut authorized = route_match.route.security.is_empty();
+        // Index of the `security` alternative that validated; claims come from its schemes.
+        let mut satisfied_group: Option<usize> = None;
+        let mut insufficient_scope = false;
+        // Scopes of the requirement whose token was valid but under-scoped (RFC 6750 §3),
+        // and those of them the token lacks
+        let mut demanded_scopes: &[String] = &[];
+        let mut missing_scopes: Vec<String> = Vec::new();
+
+        // Helper to perform the actual auth check for both pre-resolved and raw paths.
+        // Requirement objects are alternatives (OR); the schemes of one object must all
+        // validate (AND). Returns the index of the first requirement group that validated.
+        fn validate_requirements<'r>(
+            requirements: &'r [Vec<ResolvedSecurityRequirement>],
+            sec_req: &SecurityRequest,
+            insufficient_scope: &mut bool,
+            demanded_scopes: &mut &'r [String],
+            missing_scopes: &mut Vec<String>,
+        ) -> Option<usize> {
+            for (group_idx, req_group) in requirements.iter().enumerate() {
+                let mut rejected = false;
+                let mut scope_shortfall: Option<(&'r [String], Vec<String>)> = None;
+                for resolved in req_group {
+                    let scheme = resolved.scheme.as_ref();
+                    let provider = resolved.provider.as_ref();
+                    match provider.check(scheme, &resolved.scopes, sec_req) {
+                        AuthOutcome::Granted => {}
+                        AuthOutcome::InsufficientScope { missing } => {
+                            // Keep checking the other schemes: a group that also misses a
+                            // credential is a 401, not a 403.
+                            scope_shortfall.get_or_insert((&resolved.scopes, missing));
+                        }
+                        AuthOutcome::Unauthenticated => {
+                            rejected = true;
                             break;
                         }
                     }
-                    if ok {
-                        return true;
+                }
+                match (rejected, scope_shortfall) {
+                    (false, None) => return Some(group_idx),
+                    (false, Some((scopes, missing))) => {
+                        *insufficient_scope = true;
+                        *demanded_scopes = scopes;
+                        *missing_scopes = missing;
                     }
+                    (true, _) => {}
                 }
-                false
             }
+            None
+        }
 
-            if !route_match.route.security.is_empty() {
-                // S1: Security check start
+        if !route_match.route.security.is_empty() {
+            // S1: Security check start
+            debug!(
+                handler = %route_match.handler_name,
+                "Security check start"
+            );
+
+            let sec_req = SecurityRequest {
+                headers: &headers,
+                query: &query_params,
+                cookies: &cookies,
+            };
+
+            if let Some(resolved) = &use_preresolved {
+                // JSF P2: Pre-resolved path — zero HashMap lookups, already have scheme/provider
                 debug!(
                     handler = %route_match.handler_name,
-                    "Security check start"
+                    scheme_type = "pre-resolved",
+                    "Using pre-resolved security"
                 );
 
-                let sec_req = SecurityRequest {
-                    headers: &headers,
-                    query: &query_params,
-                    cookies: &cookies,
-                };
-
-                if let Some(resolved) = use_preresolved {
-                    // JSF P2: Pre-resolved path — zero HashMap lookups, already have scheme/provider
-                    debug!(
-                        handler = %route_match.handler_name,
-                        scheme_type = "pre-resolved",
-                        "Using pre-resolved security"
-                    );
-
-                    if validate_requirements(
-                        &resolved.requirements,
-                        &sec_req,
-                        &mut insufficient_scope,
-                    ) {
+                if let Some(group_idx) = validate_requirements(
+                    &resolved.requirements,
+                    &sec_req,
+                    &mut insufficient_scope,
+                    &mut demanded_scopes,
+                    &mut missing_scopes,
+                ) {
+                    authorized = true;
+                    satisfied_group = Some(group_idx);
+                }
+            } else {
+                // Fallback: per-request HashMap lookup (original behavior for backward compat)
+                for (group_idx, req) in route_match.route.security.iter().enumerate() {
+                    if req.0.is_empty() {
+                        // `{}` alternative: anonymous access is allowed (same as pre-resolved path)
                         authorized = true;
+                        satisfied_group = Some(group_idx);
+                        break;
                     }
-                } else {
-                    // Fallback: per-request HashMap lookup (original behavior for backward compat)
-                    for req in &route_match.route.security {
-                        if req.0.is_empty() {
-                            continue;
-                        }
-                        let mut ok = true;
-                        for (scheme_name, scopes) in &req.0 {
-                            // S2: Security scheme lookup
-                            debug!(
-                                scheme_name = %scheme_name,
-                                scheme_type = "lookup",
-                                "Security scheme lookup"
-                            );
+                    let mut ok = true;
+                    let mut scope_shortfall: Option<(&[String], Vec<String>)> = None;
+                    for (scheme_name, scopes) in &req.0 {
+                        // S2: Security scheme lookup
+                        debug!(
+                            scheme_name = %scheme_name,
+                            scheme_type = "lookup",
+                            "Security scheme lookup"
+                        );
 
-                            let scheme = match self.security_schemes.get(scheme_name.as_str()) {
-                                Some(s) => s,
-                                None => {
-                                    warn!(
-                                        handler = %route_match.handler_name,
-                                        scheme_name = %scheme_name,
-                                        "Security scheme not found"
-                                    );
-                                    ok = false;
-                                    break;
-                                }
-                            };
+                        let scheme = match self.security_schemes.get(scheme_name.as_str()) {
+                            Some(s) => s,
+                            None => {
+                                warn!(
+                                    handler = %route_match.handler_name,
+                                    scheme_name = %scheme_name,
+                                    "Security scheme not found"
+                                );
+                                ok = false;
+                                break;
+                            }
+                        };
 
-                            // S3: Provider lookup
-                            let provider = match self.security_providers.get(scheme_name.as_str()) {
-                                Some(p) => p,
-                                None => {
-                                    warn!(
-                                        handler = %route_match.handler_name,
-                                        scheme_name = %scheme_name,
-                                        "Security provider not found"
-                                    );
-                                    ok = false;
-                                    break;
-                                }
-                            };
+                        // S3: Provider lookup
+                        let provider = match self.security_providers.get(scheme_name.as_str()) {
+                            Some(p) => p,
+                            None => {
+                                warn!(
+                                    handler = %route_match.handler_name,
+                                    scheme_name = %scheme_name,
+                                    "Security provider not found"
+                                );
+                                ok = false;
+                                break;
+                            }
+                        };
+
+                        // S4: Provider validation start
+                        debug!(
+                            provider_type = %scheme_name,
+                            scopes = ?scopes,
+                            "Provider validation start"
+                        );
 
-                            // S4: Provider validation start
+                        // Measure authentication/authorization performance
+                        let auth_start = std::time::Instant::now();
+                        let outcome = provider.check(scheme, scopes, &sec_req);
+                        let auth_duration = auth_start.elapsed();
+                        let auth_result = outcome.is_granted();
+
+                        // Log slow authentication
+                        if auth_duration > Duration::from_millis(100) {
+                            warn!(
+                                provider_type = %scheme_name,
+                                duration_ms = auth_duration.as_millis(),
+                                success = auth_result,
+                                "Slow authentication detected"
+                            );
+                        } else {
+                            // Per-auth-request; demoted to debug (PRD 2.2).
                             debug!(
                                 provider_type = %scheme_name,
-                                scopes = ?scopes,
-                                "Provider validation start"
+                                duration_us = auth_duration.as_micros(),
+                                success = auth_result,
+                                "Authentication completed"
                             );
+                        }
 
-                            // Measure authentication/authorization performance
-                            let auth_start = std::time::Instant::now();
-                            let auth_result = provider.validate(scheme, scopes, &sec_req);
-                            let auth_duration = auth_start.elapsed();
-
-                            // Log slow authentication
-                            if auth_duration > Duration::from_millis(100) {
-                                warn!(
-                                    provider_type = %scheme_name,
-                                    duration_ms = auth_duration.as_millis(),
-                                    success = auth_result,
-                                    "Slow authentication detected"
-                                );
-                            } else {
-                                // Per-auth-request; demoted to debug (PRD 2.2).
-                                debug!(
-                                    provider_type = %scheme_name,
-                                    duration_us = auth_duration.as_micros(),
-                                    success = auth_result,
-                                    "Authentication completed"
-                                );
+                        match outcome {
+                            AuthOutcome::Granted => {}
+                            AuthOutcome::InsufficientScope { missing } => {
+                                // As in `validate_requirements`: the other schemes of the
+                                // group decide between 401 and 403.
+                                scope_shortfall.get_or_insert((scopes, missing));
                             }
-
-                            if !auth_result {
-                                // Detect insufficient scope for Bearer/OAuth2
-                                match scheme {
-                                    SecurityScheme::Http {
-                                        scheme: http_scheme,
-                                        ..
-                                    } if http_scheme.eq_ignore_ascii_case("bearer") => {
-                                        if provider.validate(scheme, &[], &sec_req) {
-                                            insufficient_scope = true;
-                                        }
-                                    }
-                                    SecurityScheme::OAuth2 { .. } => {
-                                        if provider.validate(scheme, &[], &sec_req) {
-                                            insufficient_scope = true;
-                                        }
-                                    }
-                                    _ => {}
-                                }
+                            AuthOutcome::Unauthenticated => {
                                 ok = false;
                                 break;
                             }
                         }
-                        if ok {
-                            authorized = true;
-                            break;
+                    }
+                    if ok {
+                        if let Some((scopes, missing)) = scope_shortfall {
+                            insufficient_scope = true;
+                            demanded_scopes = scopes;
+                            missing_scopes = missing;
+                            continue;
                         }
+                        authorized = true;
+                        satisfied_group = Some(group_idx);
+                        break;
                     }
                 }
             }
+        }
 
-            if !authorized {
-                if let Some(metrics) = &self.metrics {
-                    metrics.inc_auth_failure();
-                }
+        // x-auth-optional: absent or invalid credentials proceed as anonymous. Claims are
+        // never extracted for anonymous requests, so handlers can branch on `jwt_claims`.
+        let anonymous = !authorized && route_match.route.auth_optional;
+        if anonymous {
+            debug!(
+                method = %method,
+                path = %path,
+                handler = %route_match.handler_name,
+                "Optional authentication: proceeding anonymously"
+            );
+        } else if !authorized {
+            if let Some(metrics) = &self.metrics {
+                metrics.inc_auth_failure();
+            }
 
-                let status = if insufficient_scope { 403 } else { 401 };
-                let title = if status == 403 {
-                    "Forbidden"
-                } else {
-                    "Unauthorized"
-                };
-                let detail = if status == 403 {
-                    "Insufficient scope or permissions"
-                } else {
-                    "Missing or invalid credentials"
-                };
+            let status = if insufficient_scope { 403 } else { 401 };
+            let title = if status == 403 {
+                "Forbidden"
+            } else {
+                "Unauthorized"
+            };
+            let detail = if status == 403 {
+                "Insufficient scope or permissions"
+            } else {
+                "Missing or invalid credentials"
+            };
+
+            // S7: Validation failed (401) or S8: Insufficient scope (403)
+            if status == 403 {
+                // S8: Insufficient scope (403)
+                warn!(
+                    method = %method,
+                    path = %path,
+                    handler = %route_match.handler_name,
+                    status = 403,
+                    reason = "insufficient_scope",
+                    "Insufficient scope (403 forbidden)"
+                );
+            } else {
+                // S7: Validation failed (401)
+                warn!(
+                    method = %method,
+                    path = %path,
+                    handler = %route_match.handler_name,
+                    status = 401,
+                    reason = "invalid_credentials",
+                    "Authentication failed (401 unauthorized)"
+                );
+            }
+
+            let debug = std::env::var("BRRTR_DEBUG_VALIDATION")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false);
+
+            let www_authenticate = if status == 401 {
+                "Bearer error=\"invalid_token\"".to_string()
+            } else if demanded_scopes.is_empty() {
+                "Bearer error=\"insufficient_scope\"".to_string()
+            } else {
+                format!(
+                    "Bearer error=\"insufficient_scope\", scope=\"{}\"",
+                    demanded_scopes.join(" ")
+                )
+            };
+            let mut problem = ProblemDetails::new(status).title(title).detail(detail);
+            if status == 403 && !missing_scopes.is_empty() {
+                // Scope names come from the spec, never from the credential itself.
+                problem = problem.extension("missing_scopes", missing_scopes);
+            }
+            if debug {
+                problem = problem
+                    .extension("method", method.to_string())
+                    .extension("path", path.as_str())
+                    // Convert Arc<str> to &str for JSON serialization
+                    .extension("handler", route_match.route.handler_name.as_ref());
+            }
+            return RouteOutcome::problem(problem)
+                .with_header("WWW-Authenticate", www_authenticate);
+        } else {
+            // S6: Validation success — per-request, demoted to debug (PRD 2.2).
+            debug!(
+                method = %method,
+                path = %path,
+                handler = %route_match.handler_name,
+                "Authentication success"
+            );
+        }
+
+        // `Warning: 299` for each deprecated parameter the client sent, defaults aside
+        let deprecation_warnings: Vec<String> = deprecated_params_sent(
+            &route_match.route.parameters,
+            &route_match.query_params,
+            &headers,
+            &cookies,
+        )
+        .into_iter()
+        .map(|name| format!("299 - \"Deprecated parameter: {name}\""))
+        .collect();
 
-                // S7: Validation failed (401) or S8: Insufficient scope (403)
-                if status == 403 {
-                    // S8: Insufficient scope (403)
+        // Omitted optional parameters take their schema defaults, then are validated like
+        // sent ones.
+        apply_param_defaults(
+            &route_match.route.parameters,
+            &mut route_match.query_params,
+            &mut headers,
+        );
+
+        // V1b: Required / typed path, query, header and cookie parameters (400)
+        if let Some(problem) = self.check_params(&route_match, &headers, &cookies) {
+            warn!(
+                method = %method,
+                path = %path,
+                handler = %route_match.handler_name,
+                detail = ?problem.detail,
+                "Parameter validation failed"
+            );
+            return RouteOutcome::problem(problem);
+        }
+
+        // WebSocket routes have no HTTP handler: a validated handshake goes back to the
+        // WebSocket listener, any other request is told to upgrade.
+        if route_match.route.websocket {
+            if !upgrade {
+                return RouteOutcome::problem(upgrade_required_problem())
+                    .with_header("Upgrade", "websocket".to_string());
+            }
+            let auth_context = match satisfied_group {
+                Some(group_idx) if !anonymous => {
+                    let sec_req = SecurityRequest {
+                        headers: &headers,
+                        query: &route_match.query_params,
+                        cookies: &cookies,
+                    };
+                    self.auth_context_for(&route_match.route, group_idx, &sec_req)
+                }
+                _ => None,
+            };
+            return RouteOutcome::Upgrade(Box::new(WebSocketRequest {
+                handler_name: route_match.handler_name,
+                path,
+                path_params: route_match.path_params,
+                query_params: route_match.query_params,
+                headers,
+                cookies,
+                auth_context,
+                request_id: canonical_req_id,
+            }));
+        }
+
+        // V1a: Content-Type enforcement (415 Unsupported Media Type)
+        //
+        // If the request carries a body, the client's Content-Type must be
+        // one of the content types the operation declared in
+        // `requestBody.content` (e.g. `application/json`). An operation that
+        // declares only `application/json` will reject `multipart/form-data`
+        // here before any further processing, closing the multipart
+        // fabrication bypass that previously produced empty `{}` bodies.
+        //
+        // Empty bodies (body_size_bytes == 0) skip this check — they are
+        // handled by the "V2: Required body missing" step below.
+        //
+        // Operations with no `requestBody` declared
+        // (`request_content_types.is_empty()`) are not affected, to preserve
+        // backward-compatible behavior for GET / DELETE with accidental bodies.
+        if body_size_bytes > 0 && !route_match.route.request_content_types.is_empty() {
+            let client_content_type = headers
+                .get("content-type")
+                .map(|v| crate::server::request::primary_content_type(v).to_ascii_lowercase())
+                .unwrap_or_default();
+            if !client_content_type.is_empty() {
+                let declared = &route_match.route.request_content_types;
+                let accepted = declared.iter().any(|d| {
+                    crate::server::request::primary_content_type(d)
+                        .eq_ignore_ascii_case(client_content_type.as_str())
+                });
+                if !accepted {
                     warn!(
                         method = %method,
                         path = %path,
                         handler = %route_match.handler_name,
-                        status = 403,
-                        reason = "insufficient_scope",
-                        "Insufficient scope (403 forbidden)"
+                        client_content_type = %client_content_type,
+                        declared = ?declared,
+                        "Unsupported Media Type"
                     );
-                } else {
-                    // S7: Validation failed (401)
+                    // Advertise the accepted types via the `Accept-Post` header
+                    // (RFC 7231 §6.5.13 uses Accept-Post to indicate media types
+                    // accepted by the resource for POST — we include it on all
+                    // methods as a diagnostic aid for clients).
+                    let accept_post = declared.join(", ");
+                    return RouteOutcome::problem(
+                        ProblemDetails::new(415)
+                            .detail(format!(
+                                "Content-Type '{client_content_type}' not declared by this operation; accepted: {accept_post}"
+                            ))
+                            .extension("accepted", declared.clone()),
+                    )
+                    .with_header("Accept-Post", accept_post);
+                }
+            }
+        }
+
+        // The rest needs the body, which an `Expect: 100-continue` client sends only now.
+        if expect_continue {
+            return RouteOutcome::Continue;
+        }
+
+        // V1b: Upload limits of `multipart/form-data` fields (413 / 415), before the handler
+        // sees the body
+        if let (false, Some(raw)) = (route_match.route.upload_limits.is_empty(), &raw_body) {
+            let content_type = headers.get("content-type").unwrap_or_default();
+            if is_multipart_form_data(content_type) {
+                let checked = Multipart::parse(raw, content_type)
+                    .map_err(|e| ProblemDetails::new(400).detail(e.to_string()))
+                    .and_then(|multipart| {
+                        multipart
+                            .check_limits(&route_match.route.upload_limits)
+                            .map_err(|rejection| rejection.problem())
+                    });
+                if let Err(problem) = checked {
                     warn!(
                         method = %method,
                         path = %path,
                         handler = %route_match.handler_name,
-                        status = 401,
-                        reason = "invalid_credentials",
-                        "Authentication failed (401 unauthorized)"
+                        status = problem.status,
+                        detail = ?problem.detail,
+                        "Upload rejected"
                     );
+                    return RouteOutcome::problem(problem);
                 }
+            }
+        }
 
-                let debug = std::env::var("BRRTR_DEBUG_VALIDATION")
-                    .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-                    .unwrap_or(false);
-
-                if status == 401 {
-                    res.header("WWW-Authenticate: Bearer error=\"invalid_token\"");
-                } else {
-                    res.header("WWW-Authenticate: Bearer error=\"insufficient_scope\"");
-                }
-                let mut body = serde_json::json!({
-                    "type": "about:blank",
-                    "title": title,
-                    "status": status,
-                    "detail": detail
-                });
-                if debug {
-                    if let Some(map) = body.as_object_mut() {
-                        map.insert("method".to_string(), json!(method.to_string()));
-                        map.insert("path".to_string(), json!(path));
-                        map.insert(
-                            "handler".to_string(),
-                            // Convert Arc<str> to &str for JSON serialization
-                            json!(route_match.route.handler_name.as_ref()),
-                        );
-                    }
-                }
-                _request_logger.respond_json_error(res, status as u16, body);
-                return Ok(());
-            } else {
-                // S6: Validation success — per-request, demoted to debug (PRD 2.2).
-                debug!(
+        // V2a: A JSON body was sent but does not parse. Not the same as no body: an operation
+        // whose `requestBody` is optional must not run without the body the client meant.
+        if body.is_none()
+            && raw_body.is_some()
+            && !route_match.route.request_content_types.is_empty()
+        {
+            let content_type = headers
+                .get("content-type")
+                .map(crate::server::request::primary_content_type)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if content_type.is_empty()
+                || content_type == "application/json"
+                || content_type.ends_with("+json")
+            {
+                warn!(
                     method = %method,
                     path = %path,
                     handler = %route_match.handler_name,
-                    "Authentication success"
+                    "Request body is not valid JSON"
+                );
+                return RouteOutcome::problem(
+                    ProblemDetails::new(400).detail("Request body is not valid JSON"),
                 );
             }
+        }
 
-            // V1a: Content-Type enforcement (415 Unsupported Media Type)
-            //
-            // If the request carries a body, the client's Content-Type must be
-            // one of the content types the operation declared in
-            // `requestBody.content` (e.g. `application/json`). An operation that
-            // declares only `application/json` will reject `multipart/form-data`
-            // here before any further processing, closing the multipart
-            // fabrication bypass that previously produced empty `{}` bodies.
-            //
-            // Empty bodies (body_size_bytes == 0) skip this check — they are
-            // handled by the "V2: Required body missing" step below.
-            //
-            // Operations with no `requestBody` declared
-            // (`request_content_types.is_empty()`) are not affected, to preserve
-            // backward-compatible behavior for GET / DELETE with accidental bodies.
-            if body_size_bytes > 0 && !route_match.route.request_content_types.is_empty() {
-                let client_content_type = headers
-                    .iter()
-                    .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
-                    .map(|(_, v)| {
-                        crate::server::request::primary_content_type(v.as_str())
-                            .to_ascii_lowercase()
-                    })
-                    .unwrap_or_default();
-                if !client_content_type.is_empty() {
-                    let declared = &route_match.route.request_content_types;
-                    let accepted = declared.iter().any(|d| {
-                        crate::server::request::primary_content_type(d)
-                            .eq_ignore_ascii_case(client_content_type.as_str())
+        // V2: Required body missing
+        if route_match.route.request_body_required && body.is_none() {
+            let expected_content_type = "application/json";
+            warn!(
+                method = %method,
+                path = %path,
+                handler = %route_match.handler_name,
+                expected_content_type = %expected_content_type,
+                "Required body missing"
+            );
+            return RouteOutcome::problem(ProblemDetails::new(400).detail("Request body required"));
+        }
+
+        // V1 & V3: Request validation start and failure
+        if let (Some(schema), Some(body_val)) =
+            (route_match.route.request_validation_schema(), &body)
+        {
+            // V1: Request validation start (schema is operation requestBody, not necessarily #/components/schemas/*)
+            let schema_path = "(operation requestBody)";
+            // Avoid allocating `Vec<String>` for `required` on every request — log the raw JSON slice only at DEBUG.
+            debug!(
+                handler = %route_match.handler_name,
+                schema_present = true,
+                required = ?schema.get("required"),
+                "Request validation start"
+            );
+
+            // Use cached validator instead of compiling on every request
+            let compiled = match self.validator_cache.get_or_compile(
+                &route_match.handler_name,
+                "request",
+                None,
+                schema,
+            ) {
+                Some(v) => v,
+                None => {
+                    // Schema compilation failed - this is a server configuration error
+                    tracing::error!(handler = %route_match.handler_name, "Failed to compile request schema");
+                    let problem =
+                        ProblemDetails::new(500).detail("Request schema configuration error");
+                    let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+                        fallback_request(
+                            &method,
+                            &path,
+                            &headers,
+                            &cookies,
+                            &query_params,
+                            canonical_req_id,
+                            self.peer_addr,
+                        )
                     });
-                    if !accepted {
-                        warn!(
-                            method = %method,
-                            path = %path,
-                            handler = %route_match.handler_name,
-                            client_content_type = %client_content_type,
-                            declared = ?declared,
-                            "Unsupported Media Type"
-                        );
-                        // Advertise the accepted types via the `Accept-Post` header
-                        // (RFC 7231 §6.5.13 uses Accept-Post to indicate media types
-                        // accepted by the resource for POST — we include it on all
-                        // methods as a diagnostic aid for clients).
-                        let accept_post = declared.join(", ");
-                        res.header(format!("Accept-Post: {accept_post}"));
-                        _request_logger.respond_json_error(
-                            res,
-                            415,
-                            json!({
-                                "error": "Unsupported Media Type",
-                                "message": format!(
-                                    "Content-Type '{client_content_type}' not declared by this operation; accepted: {accept_post}"
-                                ),
-                                "accepted": declared,
-                            }),
-                        );
-                        return Ok(());
-                    }
+                    return RouteOutcome::fallback_or_problem(fallback, problem);
                 }
-            }
+            };
+            // Valid bodies: `is_valid` avoids constructing error iterators/objects (jsonschema docs).
+            // Invalid: collect up to MAX_JSON_SCHEMA_ERRORS — pathological bodies cannot burn unbounded CPU.
+            if !compiled.is_valid(body_val) {
+                // V3: Schema validation failed
+                let (field_errors, categories) = schema_failures(&compiled, body_val);
+                self.record_validation_failures(&route_match, &categories);
+                let error_details: Vec<&str> =
+                    field_errors.iter().map(|e| e.detail.as_str()).collect();
+                let invalid_fields: Vec<String> = error_details
+                    .iter()
+                    .filter_map(|e| {
+                        // Extract field names from error messages
+                        e.split('\'').nth(1).map(String::from)
+                    })
+                    .collect();
 
-            // V2: Required body missing
-            if route_match.route.request_body_required && body.is_none() {
-                let expected_content_type = "application/json";
                 warn!(
                     method = %method,
                     path = %path,
                     handler = %route_match.handler_name,
-                    expected_content_type = %expected_content_type,
-                    "Required body missing"
+                    errors = ?error_details,
+                    schema_path = %schema_path,
+                    invalid_fields = ?invalid_fields,
+                    "Request schema validation failed"
                 );
-                _request_logger.respond_json_error(
-                    res,
-                    400,
-                    json!({"error": "Request body required"}),
-                );
-                return Ok(());
-            }
 
-            // V1 & V3: Request validation start and failure
-            if let (Some(schema), Some(body_val)) = (&route_match.route.request_schema, &body) {
-                // V1: Request validation start (schema is operation requestBody, not necessarily #/components/schemas/*)
-                let schema_path = "(operation requestBody)";
-                // Avoid allocating `Vec<String>` for `required` on every request — log the raw JSON slice only at DEBUG.
-                debug!(
-                    handler = %route_match.handler_name,
-                    schema_present = true,
-                    required = ?schema.get("required"),
-                    "Request validation start"
+                return RouteOutcome::problem(
+                    ProblemDetails::new(400)
+                        .detail("Request validation failed")
+                        .errors(field_errors),
                 );
-
-                // Use cached validator instead of compiling on every request
-                let compiled = match self.validator_cache.get_or_compile(
-                    &route_match.handler_name,
-                    "request",
-                    None,
-                    schema,
-                ) {
-                    Some(v) => v,
-                    None => {
-                        // Schema compilation failed - this is a server configuration error
-                        tracing::error!(handler = %route_match.handler_name, "Failed to compile request schema");
-                        _request_logger.respond_json_error(
-                            res,
-                            500,
-                            serde_json::json!({
-                                "error": "Internal Server Error",
-                                "message": "Request schema configuration error"
-                            }),
-                        );
-                        return Ok(());
-                    }
-                };
-                // Valid bodies: `is_valid` avoids constructing error iterators/objects (jsonschema docs).
-                // Invalid: collect up to MAX_JSON_SCHEMA_ERRORS — pathological bodies cannot burn unbounded CPU.
-                if !compiled.is_valid(body_val) {
-                    // V3: Schema validation failed
-                    let error_details: Vec<String> = compiled
-                        .iter_errors(body_val)
-                        .take(MAX_JSON_SCHEMA_ERRORS)
-                        .map(|e| e.to_string())
-                        .collect();
-                    let invalid_fields: Vec<String> = error_details
-                        .iter()
-                        .filter_map(|e| {
-                            // Extract field names from error messages
-                            e.split('\'').nth(1).map(String::from)
-                        })
-                        .collect();
-
-                    warn!(
-                        method = %method,
-                        path = %path,
-                        handler = %route_match.handler_name,
-                        errors = ?error_details,
-                        schema_path = %schema_path,
-                        invalid_fields = ?invalid_fields,
-                        "Request schema validation failed"
-                    );
-
-                    _request_logger.respond_json_error(
-                        res,
-                        400,
-                        json!({"error": "Request validation failed", "details": error_details}),
-                    );
-                    return Ok(());
-                }
-            }
-            let is_sse = route_match.route.sse;
-            // Ensure RequestLogger has the request_id for completion logs
-            if _request_logger.request_id.is_none() {
-                _request_logger.request_id = Some(canonical_req_id);
             }
-
-            let handler_response = {
-                // Lock-free dispatcher load (PRD Phase 1).
-                let dispatcher = self.dispatcher.load();
-                // Determine or generate request id to pass into dispatcher
-                let req_id = _request_logger
-                    .request_id
-                    .unwrap_or(canonical_req_id)
-                    .to_string();
-                // Extract JWT claims if available (moved here to ensure it's in scope)
-                let jwt_claims = if !route_match.route.security.is_empty() {
+        }
+        let is_sse = route_match.route.sse;
+        drop(validation_timer);
+        drop(validate_phase);
+
+        let handler_response = {
+            // Lock-free dispatcher load (PRD Phase 1).
+            let dispatcher = self.dispatcher.load();
+            // Determine or generate request id to pass into dispatcher
+            let req_id = canonical_req_id.to_string();
+            // Identity from the schemes that validated (never for anonymous requests)
+            let auth_context = match satisfied_group {
+                Some(group_idx) if !anonymous => {
                     let sec_req = SecurityRequest {
                         headers: &headers,
                         query: &route_match.query_params,
                         cookies: &cookies,
                     };
-                    route_match.route.security.iter().find_map(|requirement| {
-                        requirement.0.iter().find_map(|(scheme_name, _)| {
-                            if let Some(provider) = self.security_providers.get(scheme_name) {
-                                if let Some(scheme) = self.security_schemes.get(scheme_name) {
-                                    provider.extract_claims(scheme, &sec_req)
-                                } else {
-                                    None
-                                }
+                    self.auth_context_for(&route_match.route, group_idx, &sec_req)
+                }
+                _ => None,
+            };
+
+            dispatcher.dispatch_with_auth(
+                route_match.clone(),
+                body,
+                raw_body,
+                headers.clone(),
+                // Cookies stay available to the internal-error fallback when one is set.
+                if self.fallbacks.handles(500) {
+                    cookies.clone()
+                } else {
+                    std::mem::take(&mut cookies)
+                },
+                req_id,
+                auth_context,
+                self.peer_addr,
+            )
+        };
+        let request_headers = &headers;
+        match handler_response {
+            Some(hr) => {
+                let mut headers = hr.headers.clone();
+                // Always echo X-Request-ID on the response if we have one
+                // JSF P2: Use Arc::from for header names (O(1) clone, no allocation)
+                headers.push((Arc::from("x-request-id"), canonical_req_id.to_string()));
+                let has_content_type = headers.get("content-type").is_some();
+                if response_status_allows_body(hr.status) && !has_content_type {
+                    if let Some(ct) = route_match.route.content_type_for(hr.status) {
+                        // JSF P2: Use Arc::from for header names (O(1) clone, no allocation)
+                        headers.push((Arc::from("content-type"), ct));
+                    }
+                }
+                // A `201` the handler gave no `Location` points at the created resource's
+                // `GET` route, by the id in its body (see `RouteMeta::created_location`).
+                if hr.status == 201 && headers.get("location").is_none() {
+                    if let Some(location) =
+                        route_match.route.created_location.as_ref().and_then(|c| {
+                            // Parent parameters go back into the URL as received.
+                            let params = if route_match.raw_path_params.is_empty() {
+                                &route_match.path_params
                             } else {
-                                None
-                            }
+                                &route_match.raw_path_params
+                            };
+                            c.for_body(&hr.body, params.iter().map(|(k, v)| (k, v)))
                         })
-                    })
-                } else {
-                    None
-                };
-
-                dispatcher.dispatch_with_request_id(
-                    route_match.clone(),
-                    body,
-                    headers.clone(),
-                    cookies,
-                    req_id,
-                    jwt_claims,
-                )
-            };
-            match handler_response {
-                Some(hr) => {
-                    let mut headers = hr.headers.clone();
-                    // Always echo X-Request-ID on the response if we have one
-                    // JSF P2: Use Arc::from for header names (O(1) clone, no allocation)
-                    if let Some(ref rid) = _request_logger.request_id {
-                        headers.push((Arc::from("x-request-id"), rid.to_string()));
+                    {
+                        headers.push((Arc::from("location"), location));
                     }
-                    let has_content_type = headers
-                        .iter()
-                        .any(|(k, _)| k.eq_ignore_ascii_case("content-type"));
-                    if response_status_allows_body(hr.status) && !has_content_type {
-                        if let Some(ct) = route_match.route.content_type_for(hr.status) {
-                            // JSF P2: Use Arc::from for header names (O(1) clone, no allocation)
-                            headers.push((Arc::from("content-type"), ct));
-                        }
+                }
+                for warning in deprecation_warnings {
+                    headers.push((Arc::from("warning"), warning));
+                }
+                if is_sse && self.response_validation != ResponseValidationMode::Off {
+                    self.warn_invalid_sse_events(&route_match.route, hr.status, &hr.body);
+                }
+                // An explicitly chosen success status (`typed::Created`, `HttpJson::new(201, ..)`)
+                // must be one the operation declares. A plain 200 stays lenient: it is what any
+                // `Serialize` return produces, declared or not.
+                if self.response_validation != ResponseValidationMode::Off
+                    && (201..300).contains(&hr.status)
+                    && !route_match.route.declares_status(hr.status)
+                {
+                    if self.response_validation == ResponseValidationMode::Warn {
+                        warn!(
+                            handler = %route_match.handler_name,
+                            status = hr.status,
+                            declared = ?route_match.route.declared_statuses,
+                            "Handler returned an undeclared status (warn mode, response sent)"
+                        );
+                    } else {
+                        error!(
+                            handler = %route_match.handler_name,
+                            status = hr.status,
+                            declared = ?route_match.route.declared_statuses,
+                            "Handler returned an undeclared status"
+                        );
+                        let problem = ProblemDetails::new(500)
+                            .detail("Handler returned a status the operation does not declare")
+                            .extension("returned_status", hr.status);
+                        let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+                            fallback_request(
+                                &method,
+                                &path,
+                                request_headers,
+                                &cookies,
+                                &query_params,
+                                canonical_req_id,
+                                self.peer_addr,
+                            )
+                        });
+                        return RouteOutcome::fallback_or_problem(fallback, problem);
                     }
-                    if let Some(schema) = if response_status_allows_body(hr.status) {
-                        response_body_schema_for_status(&route_match.route, hr.status)
+                }
+                let header_report = self.response_headers.check(
+                    &self.validator_cache,
+                    &route_match.route,
+                    hr.status,
+                    &headers,
+                );
+                if !header_report.undocumented.is_empty() {
+                    warn!(
+                        handler = %route_match.handler_name,
+                        status = hr.status,
+                        headers = ?header_report.undocumented,
+                        "Handler sent response headers the operation does not declare"
+                    );
+                }
+                if !header_report.errors.is_empty() {
+                    let error_details: Vec<&str> = header_report
+                        .errors
+                        .iter()
+                        .map(|e| e.detail.as_str())
+                        .collect();
+                    if self.response_headers.mode == ResponseValidationMode::Warn {
+                        warn!(
+                            handler = %route_match.handler_name,
+                            status = hr.status,
+                            errors = ?error_details,
+                            "Response header validation failed (warn mode, response sent)"
+                        );
                     } else {
-                        None
-                    } {
-                        // V6: Response validation start
-                        debug!(
+                        error!(
                             handler = %route_match.handler_name,
                             status = hr.status,
-                            schema_present = true,
-                            "Response validation start"
+                            errors = ?error_details,
+                            "Response header validation failed"
                         );
+                        let problem = ProblemDetails::new(500)
+                            .detail("Response header validation failed")
+                            .extension("headers", header_report.invalid)
+                            .errors(header_report.errors);
+                        let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+                            fallback_request(
+                                &method,
+                                &path,
+                                request_headers,
+                                &cookies,
+                                &query_params,
+                                canonical_req_id,
+                                self.peer_addr,
+                            )
+                        });
+                        return RouteOutcome::fallback_or_problem(fallback, problem);
+                    }
+                }
+                // An estimated `HEAD` response need not carry the body it measures.
+                let estimated_head = method == Method::HEAD
+                    && route_match.route.head_strategy == HeadStrategy::Estimate;
+                // An SSE body is a stream of frames; its events are checked one by one above.
+                if let Some(schema) = if self.response_validation != ResponseValidationMode::Off
+                    && response_status_allows_body(hr.status)
+                    && !is_sse
+                    && !estimated_head
+                {
+                    response_body_schema_for_status(&route_match.route, hr.status)
+                } else {
+                    None
+                } {
+                    // V6: Response validation start
+                    debug!(
+                        handler = %route_match.handler_name,
+                        status = hr.status,
+                        schema_present = true,
+                        "Response validation start"
+                    );
 
-                        // Use cached validator instead of compiling on every response
-                        // If compilation fails, skip validation but still return response
-                        if let Some(compiled) = self.validator_cache.get_or_compile(
-                            &route_match.handler_name,
-                            "response",
-                            Some(hr.status),
-                            schema,
-                        ) {
-                            if !compiled.is_valid(&hr.body) {
-                                // V7: Response validation failed
-                                let error_details: Vec<String> = compiled
-                                    .iter_errors(&hr.body)
-                                    .take(MAX_JSON_SCHEMA_ERRORS)
-                                    .map(|e| e.to_string())
-                                    .collect();
-                                let schema_path = "(operation response schema)";
-
+                    // Use cached validator instead of compiling on every response
+                    // If compilation fails, skip validation but still return response
+                    if let Some(compiled) = self.validator_cache.get_or_compile(
+                        &route_match.handler_name,
+                        "response",
+                        Some(hr.status),
+                        schema,
+                    ) {
+                        // The compiled schema is the fully `$ref`-expanded one, so array
+                        // items and nested objects are checked at every depth.
+                        if !compiled.is_valid(&hr.body) {
+                            // V7: Response validation failed
+                            let field_errors = schema_field_errors(&compiled, &hr.body);
+                            let error_details: Vec<&str> =
+                                field_errors.iter().map(|e| e.detail.as_str()).collect();
+                            let first_pointer = field_errors
+                                .first()
+                                .map(|e| e.pointer.clone())
+                                .unwrap_or_else(|| "#".to_string());
+                            let schema_path = "(operation response schema)";
+
+                            if self.response_validation == ResponseValidationMode::Warn {
+                                warn!(
+                                    handler = %route_match.handler_name,
+                                    status = hr.status,
+                                    pointer = %first_pointer,
+                                    errors = ?error_details,
+                                    schema_path = %schema_path,
+                                    "Response validation failed (warn mode, response sent)"
+                                );
+                            } else {
                                 error!(
                                     handler = %route_match.handler_name,
                                     status = hr.status,
+                                    pointer = %first_pointer,
                                     errors = ?error_details,
                                     schema_path = %schema_path,
                                     "Response validation failed"
                                 );
 
-                                _request_logger.respond_json_error(
-                                    res,
-                                    500, // Changed from 400 to 500 since this is a server error
-                                    json!({"error": "Response validation failed", "details": error_details}),
-                                );
-                                return Ok(());
+                                // 500, not 400: the handler, not the client, produced the invalid body.
+                                let problem = ProblemDetails::new(500)
+                                    .detail("Response validation failed")
+                                    .extension("pointer", first_pointer)
+                                    .errors(field_errors);
+                                let fallback =
+                                    self.fallbacks.respond(&self.dispatcher, &problem, || {
+                                        fallback_request(
+                                            &method,
+                                            &path,
+                                            request_headers,
+                                            &cookies,
+                                            &query_params,
+                                            canonical_req_id,
+                                            self.peer_addr,
+                                        )
+                                    });
+                                return RouteOutcome::fallback_or_problem(fallback, problem);
                             }
-                        } // End if let Some(compiled)
-                    } // End if let Some(schema)
-                    _request_logger.respond_handler(res, hr.status, hr.body, is_sse, &headers);
-                }
-                None => {
-                    _request_logger.respond_json_error(
-                        res,
-                        500,
-                        serde_json::json!({
-                            "error": "Handler failed or not registered",
-                            "method": method.to_string(),
-                            "path": path
-                        }),
+                        }
+                    } // End if let Some(compiled)
+                } // End if let Some(schema)
+                if method == Method::HEAD {
+                    let pretty = self.pretty_json
+                        || (self.dev_mode && pretty_query_override(&route_match.query_params));
+                    let length = head_content_length(
+                        route_match.route.head_strategy,
+                        hr.status,
+                        &hr.body,
+                        is_sse,
+                        &headers,
+                        pretty,
                     );
+                    headers.retain(|(k, _)| !k.eq_ignore_ascii_case("content-length"));
+                    headers.push((Arc::from("content-length"), length.to_string()));
+                }
+                RouteOutcome::Handler {
+                    status: hr.status,
+                    body: hr.body,
+                    is_sse,
+                    headers,
                 }
             }
-        } else {
-            _request_logger.respond_json_error(
-                res,
-                404,
-                serde_json::json!({"error": "Not Found", "method": method.to_string(), "path": path}),
+            None => {
+                let problem = ProblemDetails::new(500)
+                    .detail("Handler failed or not registered")
+                    .instance(path.as_str())
+                    .extension("method", method.to_string());
+                let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+                    fallback_request(
+                        &method,
+                        &path,
+                        &headers,
+                        &cookies,
+                        &query_params,
+                        canonical_req_id,
+                        self.peer_addr,
+                    )
+                });
+                RouteOutcome::fallback_or_problem(fallback, problem)
+            }
+        }
+    }
+
+    /// Log each `data:` frame of an SSE body that violates the status's `text/event-stream`
+    /// schema.
+    ///
+    /// Warn-only in every validation mode but `Off`: the events are already produced, and a
+    /// stream is not failed for one bad event. Frames that are not JSON are checked as strings.
+    fn warn_invalid_sse_events(&self, route: &crate::spec::RouteMeta, status: u16, body: &Value) {
+        let Value::String(stream) = body else {
+            return;
+        };
+        let Some(schema) = route
+            .responses
+            .get(&status)
+            .and_then(|m| m.get("text/event-stream"))
+            .and_then(|r| r.schema.as_ref())
+        else {
+            return;
+        };
+        let Some(compiled) = self.validator_cache.get_or_compile(
+            &route.handler_name,
+            "response",
+            Some(status),
+            schema,
+        ) else {
+            return;
+        };
+        for (index, data) in crate::sse::data_frames(stream).enumerate() {
+            let event = serde_json::from_str(&data).unwrap_or(Value::String(data));
+            if compiled.is_valid(&event) {
+                continue;
+            }
+            let field_errors = schema_field_errors(&compiled, &event);
+            let error_details: Vec<&str> = field_errors.iter().map(|e| e.detail.as_str()).collect();
+            tracing::warn!(
+                handler = %route.handler_name,
+                status,
+                index,
+                errors = ?error_details,
+                "SSE event validation failed (warn mode, event sent)"
             );
         }
-        Ok(())
+    }
+
+    /// Redirect to `location`, the request target with duplicate slashes collapsed.
+    pub(crate) fn canonical_path_redirect(&self, location: String) -> RouteOutcome {
+        let status = self.path_normalization.redirect_status();
+        debug!(location = %location, status, "Redirecting to canonical path");
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("location"), location.clone()));
+        RouteOutcome::Problem {
+            problem: ProblemDetails::new(status)
+                .detail(format!("Path contains duplicate slashes; use {location}")),
+            headers,
+        }
+    }
+
+    /// `404` (or the 404 fallback) for a request outside the base path, without consulting
+    /// the router: the unprefixed path may well match a route.
+    pub(crate) fn handle_outside_base_path(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderVec,
+        cookies: &HeaderVec,
+        query_params: &ParamVec,
+        canonical_req_id: RequestId,
+    ) -> RouteOutcome {
+        let problem = not_found_problem(method, path);
+        let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+            fallback_request(
+                method,
+                path,
+                headers,
+                cookies,
+                query_params,
+                canonical_req_id,
+                self.peer_addr,
+            )
+        });
+        RouteOutcome::fallback_or_problem(fallback, problem)
+    }
+
+    /// Outcome for a request no route matched: `204` with `Allow` for a plain `OPTIONS` on a
+    /// documented path, otherwise `405` (with `Allow`) or `404`, through the fallback
+    /// handlers when set.
+    pub(crate) fn handle_unrouted(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderVec,
+        cookies: &HeaderVec,
+        query_params: &ParamVec,
+        canonical_req_id: RequestId,
+    ) -> RouteOutcome {
+        let allowed = self.router.load().allowed_methods(path);
+        // A plain OPTIONS (no `Origin`) on a documented path lists its methods. With an
+        // `Origin` it is a CORS request, which only the CORS middleware of a matched
+        // OPTIONS route may answer.
+        if *method == Method::OPTIONS && !allowed.is_empty() && headers.get("origin").is_none() {
+            return RouteOutcome::Handler {
+                status: 204,
+                body: Value::Null,
+                is_sse: false,
+                headers: HeaderVec::new(),
+            }
+            .with_header("Allow", allow_methods(&allowed));
+        }
+        let problem = if allowed.is_empty() {
+            not_found_problem(method, path)
+        } else {
+            method_not_allowed_problem(method, path)
+        };
+        let fallback = self.fallbacks.respond(&self.dispatcher, &problem, || {
+            fallback_request(
+                method,
+                path,
+                headers,
+                cookies,
+                query_params,
+                canonical_req_id,
+                self.peer_addr,
+            )
+        });
+        let has_allow = fallback
+            .as_ref()
+            .is_some_and(|hr| hr.get_header("allow").is_some());
+        let outcome = RouteOutcome::fallback_or_problem(fallback, problem);
+        if allowed.is_empty() || has_allow {
+            outcome
+        } else {
+            outcome.with_header("Allow", allow_methods(&allowed))
+        }
     }
 }
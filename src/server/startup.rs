@@ -0,0 +1,137 @@
+//! Startup summary: spec path, resolved config, bound address and route table.
+//!
+//! [`StartupSummary`] is logged once the server is listening. With
+//! [`LogFormat::Json`](crate::otel::LogFormat::Json) (`BRRTR_LOG_FORMAT=json`, the default)
+//! it is a single `tracing` event on the `brrtrouter::startup` target. Its `config` and
+//! `routes` fields hold JSON documents, so log pipelines can parse boot diagnostics. With
+//! `pretty` it is printed as readable lines instead.
+//!
+//! Secrets never reach either form: every config field named `key` or `signature`, and
+//! every field the [`Sanitizer`] treats as a credential (`secret`, `password`, `token`, ...),
+//! is replaced with [`REDACTED`] whatever `BRRTR_LOG_REDACT_LEVEL` says.
+
+use serde_json::Value;
+use tracing::info;
+
+use crate::otel::{LogFormat, RedactionLevel};
+use crate::router::Router;
+use crate::sanitize::Sanitizer;
+
+use super::app_config::AppConfig;
+
+/// Replacement for secret config values in the startup summary.
+pub const REDACTED: &str = "<REDACTED>";
+
+/// Config field names that hold secrets but match no [`Sanitizer`] credential pattern.
+const SECRET_FIELDS: &[&str] = &["key", "keys", "signature", "private_key"];
+
+/// What the server booted with, for one structured log event
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupSummary {
+    /// OpenAPI spec the routes were loaded from
+    pub spec_path: String,
+    /// Address the server listens on
+    pub addr: String,
+    /// `config.yaml` as resolved, secrets redacted
+    pub config: Value,
+    /// Route table from [`Router::dump_routes_json`]
+    pub routes: Value,
+}
+
+impl StartupSummary {
+    /// Summarize a server listening on `addr` with `config` and `router`
+    #[must_use]
+    pub fn new(
+        spec_path: impl Into<String>,
+        addr: impl Into<String>,
+        config: &AppConfig,
+        router: &Router,
+    ) -> Self {
+        Self {
+            spec_path: spec_path.into(),
+            addr: addr.into(),
+            config: sanitized_config(config),
+            routes: router.dump_routes_json(),
+        }
+    }
+
+    /// The whole summary as one JSON object
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "spec_path": self.spec_path,
+            "addr": self.addr,
+            "config": self.config,
+            "routes": self.routes,
+        })
+    }
+
+    /// Log the summary: one `tracing` event for [`LogFormat::Json`], printed lines otherwise
+    pub fn log(&self, format: LogFormat) {
+        let route_count = self.routes.as_array().map_or(0, Vec::len);
+        match format {
+            LogFormat::Json => info!(
+                target: "brrtrouter::startup",
+                spec_path = %self.spec_path,
+                addr = %self.addr,
+                route_count,
+                config = %self.config,
+                routes = %self.routes,
+                "Server started"
+            ),
+            LogFormat::Pretty => {
+                println!("[startup] spec_path={}", self.spec_path);
+                println!("[startup] addr={} routes_count={route_count}", self.addr);
+                for route in self.routes.as_array().into_iter().flatten() {
+                    println!(
+                        "[route] {} {} -> {} auth={}",
+                        route["method"].as_str().unwrap_or_default(),
+                        route["path"].as_str().unwrap_or_default(),
+                        route["handler"].as_str().unwrap_or_default(),
+                        route["auth"]
+                    );
+                }
+                match serde_yaml::to_string(&self.config) {
+                    Ok(y) => println!("[config]\n{y}"),
+                    Err(_) => println!("[config] <failed to serialize config>"),
+                }
+            }
+        }
+    }
+}
+
+/// `config` as JSON with every secret value replaced by [`REDACTED`]
+///
+/// Unset (`null`) and boolean fields are kept, so `allow_credentials: true` stays readable,
+/// and sections such as `api_keys` keep their structure with only their values redacted.
+#[must_use]
+pub fn sanitized_config(config: &AppConfig) -> Value {
+    let mut value = serde_json::to_value(config).unwrap_or(Value::Null);
+    redact_secrets(&mut value, &Sanitizer::new(RedactionLevel::Credentials));
+    value
+}
+
+fn redact_secrets(value: &mut Value, sanitizer: &Sanitizer) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                let secret = SECRET_FIELDS.contains(&name.to_ascii_lowercase().as_str())
+                    || sanitizer.should_redact(name);
+                match field {
+                    // `api_keys: {scheme: {key: ...}}`: keep the structure, redact the leaves
+                    Value::Object(_) | Value::Null | Value::Bool(_) => {
+                        redact_secrets(field, sanitizer)
+                    }
+                    _ if secret => *field = Value::String(REDACTED.to_string()),
+                    _ => redact_secrets(field, sanitizer),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_secrets(item, sanitizer);
+            }
+        }
+        _ => {}
+    }
+}
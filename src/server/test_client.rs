@@ -0,0 +1,374 @@
+//! In-process test client: requests through an [`AppService`] without a socket.
+//!
+//! [`TestClient`] hands a request straight to the service's routing: authentication,
+//! parameter and body validation, middleware, the handler and response validation run
+//! exactly as for a request read from a connection, but nothing is bound, written or parsed
+//! back, so handler tests are fast and deterministic. The answer is the
+//! [`HandlerResponse`] the connection would have been sent, wrapped in a [`TestResponse`]
+//! with assertion helpers; framework problems become `application/problem+json` responses.
+//!
+//! ```rust,ignore
+//! use brrtrouter::server::TestClient;
+//! use serde_json::json;
+//!
+//! let client = TestClient::new(service);
+//! client
+//!     .post("/pets")
+//!     .bearer("token")
+//!     .json(json!({"name": "Rex"}))
+//!     .send()
+//!     .assert_status(201)
+//!     .assert_json_at("/name", json!("Rex"));
+//! ```
+//!
+//! Like sub-requests of the batch endpoint (see [`super::batch`]), only spec operations are
+//! reachable: connection-level handling (header limits, keep-alive, `Server` header) and the
+//! built-in endpoints (`/health`, `/metrics`, docs, static files, batch) are not exercised.
+
+use super::request::{check_json_depth, parse_request_body, parse_request_head};
+use super::response::{ProblemDetails, PROBLEM_JSON};
+use super::service::{upgrade_required_problem, AppService, RouteOutcome, RoutedRequest};
+use crate::dispatcher::{HandlerResponse, HeaderLookup, HeaderVec};
+use crate::ids::RequestId;
+use base64::Engine as _;
+use bytes::Bytes;
+use http::Method;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Sends requests through an [`AppService`] in-process.
+#[derive(Clone)]
+pub struct TestClient {
+    service: AppService,
+}
+
+impl TestClient {
+    /// Client for `service`; register handlers, security providers and settings first.
+    #[must_use]
+    pub fn new(service: AppService) -> Self {
+        Self { service }
+    }
+
+    /// Request with `method` for `target` (path and optional query string).
+    #[must_use]
+    pub fn request(&self, method: Method, target: &str) -> TestRequest<'_> {
+        TestRequest {
+            client: self,
+            method,
+            target: target.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// `GET` request for `target`.
+    #[must_use]
+    pub fn get(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::GET, target)
+    }
+
+    /// `POST` request for `target`.
+    #[must_use]
+    pub fn post(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::POST, target)
+    }
+
+    /// `PUT` request for `target`.
+    #[must_use]
+    pub fn put(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, target)
+    }
+
+    /// `PATCH` request for `target`.
+    #[must_use]
+    pub fn patch(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::PATCH, target)
+    }
+
+    /// `DELETE` request for `target`.
+    #[must_use]
+    pub fn delete(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, target)
+    }
+
+    /// `HEAD` request for `target`; the response has its `Content-Length` and no body.
+    #[must_use]
+    pub fn head(&self, target: &str) -> TestRequest<'_> {
+        self.request(Method::HEAD, target)
+    }
+}
+
+/// A request being built by a [`TestClient`]; [`Self::send`] runs it.
+#[must_use = "a request does nothing until it is sent"]
+pub struct TestRequest<'a> {
+    client: &'a TestClient,
+    method: Method,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+}
+
+impl TestRequest<'_> {
+    /// Add a header; a repeated name is sent once per call.
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// `Authorization: Bearer <token>`.
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("authorization", format!("Bearer {token}"))
+    }
+
+    /// `Authorization: Basic <base64(user:password)>`.
+    pub fn basic_auth(self, user: &str, password: &str) -> Self {
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("{user}:{password}"));
+        self.header("authorization", format!("Basic {credentials}"))
+    }
+
+    /// Add a cookie to the `Cookie` header.
+    pub fn cookie(mut self, name: &str, value: &str) -> Self {
+        match self
+            .headers
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case("cookie"))
+        {
+            Some((_, cookies)) => {
+                cookies.push_str("; ");
+                cookies.push_str(name);
+                cookies.push('=');
+                cookies.push_str(value);
+            }
+            None => self
+                .headers
+                .push(("cookie".to_string(), format!("{name}={value}"))),
+        }
+        self
+    }
+
+    /// JSON body; sets `content-type: application/json` unless a content type was set.
+    pub fn json(mut self, body: Value) -> Self {
+        if !self.has_header("content-type") {
+            self.headers
+                .push(("content-type".to_string(), "application/json".to_string()));
+        }
+        self.body = Some(serde_json::to_vec(&body).unwrap_or_default());
+        self
+    }
+
+    /// Raw body sent as `content_type`, parsed like a body read from a connection.
+    pub fn body(mut self, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        self.headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case("content-type"));
+        self.headers
+            .push(("content-type".to_string(), content_type.to_string()));
+        self.body = Some(body.into());
+        self
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Run the request through the service.
+    ///
+    /// A malformed request line answers `400` as the server would.
+    pub fn send(self) -> TestResponse {
+        let head = parse_request_head(
+            self.method.as_str(),
+            &self.target,
+            self.headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_bytes())),
+        );
+        let parsed = match head {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return TestResponse::from_outcome(RouteOutcome::problem(
+                    ProblemDetails::new(400).detail(format!("Invalid request: {e}")),
+                ))
+            }
+        };
+        let service = &self.client.service;
+        let (body, raw_body) = match self.body.filter(|b| !b.is_empty()) {
+            Some(raw) => {
+                let content_type = parsed.headers.get("content-type").unwrap_or("");
+                if let Err(e) = check_json_depth(&raw, content_type, service.max_json_depth) {
+                    return TestResponse::from_outcome(RouteOutcome::problem(e.problem()));
+                }
+                (
+                    parse_request_body(&raw, content_type),
+                    Some(Bytes::from(raw)),
+                )
+            }
+            None => (None, None),
+        };
+        let request_id = RequestId::from_header_or_new(
+            parsed
+                .headers
+                .get("x-request-id")
+                .filter(|s| !s.trim().is_empty()),
+        );
+        if let Some(location) = service.path_normalization.redirect_location(&self.target) {
+            return TestResponse::from_outcome(service.canonical_path_redirect(location));
+        }
+        let path = service.path_normalization.normalize(parsed.path);
+        let path = match service.strip_base_path(path) {
+            Ok(path) => path,
+            Err(path) => {
+                return TestResponse::from_outcome(service.handle_outside_base_path(
+                    &parsed.method,
+                    &path,
+                    &parsed.headers,
+                    &parsed.cookies,
+                    &parsed.query_params,
+                    request_id,
+                ))
+            }
+        };
+        let is_head = parsed.method == Method::HEAD;
+        let route = service.router.load().route(parsed.method.clone(), &path);
+        let outcome = match route {
+            Some(route_match) => service.handle_route(
+                route_match,
+                RoutedRequest {
+                    method: parsed.method,
+                    path,
+                    headers: parsed.headers,
+                    cookies: parsed.cookies,
+                    query_params: parsed.query_params,
+                    body,
+                    body_size_bytes: raw_body.as_ref().map_or(0, Bytes::len),
+                    raw_body,
+                    request_id,
+                    upgrade: false,
+                    expect_continue: false,
+                },
+            ),
+            None => service.handle_unrouted(
+                &parsed.method,
+                &path,
+                &parsed.headers,
+                &parsed.cookies,
+                &parsed.query_params,
+                request_id,
+            ),
+        };
+        let mut response = TestResponse::from_outcome(service.prefix_outcome(outcome));
+        if is_head {
+            // A client reads no body from a `HEAD` response.
+            response.response.body = Value::Null;
+        }
+        response
+    }
+}
+
+/// Response to a [`TestRequest`], with assertion helpers that panic with the response in
+/// the message.
+#[derive(Debug)]
+pub struct TestResponse {
+    response: HandlerResponse,
+}
+
+impl TestResponse {
+    fn from_outcome(outcome: RouteOutcome) -> Self {
+        let response = match outcome {
+            RouteOutcome::Problem {
+                problem,
+                mut headers,
+            } => {
+                headers.push((Arc::from("content-type"), PROBLEM_JSON.to_string()));
+                HandlerResponse::new(problem.status, headers, problem.to_value())
+            }
+            RouteOutcome::Handler {
+                status,
+                body,
+                headers,
+                ..
+            } => HandlerResponse::new(status, headers, body),
+            // Test requests are never upgrades, so a WebSocket route already answered `426`;
+            // nor `Expect: 100-continue` heads.
+            RouteOutcome::Upgrade(_) | RouteOutcome::Continue => {
+                let problem = upgrade_required_problem();
+                let mut headers = HeaderVec::new();
+                headers.push((Arc::from("content-type"), PROBLEM_JSON.to_string()));
+                HandlerResponse::new(problem.status, headers, problem.to_value())
+            }
+        };
+        Self { response }
+    }
+
+    /// HTTP status code.
+    #[must_use]
+    pub fn status(&self) -> u16 {
+        self.response.status
+    }
+
+    /// First value of header `name` (case-insensitive).
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.response
+            .headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Response body (a string for text bodies).
+    #[must_use]
+    pub fn body(&self) -> &Value {
+        &self.response.body
+    }
+
+    /// The response as the handler pipeline produced it.
+    #[must_use]
+    pub fn into_response(self) -> HandlerResponse {
+        self.response
+    }
+
+    /// Panic unless the status is `expected`.
+    #[track_caller]
+    pub fn assert_status(&self, expected: u16) -> &Self {
+        assert_eq!(
+            self.response.status, expected,
+            "unexpected status; body: {}",
+            self.response.body
+        );
+        self
+    }
+
+    /// Panic unless header `name` is `expected`.
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, expected: &str) -> &Self {
+        assert_eq!(
+            self.header(name),
+            Some(expected),
+            "unexpected `{name}` header; headers: {:?}",
+            self.response.headers
+        );
+        self
+    }
+
+    /// Panic unless the body equals `expected`.
+    #[track_caller]
+    pub fn assert_json(&self, expected: &Value) -> &Self {
+        assert_eq!(&self.response.body, expected, "unexpected body");
+        self
+    }
+
+    /// Panic unless the value at JSON Pointer `pointer` (e.g. `/items/0/id`) equals
+    /// `expected`.
+    #[track_caller]
+    pub fn assert_json_at(&self, pointer: &str, expected: Value) -> &Self {
+        assert_eq!(
+            self.response.body.pointer(pointer),
+            Some(&expected),
+            "unexpected value at `{pointer}`; body: {}",
+            self.response.body
+        );
+        self
+    }
+}
@@ -0,0 +1,130 @@
+//! Unix domain socket listener for [`super::HttpServer::start_unix`].
+//!
+//! `may_minihttp` only serves TCP and keeps its accept loop private, so a Unix socket
+//! listener cannot hand streams to it directly. Instead the HTTP server runs on an ephemeral
+//! `127.0.0.1` port and every accepted Unix connection is relayed to it, one relay per
+//! connection. Health, metrics, the middleware chain and keep-alive behave exactly as over
+//! TCP.
+//!
+//! ## Differences from TCP
+//!
+//! - **Peer address:** Unix socket clients have no IP and the relay hides its own, so
+//!   [`crate::dispatcher::HandlerRequest::peer_addr`] is `None` and
+//!   [`crate::dispatcher::HandlerRequest::client_ip`] resolves nothing. `X-Forwarded-*`
+//!   headers reach handlers as sent; nothing vouches for them, so do not use them for
+//!   access control or rate limiting.
+//! - **Access control:** the socket file is created with the process umask; restrict the
+//!   parent directory or umask to limit who can connect. The internal loopback port only
+//!   serves connections that present the relay secret, so it does not bypass those
+//!   permissions.
+//! - **Idle connections:** the relay closes a connection that stays silent for
+//!   [`super::ConnectionConfig::idle_timeout`] between requests, or whose client stops
+//!   reading a response for that long (see [`super::relay`]).
+//! - **Lifecycle:** a stale socket file (nothing listening) is removed on start, a live one
+//!   is an `AddrInUse` error, and a non-socket file at the path is never deleted. The socket
+//!   file is removed by [`super::ServerHandle::stop`].
+
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use may::coroutine::JoinHandle;
+use may::os::unix::net::UnixListener;
+
+use super::relay;
+
+/// A bound Unix socket and the coroutine accepting on it.
+pub(crate) struct UnixBinding {
+    path: PathBuf,
+    accept: JoinHandle<()>,
+}
+
+impl UnixBinding {
+    /// Bind `path` and relay every accepted connection to `upstream`.
+    pub(crate) fn bind(path: &Path, upstream: relay::Upstream) -> io::Result<Self> {
+        remove_stale_socket(path)?;
+        let listener = UnixListener::bind(path)?;
+        // SAFETY: `may::coroutine::Builder::spawn` is unsafe because the coroutine must not
+        // block the worker thread or access thread-local storage; the accept loop only performs
+        // may-aware socket I/O.
+        let accept = unsafe {
+            may::coroutine::Builder::new()
+                .name("brrtrouter-unix-accept".to_string())
+                .spawn(move || accept_loop(&listener, &upstream))
+        }?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            accept,
+        })
+    }
+
+    /// Path of the socket file.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop accepting and remove the socket file. In-flight connections finish on their own.
+    pub(crate) fn shutdown(self) {
+        // SAFETY: see `ServerHandle::stop`; cancelling the accept coroutine drops the listener.
+        unsafe {
+            self.accept.coroutine().cancel();
+        }
+        let _ = self.accept.join();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                tracing::warn!(
+                    target: "brrtrouter::server",
+                    path = %self.path.display(),
+                    error = %e,
+                    "failed to remove unix socket file"
+                );
+            }
+        }
+    }
+}
+
+/// Remove a socket file nothing is listening on; refuse live sockets and non-socket files.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !meta.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is already being served", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)
+}
+
+fn accept_loop(listener: &UnixListener, upstream: &relay::Upstream) {
+    for stream in listener.incoming() {
+        let client = match stream {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(target: "brrtrouter::server", error = %e, "unix socket accept failed");
+                continue;
+            }
+        };
+        let upstream = upstream.clone();
+        // SAFETY: as in `UnixBinding::bind`; the relay only performs may-aware socket I/O.
+        let spawned = unsafe {
+            may::coroutine::Builder::new().spawn(move || {
+                if let Err(e) = relay::relay(client, &upstream, &[], None) {
+                    tracing::debug!(target: "brrtrouter::server", error = %e, "unix socket relay ended");
+                }
+            })
+        };
+        if let Err(e) = spawned {
+            tracing::warn!(target: "brrtrouter::server", error = %e, "failed to spawn unix socket relay");
+        }
+    }
+}
@@ -0,0 +1,807 @@
+//! WebSocket endpoints for operations marked `x-websocket: true`.
+//!
+//! `may_minihttp` owns its connections and keeps its accept loop private, so a request cannot
+//! take over its socket after a `101 Switching Protocols`. [`HttpServer::start_with_websockets`]
+//! therefore accepts connections itself, as [`HttpServer::start_unix`] does for Unix sockets:
+//! the first request head on every connection is read, and
+//!
+//! - a `GET` with `Upgrade: websocket` (RFC 6455, version 13) is routed, authenticated and
+//!   parameter-validated like any other request to its route; when it reaches
+//!   a WebSocket route, the handshake is answered and the handler registered with
+//!   [`Dispatcher::register_websocket_handler`] runs on this connection's coroutine with a
+//!   [`WebSocketChannel`]. Rejections (`401`, `404`, ...) are answered as problems and the
+//!   connection is closed.
+//! - a head with `Expect: 100-continue` is checked without its body and answered `100 Continue`
+//!   or rejected, when enabled (see [`super::expect`]);
+//! - anything else is relayed byte-for-byte to the HTTP server on an ephemeral loopback port
+//!   that only serves connections presenting a per-server secret, so every other request
+//!   behaves exactly as with [`HttpServer::start`].
+//!
+//! A plain HTTP request to a WebSocket route is answered `426 Upgrade Required`.
+//!
+//! ## Frames
+//!
+//! [`WebSocketChannel::recv`] answers pings with pongs, ignores pongs, reassembles fragmented
+//! messages and answers a close frame before returning `None`. Messages larger than
+//! [`WebSocketConfig::max_message_bytes`](super::WebSocketConfig::max_message_bytes) close the
+//! connection with `1009 Message Too Big`, unmasked client frames with `1002 Protocol Error`
+//! and invalid UTF-8 text with `1007`. When the handler returns, the connection is closed
+//! with `1000` unless a close was already sent.
+//!
+//! [`HttpServer::start_with_websockets`]: super::HttpServer::start_with_websockets
+//! [`HttpServer::start_unix`]: super::HttpServer::start_unix
+//! [`HttpServer::start`]: super::HttpServer::start
+//! [`Dispatcher::register_websocket_handler`]: crate::dispatcher::Dispatcher::register_websocket_handler
+
+use super::expect;
+use super::limits::DEFAULT_MAX_HEADER_BYTES;
+use super::relay;
+use super::request::parse_request_head;
+use super::response::{ProblemDetails, PROBLEM_JSON};
+use super::service::{AppService, RouteOutcome, RoutedRequest};
+use crate::dispatcher::{HeaderLookup, HeaderVec};
+use crate::ids::RequestId;
+use crate::router::ParamVec;
+use crate::security::AuthContext;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use may::coroutine::JoinHandle;
+use may::net::{TcpListener, TcpStream};
+use may::sync::Mutex;
+use sha1::{Digest, Sha1};
+use std::io::{self, Cursor, Read, Write};
+use std::net::Shutdown;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Default [`super::WebSocketConfig::max_message_bytes`] (1 MiB).
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+
+/// Appended to `Sec-WebSocket-Key` before hashing (RFC 6455 §1.3).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Close status codes (RFC 6455 §7.4.1).
+const CLOSE_NORMAL: u16 = 1000;
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+const CLOSE_INVALID_DATA: u16 = 1007;
+const CLOSE_TOO_BIG: u16 = 1009;
+
+/// A complete WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// UTF-8 text message
+    Text(String),
+    /// Binary message
+    Binary(Vec<u8>),
+}
+
+/// Handler for a WebSocket route, run on the connection's coroutine after the handshake.
+pub type WebSocketHandler = Arc<dyn Fn(WebSocketRequest, WebSocketChannel) + Send + Sync>;
+
+/// The authenticated upgrade request handed to a [`WebSocketHandler`].
+#[derive(Debug, Clone)]
+pub struct WebSocketRequest {
+    /// Handler name (`operationId`) of the matched route
+    pub handler_name: String,
+    /// Request path without the query string
+    pub path: String,
+    /// Path parameters of the matched route
+    pub path_params: ParamVec,
+    /// Query string parameters
+    pub query_params: ParamVec,
+    /// Request headers (lowercase names)
+    pub headers: HeaderVec,
+    /// Request cookies
+    pub cookies: HeaderVec,
+    /// Identity from the security scheme that validated, as for HTTP handlers
+    pub auth_context: Option<AuthContext>,
+    /// `X-Request-ID` of the upgrade request (inbound or generated)
+    pub request_id: RequestId,
+}
+
+impl WebSocketRequest {
+    /// Get a path parameter by name
+    #[must_use]
+    pub fn get_path_param(&self, name: &str) -> Option<&str> {
+        self.path_params
+            .iter()
+            .rfind(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get a query parameter by name
+    #[must_use]
+    pub fn get_query_param(&self, name: &str) -> Option<&str> {
+        self.query_params
+            .iter()
+            .rfind(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get a header by name (case-insensitive)
+    #[must_use]
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+}
+
+/// Sending half of a [`WebSocketChannel`].
+///
+/// Clone it to send from other coroutines while the handler blocks in
+/// [`WebSocketChannel::recv`]; frames from different senders never interleave.
+#[derive(Clone)]
+pub struct WebSocketSender {
+    stream: Arc<Mutex<TcpStream>>,
+    close_sent: Arc<AtomicBool>,
+}
+
+impl WebSocketSender {
+    /// Send a text message
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send_text(&self, text: &str) -> io::Result<()> {
+        self.write_frame(OP_TEXT, text.as_bytes())
+    }
+
+    /// Send a binary message
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send_binary(&self, data: &[u8]) -> io::Result<()> {
+        self.write_frame(OP_BINARY, data)
+    }
+
+    /// Send `message` as one frame
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send(&self, message: &Message) -> io::Result<()> {
+        match message {
+            Message::Text(text) => self.send_text(text),
+            Message::Binary(data) => self.send_binary(data),
+        }
+    }
+
+    /// Send a ping; the client's pong is consumed by [`WebSocketChannel::recv`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn ping(&self, payload: &[u8]) -> io::Result<()> {
+        self.write_frame(OP_PING, &payload[..payload.len().min(125)])
+    }
+
+    /// Start the closing handshake with `code` and `reason`; later calls do nothing
+    ///
+    /// # Errors
+    ///
+    /// Fails when the write fails.
+    pub fn close(&self, code: u16, reason: &str) -> io::Result<()> {
+        if self.close_sent.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let mut payload = code.to_be_bytes().to_vec();
+        payload.extend_from_slice(&reason.as_bytes()[..reason.len().min(123)]);
+        self.write_raw(OP_CLOSE, &payload)
+    }
+
+    fn write_frame(&self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        if self.close_sent.load(Ordering::Acquire) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "WebSocket closed",
+            ));
+        }
+        self.write_raw(opcode, payload)
+    }
+
+    /// Write one unmasked, final frame (servers never mask, RFC 6455 §5.1).
+    fn write_raw(&self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(payload.len() + 10);
+        frame.push(0x80 | opcode);
+        match payload.len() {
+            len @ 0..=125 => frame.push(len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        let mut stream = self
+            .stream
+            .lock()
+            .map_err(|_| io::Error::other("WebSocket writer poisoned"))?;
+        stream.write_all(&frame)?;
+        stream.flush()
+    }
+}
+
+/// A WebSocket connection as seen by its handler.
+pub struct WebSocketChannel {
+    reader: io::Chain<Cursor<Vec<u8>>, TcpStream>,
+    sender: WebSocketSender,
+    max_message_bytes: usize,
+    closed: bool,
+}
+
+/// A frame could not be read: an I/O error, or a protocol violation to close with.
+enum FrameError {
+    Io(io::Error),
+    Close(u16, &'static str),
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+impl WebSocketChannel {
+    /// Wrap an upgraded connection; `buffered` holds bytes read past the request head.
+    fn new(stream: TcpStream, buffered: Vec<u8>, max_message_bytes: usize) -> io::Result<Self> {
+        let writer = stream.try_clone()?;
+        Ok(Self {
+            reader: Cursor::new(buffered).chain(stream),
+            sender: WebSocketSender {
+                stream: Arc::new(Mutex::new(writer)),
+                close_sent: Arc::new(AtomicBool::new(false)),
+            },
+            max_message_bytes,
+            closed: false,
+        })
+    }
+
+    /// Wait for the next message; `None` once the client closed the connection
+    ///
+    /// Pings are answered, pongs skipped and fragments reassembled while waiting.
+    ///
+    /// # Errors
+    ///
+    /// I/O errors, and protocol violations (unmasked or oversized frames, invalid UTF-8),
+    /// after which the connection has been closed with the matching status code.
+    pub fn recv(&mut self) -> io::Result<Option<Message>> {
+        let mut partial: Option<(u8, Vec<u8>)> = None;
+        while !self.closed {
+            let frame = match read_frame(&mut self.reader, self.max_message_bytes) {
+                Ok(frame) => frame,
+                Err(FrameError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.closed = true;
+                    return Ok(None);
+                }
+                Err(FrameError::Io(e)) => return Err(e),
+                Err(FrameError::Close(code, reason)) => return Err(self.fail(code, reason)),
+            };
+            match frame.opcode {
+                OP_PING => self.sender.write_frame(OP_PONG, &frame.payload)?,
+                OP_PONG => {}
+                OP_CLOSE => {
+                    self.closed = true;
+                    let code = match frame.payload.get(..2) {
+                        Some(&[hi, lo]) => u16::from_be_bytes([hi, lo]),
+                        _ => CLOSE_NORMAL,
+                    };
+                    self.sender.close(code, "")?;
+                    return Ok(None);
+                }
+                OP_TEXT | OP_BINARY if partial.is_none() => {
+                    if frame.fin {
+                        return self.complete(frame.opcode, frame.payload).map(Some);
+                    }
+                    partial = Some((frame.opcode, frame.payload));
+                }
+                OP_CONTINUATION => {
+                    let Some((opcode, mut data)) = partial.take() else {
+                        return Err(self.fail(CLOSE_PROTOCOL_ERROR, "unexpected continuation"));
+                    };
+                    if data.len() + frame.payload.len() > self.max_message_bytes {
+                        return Err(self.fail(CLOSE_TOO_BIG, "message too big"));
+                    }
+                    data.extend_from_slice(&frame.payload);
+                    if frame.fin {
+                        return self.complete(opcode, data).map(Some);
+                    }
+                    partial = Some((opcode, data));
+                }
+                _ => return Err(self.fail(CLOSE_PROTOCOL_ERROR, "unexpected opcode")),
+            }
+        }
+        Ok(None)
+    }
+
+    /// A handle for sending from other coroutines
+    #[must_use]
+    pub fn sender(&self) -> WebSocketSender {
+        self.sender.clone()
+    }
+
+    /// See [`WebSocketSender::send_text`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send_text(&self, text: &str) -> io::Result<()> {
+        self.sender.send_text(text)
+    }
+
+    /// See [`WebSocketSender::send_binary`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send_binary(&self, data: &[u8]) -> io::Result<()> {
+        self.sender.send_binary(data)
+    }
+
+    /// See [`WebSocketSender::send`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the write fails.
+    pub fn send(&self, message: &Message) -> io::Result<()> {
+        self.sender.send(message)
+    }
+
+    /// See [`WebSocketSender::close`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when the write fails.
+    pub fn close(&self, code: u16, reason: &str) -> io::Result<()> {
+        self.sender.close(code, reason)
+    }
+
+    fn complete(&mut self, opcode: u8, data: Vec<u8>) -> io::Result<Message> {
+        if opcode == OP_BINARY {
+            return Ok(Message::Binary(data));
+        }
+        String::from_utf8(data)
+            .map(Message::Text)
+            .map_err(|_| self.fail(CLOSE_INVALID_DATA, "invalid UTF-8 in text message"))
+    }
+
+    /// Close with `code` and return the error reported to the handler.
+    fn fail(&mut self, code: u16, reason: &'static str) -> io::Error {
+        self.closed = true;
+        let _ = self.sender.close(code, reason);
+        io::Error::new(io::ErrorKind::InvalidData, reason)
+    }
+}
+
+/// Read one client frame: masked, at most `max_payload` bytes, control frames unfragmented.
+fn read_frame(reader: &mut impl Read, max_payload: usize) -> Result<Frame, FrameError> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let fin = head[0] & 0x80 != 0;
+    let opcode = head[0] & 0x0F;
+    if head[0] & 0x70 != 0 {
+        return Err(FrameError::Close(CLOSE_PROTOCOL_ERROR, "reserved bits set"));
+    }
+    if head[1] & 0x80 == 0 {
+        return Err(FrameError::Close(
+            CLOSE_PROTOCOL_ERROR,
+            "client frame not masked",
+        ));
+    }
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut ext = [0u8; 2];
+            reader.read_exact(&mut ext)?;
+            u64::from(u16::from_be_bytes(ext))
+        }
+        127 => {
+            let mut ext = [0u8; 8];
+            reader.read_exact(&mut ext)?;
+            u64::from_be_bytes(ext)
+        }
+        n => u64::from(n),
+    };
+    if opcode >= OP_CLOSE && (len > 125 || !fin) {
+        return Err(FrameError::Close(
+            CLOSE_PROTOCOL_ERROR,
+            "invalid control frame",
+        ));
+    }
+    let len = match usize::try_from(len) {
+        Ok(len) if len <= max_payload => len,
+        _ => return Err(FrameError::Close(CLOSE_TOO_BIG, "message too big")),
+    };
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask)?;
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok(Frame {
+        fin,
+        opcode,
+        payload,
+    })
+}
+
+/// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key` (RFC 6455 §4.2.2).
+#[must_use]
+pub fn accept_key(key: &str) -> String {
+    let digest = Sha1::new()
+        .chain_update(key.trim().as_bytes())
+        .chain_update(WEBSOCKET_GUID.as_bytes())
+        .finalize();
+    STANDARD.encode(digest)
+}
+
+/// The accepting coroutine of [`super::HttpServer::start_with_websockets`].
+pub(crate) struct WebSocketBinding {
+    accept: JoinHandle<()>,
+}
+
+impl WebSocketBinding {
+    /// Accept on `listener`; upgrades are served by `service`, everything else is relayed to
+    /// `upstream`.
+    pub(crate) fn bind(
+        listener: TcpListener,
+        upstream: relay::Upstream,
+        service: AppService,
+    ) -> io::Result<Self> {
+        // SAFETY: `may::coroutine::Builder::spawn` is unsafe because the coroutine must not
+        // block the worker thread or access thread-local storage; the accept loop only performs
+        // may-aware socket I/O.
+        let accept = unsafe {
+            may::coroutine::Builder::new()
+                .name("brrtrouter-websocket-accept".to_string())
+                .spawn(move || accept_loop(&listener, &upstream, &service))
+        }?;
+        Ok(Self { accept })
+    }
+
+    /// Stop accepting. Open connections finish on their own.
+    pub(crate) fn shutdown(self) {
+        // SAFETY: see `ServerHandle::stop`; cancelling the accept coroutine drops the listener.
+        unsafe {
+            self.accept.coroutine().cancel();
+        }
+        let _ = self.accept.join();
+    }
+}
+
+fn accept_loop(listener: &TcpListener, upstream: &relay::Upstream, service: &AppService) {
+    for stream in listener.incoming() {
+        let client = match stream {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(target: "brrtrouter::server", error = %e, "websocket listener accept failed");
+                continue;
+            }
+        };
+        let service = service.clone();
+        let upstream = upstream.clone();
+        // SAFETY: as in `WebSocketBinding::bind`; WebSocket handlers run here and must follow
+        // the same rules as HTTP handlers (no blocking std I/O, no thread-locals).
+        let spawned = unsafe {
+            may::coroutine::Builder::new().spawn(move || {
+                if let Err(e) = serve_connection(client, &upstream, &service) {
+                    tracing::debug!(target: "brrtrouter::server", error = %e, "connection ended");
+                }
+            })
+        };
+        if let Err(e) = spawned {
+            tracing::warn!(target: "brrtrouter::server", error = %e, "failed to spawn connection coroutine");
+        }
+    }
+}
+
+fn serve_connection(
+    mut client: TcpStream,
+    upstream: &relay::Upstream,
+    service: &AppService,
+) -> io::Result<()> {
+    client.set_nodelay(true)?;
+    let peer = client.peer_addr().ok().map(|addr| addr.ip());
+    // A client that connects and sends nothing is dropped like an idle relayed connection.
+    client.set_read_timeout(upstream.idle_timeout())?;
+    let (buf, head_len) = read_head(&mut client)?;
+    if buf.is_empty() {
+        return Ok(());
+    }
+    let head = head_len.and_then(|len| Some((parse_head(&buf[..len])?, len)));
+    match head {
+        Some((head, len)) if is_upgrade(&head) => {
+            let buffered = buf[len..].to_vec();
+            // WebSocket connections may stay quiet for as long as the handler likes.
+            client.set_read_timeout(None)?;
+            upgrade_connection(client, &head, buffered, service)
+        }
+        Some((head, len)) if service.expect_continue && head.header("expect").is_some() => {
+            if let Some(rejection) = expect::preflight(service, &head) {
+                return write_outcome(&mut client, rejection);
+            }
+            client.write_all(expect::CONTINUE)?;
+            let mut prefix = expect::strip_expect(&buf[..len]);
+            prefix.extend_from_slice(&buf[len..]);
+            relay::relay(client, upstream, &prefix, peer)
+        }
+        _ => relay::relay(client, upstream, &buf, peer),
+    }
+}
+
+/// Read until the end of the first request head. Returns everything read and, when the head
+/// is complete within [`DEFAULT_MAX_HEADER_BYTES`], its length including the blank line.
+fn read_head(client: &mut TcpStream) -> io::Result<(Vec<u8>, Option<usize>)> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 2048];
+    loop {
+        let n = client.read(&mut chunk)?;
+        if n == 0 {
+            return Ok((buf, None));
+        }
+        let searched_from = buf.len().saturating_sub(3);
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf[searched_from..]
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+        {
+            return Ok((buf, Some(searched_from + end + 4)));
+        }
+        if buf.len() >= DEFAULT_MAX_HEADER_BYTES {
+            return Ok((buf, None));
+        }
+    }
+}
+
+/// A parsed request head, borrowed from the bytes read by [`read_head`].
+pub(crate) struct Head<'a> {
+    pub(crate) method: &'a str,
+    pub(crate) target: &'a str,
+    pub(crate) headers: Vec<(&'a str, &'a str)>,
+}
+
+impl Head<'_> {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+
+    fn header_has_token(&self, name: &str, token: &str) -> bool {
+        self.header(name)
+            .is_some_and(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)))
+    }
+}
+
+fn parse_head(bytes: &[u8]) -> Option<Head<'_>> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let mut lines = text.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let (method, target) = (request_line.next()?, request_line.next()?);
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Head {
+        method,
+        target,
+        headers,
+    })
+}
+
+/// A version 13 WebSocket opening handshake (RFC 6455 §4.2.1).
+fn is_upgrade(head: &Head<'_>) -> bool {
+    head.method == "GET"
+        && head.header_has_token("upgrade", "websocket")
+        && head.header_has_token("connection", "upgrade")
+        && head.header("sec-websocket-version") == Some("13")
+        && head.header("sec-websocket-key").is_some()
+}
+
+fn upgrade_connection(
+    mut client: TcpStream,
+    head: &Head<'_>,
+    buffered: Vec<u8>,
+    service: &AppService,
+) -> io::Result<()> {
+    let headers = head.headers.iter().map(|(n, v)| (*n, v.as_bytes()));
+    let parsed = match parse_request_head(head.method, head.target, headers) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            let problem = ProblemDetails::new(400).detail("Malformed upgrade request");
+            return write_outcome(&mut client, RouteOutcome::problem(problem));
+        }
+    };
+    let request_id = RequestId::from_header_or_new(parsed.headers.get("x-request-id"));
+    let path = match service.strip_base_path(parsed.path) {
+        Ok(path) => path,
+        Err(path) => {
+            let outcome = service.handle_outside_base_path(
+                &parsed.method,
+                &path,
+                &parsed.headers,
+                &parsed.cookies,
+                &parsed.query_params,
+                request_id,
+            );
+            return write_outcome(&mut client, outcome);
+        }
+    };
+    let Some(route_match) = service.router.load().route(parsed.method.clone(), &path) else {
+        let outcome = service.handle_unrouted(
+            &parsed.method,
+            &path,
+            &parsed.headers,
+            &parsed.cookies,
+            &parsed.query_params,
+            request_id,
+        );
+        return write_outcome(&mut client, outcome);
+    };
+    let outcome = service.handle_route(
+        route_match,
+        RoutedRequest {
+            method: parsed.method,
+            path,
+            headers: parsed.headers,
+            cookies: parsed.cookies,
+            query_params: parsed.query_params,
+            body: None,
+            raw_body: None,
+            body_size_bytes: 0,
+            request_id,
+            upgrade: true,
+            expect_continue: false,
+        },
+    );
+    let request = match outcome {
+        RouteOutcome::Upgrade(request) => *request,
+        other => return write_outcome(&mut client, service.prefix_outcome(other)),
+    };
+    let handler = service
+        .dispatcher
+        .load()
+        .websocket_handlers
+        .get(&request.handler_name)
+        .cloned();
+    let Some(handler) = handler else {
+        let problem = ProblemDetails::new(500).detail("WebSocket handler not registered");
+        return write_outcome(&mut client, RouteOutcome::problem(problem));
+    };
+
+    let key = head.header("sec-websocket-key").unwrap_or_default();
+    client.write_all(
+        format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\nX-Request-ID: {}\r\n\r\n",
+            accept_key(key),
+            request.request_id
+        )
+        .as_bytes(),
+    )?;
+    let max_message_bytes = service.websocket.max_message_bytes;
+    let channel = WebSocketChannel::new(client.try_clone()?, buffered, max_message_bytes)?;
+    let sender = channel.sender();
+    handler(request, channel);
+    let _ = sender.close(CLOSE_NORMAL, "");
+    client.shutdown(Shutdown::Write)
+}
+
+/// Answer a rejected upgrade with `outcome` and close the connection.
+fn write_outcome(client: &mut TcpStream, outcome: RouteOutcome) -> io::Result<()> {
+    let (status, headers, content_type, body) = match outcome {
+        RouteOutcome::Problem { problem, headers } => {
+            (problem.status, headers, PROBLEM_JSON, problem.to_value())
+        }
+        RouteOutcome::Handler {
+            status,
+            body,
+            headers,
+            ..
+        } => (status, headers, "application/json", body),
+        RouteOutcome::Upgrade(_) => {
+            let problem = ProblemDetails::new(500).detail("Unexpected WebSocket upgrade");
+            (
+                problem.status,
+                HeaderVec::new(),
+                PROBLEM_JSON,
+                problem.to_value(),
+            )
+        }
+    };
+    let body = serde_json::to_vec(&body).map_err(io::Error::other)?;
+    let reason = http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("");
+    let mut out = format!("HTTP/1.1 {status} {reason}\r\n");
+    let mut has_content_type = false;
+    for (name, value) in &headers {
+        if name.eq_ignore_ascii_case("content-length") || name.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        has_content_type |= name.eq_ignore_ascii_case("content-type");
+        out.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if !has_content_type {
+        out.push_str(&format!("Content-Type: {content_type}\r\n"));
+    }
+    out.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+    client.write_all(out.as_bytes())?;
+    client.write_all(&body)?;
+    client.shutdown(Shutdown::Both)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        // RFC 6455 §1.3
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kCE9Ic1E4dJqK8="
+        );
+    }
+
+    #[test]
+    fn frames_are_unmasked_and_bounded() {
+        let mask = [1u8, 2, 3, 4];
+        let masked: Vec<u8> = b"hi!"
+            .iter()
+            .zip(mask.iter().cycle())
+            .map(|(b, m)| b ^ m)
+            .collect();
+        let mut wire = vec![0x81, 0x80 | 3];
+        wire.extend_from_slice(&mask);
+        wire.extend_from_slice(&masked);
+
+        let frame = read_frame(&mut Cursor::new(wire.clone()), 16).ok().unwrap();
+        assert!(frame.fin);
+        assert_eq!(frame.opcode, OP_TEXT);
+        assert_eq!(frame.payload, b"hi!");
+
+        assert!(matches!(
+            read_frame(&mut Cursor::new(wire), 2),
+            Err(FrameError::Close(CLOSE_TOO_BIG, _))
+        ));
+        assert!(matches!(
+            read_frame(&mut Cursor::new(vec![0x81, 0x01, b'x']), 16),
+            Err(FrameError::Close(CLOSE_PROTOCOL_ERROR, _))
+        ));
+    }
+
+    #[test]
+    fn only_version_13_upgrades_are_taken_over() {
+        let request = b"GET /chat HTTP/1.1\r\nHost: x\r\nUpgrade: WebSocket\r\n\
+                        Connection: keep-alive, Upgrade\r\nSec-WebSocket-Key: abc\r\n\
+                        Sec-WebSocket-Version: 13\r\n\r\n";
+        assert!(is_upgrade(&parse_head(request).unwrap()));
+
+        let plain = b"GET /chat HTTP/1.1\r\nHost: x\r\n\r\n";
+        assert!(!is_upgrade(&parse_head(plain).unwrap()));
+    }
+}
@@ -1,13 +1,16 @@
 use super::security_presence::{resolve_operation_security, OperationSecurityPresence};
 use super::types::{
-    ParameterLocation, ParameterMeta, ParameterStyle, ResponseSpec, Responses, RouteMeta,
+    CreatedLocation, HeadStrategy, LinkTarget, ParameterLocation, ParameterMeta, ParameterStyle,
+    ResponseHeaderSpec, ResponseHeaders, ResponseLink, ResponseSpec, Responses, RouteMeta,
+    SchemaOverrides, UploadLimit, UploadLimits,
 };
 use super::SecurityScheme;
-use crate::validator::{fail_if_issues, ValidationIssue};
+use http::Method;
 use oas3::spec::{MediaTypeExamples, ObjectOrReference, Parameter};
 use oas3::OpenApiV3Spec;
 use serde_json::Value;
 use std::cmp;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 /// Maximum estimated size for unbounded types (arrays/strings without maxItems/maxLength)
@@ -53,20 +56,50 @@ pub fn resolve_schema_ref<'a>(
 /// resolved schema definitions from the OpenAPI spec. Adds an `x-ref-name` field
 /// to track the original reference name.
 ///
+/// Self-referential schemas (`Node.next -> Node`, `A -> B -> A`) are expanded once per
+/// path: a `$ref` to a schema that is already being expanded further up is rewritten to a
+/// local JSON pointer (`#/properties/next`) at that enclosing expansion, keeping its
+/// `x-ref-name`, so the result stays finite and still validates recursively.
+///
 /// # Arguments
 ///
 /// * `spec` - The OpenAPI specification
 /// * `value` - The JSON value to process (modified in-place)
 pub fn expand_schema_refs(spec: &OpenApiV3Spec, value: &mut Value) {
+    expand_schema_refs_at(spec, value, &mut String::new(), &mut Vec::new());
+}
+
+/// [`expand_schema_refs`] at JSON pointer `pointer`, with `active` holding the `$ref`s
+/// currently being expanded and the pointer each was expanded at.
+fn expand_schema_refs_at(
+    spec: &OpenApiV3Spec,
+    value: &mut Value,
+    pointer: &mut String,
+    active: &mut Vec<(String, String)>,
+) {
     match value {
         Value::Object(obj) => {
             if let Some(ref_path) = obj.get("$ref").and_then(|v| v.as_str()) {
+                let ref_name = ref_path
+                    .strip_prefix("#/components/schemas/")
+                    .map(str::to_string);
+                if let Some((_, at)) = active.iter().find(|(r, _)| r == ref_path) {
+                    let mut local = serde_json::Map::new();
+                    local.insert("$ref".to_string(), Value::String(format!("#{at}")));
+                    if let Some(name) = ref_name {
+                        local.insert("x-ref-name".to_string(), Value::String(name));
+                    }
+                    *value = Value::Object(local);
+                    return;
+                }
                 if let Some(schema) = resolve_schema_ref(spec, ref_path) {
                     if let Ok(mut new_val) = serde_json::to_value(schema) {
-                        expand_schema_refs(spec, &mut new_val);
-                        if let Some(name) = ref_path.strip_prefix("#/components/schemas/") {
+                        active.push((ref_path.to_string(), pointer.clone()));
+                        expand_schema_refs_at(spec, &mut new_val, pointer, active);
+                        active.pop();
+                        if let Some(name) = ref_name {
                             if let Value::Object(o) = &mut new_val {
-                                o.insert("x-ref-name".to_string(), Value::String(name.to_string()));
+                                o.insert("x-ref-name".to_string(), Value::String(name));
                             }
                         }
                         *value = new_val;
@@ -74,13 +107,21 @@ pub fn expand_schema_refs(spec: &OpenApiV3Spec, value: &mut Value) {
                     }
                 }
             }
-            for v in obj.values_mut() {
-                expand_schema_refs(spec, v);
+            for (key, v) in obj.iter_mut() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                expand_schema_refs_at(spec, v, pointer, active);
+                pointer.truncate(len);
             }
         }
         Value::Array(arr) => {
-            for v in arr.iter_mut() {
-                expand_schema_refs(spec, v);
+            for (index, v) in arr.iter_mut().enumerate() {
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+                expand_schema_refs_at(spec, v, pointer, active);
+                pointer.truncate(len);
             }
         }
         _ => {}
@@ -195,11 +236,8 @@ pub fn estimate_body_size(schema: Option<&Value>) -> Option<usize> {
     schema.map(|s| estimate_schema_size(s, 0))
 }
 
-fn resolve_handler_name(
-    operation: &oas3::spec::Operation,
-    location: &str,
-    issues: &mut Vec<ValidationIssue>,
-) -> Option<String> {
+/// Handler name from `x-handler-*` or `operationId`; `None` when the spec names neither.
+fn declared_handler_name(operation: &oas3::spec::Operation) -> Option<String> {
     operation
         .extensions
         .iter()
@@ -212,14 +250,62 @@ fn resolve_handler_name(
             None
         })
         .or_else(|| operation.operation_id.clone())
-        .or_else(|| {
-            issues.push(ValidationIssue::new(
-                location,
-                "MissingHandler",
-                "Missing operationId or x-handler-* extension",
-            ));
-            None
-        })
+}
+
+/// Handler name for an operation with neither `operationId` nor `x-handler-*`
+///
+/// The name depends only on the method and the path template, so regenerating from the same
+/// spec always yields the same registry:
+///
+/// 1. Start with the lowercase method.
+/// 2. Append each non-empty path segment: a parameter segment `{petId}` becomes `by_pet_id`,
+///    any other segment is snake-cased (`petToys` → `pet_toys`).
+/// 3. Snake-casing splits lowercase/digit → uppercase boundaries, lowercases ASCII letters and
+///    turns every other character (`-`, `.`, braces, non-ASCII) into `_`.
+/// 4. Join with `_`, collapse runs of `_` and trim trailing ones.
+///
+/// ```rust
+/// use brrtrouter::spec::synthesize_handler_name;
+///
+/// assert_eq!(synthesize_handler_name("GET", "/pets/{id}"), "get_pets_by_id");
+/// assert_eq!(synthesize_handler_name("post", "/petStore/{storeId}/toys"), "post_pet_store_by_store_id_toys");
+/// assert_eq!(synthesize_handler_name("GET", "/"), "get");
+/// ```
+#[must_use]
+pub fn synthesize_handler_name(method: &str, path: &str) -> String {
+    fn push_snake(out: &mut String, segment: &str) {
+        let mut prev_lower = false;
+        for c in segment.chars() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            if c.is_ascii_alphanumeric() {
+                out.push(c.to_ascii_lowercase());
+            } else {
+                out.push('_');
+            }
+        }
+    }
+
+    let mut raw = method.to_ascii_lowercase();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        raw.push('_');
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => {
+                raw.push_str("by_");
+                push_snake(&mut raw, param);
+            }
+            None => push_snake(&mut raw, segment),
+        }
+    }
+    let mut name = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        if c != '_' || !name.ends_with('_') {
+            name.push(c);
+        }
+    }
+    name.trim_end_matches('_').to_string()
 }
 
 /// Extract the request body schema from an OpenAPI operation
@@ -285,6 +371,122 @@ pub fn extract_request_body_details(
     (schema, required, content_types)
 }
 
+/// Size and content-type limits of the fields of an operation's `multipart/form-data` body
+///
+/// For each property of the multipart schema, `x-brrtrouter-max-bytes` caps the part size and
+/// `encoding.<field>.contentType` (comma-separated), else the property's `contentMediaType`,
+/// lists the accepted part types. Properties with neither are left out.
+pub fn extract_upload_limits(
+    spec: &OpenApiV3Spec,
+    operation: &oas3::spec::Operation,
+) -> UploadLimits {
+    let Some(ObjectOrReference::Object(req_body)) = operation.request_body.as_ref() else {
+        return UploadLimits::new();
+    };
+    let Some(media) = req_body.content.get("multipart/form-data") else {
+        return UploadLimits::new();
+    };
+    let Some(mut schema) = media.schema.as_ref().and_then(|schema| match schema {
+        ObjectOrReference::Object(schema_obj) => serde_json::to_value(schema_obj).ok(),
+        ObjectOrReference::Ref { ref_path, .. } => {
+            resolve_schema_ref(spec, ref_path).and_then(|s| serde_json::to_value(s).ok())
+        }
+    }) else {
+        return UploadLimits::new();
+    };
+    expand_schema_refs(spec, &mut schema);
+    let encoding = serde_json::to_value(media)
+        .ok()
+        .and_then(|media| media.get("encoding").cloned())
+        .unwrap_or(Value::Null);
+
+    let mut limits = UploadLimits::new();
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return limits;
+    };
+    for (field, property) in properties {
+        // An array of files limits each of its items
+        let property = match property.get("type").and_then(Value::as_str) {
+            Some("array") => property.get("items").unwrap_or(property),
+            _ => property,
+        };
+        let max_bytes = property
+            .get("x-brrtrouter-max-bytes")
+            .and_then(Value::as_u64);
+        let content_types: Vec<String> = encoding
+            .get(field)
+            .and_then(|e| e.get("contentType"))
+            .and_then(Value::as_str)
+            .or_else(|| property.get("contentMediaType").and_then(Value::as_str))
+            .map(|types| {
+                types
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if max_bytes.is_some() || !content_types.is_empty() {
+            limits.insert(
+                field.clone(),
+                UploadLimit {
+                    max_bytes,
+                    content_types,
+                },
+            );
+        }
+    }
+    limits
+}
+
+/// Resolve an example reference (e.g. `#/components/examples/Fluffy`)
+fn resolve_example_ref<'a>(
+    spec: &'a OpenApiV3Spec,
+    ref_path: &str,
+) -> Option<&'a oas3::spec::Example> {
+    if let Some(name) = ref_path.strip_prefix("#/components/examples/") {
+        spec.components
+            .as_ref()?
+            .examples
+            .get(name)
+            .and_then(|example_ref| match example_ref {
+                ObjectOrReference::Object(example) => Some(example),
+                _ => None,
+            })
+    } else {
+        None
+    }
+}
+
+/// Named example values of an `examples` map, resolving `$ref`s to `components.examples`
+///
+/// Examples without an inline `value` (e.g. `externalValue` only) are skipped.
+fn named_examples<'a>(
+    spec: &OpenApiV3Spec,
+    examples: impl IntoIterator<Item = (&'a String, &'a ObjectOrReference<oas3::spec::Example>)>,
+) -> Vec<(String, Value)> {
+    examples
+        .into_iter()
+        .filter_map(|(name, example_ref)| {
+            let example = match example_ref {
+                ObjectOrReference::Object(example) => Some(example),
+                ObjectOrReference::Ref { ref_path, .. } => resolve_example_ref(spec, ref_path),
+            }?;
+            Some((name.clone(), example.value.clone()?))
+        })
+        .collect()
+}
+
+/// The default of a set of named examples: the one named `default`, else the first
+pub fn default_named_example(examples: &[(String, Value)]) -> Option<&Value> {
+    examples
+        .iter()
+        .find(|(name, _)| name == "default")
+        .or_else(|| examples.first())
+        .map(|(_, value)| value)
+}
+
 /// Extract response schemas and examples from an OpenAPI operation
 ///
 /// Parses all response definitions from an operation and extracts schemas, examples,
@@ -307,9 +509,6 @@ pub fn extract_response_schema_and_example(
     operation: &oas3::spec::Operation,
 ) -> (Option<Value>, Option<Value>, Responses) {
     let mut all: Responses = std::collections::HashMap::new();
-    let mut default_schema = None;
-    let mut default_example = None;
-
     if let Some(responses_map) = operation.responses.as_ref() {
         for (status_str, resp_ref) in responses_map {
             let status: u16 = match status_str.parse() {
@@ -318,15 +517,15 @@ pub fn extract_response_schema_and_example(
             };
             if let ObjectOrReference::Object(resp_obj) = resp_ref {
                 for (mt, media) in &resp_obj.content {
-                    let example = match &media.examples {
-                        Some(MediaTypeExamples::Example { example }) => Some(example.clone()),
+                    let (example, examples) = match &media.examples {
+                        Some(MediaTypeExamples::Example { example }) => {
+                            (Some(example.clone()), Vec::new())
+                        }
                         Some(MediaTypeExamples::Examples { examples }) => {
-                            examples.iter().find_map(|(_, v)| match v {
-                                ObjectOrReference::Object(obj) => obj.value.clone(),
-                                _ => None,
-                            })
+                            let examples = named_examples(spec, examples);
+                            (default_named_example(&examples).cloned(), examples)
                         }
-                        None => None,
+                        None => (None, Vec::new()),
                     };
 
                     let mut schema = match media.schema.as_ref() {
@@ -346,68 +545,393 @@ pub fn extract_response_schema_and_example(
                     all.entry(status).or_default().insert(
                         mt.clone(),
                         ResponseSpec {
-                            schema: schema.clone(),
-                            example: example.clone(),
+                            schema,
+                            example,
+                            examples,
                         },
                     );
-
-                    if status == 200 && mt == "application/json" {
-                        default_schema = schema;
-                        default_example = example;
-                    }
                 }
             }
         }
     }
 
+    let (default_schema, default_example) = select_default_response(&all)
+        .map(|spec| (spec.schema.clone(), spec.example.clone()))
+        .unwrap_or_default();
+    (default_schema, default_example, all)
+}
+
+/// The response a route's `response_schema` and `example` are taken from
+///
+/// Prefers 200 `application/json`, then the first 2xx `application/json`, then the first
+/// 2xx media type with a schema or example, then the first `application/json` of any status.
+pub fn select_default_response(all: &Responses) -> Option<&ResponseSpec> {
+    let mut statuses: Vec<u16> = all.keys().cloned().collect();
+    statuses.sort_unstable();
+    let has_schema = |spec: Option<&ResponseSpec>| spec.is_some_and(|s| s.schema.is_some());
+
+    let mut default = all.get(&200).and_then(|m| m.get("application/json"));
+
     // Fallback selection if no 200 application/json found
-    if default_schema.is_none() {
+    if !has_schema(default) {
         // Prefer any 2xx with application/json
-        let mut statuses: Vec<u16> = all.keys().cloned().collect();
-        statuses.sort_unstable();
-        if let Some((schema, example)) = statuses
+        if let Some(spec) = statuses
             .iter()
             .filter(|s| **s >= 200 && **s < 300)
             .find_map(|s| all.get(s).and_then(|m| m.get("application/json")))
-            .map(|spec| (spec.schema.clone(), spec.example.clone()))
         {
-            default_schema = schema;
-            default_example = example;
+            default = Some(spec);
         }
     }
 
-    if default_schema.is_none() {
+    if !has_schema(default) {
         // Next, any 2xx with any media type
-        let mut statuses: Vec<u16> = all.keys().cloned().collect();
-        statuses.sort_unstable();
-        'outer: for s in statuses.iter().filter(|s| **s >= 200 && **s < 300) {
-            if let Some(mt_map) = all.get(s) {
-                for spec in mt_map.values() {
-                    if spec.schema.is_some() || spec.example.is_some() {
-                        default_schema = spec.schema.clone();
-                        default_example = spec.example.clone();
-                        break 'outer;
-                    }
-                }
-            }
+        if let Some(spec) = statuses
+            .iter()
+            .filter(|s| **s >= 200 && **s < 300)
+            .filter_map(|s| all.get(s))
+            .flat_map(|mt_map| mt_map.values())
+            .find(|spec| spec.schema.is_some() || spec.example.is_some())
+        {
+            default = Some(spec);
         }
     }
 
-    if default_schema.is_none() {
+    if !has_schema(default) {
         // Finally, any status preferring application/json
-        let mut statuses: Vec<u16> = all.keys().cloned().collect();
-        statuses.sort_unstable();
-        if let Some((schema, example)) = statuses
+        if let Some(spec) = statuses
             .iter()
             .find_map(|s| all.get(s).and_then(|m| m.get("application/json")))
-            .map(|spec| (spec.schema.clone(), spec.example.clone()))
         {
-            default_schema = schema;
-            default_example = example;
+            default = Some(spec);
         }
     }
 
-    (default_schema, default_example, all)
+    default
+}
+
+/// Response examples of a route that do not validate against their media type's schema
+///
+/// Checks every named example (or the singular `example`) of every response; returns one
+/// message per failing example. Used by the generator to warn without failing generation.
+pub fn invalid_response_examples(route: &RouteMeta) -> Vec<String> {
+    let mut statuses: Vec<u16> = route.responses.keys().cloned().collect();
+    statuses.sort_unstable();
+    let mut messages = Vec::new();
+    for status in statuses {
+        let Some(media_types) = route.responses.get(&status) else {
+            continue;
+        };
+        let mut media_types: Vec<_> = media_types.iter().collect();
+        media_types.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        for (mt, spec) in media_types {
+            let Some(validator) = spec
+                .schema
+                .as_ref()
+                .and_then(|schema| jsonschema::validator_for(schema).ok())
+            else {
+                continue;
+            };
+            let candidates: Vec<(&str, &Value)> = if spec.examples.is_empty() {
+                spec.example.iter().map(|v| ("example", v)).collect()
+            } else {
+                spec.examples.iter().map(|(n, v)| (n.as_str(), v)).collect()
+            };
+            for (name, value) in candidates {
+                if let Some(err) = validator.iter_errors(value).next() {
+                    messages.push(format!(
+                        "{} {} → {status} {mt}: example '{name}' does not match its schema at #{}: {err}",
+                        route.method,
+                        route.path_pattern,
+                        err.instance_path(),
+                    ));
+                }
+            }
+        }
+    }
+    messages
+}
+
+/// Parse one OpenAPI Link Object declared as `name` under response `status`
+///
+/// The returned link has no [`ResponseLink::target_route`] yet; [`build_routes`] resolves it
+/// once every operation is known.
+///
+/// # Errors
+///
+/// Returns why the link is malformed: it must be an object declaring exactly one of
+/// `operationId` / `operationRef` as a string, and `parameters`, when present, must be a map.
+pub fn parse_response_link(status: &str, name: &str, link: &Value) -> Result<ResponseLink, String> {
+    let obj = link
+        .as_object()
+        .ok_or_else(|| "link is not an object".to_string())?;
+    let target = match (obj.get("operationId"), obj.get("operationRef")) {
+        (Some(Value::String(id)), None) => LinkTarget::OperationId(id.clone()),
+        (None, Some(Value::String(reference))) => LinkTarget::OperationRef(reference.clone()),
+        (Some(_), Some(_)) => return Err("declares both operationId and operationRef".into()),
+        (None, None) => return Err("declares neither operationId nor operationRef".into()),
+        _ => return Err("operationId/operationRef must be a string".into()),
+    };
+    let parameters = match obj.get("parameters") {
+        None => Vec::new(),
+        Some(Value::Object(map)) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        Some(_) => return Err("parameters must be a map".into()),
+    };
+    Ok(ResponseLink {
+        status: status.to_string(),
+        name: name.to_string(),
+        target,
+        target_route: None,
+        parameters,
+        request_body: obj.get("requestBody").cloned(),
+        description: obj
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// Resolve a `#/components/links/{name}` reference to the raw Link Object
+fn resolve_link_ref(spec: &OpenApiV3Spec, ref_path: &str) -> Option<Value> {
+    let name = ref_path.strip_prefix("#/components/links/")?;
+    let components = serde_json::to_value(spec.components.as_ref()?).ok()?;
+    components.get("links")?.get(name).cloned()
+}
+
+/// Extract the response `links` of an operation, ordered by status then link name
+///
+/// `#/components/links/...` references are resolved; unresolvable references and malformed
+/// links (see [`parse_response_link`]) are skipped with a warning instead of failing the spec.
+pub fn extract_response_links(
+    spec: &OpenApiV3Spec,
+    operation: &oas3::spec::Operation,
+    location: &str,
+) -> Vec<ResponseLink> {
+    let Some(responses) = operation.responses.as_ref() else {
+        return Vec::new();
+    };
+    let mut links = Vec::new();
+    for (status, response) in responses {
+        let ObjectOrReference::Object(response) = response else {
+            continue;
+        };
+        let Ok(response) = serde_json::to_value(response) else {
+            continue;
+        };
+        let Some(map) = response.get("links").and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, link) in map {
+            let resolved = match link.get("$ref").and_then(Value::as_str) {
+                Some(ref_path) => match resolve_link_ref(spec, ref_path) {
+                    Some(resolved) => resolved,
+                    None => {
+                        tracing::warn!(
+                            "ignoring link '{name}' of {location} response {status}: unresolved $ref {ref_path}"
+                        );
+                        continue;
+                    }
+                },
+                None => link.clone(),
+            };
+            match parse_response_link(status, name, &resolved) {
+                Ok(link) => links.push(link),
+                Err(reason) => tracing::warn!(
+                    "ignoring malformed link '{name}' of {location} response {status}: {reason}"
+                ),
+            }
+        }
+    }
+    links.sort_by(|a, b| (&a.status, &a.name).cmp(&(&b.status, &b.name)));
+    links
+}
+
+/// Resolve a `#/components/headers/{name}` reference to the raw Header Object
+fn resolve_header_ref(spec: &OpenApiV3Spec, ref_path: &str) -> Option<Value> {
+    let name = ref_path.strip_prefix("#/components/headers/")?;
+    let components = serde_json::to_value(spec.components.as_ref()?).ok()?;
+    components.get("headers")?.get(name).cloned()
+}
+
+/// Extract the response `headers` an operation declares, by numeric status
+///
+/// `#/components/headers/...` references and schema `$ref`s are resolved; unresolvable
+/// references are skipped with a warning. `Content-Type` is left out: OpenAPI ignores a
+/// header definition by that name.
+pub fn extract_response_headers(
+    spec: &OpenApiV3Spec,
+    operation: &oas3::spec::Operation,
+    location: &str,
+) -> ResponseHeaders {
+    let mut all = ResponseHeaders::new();
+    let Some(responses) = operation.responses.as_ref() else {
+        return all;
+    };
+    for (status_str, response) in responses {
+        let (Ok(status), ObjectOrReference::Object(response)) =
+            (status_str.parse::<u16>(), response)
+        else {
+            continue;
+        };
+        let Ok(response) = serde_json::to_value(response) else {
+            continue;
+        };
+        let Some(map) = response.get("headers").and_then(Value::as_object) else {
+            continue;
+        };
+        let mut headers = Vec::new();
+        for (name, header) in map {
+            if name.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+            let header = match header.get("$ref").and_then(Value::as_str) {
+                Some(ref_path) => match resolve_header_ref(spec, ref_path) {
+                    Some(resolved) => resolved,
+                    None => {
+                        tracing::warn!(
+                            "ignoring header '{name}' of {location} response {status}: unresolved $ref {ref_path}"
+                        );
+                        continue;
+                    }
+                },
+                None => header.clone(),
+            };
+            let mut schema = header.get("schema").cloned();
+            if let Some(schema) = schema.as_mut() {
+                expand_schema_refs(spec, schema);
+            }
+            headers.push(ResponseHeaderSpec {
+                name: name.clone(),
+                required: header.get("required").and_then(Value::as_bool) == Some(true),
+                schema,
+            });
+        }
+        if !headers.is_empty() {
+            headers.sort_by(|a, b| a.name.cmp(&b.name));
+            all.insert(status, headers);
+        }
+    }
+    all
+}
+
+/// `(path, method)` of a local `operationRef` such as `#/paths/~1pets~1{id}/get`
+///
+/// The fragment is percent-decoded before JSON-pointer unescaping (`~1` → `/`, `~0` → `~`).
+/// Returns `None` for references into other documents.
+fn local_operation_ref(reference: &str) -> Option<(String, String)> {
+    let (path, method) = reference.strip_prefix("#/paths/")?.rsplit_once('/')?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&decoded)
+        .replace("~1", "/")
+        .replace("~0", "~");
+    Some((path, method.to_ascii_lowercase()))
+}
+
+/// Fill in [`ResponseLink::target_route`] for every route's links
+///
+/// `operation_ids` maps each declared `operationId` to the index of its route. Links to an
+/// unknown `operationId`, or a local `operationRef` matching no operation, are dropped with a
+/// warning; `operationRef`s into other documents are kept unresolved.
+fn resolve_link_targets(routes: &mut [RouteMeta], operation_ids: &HashMap<String, usize>) {
+    let targets: Vec<(Method, Arc<str>)> = routes
+        .iter()
+        .map(|r| (r.method.clone(), Arc::clone(&r.path_pattern)))
+        .collect();
+    for route in routes.iter_mut() {
+        let (method, path) = (&route.method, &route.path_pattern);
+        route.links.retain_mut(|link| {
+            let target = match &link.target {
+                LinkTarget::OperationId(id) => operation_ids
+                    .get(id)
+                    .and_then(|&index| targets.get(index))
+                    .cloned(),
+                LinkTarget::OperationRef(reference) => match local_operation_ref(reference) {
+                    Some((ref_path, ref_method)) => targets
+                        .iter()
+                        .find(|(m, p)| {
+                            m.as_str().eq_ignore_ascii_case(&ref_method) && **p == *ref_path
+                        })
+                        .cloned(),
+                    None => return true,
+                },
+            };
+            if target.is_none() {
+                tracing::warn!(
+                    "ignoring link '{}' of {method} {path} response {}: {:?} matches no operation",
+                    link.name,
+                    link.status,
+                    link.target
+                );
+            }
+            link.target_route = target;
+            link.target_route.is_some()
+        });
+    }
+}
+
+/// Whether the operation's `201` response declares a `Location` header
+fn declares_created_location(operation: &oas3::spec::Operation) -> bool {
+    let Some(ObjectOrReference::Object(created)) =
+        operation.responses.as_ref().and_then(|r| r.get("201"))
+    else {
+        return false;
+    };
+    serde_json::to_value(created)
+        .ok()
+        .as_ref()
+        .and_then(|r| r.get("headers"))
+        .and_then(Value::as_object)
+        .is_some_and(|headers| headers.keys().any(|h| h.eq_ignore_ascii_case("location")))
+}
+
+/// Fill in [`RouteMeta::created_location`] for the routes at `creating`
+///
+/// The created resource is read back by the `GET` route whose path is the creating route's
+/// plus one parameter segment (`POST /pets` → `GET /pets/{id}`). A `201` declaring
+/// `Location` without such a route is left alone with a warning.
+fn resolve_created_locations(routes: &mut [RouteMeta], creating: &[usize]) {
+    for &index in creating {
+        let collection = routes[index].path_pattern.trim_end_matches('/').to_string();
+        let found = routes.iter().find_map(|r| {
+            if r.method != Method::GET {
+                return None;
+            }
+            let param = r
+                .path_pattern
+                .strip_prefix(collection.as_str())?
+                .strip_prefix("/{")?
+                .strip_suffix('}')?;
+            (!param.contains(['/', '{', '}'])).then(|| CreatedLocation::new(&r.path_pattern, param))
+        });
+        let route = &mut routes[index];
+        if found.is_none() {
+            tracing::warn!(
+                "{} {} declares a 201 Location header but no GET {collection}/{{id}} route; \
+                 it is not filled in",
+                route.method,
+                route.path_pattern
+            );
+        }
+        route.created_location = found;
+    }
 }
 
 /// Extract all security schemes from an OpenAPI specification
@@ -496,6 +1020,7 @@ pub fn extract_parameters(
                 schema,
                 style: param.style.map(ParameterStyle::from),
                 explode: param.explode,
+                deprecated: param.deprecated.unwrap_or(false),
             });
         }
     }
@@ -505,7 +1030,8 @@ pub fn extract_parameters(
 /// Extract the SSE flag from an OpenAPI operation
 ///
 /// Checks for `x-sse` or `sse` extension fields to determine if the operation
-/// uses Server-Sent Events for streaming responses.
+/// uses Server-Sent Events for streaming responses. Without either, an operation
+/// with a 2xx response whose only content is `text/event-stream` is SSE.
 ///
 /// # Arguments
 ///
@@ -520,9 +1046,35 @@ pub fn extract_sse_flag(operation: &oas3::spec::Operation) -> bool {
         .get("x-sse")
         .or_else(|| operation.extensions.get("sse"))
         .and_then(|v| v.as_bool())
+        .unwrap_or_else(|| declares_event_stream(operation))
+}
+
+/// Extract the WebSocket flag from an OpenAPI operation
+///
+/// Checks for `x-websocket` or `websocket` extension fields. Such an operation is served
+/// by a handler registered with `Dispatcher::register_websocket_handler` after the upgrade
+/// handshake; plain HTTP requests to it are answered `426 Upgrade Required`.
+pub fn extract_websocket_flag(operation: &oas3::spec::Operation) -> bool {
+    operation
+        .extensions
+        .get("x-websocket")
+        .or_else(|| operation.extensions.get("websocket"))
+        .and_then(|v| v.as_bool())
         .unwrap_or(false)
 }
 
+/// True when a 2xx response of `operation` declares only `text/event-stream` content
+fn declares_event_stream(operation: &oas3::spec::Operation) -> bool {
+    operation.responses.as_ref().is_some_and(|responses| {
+        responses.iter().any(|(status, response)| {
+            status.starts_with('2')
+                && matches!(response, ObjectOrReference::Object(r)
+                    if !r.content.is_empty()
+                        && r.content.keys().all(|mt| mt.starts_with("text/event-stream")))
+        })
+    })
+}
+
 /// Extract the stack size vendor extension from an OpenAPI operation
 ///
 /// Checks for `x-brrtrouter-stack-size` extension field to get an explicit
@@ -557,6 +1109,32 @@ pub fn extract_stack_size_override(operation: &oas3::spec::Operation) -> Option<
         })
 }
 
+/// Extract the `x-brrtrouter-timeout-ms` request timeout from an OpenAPI operation
+///
+/// Accepts an integer or a numeric string; anything else yields `None` (no route timeout).
+pub fn extract_timeout_ms(operation: &oas3::spec::Operation) -> Option<u64> {
+    operation
+        .extensions
+        .get("x-brrtrouter-timeout-ms")
+        .or_else(|| operation.extensions.get("brrtrouter-timeout-ms"))
+        .and_then(|v| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        })
+}
+
+/// Extract the `x-slow-threshold-ms` vendor extension (number or numeric string).
+pub fn extract_slow_threshold_ms(operation: &oas3::spec::Operation) -> Option<u64> {
+    operation
+        .extensions
+        .get("x-slow-threshold-ms")
+        .or_else(|| operation.extensions.get("slow-threshold-ms"))
+        .and_then(|v| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        })
+}
+
 /// Extract the `x-brrtrouter-impl` tri-state marker from an OpenAPI operation.
 ///
 /// Returns `Some(true)` when real impl is required, `Some(false)` for gen-stub-only,
@@ -575,11 +1153,335 @@ pub fn extract_brrtrouter_impl(operation: &oas3::spec::Operation) -> Option<bool
         })
 }
 
+/// Extract the `x-auth-optional` flag from an OpenAPI operation.
+///
+/// When `true`, the auth step attempts validation but lets the request through
+/// anonymously if credentials are absent or invalid.
+pub fn extract_auth_optional(operation: &oas3::spec::Operation) -> bool {
+    operation
+        .extensions
+        .get("x-auth-optional")
+        .or_else(|| operation.extensions.get("auth-optional"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Extract the `x-forward-claims` claim → header mapping from an OpenAPI operation.
+///
+/// Accepts a mapping (`{sub: X-User-Id, org_id: X-Org-Id}`) or `false` to disable forwarding
+/// for the route. Non-string header names are ignored; any other value is treated as absent.
+pub fn extract_forward_claims(operation: &oas3::spec::Operation) -> Option<Vec<(String, String)>> {
+    let value = operation
+        .extensions
+        .get("x-forward-claims")
+        .or_else(|| operation.extensions.get("forward-claims"))?;
+    match value {
+        Value::Bool(false) => Some(Vec::new()),
+        Value::Object(map) => Some(
+            map.iter()
+                .filter_map(|(claim, header)| {
+                    header.as_str().map(|h| (claim.clone(), h.to_string()))
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Extract the `x-feature` flag of an OpenAPI operation: the feature that must be enabled
+/// (`BRRTR_FEATURES`) for the route to be served. Blank or non-string values are ignored.
+pub fn extract_feature(operation: &oas3::spec::Operation) -> Option<String> {
+    operation
+        .extensions
+        .get("x-feature")
+        .or_else(|| operation.extensions.get("feature"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Extract the `x-head-strategy` of an OpenAPI operation (`compute` or `estimate`).
+///
+/// Missing values give [`HeadStrategy::Compute`]; unknown ones do too, with a warning.
+pub fn extract_head_strategy(operation: &oas3::spec::Operation, location: &str) -> HeadStrategy {
+    let Some(value) = operation
+        .extensions
+        .get("x-head-strategy")
+        .or_else(|| operation.extensions.get("head-strategy"))
+    else {
+        return HeadStrategy::Compute;
+    };
+    match value.as_str().map(str::trim) {
+        Some(s) if s.eq_ignore_ascii_case("compute") => HeadStrategy::Compute,
+        Some(s) if s.eq_ignore_ascii_case("estimate") => HeadStrategy::Estimate,
+        _ => {
+            tracing::warn!(
+                "ignoring x-head-strategy {value} of {location}: expected compute or estimate"
+            );
+            HeadStrategy::Compute
+        }
+    }
+}
+
+/// Extract the `x-request-schema` / `x-response-schema` validation overrides of an operation.
+///
+/// `x-response-schema` is either one schema, applied to every 2xx response, or a map from
+/// status code to schema (`{"200": {...}, "404": {...}}`). `$ref`s are expanded against
+/// `components` like the declared schemas.
+///
+/// # Errors
+///
+/// Returns an error naming `location` when an override is not an object or boolean, or does
+/// not compile as a JSON Schema; a broken override would otherwise only surface as a 500 on
+/// the first request.
+pub fn extract_schema_overrides(
+    spec: &OpenApiV3Spec,
+    operation: &oas3::spec::Operation,
+    location: &str,
+) -> anyhow::Result<SchemaOverrides> {
+    let extension = |name: &str| {
+        operation
+            .extensions
+            .get(&format!("x-{name}"))
+            .or_else(|| operation.extensions.get(name))
+    };
+    let checked = |name: &str, value: &Value| -> anyhow::Result<Value> {
+        let mut schema = value.clone();
+        expand_schema_refs(spec, &mut schema);
+        if !(schema.is_object() || schema.is_boolean()) {
+            return Err(anyhow::anyhow!(
+                "{location}: x-{name} must be a JSON Schema object or boolean"
+            ));
+        }
+        jsonschema::validator_for(&schema).map_err(|err| {
+            anyhow::anyhow!("{location}: x-{name} is not a valid JSON Schema: {err}")
+        })?;
+        Ok(schema)
+    };
+
+    let mut overrides = SchemaOverrides::default();
+    if let Some(value) = extension("request-schema") {
+        overrides.request = Some(checked("request-schema", value)?);
+    }
+    if let Some(value) = extension("response-schema") {
+        let by_status = value
+            .as_object()
+            .filter(|map| !map.is_empty() && map.keys().all(|key| key.parse::<u16>().is_ok()));
+        match by_status {
+            Some(map) => {
+                for (status, schema) in map {
+                    let name = format!("response-schema.{status}");
+                    overrides
+                        .responses
+                        .insert(status.parse()?, checked(&name, schema)?);
+                }
+            }
+            None => overrides.success_response = Some(checked("response-schema", value)?),
+        }
+    }
+    Ok(overrides)
+}
+
+/// Environment variable selecting the [`DuplicateRoutePolicy`] (`error` or `warn`).
+pub const DUPLICATE_ROUTES_ENV: &str = "BRRTR_DUPLICATE_ROUTES";
+
+/// How route building treats two operations sharing a handler name, or the same method on
+/// paths that compile to the same template (`/pets/{id}` and `/pets/{petId}`).
+///
+/// Either collision makes one operation dispatch to the other's handler, so the default is
+/// to refuse the spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateRoutePolicy {
+    /// Fail route building with an error naming both operations (default)
+    #[default]
+    Error,
+    /// Log a warning per collision and keep all routes
+    Warn,
+}
+
+impl DuplicateRoutePolicy {
+    /// Policy from `BRRTR_DUPLICATE_ROUTES` (`warn` / `lenient`); [`Self::Error`] otherwise.
+    #[must_use]
+    pub fn from_env() -> Self {
+        match std::env::var(DUPLICATE_ROUTES_ENV)
+            .map(|v| v.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Ok("warn" | "lenient") => Self::Warn,
+            _ => Self::Error,
+        }
+    }
+}
+
+/// `/pets/{id}` and `/pets/{petId}` both become `/pets/{}`: paths that differ only in
+/// parameter names match the same requests.
+pub(crate) fn normalize_path_template(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut in_param = false;
+    for c in path.chars() {
+        match c {
+            '{' => {
+                in_param = true;
+                out.push_str("{}");
+            }
+            '}' => in_param = false,
+            _ if !in_param => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Detect routes sharing a handler name or a (method, path template) pair.
+///
+/// Every collision is described with both operations (`GET /pets/{id}`); with
+/// [`DuplicateRoutePolicy::Error`] they are returned as one error, with
+/// [`DuplicateRoutePolicy::Warn`] they are logged and `Ok` is returned.
+///
+/// # Errors
+///
+/// With [`DuplicateRoutePolicy::Error`], when any collision exists.
+pub fn check_duplicate_routes(
+    routes: &[RouteMeta],
+    policy: DuplicateRoutePolicy,
+) -> anyhow::Result<()> {
+    let mut handlers: HashMap<&str, &RouteMeta> = HashMap::new();
+    let mut templates: HashMap<(&Method, String), &RouteMeta> = HashMap::new();
+    let mut collisions = Vec::new();
+    for route in routes {
+        if let Some(first) = handlers.insert(route.handler_name.as_ref(), route) {
+            collisions.push(format!(
+                "handler '{}' is used by both {} {} and {} {}",
+                route.handler_name,
+                first.method,
+                first.path_pattern,
+                route.method,
+                route.path_pattern
+            ));
+        }
+        let key = (&route.method, normalize_path_template(&route.path_pattern));
+        if let Some(first) = templates.insert(key, route) {
+            collisions.push(format!(
+                "{} {} and {} {} match the same requests (handlers '{}' and '{}')",
+                first.method,
+                first.path_pattern,
+                route.method,
+                route.path_pattern,
+                first.handler_name,
+                route.handler_name
+            ));
+        }
+    }
+    if collisions.is_empty() {
+        return Ok(());
+    }
+    match policy {
+        DuplicateRoutePolicy::Error => Err(anyhow::anyhow!(
+            "duplicate routes in spec (set {DUPLICATE_ROUTES_ENV}=warn to allow):\n  {}",
+            collisions.join("\n  ")
+        )),
+        DuplicateRoutePolicy::Warn => {
+            for collision in &collisions {
+                tracing::warn!("duplicate route: {collision}");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Environment variable selecting the [`UndefinedSchemePolicy`] (`strict` or `lenient`).
+pub const UNDEFINED_SECURITY_SCHEMES_ENV: &str = "BRRTR_UNDEFINED_SECURITY_SCHEMES";
+
+/// How route building treats a `security` requirement naming a scheme that
+/// `components.securitySchemes` does not define.
+///
+/// No credential can satisfy such a scheme, so its requirement never passes: the route is
+/// fail-closed (`401`) unless another alternative of its `security` array succeeds. The
+/// default refuses the spec, as the reference is almost always a typo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UndefinedSchemePolicy {
+    /// Fail route building with an error listing every undefined reference (default)
+    #[default]
+    Strict,
+    /// Log a warning per reference and keep the routes, which then answer `401`
+    Lenient,
+}
+
+impl UndefinedSchemePolicy {
+    /// Policy from `BRRTR_UNDEFINED_SECURITY_SCHEMES` (`lenient` / `warn`); [`Self::Strict`]
+    /// otherwise.
+    #[must_use]
+    pub fn from_env() -> Self {
+        match std::env::var(UNDEFINED_SECURITY_SCHEMES_ENV)
+            .map(|v| v.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Ok("lenient" | "warn") => Self::Lenient,
+            _ => Self::Strict,
+        }
+    }
+}
+
+/// Detect `security` requirements naming a scheme missing from `components.securitySchemes`.
+///
+/// Each reference is described with its operation (`GET /pets: scheme 'ApiKye'`); with
+/// [`UndefinedSchemePolicy::Strict`] they are returned as one error, with
+/// [`UndefinedSchemePolicy::Lenient`] they are logged and `Ok` is returned.
+///
+/// # Errors
+///
+/// With [`UndefinedSchemePolicy::Strict`], when any route references an undefined scheme.
+pub fn check_security_scheme_refs(
+    spec: &OpenApiV3Spec,
+    routes: &[RouteMeta],
+    policy: UndefinedSchemePolicy,
+) -> anyhow::Result<()> {
+    let defined = spec.components.as_ref().map(|c| &c.security_schemes);
+    let mut undefined = Vec::new();
+    for route in routes {
+        let mut names: Vec<&str> = route
+            .security
+            .iter()
+            .flat_map(|requirement| requirement.0.keys())
+            .map(String::as_str)
+            .filter(|name| !defined.is_some_and(|schemes| schemes.contains_key(*name)))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        undefined.extend(names.into_iter().map(|name| {
+            format!(
+                "{} {} (handler '{}'): scheme '{name}'",
+                route.method, route.path_pattern, route.handler_name
+            )
+        }));
+    }
+    if undefined.is_empty() {
+        return Ok(());
+    }
+    match policy {
+        UndefinedSchemePolicy::Strict => Err(anyhow::anyhow!(
+            "security requirements reference schemes not defined in components.securitySchemes \
+             (set {UNDEFINED_SECURITY_SCHEMES_ENV}=lenient to answer them 401 instead):\n  {}",
+            undefined.join("\n  ")
+        )),
+        UndefinedSchemePolicy::Lenient => {
+            for reference in &undefined {
+                tracing::warn!(
+                    "undefined security scheme, requirement can never be satisfied: {reference}"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Build route metadata for all operations in an OpenAPI specification
 ///
 /// This is the main function that processes an OpenAPI spec and extracts all the
 /// metadata needed to generate handlers, validate requests, and register routes.
-/// It validates the spec and reports any issues found.
+/// Operations with neither `operationId` nor `x-handler-*` get a handler name from
+/// [`synthesize_handler_name`].
 ///
 /// # Arguments
 ///
@@ -592,12 +1494,38 @@ pub fn extract_brrtrouter_impl(operation: &oas3::spec::Operation) -> Option<bool
 ///
 /// # Errors
 ///
-/// Returns an error if critical validation issues are found that prevent
-/// code generation (e.g., missing handler names, invalid parameters).
+/// Returns an error if a synthesized handler name is shared with another operation, or if
+/// two operations share a handler name or path template (see [`check_duplicate_routes`];
+/// `BRRTR_DUPLICATE_ROUTES=warn` only logs those), or if a `security` requirement names a
+/// scheme `components.securitySchemes` does not define (see [`check_security_scheme_refs`];
+/// `BRRTR_UNDEFINED_SECURITY_SCHEMES=lenient` only logs those).
 pub fn build_routes(spec: &OpenApiV3Spec, slug: &str) -> anyhow::Result<Vec<RouteMeta>> {
     build_routes_with_security_presence(spec, slug, None)
 }
 
+/// Keep the routes served with `enabled_features` (see [`RouteMeta::is_enabled`]).
+///
+/// The others are left out entirely: the router answers them `404` like any unknown path,
+/// no handler has to be registered for them and `inspect` does not list them.
+pub fn enabled_routes(
+    routes: Vec<RouteMeta>,
+    enabled_features: &BTreeSet<String>,
+) -> Vec<RouteMeta> {
+    let (enabled, disabled): (Vec<_>, Vec<_>) = routes
+        .into_iter()
+        .partition(|route| route.is_enabled(enabled_features));
+    for route in &disabled {
+        tracing::info!(
+            method = %route.method,
+            path = %route.path_pattern,
+            handler = %route.handler_name,
+            feature = route.feature.as_deref().unwrap_or_default(),
+            "Route disabled: feature not enabled"
+        );
+    }
+    enabled
+}
+
 /// Build route metadata with optional explicit-operation security presence tracking.
 ///
 /// When `security_presence` is provided, operation-level `security: []` is treated as
@@ -608,7 +1536,12 @@ pub fn build_routes_with_security_presence(
     security_presence: Option<&OperationSecurityPresence>,
 ) -> anyhow::Result<Vec<RouteMeta>> {
     let mut routes = Vec::new();
-    let mut issues = Vec::new();
+    // (name, location) of handler names synthesized from method + path
+    let mut synthesized = Vec::new();
+    // operationId → index in `routes`, for resolving response links
+    let mut operation_ids = HashMap::new();
+    // indices in `routes` of operations whose 201 declares a `Location` header
+    let mut creating = Vec::new();
 
     let base_path = if let Some(server) = spec.servers.first() {
         let url_str = &server.url;
@@ -633,15 +1566,25 @@ pub fn build_routes_with_security_presence(
                 let method = method_str.clone();
                 let location = format!("{path} → {method}");
 
-                let handler_name = match resolve_handler_name(operation, &location, &mut issues) {
+                if let Some(id) = &operation.operation_id {
+                    operation_ids.insert(id.clone(), routes.len());
+                }
+                let handler_name = match declared_handler_name(operation) {
                     Some(name) => name,
-                    None => continue,
+                    None => {
+                        let name = synthesize_handler_name(method.as_str(), path);
+                        synthesized.push((name.clone(), location.clone()));
+                        name
+                    }
                 };
 
                 let (request_schema, request_body_required, request_content_types) =
                     extract_request_body_details(spec, operation);
                 let (response_schema, example, responses) =
                     extract_response_schema_and_example(spec, operation);
+                let examples = select_default_response(&responses)
+                    .map(|r| r.examples.clone())
+                    .unwrap_or_default();
 
                 let security = resolve_operation_security(
                     path,
@@ -679,6 +1622,11 @@ pub fn build_routes_with_security_presence(
 
                 let x_brrtrouter_impl = extract_brrtrouter_impl(operation);
 
+                let schema_overrides = extract_schema_overrides(spec, operation, &location)?;
+                if declares_created_location(operation) {
+                    creating.push(routes.len());
+                }
+
                 routes.push(RouteMeta {
                     method,
                     // JSF P0-2: Use Arc<str> for O(1) cloning
@@ -690,6 +1638,7 @@ pub fn build_routes_with_security_presence(
                     request_content_types,
                     response_schema,
                     example,
+                    examples,
                     responses,
                     security,
                     example_name: format!("{slug}_example"),
@@ -699,24 +1648,113 @@ pub fn build_routes_with_security_presence(
                     sse: extract_sse_flag(operation),
                     estimated_request_body_bytes,
                     x_brrtrouter_stack_size,
+                    x_brrtrouter_timeout_ms: extract_timeout_ms(operation),
+                    x_slow_threshold_ms: extract_slow_threshold_ms(operation),
                     cors_policy,
                     x_service,
                     x_brrtrouter_downstream_path,
                     x_brrtrouter_impl,
+                    auth_optional: extract_auth_optional(operation),
+                    forward_claims: extract_forward_claims(operation),
+                    tags: operation.tags.clone(),
+                    deprecated: operation.deprecated.unwrap_or(false),
+                    websocket: extract_websocket_flag(operation),
+                    links: extract_response_links(spec, operation, &location),
+                    declared_statuses: operation
+                        .responses
+                        .as_ref()
+                        .map(|r| r.keys().cloned().collect())
+                        .unwrap_or_default(),
+                    schema_overrides,
+                    created_location: None,
+                    response_headers: extract_response_headers(spec, operation, &location),
+                    upload_limits: extract_upload_limits(spec, operation),
+                    feature: extract_feature(operation),
+                    head_strategy: extract_head_strategy(operation, &location),
                 });
             }
         }
     }
 
-    fail_if_issues(issues);
+    check_synthesized_handler_names(&routes, &synthesized)?;
+    resolve_link_targets(&mut routes, &operation_ids);
+    resolve_created_locations(&mut routes, &creating);
+    check_duplicate_routes(&routes, DuplicateRoutePolicy::from_env())?;
+    check_security_scheme_refs(spec, &routes, UndefinedSchemePolicy::from_env())?;
     Ok(routes)
 }
 
+/// Synthesized names must be unique regardless of [`DuplicateRoutePolicy`]: the spec never
+/// chose them, so a collision can only be fixed by adding an `operationId`.
+fn check_synthesized_handler_names(
+    routes: &[RouteMeta],
+    synthesized: &[(String, String)],
+) -> anyhow::Result<()> {
+    let collisions: Vec<String> = synthesized
+        .iter()
+        .filter_map(|(name, location)| {
+            let others: Vec<String> = routes
+                .iter()
+                .filter(|r| r.handler_name.as_ref() == name)
+                .map(|r| format!("{} {}", r.method, r.path_pattern))
+                .collect();
+            (others.len() > 1).then(|| {
+                format!(
+                    "synthesized handler name '{name}' for {location} is shared by {}",
+                    others.join(", ")
+                )
+            })
+        })
+        .collect();
+    if collisions.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "handler name collision; add an operationId to disambiguate:\n  {}",
+        collisions.join("\n  ")
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn templates_differing_only_in_parameter_names_normalize_equal() {
+        assert_eq!(normalize_path_template("/pets/{id}"), "/pets/{}");
+        assert_eq!(
+            normalize_path_template("/pets/{petId}/toys/{toy}"),
+            normalize_path_template("/pets/{id}/toys/{t}")
+        );
+        assert_ne!(
+            normalize_path_template("/pets/{id}"),
+            normalize_path_template("/pets/mine")
+        );
+    }
+
+    #[test]
+    fn event_stream_only_success_responses_are_sse() {
+        let operation = |responses: Value| -> oas3::spec::Operation {
+            serde_json::from_value(json!({ "responses": responses })).unwrap()
+        };
+        let stream = json!({ "description": "events", "content": { "text/event-stream": {} } });
+        assert!(extract_sse_flag(&operation(json!({ "200": stream }))));
+        assert!(!extract_sse_flag(&operation(json!({ "400": stream }))));
+        assert!(!extract_sse_flag(&operation(json!({
+            "200": {
+                "description": "negotiated",
+                "content": { "text/event-stream": {}, "application/json": {} }
+            }
+        }))));
+
+        let mut opted_out = operation(json!({ "200": stream }));
+        opted_out
+            .extensions
+            .insert("sse".to_string(), Value::Bool(false));
+        assert!(!extract_sse_flag(&opted_out));
+    }
+
     #[test]
     fn test_estimate_body_size_string() {
         let schema = json!({
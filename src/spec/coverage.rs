@@ -0,0 +1,190 @@
+//! Bodies the spec lets through without a schema.
+//!
+//! A request body declared as `application/json` without a `schema` is never validated, and
+//! a response without a JSON schema (or a 2xx response without any `content`) passes
+//! response validation whatever the handler returns. [`missing_schemas`] lists those gaps
+//! per route; [`check_schemas`] turns them into a startup error with `validation.strict_schema`
+//! in `config.yaml` (`brrtrouter-gen generate --strict-schema` when generating) and into one
+//! warning per route otherwise.
+
+use std::fmt;
+use std::sync::Arc;
+
+use http::Method;
+
+use super::RouteMeta;
+
+/// One body of an operation that has no schema to validate against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaGap {
+    /// The request body declares this JSON content type without a `schema`.
+    RequestBody(String),
+    /// Response `status` declares JSON content type `content_type` without a `schema`.
+    Response {
+        /// Response status.
+        status: u16,
+        /// Declared content type.
+        content_type: String,
+    },
+    /// 2xx response (other than 204/205) without any `content`.
+    ResponseContent(u16),
+}
+
+impl fmt::Display for SchemaGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RequestBody(content_type) => {
+                write!(f, "request body {content_type} has no schema")
+            }
+            Self::Response {
+                status,
+                content_type,
+            } => write!(f, "response {status} {content_type} has no schema"),
+            Self::ResponseContent(status) => write!(f, "response {status} declares no content"),
+        }
+    }
+}
+
+/// The [`SchemaGap`]s of one route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSchemaGaps {
+    /// HTTP method of the route.
+    pub method: Method,
+    /// Path pattern of the route.
+    pub path: Arc<str>,
+    /// Handler name of the route.
+    pub handler_name: Arc<str>,
+    /// Gaps, request body first, then responses by status.
+    pub gaps: Vec<SchemaGap>,
+}
+
+impl fmt::Display for RouteSchemaGaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({}): ", self.method, self.path, self.handler_name)?;
+        for (i, gap) in self.gaps.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{gap}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned by [`check_schemas`] in strict mode: every route with a [`SchemaGap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSchemaError(pub Vec<RouteSchemaGaps>);
+
+impl fmt::Display for MissingSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} operation(s) lack a schema", self.0.len())?;
+        for route in &self.0 {
+            write!(f, "\n  {route}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingSchemaError {}
+
+/// Routes with a body the spec declares but gives no schema, in route order.
+///
+/// Only JSON bodies are expected to have a schema; other media types (`text/plain`,
+/// `multipart/form-data`, `text/event-stream`, ...) are not validated anyway. WebSocket
+/// routes are skipped.
+#[must_use]
+pub fn missing_schemas(routes: &[RouteMeta]) -> Vec<RouteSchemaGaps> {
+    routes
+        .iter()
+        .filter(|route| !route.websocket)
+        .filter_map(|route| {
+            let gaps = route_gaps(route);
+            (!gaps.is_empty()).then(|| RouteSchemaGaps {
+                method: route.method.clone(),
+                path: route.path_pattern.clone(),
+                handler_name: route.handler_name.clone(),
+                gaps,
+            })
+        })
+        .collect()
+}
+
+/// Check every route for [`SchemaGap`]s.
+///
+/// With `strict`, any gap is an error. Otherwise each route with gaps is logged as one
+/// warning and the check passes.
+///
+/// # Errors
+///
+/// [`MissingSchemaError`] listing every route with gaps, only when `strict` is set.
+pub fn check_schemas(routes: &[RouteMeta], strict: bool) -> Result<(), MissingSchemaError> {
+    let missing = missing_schemas(routes);
+    if strict && !missing.is_empty() {
+        return Err(MissingSchemaError(missing));
+    }
+    for route in &missing {
+        tracing::warn!(
+            method = %route.method,
+            path = %route.path,
+            handler = %route.handler_name,
+            gaps = %route,
+            "Operation body is not validated: no schema (set validation.strict_schema to reject)"
+        );
+    }
+    Ok(())
+}
+
+fn route_gaps(route: &RouteMeta) -> Vec<SchemaGap> {
+    let mut gaps = Vec::new();
+    if route.request_validation_schema().is_none()
+        && route
+            .request_content_types
+            .iter()
+            .any(|ct| ct == "application/json")
+    {
+        gaps.push(SchemaGap::RequestBody("application/json".to_string()));
+    }
+
+    let mut statuses: Vec<u16> = route
+        .declared_statuses
+        .iter()
+        .filter_map(|s| s.parse().ok())
+        .chain(route.responses.keys().copied())
+        .collect();
+    statuses.sort_unstable();
+    statuses.dedup();
+    for status in statuses {
+        if route.schema_overrides.response(status).is_some() {
+            continue;
+        }
+        match route.responses.get(&status) {
+            Some(content) => {
+                let mut content_types: Vec<&String> = content
+                    .iter()
+                    .filter(|(ct, spec)| spec.schema.is_none() && is_json(ct))
+                    .map(|(ct, _)| ct)
+                    .collect();
+                content_types.sort_unstable();
+                gaps.extend(content_types.into_iter().map(|ct| SchemaGap::Response {
+                    status,
+                    content_type: ct.clone(),
+                }));
+            }
+            None if (200..300).contains(&status) && status != 204 && status != 205 => {
+                gaps.push(SchemaGap::ResponseContent(status));
+            }
+            None => {}
+        }
+    }
+    gaps
+}
+
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json" || essence.ends_with("+json")
+}
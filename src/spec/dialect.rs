@@ -0,0 +1,262 @@
+//! Schema dialects: which JSON Schema flavour the spec's schemas are written in.
+//!
+//! OpenAPI 3.1 schemas are JSON Schema 2020-12, but specs migrated from 3.0 often keep 3.0
+//! idioms. The dialect (config.yaml `validation.schema_dialect`, or
+//! [`load_spec_full_with_dialect`](super::load_spec_full_with_dialect)) decides how the loader
+//! reads them before anything is validated:
+//!
+//! | Keyword | `2020-12` (default) | `openapi-3.0` |
+//! |---|---|---|
+//! | `exclusiveMinimum` / `exclusiveMaximum` | the bound itself (`exclusiveMinimum: 0`); a boolean fails loading | `true` makes the sibling `minimum` / `maximum` exclusive, `false` is dropped; a numeric bound is kept |
+//! | `type` | a string or an array (`[integer, "null"]`) | a string; an array fails loading |
+//! | `nullable: true` | allows `null` | allows `null` |
+//!
+//! `nullable` is honoured in both dialects: it is rewritten to the 2020-12 form
+//! (`type: [T, "null"]`) so that 3.1 specs already relying on it keep accepting `null`. Under
+//! `2020-12`, prefer writing the type array.
+//!
+//! Example values (`example`, `examples`, `default`, `enum`, `const`) are never rewritten.
+
+use super::load::normalize_nullable;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// JSON Schema flavour of the spec's schemas.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum SchemaDialect {
+    /// JSON Schema 2020-12, as OpenAPI 3.1 specifies
+    #[default]
+    #[serde(rename = "2020-12")]
+    Draft2020_12,
+    /// The OpenAPI 3.0 schema object (a JSON Schema draft-04 subset plus `nullable`)
+    #[serde(rename = "openapi-3.0")]
+    OpenApi30,
+}
+
+impl SchemaDialect {
+    /// Config / CLI name (`2020-12`, `openapi-3.0`)
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft2020_12 => "2020-12",
+            Self::OpenApi30 => "openapi-3.0",
+        }
+    }
+
+    /// Rewrite the schemas of `document` (a whole spec, or one schema) into the 2020-12 form
+    /// the router validates with.
+    ///
+    /// # Errors
+    ///
+    /// [`DialectError`] listing every keyword this dialect does not allow, with its JSON
+    /// pointer.
+    pub fn normalize(self, document: &mut Value) -> Result<(), DialectError> {
+        let mut problems = Vec::new();
+        self.walk(document, &mut String::new(), &mut problems);
+        if !problems.is_empty() {
+            return Err(DialectError {
+                dialect: self,
+                problems,
+            });
+        }
+        normalize_nullable(document);
+        Ok(())
+    }
+
+    fn walk(self, value: &mut Value, pointer: &mut String, problems: &mut Vec<String>) {
+        match value {
+            Value::Object(obj) => {
+                self.rewrite_schema(obj, pointer, problems);
+                for (key, v) in obj.iter_mut() {
+                    if matches!(
+                        key.as_str(),
+                        "example" | "examples" | "default" | "enum" | "const"
+                    ) {
+                        continue;
+                    }
+                    let len = pointer.len();
+                    push_token(pointer, key);
+                    self.walk(v, pointer, problems);
+                    pointer.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                for (i, v) in items.iter_mut().enumerate() {
+                    let len = pointer.len();
+                    push_token(pointer, &i.to_string());
+                    self.walk(v, pointer, problems);
+                    pointer.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply this dialect to the keywords of one object that may be a schema.
+    fn rewrite_schema(
+        self,
+        obj: &mut Map<String, Value>,
+        pointer: &str,
+        problems: &mut Vec<String>,
+    ) {
+        for (exclusive, bound) in [
+            ("exclusiveMinimum", "minimum"),
+            ("exclusiveMaximum", "maximum"),
+        ] {
+            let Some(Value::Bool(flag)) = obj.get(exclusive) else {
+                continue;
+            };
+            match self {
+                Self::Draft2020_12 => problems.push(format!(
+                    "#{pointer}/{exclusive}: boolean `{exclusive}` is OpenAPI 3.0 syntax; \
+                     2020-12 expects the bound itself (`{exclusive}: <number>`)"
+                )),
+                Self::OpenApi30 => {
+                    let flag = *flag;
+                    obj.remove(exclusive);
+                    if flag {
+                        match obj.remove(bound) {
+                            Some(limit @ Value::Number(_)) => {
+                                obj.insert(exclusive.to_string(), limit);
+                            }
+                            other => {
+                                if let Some(other) = other {
+                                    obj.insert(bound.to_string(), other);
+                                }
+                                tracing::warn!(
+                                    "ignoring `{exclusive}: true` at #{pointer}: no numeric `{bound}`"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if self == Self::OpenApi30 && matches!(obj.get("type"), Some(Value::Array(_))) {
+            problems.push(format!(
+                "#{pointer}/type: type arrays are not OpenAPI 3.0 syntax; use one type \
+                 (with `nullable: true` for null)"
+            ));
+        }
+    }
+}
+
+/// Append `token` to a JSON pointer, escaping `~` and `/`.
+fn push_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+impl fmt::Display for SchemaDialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SchemaDialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2020-12" => Ok(Self::Draft2020_12),
+            "openapi-3.0" | "3.0" => Ok(Self::OpenApi30),
+            other => Err(format!(
+                "unknown schema dialect '{other}' (expected 2020-12 or openapi-3.0)"
+            )),
+        }
+    }
+}
+
+/// Schema keywords the configured [`SchemaDialect`] does not allow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialectError {
+    /// Dialect the spec was loaded with
+    pub dialect: SchemaDialect,
+    /// One message per offending keyword, starting with its JSON pointer
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for DialectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} schema keyword(s) not valid in the {} dialect (config.yaml `validation.schema_dialect`)",
+            self.problems.len(),
+            self.dialect
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DialectError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn openapi_30_turns_boolean_bounds_into_numbers() {
+        let mut schema = json!({
+            "properties": {
+                "low": { "type": "integer", "minimum": 0, "exclusiveMinimum": true },
+                "high": { "type": "integer", "maximum": 9, "exclusiveMaximum": false },
+                "both": { "type": "number", "exclusiveMinimum": 1 },
+                "sample": { "type": "object", "example": { "exclusiveMinimum": true } }
+            }
+        });
+        SchemaDialect::OpenApi30.normalize(&mut schema).unwrap();
+        let props = &schema["properties"];
+        assert_eq!(
+            props["low"],
+            json!({ "type": "integer", "exclusiveMinimum": 0 })
+        );
+        assert_eq!(props["high"], json!({ "type": "integer", "maximum": 9 }));
+        assert_eq!(
+            props["both"],
+            json!({ "type": "number", "exclusiveMinimum": 1 })
+        );
+        assert_eq!(
+            props["sample"]["example"],
+            json!({ "exclusiveMinimum": true })
+        );
+    }
+
+    #[test]
+    fn each_dialect_rejects_the_other_ones_syntax() {
+        let mut bounds =
+            json!({ "properties": { "a/b": { "minimum": 0, "exclusiveMinimum": true } } });
+        let err = SchemaDialect::Draft2020_12
+            .normalize(&mut bounds)
+            .unwrap_err();
+        assert_eq!(err.problems.len(), 1);
+        assert!(
+            err.problems[0].starts_with("#/properties/a~1b/exclusiveMinimum"),
+            "{err}"
+        );
+
+        let mut types = json!({ "type": ["integer", "null"] });
+        let err = SchemaDialect::OpenApi30.normalize(&mut types).unwrap_err();
+        assert!(err.problems[0].starts_with("#/type"), "{err}");
+        SchemaDialect::Draft2020_12.normalize(&mut types).unwrap();
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for dialect in [SchemaDialect::Draft2020_12, SchemaDialect::OpenApi30] {
+            assert_eq!(dialect.as_str().parse::<SchemaDialect>(), Ok(dialect));
+            let yaml = serde_yaml::to_string(&dialect).unwrap();
+            assert_eq!(
+                serde_yaml::from_str::<SchemaDialect>(&yaml).unwrap(),
+                dialect
+            );
+        }
+        assert!("draft-07".parse::<SchemaDialect>().is_err());
+    }
+}
@@ -1,10 +1,13 @@
-use super::build::{build_routes_with_security_presence, extract_security_schemes};
+use super::build::{
+    build_routes_with_security_presence, extract_security_schemes, parse_response_link,
+};
 use super::security_presence::extract_operation_security_presence;
 use super::types::RouteMeta;
+use super::SchemaDialect;
 use super::SecurityScheme;
 use oas3::OpenApiV3Spec;
 
-fn strip_unknown_verbs(val: &mut serde_json::Value) {
+pub(crate) fn strip_unknown_verbs(val: &mut serde_json::Value) {
     const METHODS: [&str; 8] = [
         "get", "post", "put", "delete", "patch", "options", "head", "trace",
     ];
@@ -29,6 +32,154 @@ fn strip_unknown_verbs(val: &mut serde_json::Value) {
     }
 }
 
+/// Why a raw Link Object is unusable, or `None` when it is well-formed
+///
+/// Besides the checks of [`parse_response_link`], the link must deserialize as an `oas3`
+/// Link (e.g. parameter bindings are runtime-expression strings). `$ref` links are not
+/// inspected here.
+pub(crate) fn link_defect(name: &str, link: &serde_json::Value) -> Option<String> {
+    if link.get("$ref").is_some() {
+        return None;
+    }
+    if let Err(reason) = parse_response_link("", name, link) {
+        return Some(reason);
+    }
+    serde_json::from_value::<oas3::spec::Link>(link.clone())
+        .err()
+        .map(|e| e.to_string())
+}
+
+/// Drop malformed response `links` with a warning so they cannot fail deserializing the spec
+///
+/// Covers links of operation responses, `components.responses` and `components.links`.
+pub(crate) fn strip_malformed_links(val: &mut serde_json::Value) {
+    fn retain_valid(links: &mut serde_json::Map<String, serde_json::Value>, location: &str) {
+        links.retain(|name, link| {
+            let defect = link_defect(name, link);
+            if let Some(reason) = &defect {
+                tracing::warn!("ignoring malformed link '{name}' of {location}: {reason}");
+            }
+            defect.is_none()
+        });
+    }
+    fn strip_response(response: &mut serde_json::Value, location: &str) {
+        let Some(response) = response.as_object_mut() else {
+            return;
+        };
+        match response.get_mut("links") {
+            Some(serde_json::Value::Object(links)) => retain_valid(links, location),
+            Some(_) => {
+                tracing::warn!("ignoring `links` of {location}: not a map");
+                response.remove("links");
+            }
+            None => {}
+        }
+    }
+
+    if let Some(serde_json::Value::Object(paths)) = val.get_mut("paths") {
+        for (path, item) in paths.iter_mut() {
+            let Some(item) = item.as_object_mut() else {
+                continue;
+            };
+            for (method, operation) in item.iter_mut() {
+                let Some(serde_json::Value::Object(responses)) = operation.get_mut("responses")
+                else {
+                    continue;
+                };
+                for (status, response) in responses.iter_mut() {
+                    strip_response(response, &format!("{path} → {method} response {status}"));
+                }
+            }
+        }
+    }
+    if let Some(serde_json::Value::Object(components)) = val.get_mut("components") {
+        if let Some(serde_json::Value::Object(responses)) = components.get_mut("responses") {
+            for (name, response) in responses.iter_mut() {
+                strip_response(response, &format!("components.responses.{name}"));
+            }
+        }
+        if let Some(serde_json::Value::Object(links)) = components.get_mut("links") {
+            retain_valid(links, "components.links");
+        }
+    }
+}
+
+/// Rewrite OpenAPI 3.0 `nullable: true` into the OpenAPI 3.1 form
+///
+/// `oas3` models 3.1 only and drops `nullable`, and the JSON Schema validator ignores it, so a
+/// 3.0 nullable schema would reject `null`. `type: T` becomes `type: [T, "null"]`, `null`
+/// joins an `enum`, and a schema without `type` gets a `{type: null}` `oneOf` / `anyOf`
+/// member. `nullable: false` is dropped. Examples, defaults and enum values are not touched.
+pub(crate) fn normalize_nullable(val: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    fn allow_null(schema: &mut serde_json::Map<String, Value>) {
+        match schema.get_mut("type") {
+            Some(Value::String(ty)) => {
+                let ty = std::mem::take(ty);
+                schema.insert("type".to_string(), serde_json::json!([ty, "null"]));
+            }
+            Some(Value::Array(types)) => {
+                if !types.iter().any(|t| t == "null") {
+                    types.push(Value::from("null"));
+                }
+            }
+            _ => {
+                for key in ["oneOf", "anyOf"] {
+                    if let Some(Value::Array(variants)) = schema.get_mut(key) {
+                        variants.push(serde_json::json!({ "type": "null" }));
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(Value::Array(values)) = schema.get_mut("enum") {
+            if !values.contains(&Value::Null) {
+                values.push(Value::Null);
+            }
+        }
+    }
+
+    match val {
+        Value::Object(obj) => {
+            if let Some(nullable) = obj.get("nullable").and_then(Value::as_bool) {
+                obj.remove("nullable");
+                if nullable {
+                    allow_null(obj);
+                }
+            }
+            for (key, v) in obj.iter_mut() {
+                match key.as_str() {
+                    "example" | "examples" | "default" | "enum" | "const" => {}
+                    // Maps of property name → schema: a property may itself be named `nullable`.
+                    "properties" | "patternProperties" => {
+                        if let Value::Object(props) = v {
+                            props.values_mut().for_each(normalize_nullable);
+                        }
+                    }
+                    _ => normalize_nullable(v),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_nullable),
+        _ => {}
+    }
+}
+
+/// Read a YAML (`.yaml` / `.yml`) or JSON spec file into an [`OpenApiV3Spec`], with its
+/// schemas normalized as the default [`SchemaDialect`]
+pub(crate) fn read_spec_document(path: &std::path::Path) -> anyhow::Result<OpenApiV3Spec> {
+    let content = std::fs::read_to_string(path)?;
+    let mut value: serde_json::Value =
+        if path.extension().is_some_and(|e| e == "yaml" || e == "yml") {
+            serde_yaml::from_str(&content)?
+        } else {
+            serde_json::from_str(&content)?
+        };
+    SchemaDialect::default().normalize(&mut value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
 /// Load an OpenAPI specification from a file and extract route metadata
 ///
 /// Supports both YAML and JSON formats. Returns route metadata and a URL-safe project slug
@@ -52,6 +203,19 @@ fn strip_unknown_verbs(val: &mut serde_json::Value) {
 /// - The spec doesn't conform to OpenAPI 3.x
 /// - Route extraction fails
 pub fn load_spec(file_path: &str) -> anyhow::Result<(Vec<RouteMeta>, String)> {
+    load_spec_with_dialect(file_path, SchemaDialect::default())
+}
+
+/// [`load_spec`] for a spec whose schemas are written in `dialect`
+///
+/// # Errors
+///
+/// As [`load_spec`], plus a [`DialectError`](super::DialectError) when a schema uses
+/// keywords `dialect` does not allow.
+pub fn load_spec_with_dialect(
+    file_path: &str,
+    dialect: SchemaDialect,
+) -> anyhow::Result<(Vec<RouteMeta>, String)> {
     let content = std::fs::read_to_string(file_path)?;
     let mut value: serde_json::Value =
         if file_path.ends_with(".yaml") || file_path.ends_with(".yml") {
@@ -61,6 +225,8 @@ pub fn load_spec(file_path: &str) -> anyhow::Result<(Vec<RouteMeta>, String)> {
         };
 
     strip_unknown_verbs(&mut value);
+    strip_malformed_links(&mut value);
+    dialect.normalize(&mut value)?;
     let security_presence = extract_operation_security_presence(&value);
     let spec: OpenApiV3Spec = serde_json::from_value(value)?;
 
@@ -100,6 +266,24 @@ pub fn load_spec_full(
     Vec<RouteMeta>,
     std::collections::HashMap<String, SecurityScheme>,
     String,
+)> {
+    load_spec_full_with_dialect(file_path, SchemaDialect::default())
+}
+
+/// [`load_spec_full`] for a spec whose schemas are written in `dialect` (config.yaml
+/// `validation.schema_dialect`)
+///
+/// # Errors
+///
+/// As [`load_spec_full`], plus a [`DialectError`](super::DialectError) when a schema uses
+/// keywords `dialect` does not allow.
+pub fn load_spec_full_with_dialect(
+    file_path: &str,
+    dialect: SchemaDialect,
+) -> anyhow::Result<(
+    Vec<RouteMeta>,
+    std::collections::HashMap<String, SecurityScheme>,
+    String,
 )> {
     let content = std::fs::read_to_string(file_path)?;
     let mut value: serde_json::Value =
@@ -110,6 +294,8 @@ pub fn load_spec_full(
         };
 
     strip_unknown_verbs(&mut value);
+    strip_malformed_links(&mut value);
+    dialect.normalize(&mut value)?;
     let security_presence = extract_operation_security_presence(&value);
     let spec: OpenApiV3Spec = serde_json::from_value(value)?;
 
@@ -191,4 +377,59 @@ mod tests {
         strip_unknown_verbs(&mut v);
         assert!(v["paths"]["/x"].get("unknown").is_none());
     }
+
+    #[test]
+    fn test_strip_malformed_links() {
+        let mut v = json!({
+            "paths": {
+                "/x": { "get": { "responses": { "200": { "description": "OK", "links": {
+                    "self": { "operationId": "get_x" },
+                    "both": { "operationId": "a", "operationRef": "#/paths/~1x/get" },
+                    "neither": { "parameters": { "id": "$response.body#/id" } },
+                    "bad_params": { "operationId": "a", "parameters": ["id"] },
+                    "shared": { "$ref": "#/components/links/Next" }
+                } } } } }
+            },
+            "components": { "links": {
+                "Next": { "operationId": "get_x" },
+                "Broken": "nope"
+            } }
+        });
+        strip_malformed_links(&mut v);
+        let links = v["paths"]["/x"]["get"]["responses"]["200"]["links"]
+            .as_object()
+            .unwrap();
+        let mut names: Vec<&str> = links.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["self", "shared"]);
+        assert!(v["components"]["links"].get("Next").is_some());
+        assert!(v["components"]["links"].get("Broken").is_none());
+    }
+
+    #[test]
+    fn test_normalize_nullable() {
+        let mut v = json!({
+            "components": { "schemas": { "Pet": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "nullable": true, "example": { "nullable": true } },
+                    "size": { "type": "string", "enum": ["S", "L"], "nullable": true },
+                    "age": { "type": ["integer", "null"], "nullable": true },
+                    "owner": { "oneOf": [{ "$ref": "#/components/schemas/Owner" }], "nullable": true },
+                    "tag": { "type": "string", "nullable": false },
+                    "nullable": { "type": "boolean" }
+                }
+            } } }
+        });
+        normalize_nullable(&mut v);
+        let props = &v["components"]["schemas"]["Pet"]["properties"];
+        assert_eq!(props["name"]["type"], json!(["string", "null"]));
+        assert!(props["name"].get("nullable").is_none());
+        assert_eq!(props["name"]["example"], json!({ "nullable": true }));
+        assert_eq!(props["size"]["enum"], json!(["S", "L", null]));
+        assert_eq!(props["age"]["type"], json!(["integer", "null"]));
+        assert_eq!(props["owner"]["oneOf"][1], json!({ "type": "null" }));
+        assert_eq!(props["tag"], json!({ "type": "string" }));
+        assert_eq!(props["nullable"], json!({ "type": "boolean" }));
+    }
 }
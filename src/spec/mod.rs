@@ -17,6 +17,7 @@
 //! - [`RouteMeta`] - Complete metadata for a single route (path + method)
 //! - [`ParameterMeta`] - Metadata for path/query/header parameters
 //! - [`SecurityRequirement`] - Security scheme requirements for a route
+//! - [`RouteSecurityPolicy`] - Structured OR-of-AND view of a route's schemes and scopes
 //!
 //! ## Loading Specifications
 //!
@@ -86,13 +87,21 @@
 
 pub use oas3::spec::{SecurityRequirement, SecurityScheme};
 mod build;
+mod coverage;
+mod dialect;
 mod load;
 mod security_presence;
 mod types;
+mod validate;
 
 pub use build::*;
+pub use coverage::{
+    check_schemas, missing_schemas, MissingSchemaError, RouteSchemaGaps, SchemaGap,
+};
+pub use dialect::{DialectError, SchemaDialect};
 pub use load::*;
 pub use security_presence::{
     extract_operation_security_presence, resolve_operation_security, OperationSecurityPresence,
 };
 pub use types::*;
+pub use validate::{validate_spec, validate_spec_value};
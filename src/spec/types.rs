@@ -1,6 +1,7 @@
 use super::SecurityRequirement;
 use http::Method;
 use serde_json::Value;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -233,8 +234,11 @@ pub struct RouteMeta {
     pub request_content_types: Vec<String>,
     /// JSON Schema for response body validation
     pub response_schema: Option<Value>,
-    /// Example response data from OpenAPI spec
+    /// Example response data from OpenAPI spec; with an `examples` map, the one named
+    /// `default`, else the first
     pub example: Option<Value>,
+    /// Named examples of the default response (`examples` map), sorted by name
+    pub examples: Vec<(String, Value)>,
     /// All possible responses by status code and content type
     pub responses: Responses,
     /// Security requirements for this route (API keys, JWT, OAuth2, etc.)
@@ -254,6 +258,12 @@ pub struct RouteMeta {
     pub estimated_request_body_bytes: Option<usize>,
     /// Vendor extension override for stack size (x-brrtrouter-stack-size)
     pub x_brrtrouter_stack_size: Option<usize>,
+    /// Request timeout in milliseconds (`x-brrtrouter-timeout-ms`); sets
+    /// [`HandlerRequest::deadline`](crate::dispatcher::HandlerRequest::deadline)
+    pub x_brrtrouter_timeout_ms: Option<u64>,
+    /// Slow-request threshold in milliseconds (`x-slow-threshold-ms`), overriding the
+    /// [`MetricsMiddleware`](crate::middleware::MetricsMiddleware) default for this route
+    pub x_slow_threshold_ms: Option<u64>,
     /// Route-specific CORS policy from OpenAPI `x-cors` extension
     /// Determines how CORS should be handled for this route:
     /// - `Inherit`: Use global CORS configuration (default)
@@ -268,9 +278,339 @@ pub struct RouteMeta {
     /// `Some(true)` ⇒ real impl controller required; `Some(false)` ⇒ gen stub only;
     /// `None` ⇒ legacy (warn if impl file exists on disk).
     pub x_brrtrouter_impl: Option<bool>,
+    /// Optional authentication from OpenAPI `x-auth-optional: true`.
+    /// Credentials are still validated and `jwt_claims` populated when valid, but missing
+    /// or invalid credentials proceed anonymously instead of returning 401/403.
+    pub auth_optional: bool,
+    /// JWT claim → downstream header mapping from OpenAPI `x-forward-claims`
+    /// (e.g. `sub` → `X-User-Id`).
+    /// `None` inherits the `forward_claims` middleware default; `Some(empty)` disables
+    /// forwarding for this route (`x-forward-claims: false`).
+    pub forward_claims: Option<Vec<(String, String)>>,
+    /// Operation `tags`, in spec order
+    pub tags: Vec<String>,
+    /// Whether the operation is marked `deprecated: true`
+    pub deprecated: bool,
+    /// Whether the operation is a WebSocket endpoint (`x-websocket: true`)
+    pub websocket: bool,
+    /// Response `links` to related operations, ordered by status then link name
+    pub links: Vec<ResponseLink>,
+    /// Response keys exactly as the operation declares them (`"201"`, `"2XX"`, `"default"`),
+    /// sorted; see [`RouteMeta::declares_status`]
+    pub declared_statuses: Vec<String>,
+    /// Validation-only schemas from `x-request-schema` / `x-response-schema`
+    pub schema_overrides: SchemaOverrides,
+    /// Where a resource this operation creates can be fetched, when its `201` response
+    /// declares a `Location` header; `201` responses without one get it filled in
+    pub created_location: Option<CreatedLocation>,
+    /// Response `headers` declared per status (`Content-Type` excluded, as OpenAPI ignores it)
+    pub response_headers: ResponseHeaders,
+    /// Size and content-type limits of `multipart/form-data` fields
+    pub upload_limits: UploadLimits,
+    /// Feature flag from `x-feature`; the route is only served while the feature is enabled
+    /// (see [`RouteMeta::is_enabled`])
+    pub feature: Option<String>,
+    /// How a `HEAD` response's `Content-Length` is found (`x-head-strategy`)
+    pub head_strategy: HeadStrategy,
+}
+
+/// `Location` of a created resource: the `GET` route one path parameter below the creating
+/// operation's path (`POST /pets` → `GET /pets/{petId}`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedLocation {
+    /// Path template of the `GET` route, e.g. `/pets/{petId}`
+    pub template: Arc<str>,
+    /// Its last path parameter, filled with the created resource's id
+    pub param: String,
+}
+
+impl CreatedLocation {
+    /// Location for `template`, whose last segment is the parameter `{param}`
+    pub fn new(template: &str, param: &str) -> Self {
+        Self {
+            template: Arc::from(template),
+            param: param.to_string(),
+        }
+    }
+
+    /// The template with `id`, URL-encoded (`a b/c` → `a%20b%2Fc`), in its last parameter.
+    ///
+    /// Other parameters (`ownerId` in `/owners/{ownerId}/pets/{petId}`) take the creating
+    /// request's path parameters, which must be passed still encoded as received
+    /// ([`crate::router::RouteMatch::get_raw_path_param`]); one missing from them is left as
+    /// is.
+    pub fn expand<K: AsRef<str>, V: AsRef<str>>(
+        &self,
+        id: &str,
+        path_params: impl IntoIterator<Item = (K, V)>,
+    ) -> String {
+        let params: Vec<(K, V)> = path_params.into_iter().collect();
+        let mut out = String::with_capacity(self.template.len() + id.len());
+        let mut rest = &*self.template;
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}').map(|c| open + c) else {
+                break;
+            };
+            out.push_str(&rest[..open]);
+            let name = &rest[open + 1..close];
+            if name == self.param {
+                out.push_str(&urlencoding::encode(id));
+            } else {
+                match params.iter().find(|(k, _)| k.as_ref() == name) {
+                    Some((_, value)) => out.push_str(value.as_ref()),
+                    None => out.push_str(&rest[open..=close]),
+                }
+            }
+            rest = &rest[close + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Location for the id in a created resource's body: its top-level field named like the
+    /// parameter, else `id`, as a string or number
+    pub fn for_body<K: AsRef<str>, V: AsRef<str>>(
+        &self,
+        body: &Value,
+        path_params: impl IntoIterator<Item = (K, V)>,
+    ) -> Option<String> {
+        let id = match body.get(&self.param).or_else(|| body.get("id"))? {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            _ => return None,
+        };
+        Some(self.expand(&id, path_params))
+    }
+}
+
+/// Schemas from the `x-request-schema` / `x-response-schema` operation extensions
+///
+/// They replace the declared schemas when this route's bodies are validated, and only then:
+/// generated types and docs keep the operation's `requestBody` and `responses`. Each one is
+/// `$ref`-expanded and checked to compile when the spec is loaded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaOverrides {
+    /// Request body schema (`x-request-schema`)
+    pub request: Option<Value>,
+    /// Response body schemas by status (`x-response-schema` given as `{status: schema}`)
+    pub responses: std::collections::HashMap<u16, Value>,
+    /// Response body schema for every 2xx status (`x-response-schema` given as one schema);
+    /// an entry in `responses` takes precedence
+    pub success_response: Option<Value>,
+}
+
+impl SchemaOverrides {
+    /// Whether no override is set
+    pub fn is_empty(&self) -> bool {
+        self.request.is_none() && self.responses.is_empty() && self.success_response.is_none()
+    }
+
+    /// Override for a response body with `status`, if any
+    pub fn response(&self, status: u16) -> Option<&Value> {
+        self.responses.get(&status).or_else(|| {
+            self.success_response
+                .as_ref()
+                .filter(|_| (200..300).contains(&status))
+        })
+    }
+}
+
+/// Limits on one `multipart/form-data` field, from the operation's multipart schema
+///
+/// `max_bytes` comes from the property's `x-brrtrouter-max-bytes`; `content_types` from
+/// `encoding.<field>.contentType`, else the property's `contentMediaType`. Patterns may end
+/// in `/*` (`image/*`). Uploads breaking them are answered `413` / `415` before dispatch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UploadLimit {
+    /// Largest accepted part, in bytes
+    pub max_bytes: Option<u64>,
+    /// Accepted part content types; empty accepts any
+    pub content_types: Vec<String>,
+}
+
+impl UploadLimit {
+    /// Whether a part sent as `content_type` is accepted
+    ///
+    /// Parameters (`; charset=...`) are ignored and types compare case-insensitively.
+    pub fn accepts_content_type(&self, content_type: &str) -> bool {
+        if self.content_types.is_empty() {
+            return true;
+        }
+        let sent = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        self.content_types.iter().any(|allowed| {
+            let allowed = allowed.trim().to_ascii_lowercase();
+            match allowed.strip_suffix("/*") {
+                _ if allowed == "*/*" => true,
+                Some(kind) => sent
+                    .split_once('/')
+                    .is_some_and(|(sent_kind, _)| sent_kind == kind),
+                None => sent == allowed,
+            }
+        })
+    }
+}
+
+/// Upload limits of an operation's `multipart/form-data` fields, by field name
+pub type UploadLimits = std::collections::HashMap<String, UploadLimit>;
+
+/// Operation a response link points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// `operationId` of an operation in the same spec
+    OperationId(String),
+    /// `operationRef`, e.g. `#/paths/~1pets~1{id}/get` or an external URI
+    OperationRef(String),
+}
+
+/// One entry of a response's `links` map (OpenAPI Link Object)
+///
+/// Links let clients follow relations such as `self` or `next` from a response to another
+/// operation, binding that operation's parameters with runtime expressions like
+/// `$response.body#/id`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseLink {
+    /// Response status key the link is declared under (`"200"`, `"2XX"`, `"default"`)
+    pub status: String,
+    /// Link name (the key in the `links` map, e.g. `next`)
+    pub name: String,
+    /// Linked operation as declared
+    pub target: LinkTarget,
+    /// Method and path template of the linked operation when it is in this spec;
+    /// `None` for external `operationRef`s
+    pub target_route: Option<(Method, Arc<str>)>,
+    /// Parameter name → runtime expression or constant, sorted by name
+    pub parameters: Vec<(String, Value)>,
+    /// Runtime expression or constant to use as the linked operation's request body
+    pub request_body: Option<Value>,
+    /// Human-readable description
+    pub description: Option<String>,
+}
+
+/// One security scheme within a requirement, with the scopes the operation demands from it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SchemeScopes {
+    /// Scheme name from `components.securitySchemes`
+    pub scheme: String,
+    /// Scopes required for this scheme (empty for non-OAuth2 schemes)
+    pub scopes: Vec<String>,
+}
+
+/// How a `HEAD` request to a route gets its `Content-Length` (`x-head-strategy`)
+///
+/// `HEAD` is answered by the operation's handler (a path's `GET` operation when it declares
+/// no `HEAD` of its own), with `HandlerRequest::method` set to `HEAD`. The response keeps
+/// the status and headers the handler returned and its `Content-Length` is that of the
+/// `GET` body.
+///
+/// Over a connection the body is never written: a client reads none after a `HEAD`
+/// response, so on a kept-alive connection it would be taken for the next response.
+/// `may_minihttp` derives `Content-Length` from the bytes written and cannot send a
+/// header-only response, so there the header is `0`; the `GET` length computed here reaches
+/// in-process clients such as `TestClient` until the transport can frame `HEAD`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeadStrategy {
+    /// Measure the body the handler returned, serialized exactly as for `GET`. Always
+    /// correct, but the handler builds the whole body.
+    #[default]
+    Compute,
+    /// Trust a `Content-Length` the handler sets, so it can skip building the body; the
+    /// body is measured as for [`HeadStrategy::Compute`] when it sets none. Response body
+    /// validation is skipped for these `HEAD` responses.
+    Estimate,
+}
+
+/// Effective security policy of a route, derived from its OpenAPI `security` array.
+///
+/// OpenAPI composes requirements as OR-of-ANDs: any one alternative may be satisfied,
+/// and every scheme within that alternative must validate. An empty requirement object
+/// (`{}`) in the array makes credentials optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteSecurityPolicy {
+    /// No security applies (omitted without a global default, or explicit `security: []`).
+    Public,
+    /// At least one alternative applies.
+    Alternatives {
+        /// OR-list of AND-groups; `{}` entries are not included here.
+        alternatives: Vec<Vec<SchemeScopes>>,
+        /// True when the array contains an empty requirement (`{}`), i.e. anonymous access.
+        allows_anonymous: bool,
+    },
 }
 
 impl RouteMeta {
+    /// Whether the route is served with `enabled_features`: always without `x-feature`,
+    /// otherwise only when its feature is in the set
+    pub fn is_enabled(&self, enabled_features: &BTreeSet<String>) -> bool {
+        self.feature
+            .as_ref()
+            .is_none_or(|feature| enabled_features.contains(feature))
+    }
+
+    /// Structured view of [`RouteMeta::security`] for docs, inspection, and scope checks.
+    ///
+    /// Distinguishes public routes from routes with OR-composed requirements so callers
+    /// do not have to interpret empty vectors and empty requirement objects themselves.
+    pub fn security_policy(&self) -> RouteSecurityPolicy {
+        if self.security.is_empty() {
+            return RouteSecurityPolicy::Public;
+        }
+        let mut allows_anonymous = false;
+        let mut alternatives = Vec::with_capacity(self.security.len());
+        for requirement in &self.security {
+            if requirement.0.is_empty() {
+                allows_anonymous = true;
+                continue;
+            }
+            alternatives.push(
+                requirement
+                    .0
+                    .iter()
+                    .map(|(scheme, scopes)| SchemeScopes {
+                        scheme: scheme.clone(),
+                        scopes: scopes.clone(),
+                    })
+                    .collect(),
+            );
+        }
+        RouteSecurityPolicy::Alternatives {
+            alternatives,
+            allows_anonymous,
+        }
+    }
+
+    /// Scopes the route demands from `scheme`, merged across every alternative naming it.
+    ///
+    /// Returns `None` when the scheme is not referenced by this route.
+    pub fn required_scopes(&self, scheme: &str) -> Option<Vec<String>> {
+        let mut found = false;
+        let mut scopes: Vec<String> = Vec::new();
+        for requirement in &self.security {
+            if let Some(s) = requirement.0.get(scheme) {
+                found = true;
+                for scope in s {
+                    if !scopes.contains(scope) {
+                        scopes.push(scope.clone());
+                    }
+                }
+            }
+        }
+        found.then_some(scopes)
+    }
+
+    /// Schema request bodies are validated against: the `x-request-schema` override when
+    /// present, otherwise [`RouteMeta::request_schema`]
+    pub fn request_validation_schema(&self) -> Option<&Value> {
+        self.schema_overrides
+            .request
+            .as_ref()
+            .or(self.request_schema.as_ref())
+    }
+
     /// Get the content type for a specific HTTP status code response
     ///
     /// Returns the first content type defined for the given status code
@@ -282,6 +622,50 @@ impl RouteMeta {
             .cloned()
     }
 
+    /// Whether the operation's `responses` cover `status`
+    ///
+    /// Matches an exact code, a range key (`2XX`, case-insensitive) or `default`. A route that
+    /// declares nothing (e.g. one built in code rather than from a spec) covers every status.
+    pub fn declares_status(&self, status: u16) -> bool {
+        if self.declared_statuses.is_empty() {
+            return true;
+        }
+        self.declared_statuses.iter().any(|key| {
+            if key == "default" {
+                return true;
+            }
+            match key.as_bytes() {
+                [class @ b'1'..=b'5', x1, x2]
+                    if x1.eq_ignore_ascii_case(&b'X') && x2.eq_ignore_ascii_case(&b'X') =>
+                {
+                    u16::from(class - b'0') == status / 100
+                }
+                _ => key.parse::<u16>().ok() == Some(status),
+            }
+        })
+    }
+
+    /// Success statuses codegen offers as `typed::{Ok, Created, Accepted, NoContent}` wrappers
+    ///
+    /// The exactly-declared codes among 200, 201, 202 and 204, ascending. Empty for SSE
+    /// operations and when 200 is the only one, since a plain `Response` already sends 200.
+    pub fn typed_success_statuses(&self) -> Vec<u16> {
+        if self.sse {
+            return Vec::new();
+        }
+        let statuses: Vec<u16> = [200, 201, 202, 204]
+            .into_iter()
+            .filter(|status| {
+                let code = status.to_string();
+                self.declared_statuses.iter().any(|key| *key == code)
+            })
+            .collect();
+        if statuses.iter().all(|&status| status == 200) {
+            return Vec::new();
+        }
+        statuses
+    }
+
     /// True when codegen should emit `HttpJson<Response>` for this operation.
     ///
     /// Triggered when the OpenAPI operation declares at least one **non-2xx**
@@ -320,6 +704,9 @@ pub struct ParameterMeta {
     pub style: Option<ParameterStyle>,
     /// Whether to use exploded format for arrays/objects
     pub explode: Option<bool>,
+    /// Whether the parameter is marked `deprecated: true`; requests sending it get a
+    /// `Warning: 299` response header
+    pub deprecated: bool,
 }
 
 /// Specification for a single response variant
@@ -329,10 +716,27 @@ pub struct ParameterMeta {
 pub struct ResponseSpec {
     /// JSON Schema for response body validation
     pub schema: Option<Value>,
-    /// Example response data from OpenAPI spec
+    /// Example response data from OpenAPI spec; with an `examples` map, the one named
+    /// `default`, else the first
     pub example: Option<Value>,
+    /// Named examples from the `examples` map, sorted by name
+    pub examples: Vec<(String, Value)>,
+}
+
+/// A header a response declares under `headers`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseHeaderSpec {
+    /// Header name as declared; compared case-insensitively
+    pub name: String,
+    /// Whether the response must carry the header (`required: true`)
+    pub required: bool,
+    /// JSON Schema of the header value, `$ref`s expanded
+    pub schema: Option<Value>,
 }
 
+/// Declared response headers by HTTP status code, each list sorted by name
+pub type ResponseHeaders = std::collections::HashMap<u16, Vec<ResponseHeaderSpec>>;
+
 /// Map of HTTP status codes to content types to response specifications
 ///
 /// Example: `{ 200: { "application/json": ResponseSpec { ... } } }`
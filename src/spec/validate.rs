@@ -0,0 +1,250 @@
+//! Whole-document structural validation behind `brrtrouter-gen validate`.
+//!
+//! [`load_spec`](super::load_spec) stops at the first problem. [`validate_spec`] instead walks
+//! the raw document and collects every issue it finds, each located by a JSON pointer
+//! (e.g. `/paths/~1pets~1{id}/get/responses`):
+//!
+//! | kind | severity | meaning |
+//! |------|----------|---------|
+//! | `InvalidSyntax` | error | the file is not valid YAML/JSON |
+//! | `InvalidDocument` | error | the document does not deserialize as OpenAPI 3.x |
+//! | `MissingHandler` | warning | operation without `operationId` or `x-handler-*` (a name is synthesized) |
+//! | `DuplicateOperationId` | error | `operationId` already used by another operation |
+//! | `ConflictingPathTemplates` | error | paths differing only in parameter names |
+//! | `MissingResponses` | error | operation without responses |
+//! | `MissingDescription` | error | response without the required `description` |
+//! | `MissingResponseContent` | warning | 2xx response (other than 204/205) without `content` |
+//! | `MalformedLink` | warning | response link without exactly one `operationId`/`operationRef` (ignored) |
+//! | `UnresolvedRef` | error | local `$ref` that points nowhere |
+//! | `ExternalRef` | warning | `$ref` to another document (not supported) |
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use oas3::OpenApiV3Spec;
+use serde_json::Value;
+
+use super::build::normalize_path_template;
+use super::load::{link_defect, strip_malformed_links, strip_unknown_verbs};
+use crate::validator::ValidationIssue;
+
+const METHODS: [&str; 8] = [
+    "get", "post", "put", "delete", "patch", "options", "head", "trace",
+];
+
+/// Validate the OpenAPI specification at `path` and return every issue found.
+///
+/// # Errors
+///
+/// Only when the file cannot be read; syntax errors are reported as an `InvalidSyntax` issue.
+pub fn validate_spec(path: &Path) -> anyhow::Result<Vec<ValidationIssue>> {
+    let content = std::fs::read_to_string(path)?;
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let parsed = if is_yaml {
+        serde_yaml::from_str::<Value>(&content).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())
+    };
+    Ok(match parsed {
+        Ok(value) => validate_spec_value(&value),
+        Err(e) => vec![ValidationIssue::new("", "InvalidSyntax", e)],
+    })
+}
+
+/// Validate an already parsed OpenAPI document; see the [module docs](self) for the checks.
+pub fn validate_spec_value(spec: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    let mut document = spec.clone();
+    strip_unknown_verbs(&mut document);
+    // Malformed links are reported as `MalformedLink` warnings, not as `InvalidDocument`.
+    strip_malformed_links(&mut document);
+    if let Err(e) = serde_json::from_value::<OpenApiV3Spec>(document) {
+        issues.push(ValidationIssue::new("", "InvalidDocument", e.to_string()));
+    }
+
+    if let Some(paths) = spec.get("paths").and_then(Value::as_object) {
+        let mut templates: HashMap<String, &str> = HashMap::new();
+        let mut operation_ids: HashMap<&str, String> = HashMap::new();
+        for (path, item) in paths {
+            let path_pointer = format!("/paths/{}", escape_pointer(path));
+            if let Some(first) = templates.insert(normalize_path_template(path), path) {
+                issues.push(ValidationIssue::new(
+                    path_pointer.clone(),
+                    "ConflictingPathTemplates",
+                    format!("'{path}' differs from '{first}' only in parameter names"),
+                ));
+            }
+            for method in METHODS {
+                if let Some(operation) = item.get(method) {
+                    let pointer = format!("{path_pointer}/{method}");
+                    check_operation(spec, operation, &pointer, &mut operation_ids, &mut issues);
+                }
+            }
+        }
+    }
+
+    check_refs(spec, spec, &mut String::new(), &mut issues);
+    issues
+}
+
+fn check_operation<'a>(
+    spec: &Value,
+    operation: &'a Value,
+    pointer: &str,
+    operation_ids: &mut HashMap<&'a str, String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let operation_id = operation.get("operationId").and_then(Value::as_str);
+    let has_handler_extension = operation.as_object().is_some_and(|o| {
+        o.iter()
+            .any(|(k, v)| k.starts_with("x-handler") && v.is_string())
+    });
+    if operation_id.is_none() && !has_handler_extension {
+        issues.push(ValidationIssue::warning(
+            pointer,
+            "MissingHandler",
+            "Missing operationId or x-handler-* extension; the handler name is synthesized from method and path",
+        ));
+    }
+    if let Some(id) = operation_id {
+        if let Some(first) = operation_ids.get(id) {
+            issues.push(ValidationIssue::new(
+                format!("{pointer}/operationId"),
+                "DuplicateOperationId",
+                format!("operationId '{id}' is already used at {first}"),
+            ));
+        } else {
+            operation_ids.insert(id, pointer.to_string());
+        }
+    }
+
+    let responses_pointer = format!("{pointer}/responses");
+    let Some(responses) = operation
+        .get("responses")
+        .and_then(Value::as_object)
+        .filter(|r| !r.is_empty())
+    else {
+        issues.push(ValidationIssue::new(
+            responses_pointer,
+            "MissingResponses",
+            "Operation declares no responses",
+        ));
+        return;
+    };
+    for (status, response) in responses {
+        let response_pointer = format!("{responses_pointer}/{}", escape_pointer(status));
+        // `$ref`s are checked separately; an unresolved one has nothing to inspect here.
+        let Some(response) = resolve_local_ref(spec, response) else {
+            continue;
+        };
+        if response.get("description").is_none() {
+            issues.push(ValidationIssue::new(
+                format!("{response_pointer}/description"),
+                "MissingDescription",
+                format!("Response {status} has no description"),
+            ));
+        }
+        let expects_content = status.starts_with('2') && status != "204" && status != "205";
+        if expects_content && response.get("content").is_none() {
+            issues.push(ValidationIssue::warning(
+                response_pointer.clone(),
+                "MissingResponseContent",
+                format!("Success response {status} declares no content"),
+            ));
+        }
+        if let Some(links) = response.get("links").and_then(Value::as_object) {
+            for (name, link) in links {
+                let Some(link) = resolve_local_ref(spec, link) else {
+                    continue;
+                };
+                if let Some(reason) = link_defect(name, link) {
+                    issues.push(ValidationIssue::warning(
+                        format!("{response_pointer}/links/{}", escape_pointer(name)),
+                        "MalformedLink",
+                        format!("Link '{name}' is ignored: {reason}"),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_refs(
+    spec: &Value,
+    value: &Value,
+    pointer: &mut String,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(target) = obj.get("$ref").and_then(Value::as_str) {
+                let location = format!("{pointer}/$ref");
+                match target.strip_prefix('#') {
+                    Some(local) => {
+                        if spec.pointer(local).is_none() {
+                            issues.push(ValidationIssue::new(
+                                location,
+                                "UnresolvedRef",
+                                format!("'{target}' does not resolve"),
+                            ));
+                        }
+                    }
+                    None => issues.push(ValidationIssue::warning(
+                        location,
+                        "ExternalRef",
+                        format!(
+                            "External reference '{target}' is not supported; bundle the spec first"
+                        ),
+                    )),
+                }
+            }
+            for (key, child) in obj {
+                // Example payloads are data, not schema.
+                if key == "example" {
+                    continue;
+                }
+                let len = pointer.len();
+                pointer.push('/');
+                pointer.push_str(&escape_pointer(key));
+                check_refs(spec, child, pointer, issues);
+                pointer.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let len = pointer.len();
+                pointer.push_str(&format!("/{i}"));
+                check_refs(spec, child, pointer, issues);
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Follow a local `$ref` (one level); `None` when it does not resolve.
+fn resolve_local_ref<'a>(spec: &'a Value, value: &'a Value) -> Option<&'a Value> {
+    match value.get("$ref").and_then(Value::as_str) {
+        Some(target) => spec.pointer(target.strip_prefix('#')?),
+        None => Some(value),
+    }
+}
+
+/// Escape a key for use as a JSON pointer segment (RFC 6901).
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pointer_segments_are_escaped() {
+        assert_eq!(escape_pointer("/pets/{id}"), "~1pets~1{id}");
+        assert_eq!(escape_pointer("a~b"), "a~0b");
+    }
+}
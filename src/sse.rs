@@ -90,59 +90,639 @@
 //! };
 //! ```
 //!
+//! ## Request Correlation
+//!
+//! A stream outlives the span of the request that opened it. For routes whose response is
+//! `text/event-stream`, the dispatcher stores an [`SseCorrelation`] (request id and
+//! `traceparent`) in the request's extensions; pass it to [`SseReceiver::correlate`] (or
+//! `typed::SseResponse::correlate`) to keep it for the stream's lifetime:
+//!
+//! - the stream starts with a `: request-id=<id>` comment, which `EventSource` ignores but
+//!   clients reading the raw stream can log;
+//! - each event is logged in an `sse_event` span carrying the request id, inside an
+//!   `sse_stream` span opened from the handler's span, so both belong to the request's trace;
+//! - the `sse_stream` span closes when the stream ends (every sender dropped) or is dropped
+//!   unsent, e.g. because the handler gave up on a disconnected client.
+//!
+//! ```rust
+//! use brrtrouter::sse::{self, SseCorrelation};
+//!
+//! let (sender, receiver) = sse::channel();
+//! let receiver = receiver.correlate(Some(&SseCorrelation::new("01J0REQ")));
+//! sender.send("Event 1");
+//! drop(sender);
+//! assert_eq!(receiver.collect(), ": request-id=01J0REQ\n\ndata: Event 1\n\n");
+//! ```
+//!
+//! ## Bounded Buffers
+//!
+//! Both buffers are in-process; neither applies backpressure from the client's socket.
+//!
+//! Events wait in a buffer of [`SseConfig::buffer`] events (default
+//! [`DEFAULT_SSE_BUFFER`]) until the receiver takes them, so a producer outrunning the
+//! receiver cannot grow memory without bound. When the buffer is full, [`SseOverflow`]
+//! decides: drop the oldest queued event (the default), drop the new one, or close the
+//! stream, discarding its backlog. Each case is logged as a warning, and
+//! [`SseSender::is_closed`] tells the producer of a closed stream to stop.
+//!
+//! The receiver does not stream to the socket: [`SseReceiver::collect`] (and
+//! [`crate::typed::SseResponse`]) gathers the frames into one response body, written once every
+//! sender is gone. That body is capped at [`SseConfig::max_body_bytes`] (default
+//! [`DEFAULT_SSE_MAX_BODY_BYTES`]) and the same [`SseOverflow`] applies to it: drop the oldest
+//! collected events, drop the new one, or close the stream with an empty body. How fast the
+//! client reads the written body plays no part.
+//!
+//! ```rust
+//! use brrtrouter::sse::{self, SseConfig, SseOverflow};
+//!
+//! let config = SseConfig {
+//!     buffer: 2,
+//!     overflow: SseOverflow::DropOldest,
+//!     ..SseConfig::default()
+//! };
+//! let (sender, receiver) = sse::channel_with(config);
+//! for n in 1..=4 {
+//!     sender.send(n.to_string());
+//! }
+//! assert_eq!(sender.dropped(), 2);
+//! drop(sender);
+//! assert_eq!(receiver.collect(), "data: 3\n\ndata: 4\n\n");
+//! ```
+//!
 //! ## Performance
 //!
-//! - Uses `may` coroutine channels for efficient communication
-//! - Lock-free message passing
+//! - Uses `may` coroutine locks, so a waiting receiver parks its coroutine, not its thread
 //! - Minimal per-event overhead
 //! - Suitable for thousands of concurrent streams
 
-use may::sync::mpsc;
+use crate::dispatcher::HandlerRequest;
+use crate::lock_poison::record_poison;
+use may::sync::{Condvar, Mutex, MutexGuard};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tracing::{debug, info_span, warn, Span};
+
+/// Events an SSE channel buffers by default before [`SseOverflow`] applies
+pub const DEFAULT_SSE_BUFFER: usize = 1024;
+
+/// Bytes of frames an SSE response body collects by default before [`SseOverflow`] applies
+pub const DEFAULT_SSE_MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+/// What a full SSE buffer does with one more event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SseOverflow {
+    /// Discard the oldest queued event to make room (default): the stream skips ahead
+    #[default]
+    DropOldest,
+    /// Discard the new event: the stream keeps the events already buffered
+    DropNewest,
+    /// Discard the backlog and end the stream; later events are discarded too
+    CloseStream,
+}
+
+/// Buffer size and overflow policy of an SSE channel; see [`channel_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SseConfig {
+    /// Most events queued for the receiver (at least 1)
+    pub buffer: usize,
+    /// What happens to an event sent while `buffer` events are queued, or collected while
+    /// the body holds `max_body_bytes`
+    pub overflow: SseOverflow,
+    /// Most bytes of frames collected into the response body (see the
+    /// [module docs](crate::sse#bounded-buffers))
+    pub max_body_bytes: usize,
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self {
+            buffer: DEFAULT_SSE_BUFFER,
+            overflow: SseOverflow::default(),
+            max_body_bytes: DEFAULT_SSE_MAX_BODY_BYTES,
+        }
+    }
+}
+
+/// Why [`SseBuffer::try_recv`] returned no event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TryRecvError {
+    /// Nothing queued yet
+    Empty,
+    /// Nothing queued and nothing more will be: every sender is gone or the stream closed
+    Disconnected,
+}
+
+/// Frames collected into an SSE response body, bounded by [`SseConfig::max_body_bytes`]
+pub(crate) struct SseBody {
+    preamble: String,
+    frames: VecDeque<String>,
+    len: usize,
+    overflowed: bool,
+}
+
+impl SseBody {
+    /// An empty body starting with `preamble`, which is never dropped
+    pub(crate) fn new(preamble: String) -> Self {
+        Self {
+            preamble,
+            frames: VecDeque::new(),
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    fn push(&mut self, frame: String) {
+        self.len += frame.len();
+        self.frames.push_back(frame);
+    }
+
+    /// The response body
+    pub(crate) fn into_string(self) -> String {
+        let mut out = self.preamble;
+        out.reserve(self.len);
+        for frame in self.frames {
+            out.push_str(&frame);
+        }
+        out
+    }
+}
+
+struct BufferState<T> {
+    events: VecDeque<T>,
+    senders: usize,
+    /// Set on [`SseOverflow::CloseStream`] overflow or when the receiver is dropped
+    closed: bool,
+    dropped: u64,
+}
+
+/// Bounded event queue between the senders and the receiver of an SSE stream
+pub(crate) struct SseBuffer<T> {
+    state: Mutex<BufferState<T>>,
+    ready: Condvar,
+    config: SseConfig,
+}
+
+impl<T> SseBuffer<T> {
+    pub(crate) fn new(config: SseConfig) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(BufferState {
+                events: VecDeque::new(),
+                senders: 1,
+                closed: false,
+                dropped: 0,
+            }),
+            ready: Condvar::new(),
+            config: SseConfig {
+                buffer: config.buffer.max(1),
+                ..config
+            },
+        })
+    }
+
+    /// Each critical section pushes, pops or assigns whole values, so a poisoned lock is
+    /// recovered (see [`crate::lock_poison`]).
+    fn state(&self) -> MutexGuard<'_, BufferState<T>> {
+        self.state.lock().unwrap_or_else(|poisoned| {
+            record_poison("sse.buffer");
+            poisoned.into_inner()
+        })
+    }
+
+    /// Queue `event`, applying the overflow policy when the buffer is full
+    pub(crate) fn push(&self, event: T) {
+        let mut state = self.state();
+        if state.closed {
+            state.dropped += 1;
+            return;
+        }
+        if state.events.len() >= self.config.buffer {
+            state.dropped += 1;
+            match self.config.overflow {
+                SseOverflow::DropOldest => {
+                    state.events.pop_front();
+                    state.events.push_back(event);
+                }
+                SseOverflow::DropNewest => {}
+                SseOverflow::CloseStream => {
+                    state.dropped += state.events.len() as u64;
+                    state.events.clear();
+                    state.closed = true;
+                    warn!(
+                        buffer = self.config.buffer,
+                        "SSE buffer full: closing the stream"
+                    );
+                    self.ready.notify_all();
+                    return;
+                }
+            }
+            if state.dropped == 1 {
+                warn!(
+                    buffer = self.config.buffer,
+                    overflow = ?self.config.overflow,
+                    "SSE buffer full: dropping events"
+                );
+            }
+            return;
+        }
+        state.events.push_back(event);
+        self.ready.notify_one();
+    }
+
+    /// Wait for the next event; `None` once the stream has ended
+    pub(crate) fn recv(&self) -> Option<T> {
+        let mut state = self.state();
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                return Some(event);
+            }
+            if state.closed || state.senders == 0 {
+                return None;
+            }
+            state = self.ready.wait(state).unwrap_or_else(|poisoned| {
+                record_poison("sse.buffer");
+                poisoned.into_inner()
+            });
+        }
+    }
+
+    /// The next event if one is queued
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.state();
+        match state.events.pop_front() {
+            Some(event) => Ok(event),
+            None if state.closed || state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Events queued for the receiver
+    pub(crate) fn buffered(&self) -> usize {
+        self.state().events.len()
+    }
+
+    /// Events discarded by the overflow policy or sent after the stream closed
+    pub(crate) fn dropped(&self) -> u64 {
+        self.state().dropped
+    }
+
+    /// Whether the stream closed: on [`SseOverflow::CloseStream`] overflow or because the
+    /// receiver is gone
+    pub(crate) fn is_closed(&self) -> bool {
+        self.state().closed
+    }
+
+    pub(crate) fn add_sender(&self) {
+        self.state().senders += 1;
+    }
+
+    pub(crate) fn remove_sender(&self) {
+        let mut state = self.state();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.ready.notify_all();
+        }
+    }
+
+    /// Collect `frame` into `body`, applying the overflow policy when it would exceed
+    /// [`SseConfig::max_body_bytes`]; `false` once the stream is closed
+    ///
+    /// Comment frames (starting with `:`) dropped to make room are not counted as events.
+    pub(crate) fn append(&self, body: &mut SseBody, frame: String) -> bool {
+        let max = self.config.max_body_bytes;
+        if body.len + frame.len() <= max {
+            body.push(frame);
+            return true;
+        }
+        let mut state = self.state();
+        if !body.overflowed {
+            body.overflowed = true;
+            warn!(
+                max_body_bytes = max,
+                overflow = ?self.config.overflow,
+                "SSE response body full: applying the overflow policy"
+            );
+        }
+        let is_event = |frame: &str| !frame.starts_with(':');
+        match self.config.overflow {
+            SseOverflow::DropOldest => {
+                while body.len + frame.len() > max {
+                    let Some(oldest) = body.frames.pop_front() else {
+                        // Larger than the whole body on its own
+                        state.dropped += u64::from(is_event(&frame));
+                        return true;
+                    };
+                    body.len -= oldest.len();
+                    state.dropped += u64::from(is_event(&oldest));
+                }
+                body.push(frame);
+                true
+            }
+            SseOverflow::DropNewest => {
+                state.dropped += u64::from(is_event(&frame));
+                true
+            }
+            SseOverflow::CloseStream => {
+                let collected = body.frames.iter().filter(|f| is_event(f)).count();
+                state.dropped +=
+                    (collected + state.events.len()) as u64 + u64::from(is_event(&frame));
+                body.frames.clear();
+                body.len = 0;
+                state.events.clear();
+                state.closed = true;
+                false
+            }
+        }
+    }
+
+    /// The receiver is gone: discard the backlog and everything sent from now on
+    pub(crate) fn close(&self) {
+        let mut state = self.state();
+        state.closed = true;
+        state.events.clear();
+    }
+
+    /// Log what the overflow policy discarded over the stream's life
+    pub(crate) fn log_dropped(&self) {
+        let dropped = self.dropped();
+        if dropped > 0 {
+            warn!(
+                dropped,
+                overflow = ?self.config.overflow,
+                "SSE stream ended with events dropped by the overflow policy"
+            );
+        }
+    }
+}
 
 /// Sender side of an SSE channel.
 ///
 /// Clone this to send events from multiple coroutines.
-#[derive(Clone)]
 pub struct SseSender {
-    tx: mpsc::Sender<String>,
+    buffer: Arc<SseBuffer<String>>,
+}
+
+impl Clone for SseSender {
+    fn clone(&self) -> Self {
+        self.buffer.add_sender();
+        Self {
+            buffer: Arc::clone(&self.buffer),
+        }
+    }
+}
+
+impl Drop for SseSender {
+    fn drop(&mut self) {
+        self.buffer.remove_sender();
+    }
 }
 
 impl SseSender {
     /// Send a message to the SSE stream
     ///
     /// Messages are sent as `data:` events. The connection will be closed
-    /// when all senders are dropped.
+    /// when all senders are dropped. A full buffer applies the channel's [`SseOverflow`].
     ///
     /// # Arguments
     ///
     /// * `data` - The message data to send
     pub fn send(&self, data: impl Into<String>) {
-        let _ = self.tx.send(data.into());
+        self.buffer.push(data.into());
+    }
+
+    /// Events waiting for the receiver, at most [`SseConfig::buffer`]
+    pub fn buffered(&self) -> usize {
+        self.buffer.buffered()
+    }
+
+    /// Events discarded so far because the buffer or the response body was full, or the stream
+    /// closed
+    pub fn dropped(&self) -> u64 {
+        self.buffer.dropped()
+    }
+
+    /// Whether the stream closed (after an [`SseOverflow::CloseStream`] overflow, or because
+    /// the receiver is gone), so further events are discarded
+    pub fn is_closed(&self) -> bool {
+        self.buffer.is_closed()
     }
 }
 
 /// Receiver side that converts queued events into `text/event-stream` frames.
 pub struct SseReceiver {
-    rx: mpsc::Receiver<String>,
+    buffer: Arc<SseBuffer<String>>,
+    log: Option<SseStreamLog>,
+}
+
+impl Drop for SseReceiver {
+    fn drop(&mut self) {
+        self.buffer.close();
+    }
 }
 
 impl SseReceiver {
+    /// Correlate the stream with the request that opened it (see the
+    /// [module docs](crate::sse#request-correlation)); `None` leaves it uncorrelated.
+    #[must_use]
+    pub fn correlate(mut self, correlation: Option<&SseCorrelation>) -> Self {
+        self.log = correlation.map(SseStreamLog::open);
+        self
+    }
+
     /// Collect all events from the channel and return a single string containing
     /// properly formatted SSE frames.
-    pub fn collect(self) -> String {
-        let mut out = String::new();
-        let rx = self.rx;
-        while let Ok(msg) = rx.recv() {
-            out.push_str("data: ");
-            out.push_str(&msg);
+    ///
+    /// The body is capped at [`SseConfig::max_body_bytes`] (see the
+    /// [module docs](crate::sse#bounded-buffers)).
+    pub fn collect(mut self) -> String {
+        let mut preamble = String::new();
+        if let Some(log) = &self.log {
+            log.write_preamble(&mut preamble);
+        }
+        let mut body = SseBody::new(preamble);
+        while let Some(msg) = self.buffer.recv() {
+            if let Some(log) = &mut self.log {
+                log.event(None, msg.len());
+            }
+            let frame = format!("data: {msg}\n\n");
+            if !self.buffer.append(&mut body, frame) {
+                break;
+            }
+        }
+        self.buffer.log_dropped();
+        body.into_string()
+    }
+}
+
+/// Request id and trace context an SSE stream is correlated with
+///
+/// Stored in [`HandlerRequest::extensions`] for `text/event-stream` routes; build one with
+/// [`SseCorrelation::from_request`] or [`SseCorrelation::new`] elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseCorrelation {
+    request_id: String,
+    traceparent: Option<String>,
+    comment: bool,
+}
+
+impl SseCorrelation {
+    /// Correlate with `request_id`, announced in a `: request-id=` comment
+    #[must_use]
+    pub fn new(request_id: impl Into<String>) -> Self {
+        Self {
+            request_id: request_id.into(),
+            traceparent: None,
+            comment: true,
+        }
+    }
+
+    /// The request's id and W3C `traceparent` header
+    #[must_use]
+    pub fn from_request(req: &HandlerRequest) -> Self {
+        let correlation = Self::new(req.request_id.to_string());
+        match req.get_header("traceparent") {
+            Some(traceparent) => correlation.traceparent(traceparent),
+            None => correlation,
+        }
+    }
+
+    /// Record `traceparent` on the stream's span
+    #[must_use]
+    pub fn traceparent(mut self, traceparent: impl Into<String>) -> Self {
+        self.traceparent = Some(traceparent.into());
+        self
+    }
+
+    /// Start the stream with the `: request-id=` comment (default `true`)
+    #[must_use]
+    pub fn comment(mut self, enabled: bool) -> Self {
+        self.comment = enabled;
+        self
+    }
+
+    /// The correlated request id
+    #[must_use]
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+/// Log span of one correlated stream; dropping it closes the span.
+pub(crate) struct SseStreamLog {
+    span: Span,
+    request_id: String,
+    comment: bool,
+    events: u64,
+}
+
+impl SseStreamLog {
+    /// Open the `sse_stream` span, as a child of the current (handler) span.
+    pub(crate) fn open(correlation: &SseCorrelation) -> Self {
+        let span = info_span!(
+            "sse_stream",
+            request_id = %correlation.request_id,
+            traceparent = correlation.traceparent.as_deref().unwrap_or(""),
+            events = tracing::field::Empty,
+        );
+        Self {
+            span,
+            // A line break would end the comment early.
+            request_id: correlation
+                .request_id
+                .split(['\r', '\n'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            comment: correlation.comment,
+            events: 0,
+        }
+    }
+
+    /// Write the `: request-id=` comment frame, if enabled.
+    pub(crate) fn write_preamble(&self, out: &mut String) {
+        if self.comment {
+            out.push_str(": request-id=");
+            out.push_str(&self.request_id);
             out.push_str("\n\n");
         }
-        out
+    }
+
+    /// Log one event in its own span under the stream's.
+    pub(crate) fn event(&mut self, name: Option<&str>, bytes: usize) {
+        self.events += 1;
+        let span = info_span!(
+            parent: &self.span,
+            "sse_event",
+            request_id = %self.request_id,
+            seq = self.events,
+        );
+        let _enter = span.enter();
+        debug!(event = name.unwrap_or("message"), bytes, "SSE event sent");
+    }
+}
+
+impl Drop for SseStreamLog {
+    fn drop(&mut self) {
+        self.span.record("events", self.events);
+        let _enter = self.span.enter();
+        debug!(events = self.events, "SSE stream closed");
     }
 }
 
 /// Create a new SSE channel returning the sender and receiver halves.
+///
+/// Buffers up to [`DEFAULT_SSE_BUFFER`] events, dropping the oldest beyond that; see
+/// [`channel_with`].
 pub fn channel() -> (SseSender, SseReceiver) {
-    let (tx, rx) = mpsc::channel();
-    (SseSender { tx }, SseReceiver { rx })
+    channel_with(SseConfig::default())
+}
+
+/// Create an SSE channel with `config`'s buffer size and overflow policy.
+pub fn channel_with(config: SseConfig) -> (SseSender, SseReceiver) {
+    let buffer = SseBuffer::new(config);
+    (
+        SseSender {
+            buffer: Arc::clone(&buffer),
+        },
+        SseReceiver { buffer, log: None },
+    )
+}
+
+/// The `data:` payloads of the events in an SSE stream, in order
+///
+/// The `data:` lines of one event are joined with `\n`; comments, other fields and events
+/// without data are skipped.
+pub(crate) fn data_frames(stream: &str) -> impl Iterator<Item = String> + '_ {
+    stream.split("\n\n").filter_map(|event| {
+        let mut data: Option<String> = None;
+        for line in event.lines() {
+            let Some(value) = line.strip_prefix("data:") else {
+                continue;
+            };
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match &mut data {
+                Some(joined) => {
+                    joined.push('\n');
+                    joined.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            }
+        }
+        data
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_frames_skip_comments_and_join_lines() {
+        let stream = "event: tick\ndata: {\"n\":1}\n\n: heartbeat\n\ndata: a\ndata: b\n\n";
+        assert_eq!(
+            data_frames(stream).collect::<Vec<_>>(),
+            ["{\"n\":1}", "a\nb"]
+        );
+    }
 }
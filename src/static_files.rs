@@ -52,15 +52,30 @@
 //!
 //! ## Supported MIME Types
 //!
-//! The module automatically detects content types:
+//! Content types come from the file extension (case-insensitive) via an embedded table
+//! covering web documents, scripts, source maps, images, fonts, media, archives and
+//! WebAssembly, for example:
 //!
-//! - `.html` → `text/html`
-//! - `.css` → `text/css`
-//! - `.js` → `application/javascript`
-//! - `.json` → `application/json`
-//! - `.txt` → `text/plain`
+//! - `.html` → `text/html`, `.css` → `text/css`, `.js` / `.mjs` → `application/javascript`
+//! - `.json` / `.map` → `application/json`
+//! - `.svg` → `image/svg+xml`, `.png` → `image/png`, `.webp` → `image/webp`
+//! - `.woff2` → `font/woff2`, `.woff` → `font/woff`, `.ttf` → `font/ttf`
+//! - `.wasm` → `application/wasm` (required by `WebAssembly.instantiateStreaming`)
 //! - Others → `application/octet-stream`
 //!
+//! [`StaticFiles::with_mime_types`] adds or overrides entries (config.yaml
+//! `static_files.mime_types`).
+//!
+//! ## Precompressed Variants
+//!
+//! Assets compressed at build time are served without runtime compression:
+//! [`StaticFiles::load_precompressed`] picks a sibling `app.js.br` or `app.js.gz` when the
+//! client's `Accept-Encoding` allows it (brotli preferred), returning the original file's
+//! content type and the matching `Content-Encoding`. Without an acceptable variant the
+//! original is served uncompressed. With
+//! [`verify_precompressed_mtime`](StaticFiles::verify_precompressed_mtime) enabled, a variant
+//! older than its original is treated as stale and skipped.
+//!
 //! ## Handler Integration
 //!
 //! ```rust,ignore
@@ -83,9 +98,16 @@
 
 use minijinja::Environment;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::middleware::accepts_encoding;
+
+/// Precompressed sibling files as (`Content-Encoding`, file suffix), in preference order.
+const PRECOMPRESSED_VARIANTS: [(&str, &str); 2] = [("br", ".br"), ("gzip", ".gz")];
 
 /// Static file server with security and template rendering support.
 ///
@@ -94,6 +116,9 @@ use std::path::{Component, Path, PathBuf};
 #[derive(Clone)]
 pub struct StaticFiles {
     base_dir: PathBuf,
+    verify_precompressed_mtime: bool,
+    /// Lowercase extension (no dot) → MIME type, consulted before the built-in table
+    mime_overrides: Arc<HashMap<String, String>>,
 }
 
 impl StaticFiles {
@@ -109,7 +134,38 @@ impl StaticFiles {
     pub fn new<P: Into<PathBuf>>(base: P) -> Self {
         Self {
             base_dir: base.into(),
+            verify_precompressed_mtime: false,
+            mime_overrides: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// Add or override extension → MIME type mappings
+    ///
+    /// Extensions match case-insensitively, with or without a leading dot
+    /// (`"glb"`, `".GLB"`). Overrides take precedence over the built-in table.
+    #[must_use]
+    pub fn with_mime_types<I, K, V>(mut self, mime_types: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let overrides = Arc::make_mut(&mut self.mime_overrides);
+        for (ext, mime) in mime_types {
+            let ext = ext.as_ref().trim_start_matches('.').to_ascii_lowercase();
+            overrides.insert(ext, mime.into());
         }
+        self
+    }
+
+    /// Skip precompressed variants older than their original file (off by default).
+    ///
+    /// Guards against serving a stale `.br` / `.gz` after the original was rebuilt without
+    /// recompressing.
+    #[must_use]
+    pub fn verify_precompressed_mtime(mut self, verify: bool) -> Self {
+        self.verify_precompressed_mtime = verify;
+        self
     }
 
     fn map_path(&self, url_path: &str) -> Option<PathBuf> {
@@ -133,21 +189,16 @@ impl StaticFiles {
         Some(pb)
     }
 
-    fn content_type(path: &Path) -> &'static str {
-        match path
+    fn content_type(&self, path: &Path) -> &str {
+        let ext = path
             .extension()
             .and_then(|s| s.to_str())
             .unwrap_or("")
-            .to_lowercase()
-            .as_str()
-        {
-            "html" => "text/html",
-            "css" => "text/css",
-            "js" => "application/javascript",
-            "json" => "application/json",
-            "txt" => "text/plain",
-            _ => "application/octet-stream",
+            .to_ascii_lowercase();
+        if let Some(mime) = self.mime_overrides.get(&ext) {
+            return mime;
         }
+        builtin_mime_type(&ext).unwrap_or("application/octet-stream")
     }
 
     /// Load a file from the static directory with optional template rendering
@@ -171,11 +222,7 @@ impl StaticFiles {
     /// - The file doesn't exist
     /// - Template rendering fails (for HTML files)
     /// - File I/O fails
-    pub fn load(
-        &self,
-        url_path: &str,
-        ctx: Option<&JsonValue>,
-    ) -> io::Result<(Vec<u8>, &'static str)> {
+    pub fn load(&self, url_path: &str, ctx: Option<&JsonValue>) -> io::Result<(Vec<u8>, &str)> {
         let path = self
             .map_path(url_path)
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "invalid path"))?;
@@ -189,14 +236,109 @@ impl StaticFiles {
                 env.add_template("tpl", &source).map_err(io::Error::other)?;
                 let tmpl = env.get_template("tpl").map_err(io::Error::other)?;
                 let rendered = tmpl.render(ctx_val).map_err(io::Error::other)?;
-                return Ok((rendered.into_bytes(), Self::content_type(&path)));
+                return Ok((rendered.into_bytes(), self.content_type(&path)));
             }
         }
         let bytes = fs::read(&path)?;
-        Ok((bytes, Self::content_type(&path)))
+        Ok((bytes, self.content_type(&path)))
+    }
+
+    /// Load a precompressed sibling of `url_path` acceptable under `accept_encoding`
+    ///
+    /// Looks for `<file>.br`, then `<file>.gz`, next to the original, which must itself
+    /// exist. Templates are not rendered: callers passing a context use [`Self::load`].
+    ///
+    /// # Returns
+    ///
+    /// `(file_contents, content_type, content_encoding)`, where `content_type` is the
+    /// original file's; `None` when no acceptable, fresh variant can be read, in which case
+    /// the original should be served.
+    pub fn load_precompressed(
+        &self,
+        url_path: &str,
+        accept_encoding: &str,
+    ) -> Option<(Vec<u8>, &str, &'static str)> {
+        let path = self.map_path(url_path)?;
+        if !path.is_file() {
+            return None;
+        }
+        PRECOMPRESSED_VARIANTS
+            .iter()
+            .filter(|(encoding, _)| accepts_encoding(accept_encoding, encoding))
+            .find_map(|(encoding, suffix)| {
+                let mut variant = path.clone().into_os_string();
+                variant.push(suffix);
+                let variant = PathBuf::from(variant);
+                if !variant.is_file()
+                    || (self.verify_precompressed_mtime && Self::is_stale(&path, &variant))
+                {
+                    return None;
+                }
+                let bytes = fs::read(&variant).ok()?;
+                Some((bytes, self.content_type(&path), *encoding))
+            })
+    }
+
+    /// Whether `variant` is older than `original` (or either mtime is unavailable).
+    fn is_stale(original: &Path, variant: &Path) -> bool {
+        let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+        match (modified(original), modified(variant)) {
+            (Some(original), Some(variant)) => variant < original,
+            _ => true,
+        }
     }
 }
 
+/// MIME type for a lowercase file extension from the embedded table.
+fn builtin_mime_type(ext: &str) -> Option<&'static str> {
+    Some(match ext {
+        // Documents and data
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" | "cjs" => "application/javascript",
+        "json" | "map" => "application/json",
+        "jsonld" => "application/ld+json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        // Images
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        // Fonts
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "eot" => "application/vnd.ms-fontobject",
+        // Audio and video
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "oga" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "ogv" => "video/ogg",
+        // Archives
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "br" => "application/x-brotli",
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,6 +369,92 @@ mod tests {
         assert_eq!(String::from_utf8(bytes).unwrap(), "<h1>Hello World!</h1>");
     }
 
+    /// Temp dir holding `app.js` plus the given precompressed suffixes.
+    fn precompressed_dir(suffixes: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.js"), "plain").unwrap();
+        for suffix in suffixes {
+            fs::write(dir.path().join(format!("app.js{suffix}")), *suffix).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_precompressed_prefers_br_then_gzip() {
+        let dir = precompressed_dir(&[".br", ".gz"]);
+        let sf = StaticFiles::new(dir.path());
+
+        let (bytes, ct, encoding) = sf.load_precompressed("/app.js", "gzip, br").unwrap();
+        assert_eq!(
+            (bytes.as_slice(), ct, encoding),
+            (&b".br"[..], "application/javascript", "br")
+        );
+
+        let (bytes, _, encoding) = sf.load_precompressed("/app.js", "gzip, br;q=0").unwrap();
+        assert_eq!((bytes.as_slice(), encoding), (&b".gz"[..], "gzip"));
+
+        assert!(sf.load_precompressed("/app.js", "deflate").is_none());
+        assert!(sf.load_precompressed("/missing.js", "br").is_none());
+    }
+
+    #[test]
+    fn test_precompressed_falls_back_when_variant_missing() {
+        let dir = precompressed_dir(&[".gz"]);
+        let sf = StaticFiles::new(dir.path());
+        let (_, _, encoding) = sf.load_precompressed("/app.js", "br, gzip").unwrap();
+        assert_eq!(encoding, "gzip");
+        assert!(sf.load_precompressed("/app.js", "br").is_none());
+    }
+
+    #[test]
+    fn test_precompressed_mtime_check_skips_stale_variant() {
+        let dir = precompressed_dir(&[".br"]);
+        let original = dir.path().join("app.js");
+        let stale = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(dir.path().join("app.js.br"))
+            .unwrap()
+            .set_modified(stale)
+            .unwrap();
+        assert!(fs::metadata(&original).unwrap().modified().unwrap() > stale);
+
+        let sf = StaticFiles::new(dir.path());
+        assert!(sf.load_precompressed("/app.js", "br").is_some());
+        let sf = sf.verify_precompressed_mtime(true);
+        assert!(sf.load_precompressed("/app.js", "br").is_none());
+    }
+
+    #[test]
+    fn test_content_type_table() {
+        let sf = StaticFiles::new("tests/staticdata");
+        for (file, expected) in [
+            ("app.wasm", "application/wasm"),
+            ("font.woff2", "font/woff2"),
+            ("data.json", "application/json"),
+            ("logo.SVG", "image/svg+xml"),
+            ("bundle.js.map", "application/json"),
+            ("module.mjs", "application/javascript"),
+            ("photo.jpeg", "image/jpeg"),
+            ("archive.unknownext", "application/octet-stream"),
+            ("Makefile", "application/octet-stream"),
+        ] {
+            assert_eq!(sf.content_type(Path::new(file)), expected, "{file}");
+        }
+    }
+
+    #[test]
+    fn test_mime_overrides_take_precedence() {
+        let sf = StaticFiles::new("tests/staticdata").with_mime_types([
+            (".GLB", "model/gltf-binary"),
+            ("txt", "text/plain; charset=utf-8"),
+        ]);
+        assert_eq!(sf.content_type(Path::new("scene.glb")), "model/gltf-binary");
+        let (_, ct) = sf.load("hello.txt", None).unwrap();
+        assert_eq!(ct, "text/plain; charset=utf-8");
+        assert_eq!(sf.content_type(Path::new("app.wasm")), "application/wasm");
+    }
+
     #[test]
     fn test_load_js() {
         let sf = StaticFiles::new("tests/staticdata");
@@ -0,0 +1,335 @@
+//! Helpers for testing JWT-secured services (feature `testing`).
+//!
+//! - [`TestJwt`] signs HS256 or RS256 access tokens with the `kid`, expiry, scopes and claims
+//!   a test needs, carrying the `typ: at+jwt` header [`JwksBearerProvider`] requires. With
+//!   [`TestJwt::fixed_signature`] it builds tokens for [`BearerJwtProvider`] and
+//!   [`OAuth2Provider`], which compare the signature instead of verifying it.
+//! - [`MockJwksServer`] serves a JWKS document on a loopback port until dropped; it counts
+//!   fetches and can rotate keys mid-test.
+//! - [`hs256_jwk`], [`rsa_jwk`] and [`jwks`] build the document it serves.
+//!
+//! ```rust,ignore
+//! use brrtrouter::security::JwksBearerProvider;
+//! use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
+//!
+//! let server = MockJwksServer::start(jwks([hs256_jwk("k1", b"secret")]))?;
+//! let provider = JwksBearerProvider::new(server.url()).issuer("https://issuer.example");
+//! let token = TestJwt::hs256(b"secret")
+//!     .kid("k1")
+//!     .issuer("https://issuer.example")
+//!     .scopes(["pets:read"])
+//!     .sign();
+//! ```
+//!
+//! Test code only: keys are whatever the test passes in and the server speaks plain HTTP.
+//!
+//! [`JwksBearerProvider`]: crate::security::JwksBearerProvider
+//! [`BearerJwtProvider`]: crate::security::BearerJwtProvider
+//! [`OAuth2Provider`]: crate::security::OAuth2Provider
+
+use crate::lock_poison;
+use base64::Engine as _;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde_json::{json, Map, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const JWKS_BODY_LOCK: &str = "mock_jwks_body";
+
+/// Builder for a signed test access token
+///
+/// Defaults: no `kid`, header `typ: at+jwt`, `exp` one hour from signing, no other claims.
+#[derive(Clone)]
+pub struct TestJwt {
+    header: Header,
+    key: EncodingKey,
+    claims: Map<String, Value>,
+    expires_in: Option<i64>,
+    signature: Option<String>,
+}
+
+impl TestJwt {
+    /// A token signed with HMAC-SHA256 using `secret`
+    #[must_use]
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self::new(Algorithm::HS256, EncodingKey::from_secret(secret))
+    }
+
+    /// A token signed with RSA-SHA256 using the PEM-encoded private key
+    ///
+    /// # Errors
+    ///
+    /// When `private_key_pem` is not a PKCS#1 or PKCS#8 RSA private key.
+    pub fn rs256(private_key_pem: &[u8]) -> Result<Self, jsonwebtoken::errors::Error> {
+        Ok(Self::new(
+            Algorithm::RS256,
+            EncodingKey::from_rsa_pem(private_key_pem)?,
+        ))
+    }
+
+    /// A token whose signature segment is `signature` verbatim
+    ///
+    /// For [`BearerJwtProvider`](crate::security::BearerJwtProvider) and
+    /// [`OAuth2Provider`](crate::security::OAuth2Provider), e.g. `TestJwt::fixed_signature("sig")`
+    /// for `BearerJwtProvider::new("sig")`.
+    #[must_use]
+    pub fn fixed_signature(signature: &str) -> Self {
+        Self {
+            signature: Some(signature.to_string()),
+            ..Self::hs256(signature.as_bytes())
+        }
+    }
+
+    fn new(alg: Algorithm, key: EncodingKey) -> Self {
+        Self {
+            header: Header {
+                alg,
+                typ: Some("at+jwt".to_string()),
+                ..Default::default()
+            },
+            key,
+            claims: Map::new(),
+            expires_in: Some(3600),
+            signature: None,
+        }
+    }
+
+    /// Set the header `kid` the JWKS key is looked up by
+    #[must_use]
+    pub fn kid(mut self, kid: impl Into<String>) -> Self {
+        self.header.kid = Some(kid.into());
+        self
+    }
+
+    /// Replace the header `typ` (`None` leaves it out)
+    #[must_use]
+    pub fn typ(mut self, typ: Option<&str>) -> Self {
+        self.header.typ = typ.map(str::to_string);
+        self
+    }
+
+    /// Set the `iss` claim
+    #[must_use]
+    pub fn issuer(self, iss: &str) -> Self {
+        self.claim("iss", iss)
+    }
+
+    /// Set the `aud` claim
+    #[must_use]
+    pub fn audience(self, aud: &str) -> Self {
+        self.claim("aud", aud)
+    }
+
+    /// Set the `scope` claim to `scopes` joined by spaces
+    #[must_use]
+    pub fn scopes<I, S>(self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let scope = scopes
+            .into_iter()
+            .map(|s| s.as_ref().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.claim("scope", scope)
+    }
+
+    /// Set claim `name`, replacing an earlier value (an explicit `exp` wins over
+    /// [`expires_in`](Self::expires_in))
+    #[must_use]
+    pub fn claim(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.claims.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Set claim `name` (e.g. `nbf` or `iat`) to the time `secs` seconds from now; negative
+    /// values lie in the past
+    #[must_use]
+    pub fn claim_from_now(self, name: &str, secs: i64) -> Self {
+        self.claim(name, unix_now().saturating_add(secs))
+    }
+
+    /// Expire `secs` seconds after signing; negative values give an already expired token
+    #[must_use]
+    pub fn expires_in(mut self, secs: i64) -> Self {
+        self.expires_in = Some(secs);
+        self
+    }
+
+    /// Leave out `exp`
+    #[must_use]
+    pub fn no_expiry(mut self) -> Self {
+        self.expires_in = None;
+        self
+    }
+
+    /// The claims [`sign`](Self::sign) encodes, with `exp` resolved against the current time
+    #[must_use]
+    pub fn claims(&self) -> Value {
+        let mut claims = self.claims.clone();
+        if let Some(secs) = self.expires_in {
+            claims
+                .entry("exp")
+                .or_insert_with(|| json!(unix_now().saturating_add(secs)));
+        }
+        Value::Object(claims)
+    }
+
+    /// Encode and sign the token
+    ///
+    /// # Panics
+    ///
+    /// When signing fails, which for keys this builder accepts does not happen.
+    #[must_use]
+    #[track_caller]
+    #[allow(clippy::panic)]
+    pub fn sign(&self) -> String {
+        let token = jsonwebtoken::encode(&self.header, &self.claims(), &self.key)
+            .unwrap_or_else(|e| panic!("failed to sign test JWT: {e}"));
+        match (&self.signature, token.rsplit_once('.')) {
+            (Some(signature), Some((signed, _))) => format!("{signed}.{signature}"),
+            _ => token,
+        }
+    }
+}
+
+impl std::fmt::Debug for TestJwt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestJwt")
+            .field("header", &self.header)
+            .field("claims", &self.claims)
+            .field("expires_in", &self.expires_in)
+            .field("signature", &self.signature)
+            .finish_non_exhaustive()
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// JWK for an HS256 `secret` (`kty: oct`)
+#[must_use]
+pub fn hs256_jwk(kid: &str, secret: &[u8]) -> Value {
+    json!({
+        "kty": "oct",
+        "alg": "HS256",
+        "kid": kid,
+        "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret),
+    })
+}
+
+/// JWK for an RS256 public key given its base64url modulus `n` and exponent `e`
+#[must_use]
+pub fn rsa_jwk(kid: &str, n: &str, e: &str) -> Value {
+    json!({"kty": "RSA", "alg": "RS256", "use": "sig", "kid": kid, "n": n, "e": e})
+}
+
+/// JWKS document (`{"keys": [...]}`) holding `keys`
+#[must_use]
+pub fn jwks(keys: impl IntoIterator<Item = Value>) -> String {
+    json!({ "keys": keys.into_iter().collect::<Vec<_>>() }).to_string()
+}
+
+/// JWKS endpoint on `127.0.0.1` serving a fixed document to every `GET`
+///
+/// Each connection gets one `200 application/json` answer and is closed. The server stops
+/// when dropped.
+#[derive(Debug)]
+pub struct MockJwksServer {
+    addr: SocketAddr,
+    body: Arc<Mutex<String>>,
+    requests: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockJwksServer {
+    /// Serve `jwks` on an ephemeral port
+    ///
+    /// # Errors
+    ///
+    /// When the listener cannot be bound or the server thread cannot be spawned.
+    pub fn start(jwks: impl Into<String>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let body = Arc::new(Mutex::new(jwks.into()));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::Builder::new()
+            .name("mock-jwks".to_string())
+            .spawn({
+                let body = Arc::clone(&body);
+                let requests = Arc::clone(&requests);
+                let stop = Arc::clone(&stop);
+                move || serve(&listener, &body, &requests, &stop)
+            })?;
+        Ok(Self {
+            addr,
+            body,
+            requests,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// URL of the JWKS document, for `JwksBearerProvider::new`
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}/jwks.json", self.addr)
+    }
+
+    /// Address the server listens on
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Serve `jwks` from now on, e.g. to rotate keys
+    pub fn set_jwks(&self, jwks: impl Into<String>) {
+        *lock_poison::lock(&self.body, JWKS_BODY_LOCK) = jwks.into();
+    }
+
+    /// Number of requests answered so far
+    #[must_use]
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MockJwksServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the blocking accept so the loop sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(listener: &TcpListener, body: &Mutex<String>, requests: &AtomicUsize, stop: &AtomicBool) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(mut stream) = stream else { continue };
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+        let body = lock_poison::lock(body, JWKS_BODY_LOCK).clone();
+        let resp = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        requests.fetch_add(1, Ordering::SeqCst);
+        let _ = stream.write_all(resp.as_bytes());
+    }
+}
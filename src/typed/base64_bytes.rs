@@ -0,0 +1,82 @@
+//! `format: byte` wire type for generated structs.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// Binary data carried as a standard (RFC 4648, padded) base64 string on the wire.
+///
+/// The generator maps OpenAPI `type: string, format: byte` to this type under
+/// `--rich-types`. Serializes to and deserializes from the base64 text; the bytes are
+/// available through `Deref<Target = [u8]>` or [`Base64Bytes::into_inner`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Base64Bytes(pub Vec<u8>);
+
+impl Base64Bytes {
+    /// Consume the wrapper and return the decoded bytes.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Base64Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Base64Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl FromStr for Base64Bytes {
+    type Err = base64::DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        STANDARD.decode(s).map(Self)
+    }
+}
+
+impl fmt::Display for Base64Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(&self.0))
+    }
+}
+
+impl Serialize for Base64Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Base64Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let bytes = Base64Bytes(b"hello\x00\xff".to_vec());
+        let json = serde_json::to_value(&bytes).unwrap();
+        assert_eq!(json, serde_json::json!("aGVsbG8A/w=="));
+        assert_eq!(serde_json::from_value::<Base64Bytes>(json).unwrap(), bytes);
+    }
+
+    #[test]
+    fn rejects_invalid_base64() {
+        assert!(serde_json::from_value::<Base64Bytes>(serde_json::json!("not base64!")).is_err());
+    }
+}
@@ -1,9 +1,16 @@
 // typed.rs
 #[allow(unused_imports)]
-use crate::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use crate::alloc_profile::{self, Phase};
+use crate::dispatcher::{
+    exhausted_response, Deadline, Dispatcher, HandlerRequest, HandlerResponse, HeaderVec,
+    StackWatch, HANDLER_PANIC_PROBLEM_TYPE,
+};
 use crate::ids::RequestId;
+use crate::security::AuthContext;
+use crate::server::ProblemDetails;
 use anyhow::Result;
-use http::Method;
+use bytes::Bytes;
+use http::{Extensions, Method};
 use may::sync::mpsc;
 use serde::Serialize;
 use serde_json;
@@ -77,6 +84,20 @@ impl<T: Serialize + Send + 'static> HandlerResponseOutput for HttpJson<T> {
     }
 }
 
+/// `Ok` maps as `T` does; `Err` becomes an `application/problem+json` response.
+///
+/// Lets generated controllers return the framework's error envelope, e.g.
+/// `-> Result<HttpJson<Response>, ProblemDetails>` with
+/// `Err(ProblemDetails::new(404).detail("Pet not found"))`.
+impl<T: HandlerResponseOutput> HandlerResponseOutput for Result<T, ProblemDetails> {
+    fn into_handler_response(self) -> Result<HandlerResponse, serde_json::Error> {
+        match self {
+            Ok(out) => out.into_handler_response(),
+            Err(problem) => Ok(HandlerResponse::problem(&problem)),
+        }
+    }
+}
+
 /// HTTP redirect response (e.g. OAuth authorize URL).
 ///
 /// Returns the given status (typically **302** or **303**) with a `Location` header
@@ -118,32 +139,31 @@ fn typed_handler_output_to_response(
 ) -> HandlerResponse {
     match result.into_handler_response() {
         Ok(hr) => {
-            if hr.body.is_null() {
-                let mut err = serde_json::json!({
-                    "error": "Failed to serialize response",
-                    "details": "Handler response serialized to JSON null — add an explicit `-> YourResponse` return type on #[handler] functions",
-                });
-                if let Some(rid) = request_id {
-                    err["request_id"] = serde_json::json!(rid.to_string());
-                }
-                HandlerResponse::json(500, err)
+            // A null body is only a mistake where the status carries one (not for `NoContent`).
+            if hr.body.is_null() && crate::server::response::response_status_allows_body(hr.status)
+            {
+                serialization_problem(
+                    "Handler response serialized to JSON null — add an explicit `-> YourResponse` return type on #[handler] functions",
+                    request_id,
+                )
             } else {
                 hr
             }
         }
-        Err(e) => {
-            let mut err = serde_json::json!({
-                "error": "Failed to serialize response",
-                "details": e.to_string(),
-            });
-            if let Some(rid) = request_id {
-                err["request_id"] = serde_json::json!(rid.to_string());
-            }
-            HandlerResponse::json(500, err)
-        }
+        Err(e) => serialization_problem(&e.to_string(), request_id),
     }
 }
 
+fn serialization_problem(detail: &str, request_id: Option<&RequestId>) -> HandlerResponse {
+    let mut problem = ProblemDetails::new(500)
+        .title("Failed to serialize response")
+        .detail(detail);
+    if let Some(rid) = request_id {
+        problem = problem.extension("request_id", rid.to_string());
+    }
+    HandlerResponse::problem(&problem)
+}
+
 /// Get the stack size for a handler with environment variable overrides applied
 ///
 /// Checks for environment variables in this order:
@@ -272,7 +292,8 @@ where
         .spawn(move || {
             let handler = handler;
             // Main event loop: process requests until channel closes
-            for req in rx.iter() {
+            for mut req in rx.iter() {
+                let _dispatch_phase = alloc_profile::phase(Phase::Dispatch);
                 // Extract lightweight fields we need outside the panic-catching closure.
                 // These are cheap clones (sender clones or small strings) and are ok to clone.
                 let reply_tx_outer = req.reply_tx.clone();
@@ -310,6 +331,11 @@ where
                             .map(|(k, v)| (k.to_string(), v.clone()))
                             .collect();
                         let jwt_claims = req.jwt_claims.clone();
+                        let downstream_headers = req.downstream_headers.clone();
+                        let auth_context = req.auth_context.clone();
+                        let deadline = req.deadline.clone();
+                        let raw_body = req.raw_body.clone();
+                        let extensions = std::mem::take(&mut req.extensions);
 
                         // STEP 1: Type conversion - consume the HandlerRequest to produce handler data
                         // This intentionally consumes `req` (no req.clone()) to avoid heavy copies.
@@ -334,6 +360,11 @@ where
                             query_params,
                             data, // Strongly-typed request data
                             jwt_claims,
+                            downstream_headers,
+                            auth_context,
+                            deadline,
+                            raw_body,
+                            extensions,
                         };
 
                         // STEP 3: Call the actual handler
@@ -347,10 +378,8 @@ where
 
                 // PANIC RECOVERY: If handler panicked, send 500 error
                 if let Err(panic) = result {
-                    let _ = reply_tx_outer.send(HandlerResponse::error(
-                        500,
-                        &format!("Handler panicked: {:?}", panic),
-                    ));
+                    let _ = reply_tx_outer
+                        .send(HandlerResponse::handler_panic(&format!("{:?}", panic)));
                     eprintln!("Handler '{handler_name_outer}' panicked: {panic:?}");
                 }
             }
@@ -449,14 +478,20 @@ where
     // When no handler name is provided, use "unknown" as a placeholder (per-handler override won't match)
     let effective_name = handler_name.unwrap_or("unknown");
     let stack_size = get_stack_size_with_overrides(effective_name, stack_size_bytes);
+    let watched_name = effective_name.to_string();
 
     let spawn_result = may::coroutine::Builder::new()
         .stack_size(stack_size)
         .name(effective_name.to_string())
         .spawn(move || {
             let handler = handler;
+            let watch = StackWatch::start(&watched_name, stack_size);
             // Main event loop: process requests until channel closes
-            for req in rx.iter() {
+            for mut req in rx.iter() {
+                let _dispatch_phase = alloc_profile::phase(Phase::Dispatch);
+                if let Some(watch) = &watch {
+                    watch.stamp(&mut req.extensions);
+                }
                 // Extract lightweight fields we need outside the panic-catching closure.
                 // These are cheap clones (sender clones or small strings) and are ok to clone.
                 let reply_tx_outer = req.reply_tx.clone();
@@ -495,6 +530,11 @@ where
                             .map(|(k, v)| (k.to_string(), v.clone()))
                             .collect();
                         let jwt_claims = req.jwt_claims.clone();
+                        let downstream_headers = req.downstream_headers.clone();
+                        let auth_context = req.auth_context.clone();
+                        let deadline = req.deadline.clone();
+                        let raw_body = req.raw_body.clone();
+                        let extensions = std::mem::take(&mut req.extensions);
 
                         // STEP 1: Type conversion - consume the HandlerRequest to produce handler data
                         // This intentionally consumes `req` (no req.clone()) to avoid heavy copies.
@@ -519,6 +559,11 @@ where
                             query_params,
                             data, // Strongly-typed request data
                             jwt_claims,
+                            downstream_headers,
+                            auth_context,
+                            deadline,
+                            raw_body,
+                            extensions,
                         };
 
                         // STEP 3: Call the actual handler
@@ -532,16 +577,21 @@ where
 
                 // PANIC RECOVERY: If handler panicked, send 500 error
                 if let Err(panic) = result {
-                    let _ = reply_tx_outer.send(HandlerResponse {
-                        status: 500,
-                        headers: HeaderVec::new(),
-                        body: serde_json::json!({
-                            "error": "Handler panicked",
-                            "details": format!("{:?}", panic),
-                            "request_id": request_id.to_string(),
-                        }),
-                    });
-                    eprintln!("Handler '{handler_name_outer}' panicked: {panic:?}");
+                    if let Some(response) = exhausted_response(&*panic, &request_id) {
+                        let _ = reply_tx_outer.send(response);
+                    } else {
+                        let _ = reply_tx_outer.send(HandlerResponse::problem(
+                            &ProblemDetails::new(500)
+                                .problem_type(HANDLER_PANIC_PROBLEM_TYPE)
+                                .title("Handler panicked")
+                                .detail(format!("{:?}", panic))
+                                .extension("request_id", request_id.to_string()),
+                        ));
+                        eprintln!("Handler '{handler_name_outer}' panicked: {panic:?}");
+                    }
+                }
+                if let Some(watch) = &watch {
+                    watch.record();
                 }
             }
         });
@@ -592,6 +642,16 @@ pub struct TypedHandlerRequest<T> {
     pub data: T,
     /// Decoded JWT claims when the route required authentication (BR-2).
     pub jwt_claims: Option<serde_json::Value>,
+    /// Headers for downstream calls (see [`HandlerRequest::downstream_headers`]).
+    pub downstream_headers: HeaderVec,
+    /// Identity of the authenticated caller (see [`HandlerRequest::auth_context`]).
+    pub auth_context: Option<AuthContext>,
+    /// When the request is abandoned (see [`HandlerRequest::deadline`]).
+    pub deadline: Option<Deadline>,
+    /// Request body exactly as received (see [`HandlerRequest::raw_body`]).
+    pub raw_body: Option<Bytes>,
+    /// Values attached by middleware (see [`HandlerRequest::extensions`]).
+    pub extensions: Extensions,
 }
 
 impl<T> TypedHandlerFor<T> for TypedHandlerRequest<T>
@@ -619,6 +679,11 @@ where
                 .collect(),
             data,
             jwt_claims: req.jwt_claims,
+            downstream_headers: req.downstream_headers,
+            auth_context: req.auth_context,
+            deadline: req.deadline,
+            raw_body: req.raw_body,
+            extensions: req.extensions,
         })
     }
 }
@@ -790,6 +855,11 @@ impl Dispatcher {
                     .collect(),
                 data,
                 jwt_claims,
+                downstream_headers: req.downstream_headers.clone(),
+                auth_context: req.auth_context.clone(),
+                deadline: req.deadline.clone(),
+                raw_body: req.raw_body.clone(),
+                extensions: req.extensions,
             };
 
             // Call the handler
@@ -963,4 +1033,40 @@ mod tests {
         assert_eq!(hr.status, 201);
         assert_eq!(hr.body["id"], "new");
     }
+
+    #[test]
+    fn test_status_wrappers_send_their_status() {
+        let body = || serde_json::json!({"id": "new"});
+        let hr = typed_handler_output_to_response(crate::typed::Ok(body()), None);
+        assert_eq!((hr.status, hr.body["id"].as_str()), (200, Some("new")));
+        let hr = typed_handler_output_to_response(crate::typed::Created(body()), None);
+        assert_eq!((hr.status, hr.body["id"].as_str()), (201, Some("new")));
+        let hr = typed_handler_output_to_response(crate::typed::Accepted(body()), None);
+        assert_eq!((hr.status, hr.body["id"].as_str()), (202, Some("new")));
+
+        // No body is expected for 204, so the null-body guard does not turn it into a 500.
+        let hr = typed_handler_output_to_response(crate::typed::NoContent, None);
+        assert_eq!(hr.status, 204);
+        assert!(hr.body.is_null());
+        let hr = typed_handler_output_to_response(crate::typed::Created(()), None);
+        assert_eq!(hr.status, 500);
+    }
+
+    #[test]
+    fn test_result_err_problem_details_envelope() {
+        let out: Result<HttpJson<serde_json::Value>, ProblemDetails> =
+            Err(ProblemDetails::new(404).detail("Pet not found"));
+        let hr = typed_handler_output_to_response(out, None);
+        assert_eq!(hr.status, 404);
+        assert_eq!(hr.body["title"], "Not Found");
+        assert_eq!(hr.body["detail"], "Pet not found");
+        assert_eq!(
+            hr.get_header("content-type"),
+            Some("application/problem+json")
+        );
+
+        let ok: Result<HttpJson<serde_json::Value>, ProblemDetails> =
+            Ok(HttpJson::new(201, serde_json::json!({"id": "new"})));
+        assert_eq!(typed_handler_output_to_response(ok, None).status, 201);
+    }
 }
@@ -66,7 +66,34 @@
 //! Handler return types implement [`HandlerResponseOutput`]. Types that implement [`serde::Serialize`]
 //! are sent as **HTTP 200** with a JSON body. Use [`HttpJson`] for an explicit status (e.g. **201**, **404**)
 //! without panicking. See `docs/PRD_TYPED_HANDLER_HTTP_STATUS.md`.
+//!
+//! For success statuses the spec declares, [`Ok`], [`Created`], [`Accepted`] and [`NoContent`]
+//! name the status instead of spelling it out. Generated handlers for operations with a
+//! non-200 success response also get a `DeclaredResponse` enum holding only the declared
+//! wrappers; return it from the controller and an undeclared status no longer compiles.
+//! Import the wrappers by name (or as `typed::Ok`): a `typed::*` glob import shadows the
+//! prelude's `Result::Ok`.
+//!
+//! ## Server-Sent Events
+//!
+//! `text/event-stream` operations return [`SseResponse<T>`]: the handler pushes [`SseEvent`]
+//! values through an [`SseEmitter<T>`] and each one is sent as a JSON `data:` frame.
+//!
+//! ## File uploads
+//!
+//! `multipart/form-data` handlers read files with
+//! [`TypedHandlerRequest::uploaded_file`], which gives an [`UploadedFile`] (file name,
+//! content type, a reader). Size and type limits from the spec are enforced before the
+//! handler runs.
 
+mod base64_bytes;
 mod core;
+mod sse;
+mod status;
+mod upload;
 
+pub use base64_bytes::Base64Bytes;
 pub use core::*;
+pub use sse::{SseEmitter, SseEvent, SseResponse, HEARTBEAT_FRAME};
+pub use status::{Accepted, Created, NoContent, Ok};
+pub use upload::{UploadedFile, DEFAULT_UPLOAD_SPILL_BYTES};
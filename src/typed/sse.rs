@@ -0,0 +1,272 @@
+//! Typed Server-Sent Events
+//!
+//! A handler for a `text/event-stream` operation returns an [`SseResponse<T>`] and pushes
+//! typed events through its [`SseEmitter<T>`]. Each event is serialized to JSON as the
+//! frame's `data:` line, with an `event:` line when [`SseEvent::event_name`] names it:
+//!
+//! ```rust
+//! use brrtrouter::typed::{SseEvent, SseResponse};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Tick {
+//!     n: u32,
+//! }
+//!
+//! impl SseEvent for Tick {
+//!     fn event_name(&self) -> Option<&str> {
+//!         Some("tick")
+//!     }
+//! }
+//!
+//! let (events, stream) = SseResponse::channel();
+//! events.send(Tick { n: 1 });
+//! drop(events);
+//! assert_eq!(stream.collect().unwrap(), "event: tick\ndata: {\"n\":1}\n\n");
+//! ```
+//!
+//! The stream ends when every emitter is dropped. With [`SseResponse::heartbeat`], a
+//! `: heartbeat` comment frame is written whenever no event arrived for that long.
+//! [`SseResponse::channel_with`] sets the in-process buffer size and overflow policy, as
+//! [`crate::sse::channel_with`] does.
+//!
+//! [`SseResponse::correlate`] ties the stream to its request for logging, as described in
+//! [`crate::sse`]: `SseResponse::channel().1.correlate(req.extensions.get())`.
+
+use super::HandlerResponseOutput;
+use crate::dispatcher::{HandlerResponse, HeaderVec};
+use crate::sse::{SseBody, SseBuffer, SseConfig, SseCorrelation, SseStreamLog, TryRecvError};
+use may::coroutine;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Comment frame written when a stream with a heartbeat has been idle
+pub const HEARTBEAT_FRAME: &str = ": heartbeat\n\n";
+
+/// How often an idle stream with a heartbeat checks for events
+const HEARTBEAT_POLL: Duration = Duration::from_millis(1);
+
+/// An event sent on an [`SseResponse`]
+///
+/// The value is serialized to JSON as the frame's `data:` line. Return a name from
+/// [`event_name`](SseEvent::event_name) to add an `event:` line, so clients can listen with
+/// `EventSource.addEventListener(name, ...)` instead of `onmessage`. Enums can name each
+/// variant differently.
+pub trait SseEvent: Serialize + Send + 'static {
+    /// Name for the `event:` line; `None` (the default) sends an unnamed `message` event
+    ///
+    /// Anything from the first line break on is dropped, as it would end the field.
+    fn event_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl SseEvent for Value {}
+
+impl SseEvent for String {}
+
+/// Sender half of an [`SseResponse`]
+///
+/// Clone it to push events from several coroutines.
+pub struct SseEmitter<T> {
+    buffer: Arc<SseBuffer<T>>,
+}
+
+impl<T> Clone for SseEmitter<T> {
+    fn clone(&self) -> Self {
+        self.buffer.add_sender();
+        Self {
+            buffer: Arc::clone(&self.buffer),
+        }
+    }
+}
+
+impl<T> Drop for SseEmitter<T> {
+    fn drop(&mut self) {
+        self.buffer.remove_sender();
+    }
+}
+
+impl<T: SseEvent> SseEmitter<T> {
+    /// Queue `event` on the stream
+    ///
+    /// A full buffer applies the stream's [`SseOverflow`](crate::sse::SseOverflow). Events
+    /// sent after the response was dropped or the stream closed are discarded.
+    pub fn send(&self, event: T) {
+        self.buffer.push(event);
+    }
+
+    /// Events discarded so far because the buffer or the response body was full, or the stream
+    /// closed
+    pub fn dropped(&self) -> u64 {
+        self.buffer.dropped()
+    }
+
+    /// Whether the stream closed, so further events are discarded
+    pub fn is_closed(&self) -> bool {
+        self.buffer.is_closed()
+    }
+}
+
+/// A `text/event-stream` response of typed events
+///
+/// Does not implement [`Serialize`], so its [`HandlerResponseOutput`] impl (status 200,
+/// `Content-Type: text/event-stream`, `Cache-Control: no-cache`) does not collide with the one
+/// for plain serializable types.
+pub struct SseResponse<T> {
+    buffer: Arc<SseBuffer<T>>,
+    heartbeat: Option<Duration>,
+    log: Option<SseStreamLog>,
+}
+
+impl<T> Drop for SseResponse<T> {
+    fn drop(&mut self) {
+        self.buffer.close();
+    }
+}
+
+impl<T: SseEvent> SseResponse<T> {
+    /// Create a response and the emitter that feeds it, buffering the default
+    /// [`SseConfig`]
+    #[must_use]
+    pub fn channel() -> (SseEmitter<T>, Self) {
+        Self::channel_with(SseConfig::default())
+    }
+
+    /// Create a response and its emitter with `config`'s buffer size and overflow policy
+    #[must_use]
+    pub fn channel_with(config: SseConfig) -> (SseEmitter<T>, Self) {
+        let buffer = SseBuffer::new(config);
+        (
+            SseEmitter {
+                buffer: Arc::clone(&buffer),
+            },
+            Self {
+                buffer,
+                heartbeat: None,
+                log: None,
+            },
+        )
+    }
+
+    /// A response carrying exactly `events`
+    #[must_use]
+    pub fn from_events(events: impl IntoIterator<Item = T>) -> Self {
+        let events: Vec<T> = events.into_iter().collect();
+        // Buffered as a whole: nothing is read until the response is sent
+        let (emitter, response) = Self::channel_with(SseConfig {
+            buffer: events.len(),
+            ..SseConfig::default()
+        });
+        for event in events {
+            emitter.send(event);
+        }
+        response
+    }
+
+    /// Write [`HEARTBEAT_FRAME`] whenever no event arrived for `every`
+    ///
+    /// Keeps proxies from closing a quiet stream. While a heartbeat is set the stream polls for
+    /// events instead of blocking on them.
+    #[must_use]
+    pub fn heartbeat(mut self, every: Duration) -> Self {
+        self.heartbeat = Some(every);
+        self
+    }
+
+    /// Correlate the stream with the request that opened it: a `: request-id=` comment first,
+    /// and each event logged under the request id until the stream ends or is dropped
+    ///
+    /// Pass `req.extensions.get()`; `None` leaves the stream uncorrelated.
+    #[must_use]
+    pub fn correlate(mut self, correlation: Option<&SseCorrelation>) -> Self {
+        self.log = correlation.map(SseStreamLog::open);
+        self
+    }
+
+    /// Wait for every emitter to be dropped and return the events as SSE frames
+    ///
+    /// The body is capped at [`SseConfig::max_body_bytes`] (see the
+    /// [`crate::sse` docs](crate::sse#bounded-buffers)).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first event that fails to serialize.
+    pub fn collect(mut self) -> Result<String, serde_json::Error> {
+        let buffer = Arc::clone(&self.buffer);
+        let heartbeat = self.heartbeat;
+        let mut log = self.log.take();
+        let mut preamble = String::new();
+        if let Some(log) = &log {
+            log.write_preamble(&mut preamble);
+        }
+        let mut body = SseBody::new(preamble);
+        // `Ok(false)` once the stream is closed
+        let mut write = |body: &mut SseBody, event: &T| -> Result<bool, serde_json::Error> {
+            let mut frame = String::new();
+            write_event(&mut frame, event)?;
+            if let Some(log) = &mut log {
+                log.event(event.event_name(), frame.len());
+            }
+            Ok(buffer.append(body, frame))
+        };
+        let Some(every) = heartbeat else {
+            while let Some(event) = buffer.recv() {
+                if !write(&mut body, &event)? {
+                    break;
+                }
+            }
+            buffer.log_dropped();
+            return Ok(body.into_string());
+        };
+        let mut idle_since = Instant::now();
+        loop {
+            match buffer.try_recv() {
+                Ok(event) => {
+                    if !write(&mut body, &event)? {
+                        buffer.log_dropped();
+                        return Ok(body.into_string());
+                    }
+                    idle_since = Instant::now();
+                }
+                Err(TryRecvError::Disconnected) => {
+                    buffer.log_dropped();
+                    return Ok(body.into_string());
+                }
+                Err(TryRecvError::Empty) => {
+                    if idle_since.elapsed() >= every {
+                        buffer.append(&mut body, HEARTBEAT_FRAME.to_string());
+                        idle_since = Instant::now();
+                    }
+                    coroutine::sleep(HEARTBEAT_POLL.min(every));
+                }
+            }
+        }
+    }
+}
+
+/// Append `event` as one frame; compact JSON never contains a raw line break.
+fn write_event<T: SseEvent>(out: &mut String, event: &T) -> Result<(), serde_json::Error> {
+    let data = serde_json::to_string(event)?;
+    if let Some(name) = event.event_name() {
+        out.push_str("event: ");
+        out.push_str(name.split(['\r', '\n']).next().unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str("data: ");
+    out.push_str(&data);
+    out.push_str("\n\n");
+    Ok(())
+}
+
+impl<T: SseEvent> HandlerResponseOutput for SseResponse<T> {
+    fn into_handler_response(self) -> Result<HandlerResponse, serde_json::Error> {
+        let body = self.collect()?;
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("content-type"), "text/event-stream".to_string()));
+        headers.push((Arc::from("cache-control"), "no-cache".to_string()));
+        Ok(HandlerResponse::new(200, headers, Value::String(body)))
+    }
+}
@@ -0,0 +1,61 @@
+//! Success wrappers named after the status they send.
+//!
+//! `Ok(body)`, `Created(body)`, `Accepted(body)` and `NoContent` pick the status a typed
+//! handler answers with. Generated handlers wrap the ones their operation declares in a
+//! `DeclaredResponse` enum, so returning an undeclared status is a compile error; at runtime
+//! the service checks explicit success statuses against the spec as well (see
+//! [`crate::runtime_config::ResponseValidationMode`]).
+
+use super::core::HandlerResponseOutput;
+use crate::dispatcher::{HandlerResponse, HeaderVec};
+use serde::Serialize;
+use serde_json::Value;
+
+macro_rules! status_wrapper {
+    ($(#[$doc:meta])* $name:ident, $status:literal) => {
+        $(#[$doc])*
+        ///
+        /// Does **not** implement [`Serialize`], so it does not collide with the blanket
+        /// [`HandlerResponseOutput`] impl for plain serializable types.
+        #[derive(Debug)]
+        pub struct $name<T>(pub T);
+
+        impl<T: Serialize + Send + 'static> HandlerResponseOutput for $name<T> {
+            fn into_handler_response(self) -> Result<HandlerResponse, serde_json::Error> {
+                let body = serde_json::to_value(self.0)?;
+                // `Result::Ok`: the `Ok` wrapper above shadows the prelude variant here.
+                Result::Ok(HandlerResponse::json($status, body))
+            }
+        }
+    };
+}
+
+status_wrapper!(
+    /// **200 OK** with a JSON body
+    Ok,
+    200
+);
+status_wrapper!(
+    /// **201 Created** with a JSON body
+    ///
+    /// When the operation's `201` declares a `Location` header, the service sets it from the
+    /// body's id (see [`crate::spec::RouteMeta::created_location`]); the generated handler
+    /// module's `location` helper builds it by hand.
+    Created,
+    201
+);
+status_wrapper!(
+    /// **202 Accepted** with a JSON body
+    Accepted,
+    202
+);
+
+/// **204 No Content**; the response has no body
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoContent;
+
+impl HandlerResponseOutput for NoContent {
+    fn into_handler_response(self) -> Result<HandlerResponse, serde_json::Error> {
+        Result::Ok(HandlerResponse::new(204, HeaderVec::new(), Value::Null))
+    }
+}
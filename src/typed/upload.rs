@@ -0,0 +1,181 @@
+//! Files uploaded in a `multipart/form-data` body.
+//!
+//! By the time a handler runs, every part has passed its field's size and content-type
+//! limits (see [`crate::spec::UploadLimit`]). [`TypedHandlerRequest::uploaded_file`] turns a
+//! part into an [`UploadedFile`]: kept in memory up to [`DEFAULT_UPLOAD_SPILL_BYTES`], written
+//! to a temporary file above it. The temporary file is removed when the `UploadedFile` is
+//! dropped, unless the handler takes it with [`UploadedFile::into_path`].
+//!
+//! Spilling does not lower memory use: the whole request body has already been read into
+//! memory by then and stays there until the request completes. It gives large files a path
+//! for tools that want one. Temporary files are created in [`std::env::temp_dir`] with a
+//! random name, never over an existing file, readable only by the process owner (`0o600` on
+//! Unix) and without `fsync`: they are scratch copies of data the request still holds.
+
+use bytes::Bytes;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::core::TypedHandlerRequest;
+use crate::server::multipart::{Multipart, MultipartPart};
+
+/// Largest upload [`TypedHandlerRequest::uploaded_file`] keeps only in memory (1 MiB)
+pub const DEFAULT_UPLOAD_SPILL_BYTES: usize = 1024 * 1024;
+
+/// A temporary file removed on drop unless kept
+#[derive(Debug)]
+struct SpilledFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl SpilledFile {
+    fn write(data: &[u8]) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("brrtrouter-upload-{}", ulid::Ulid::new()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path)?;
+        let spilled = Self { path, keep: false };
+        file.write_all(data)?;
+        Ok(spilled)
+    }
+}
+
+impl Drop for SpilledFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Storage {
+    Memory(Bytes),
+    Spilled(SpilledFile),
+}
+
+/// One uploaded file: its name, content type and content
+#[derive(Debug)]
+pub struct UploadedFile {
+    field: String,
+    filename: Option<String>,
+    content_type: String,
+    len: u64,
+    storage: Storage,
+}
+
+impl UploadedFile {
+    /// The file in `part`, also written to a temporary file when larger than
+    /// `spill_threshold` bytes (see the [module docs](self))
+    ///
+    /// # Errors
+    ///
+    /// The temporary file could not be written.
+    pub fn from_part(part: &MultipartPart, spill_threshold: usize) -> io::Result<Self> {
+        let storage = if part.data.len() > spill_threshold {
+            Storage::Spilled(SpilledFile::write(&part.data)?)
+        } else {
+            Storage::Memory(part.data.clone())
+        };
+        Ok(Self {
+            field: part.name.clone(),
+            filename: part.filename.clone(),
+            content_type: part.content_type_or_default().to_string(),
+            len: part.data.len() as u64,
+            storage,
+        })
+    }
+
+    /// Form field the file was sent in
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// File name the client gave; not a safe path, sanitize before using it as one
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The part's content type (`text/plain` when it sent none)
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Size in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Temporary file holding the content, when it was spilled to disk
+    pub fn path(&self) -> Option<&Path> {
+        match &self.storage {
+            Storage::Memory(_) => None,
+            Storage::Spilled(file) => Some(&file.path),
+        }
+    }
+
+    /// Read the content from the start
+    ///
+    /// # Errors
+    ///
+    /// A spilled file could not be opened.
+    pub fn reader(&self) -> io::Result<Box<dyn Read + Send + '_>> {
+        Ok(match &self.storage {
+            Storage::Memory(data) => Box::new(Cursor::new(&data[..])),
+            Storage::Spilled(file) => Box::new(File::open(&file.path)?),
+        })
+    }
+
+    /// Take ownership of the content as a file: the caller moves or removes it
+    ///
+    /// A file still in memory is written to a temporary file first.
+    ///
+    /// # Errors
+    ///
+    /// The temporary file could not be written.
+    pub fn into_path(self) -> io::Result<PathBuf> {
+        let mut file = match self.storage {
+            Storage::Memory(data) => SpilledFile::write(&data)?,
+            Storage::Spilled(file) => file,
+        };
+        file.keep = true;
+        Ok(file.path.clone())
+    }
+}
+
+impl<T> TypedHandlerRequest<T> {
+    /// The file uploaded in the multipart field `field` (the first, when repeated), spilling
+    /// to disk above [`DEFAULT_UPLOAD_SPILL_BYTES`]
+    ///
+    /// `None` when the request is not `multipart/form-data` or has no such field.
+    pub fn uploaded_file(&self, field: &str) -> Option<io::Result<UploadedFile>> {
+        let part = self.extensions.get::<Multipart>()?.part(field)?;
+        Some(UploadedFile::from_part(part, DEFAULT_UPLOAD_SPILL_BYTES))
+    }
+
+    /// Every file uploaded in the multipart field `field`, in the order sent
+    ///
+    /// # Errors
+    ///
+    /// A temporary file could not be written.
+    pub fn uploaded_files(&self, field: &str) -> io::Result<Vec<UploadedFile>> {
+        self.extensions
+            .get::<Multipart>()
+            .map(|multipart| {
+                multipart
+                    .parts_named(field)
+                    .map(|part| UploadedFile::from_part(part, DEFAULT_UPLOAD_SPILL_BYTES))
+                    .collect()
+            })
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+}
@@ -42,13 +42,32 @@
 //! # fn some_condition_fails() -> bool { false }
 //! ```
 
+/// How serious a [`ValidationIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The spec cannot be served or generated as is
+    Error,
+    /// Likely a mistake, but the spec still works
+    Warning,
+}
+
+impl std::fmt::Display for IssueSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IssueSeverity::Error => write!(f, "error"),
+            IssueSeverity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
 /// Represents a validation issue found in an OpenAPI specification.
 ///
 /// Each issue has:
 /// - `location` - Where in the spec the issue was found (e.g., "/paths/pets/{id}")
 /// - `kind` - The type of issue (e.g., "missing_handler", "invalid_type")
 /// - `message` - A human-readable description of the problem
-#[derive(Debug)]
+/// - `severity` - Whether it is an error or only a warning
+#[derive(Debug, Clone)]
 pub struct ValidationIssue {
     /// Where the issue occurred (e.g., "path:/users/{id}", "query:limit")
     pub location: String,
@@ -56,6 +75,8 @@ pub struct ValidationIssue {
     pub kind: String,
     /// Human-readable description of what went wrong
     pub message: String,
+    /// Error or warning
+    pub severity: IssueSeverity,
 }
 
 impl ValidationIssue {
@@ -75,8 +96,26 @@ impl ValidationIssue {
             location: location.into(),
             kind: kind.into(),
             message: message.into(),
+            severity: IssueSeverity::Error,
+        }
+    }
+
+    /// Create a validation issue with [`IssueSeverity::Warning`]
+    pub fn warning(
+        location: impl Into<String>,
+        kind: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        ValidationIssue {
+            severity: IssueSeverity::Warning,
+            ..Self::new(location, kind, message)
         }
     }
+
+    /// Whether the issue is an error (as opposed to a warning)
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
 }
 
 /// Print validation issues to stderr in a formatted, user-friendly way
@@ -95,6 +134,31 @@ pub fn print_issues(issues: &[ValidationIssue]) {
     eprintln!("\nPlease fix the issues in your OpenAPI spec before starting the server.\n");
 }
 
+/// Print the report of `brrtrouter-gen validate`: one line per issue with its severity and
+/// JSON-pointer location, followed by a summary
+///
+/// # Arguments
+///
+/// * `issues` - List of validation issues to display
+pub fn print_validation_report(issues: &[ValidationIssue]) {
+    if issues.is_empty() {
+        println!("✅ OpenAPI spec is valid");
+        return;
+    }
+    for issue in issues {
+        eprintln!(
+            "{} [{}] {}: {}",
+            issue.severity, issue.kind, issue.location, issue.message
+        );
+    }
+    let errors = issues.iter().filter(|i| i.is_error()).count();
+    eprintln!(
+        "\n{} error(s), {} warning(s)",
+        errors,
+        issues.len() - errors
+    );
+}
+
 /// Exit the process with error code 1 if there are any validation issues
 ///
 /// Prints all validation issues to stderr before exiting. Used by CLI commands
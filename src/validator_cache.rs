@@ -71,7 +71,12 @@
 //!
 //! ```rust,ignore
 //! // At service startup
-//! let service = AppService::new(router, dispatcher, schemes, spec_path, None, None);
+//! let service = AppService::builder()
+//!     .shared_router(router.clone())
+//!     .shared_dispatcher(dispatcher.clone())
+//!     .security_schemes(schemes)
+//!     .spec_path(&spec_path)
+//!     .build()?;
 //! let compiled_count = service.precompile_schemas(&routes);
 //! println!("Pre-compiled {} schemas", compiled_count);
 //!
@@ -84,12 +89,20 @@
 //! });
 //! ```
 
+use crate::lock_poison;
 use jsonschema::Validator;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use tracing::{debug, info};
+
+// Lock names reported in `brrtrouter_lock_poison_total`.
+const CACHE_LOCK: &str = "validator_cache.validators";
+const SPEC_VERSION_LOCK: &str = "validator_cache.spec_version";
+const DIGESTS_LOCK: &str = "validator_cache.schema_digests";
+const SUFFIXES_LOCK: &str = "validator_cache.stable_suffixes";
 /// Version identifier for an OpenAPI specification
 ///
 /// Combines a monotonic version counter with a content hash to uniquely identify
@@ -231,6 +244,8 @@ pub struct ValidatorCache {
     /// Format: "{handler_name}:{kind}[:{status}]:{digest}"
     /// The spec_version prefix is prepended on the hot path, avoiding re-formatting the stable parts.
     stable_suffixes: Arc<RwLock<HashMap<String, String>>>,
+    /// Number of schemas compiled so far, shared by clones (see [`Self::compilations`])
+    compilations: Arc<AtomicU64>,
 }
 
 impl ValidatorCache {
@@ -254,9 +269,26 @@ impl ValidatorCache {
             spec_version: Arc::new(RwLock::new(SpecVersion::default())),
             schema_digests: Arc::new(RwLock::new(HashMap::with_capacity(256))),
             stable_suffixes: Arc::new(RwLock::new(HashMap::with_capacity(256))),
+            compilations: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Compile `schema`, including any `pattern` regexes it contains.
+    fn compile(&self, schema: &Value) -> Result<Validator, jsonschema::ValidationError<'static>> {
+        self.compilations.fetch_add(1, Ordering::Relaxed);
+        jsonschema::validator_for(schema)
+    }
+
+    /// Number of schemas compiled since this cache was created, across all clones
+    ///
+    /// After [`Self::precompile_schemas`], serving requests for the same routes must not
+    /// change this: a compilation (and the regex compilation for `pattern` keywords that
+    /// comes with it) in the request path shows up here.
+    #[must_use]
+    pub fn compilations(&self) -> u64 {
+        self.compilations.load(Ordering::Relaxed)
+    }
+
     /// Stable digest of a JSON Schema value for cache keys (same schema → same digest).
     fn schema_digest(schema: &Value) -> String {
         let bytes = serde_json::to_vec(schema).unwrap_or_default();
@@ -271,7 +303,7 @@ impl ValidatorCache {
     ///
     /// * `spec_version` - Current spec version with hash
     /// * `handler_name` - Name of the handler function
-    /// * `kind` - Validation kind: "request" or "response"
+    /// * `kind` - Validation kind: "request", "parameter" or "response"
     /// * `status` - Optional HTTP status code (for response validators)
     /// * `schema` - JSON Schema used to compile the validator (distinguishes multiple
     ///   response media types for the same status)
@@ -334,7 +366,7 @@ impl ValidatorCache {
     /// # Arguments
     ///
     /// * `handler_name` - Name of the handler function
-    /// * `kind` - Validation kind: "request" or "response"
+    /// * `kind` - Validation kind: "request", "parameter" or "response"
     /// * `status` - Optional HTTP status code (for response validators)
     /// * `schema` - JSON Schema definition to compile (if not cached)
     ///
@@ -356,20 +388,16 @@ impl ValidatorCache {
     ) -> Option<Arc<Validator>> {
         // If cache is disabled, compile on-demand without caching
         if !self.enabled {
-            return jsonschema::validator_for(schema).map(Arc::new).ok();
+            return self.compile(schema).map(Arc::new).ok();
         }
 
-        let spec_version = self
-            .spec_version
-            .read()
-            .expect("spec version lock poisoned")
-            .clone();
+        let spec_version = lock_poison::read(&self.spec_version, SPEC_VERSION_LOCK).clone();
 
         // Fast path: look up pre-built stable suffix to avoid per-request format! allocations
         // Cache key format: "{spec_version}:{spec_hash}:{handler_name}:{kind}[:{status}]:{digest}"
         // The stable suffix (handler_name:kind[:status]:digest) is pre-built at startup.
         let key = {
-            let suffixes = self.stable_suffixes.read().expect("suffix lock poisoned");
+            let suffixes = lock_poison::read(&self.stable_suffixes, SUFFIXES_LOCK);
             let digest = Self::schema_digest(schema);
             let lookup = Self::digest_lookup_key(handler_name, kind, status, &digest);
             match suffixes.get(&lookup) {
@@ -400,7 +428,7 @@ impl ValidatorCache {
 
         // Fast path: Check if validator is already cached (read lock only)
         {
-            let cache = self.cache.read().expect("validator cache lock poisoned");
+            let cache = lock_poison::read(&self.cache, CACHE_LOCK);
             if let Some(validator) = cache.get(&key) {
                 debug!(
                     handler_name = handler_name,
@@ -416,10 +444,10 @@ impl ValidatorCache {
         }
 
         // Slow path: Compile and cache the validator (write lock required)
-        match jsonschema::validator_for(schema) {
+        match self.compile(schema) {
             Ok(compiled) => {
                 let validator = Arc::new(compiled);
-                let mut cache = self.cache.write().expect("validator cache lock poisoned");
+                let mut cache = lock_poison::write(&self.cache, CACHE_LOCK);
 
                 // Double-check pattern: Another thread might have compiled while we waited
                 if let Some(existing) = cache.get(&key) {
@@ -471,10 +499,7 @@ impl ValidatorCache {
     ///
     /// Number of validators currently cached
     pub fn size(&self) -> usize {
-        self.cache
-            .read()
-            .expect("validator cache lock poisoned")
-            .len()
+        lock_poison::read(&self.cache, CACHE_LOCK).len()
     }
 
     /// Clear all cached validators and increment spec version
@@ -484,11 +509,8 @@ impl ValidatorCache {
     /// Incrementing the spec version ensures that even if old keys somehow remain,
     /// they won't match new requests (defense in depth).
     pub fn clear(&self) {
-        let mut cache = self.cache.write().expect("validator cache lock poisoned");
-        let mut version = self
-            .spec_version
-            .write()
-            .expect("spec version lock poisoned");
+        let mut cache = lock_poison::write(&self.cache, CACHE_LOCK);
+        let mut version = lock_poison::write(&self.spec_version, SPEC_VERSION_LOCK);
 
         let old_version = version.clone();
         // Increment version and generate new placeholder hash
@@ -497,14 +519,8 @@ impl ValidatorCache {
         let new_version = version.clone();
 
         cache.clear();
-        self.schema_digests
-            .write()
-            .expect("digest lock poisoned")
-            .clear();
-        self.stable_suffixes
-            .write()
-            .expect("suffix lock poisoned")
-            .clear();
+        lock_poison::write(&self.schema_digests, DIGESTS_LOCK).clear();
+        lock_poison::write(&self.stable_suffixes, SUFFIXES_LOCK).clear();
         info!(
             old_version = old_version.version,
             old_hash = %old_version.hash,
@@ -523,11 +539,8 @@ impl ValidatorCache {
     ///
     /// * `spec_content` - Raw spec file content for hash computation
     pub fn update_spec_version(&self, spec_content: &[u8]) {
-        let mut cache = self.cache.write().expect("validator cache lock poisoned");
-        let mut version = self
-            .spec_version
-            .write()
-            .expect("spec version lock poisoned");
+        let mut cache = lock_poison::write(&self.cache, CACHE_LOCK);
+        let mut version = lock_poison::write(&self.spec_version, SPEC_VERSION_LOCK);
         let old_version = version.clone();
 
         // Increment version and compute content hash
@@ -567,16 +580,13 @@ impl ValidatorCache {
     ///
     /// Current spec version
     pub fn spec_version(&self) -> SpecVersion {
-        self.spec_version
-            .read()
-            .expect("spec version lock poisoned")
-            .clone()
+        lock_poison::read(&self.spec_version, SPEC_VERSION_LOCK).clone()
     }
 
     /// Pre-compile and cache all schemas from routes at startup
     ///
-    /// This method compiles all request and response schemas from the given routes
-    /// and stores them in the cache. This eliminates compilation overhead during
+    /// This method compiles all request, parameter and response schemas from the given
+    /// routes and stores them in the cache. This eliminates compilation overhead during
     /// the first requests and ensures all schemas are valid at startup.
     ///
     /// # Arguments
@@ -602,7 +612,7 @@ impl ValidatorCache {
         let mut local_digests: HashMap<String, String> = HashMap::with_capacity(routes.len() * 4);
         let mut local_suffixes: HashMap<String, String> = HashMap::with_capacity(routes.len() * 4);
         for route in routes {
-            if let Some(ref request_schema) = route.request_schema {
+            if let Some(request_schema) = route.request_validation_schema() {
                 let digest = Self::schema_digest(request_schema);
                 let lookup = Self::digest_lookup_key(&route.handler_name, "request", None, &digest);
                 local_digests.insert(lookup.clone(), digest.clone());
@@ -618,7 +628,30 @@ impl ValidatorCache {
                 }
             }
 
+            // Parameter schemas (`pattern`, `enum`, bounds) are validated per request too.
+            for schema in route.parameters.iter().filter_map(|p| p.schema.as_ref()) {
+                let digest = Self::schema_digest(schema);
+                let lookup =
+                    Self::digest_lookup_key(&route.handler_name, "parameter", None, &digest);
+                local_digests.insert(lookup.clone(), digest.clone());
+                local_suffixes.insert(
+                    lookup,
+                    format!("{}:{}:{}", route.handler_name, "parameter", digest),
+                );
+                if self
+                    .get_or_compile(&route.handler_name, "parameter", None, schema)
+                    .is_some()
+                {
+                    compiled_count += 1;
+                }
+            }
+
+            let overrides = &route.schema_overrides;
             for (status_code, content_types) in &route.responses {
+                // Overridden statuses are validated against the override only.
+                if overrides.response(*status_code).is_some() {
+                    continue;
+                }
                 for response_spec in content_types.values() {
                     if let Some(ref response_schema) = response_spec.schema {
                         let digest = Self::schema_digest(response_schema);
@@ -628,14 +661,12 @@ impl ValidatorCache {
                             Some(*status_code),
                             &digest,
                         );
-                        let lookup_key = lookup.clone();
-                        local_digests.insert(lookup_key, digest.clone());
-                        let kind = "response";
+                        local_digests.insert(lookup.clone(), digest.clone());
                         local_suffixes.insert(
                             lookup,
                             format!(
-                                "{}:{}:{}:{}:{}",
-                                route.handler_name, kind, status_code, digest, kind
+                                "{}:{}:{}:{}",
+                                route.handler_name, "response", status_code, digest
                             ),
                         );
                         if self
@@ -652,18 +683,55 @@ impl ValidatorCache {
                     }
                 }
             }
+
+            let mut override_statuses: Vec<u16> = overrides.responses.keys().copied().collect();
+            if overrides.success_response.is_some() {
+                override_statuses
+                    .extend(route.responses.keys().filter(|s| (200..300).contains(*s)));
+            }
+            for status_code in override_statuses {
+                let Some(response_schema) = overrides.response(status_code) else {
+                    continue;
+                };
+                let digest = Self::schema_digest(response_schema);
+                let lookup = Self::digest_lookup_key(
+                    &route.handler_name,
+                    "response",
+                    Some(status_code),
+                    &digest,
+                );
+                local_digests.insert(lookup.clone(), digest.clone());
+                local_suffixes.insert(
+                    lookup,
+                    format!(
+                        "{}:{}:{}:{}",
+                        route.handler_name, "response", status_code, digest
+                    ),
+                );
+                if self
+                    .get_or_compile(
+                        &route.handler_name,
+                        "response",
+                        Some(status_code),
+                        response_schema,
+                    )
+                    .is_some()
+                {
+                    compiled_count += 1;
+                }
+            }
         }
 
         // Now populate both maps in a single write — no lock held during get_or_compile.
         let digest_count = local_digests.len();
         let _suffix_count = local_suffixes.len();
         {
-            let mut digests = self.schema_digests.write().expect("digest lock poisoned");
+            let mut digests = lock_poison::write(&self.schema_digests, DIGESTS_LOCK);
             digests.clear();
             digests.extend(local_digests);
         }
         {
-            let mut suffixes = self.stable_suffixes.write().expect("suffix lock poisoned");
+            let mut suffixes = lock_poison::write(&self.stable_suffixes, SUFFIXES_LOCK);
             suffixes.clear();
             suffixes.extend(local_suffixes);
         }
@@ -862,6 +930,7 @@ mod tests {
                     }
                 })),
                 example: None,
+                examples: Vec::new(),
             },
         );
         responses.insert(200, response_content);
@@ -870,6 +939,19 @@ mod tests {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method: Method::POST,
             path_pattern: Arc::from("/test"),
             handler_name: Arc::from("test_handler"),
@@ -885,6 +967,7 @@ mod tests {
             request_content_types: vec!["application/json".to_string()],
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses,
             security: vec![],
             example_name: "test".to_string(),
@@ -894,6 +977,8 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
         };
 
@@ -953,6 +1038,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn precompiled_routes_never_compile_in_the_request_path() {
+        use crate::spec::{ParameterLocation, ParameterMeta, ResponseSpec, RouteMeta};
+        use http::Method;
+        use std::path::PathBuf;
+
+        let cache = ValidatorCache::new(true);
+        let param_schema = json!({"type": "string", "pattern": "^[a-z]{3}-[0-9]+$"});
+        let request_schema = json!({
+            "type": "object",
+            "properties": {"sku": {"type": "string", "pattern": "^[A-Z]+$"}}
+        });
+        let response_schema = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+        let mut responses = HashMap::new();
+        for status in [200, 404] {
+            responses.insert(
+                status,
+                HashMap::from([(
+                    "application/json".to_string(),
+                    ResponseSpec {
+                        schema: Some(response_schema.clone()),
+                        example: None,
+                        examples: Vec::new(),
+                    },
+                )]),
+            );
+        }
+        let route = RouteMeta {
+            x_service: None,
+            x_brrtrouter_downstream_path: None,
+            x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
+            method: Method::PUT,
+            path_pattern: Arc::from("/items/{code}"),
+            handler_name: Arc::from("put_item"),
+            parameters: vec![ParameterMeta {
+                name: "code".to_string(),
+                location: ParameterLocation::Path,
+                required: true,
+                schema: Some(param_schema.clone()),
+                style: None,
+                explode: None,
+                deprecated: false,
+            }],
+            request_schema: Some(request_schema.clone()),
+            request_body_required: true,
+            request_content_types: vec!["application/json".to_string()],
+            response_schema: None,
+            example: None,
+            examples: Vec::new(),
+            responses,
+            security: vec![],
+            example_name: "test".to_string(),
+            project_slug: "test".to_string(),
+            output_dir: PathBuf::from("/tmp"),
+            base_path: "".to_string(),
+            sse: false,
+            estimated_request_body_bytes: None,
+            x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
+            cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
+        };
+
+        assert_eq!(cache.precompile_schemas(&[route]), 4);
+        let compiled_at_startup = cache.compilations();
+
+        // The lookups the service makes while handling requests
+        let handler = cache.clone();
+        assert!(handler
+            .get_or_compile("put_item", "parameter", None, &param_schema)
+            .is_some());
+        assert!(handler
+            .get_or_compile("put_item", "request", None, &request_schema)
+            .is_some());
+        for status in [200, 404] {
+            assert!(handler
+                .get_or_compile("put_item", "response", Some(status), &response_schema)
+                .is_some());
+        }
+        assert_eq!(cache.compilations(), compiled_at_startup);
+    }
+
     #[test]
     fn test_precompile_schemas_disabled_cache() {
         use crate::spec::RouteMeta;
@@ -970,6 +1150,7 @@ mod tests {
             crate::spec::ResponseSpec {
                 schema: Some(json!({"type": "object"})),
                 example: None,
+                examples: Vec::new(),
             },
         );
         responses.insert(200, response_content);
@@ -978,6 +1159,19 @@ mod tests {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method: Method::POST,
             path_pattern: Arc::from("/test"),
             handler_name: Arc::from("test_handler"),
@@ -987,6 +1181,7 @@ mod tests {
             request_content_types: vec!["application/json".to_string()],
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses,
             security: vec![],
             example_name: "test".to_string(),
@@ -996,6 +1191,8 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
         };
 
@@ -1031,6 +1228,7 @@ mod tests {
                     json!({"type": "object", "properties": {"success": {"type": "boolean"}}}),
                 ),
                 example: None,
+                examples: Vec::new(),
             },
         );
         responses.insert(200, response_200);
@@ -1043,6 +1241,7 @@ mod tests {
                     json!({"type": "object", "properties": {"error": {"type": "string"}}}),
                 ),
                 example: None,
+                examples: Vec::new(),
             },
         );
         responses.insert(400, response_400);
@@ -1055,6 +1254,7 @@ mod tests {
                     json!({"type": "object", "properties": {"message": {"type": "string"}}}),
                 ),
                 example: None,
+                examples: Vec::new(),
             },
         );
         responses.insert(500, response_500);
@@ -1063,6 +1263,19 @@ mod tests {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method: Method::POST,
             path_pattern: Arc::from("/multi"),
             handler_name: Arc::from("multi_handler"),
@@ -1072,6 +1285,7 @@ mod tests {
             request_content_types: vec!["application/json".to_string()],
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses,
             security: vec![],
             example_name: "multi".to_string(),
@@ -1081,6 +1295,8 @@ mod tests {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: crate::middleware::RouteCorsPolicy::Inherit,
         };
 
@@ -24,6 +24,7 @@
 //! - `BRRTR_BACKPRESSURE_TIMEOUT_MS`: Maximum time `Block` mode will wait for a
 //!   slot before shedding (default: 50 ms).
 
+use crate::alloc_profile::{self, Phase};
 use crate::dispatcher::{HandlerRequest, HandlerResponse};
 use may::sync::mpmc;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
@@ -284,8 +285,10 @@ impl WorkerPool {
                     // Note: All workers share the same receiver, so they will
                     // automatically load balance across incoming requests
                     while let Ok(req) = rx_clone.recv() {
+                        let _dispatch_phase = alloc_profile::phase(Phase::Dispatch);
                         let request_id = req.request_id;
                         let handler_name = req.handler_name.clone();
+                        let reply_tx = req.reply_tx.clone();
 
                         debug!(
                             request_id = %request_id,
@@ -308,6 +311,8 @@ impl WorkerPool {
                                 panic_message = ?panic,
                                 "Handler panicked - CRITICAL"
                             );
+                            let _ = reply_tx
+                                .send(HandlerResponse::handler_panic(&format!("{panic:?}")));
                         }
 
                         // Record completion
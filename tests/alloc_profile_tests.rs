@@ -0,0 +1,108 @@
+#![cfg(feature = "alloc-profiling")]
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Allocation profiling (`--features alloc-profiling`): a request served over HTTP records
+//! allocation counts for each phase, and `/metrics` exports them per phase.
+
+mod common;
+
+use brrtrouter::alloc_profile::{self, Phase};
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::MetricsMiddleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer};
+use common::http::send_request;
+use serde_json::json;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Profiled
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      parameters:
+        - name: limit
+          in: query
+          schema: { type: integer }
+      responses:
+        "200": { description: OK }
+"#;
+
+#[test]
+fn sample_request_produces_counts_per_phase() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", |req: HandlerRequest| {
+            let pets: Vec<_> = (0..10)
+                .map(|id| json!({ "id": id, "name": "Rex" }))
+                .collect();
+            let _ = req.reply_tx.send(HandlerResponse::json(200, json!(pets)));
+        });
+    }
+    let metrics = Arc::new(MetricsMiddleware::new());
+    dispatcher.add_middleware(metrics.clone());
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_metrics_middleware(metrics);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+
+    alloc_profile::reset();
+    let resp = send_request(
+        &addr,
+        "GET /pets?limit=10 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+
+    // The handler coroutine finishes its phase just after replying.
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let stats = loop {
+        let stats = alloc_profile::snapshot();
+        if stats.iter().all(|(_, s)| s.samples > 0) || Instant::now() > deadline {
+            break stats;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    for (phase, s) in &stats {
+        assert!(s.samples > 0, "{phase:?} not measured: {stats:?}");
+        if *phase != Phase::Validate {
+            assert!(s.allocations > 0, "{phase:?} counted nothing: {stats:?}");
+            assert!(s.bytes > 0, "{phase:?} counted no bytes: {stats:?}");
+        }
+    }
+
+    let resp = send_request(&addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    for phase in Phase::ALL {
+        for series in [
+            "brrtrouter_alloc_phase_samples_total",
+            "brrtrouter_alloc_phase_allocations_total",
+            "brrtrouter_alloc_phase_bytes_total",
+        ] {
+            let line = format!("{series}{{phase=\"{}\"}} ", phase.as_str());
+            assert!(resp.contains(&line), "missing {line}: {resp}");
+        }
+    }
+    handle.stop();
+}
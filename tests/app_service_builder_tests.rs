@@ -0,0 +1,164 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `AppService::builder()`: a service from named settings. Middleware join the dispatcher,
+//! config.yaml sections apply, `base_path` overrides the config's, and `build` names the
+//! first missing required setting. The deprecated `AppService::new` keeps working.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::Middleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppConfig, AppService, AppServiceBuildError, TestClient};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: create_pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "200": { description: OK }
+"#;
+
+/// Tags every response with `x-tagged: yes`
+struct Tag;
+
+impl Middleware for Tag {
+    fn after(&self, _req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        res.set_header("x-tagged", "yes".to_string());
+    }
+}
+
+fn dispatcher() -> Dispatcher {
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("create_pet", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "ok": true })));
+        });
+    }
+    dispatcher
+}
+
+#[test]
+fn builder_applies_middleware_config_and_base_path() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let config: AppConfig =
+        serde_yaml::from_str("http:\n  base_path: /from-config\n  max_json_depth: 2\n").unwrap();
+
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher())
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .middleware(Arc::new(Tag))
+        .config(&config)
+        .base_path("/svc")
+        .build()
+        .unwrap();
+    assert_eq!(service.max_json_depth, 2);
+    let client = TestClient::new(service);
+
+    client
+        .post("/svc/pets")
+        .json(json!({ "a": {} }))
+        .send()
+        .assert_status(200)
+        .assert_header("x-tagged", "yes");
+    // `http.max_json_depth` from the config
+    client
+        .post("/svc/pets")
+        .json(json!({ "a": { "b": {} } }))
+        .send()
+        .assert_status(400);
+    // The builder's base path wins over the config's.
+    client
+        .post("/from-config/pets")
+        .json(json!({}))
+        .send()
+        .assert_status(404);
+}
+
+#[test]
+fn build_names_the_missing_setting() {
+    assert_eq!(
+        AppService::builder().build().err(),
+        Some(AppServiceBuildError::Missing("router"))
+    );
+    assert_eq!(
+        AppService::builder()
+            .router(Router::new(Vec::new()))
+            .build()
+            .err(),
+        Some(AppServiceBuildError::Missing("dispatcher"))
+    );
+    let err = AppService::builder()
+        .router(Router::new(Vec::new()))
+        .dispatcher(Dispatcher::new())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err, AppServiceBuildError::Missing("spec_path"));
+    assert_eq!(
+        err.to_string(),
+        "AppServiceBuilder: `spec_path` is required"
+    );
+}
+
+#[test]
+fn build_rejects_an_unloadable_error_template() {
+    let config: AppConfig =
+        serde_yaml::from_str("errors:\n  html_template: /nonexistent/error.html\n").unwrap();
+    let err = AppService::builder()
+        .router(Router::new(Vec::new()))
+        .dispatcher(Dispatcher::new())
+        .spec_path("openapi.yaml")
+        .config(&config)
+        .build()
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, AppServiceBuildError::InvalidErrorsConfig(_)),
+        "{err}"
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_new_still_serves_like_the_builder() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let service = AppService::new(
+        Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes))),
+        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher())),
+        schemes,
+        spec_path,
+        None,
+        None,
+    );
+    TestClient::new(service)
+        .post("/pets")
+        .json(json!({}))
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "ok": true }));
+}
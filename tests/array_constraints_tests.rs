@@ -0,0 +1,162 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Array constraints: `maxItems`, `minItems` and `uniqueItems` on request bodies and query
+//! parameters are enforced, and the problem response names the field and the keyword.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Bulk
+  version: "1.0"
+paths:
+  /bulk:
+    post:
+      operationId: bulk_upsert
+      parameters:
+        - name: ids
+          in: query
+          style: form
+          explode: false
+          schema:
+            type: array
+            maxItems: 3
+            items: { type: integer }
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              properties:
+                tags:
+                  type: array
+                  maxItems: 3
+                  items: { type: string }
+                items:
+                  type: array
+                  minItems: 1
+                  uniqueItems: true
+                  items:
+                    type: object
+                    properties:
+                      id: { type: integer }
+                      name: { type: string }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("bulk_upsert", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "ok": true })));
+        });
+    }
+    let client = TestClient::new(
+        AppService::builder()
+            .router(Router::new(routes))
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from(&spec_path))
+            .build()
+            .unwrap(),
+    );
+    (client, dir)
+}
+
+#[test]
+fn over_max_items_names_field_and_keyword() {
+    let (client, _dir) = client();
+    let tags: Vec<String> = (0..1000).map(|i| format!("tag-{i}")).collect();
+    let resp = client
+        .post("/bulk")
+        .json(json!({ "tags": tags, "items": [{ "id": 1 }] }))
+        .send();
+    resp.assert_status(400)
+        .assert_json_at("/errors/0/pointer", json!("#/tags"))
+        .assert_json_at("/errors/0/keyword", json!("maxItems"))
+        .assert_json_at(
+            "/errors/0/detail",
+            json!("'tags' has more than 3 items (maxItems: 3)"),
+        );
+    // The detail does not echo the oversized array back.
+    assert!(resp.body().to_string().len() < 1000, "{}", resp.body());
+
+    client
+        .post("/bulk")
+        .json(json!({ "tags": ["a", "b", "c"], "items": [{ "id": 1 }] }))
+        .send()
+        .assert_status(200);
+}
+
+#[test]
+fn duplicate_objects_violate_unique_items() {
+    let (client, _dir) = client();
+    // Equal as JSON values even though their keys are written in a different order.
+    client
+        .post("/bulk")
+        .json(json!({ "items": [
+            { "id": 1, "name": "a" },
+            { "id": 2, "name": "b" },
+            { "name": "a", "id": 1 },
+        ] }))
+        .send()
+        .assert_status(400)
+        .assert_json_at("/errors/0/pointer", json!("#/items"))
+        .assert_json_at("/errors/0/keyword", json!("uniqueItems"))
+        .assert_json_at(
+            "/errors/0/detail",
+            json!("'items' has duplicate items (uniqueItems)"),
+        );
+
+    client
+        .post("/bulk")
+        .json(json!({ "items": [{ "id": 1, "name": "a" }, { "id": 1, "name": "b" }] }))
+        .send()
+        .assert_status(200);
+}
+
+#[test]
+fn empty_array_violates_min_items() {
+    let (client, _dir) = client();
+    client
+        .post("/bulk")
+        .json(json!({ "items": [] }))
+        .send()
+        .assert_status(400)
+        .assert_json_at("/errors/0/keyword", json!("minItems"));
+}
+
+#[test]
+fn query_array_over_max_items_is_rejected() {
+    let (client, _dir) = client();
+    client
+        .post("/bulk?ids=1,2,3,4")
+        .json(json!({ "items": [{ "id": 1 }] }))
+        .send()
+        .assert_status(400)
+        .assert_json_at("/parameter", json!("ids"))
+        .assert_json_at("/errors/0/keyword", json!("maxItems"))
+        .assert_json_at(
+            "/errors/0/detail",
+            json!("The value has more than 3 items (maxItems: 3)"),
+        );
+    client
+        .post("/bulk?ids=1,2,3")
+        .json(json!({ "items": [{ "id": 1 }] }))
+        .send()
+        .assert_status(200);
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `HandlerResponse::attachment`: file downloads carry `Content-Disposition: attachment` with
+//! an RFC 5987 encoded name when it is not plain ASCII, and `X-Content-Type-Options: nosniff`.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Files
+  version: "1.0"
+paths:
+  /download:
+    get:
+      operationId: download
+      parameters:
+        - { name: name, in: query, required: true, schema: { type: string } }
+      responses:
+        "200":
+          description: The file
+          content:
+            text/csv:
+              schema: { type: string }
+"#;
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("download", |req: HandlerRequest| {
+            let name = req.get_query_param("name").unwrap_or_default().to_string();
+            let _ = req.reply_tx.send(HandlerResponse::attachment(
+                &name,
+                "text/csv",
+                "id,name\n1,Cat\n",
+            ));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn attachment_sets_disposition_and_nosniff() {
+    let (client, _dir) = client();
+    let resp = client.get("/download?name=pets.csv").send();
+    resp.assert_status(200)
+        .assert_header("content-type", "text/csv")
+        .assert_header("content-disposition", r#"attachment; filename="pets.csv""#)
+        .assert_header("x-content-type-options", "nosniff");
+    assert_eq!(resp.body(), &serde_json::json!("id,name\n1,Cat\n"));
+}
+
+#[test]
+fn non_ascii_names_are_rfc5987_encoded() {
+    let (client, _dir) = client();
+    // `Übersicht 2024 (€).csv`
+    client
+        .get("/download?name=%C3%9Cbersicht%202024%20(%E2%82%AC).csv")
+        .send()
+        .assert_status(200)
+        .assert_header(
+            "content-disposition",
+            "attachment; filename=\"_bersicht 2024 (_).csv\"; \
+             filename*=UTF-8''%C3%9Cbersicht%202024%20%28%E2%82%AC%29.csv",
+        );
+    // Quotes and line breaks cannot break out of the header.
+    client
+        .get("/download?name=a%22b%0D%0AX-Evil%3A%201.txt")
+        .send()
+        .assert_header(
+            "content-disposition",
+            "attachment; filename=\"a_b__X-Evil: 1.txt\"; \
+             filename*=UTF-8''a%22b%0D%0AX-Evil%3A%201.txt",
+        );
+}
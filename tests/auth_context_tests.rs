@@ -0,0 +1,217 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! `HandlerRequest::auth_context` gives handlers the same identity view for every scheme:
+//! a configured API key yields its principal, a token provider yields `sub` and scopes, and
+//! unsecured or anonymous requests carry no context.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::app_config::AppConfig;
+use brrtrouter::server::security_setup::register_security_from_config;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use brrtrouter::spec::SecurityScheme;
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Identity
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKeyAuth:
+      type: apiKey
+      in: header
+      name: X-API-Key
+    TokenAuth:
+      type: http
+      scheme: bearer
+paths:
+  /whoami:
+    get:
+      operationId: whoami
+      security:
+        - ApiKeyAuth: []
+        - TokenAuth: []
+      responses:
+        "200": { description: OK }
+  /maybe:
+    get:
+      operationId: maybe
+      x-auth-optional: true
+      security:
+        - TokenAuth: []
+      responses:
+        "200": { description: OK }
+  /public:
+    get:
+      operationId: public
+      responses:
+        "200": { description: OK }
+"#;
+
+const CONFIG: &str = r#"
+security:
+  api_keys:
+    ApiKeyAuth:
+      key: secret-key
+      principal: billing-service
+"#;
+
+/// Token provider standing in for a JWT provider: `Bearer good` carries fixed claims.
+struct TokenProvider;
+
+impl SecurityProvider for TokenProvider {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.get_header("authorization") == Some("Bearer good")
+    }
+
+    fn extract_claims(&self, _scheme: &SecurityScheme, _req: &SecurityRequest) -> Option<Value> {
+        Some(json!({"sub": "user-42", "scope": "orders:read orders:write"}))
+    }
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(preresolve: bool) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let echo = |req: HandlerRequest| {
+        let body = match &req.auth_context {
+            Some(ctx) => json!({
+                "scheme": ctx.scheme,
+                "subject": ctx.subject,
+                "scopes": ctx.scopes,
+                "claims": ctx.claims,
+                "jwt_claims": req.jwt_claims,
+            }),
+            None => json!({"anonymous": true, "jwt_claims": req.jwt_claims}),
+        };
+        let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+    };
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("whoami", echo);
+        dispatcher.register_handler("maybe", echo);
+        dispatcher.register_handler("public", echo);
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    let config: AppConfig = serde_yaml::from_str(CONFIG).unwrap();
+    register_security_from_config(&mut service, &config, None).unwrap();
+    service.register_security_provider("TokenAuth", Arc::new(TokenProvider));
+    if preresolve {
+        service.resolve_security(&routes);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn get(server: &Server, path: &str, auth_header: &str) -> (u16, Value) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{auth_header}\r\n"),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let body = resp
+        .split_once("\r\n\r\n")
+        .and_then(|(_, b)| serde_json::from_str(b).ok())
+        .unwrap_or(Value::Null);
+    (status, body)
+}
+
+#[test]
+fn api_key_and_token_yield_the_same_context_shape() {
+    for preresolve in [false, true] {
+        let server = start(preresolve);
+
+        let (status, body) = get(&server, "/whoami", "X-API-Key: secret-key\r\n");
+        assert_eq!(status, 200, "{body}");
+        assert_eq!(body["scheme"], "ApiKeyAuth");
+        assert_eq!(body["subject"], "billing-service");
+        assert_eq!(body["scopes"], json!([]));
+        assert_eq!(body["jwt_claims"], json!({"sub": "billing-service"}));
+
+        let (status, body) = get(&server, "/whoami", "Authorization: Bearer good\r\n");
+        assert_eq!(status, 200, "{body}");
+        assert_eq!(body["scheme"], "TokenAuth");
+        assert_eq!(body["subject"], "user-42");
+        assert_eq!(body["scopes"], json!(["orders:read", "orders:write"]));
+        assert_eq!(body["claims"]["sub"], "user-42");
+    }
+}
+
+#[test]
+fn context_comes_from_the_scheme_that_validated() {
+    let server = start(true);
+    // A wrong API key alongside a valid token: identity is the token's, not the key's principal.
+    let (status, body) = get(
+        &server,
+        "/whoami",
+        "X-API-Key: wrong\r\nAuthorization: Bearer good\r\n",
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["scheme"], "TokenAuth");
+    assert_eq!(body["subject"], "user-42");
+}
+
+#[test]
+fn unsecured_and_anonymous_requests_have_no_context() {
+    let server = start(true);
+    let (status, body) = get(&server, "/public", "");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body, json!({"anonymous": true, "jwt_claims": null}));
+
+    let (status, body) = get(&server, "/maybe", "Authorization: Bearer bad\r\n");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["anonymous"], true);
+
+    let (status, body) = get(&server, "/whoami", "");
+    assert_eq!(status, 401, "{body}");
+}
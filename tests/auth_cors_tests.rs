@@ -33,6 +33,12 @@ fn test_auth_middleware_allows_valid_token() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
     assert!(mw.before(&req).is_none());
 }
@@ -54,10 +60,16 @@ fn test_auth_middleware_blocks_invalid_token() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
     let resp = mw.before(&req).expect("should produce response");
     assert_eq!(resp.status, 401);
-    assert_eq!(resp.body["error"], "Unauthorized");
+    assert_eq!(resp.body["detail"], "Unauthorized");
 }
 
 #[test]
@@ -79,6 +91,12 @@ fn test_cors_middleware_sets_headers() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
     let mut resp = HandlerResponse::new(200, HeaderVec::new(), serde_json::Value::Null);
     mw.after(&req, &mut resp, Duration::from_millis(0));
@@ -0,0 +1,278 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `SecurityProvider::check`: missing or invalid credentials are `Unauthenticated` (401),
+//! valid credentials lacking scopes are `InsufficientScope` (403) naming the missing scopes.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::router::{ParamVec, Router};
+use brrtrouter::security::{
+    AuthOutcome, BearerJwtProvider, JwksBearerProvider, OAuth2Provider, SecurityProvider,
+    SecurityRequest,
+};
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::SecurityScheme;
+use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn bearer_scheme() -> SecurityScheme {
+    SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: None,
+        description: None,
+    }
+}
+
+fn scopes(names: &[&str]) -> Vec<String> {
+    names.iter().map(|s| s.to_string()).collect()
+}
+
+/// Unsigned-style token accepted by `BearerJwtProvider::new("sig")`.
+fn make_token(scope: &str) -> String {
+    TestJwt::fixed_signature("sig").claim("scope", scope).sign()
+}
+
+fn authorization(token: &str) -> HeaderVec {
+    let mut headers = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    headers
+}
+
+fn check(
+    provider: &dyn SecurityProvider,
+    scheme: &SecurityScheme,
+    required: &[&str],
+    headers: &HeaderVec,
+) -> AuthOutcome {
+    let req = SecurityRequest {
+        headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    provider.check(scheme, &scopes(required), &req)
+}
+
+#[test]
+fn scope_claim_outcomes() {
+    assert_eq!(
+        AuthOutcome::from_scope_claim(&scopes(&["read"]), "read write"),
+        AuthOutcome::Granted
+    );
+    assert_eq!(
+        AuthOutcome::from_scope_claim(&scopes(&["admin", "read", "audit"]), "read"),
+        AuthOutcome::InsufficientScope {
+            missing: scopes(&["admin", "audit"])
+        }
+    );
+    assert_eq!(AuthOutcome::from(true), AuthOutcome::Granted);
+    assert_eq!(AuthOutcome::from(false), AuthOutcome::Unauthenticated);
+}
+
+#[test]
+fn bearer_jwt_outcomes() {
+    let provider = BearerJwtProvider::new("sig");
+    let scheme = bearer_scheme();
+    let token = authorization(&make_token("read"));
+
+    assert_eq!(
+        check(&provider, &scheme, &["read"], &token),
+        AuthOutcome::Granted
+    );
+    assert_eq!(
+        check(&provider, &scheme, &["read", "write"], &token),
+        AuthOutcome::InsufficientScope {
+            missing: scopes(&["write"])
+        }
+    );
+    assert_eq!(
+        check(&provider, &scheme, &["read"], &HeaderVec::new()),
+        AuthOutcome::Unauthenticated
+    );
+    let forged = make_token("read write").replace(".sig", ".forged");
+    assert_eq!(
+        check(&provider, &scheme, &["write"], &authorization(&forged)),
+        AuthOutcome::Unauthenticated
+    );
+    assert!(!provider.validate(
+        &scheme,
+        &scopes(&["write"]),
+        &SecurityRequest {
+            headers: &token,
+            query: &ParamVec::new(),
+            cookies: &HeaderVec::new(),
+        }
+    ));
+}
+
+#[test]
+fn oauth2_outcomes() {
+    let provider = OAuth2Provider::new("sig");
+    let scheme = SecurityScheme::OAuth2 {
+        flows: Default::default(),
+        description: None,
+    };
+    let token = authorization(&make_token("pets:read"));
+
+    assert_eq!(
+        check(&provider, &scheme, &["pets:read"], &token),
+        AuthOutcome::Granted
+    );
+    assert_eq!(
+        check(&provider, &scheme, &["pets:write"], &token),
+        AuthOutcome::InsufficientScope {
+            missing: scopes(&["pets:write"])
+        }
+    );
+    assert_eq!(
+        check(&provider, &bearer_scheme(), &["pets:read"], &token),
+        AuthOutcome::Unauthenticated
+    );
+}
+
+fn make_hs256_jwt(secret: &[u8], scope: &str) -> String {
+    TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer("https://issuer.example")
+        .audience("my-audience")
+        .claim("scope", scope)
+        .sign()
+}
+
+#[test]
+fn jwks_bearer_outcomes() {
+    let secret = b"supersecret";
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let provider = JwksBearerProvider::new(jwks_server.url())
+        .issuer("https://issuer.example")
+        .audience("my-audience");
+    let scheme = bearer_scheme();
+    let token = authorization(&make_hs256_jwt(secret, "read write"));
+
+    assert_eq!(
+        check(&provider, &scheme, &["read"], &token),
+        AuthOutcome::Granted
+    );
+    assert_eq!(
+        check(&provider, &scheme, &["read", "admin"], &token),
+        AuthOutcome::InsufficientScope {
+            missing: scopes(&["admin"])
+        }
+    );
+    let forged = authorization(&make_hs256_jwt(b"wrong", "read write admin"));
+    assert_eq!(
+        check(&provider, &scheme, &["admin"], &forged),
+        AuthOutcome::Unauthenticated
+    );
+    assert_eq!(
+        check(&provider, &scheme, &[], &HeaderVec::new()),
+        AuthOutcome::Unauthenticated
+    );
+}
+
+/// Accepts `Bearer <scope>`, a token carrying that single scope.
+struct SingleScopeProvider;
+
+impl SecurityProvider for SingleScopeProvider {
+    fn validate(&self, _scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool {
+        match req
+            .get_header("authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+        {
+            Some(granted) => scopes.iter().all(|s| s == granted),
+            None => false,
+        }
+    }
+}
+
+#[test]
+fn default_check_reports_every_scope_missing() {
+    let scheme = bearer_scheme();
+    let read = authorization("read");
+
+    assert_eq!(
+        check(&SingleScopeProvider, &scheme, &["read"], &read),
+        AuthOutcome::Granted
+    );
+    // `validate` only says yes or no, so none of the required scopes can be credited.
+    assert_eq!(
+        check(&SingleScopeProvider, &scheme, &["read", "write"], &read),
+        AuthOutcome::InsufficientScope {
+            missing: scopes(&["read", "write"])
+        }
+    );
+    assert_eq!(
+        check(&SingleScopeProvider, &scheme, &["read"], &HeaderVec::new()),
+        AuthOutcome::Unauthenticated
+    );
+}
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Scopes
+  version: "1.0"
+components:
+  securitySchemes:
+    BearerAuth:
+      type: http
+      scheme: bearer
+paths:
+  /pets:
+    delete:
+      operationId: delete_pets
+      security:
+        - BearerAuth: [pets:read, pets:admin]
+      responses:
+        "200": { description: OK }
+"#;
+
+#[test]
+fn problem_names_missing_scopes_only_for_403() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("delete_pets", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({"ok": true})));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.register_security_provider("BearerAuth", Arc::new(BearerJwtProvider::new("sig")));
+    service.resolve_security(&routes);
+    let client = TestClient::new(service);
+
+    let token = make_token("pets:read");
+    let response = client.delete("/pets").bearer(&token).send();
+    response
+        .assert_status(403)
+        .assert_json_at("/missing_scopes", json!(["pets:admin"]))
+        .assert_header(
+            "www-authenticate",
+            "Bearer error=\"insufficient_scope\", scope=\"pets:read pets:admin\"",
+        );
+    // Nothing from the token itself is echoed back.
+    assert!(!response.body().to_string().contains(&token));
+
+    let response = client.delete("/pets").send();
+    response.assert_status(401);
+    assert!(response.body().get("missing_scopes").is_none());
+
+    client
+        .delete("/pets")
+        .bearer(&make_token("pets:read pets:admin"))
+        .send()
+        .assert_status(200);
+}
@@ -0,0 +1,169 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Base path: requests under the prefix route as the spec's paths, anything else gets `404`,
+//! `/health` answers with and without the prefix unless configured otherwise, and relative
+//! `Location` / `Link` targets are prefixed.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, BasePath, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Mounted
+  version: "1.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /pets:
+    post:
+      operationId: create_pet
+      responses:
+        "201": { description: Created }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(base_path: Option<BasePath>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("get_pet", |req: HandlerRequest| {
+            let id = req.get_path_param("id").unwrap_or_default().to_string();
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "id": id })));
+        });
+        dispatcher.register_handler("create_pet", |req: HandlerRequest| {
+            let mut resp = HandlerResponse::json(201, json!({ "id": "1" }));
+            resp.set_header("Location", "/pets/1".to_string());
+            resp.set_header(
+                "Link",
+                r#"</pets/1>; rel="self", <https://docs.test/>; rel="help""#.to_string(),
+            );
+            let _ = req.reply_tx.send(resp);
+        });
+    }
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_base_path(base_path);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block, body) of `method path`
+fn request(server: &Server, method: &str, path: &str) -> (u16, String, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((resp.as_str(), ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, head.to_ascii_lowercase(), body.to_string())
+}
+
+fn status(server: &Server, method: &str, path: &str) -> u16 {
+    request(server, method, path).0
+}
+
+#[test]
+fn prefixed_paths_route_without_the_prefix() {
+    let server = start(BasePath::new("/service-a/"));
+    let (code, _, body) = request(&server, "GET", "/service-a/pets/7");
+    assert_eq!(code, 200, "{body}");
+    assert!(body.contains(r#""id":"7""#), "{body}");
+}
+
+#[test]
+fn paths_outside_the_prefix_are_not_found() {
+    let server = start(BasePath::new("/service-a"));
+    // Would route without a base path.
+    assert_eq!(status(&server, "GET", "/pets/7"), 404);
+    // Shares the prefix's characters but not its segment.
+    assert_eq!(status(&server, "GET", "/service-ab/pets/7"), 404);
+    // Under the prefix, but no such route.
+    assert_eq!(status(&server, "GET", "/service-a/owners/7"), 404);
+}
+
+#[test]
+fn health_answers_with_and_without_the_prefix() {
+    let server = start(BasePath::new("/service-a"));
+    assert_eq!(status(&server, "GET", "/health"), 200);
+    assert_eq!(status(&server, "GET", "/service-a/health"), 200);
+}
+
+#[test]
+fn unprefixed_health_can_be_turned_off() {
+    let server = start(BasePath::new("/service-a").map(|b| b.unprefixed_builtins(false)));
+    assert_eq!(status(&server, "GET", "/health"), 404);
+    assert_eq!(status(&server, "GET", "/service-a/health"), 200);
+}
+
+#[test]
+fn relative_location_and_link_targets_get_the_prefix() {
+    let server = start(BasePath::new("/service-a"));
+    let (code, head, _) = request(&server, "POST", "/service-a/pets");
+    assert_eq!(code, 201, "{head}");
+    assert!(head.contains("\r\nlocation: /service-a/pets/1"), "{head}");
+    assert!(
+        head.contains(r#"link: </service-a/pets/1>; rel="self", <https://docs.test/>; rel="help""#),
+        "{head}"
+    );
+}
+
+#[test]
+fn without_a_base_path_nothing_changes() {
+    let server = start(None);
+    assert_eq!(status(&server, "GET", "/pets/7"), 200);
+    assert_eq!(status(&server, "GET", "/service-a/pets/7"), 404);
+    let (_, head, _) = request(&server, "POST", "/pets");
+    assert!(head.contains("\r\nlocation: /pets/1"), "{head}");
+}
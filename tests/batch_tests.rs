@@ -0,0 +1,297 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Batch endpoint: sub-requests are routed, authenticated and validated one by one, results
+//! come back in order with mixed statuses, credentials are inherited unless overridden, and
+//! oversized or malformed batches are rejected.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::{AppService, BatchConfig, HttpServer, ServerHandle};
+use brrtrouter::spec::SecurityScheme;
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Batch
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+    post:
+      operationId: add_pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [name]
+              properties:
+                name: { type: string }
+      responses:
+        "201": { description: Created }
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: integer }
+      responses:
+        "200": { description: OK }
+  /admin:
+    get:
+      operationId: admin_stats
+      security:
+        - ApiKey: []
+      responses:
+        "200": { description: OK }
+  /slow:
+    get:
+      operationId: slow
+      responses:
+        "200": { description: OK }
+"#;
+
+const SLOW_MS: u64 = 300;
+
+struct HeaderKeyProvider;
+
+impl SecurityProvider for HeaderKeyProvider {
+    fn validate(&self, scheme: &SecurityScheme, _scopes: &[String], req: &SecurityRequest) -> bool {
+        match scheme {
+            SecurityScheme::ApiKey { name, .. } => {
+                req.get_header(&name.to_ascii_lowercase()) == Some("secret")
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(batch: Option<BatchConfig>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!([{ "name": "Rex" }])));
+        });
+        dispatcher.register_handler("add_pet", |req: HandlerRequest| {
+            let body = req.body.clone().unwrap_or_default();
+            let _ = req.reply_tx.send(HandlerResponse::json(201, body));
+        });
+        dispatcher.register_handler("get_pet", |req: HandlerRequest| {
+            let id = req.get_path_param("id").unwrap_or_default().to_string();
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "id": id })));
+        });
+        dispatcher.register_handler("admin_stats", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "admin": true })));
+        });
+        dispatcher.register_handler("slow", |req: HandlerRequest| {
+            may::coroutine::sleep(Duration::from_millis(SLOW_MS));
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "slow": true })));
+        });
+    }
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.register_security_provider("ApiKey", Arc::new(HeaderKeyProvider));
+    service.set_batch(batch);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn enabled(max_requests: usize) -> Option<BatchConfig> {
+    Some(BatchConfig {
+        enabled: true,
+        max_requests,
+        ..BatchConfig::default()
+    })
+}
+
+/// POST `batch` to `/batch`; returns (status, parsed body).
+fn post_batch(server: &Server, batch: &Value, extra_headers: &str) -> (u16, Value) {
+    let body = batch.to_string();
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST /batch HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\n{extra_headers}\r\n{body}",
+            body.len()
+        ),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+fn statuses(results: &Value) -> Vec<u64> {
+    results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["status"].as_u64().unwrap())
+        .collect()
+}
+
+#[test]
+fn mixed_success_and_failure_come_back_in_order() {
+    let server = start(enabled(10));
+    let (status, results) = post_batch(
+        &server,
+        &json!([
+            { "method": "GET", "path": "/pets" },
+            { "method": "POST", "path": "/pets", "body": { "name": "Tom" } },
+            { "method": "POST", "path": "/pets", "body": { "age": 3 } },
+            { "method": "get", "path": "/pets/7" },
+            { "method": "GET", "path": "/pets/seven" },
+            { "method": "GET", "path": "/nowhere" },
+            { "method": "DELETE", "path": "/pets" },
+            { "path": "/pets" },
+            { "method": "GET", "path": "/admin" },
+        ]),
+        "",
+    );
+    assert_eq!(status, 200, "{results}");
+    assert_eq!(
+        statuses(&results),
+        [200, 201, 400, 200, 400, 404, 405, 400, 401],
+        "{results}"
+    );
+    assert_eq!(results[0]["body"], json!([{ "name": "Rex" }]));
+    assert_eq!(results[1]["body"], json!({ "name": "Tom" }));
+    assert_eq!(results[3]["body"], json!({ "id": "7" }));
+    assert_eq!(
+        results[2]["headers"]["content-type"],
+        "application/problem+json"
+    );
+    assert_eq!(results[6]["headers"]["allow"], "GET, POST, HEAD, OPTIONS");
+    assert_eq!(results[7]["body"]["index"], 7);
+    assert!(results[1]["headers"]["x-request-id"].is_string());
+}
+
+#[test]
+fn sub_requests_inherit_credentials_unless_they_carry_their_own() {
+    let server = start(enabled(10));
+    let (status, results) = post_batch(
+        &server,
+        &json!([
+            { "method": "GET", "path": "/admin" },
+            { "method": "GET", "path": "/admin", "headers": { "X-API-Key": "wrong" } },
+        ]),
+        "X-API-Key: secret\r\n",
+    );
+    assert_eq!(status, 200, "{results}");
+    assert_eq!(statuses(&results), [200, 401], "{results}");
+    assert_eq!(results[0]["body"], json!({ "admin": true }));
+    assert!(results[1]["headers"]["www-authenticate"].is_string());
+}
+
+#[test]
+fn sub_requests_run_concurrently() {
+    let server = start(enabled(10));
+    let slow = json!({ "method": "GET", "path": "/slow" });
+    let started = Instant::now();
+    let (status, results) = post_batch(&server, &json!([slow, slow, slow, slow]), "");
+    let elapsed = started.elapsed();
+    assert_eq!(status, 200, "{results}");
+    assert_eq!(statuses(&results), [200, 200, 200, 200]);
+    assert!(
+        elapsed < Duration::from_millis(SLOW_MS * 3),
+        "4 sub-requests of {SLOW_MS}ms took {elapsed:?}"
+    );
+}
+
+#[test]
+fn oversized_malformed_and_nested_batches_are_rejected() {
+    let server = start(enabled(2));
+    let get = json!({ "method": "GET", "path": "/pets" });
+
+    let (status, problem) = post_batch(&server, &json!([get, get, get]), "");
+    assert_eq!(status, 413, "{problem}");
+    assert_eq!(problem["max_requests"], 2);
+
+    let (status, _) = post_batch(&server, &json!({ "method": "GET", "path": "/pets" }), "");
+    assert_eq!(status, 400);
+
+    let (status, results) = post_batch(
+        &server,
+        &json!([{ "method": "POST", "path": "/batch", "body": [get] }]),
+        "",
+    );
+    assert_eq!(status, 200);
+    assert_eq!(statuses(&results), [400], "{results}");
+
+    let (status, results) = post_batch(&server, &json!([]), "");
+    assert_eq!((status, results), (200, json!([])));
+}
+
+#[test]
+fn batch_endpoint_is_off_by_default() {
+    let server = start(None);
+    let (status, _) = post_batch(&server, &json!([{ "method": "GET", "path": "/pets" }]), "");
+    assert_eq!(status, 404);
+}
@@ -0,0 +1,156 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Declarative claim forwarding (`x-forward-claims` + the `forward_claims` middleware):
+//! spec extraction, per-route overrides of the config default, and the dispatcher handing
+//! claim-derived `downstream_headers` to the handler.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::load_spec_full;
+use brrtrouter::middleware::{ClaimsForwardingConfig, ClaimsForwardingMiddleware};
+use brrtrouter::router::Router;
+use brrtrouter::spec::RouteMeta;
+use common::temp_files::create_temp_yaml;
+use http::Method;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const SPEC: &str = r#"openapi: 3.1.0
+info:
+  title: BFF
+  version: "1.0"
+paths:
+  /orders:
+    get:
+      operationId: list_orders
+      responses:
+        "200": { description: OK }
+  /customers/me:
+    get:
+      operationId: get_customer
+      x-forward-claims:
+        sub: X-Customer-Id
+        tenant: X-Tenant
+      responses:
+        "200": { description: OK }
+  /feed:
+    get:
+      operationId: public_feed
+      x-forward-claims: false
+      responses:
+        "200": { description: OK }
+"#;
+
+fn routes() -> Vec<RouteMeta> {
+    let path = create_temp_yaml(SPEC);
+    let (routes, _schemes, _slug) = load_spec_full(path.to_str().unwrap()).unwrap();
+    routes
+}
+
+fn route<'a>(routes: &'a [RouteMeta], handler: &str) -> &'a RouteMeta {
+    routes
+        .iter()
+        .find(|r| r.handler_name.as_ref() == handler)
+        .unwrap()
+}
+
+#[test]
+fn x_forward_claims_is_extracted_per_route() {
+    let routes = routes();
+    assert_eq!(route(&routes, "list_orders").forward_claims, None);
+    let mut customer = route(&routes, "get_customer")
+        .forward_claims
+        .clone()
+        .unwrap();
+    customer.sort();
+    assert_eq!(
+        customer,
+        vec![
+            ("sub".to_string(), "X-Customer-Id".to_string()),
+            ("tenant".to_string(), "X-Tenant".to_string()),
+        ]
+    );
+    assert_eq!(
+        route(&routes, "public_feed").forward_claims,
+        Some(Vec::new())
+    );
+}
+
+/// Dispatch `path` with `claims` and an inbound spoofed `X-User-Id`; returns the
+/// `downstream_headers` the handler saw.
+fn dispatched_downstream_headers(path: &str, claims: Option<Value>) -> Value {
+    may::config().set_stack_size(0x8000);
+    let routes = routes();
+    let router = Router::new(routes.clone());
+    let mut dispatcher = Dispatcher::new();
+    let echo = |req: HandlerRequest| {
+        let headers: BTreeMap<String, String> = req
+            .downstream_headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        let spoofed = req.get_header("x-user-id").map(str::to_string);
+        let _ = req.reply_tx.send(HandlerResponse::json(
+            200,
+            json!({"downstream": headers, "inbound_x_user_id": spoofed}),
+        ));
+    };
+    unsafe {
+        dispatcher.register_handler("list_orders", echo);
+        dispatcher.register_handler("get_customer", echo);
+        dispatcher.register_handler("public_feed", echo);
+    }
+    let config = ClaimsForwardingConfig {
+        claims: BTreeMap::from([
+            ("sub".to_string(), "X-User-Id".to_string()),
+            ("org_id".to_string(), "X-Org-Id".to_string()),
+        ]),
+    };
+    dispatcher.add_middleware(Arc::new(ClaimsForwardingMiddleware::new(&config, &routes)));
+
+    let route_match = router.route(Method::GET, path).unwrap();
+    let mut headers = HeaderVec::new();
+    headers.push((Arc::from("x-user-id"), "spoofed".to_string()));
+    dispatcher
+        .dispatch_with_request_id(
+            route_match,
+            None,
+            headers,
+            HeaderVec::new(),
+            "req-1".to_string(),
+            claims,
+        )
+        .unwrap()
+        .body
+}
+
+#[test]
+fn default_mapping_applies_and_replaces_spoofed_header() {
+    let body = dispatched_downstream_headers("/orders", Some(json!({"sub": "u-1", "org_id": 7})));
+    assert_eq!(
+        body["downstream"],
+        json!({"X-Org-Id": "7", "X-User-Id": "u-1"})
+    );
+    assert_eq!(body["inbound_x_user_id"], Value::Null);
+}
+
+#[test]
+fn route_override_and_missing_claims() {
+    // `tenant` is absent from the token: its header is skipped, the rest still forwarded.
+    let body = dispatched_downstream_headers("/customers/me", Some(json!({"sub": "u-1"})));
+    assert_eq!(body["downstream"], json!({"X-Customer-Id": "u-1"}));
+
+    // Anonymous request: nothing forwarded, spoofed header still dropped.
+    let body = dispatched_downstream_headers("/orders", None);
+    assert_eq!(body["downstream"], json!({}));
+    assert_eq!(body["inbound_x_user_id"], Value::Null);
+}
+
+#[test]
+fn disabled_route_leaves_request_untouched() {
+    let body = dispatched_downstream_headers("/feed", Some(json!({"sub": "u-1"})));
+    assert_eq!(body["downstream"], json!({}));
+    assert_eq!(body["inbound_x_user_id"], "spoofed");
+}
@@ -0,0 +1,129 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Trusted-proxy client IP resolution (`HandlerRequest::client_ip`) against legitimate and
+//! spoofed `X-Forwarded-For` / `Forwarded` chains.
+
+use brrtrouter::dispatcher::{HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::ids::RequestId;
+use brrtrouter::router::ParamVec;
+use http::Method;
+use ipnet::IpNet;
+use may::sync::mpsc;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+fn request(peer: Option<&str>, headers: &[(&str, &str)]) -> HandlerRequest {
+    let (tx, _rx) = mpsc::channel::<HandlerResponse>();
+    let mut header_vec = HeaderVec::new();
+    for (k, v) in headers {
+        header_vec.push((Arc::from(*k), (*v).to_string()));
+    }
+    HandlerRequest {
+        request_id: RequestId::new(),
+        method: Method::GET,
+        path: "/items".into(),
+        handler_name: "list_items".into(),
+        path_params: ParamVec::new(),
+        query_params: ParamVec::new(),
+        headers: header_vec,
+        cookies: HeaderVec::new(),
+        body: None,
+        jwt_claims: None,
+        reply_tx: tx,
+        queue_guard: None,
+        peer_addr: peer.map(|p| p.parse().unwrap()),
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
+    }
+}
+
+fn ip(s: &str) -> Option<IpAddr> {
+    Some(s.parse().unwrap())
+}
+
+fn proxies() -> Vec<IpNet> {
+    vec!["10.0.0.0/8".parse().unwrap(), "fd00::/8".parse().unwrap()]
+}
+
+#[test]
+fn legitimate_chain_through_two_proxies() {
+    let req = request(
+        Some("10.0.0.1"),
+        &[("X-Forwarded-For", "203.0.113.7, 10.0.0.5")],
+    );
+    assert_eq!(req.client_ip(&proxies()), ip("203.0.113.7"));
+}
+
+#[test]
+fn spoofed_entries_left_of_the_client_are_ignored() {
+    // The client prepended a fake address; the edge proxy appended the real one.
+    let req = request(
+        Some("10.0.0.1"),
+        &[("x-forwarded-for", "1.2.3.4, 203.0.113.7")],
+    );
+    assert_eq!(req.client_ip(&proxies()), ip("203.0.113.7"));
+
+    // A client claiming to be a trusted proxy only shifts the walk one hop further left.
+    let req = request(
+        Some("10.0.0.1"),
+        &[("x-forwarded-for", "1.2.3.4, 10.9.9.9, 203.0.113.7")],
+    );
+    assert_eq!(req.client_ip(&proxies()), ip("203.0.113.7"));
+}
+
+#[test]
+fn headers_from_untrusted_peer_are_ignored() {
+    let req = request(
+        Some("198.51.100.20"),
+        &[("x-forwarded-for", "1.2.3.4"), ("forwarded", "for=1.2.3.4")],
+    );
+    assert_eq!(req.client_ip(&proxies()), ip("198.51.100.20"));
+    assert_eq!(req.client_ip(&[]), ip("198.51.100.20"));
+}
+
+#[test]
+fn forwarded_header_with_ipv6() {
+    let req = request(
+        Some("fd00::1"),
+        &[(
+            "Forwarded",
+            "for=\"[2001:db8::17]:4711\";proto=https, for=\"[fd00::2]\"",
+        )],
+    );
+    assert_eq!(req.client_ip(&proxies()), ip("2001:db8::17"));
+}
+
+#[test]
+fn falls_back_to_peer_or_nearest_hop() {
+    assert_eq!(
+        request(Some("10.0.0.1"), &[]).client_ip(&proxies()),
+        ip("10.0.0.1")
+    );
+    // Every hop trusted: the leftmost (furthest) address is the best available answer.
+    assert_eq!(
+        request(
+            Some("10.0.0.1"),
+            &[("x-forwarded-for", "10.1.1.1, 10.0.0.5")]
+        )
+        .client_ip(&proxies()),
+        ip("10.1.1.1")
+    );
+}
+
+#[test]
+fn spoofed_chain_from_unknown_peer_is_rejected() {
+    // Without a peer a client-written chain ending in a trusted address looks exactly like a
+    // legitimate one, so neither header is believed.
+    let req = request(
+        None,
+        &[
+            ("x-forwarded-for", "203.0.113.7, 10.0.0.5"),
+            ("forwarded", "for=203.0.113.7, for=10.0.0.5"),
+        ],
+    );
+    assert_eq!(req.client_ip(&proxies()), None);
+    assert_eq!(req.client_ip(&[]), None);
+}
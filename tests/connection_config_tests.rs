@@ -0,0 +1,175 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Per-connection keep-alive policy (`AppService::set_connection_config`).
+//!
+//! Requests are sent sequentially on a single TCP connection so the per-connection request
+//! counter (one `AppService` clone per accepted connection) is exercised end to end.
+
+use brrtrouter::dispatcher::Dispatcher;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, ConnectionConfig, HttpConfig, HttpServer, ServerHandle};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn service() -> AppService {
+    may::config().set_stack_size(0x8000);
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full("examples/openapi.yaml").unwrap();
+    let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes)));
+    let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(Dispatcher::new()));
+    AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .build()
+        .unwrap()
+}
+
+fn start_service(config: ConnectionConfig) -> (ServerHandle, SocketAddr) {
+    let mut service = service();
+    service.set_connection_config(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    (handle, addr)
+}
+
+/// Send `GET /health` on `stream` and return the response head (status line + headers).
+fn health_on(stream: &mut TcpStream) -> String {
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut buf = Vec::new();
+    let mut tmp = [0u8; 1024];
+    let header_end = loop {
+        let n = stream.read(&mut tmp).unwrap();
+        assert!(n > 0, "connection closed before response");
+        buf.extend_from_slice(&tmp[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let content_length: usize = head
+        .lines()
+        .filter_map(|l| l.split_once(':'))
+        .find(|(n, _)| n.eq_ignore_ascii_case("content-length"))
+        .map(|(_, v)| v.trim().parse().unwrap())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut tmp).unwrap();
+        assert!(n > 0, "connection closed mid-body");
+        buf.extend_from_slice(&tmp[..n]);
+    }
+    head.to_ascii_lowercase()
+}
+
+#[test]
+fn last_request_on_connection_sends_close() {
+    let (handle, addr) = start_service(ConnectionConfig {
+        keep_alive: true,
+        idle_timeout: Duration::from_secs(30),
+        max_requests_per_connection: Some(2),
+    });
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+
+    let first = health_on(&mut stream);
+    assert!(first.contains("connection: keep-alive"), "{first}");
+    assert!(first.contains("keep-alive: timeout=30, max=2"), "{first}");
+
+    let second = health_on(&mut stream);
+    assert!(second.contains("connection: close"), "{second}");
+    assert!(!second.contains("keep-alive: timeout"), "{second}");
+
+    // A new connection gets a fresh budget.
+    let mut fresh = TcpStream::connect(addr).unwrap();
+    fresh
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    assert!(health_on(&mut fresh).contains("connection: keep-alive"));
+
+    handle.stop();
+}
+
+#[test]
+fn interleaved_connections_keep_separate_budgets() {
+    let (handle, addr) = start_service(ConnectionConfig {
+        keep_alive: true,
+        idle_timeout: Duration::from_secs(30),
+        max_requests_per_connection: Some(2),
+    });
+    let connect = || {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        stream
+    };
+    let (mut a, mut b) = (connect(), connect());
+
+    // Each connection is served by its own service clone, so `b`'s first request does not
+    // spend `a`'s budget.
+    assert!(health_on(&mut a).contains("connection: keep-alive"));
+    assert!(health_on(&mut b).contains("connection: keep-alive"));
+    assert!(health_on(&mut a).contains("connection: close"));
+    assert!(health_on(&mut b).contains("connection: close"));
+
+    handle.stop();
+}
+
+#[test]
+fn zero_max_requests_means_unlimited() {
+    let mut service = service();
+    service.set_keep_alive(true, 30, 0);
+    assert_eq!(service.connection.max_requests_per_connection, None);
+    assert_eq!(
+        service.keep_alive_header.as_deref(),
+        Some("Keep-Alive: timeout=30")
+    );
+}
+
+#[test]
+fn connections_are_unlimited_unless_configured() {
+    assert_eq!(
+        ConnectionConfig::default().max_requests_per_connection,
+        None
+    );
+    assert_eq!(service().connection.max_requests_per_connection, None);
+    let http = HttpConfig {
+        timeout_secs: Some(30),
+        ..HttpConfig::default()
+    };
+    assert_eq!(http.connection_config().max_requests_per_connection, None);
+    let limited = HttpConfig {
+        max_requests: Some(7),
+        ..http
+    };
+    assert_eq!(
+        limited.connection_config().max_requests_per_connection,
+        Some(7)
+    );
+}
+
+#[test]
+fn disabled_keep_alive_sends_no_connection_headers() {
+    let (handle, addr) = start_service(ConnectionConfig {
+        keep_alive: false,
+        ..ConnectionConfig::default()
+    });
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    let head = health_on(&mut stream);
+    assert!(!head.contains("keep-alive"), "{head}");
+    handle.stop();
+}
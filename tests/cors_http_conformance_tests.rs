@@ -65,14 +65,15 @@ impl CorsHttpFixture {
         unsafe {
             registry::register_from_spec(&mut dispatcher, &routes);
         }
-        let mut service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            schemes,
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("examples/pet_store/static_site")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let mut service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("examples/pet_store/static_site"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
         service.set_metrics_middleware(metrics);
 
         struct ApiKeyProvider {
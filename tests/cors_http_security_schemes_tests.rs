@@ -105,14 +105,13 @@ impl MinimalCorsFixture {
                 });
             });
         }
-        let mut service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            schemes,
-            spec_path,
-            None,
-            None,
-        );
+        let mut service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(spec_path)
+            .build()
+            .unwrap();
         service.set_metrics_middleware(metrics);
         register_auth(&mut service);
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
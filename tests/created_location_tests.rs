@@ -0,0 +1,174 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `Location` for created resources: an operation whose `201` declares a `Location` header is
+//! linked to the `GET` route one parameter below it, a `201` without the header gets it from
+//! the id in its body, and the location (URL-encoded) routes back to that `GET`.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::CreatedLocation;
+use http::Method;
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Created
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: create_pet
+      responses:
+        "201":
+          description: Created
+          headers:
+            Location:
+              schema: { type: string }
+  /pets/{petId}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: petId, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /owners/{ownerId}/pets:
+    post:
+      operationId: adopt_pet
+      parameters:
+        - { name: ownerId, in: path, required: true, schema: { type: string } }
+      responses:
+        "201":
+          description: Created
+          headers:
+            location:
+              schema: { type: string }
+  /owners/{ownerId}/pets/{petId}:
+    get:
+      operationId: get_owned_pet
+      parameters:
+        - { name: ownerId, in: path, required: true, schema: { type: string } }
+        - { name: petId, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /tags:
+    post:
+      operationId: create_tag
+      responses:
+        "201":
+          description: Created
+          headers:
+            Location:
+              schema: { type: string }
+"#;
+
+fn start() -> (TestClient, Router, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        // No `Location`: the service fills it in from `petId`.
+        dispatcher.register_handler("create_pet", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(
+                201,
+                json!({ "petId": "rex the/2nd" }),
+            ));
+        });
+        dispatcher.register_handler("adopt_pet", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(201, json!({ "id": 42 })));
+        });
+        dispatcher.register_handler("create_tag", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::created(
+                "/tags/urgent",
+                json!({ "id": "urgent" }),
+            ));
+        });
+        for name in ["get_pet", "get_owned_pet"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req.reply_tx.send(HandlerResponse::json(
+                    200,
+                    json!({ "petId": req.get_path_param("petId") }),
+                ));
+            });
+        }
+    }
+    let router = Router::new(routes.clone());
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (TestClient::new(service), router, dir)
+}
+
+#[test]
+fn creating_operations_are_linked_to_their_get_route() {
+    let (_client, router, _dir) = start();
+    let created = |handler: &str| {
+        router
+            .routes()
+            .find(|r| &*r.handler_name == handler)
+            .unwrap()
+            .created_location
+            .clone()
+    };
+    assert_eq!(
+        created("create_pet"),
+        Some(CreatedLocation::new("/pets/{petId}", "petId"))
+    );
+    assert_eq!(
+        created("adopt_pet"),
+        Some(CreatedLocation::new(
+            "/owners/{ownerId}/pets/{petId}",
+            "petId"
+        ))
+    );
+    // No `GET /tags/{id}` to point at.
+    assert_eq!(created("create_tag"), None);
+    assert_eq!(created("get_pet"), None);
+}
+
+#[test]
+fn location_is_filled_in_and_resolves_to_the_get_route() {
+    let (client, router, _dir) = start();
+    let resp = client.post("/pets").send();
+    resp.assert_status(201)
+        .assert_header("location", "/pets/rex%20the%2F2nd");
+
+    let location = resp.header("location").unwrap();
+    let route = router.route(Method::GET, location).unwrap();
+    assert_eq!(route.handler_name, "get_pet");
+    client.get(location).send().assert_status(200);
+}
+
+#[test]
+fn nested_location_keeps_the_parent_parameters() {
+    let (client, router, _dir) = start();
+    let resp = client.post("/owners/ann%20b/pets").send();
+    resp.assert_status(201)
+        .assert_header("location", "/owners/ann%20b/pets/42");
+    let route = router
+        .route(Method::GET, resp.header("location").unwrap())
+        .unwrap();
+    assert_eq!(route.handler_name, "get_owned_pet");
+}
+
+#[test]
+fn explicit_location_is_kept() {
+    let (client, _router, _dir) = start();
+    client
+        .post("/tags")
+        .send()
+        .assert_status(201)
+        .assert_header("location", "/tags/urgent");
+}
@@ -0,0 +1,129 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Request deadlines: `x-brrtrouter-timeout-ms` and the `Request-Timeout` header set
+//! `HandlerRequest::deadline`; when it passes the dispatcher answers 504 and a cooperative
+//! handler sees the token flip.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::router::Router;
+use brrtrouter::spec::build_routes;
+use may::sync::mpsc;
+use oas3::OpenApiV3Spec;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"openapi: 3.1.0
+info:
+  title: Deadlines
+  version: '1.0'
+paths:
+  /slow:
+    get:
+      operationId: slow
+      x-brrtrouter-timeout-ms: 50
+      responses:
+        '200': { description: OK }
+  /unbounded:
+    get:
+      operationId: unbounded
+      responses:
+        '200': { description: OK }
+"#;
+
+/// What the handler saw: whether it had a deadline, and whether it observed it expire.
+type Observed = (bool, bool);
+
+fn setup() -> (Router, Dispatcher, mpsc::Receiver<Observed>) {
+    may::config().set_stack_size(0x8000);
+    let spec: OpenApiV3Spec = serde_yaml::from_str(SPEC).unwrap();
+    let router = Router::new(build_routes(&spec, "deadlines").unwrap());
+
+    let (observed_tx, observed_rx) = mpsc::channel();
+    let mut dispatcher = Dispatcher::new();
+    for name in ["slow", "unbounded"] {
+        let observed_tx = observed_tx.clone();
+        unsafe {
+            dispatcher.register_handler(name, move |req: HandlerRequest| {
+                // Cooperative handler: does 200ms of "work" in slices, bailing out on expiry.
+                let mut expired = false;
+                for _ in 0..40 {
+                    if req.deadline.as_ref().is_some_and(|d| d.is_expired()) {
+                        expired = true;
+                        break;
+                    }
+                    may::coroutine::sleep(Duration::from_millis(5));
+                }
+                let _ = observed_tx.send((req.deadline.is_some(), expired));
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({"done": true})));
+            });
+        }
+    }
+    (router, dispatcher, observed_rx)
+}
+
+fn headers(pairs: &[(&str, &str)]) -> HeaderVec {
+    pairs
+        .iter()
+        .map(|(k, v)| (Arc::from(*k), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn route_timeout_answers_504_and_handler_observes_expiry() {
+    let (router, dispatcher, observed) = setup();
+    let route_match = router.route(http::Method::GET, "/slow").unwrap();
+
+    let resp = dispatcher
+        .dispatch(route_match, None, HeaderVec::new(), HeaderVec::new())
+        .unwrap();
+    assert_eq!(resp.status, 504);
+
+    // The handler was not interrupted; it noticed the flipped token and stopped early.
+    assert_eq!(observed.recv().unwrap(), (true, true));
+}
+
+#[test]
+fn request_timeout_header_sets_a_deadline_on_unbounded_routes() {
+    let (router, dispatcher, observed) = setup();
+
+    let route_match = router.route(http::Method::GET, "/unbounded").unwrap();
+    let resp = dispatcher
+        .dispatch(
+            route_match,
+            None,
+            headers(&[("request-timeout", "0.05")]),
+            HeaderVec::new(),
+        )
+        .unwrap();
+    assert_eq!(resp.status, 504);
+    assert_eq!(observed.recv().unwrap(), (true, true));
+
+    // Without the header there is no deadline and the handler runs to completion.
+    let route_match = router.route(http::Method::GET, "/unbounded").unwrap();
+    let resp = dispatcher
+        .dispatch(route_match, None, HeaderVec::new(), HeaderVec::new())
+        .unwrap();
+    assert_eq!(resp.status, 200);
+    assert_eq!(observed.recv().unwrap(), (false, false));
+}
+
+#[test]
+fn earlier_of_route_timeout_and_header_wins() {
+    let (router, dispatcher, observed) = setup();
+
+    // A longer header timeout does not extend the route's 50ms.
+    let route_match = router.route(http::Method::GET, "/slow").unwrap();
+    let resp = dispatcher
+        .dispatch(
+            route_match,
+            None,
+            headers(&[("Request-Timeout", "30")]),
+            HeaderVec::new(),
+        )
+        .unwrap();
+    assert_eq!(resp.status, 504);
+    assert_eq!(observed.recv().unwrap(), (true, true));
+}
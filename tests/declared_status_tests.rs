@@ -0,0 +1,123 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Explicit success statuses (`typed::Created`, `typed::Accepted`, `HttpJson::new(201, ..)`)
+//! are checked against the operation's declared responses: `fail` answers `500`, `warn` logs
+//! and sends the response, `off` skips the check. A plain 200 is never rejected. Requests go
+//! through the in-process [`TestClient`].
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest};
+use brrtrouter::router::Router;
+use brrtrouter::runtime_config::ResponseValidationMode;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::typed::{Accepted, Created, HandlerResponseOutput, NoContent};
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Declared statuses
+  version: "1.0"
+paths:
+  /created:
+    post:
+      operationId: created
+      responses:
+        "201": { description: Created }
+  /accepted:
+    post:
+      operationId: accepted
+      responses:
+        "201": { description: Created }
+  /plain:
+    post:
+      operationId: plain
+      responses:
+        "201": { description: Created }
+  /deleted:
+    delete:
+      operationId: deleted
+      responses:
+        "2XX": { description: Done }
+"#;
+
+fn reply(req: HandlerRequest, output: impl HandlerResponseOutput) {
+    let _ = req.reply_tx.send(output.into_handler_response().unwrap());
+}
+
+fn start(mode: ResponseValidationMode) -> TestClient {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("created", |req: HandlerRequest| {
+            reply(req, Created(json!({ "id": 1 })));
+        });
+        dispatcher.register_handler("accepted", |req: HandlerRequest| {
+            reply(req, Accepted(json!({ "id": 1 })));
+        });
+        dispatcher.register_handler("plain", |req: HandlerRequest| {
+            reply(req, json!({ "id": 1 }));
+        });
+        dispatcher.register_handler("deleted", |req: HandlerRequest| {
+            reply(req, NoContent);
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_response_validation(mode);
+    TestClient::new(service)
+}
+
+#[test]
+fn fail_mode_rejects_an_undeclared_success_status() {
+    let client = start(ResponseValidationMode::Fail);
+    client
+        .post("/accepted")
+        .send()
+        .assert_status(500)
+        .assert_header("content-type", "application/problem+json")
+        .assert_json_at("/returned_status", json!(202));
+
+    client
+        .post("/created")
+        .send()
+        .assert_status(201)
+        .assert_json(&json!({ "id": 1 }));
+}
+
+#[test]
+fn range_keys_declare_no_content() {
+    start(ResponseValidationMode::Fail)
+        .delete("/deleted")
+        .send()
+        .assert_status(204);
+}
+
+#[test]
+fn implicit_200_is_not_checked() {
+    start(ResponseValidationMode::Fail)
+        .post("/plain")
+        .send()
+        .assert_status(200);
+}
+
+#[test]
+fn warn_and_off_send_the_undeclared_status() {
+    for mode in [ResponseValidationMode::Warn, ResponseValidationMode::Off] {
+        start(mode)
+            .post("/accepted")
+            .send()
+            .assert_status(202)
+            .assert_json_at("/id", json!(1));
+    }
+}
@@ -0,0 +1,265 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Default security provider wiring: a `DefaultProviderPolicy` orders the config, env and
+//! mock sources per scheme kind; the lenient policy falls back to development credentials,
+//! the strict one reports schemes without a real provider at startup instead.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{AuthOutcome, SecurityProvider, SecurityRequest};
+use brrtrouter::server::security_setup::register_security_from_config;
+use brrtrouter::server::{
+    AppConfig, AppService, DefaultProviderPolicy, ProviderSource, SchemeKind, TestClient,
+};
+use brrtrouter::spec::SecurityScheme;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Vault
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKeyAuth:
+      type: apiKey
+      in: header
+      name: X-API-Key
+    BearerAuth:
+      type: http
+      scheme: bearer
+paths:
+  /secret:
+    get:
+      operationId: secret
+      security:
+        - ApiKeyAuth: []
+      responses:
+        "200": { description: OK }
+  /token:
+    get:
+      operationId: token
+      security:
+        - BearerAuth: []
+      responses:
+        "200": { description: OK }
+"#;
+
+fn service() -> (
+    AppService,
+    Vec<brrtrouter::spec::RouteMeta>,
+    tempfile::TempDir,
+) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in ["secret", "token"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({ "ok": true })));
+            });
+        }
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (service, routes, dir)
+}
+
+/// Accepts exactly `X-API-Key: <key>`, standing in for a provider built from config.yaml
+struct ConfiguredKey(&'static str);
+
+impl SecurityProvider for ConfiguredKey {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.get_header("x-api-key") == Some(self.0)
+    }
+
+    fn check(
+        &self,
+        scheme: &SecurityScheme,
+        scopes: &[String],
+        req: &SecurityRequest,
+    ) -> AuthOutcome {
+        self.validate(scheme, scopes, req).into()
+    }
+}
+
+#[test]
+fn lenient_policy_falls_back_to_the_mock() {
+    let (mut service, routes, _dir) = service();
+    service
+        .register_default_security_providers_from_env(
+            Some("dev-key".into()),
+            &DefaultProviderPolicy::lenient(),
+        )
+        .unwrap();
+    assert!(service.security_providers.contains_key("ApiKeyAuth"));
+    assert!(service.security_providers.contains_key("BearerAuth"));
+    assert_eq!(
+        DefaultProviderPolicy::default(),
+        DefaultProviderPolicy::lenient()
+    );
+    assert!(!DefaultProviderPolicy::lenient().is_strict());
+
+    service.resolve_security(&routes);
+    let client = TestClient::new(service);
+    client
+        .get("/secret")
+        .header("x-api-key", "dev-key")
+        .send()
+        .assert_status(200);
+    client
+        .get("/secret")
+        .header("x-api-key", "wrong")
+        .send()
+        .assert_status(401);
+}
+
+#[test]
+fn strict_policy_fails_without_a_real_provider() {
+    let (mut service, _routes, _dir) = service();
+    let policy = DefaultProviderPolicy::strict();
+    assert!(policy.is_strict());
+    assert_eq!(
+        policy.order(SchemeKind::ApiKey),
+        [ProviderSource::Config, ProviderSource::Env]
+    );
+
+    let err = service
+        .register_default_security_providers_from_env(Some("dev-key".into()), &policy)
+        .unwrap_err();
+    assert_eq!(
+        err.schemes,
+        vec![
+            ("ApiKeyAuth".to_string(), SchemeKind::ApiKey),
+            ("BearerAuth".to_string(), SchemeKind::Bearer),
+        ]
+    );
+    assert!(
+        err.to_string()
+            .starts_with("no security provider for 'ApiKeyAuth' (apiKey), 'BearerAuth' (bearer)"),
+        "{err}"
+    );
+    // The test key is a mock credential: nothing accepts it.
+    assert!(service.security_providers.is_empty());
+}
+
+#[test]
+fn strict_policy_keeps_configured_providers() {
+    let (mut service, routes, _dir) = service();
+    service.register_security_provider("ApiKeyAuth", Arc::new(ConfiguredKey("from-config")));
+    let policy = DefaultProviderPolicy::strict().with_order(
+        SchemeKind::Bearer,
+        &[ProviderSource::Config, ProviderSource::Mock],
+    );
+    service
+        .register_default_security_providers_from_env(Some("dev-key".into()), &policy)
+        .unwrap();
+
+    service.resolve_security(&routes);
+    let client = TestClient::new(service);
+    client
+        .get("/secret")
+        .header("x-api-key", "from-config")
+        .send()
+        .assert_status(200);
+    client
+        .get("/secret")
+        .header("x-api-key", "dev-key")
+        .send()
+        .assert_status(401);
+}
+
+#[test]
+fn source_order_is_followed_per_kind() {
+    let (mut service, routes, _dir) = service();
+    service.register_security_provider("ApiKeyAuth", Arc::new(ConfiguredKey("from-config")));
+    // Mock first: the configured provider is replaced by the development key.
+    let policy = DefaultProviderPolicy::lenient().with_order(
+        SchemeKind::ApiKey,
+        &[ProviderSource::Mock, ProviderSource::Config],
+    );
+    service
+        .register_default_security_providers_from_env(Some("dev-key".into()), &policy)
+        .unwrap();
+
+    service.resolve_security(&routes);
+    let client = TestClient::new(service);
+    client
+        .get("/secret")
+        .header("x-api-key", "dev-key")
+        .send()
+        .assert_status(200);
+    client
+        .get("/secret")
+        .header("x-api-key", "from-config")
+        .send()
+        .assert_status(401);
+}
+
+#[test]
+fn config_yaml_strict_providers_rejects_unconfigured_schemes() {
+    let (mut service, routes, _dir) = service();
+    let config: AppConfig = serde_yaml::from_str(
+        r#"
+security:
+  strict_providers: true
+  api_keys:
+    ApiKeyAuth:
+      key: real-key
+"#,
+    )
+    .unwrap();
+    assert!(config.default_provider_policy().is_strict());
+
+    let err = register_security_from_config(&mut service, &config, Some("dev-key")).unwrap_err();
+    assert_eq!(
+        err.schemes,
+        vec![("BearerAuth".to_string(), SchemeKind::Bearer)]
+    );
+
+    service.resolve_security(&routes);
+    let client = TestClient::new(service);
+    client
+        .get("/secret")
+        .header("x-api-key", "real-key")
+        .send()
+        .assert_status(200);
+    client
+        .get("/secret")
+        .header("x-api-key", "dev-key")
+        .send()
+        .assert_status(401);
+}
+
+#[test]
+fn config_yaml_without_strict_providers_falls_back_to_the_test_key() {
+    let (mut service, routes, _dir) = service();
+    let config = AppConfig::default();
+    assert!(!config.default_provider_policy().is_strict());
+    register_security_from_config(&mut service, &config, Some("dev-key")).unwrap();
+    assert!(service.security_providers.contains_key("BearerAuth"));
+
+    service.resolve_security(&routes);
+    TestClient::new(service)
+        .get("/secret")
+        .header("x-api-key", "dev-key")
+        .send()
+        .assert_status(200);
+}
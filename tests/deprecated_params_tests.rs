@@ -0,0 +1,115 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Deprecated parameters: `build_routes` records `deprecated: true` per parameter, and a
+//! response carries `Warning: 299 - "Deprecated parameter: <name>"` for each one the request
+//! sends, and none when it sends none.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderLookup};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::ParameterLocation;
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      parameters:
+        - { name: limit, in: query, schema: { type: integer } }
+        - { name: page, in: query, deprecated: true, schema: { type: integer, default: 1 } }
+        - { name: sort, in: query, deprecated: true, schema: { type: string } }
+        - { name: X-Client-Version, in: header, deprecated: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, json!([])));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+fn warnings(client: &TestClient, target: &str, headers: &[(&str, &str)]) -> Vec<String> {
+    let mut req = client.get(target);
+    for (name, value) in headers {
+        req = req.header(name, *value);
+    }
+    let resp = req.send();
+    resp.assert_status(200);
+    resp.into_response()
+        .headers
+        .get_all("warning")
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn build_routes_records_deprecated_parameters() {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+    let deprecated: Vec<(&str, ParameterLocation)> = routes[0]
+        .parameters
+        .iter()
+        .filter(|p| p.deprecated)
+        .map(|p| (p.name.as_str(), p.location.clone()))
+        .collect();
+    assert_eq!(
+        deprecated,
+        [
+            ("page", ParameterLocation::Query),
+            ("sort", ParameterLocation::Query),
+            ("X-Client-Version", ParameterLocation::Header),
+        ]
+    );
+}
+
+#[test]
+fn warning_only_when_a_deprecated_parameter_is_sent() {
+    let (client, _dir) = client();
+
+    // `page` is deprecated with a default: the filled-in default is not the client's doing.
+    assert!(warnings(&client, "/pets", &[]).is_empty());
+    assert!(warnings(&client, "/pets?limit=5", &[]).is_empty());
+
+    assert_eq!(
+        warnings(&client, "/pets?limit=5&sort=name", &[]),
+        [r#"299 - "Deprecated parameter: sort""#]
+    );
+    assert_eq!(
+        warnings(
+            &client,
+            "/pets?page=2&sort=name",
+            &[("x-client-version", "3")]
+        ),
+        [
+            r#"299 - "Deprecated parameter: page""#,
+            r#"299 - "Deprecated parameter: sort""#,
+            r#"299 - "Deprecated parameter: X-Client-Version""#,
+        ]
+    );
+}
@@ -147,6 +147,12 @@ fn test_dispatch_post_item() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     dispatcher
@@ -194,6 +200,12 @@ fn test_dispatch_get_pet() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     dispatcher
@@ -245,6 +257,12 @@ fn test_typed_controller_params() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     dispatcher
@@ -287,6 +305,12 @@ fn test_typed_controller_invalid_params() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     dispatcher
@@ -297,7 +321,7 @@ fn test_typed_controller_invalid_params() {
         .unwrap();
     let resp = reply_rx.recv().unwrap();
     assert_eq!(resp.status, 400);
-    assert!(resp.body.get("error").is_some());
+    assert!(resp.body.get("detail").is_some());
 }
 
 #[test]
@@ -328,6 +352,12 @@ fn test_panic_handler_returns_500() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     dispatcher
@@ -338,7 +368,7 @@ fn test_panic_handler_returns_500() {
         .unwrap();
     let resp = reply_rx.recv().unwrap();
     assert_eq!(resp.status, 500);
-    assert!(resp.body.get("error").is_some());
+    assert!(resp.body.get("detail").is_some());
 }
 
 #[test]
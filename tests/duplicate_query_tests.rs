@@ -0,0 +1,171 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Repeated query keys (`?status=open&status=closed`): the `query.duplicates` policy
+//! (`first`, `last`, `array`) decides what a declared parameter gets, exploded array
+//! parameters always collect every occurrence, and `strict_duplicates` rejects repeated
+//! scalars with `400`. Requests go through the in-process [`TestClient`].
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{
+    decode_query_values, AppService, DuplicateQueryPolicy, QueryConfig, TestClient,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Duplicates
+  version: "1.0"
+paths:
+  /tickets:
+    get:
+      operationId: list_tickets
+      parameters:
+        - name: status
+          in: query
+          schema:
+            type: string
+            enum: [open, closed]
+        - name: tag
+          in: query
+          schema:
+            type: array
+            items: { type: string }
+        - name: ids
+          in: query
+          explode: false
+          schema:
+            type: array
+            items: { type: integer }
+      responses:
+        "200":
+          description: ok
+"#;
+
+fn start(query: Option<QueryConfig>) -> TestClient {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_tickets", |req: HandlerRequest| {
+            let tag_schema = json!({ "type": "array", "items": { "type": "string" } });
+            let ids_schema = json!({ "type": "array", "items": { "type": "integer" } });
+            let body = json!({
+                "status": req.get_query_param("status"),
+                "statuses": req.get_query_params("status").collect::<Vec<_>>(),
+                "tag": decode_query_values(req.get_query_params("tag"), Some(&tag_schema), None, None),
+                "ids": decode_query_values(
+                    req.get_query_params("ids"),
+                    Some(&ids_schema),
+                    None,
+                    Some(false),
+                ),
+            });
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_query(query);
+    TestClient::new(service)
+}
+
+fn policy(duplicates: DuplicateQueryPolicy, strict_duplicates: bool) -> Option<QueryConfig> {
+    Some(QueryConfig {
+        duplicates,
+        strict_duplicates,
+    })
+}
+
+fn get(client: &TestClient, uri: &str) -> (u16, Value) {
+    let resp = client.get(uri).send();
+    (resp.status(), resp.body().clone())
+}
+
+#[test]
+fn first_is_the_default() {
+    start(None)
+        .get("/tickets?status=open&status=closed")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/status", json!("open"))
+        .assert_json_at("/statuses", json!(["open"]));
+}
+
+#[test]
+fn last_keeps_the_last_occurrence() {
+    let client = start(policy(DuplicateQueryPolicy::Last, false));
+    let (status, body) = get(&client, "/tickets?status=open&status=closed");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["status"], "closed");
+    assert_eq!(body["statuses"], json!(["closed"]));
+}
+
+#[test]
+fn array_validates_the_collected_values_against_the_schema() {
+    let client = start(policy(DuplicateQueryPolicy::Array, false));
+    // A scalar schema does not accept the collected array.
+    let (status, body) = get(&client, "/tickets?status=open&status=closed");
+    assert_eq!(status, 400, "{body}");
+    assert_eq!(body["parameter"], "status");
+    assert_eq!(body["in"], "query");
+
+    // A non-exploded array merges the items of every occurrence.
+    let (status, body) = get(&client, "/tickets?ids=1,2&ids=3");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["ids"], json!([1, 2, 3]));
+}
+
+#[test]
+fn exploded_arrays_collect_every_occurrence_under_any_policy() {
+    for duplicates in [
+        DuplicateQueryPolicy::First,
+        DuplicateQueryPolicy::Last,
+        DuplicateQueryPolicy::Array,
+    ] {
+        let client = start(policy(duplicates, true));
+        let (status, body) = get(&client, "/tickets?tag=a&tag=b");
+        assert_eq!(status, 200, "{duplicates:?}: {body}");
+        assert_eq!(body["tag"], json!(["a", "b"]), "{duplicates:?}");
+    }
+}
+
+#[test]
+fn explode_false_arrays_follow_the_configured_policy() {
+    let client = start(policy(DuplicateQueryPolicy::First, false));
+    let (status, body) = get(&client, "/tickets?ids=1,2&ids=3");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["ids"], json!([1, 2]));
+
+    let client = start(policy(DuplicateQueryPolicy::Last, false));
+    let (_, body) = get(&client, "/tickets?ids=1,2&ids=3");
+    assert_eq!(body["ids"], json!([3]));
+}
+
+#[test]
+fn strict_rejects_repeated_scalars_under_first_and_last() {
+    for duplicates in [DuplicateQueryPolicy::First, DuplicateQueryPolicy::Last] {
+        let client = start(policy(duplicates, true));
+        let (status, body) = get(&client, "/tickets?status=open&status=closed");
+        assert_eq!(status, 400, "{duplicates:?}: {body}");
+        assert_eq!(body["parameter"], "status");
+        assert!(
+            body["detail"].as_str().unwrap().contains("Repeated"),
+            "{body}"
+        );
+
+        let (status, body) = get(&client, "/tickets?status=open");
+        assert_eq!(status, 200, "{duplicates:?}: {body}");
+    }
+}
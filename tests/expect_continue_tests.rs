@@ -0,0 +1,274 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `Expect: 100-continue` on the front listener: `100 Continue` is sent before the client
+//! uploads the body, and requests that would be rejected anyway are answered (`401`, `413`,
+//! `415`, `417`) and closed before any body is sent.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use brrtrouter::spec::SecurityScheme;
+use serde_json::json;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Uploads
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /upload:
+    post:
+      operationId: upload
+      security:
+        - ApiKey: []
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { type: object }
+"#;
+
+const MAX_BODY_BYTES: usize = 256;
+
+struct KeyProvider;
+
+impl SecurityProvider for KeyProvider {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.get_header("x-api-key") == Some("secret")
+    }
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("upload", |req: HandlerRequest| {
+            let body = req.body.clone().unwrap_or_default();
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "received": body })));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.register_security_provider("ApiKey", Arc::new(KeyProvider));
+    service.set_expect_continue(true);
+    service.set_max_body_bytes(Some(MAX_BODY_BYTES));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start_with_websockets(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn connect(server: &Server) -> TcpStream {
+    let stream = TcpStream::connect(server.addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+}
+
+/// Read one response head, byte by byte so nothing after it is consumed.
+fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        match stream.read(&mut byte) {
+            Ok(0) => break,
+            Ok(_) => head.push(byte[0]),
+            Err(e) => panic!("read error after {:?}: {e}", String::from_utf8_lossy(&head)),
+        }
+    }
+    String::from_utf8(head).unwrap()
+}
+
+/// Read a final response head and its `Content-Length` body.
+fn read_response(stream: &mut TcpStream) -> (String, serde_json::Value) {
+    let head = read_head(stream);
+    let length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>().unwrap())
+        .unwrap_or(0);
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body).unwrap();
+    (head, serde_json::from_slice(&body).unwrap())
+}
+
+fn upload_head(extra: &str, content_type: &str, length: usize) -> String {
+    format!(
+        "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Type: {content_type}\r\n\
+         Content-Length: {length}\r\n{extra}\r\n"
+    )
+}
+
+#[test]
+fn interim_response_precedes_the_body() {
+    let server = start();
+    let body = br#"{"name":"report.csv"}"#;
+    let mut stream = connect(&server);
+    stream
+        .write_all(
+            upload_head(
+                "X-API-Key: secret\r\nExpect: 100-continue\r\n",
+                "application/json",
+                body.len(),
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    // Nothing of the body has been sent yet.
+    assert_eq!(read_head(&mut stream), "HTTP/1.1 100 Continue\r\n\r\n");
+
+    stream.write_all(body).unwrap();
+    let (head, json) = read_response(&mut stream);
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert_eq!(json, json!({ "received": { "name": "report.csv" } }));
+}
+
+/// Send only the head and expect a rejection with `status`, then a closed connection.
+fn assert_rejected_before_body(server: &Server, head: &str, status: u16) -> serde_json::Value {
+    let mut stream = connect(server);
+    stream.write_all(head.as_bytes()).unwrap();
+    let (response, problem) = read_response(&mut stream);
+    assert!(
+        response.starts_with(&format!("HTTP/1.1 {status} ")),
+        "expected {status}: {response}"
+    );
+    assert!(!response.contains("100 Continue"), "{response}");
+    assert_eq!(stream.read(&mut [0u8; 16]).unwrap(), 0, "connection closed");
+    problem
+}
+
+#[test]
+fn missing_credentials_are_rejected_before_the_body() {
+    let server = start();
+    let head = upload_head("Expect: 100-continue\r\n", "application/json", 64);
+    assert_rejected_before_body(&server, &head, 401);
+}
+
+#[test]
+fn undeclared_content_type_is_rejected_before_the_body() {
+    let server = start();
+    let head = upload_head(
+        "X-API-Key: secret\r\nExpect: 100-continue\r\n",
+        "text/csv",
+        64,
+    );
+    assert_rejected_before_body(&server, &head, 415);
+}
+
+#[test]
+fn oversized_content_length_is_rejected_before_the_body() {
+    let server = start();
+    let head = upload_head(
+        "X-API-Key: secret\r\nExpect: 100-continue\r\n",
+        "application/json",
+        MAX_BODY_BYTES + 1,
+    );
+    let problem = assert_rejected_before_body(&server, &head, 413);
+    assert_eq!(problem["status"], 413);
+}
+
+#[test]
+fn unknown_expectation_is_rejected() {
+    let server = start();
+    let head = upload_head(
+        "X-API-Key: secret\r\nExpect: 200-ok\r\n",
+        "application/json",
+        64,
+    );
+    assert_rejected_before_body(&server, &head, 417);
+}
+
+#[test]
+fn oversized_body_without_expectation_gets_413() {
+    let server = start();
+    let body = format!(r#"{{"pad":"{}"}}"#, "x".repeat(MAX_BODY_BYTES));
+    let mut stream = connect(&server);
+    stream
+        .write_all(upload_head("X-API-Key: secret\r\n", "application/json", body.len()).as_bytes())
+        .unwrap();
+    stream.write_all(body.as_bytes()).unwrap();
+    let (head, problem) = read_response(&mut stream);
+    assert!(head.starts_with("HTTP/1.1 413"), "{head}");
+    assert_eq!(problem["status"], 413);
+}
+
+#[test]
+fn each_connection_serves_one_request_so_every_head_is_inspected() {
+    let server = start();
+    let body = br#"{"name":"report.csv"}"#;
+    let mut stream = connect(&server);
+    stream
+        .write_all(upload_head("X-API-Key: secret\r\n", "application/json", body.len()).as_bytes())
+        .unwrap();
+    stream.write_all(body).unwrap();
+    let (head, _) = read_response(&mut stream);
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert!(
+        head.to_ascii_lowercase().contains("connection: close"),
+        "{head}"
+    );
+    // A second request with `Expect` could not reach the server unchecked on this
+    // connection: it is closed, and the next one is inspected from its first head.
+    assert_eq!(stream.read(&mut [0u8; 16]).unwrap(), 0, "connection closed");
+    let head = upload_head("Expect: 100-continue\r\n", "application/json", 64);
+    assert_rejected_before_body(&server, &head, 401);
+}
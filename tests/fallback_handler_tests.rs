@@ -0,0 +1,273 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! `AppService::on_not_found` / `on_method_not_allowed` / `on_internal_error`: custom
+//! responses replace the default problems, middleware `after` hooks still run on them, and
+//! a path routed for other methods answers 405 with `Allow`. Without a handler, browsers get
+//! the (overridable) HTML error page unless `errors.format` is `json`.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::middleware::Middleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{
+    AppService, ErrorFormat, ErrorsConfig, HtmlErrorPage, HttpServer, ProblemDetails, ServerHandle,
+};
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Fallbacks
+  version: "1.0"
+paths:
+  /items:
+    get:
+      operationId: list_items
+      responses:
+        "200": { description: OK }
+  /broken:
+    get:
+      operationId: unregistered
+      responses:
+        "200": { description: OK }
+"#;
+
+/// Tags every response it sees, so the test can tell `after` ran.
+struct TagAfter;
+
+impl Middleware for TagAfter {
+    fn after(&self, req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        res.set_header("x-after", format!("{} {}", req.method, req.path));
+    }
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(configure: impl FnOnce(&mut AppService)) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_items", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, json!([])));
+        });
+    }
+    dispatcher.add_middleware(Arc::new(TagAfter));
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    configure(&mut service);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block, body)
+fn request(server: &Server, method: &str, path: &str, extra: &str) -> (u16, String, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\n{extra}\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, head.to_ascii_lowercase(), body.to_string())
+}
+
+/// HTML for browsers, a branded JSON envelope for API clients.
+fn branded(req: &HandlerRequest, problem: &ProblemDetails) -> HandlerResponse {
+    let wants_html = req
+        .get_header("accept")
+        .is_some_and(|accept| accept.contains("text/html"));
+    if wants_html {
+        let mut headers = HeaderVec::new();
+        headers.push((Arc::from("content-type"), "text/html".to_string()));
+        HandlerResponse::new(
+            problem.status,
+            headers,
+            Value::String(format!("<h1>Nothing at {}</h1>", req.path)),
+        )
+    } else {
+        HandlerResponse::json(
+            problem.status,
+            json!({"error": {"code": problem.status, "message": problem.detail}}),
+        )
+    }
+}
+
+#[test]
+fn custom_not_found_negotiates_on_accept_and_runs_after_middleware() {
+    let server = start(|service| service.on_not_found(branded));
+
+    let (status, head, body) = request(&server, "GET", "/nope", "Accept: text/html\r\n");
+    assert_eq!(status, 404);
+    assert!(head.contains("content-type: text/html"), "{head}");
+    assert_eq!(body, "<h1>Nothing at /nope</h1>");
+    assert!(head.contains("x-after: get /nope"), "{head}");
+
+    let (status, head, body) = request(&server, "GET", "/nope", "Accept: application/json\r\n");
+    assert_eq!(status, 404);
+    assert!(head.contains("content-type: application/json"), "{head}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["error"]["code"], 404);
+    assert_eq!(body["error"]["message"], "No route for GET /nope");
+    assert!(head.contains("x-after: get /nope"), "{head}");
+
+    // Routed requests are untouched.
+    let (status, _, body) = request(&server, "GET", "/items", "");
+    assert_eq!(status, 200);
+    assert_eq!(body, "[]");
+}
+
+#[test]
+fn defaults_without_fallbacks() {
+    let server = start(|_| {});
+
+    let (status, head, _) = request(&server, "GET", "/nope", "");
+    assert_eq!(status, 404);
+    assert!(head.contains("application/problem+json"), "{head}");
+    assert!(!head.contains("x-after"), "{head}");
+
+    let (status, head, body) = request(&server, "DELETE", "/items", "");
+    assert_eq!(status, 405, "{body}");
+    assert!(head.contains("allow: get"), "{head}");
+}
+
+#[test]
+fn method_not_allowed_and_internal_error_fallbacks() {
+    let server = start(|service| {
+        service.on_method_not_allowed(branded);
+        service.on_internal_error(branded);
+    });
+
+    let (status, head, body) = request(&server, "POST", "/items", "");
+    assert_eq!(status, 405);
+    assert!(head.contains("allow: get"), "{head}");
+    assert!(head.contains("x-after: post /items"), "{head}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["error"]["code"], 405);
+
+    // `/broken` is routed but has no registered handler.
+    let (status, head, body) = request(&server, "GET", "/broken", "");
+    assert_eq!(status, 500);
+    assert!(head.contains("x-after: get /broken"), "{head}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["error"]["message"], "Handler failed or not registered");
+}
+
+const BROWSER_ACCEPT: &str =
+    "Accept: text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8\r\n";
+
+#[test]
+fn default_errors_negotiate_html_for_browsers_and_problem_for_api_clients() {
+    let server = start(|_| {});
+
+    let (status, head, body) = request(&server, "GET", "/nope", BROWSER_ACCEPT);
+    assert_eq!(status, 404);
+    assert!(head.contains("content-type: text/html"), "{head}");
+    assert!(body.contains("<h1>404 Not Found</h1>"), "{body}");
+    assert!(body.contains("No route for GET /nope"), "{body}");
+
+    let (status, head, body) = request(&server, "GET", "/nope", "Accept: application/json\r\n");
+    assert_eq!(status, 404);
+    assert!(head.contains("application/problem+json"), "{head}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["status"], 404);
+
+    let (status, head, body) = request(&server, "DELETE", "/items", BROWSER_ACCEPT);
+    assert_eq!(status, 405);
+    assert!(head.contains("content-type: text/html"), "{head}");
+    assert!(head.contains("allow: get"), "{head}");
+    assert!(body.contains("405 Method Not Allowed"), "{body}");
+
+    let (status, head, _) = request(&server, "GET", "/broken", BROWSER_ACCEPT);
+    assert_eq!(status, 500);
+    assert!(head.contains("content-type: text/html"), "{head}");
+}
+
+#[test]
+fn json_error_format_ignores_accept() {
+    let server = start(|service| {
+        service
+            .set_errors(Some(ErrorsConfig {
+                format: ErrorFormat::Json,
+                html_template: None,
+            }))
+            .unwrap();
+    });
+
+    let (status, head, body) = request(&server, "GET", "/nope", BROWSER_ACCEPT);
+    assert_eq!(status, 404);
+    assert!(head.contains("application/problem+json"), "{head}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["detail"], "No route for GET /nope");
+}
+
+#[test]
+fn custom_html_template_and_handlers_take_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    let template = dir.path().join("error.html");
+    std::fs::write(
+        &template,
+        "<p>{{ status }}: {{ detail }} ({{ method }})</p>",
+    )
+    .unwrap();
+    let server = start(|service| {
+        service
+            .set_errors(Some(ErrorsConfig {
+                format: ErrorFormat::Negotiate,
+                html_template: Some(template.clone()),
+            }))
+            .unwrap();
+        service.on_method_not_allowed(branded);
+    });
+
+    let (status, _, body) = request(&server, "GET", "/broken", BROWSER_ACCEPT);
+    assert_eq!(status, 500);
+    assert_eq!(body, "<p>500: Handler failed or not registered (GET)</p>");
+
+    // A registered handler wins over the page.
+    let (status, _, body) = request(&server, "POST", "/items", BROWSER_ACCEPT);
+    assert_eq!(status, 405);
+    assert_eq!(body, "<h1>Nothing at /items</h1>");
+
+    std::fs::write(&template, "{% if %}").unwrap();
+    assert!(HtmlErrorPage::from_file(&template).is_err());
+}
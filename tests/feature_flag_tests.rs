@@ -0,0 +1,154 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Feature-gated routes: an operation marked `x-feature: admin` is only served while `admin`
+//! is enabled. Otherwise `spec::enabled_routes` leaves it out, so it answers `404` (not the
+//! `401` / `403` its security would give) and needs no handler.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerFn, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::{enabled_routes, RouteMeta, SecurityScheme};
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Admin
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /health:
+    get:
+      operationId: health
+      responses:
+        "200": { description: OK }
+  /admin/stats:
+    get:
+      operationId: admin_stats
+      x-feature: admin
+      responses:
+        "200": { description: OK }
+  /admin/users:
+    delete:
+      operationId: purge_users
+      x-feature: admin
+      security:
+        - ApiKey: []
+      responses:
+        "204": { description: Purged }
+"#;
+
+fn features(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn load() -> (
+    Vec<RouteMeta>,
+    HashMap<String, SecurityScheme>,
+    tempfile::TempDir,
+) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    (routes, schemes, dir)
+}
+
+fn ok(req: HandlerRequest) {
+    let _ = req.reply_tx.send(HandlerResponse::json(
+        200,
+        json!({ "handler": req.handler_name }),
+    ));
+}
+
+/// A client serving the routes enabled by `enabled`, with a handler for each of them
+fn client(enabled: &[&str]) -> (TestClient, tempfile::TempDir) {
+    let (routes, schemes, dir) = load();
+    let routes = enabled_routes(routes, &features(enabled));
+
+    let mut dispatcher = Dispatcher::new();
+    let handlers: Vec<(&str, HandlerFn)> = routes
+        .iter()
+        .map(|route| (&*route.handler_name, Arc::new(ok) as HandlerFn))
+        .collect();
+    dispatcher.register_from_spec(&routes, handlers).unwrap();
+
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(dir.path().join("openapi.yaml"))
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn x_feature_is_recorded_on_the_route() {
+    let (routes, _schemes, _dir) = load();
+    let feature_of = |handler: &str| {
+        routes
+            .iter()
+            .find(|r| &*r.handler_name == handler)
+            .unwrap()
+            .feature
+            .clone()
+    };
+    assert_eq!(feature_of("health"), None);
+    assert_eq!(feature_of("admin_stats").as_deref(), Some("admin"));
+
+    let stats = routes
+        .iter()
+        .find(|r| &*r.handler_name == "admin_stats")
+        .unwrap();
+    assert!(stats.is_enabled(&features(&["admin"])));
+    assert!(!stats.is_enabled(&features(&["beta"])));
+    assert!(routes
+        .iter()
+        .filter(|r| r.feature.is_none())
+        .all(|r| r.is_enabled(&BTreeSet::new())));
+}
+
+#[test]
+fn enabled_feature_serves_its_routes() {
+    let (client, _dir) = client(&["admin"]);
+    client.get("/health").send().assert_status(200);
+    client
+        .get("/admin/stats")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/handler", json!("admin_stats"));
+    // Served, so its security applies.
+    client.delete("/admin/users").send().assert_status(401);
+}
+
+#[test]
+fn disabled_feature_routes_are_not_found() {
+    let (client, _dir) = client(&[]);
+    client.get("/health").send().assert_status(200);
+    client.get("/admin/stats").send().assert_status(404);
+    // 404 rather than 401/403: a disabled route does not reveal that it exists.
+    client.delete("/admin/users").send().assert_status(404);
+}
+
+#[test]
+fn disabled_feature_routes_need_no_handler() {
+    let (routes, _schemes, _dir) = load();
+    let mut dispatcher = Dispatcher::new();
+    let health: HandlerFn = Arc::new(ok);
+
+    let routes = enabled_routes(routes, &BTreeSet::new());
+    assert_eq!(routes.len(), 1);
+    assert!(dispatcher
+        .register_from_spec(&routes, [("health", health)])
+        .is_ok());
+    assert!(dispatcher.unhandled_routes(&routes).is_empty());
+}
@@ -1,9 +1,12 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
-use brrtrouter::generator::{format_project, generate_project_from_spec};
+use brrtrouter::generator::{
+    format_project, generate_project_from_spec, generate_project_with_options, GenerationScope,
+};
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Test fixture for project generation tests with automatic cleanup via RAII
@@ -78,3 +81,91 @@ fn test_generate_project_and_format() {
 
     // Automatic cleanup when fixture drops (directory restored, files deleted)
 }
+
+fn generate_workspace(output: &Path) -> PathBuf {
+    let spec_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("openapi.yaml");
+    let scope = GenerationScope {
+        workspace: true,
+        ..GenerationScope::all()
+    };
+    generate_project_with_options(
+        &spec_path,
+        Some(output),
+        true,
+        false,
+        &scope,
+        None,
+        Some("petshop"),
+        None,
+    )
+    .expect("generate workspace")
+}
+
+#[test]
+fn workspace_puts_the_types_in_their_own_crate() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = generate_workspace(&dir.path().join("petshop"));
+
+    let manifest = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("[workspace]"), "{manifest}");
+    assert!(manifest.contains("\"petshop-types\""), "{manifest}");
+    assert!(manifest.contains("\"petshop-server\""), "{manifest}");
+
+    let types = root.join("petshop-types");
+    let types_manifest = fs::read_to_string(types.join("Cargo.toml")).unwrap();
+    assert!(types_manifest.contains("name = \"petshop-types\""));
+    let lib = fs::read_to_string(types.join("src").join("lib.rs")).unwrap();
+    assert!(lib.contains("pub struct Pet {"), "{lib}");
+
+    let server = root.join("petshop-server");
+    let server_manifest = fs::read_to_string(server.join("Cargo.toml")).unwrap();
+    assert!(server_manifest.contains("name = \"petshop-server\""));
+    assert!(
+        server_manifest.contains("petshop-types = { path = \"../petshop-types\" }"),
+        "{server_manifest}"
+    );
+
+    // Handlers import from the types crate; handlers::types only re-exports it.
+    let handlers = server.join("src").join("handlers");
+    let reexport = fs::read_to_string(handlers.join("types.rs")).unwrap();
+    assert!(reexport.contains("pub use petshop_types::*;"), "{reexport}");
+    let mut importing = 0;
+    for entry in fs::read_dir(&handlers).unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        assert!(
+            !source.contains("use crate::handlers::types::"),
+            "{path:?} imports the in-crate types"
+        );
+        if source.contains("use petshop_types::") {
+            importing += 1;
+        }
+    }
+    assert!(importing > 0, "no handler imports the types crate");
+}
+
+/// Builds the generated workspace with `cargo check --offline`: slow, and needs the
+/// BRRTRouter dependencies in the local cargo cache.
+#[test]
+#[ignore] // Compiles BRRTRouter into a separate target dir; run with --ignored
+fn workspace_builds() {
+    // Under target/, inside this checkout, so the generated crates find BRRTRouter by path.
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join("petshop_workspace");
+    let _ = fs::remove_dir_all(&output);
+    let root = generate_workspace(&output);
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let result = Command::new(cargo)
+        .args(["check", "--offline", "--workspace"])
+        .env("CARGO_TARGET_DIR", root.join("target"))
+        .current_dir(&root)
+        .output()
+        .expect("run cargo check");
+    assert!(
+        result.status.success(),
+        "cargo check failed:\n{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+}
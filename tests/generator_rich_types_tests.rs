@@ -0,0 +1,100 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! `--rich-types` generator mapping: the emitted field types serde round-trip the spec's
+//! example payload. The struct below is written exactly as `handler_types.rs.txt` renders it
+//! for `SCHEMA`, and the test first checks the generator still emits those types.
+
+use brrtrouter::generator::{extract_fields_with_options, TypeOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+fn schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "required": ["id", "created_at", "count"],
+        "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "created_at": {"type": "string", "format": "date-time"},
+            "birthday": {"type": "string", "format": "date"},
+            "count": {"type": "integer", "format": "int64"},
+            "small": {"type": "integer", "format": "int32"},
+            "avatar": {"type": "string", "format": "byte"}
+        }
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Account {
+    pub id: uuid::Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birthday: Option<chrono::NaiveDate>,
+    pub count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub small: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<brrtrouter::typed::Base64Bytes>,
+}
+
+#[test]
+fn generator_emits_the_struct_field_types() {
+    let fields = extract_fields_with_options(&schema(), TypeOptions { rich_types: true });
+    let mut rendered: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.optional {
+                format!("{}: Option<{}>", f.name, f.ty)
+            } else {
+                format!("{}: {}", f.name, f.ty)
+            }
+        })
+        .collect();
+    rendered.sort();
+    assert_eq!(
+        rendered,
+        [
+            "avatar: Option<brrtrouter::typed::Base64Bytes>",
+            "birthday: Option<chrono::NaiveDate>",
+            "count: i64",
+            "created_at: chrono::DateTime<chrono::Utc>",
+            "id: uuid::Uuid",
+            "small: Option<i32>",
+        ]
+    );
+}
+
+#[test]
+fn example_payload_round_trips() {
+    let example = json!({
+        "id": "7f1c2c62-3c1b-4a8e-9a53-2b1f1f0c9d11",
+        "created_at": "2024-05-01T12:30:00Z",
+        "birthday": "1990-02-28",
+        "count": 9_007_199_254_740_993_i64,
+        "small": -7,
+        "avatar": "aGVsbG8="
+    });
+    let account: Account = serde_json::from_value(example.clone()).unwrap();
+    assert_eq!(account.count, 9_007_199_254_740_993);
+    assert_eq!(
+        account.avatar.as_deref().map(|b| &b[..]),
+        Some(&b"hello"[..])
+    );
+    assert_eq!(serde_json::to_value(&account).unwrap(), example);
+}
+
+#[test]
+fn invalid_formats_are_rejected_on_deserialize() {
+    let bad_date = json!({
+        "id": "7f1c2c62-3c1b-4a8e-9a53-2b1f1f0c9d11",
+        "created_at": "yesterday",
+        "count": 1
+    });
+    assert!(serde_json::from_value::<Account>(bad_date).is_err());
+
+    let bad_uuid = json!({
+        "id": "not-a-uuid",
+        "created_at": "2024-05-01T12:30:00Z",
+        "count": 1
+    });
+    assert!(serde_json::from_value::<Account>(bad_uuid).is_err());
+}
@@ -2,11 +2,13 @@
 
 use brrtrouter::generator::FieldDef;
 use brrtrouter::generator::{
-    write_controller, write_handler, write_impl_controller_stub, write_impl_main_rs,
-    write_impl_registry_rs, write_main_rs, write_registry_rs, ImplControllerStubParams,
-    RegistryEntry,
+    write_controller, write_default_config, write_handler, write_impl_controller_stub,
+    write_impl_main_rs, write_impl_registry_rs, write_main_rs, write_registry_rs,
+    write_websocket_controller, ImplControllerStubParams, RegistryEntry,
 };
-use brrtrouter::spec::{ParameterMeta, ResponseSpec, RouteMeta};
+use brrtrouter::server::security_setup::check_jwks_algorithms;
+use brrtrouter::server::{AppConfig, DuplicateQueryPolicy, ErrorFormat};
+use brrtrouter::spec::{CreatedLocation, ParameterMeta, ResponseSpec, RouteMeta};
 use http::Method;
 use std::collections::{BTreeSet, HashMap};
 use std::fs;
@@ -37,6 +39,7 @@ fn test_template_writers() {
         original_name: "id".into(),
         ty: "String".into(),
         optional: false,
+        nullable: false,
         value: "\"id\".to_string()".into(),
     }];
     let res_fields = vec![FieldDef {
@@ -44,6 +47,7 @@ fn test_template_writers() {
         original_name: "ok".into(),
         ty: "bool".into(),
         optional: false,
+        nullable: false,
         value: "true".into(),
     }];
     let imports = BTreeSet::new();
@@ -60,6 +64,8 @@ fn test_template_writers() {
         false,
         true,
         false,
+        &[],
+        None,
         true,
     )
     .unwrap();
@@ -71,6 +77,7 @@ fn test_template_writers() {
         "TestController",
         &res_fields,
         None,
+        &[],
         false,
         true,
         None,
@@ -87,6 +94,7 @@ fn test_template_writers() {
         parameters: vec![],
         stack_size_bytes: 16384,
         is_proxy: false,
+        is_websocket: false,
     }];
     write_registry_rs(&src_dir, &entries).unwrap();
 
@@ -94,6 +102,19 @@ fn test_template_writers() {
         x_service: None,
         x_brrtrouter_downstream_path: None,
         x_brrtrouter_impl: None,
+        auth_optional: false,
+        forward_claims: None,
+        tags: Vec::new(),
+        deprecated: false,
+        websocket: false,
+        links: Vec::new(),
+        declared_statuses: Vec::new(),
+        schema_overrides: Default::default(),
+        created_location: None,
+        response_headers: Default::default(),
+        upload_limits: Default::default(),
+        feature: None,
+        head_strategy: Default::default(),
         method: Method::GET,
         path_pattern: "/test".into(),
         handler_name: "test".into(),
@@ -103,6 +124,7 @@ fn test_template_writers() {
         request_content_types: Vec::new(),
         response_schema: None,
         example: None,
+        examples: Vec::new(),
         responses: HashMap::new(),
         security: vec![],
         example_name: String::new(),
@@ -112,6 +134,8 @@ fn test_template_writers() {
         sse: false,
         estimated_request_body_bytes: None,
         x_brrtrouter_stack_size: None,
+        x_brrtrouter_timeout_ms: None,
+        x_slow_threshold_ms: None,
         cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
     };
     write_main_rs(&src_dir, "tester", vec![route]).unwrap();
@@ -171,6 +195,7 @@ fn impl_controller_stub_maps_hyphenated_package_in_use_lines() {
         original_name: "id".into(),
         ty: "String".into(),
         optional: false,
+        nullable: false,
         value: "\"x\".to_string()".into(),
     }];
     let res_fields = vec![FieldDef {
@@ -178,6 +203,7 @@ fn impl_controller_stub_maps_hyphenated_package_in_use_lines() {
         original_name: "ok".into(),
         ty: "bool".into(),
         optional: false,
+        nullable: false,
         value: "true".into(),
     }];
     let stub_path = controllers_dir.join("get_items.rs");
@@ -237,6 +263,7 @@ fn proxy_controller_template_delegates_to_shared_module() {
         original_name: "ok".into(),
         ty: "bool".into(),
         optional: false,
+        nullable: false,
         value: "true".into(),
     }];
 
@@ -246,6 +273,7 @@ fn proxy_controller_template_delegates_to_shared_module() {
         "ListFleetController",
         &res_fields,
         None,
+        &[],
         false,
         true,
         Some("fleet".to_string()),
@@ -294,6 +322,7 @@ fn impl_registry_renders_arms_for_discovered_controllers() {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses: HashMap::new(),
             security: vec![],
             example_name: String::new(),
@@ -303,10 +332,25 @@ fn impl_registry_renders_arms_for_discovered_controllers() {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: Some(true),
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
         },
         RouteMeta {
             method: Method::POST,
@@ -318,6 +362,7 @@ fn impl_registry_renders_arms_for_discovered_controllers() {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses: HashMap::new(),
             security: vec![],
             example_name: String::new(),
@@ -327,10 +372,25 @@ fn impl_registry_renders_arms_for_discovered_controllers() {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: Some(true),
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
         },
     ];
 
@@ -361,6 +421,7 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
         original_name: "access_token".into(),
         ty: "String".into(),
         optional: false,
+        nullable: false,
         value: "\"token\".to_string()".into(),
     }];
 
@@ -371,6 +432,7 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
         ResponseSpec {
             schema: Some(serde_json::json!({"type":"object","properties":{"access_token":{"type":"string"}}})),
             example: None,
+            examples: Vec::new(),
         },
     );
     responses.insert(200, ok);
@@ -382,6 +444,7 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
                 serde_json::json!({"type":"object","properties":{"error":{"type":"string"}}}),
             ),
             example: None,
+            examples: Vec::new(),
         },
     );
     responses.insert(401, unauthorized);
@@ -398,6 +461,7 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
             serde_json::json!({"type":"object","properties":{"access_token":{"type":"string"}}}),
         ),
         example: None,
+        examples: Vec::new(),
         responses,
         security: vec![],
         example_name: String::new(),
@@ -407,10 +471,25 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
         sse: false,
         estimated_request_body_bytes: None,
         x_brrtrouter_stack_size: None,
+        x_brrtrouter_timeout_ms: None,
+        x_slow_threshold_ms: None,
         cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
         x_service: None,
         x_brrtrouter_downstream_path: None,
         x_brrtrouter_impl: Some(true),
+        auth_optional: false,
+        forward_claims: None,
+        tags: Vec::new(),
+        deprecated: false,
+        websocket: false,
+        links: Vec::new(),
+        declared_statuses: Vec::new(),
+        schema_overrides: Default::default(),
+        created_location: None,
+        response_headers: Default::default(),
+        upload_limits: Default::default(),
+        feature: None,
+        head_strategy: Default::default(),
     };
     assert!(route.needs_http_json_return_type());
 
@@ -420,6 +499,7 @@ fn controller_emits_http_json_when_operation_has_non_2xx_json_schema() {
         "AuthRefreshController",
         &res_fields,
         None,
+        &[],
         false,
         true,
         None,
@@ -470,3 +550,463 @@ fn generate_stubs_force_preserves_sentinel_file() {
 
     fs::remove_dir_all(&dir).unwrap();
 }
+
+#[test]
+fn docker_files_render_with_project_name_and_config_port() {
+    use brrtrouter::generator::{write_docker_files, DockerOptions};
+
+    let dir = temp_dir();
+    let config = dir.join("config.yaml");
+    fs::write(
+        &config,
+        "port: 9123\ndocker:\n  healthcheck_interval_secs: 15\nhttp:\n  keep_alive: true\n",
+    )
+    .unwrap();
+    let options = DockerOptions::from_config_file(&config).unwrap();
+    assert_eq!(
+        options,
+        DockerOptions {
+            port: 9123,
+            healthcheck_interval_secs: 15
+        }
+    );
+    assert_eq!(
+        DockerOptions::from_config_file(&dir.join("missing.yaml")).unwrap(),
+        DockerOptions::default()
+    );
+
+    write_docker_files(&dir, "acme_orders", &options).unwrap();
+
+    let dockerfile = fs::read_to_string(dir.join("Dockerfile")).unwrap();
+    assert!(dockerfile.contains("cargo build --release --bin acme_orders"));
+    assert!(dockerfile.contains("/usr/local/bin/acme_orders"));
+    assert!(dockerfile.contains("EXPOSE 9123"));
+    assert!(dockerfile.contains("HEALTHCHECK --interval=15s"));
+    assert!(dockerfile.contains("http://127.0.0.1:9123/health"));
+    assert!(dockerfile.contains("USER app"));
+    assert!(
+        !dockerfile.contains("{{"),
+        "unrendered placeholder:\n{dockerfile}"
+    );
+
+    let compose = fs::read_to_string(dir.join("docker-compose.yml")).unwrap();
+    assert!(compose.contains("  acme_orders:"));
+    assert!(compose.contains("\"9123:9123\""));
+    assert!(compose.contains("OTEL_SERVICE_NAME: acme_orders"));
+    for service in ["prometheus:", "grafana:", "jaeger:"] {
+        assert!(compose.contains(service), "missing {service}");
+    }
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&compose).unwrap();
+    assert!(parsed["services"]["acme_orders"].is_mapping());
+
+    let prometheus = fs::read_to_string(dir.join("observability/prometheus.yml")).unwrap();
+    assert!(prometheus.contains("targets: ['acme_orders:9123']"));
+    assert!(dir.join("observability/grafana-datasources.yml").exists());
+}
+
+#[test]
+fn sse_operations_scaffold_a_typed_event_stream() {
+    let dir = temp_dir();
+    let event_fields = vec![FieldDef {
+        name: "n".into(),
+        original_name: "n".into(),
+        ty: "i32".into(),
+        optional: false,
+        nullable: false,
+        value: "1".into(),
+    }];
+
+    let handler_path = dir.join("ticks_handler.rs");
+    write_handler(
+        &handler_path,
+        "ticks",
+        &[],
+        &event_fields,
+        &BTreeSet::new(),
+        &[],
+        true,
+        false,
+        false,
+        &[],
+        None,
+        true,
+    )
+    .unwrap();
+    let handler = fs::read_to_string(&handler_path).unwrap();
+    assert!(handler.contains("pub struct Event {"), "{handler}");
+    assert!(handler.contains("impl SseEvent for Event {}"), "{handler}");
+    assert!(
+        handler.contains("pub type Response = SseResponse<Event>;"),
+        "{handler}"
+    );
+
+    let controller_path = dir.join("ticks_controller.rs");
+    write_controller(
+        &controller_path,
+        "ticks",
+        "TicksController",
+        &event_fields,
+        None,
+        &[],
+        true,
+        true,
+        None,
+        None,
+        "GET".to_string(),
+        false,
+    )
+    .unwrap();
+    let controller = fs::read_to_string(&controller_path).unwrap();
+    assert!(
+        controller.contains("let (events, stream) = SseResponse::channel();"),
+        "{controller}"
+    );
+    assert!(controller.contains("events.send(Event {"), "{controller}");
+    assert!(controller.contains("n: 1,"), "{controller}");
+    assert!(
+        controller.contains("stream.correlate(_req.extensions.get())"),
+        "{controller}"
+    );
+
+    // Without an event schema each event is a JSON string.
+    write_handler(
+        &handler_path,
+        "ticks",
+        &[],
+        &[],
+        &BTreeSet::new(),
+        &[],
+        true,
+        false,
+        false,
+        &[],
+        None,
+        true,
+    )
+    .unwrap();
+    let handler = fs::read_to_string(&handler_path).unwrap();
+    assert!(
+        handler.contains("pub struct Event(pub String);"),
+        "{handler}"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn declared_success_statuses_generate_a_declared_response_enum() {
+    let dir = temp_dir();
+    let res_fields = vec![FieldDef {
+        name: "id".into(),
+        original_name: "id".into(),
+        ty: "String".into(),
+        optional: false,
+        nullable: false,
+        value: "\"id\".to_string()".into(),
+    }];
+
+    let handler_path = dir.join("add_pet.rs");
+    write_handler(
+        &handler_path,
+        "add_pet",
+        &[],
+        &res_fields,
+        &BTreeSet::new(),
+        &[],
+        false,
+        false,
+        false,
+        &[201, 204],
+        Some(&CreatedLocation::new("/pets/{petId}", "petId")),
+        true,
+    )
+    .unwrap();
+    let handler = fs::read_to_string(&handler_path).unwrap();
+    assert!(handler.contains("pub enum DeclaredResponse {"), "{handler}");
+    assert!(
+        handler.contains("Created(brrtrouter::typed::Created<Response>),"),
+        "{handler}"
+    );
+    assert!(
+        handler.contains("NoContent(brrtrouter::typed::NoContent),"),
+        "{handler}"
+    );
+    assert!(
+        handler.contains("impl From<brrtrouter::typed::Created<Response>> for DeclaredResponse"),
+        "{handler}"
+    );
+    // Undeclared wrappers get no variant, so returning them does not compile.
+    assert!(!handler.contains("typed::Ok<Response>"), "{handler}");
+    assert!(!handler.contains("typed::Accepted"), "{handler}");
+    assert!(
+        handler.contains("-> impl brrtrouter::typed::HandlerResponseOutput {"),
+        "{handler}"
+    );
+    assert!(
+        handler.contains(
+            "pub fn location<T>(req: &TypedHandlerRequest<T>, id: impl std::fmt::Display)"
+        ),
+        "{handler}"
+    );
+    assert!(
+        handler.contains(r#"CreatedLocation::new("/pets/{petId}", "petId")"#),
+        "{handler}"
+    );
+
+    write_handler(
+        &handler_path,
+        "add_pet",
+        &[],
+        &res_fields,
+        &BTreeSet::new(),
+        &[],
+        false,
+        false,
+        false,
+        &[],
+        None,
+        true,
+    )
+    .unwrap();
+    let handler = fs::read_to_string(&handler_path).unwrap();
+    assert!(!handler.contains("DeclaredResponse"), "{handler}");
+    assert!(!handler.contains("pub fn location"), "{handler}");
+    assert!(
+        handler.contains("pub fn handler(req: TypedHandlerRequest<Request>) -> Response {"),
+        "{handler}"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn websocket_routes_register_an_echo_controller() {
+    let dir = temp_dir();
+    let src_dir = dir.join("src");
+    let controllers_dir = src_dir.join("controllers");
+    fs::create_dir_all(&controllers_dir).unwrap();
+
+    let entries = vec![RegistryEntry {
+        name: "chat".into(),
+        request_type: "chat::Request".into(),
+        controller_struct: "ChatController".into(),
+        parameters: vec![],
+        stack_size_bytes: 16384,
+        is_proxy: false,
+        is_websocket: true,
+    }];
+    write_registry_rs(&src_dir, &entries).unwrap();
+    let registry = fs::read_to_string(src_dir.join("registry.rs")).unwrap();
+    assert!(registry.contains(
+        "dispatcher.register_websocket_handler(\"chat\", crate::controllers::chat::handle);"
+    ));
+    assert!(registry.contains("dispatcher.websocket_handlers.clear();"));
+    assert!(!registry.contains("ChatController"));
+
+    let controller_path = controllers_dir.join("chat.rs");
+    write_websocket_controller(&controller_path, "chat", false).unwrap();
+    let controller = fs::read_to_string(&controller_path).unwrap();
+    assert!(controller.contains("pub fn handle(_req: WebSocketRequest, mut ws: WebSocketChannel)"));
+    assert!(controller.contains("ws.send(&message)"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// The generated `main.rs` for a spec without routes.
+fn generated_main() -> String {
+    let dir = temp_dir();
+    let src_dir = dir.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+    write_main_rs(&src_dir, "tester", vec![]).unwrap();
+    let main_content = fs::read_to_string(src_dir.join("main.rs")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    main_content
+}
+
+/// Routes of disabled `x-feature`s are filtered before anything is built from them, as in
+/// `run_app`.
+#[test]
+fn generated_main_drops_disabled_feature_routes() {
+    let main_content = generated_main();
+    let filtered = main_content
+        .find("brrtrouter::spec::enabled_routes(routes, &config.enabled_features)")
+        .expect("routes are not filtered by enabled features");
+    for use_of_routes in ["register_from_spec", "Router::new(routes.clone())"] {
+        let at = main_content.find(use_of_routes).unwrap();
+        assert!(
+            filtered < at,
+            "{use_of_routes} runs before the feature filter"
+        );
+    }
+    assert!(!main_content.contains("let _router"));
+}
+
+/// The generated `config.yaml` with each `(from, to)` replacement applied, parsed as the
+/// generated main parses it.
+fn generated_config(replacements: &[(&str, &str)]) -> AppConfig {
+    let dir = temp_dir();
+    write_default_config(&dir).unwrap();
+    let mut yaml = fs::read_to_string(dir.join("config.yaml")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    for (from, to) in replacements {
+        assert!(yaml.contains(from), "config.yaml has no {from:?}");
+        yaml = yaml.replace(from, to);
+    }
+    serde_yaml::from_str(&yaml).unwrap()
+}
+
+/// config.yaml reaches the service through `AppServiceBuilder::config`, not setters copied
+/// by hand, so every `http:` option it documents takes effect.
+#[test]
+fn generated_main_applies_http_config() {
+    let main_content = generated_main();
+    assert!(main_content.contains(".config(&app_config)"));
+    for setter in [
+        "set_connection_config",
+        "set_max_body_bytes",
+        "set_expect_continue",
+    ] {
+        assert!(!main_content.contains(setter), "{setter} is copied by hand");
+    }
+
+    let config = generated_config(&[
+        ("# max_headers: 100", "max_headers: 12"),
+        ("# max_header_bytes: 32768", "max_header_bytes: 2048"),
+    ]);
+    let limits = config.http.unwrap().header_limits();
+    assert_eq!((limits.max_headers, limits.max_header_bytes), (12, 2048));
+}
+
+/// `http.server_header` and `hide_server_header` from the generated config.yaml are applied
+/// with the rest of `http:` by `.config(&app_config)`.
+#[test]
+fn generated_config_sets_the_server_header() {
+    assert!(generated_main().contains(".config(&app_config)"));
+    let named = generated_config(&[("# server_header: \"BRRTRouter\"", "server_header: \"Acme\"")]);
+    assert_eq!(named.http.unwrap().server_header(), Some("Acme"));
+    let hidden = generated_config(&[("# hide_server_header: false", "hide_server_header: true")]);
+    assert_eq!(hidden.http.unwrap().server_header(), None);
+}
+
+/// The generated config.yaml's `query:` example parses, and the section reaches the service
+/// through `.config(&app_config)`.
+#[test]
+fn generated_config_sets_the_duplicate_query_policy() {
+    assert!(generated_main().contains(".config(&app_config)"));
+    let config = generated_config(&[(
+        "# query:\n#   duplicates: first\n#   strict_duplicates: false",
+        "query:\n  duplicates: last\n  strict_duplicates: true",
+    )]);
+    let query = config.query.unwrap();
+    assert_eq!(query.duplicates, DuplicateQueryPolicy::Last);
+    assert!(query.strict_duplicates);
+}
+
+/// `errors:` reaches the service through `.config(&app_config)`; `static_files.mime_types`
+/// is applied to the built service's static files, as `run_app` does.
+#[test]
+fn generated_config_sets_error_format_and_static_mime_types() {
+    let main = generated_main();
+    assert!(main.contains(".config(&app_config)"));
+    let build = main.find("builder.build()").unwrap();
+    let mime = main
+        .find("sf.with_mime_types(&static_cfg.mime_types)")
+        .unwrap();
+    assert!(build < mime);
+
+    let config = generated_config(&[
+        (
+            "# errors:\n#   format: negotiate\n#   html_template: ./static_site/error.html",
+            "errors:\n  format: json\n  html_template: ./static_site/error.html",
+        ),
+        (
+            "# static_files:\n#   mime_types:\n#     glb: model/gltf-binary",
+            "static_files:\n  mime_types:\n    glb: model/gltf-binary",
+        ),
+    ]);
+    let errors = config.errors.unwrap();
+    assert_eq!(errors.format, ErrorFormat::Json);
+    assert_eq!(
+        errors.html_template.as_deref(),
+        Some(std::path::Path::new("./static_site/error.html"))
+    );
+    assert_eq!(
+        config
+            .static_files
+            .unwrap()
+            .mime_types
+            .get("glb")
+            .map(String::as_str),
+        Some("model/gltf-binary")
+    );
+}
+
+/// The generated main registers providers through the library's `security_setup`, and a bad
+/// provider `tls:` section stops startup before the service is built.
+#[test]
+fn generated_main_checks_and_registers_security_from_config() {
+    let main = generated_main();
+    let load = main.find("serde_yaml::from_str::<AppConfig>").unwrap();
+    let tls = main.find("check_outbound_tls(&app_config)").unwrap();
+    let build = main.find("builder.build()").unwrap();
+    let register = main
+        .find("register_security_from_config(&mut service, &app_config,")
+        .unwrap();
+    assert!(load < tls && tls < build && build < register);
+    assert!(!main.contains("RemoteApiKeyProvider::new"));
+    assert!(!main.contains("JwksBearerProvider::new"));
+}
+
+/// An empty `allowed_algorithms` list aborts the generated main (`?` on
+/// `check_jwks_algorithms`) instead of logging and leaving the scheme without a provider.
+#[test]
+fn generated_main_rejects_invalid_jwks_algorithms_at_startup() {
+    let main = generated_main();
+    assert!(main.contains(".and_then(|()| check_jwks_algorithms(&app_config))"));
+    let check = main.find("check_jwks_algorithms(&app_config)").unwrap();
+    let abort = main[check..].find(")?;").unwrap();
+    assert!(check + abort < main.find("builder.build()").unwrap());
+    assert!(!main.contains("skip JwksBearerProvider"));
+
+    let config = generated_config(&[(
+        "  jwks:\n    # BearerAuth:",
+        "  jwks:\n    BearerAuth:\n      jwks_url: \"https://issuer.example/.well-known/jwks.json\"\n      allowed_algorithms: []\n    # BearerAuth:",
+    )]);
+    let err = check_jwks_algorithms(&config).unwrap_err();
+    assert!(err.starts_with("security.jwks.BearerAuth:"), "{err}");
+}
+
+/// API keys in the generated service use the library provider, so a configured `principal`
+/// reaches `HandlerRequest::auth_context` (see `auth_context_tests`).
+#[test]
+fn generated_config_api_key_principal_reaches_the_library_provider() {
+    let main = generated_main();
+    assert!(!main.contains("StaticApiKeyProvider"));
+    assert!(main.contains("register_security_from_config("));
+
+    let config = generated_config(&[(
+        "# principal: \"pet-store-client\"",
+        "principal: \"pet-store-client\"",
+    )]);
+    let api_keys = config.security.unwrap().api_keys.unwrap();
+    assert_eq!(
+        api_keys["ApiKeyHeader"].principal.as_deref(),
+        Some("pet-store-client")
+    );
+}
+
+/// The generated main has no mock credentials of its own: unconfigured schemes follow
+/// `security.strict_providers`, and a missing provider stops startup.
+#[test]
+fn generated_main_honours_the_default_provider_policy() {
+    let main = generated_main();
+    assert!(!main.contains("test123"));
+    assert!(!main.contains("\"sig\""));
+    let register = main.find("register_security_from_config(").unwrap();
+    assert!(main[register..].contains(
+        ".map_err(|e| io::Error::other(format!(\"invalid security configuration: {e}\")))?;"
+    ));
+
+    let config = generated_config(&[("# strict_providers: true", "strict_providers: true")]);
+    assert!(config.default_provider_policy().is_strict());
+}
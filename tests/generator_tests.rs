@@ -34,7 +34,7 @@
 //! generated code is type-safe and matches OpenAPI specifications
 
 use brrtrouter::generator::{
-    extract_fields, is_named_type, parameter_to_field, process_schema_type,
+    extract_fields, is_named_type, is_nullable_schema, parameter_to_field, process_schema_type,
     rust_literal_for_example, schema_to_type, to_camel_case, FieldDef, TypeDefinition,
 };
 use brrtrouter::spec::{ParameterLocation, ParameterMeta};
@@ -114,6 +114,7 @@ fn test_process_schema_type_and_parameter_to_field() {
         schema: Some(json!({"type": "boolean"})),
         style: None,
         explode: None,
+        deprecated: false,
     };
     let field = parameter_to_field(&param);
     assert_eq!(field.name, "flag");
@@ -129,6 +130,7 @@ fn test_rust_literal_for_example() {
         original_name: "count".to_string(),
         ty: "i32".to_string(),
         optional: false,
+        nullable: false,
         value: "0".to_string(),
     };
     let lit = rust_literal_for_example(&field, &json!(3));
@@ -147,6 +149,7 @@ fn test_rust_literal_for_example_string() {
         original_name: "name".to_string(),
         ty: "String".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -162,6 +165,7 @@ fn test_rust_literal_for_example_optional_string() {
         original_name: "nickname".to_string(),
         ty: "String".to_string(),
         optional: true,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -177,6 +181,7 @@ fn test_rust_literal_for_example_number() {
         original_name: "age".to_string(),
         ty: "i32".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -192,6 +197,7 @@ fn test_rust_literal_for_example_boolean() {
         original_name: "active".to_string(),
         ty: "bool".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -207,6 +213,7 @@ fn test_rust_literal_for_example_array() {
         original_name: "tags".to_string(),
         ty: "Vec<String>".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -225,6 +232,7 @@ fn test_rust_literal_for_example_array_numbers() {
         original_name: "scores".to_string(),
         ty: "Vec<i32>".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -240,6 +248,7 @@ fn test_rust_literal_for_example_json_value() {
         original_name: "metadata".to_string(),
         ty: "serde_json::Value".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -255,6 +264,7 @@ fn test_rust_literal_for_example_named_type() {
         original_name: "user".to_string(),
         ty: "User".to_string(),
         optional: false,
+        nullable: false,
         value: "default".to_string(),
     };
 
@@ -270,6 +280,7 @@ fn test_field_def_construction() {
         original_name: "test_field".to_string(),
         ty: "String".to_string(),
         optional: true,
+        nullable: false,
         value: "default_value".to_string(),
     };
 
@@ -287,6 +298,7 @@ fn test_type_definition_construction() {
             original_name: "id".to_string(),
             ty: "i32".to_string(),
             optional: false,
+            nullable: false,
             value: "0".to_string(),
         },
         FieldDef {
@@ -294,6 +306,7 @@ fn test_type_definition_construction() {
             original_name: "name".to_string(),
             ty: "String".to_string(),
             optional: false,
+            nullable: false,
             value: "String::new()".to_string(),
         },
     ];
@@ -359,6 +372,7 @@ fn test_parameter_to_field_variants() {
         schema: None,
         style: None,
         explode: None,
+        deprecated: false,
     };
     let f1 = parameter_to_field(&required);
     assert_eq!(f1.name, "id");
@@ -373,6 +387,7 @@ fn test_parameter_to_field_variants() {
         schema: Some(json!({"$ref": "#/components/schemas/pet"})),
         style: None,
         explode: None,
+        deprecated: false,
     };
     let f2 = parameter_to_field(&referenced);
     assert_eq!(f2.name, "pet");
@@ -380,3 +395,42 @@ fn test_parameter_to_field_variants() {
     assert!(f2.optional);
     assert_eq!(f2.value, "Some(Default::default())");
 }
+
+#[test]
+fn test_extract_fields_nullable() {
+    let schema = json!({
+        "type": "object",
+        "required": ["age", "nickname", "name"],
+        "properties": {
+            "age": {"type": ["integer", "null"]},
+            "nickname": {"oneOf": [{"type": "string"}, {"type": "null"}]},
+            "breed": {"type": "string", "nullable": true},
+            "tags": {"type": ["array", "null"], "items": {"type": "string"}},
+            "name": {"type": "string"},
+            "either": {"type": ["string", "integer"]}
+        }
+    });
+    let fields = extract_fields(&schema);
+    let field = |name: &str| fields.iter().find(|f| f.name == name).unwrap();
+
+    let age = field("age");
+    assert_eq!(age.ty, "i32");
+    assert!(age.optional && age.nullable);
+    let nickname = field("nickname");
+    assert_eq!(nickname.ty, "String");
+    assert!(nickname.optional && nickname.nullable);
+    let breed = field("breed");
+    assert_eq!(breed.ty, "String");
+    assert!(breed.optional && breed.nullable);
+    assert_eq!(field("tags").ty, "Vec<String>");
+    assert!(field("tags").nullable);
+
+    let name = field("name");
+    assert!(!name.optional && !name.nullable);
+    assert_eq!(field("either").ty, "serde_json::Value");
+    assert!(!field("either").nullable);
+
+    assert_eq!(schema_to_type(&json!({"type": ["integer", "null"]})), "i32");
+    assert!(is_nullable_schema(&json!({"type": ["integer", "null"]})));
+    assert!(!is_nullable_schema(&json!({"type": "integer"})));
+}
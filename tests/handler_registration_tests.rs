@@ -0,0 +1,145 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Checked registration: `Dispatcher::register` rejects names no route uses and names
+//! already taken, and `register_from_spec` reports routes left without a handler.
+
+use brrtrouter::dispatcher::{
+    Dispatcher, HandlerFn, HandlerRequest, HandlerResponse, RegistrationError,
+};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::RouteMeta;
+use serde_json::json;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Registration
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+    post:
+      operationId: add_pet
+      responses:
+        "201": { description: Created }
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn routes() -> (Vec<RouteMeta>, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+    (routes, dir)
+}
+
+fn ok(req: HandlerRequest) {
+    let _ = req
+        .reply_tx
+        .send(HandlerResponse::json(200, json!({"ok": true})));
+}
+
+#[test]
+fn register_rejects_unknown_route() {
+    let (routes, _dir) = routes();
+    let mut dispatcher = Dispatcher::new();
+
+    let err = dispatcher.register(&routes, "list_pet", ok).unwrap_err();
+    assert_eq!(err, RegistrationError::UnknownRoute("list_pet".to_string()));
+    assert_eq!(err.to_string(), "no route uses handler `list_pet`");
+    assert!(dispatcher.handlers.is_empty());
+}
+
+#[test]
+fn register_rejects_duplicates() {
+    let (routes, _dir) = routes();
+    let mut dispatcher = Dispatcher::new();
+
+    dispatcher.register(&routes, "list_pets", ok).unwrap();
+    assert_eq!(
+        dispatcher.register(&routes, "list_pets", ok),
+        Err(RegistrationError::Duplicate("list_pets".to_string()))
+    );
+
+    // Names taken through the unchecked path count too.
+    unsafe { dispatcher.register_handler("add_pet", ok) };
+    assert_eq!(
+        dispatcher.register(&routes, "add_pet", ok),
+        Err(RegistrationError::Duplicate("add_pet".to_string()))
+    );
+}
+
+#[test]
+fn register_from_spec_reports_routes_without_handlers() {
+    let (routes, _dir) = routes();
+    let mut dispatcher = Dispatcher::new();
+
+    let list_pets: HandlerFn = Arc::new(ok);
+    let err = dispatcher
+        .register_from_spec(&routes, [("list_pets", list_pets)])
+        .unwrap_err();
+    let RegistrationError::MissingHandlers(mut missing) = err else {
+        panic!("expected missing handlers, got {err:?}");
+    };
+    missing.sort_unstable();
+    assert_eq!(missing, ["add_pet", "get_pet"]);
+    assert!(dispatcher.handlers.contains_key("list_pets"));
+
+    let add_pet: HandlerFn = Arc::new(ok);
+    let get_pet: HandlerFn = Arc::new(ok);
+    dispatcher
+        .register_from_spec(&routes, [("add_pet", add_pet), ("get_pet", get_pet)])
+        .unwrap();
+    assert!(dispatcher.unhandled_routes(&routes).is_empty());
+}
+
+#[test]
+fn register_from_spec_stops_at_unknown_route() {
+    let (routes, _dir) = routes();
+    let mut dispatcher = Dispatcher::new();
+
+    let typo: HandlerFn = Arc::new(ok);
+    assert_eq!(
+        dispatcher.register_from_spec(&routes, [("get_pets", typo)]),
+        Err(RegistrationError::UnknownRoute("get_pets".to_string()))
+    );
+}
+
+#[test]
+fn registered_handlers_serve_requests() {
+    let (routes, dir) = routes();
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .register(&routes, "get_pet", |req: HandlerRequest| {
+            let id = req.get_path_param("id").map(str::to_string);
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({"id": id})));
+        })
+        .unwrap();
+
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(Default::default())
+        .spec_path(dir.path().join("openapi.yaml"))
+        .build()
+        .unwrap();
+    TestClient::new(service)
+        .get("/pets/7")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({"id": "7"}));
+}
@@ -0,0 +1,317 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `HEAD` requests: a path's `GET` operation answers them when the spec declares no `HEAD`,
+//! and the `Content-Length` is the `GET` body's, measured (`x-head-strategy: compute`, the
+//! default) or taken from the handler (`estimate`). Over a connection the response carries
+//! no body, so the connection stays usable for the next request; through the front listener
+//! of `start_with_websockets` its `Content-Length` on the wire is the `GET` body's too.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, TestClient};
+use brrtrouter::spec::HeadStrategy;
+use http::Method;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Head
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+    post:
+      operationId: add_pet
+      responses:
+        "201": { description: Created }
+  /reports/{id}:
+    get:
+      operationId: get_report
+      x-head-strategy: estimate
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema:
+                type: object
+                required: [title, rows]
+  /notes:
+    get:
+      operationId: list_notes
+      x-head-strategy: estimate
+      responses:
+        "200": { description: OK }
+  /uploads:
+    post:
+      operationId: upload
+      responses:
+        "201": { description: Created }
+"#;
+
+fn pets() -> Value {
+    json!([{ "name": "Rex", "tags": ["good", "dog"] }, { "name": "Tom" }])
+}
+
+fn report() -> Value {
+    json!({ "title": "Quarterly", "rows": [1, 2, 3] })
+}
+
+fn list_pets(req: HandlerRequest) {
+    let mut res = HandlerResponse::json(200, pets());
+    res.set_header("x-method", req.method.to_string());
+    let _ = req.reply_tx.send(res);
+}
+
+/// Reports its length to `HEAD` without building the report.
+fn get_report(req: HandlerRequest) {
+    let res = if req.method == Method::HEAD {
+        let mut res = HandlerResponse::json(200, Value::Null);
+        let length = serde_json::to_vec(&report()).unwrap().len();
+        res.set_header("content-length", length.to_string());
+        res
+    } else {
+        HandlerResponse::json(200, report())
+    };
+    let _ = req.reply_tx.send(res);
+}
+
+/// `estimate`, but answers `HEAD` like `GET`.
+fn list_notes(req: HandlerRequest) {
+    let _ = req
+        .reply_tx
+        .send(HandlerResponse::json(200, json!(["one", "two"])));
+}
+
+fn created(req: HandlerRequest) {
+    let _ = req.reply_tx.send(HandlerResponse::json(201, json!({})));
+}
+
+fn service() -> (AppService, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", list_pets);
+        dispatcher.register_handler("add_pet", created);
+        dispatcher.register_handler("get_report", get_report);
+        dispatcher.register_handler("list_notes", list_notes);
+        dispatcher.register_handler("upload", created);
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .build()
+        .unwrap();
+    (service, dir)
+}
+
+fn json_len(value: &Value) -> String {
+    serde_json::to_vec(value).unwrap().len().to_string()
+}
+
+#[test]
+fn x_head_strategy_is_recorded_on_the_route() {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _schemes, _slug) =
+        brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let strategy_of = |handler: &str| {
+        routes
+            .iter()
+            .find(|r| &*r.handler_name == handler)
+            .unwrap()
+            .head_strategy
+    };
+    assert_eq!(strategy_of("list_pets"), HeadStrategy::Compute);
+    assert_eq!(strategy_of("get_report"), HeadStrategy::Estimate);
+}
+
+#[test]
+fn compute_head_content_length_matches_get() {
+    let (service, _dir) = service();
+    let client = TestClient::new(service);
+
+    let get = client.get("/pets").send();
+    get.assert_status(200);
+    assert_eq!(get.body(), &pets());
+
+    let head = client.head("/pets").send();
+    head.assert_status(200)
+        .assert_header("content-length", &json_len(get.body()));
+    assert_eq!(head.body(), &Value::Null);
+    // The GET handler answered, and saw the request as HEAD.
+    head.assert_header("x-method", "HEAD");
+}
+
+#[test]
+fn estimate_takes_the_handlers_content_length() {
+    let (service, _dir) = service();
+    let client = TestClient::new(service);
+
+    let get = client.get("/reports/7").send();
+    get.assert_status(200);
+    // No body to validate against the schema's `required`, still 200.
+    client
+        .head("/reports/7")
+        .send()
+        .assert_status(200)
+        .assert_header("content-length", &json_len(get.body()));
+
+    // Without a handler estimate the body is measured.
+    let notes = client.get("/notes").send();
+    client
+        .head("/notes")
+        .send()
+        .assert_header("content-length", &json_len(notes.body()));
+}
+
+#[test]
+fn head_needs_a_get_operation() {
+    let (service, _dir) = service();
+    let client = TestClient::new(service);
+    client.head("/uploads").send().assert_status(405);
+    client
+        .get("/uploads")
+        .send()
+        .assert_status(405)
+        .assert_header("allow", "POST, OPTIONS");
+    client
+        .delete("/pets")
+        .send()
+        .assert_status(405)
+        .assert_header("allow", "GET, POST, HEAD, OPTIONS");
+}
+
+/// The `Content-Length` of a response head.
+fn content_length(head: &str) -> Option<usize> {
+    head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse().unwrap())
+}
+
+/// Read the next response from `stream`, starting with the bytes already in `pending`. A
+/// `HEAD` client reads no body; otherwise the body is read as `Content-Length` frames it.
+/// Returns the head and the body, leaving any further bytes in `pending`.
+fn read_response(stream: &mut TcpStream, pending: &mut Vec<u8>, head: bool) -> (String, String) {
+    let mut tmp = [0u8; 1024];
+    let header_end = loop {
+        if let Some(pos) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut tmp).unwrap();
+        assert!(n > 0, "connection closed before the response head");
+        pending.extend_from_slice(&tmp[..n]);
+    };
+    let response_head = String::from_utf8_lossy(&pending[..header_end]).to_string();
+    let content_length = if head {
+        0
+    } else {
+        content_length(&response_head).unwrap_or(0)
+    };
+    while pending.len() < header_end + content_length {
+        let n = stream.read(&mut tmp).unwrap();
+        assert!(n > 0, "connection closed mid-body");
+        pending.extend_from_slice(&tmp[..n]);
+    }
+    let body =
+        String::from_utf8_lossy(&pending[header_end..header_end + content_length]).to_string();
+    pending.drain(..header_end + content_length);
+    (response_head, body)
+}
+
+#[test]
+fn head_then_get_on_a_kept_alive_connection() {
+    let (service, _dir) = service();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    let mut pending = Vec::new();
+
+    stream
+        .write_all(b"HEAD /pets HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let (head, _) = read_response(&mut stream, &mut pending, true);
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert!(
+        head.to_ascii_lowercase().contains("x-method: head"),
+        "{head}"
+    );
+    assert!(
+        !head.to_ascii_lowercase().contains("connection: close"),
+        "{head}"
+    );
+
+    // A body sent with the `HEAD` response would be read as the start of this one.
+    stream
+        .write_all(b"GET /pets HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let (get, body) = read_response(&mut stream, &mut pending, false);
+    assert!(get.starts_with("HTTP/1.1 200"), "{get}");
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), pets());
+    assert!(
+        pending.is_empty(),
+        "unexpected bytes after the GET response"
+    );
+    handle.stop();
+}
+
+#[test]
+fn head_and_get_content_lengths_match_on_the_wire() {
+    let (service, _dir) = service();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start_with_websockets(addr).unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    let mut pending = Vec::new();
+    // Measured (`compute`) and handler-set (`estimate`) lengths alike.
+    for path in ["/pets", "/reports/7"] {
+        stream
+            .write_all(format!("HEAD {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .unwrap();
+        let (head, _) = read_response(&mut stream, &mut pending, true);
+        stream
+            .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .unwrap();
+        let (get, body) = read_response(&mut stream, &mut pending, false);
+        assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+        assert!(get.starts_with("HTTP/1.1 200"), "{get}");
+        assert_eq!(content_length(&get), Some(body.len()), "{get}");
+        assert_eq!(content_length(&head), Some(body.len()), "{head}");
+        assert!(
+            !head.to_ascii_lowercase().contains("x-brrtr-head-length"),
+            "{head}"
+        );
+    }
+    assert!(pending.is_empty(), "unexpected bytes after the responses");
+    handle.stop();
+}
@@ -0,0 +1,168 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Enforcement of `in: header` / `in: cookie` parameters declared in the spec: required
+//! presence (case-insensitive header names) and schema validation, answered with a 400
+//! problem naming the parameter and its location.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::Value;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Params
+  version: "1.0"
+paths:
+  /items:
+    get:
+      operationId: list_items
+      parameters:
+        - name: X-Tenant-Id
+          in: header
+          required: true
+          schema:
+            type: string
+            pattern: "^[a-z]+$"
+        - name: session
+          in: cookie
+          required: false
+          schema:
+            type: string
+            pattern: "^[0-9a-f]{8}$"
+      responses:
+        "200":
+          description: ok
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_items", |req: HandlerRequest| {
+            let body = serde_json::json!({
+                "ok": true,
+                "tenant": req.get_header("X-TENANT-ID"),
+                "session": req.get_cookie("session"),
+            });
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn get_items(server: &Server, extra_headers: &str) -> (u16, Value) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET /items HTTP/1.1\r\nHost: localhost\r\n{extra_headers}\r\n"),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let body = resp
+        .split_once("\r\n\r\n")
+        .and_then(|(_, b)| serde_json::from_str(b).ok())
+        .unwrap_or(Value::Null);
+    (status, body)
+}
+
+#[test]
+fn required_header_present_with_any_casing() {
+    let server = start();
+    let (status, body) = get_items(&server, "x-tenant-id: acme\r\n");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["tenant"], "acme");
+}
+
+#[test]
+fn required_header_absent_is_rejected() {
+    let server = start();
+    let (status, body) = get_items(&server, "");
+    assert_eq!(status, 400);
+    assert_eq!(body["parameter"], "X-Tenant-Id");
+    assert_eq!(body["in"], "header");
+    assert!(body["detail"].as_str().unwrap().contains("Missing"));
+}
+
+#[test]
+fn header_failing_pattern_is_rejected() {
+    let server = start();
+    let (status, body) = get_items(&server, "X-Tenant-Id: ACME-1\r\n");
+    assert_eq!(status, 400);
+    assert_eq!(body["parameter"], "X-Tenant-Id");
+    assert!(body["errors"].as_array().is_some_and(|e| !e.is_empty()));
+}
+
+#[test]
+fn cookie_failing_pattern_is_rejected() {
+    let server = start();
+    let (status, body) = get_items(&server, "X-Tenant-Id: acme\r\nCookie: session=nothex!\r\n");
+    assert_eq!(status, 400);
+    assert_eq!(body["parameter"], "session");
+    assert_eq!(body["in"], "cookie");
+
+    let (status, _) = get_items(&server, "X-Tenant-Id: acme\r\nCookie: session=deadbeef\r\n");
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn cookies_split_over_several_cookie_lines_are_all_seen() {
+    let server = start();
+    let (status, body) = get_items(
+        &server,
+        "X-Tenant-Id: acme\r\nCookie: theme=dark\r\ncookie: session=nothex!\r\n",
+    );
+    assert_eq!(status, 400, "{body}");
+    assert_eq!(body["parameter"], "session");
+
+    let (status, body) = get_items(
+        &server,
+        "X-Tenant-Id: acme\r\nCookie: theme=dark\r\nCOOKIE: session=deadbeef\r\n",
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["session"], "deadbeef");
+}
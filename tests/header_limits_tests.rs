@@ -0,0 +1,179 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Request line and header limits: an overlong URI is answered with `414 URI Too Long`, too
+//! many header lines or too many header bytes with `431 Request Header Fields Too Large`, all
+//! before routing; `http:` config sets the limits.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HeaderLimits, HttpConfig, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Limits
+  version: "1.0"
+paths:
+  /ping:
+    get:
+      operationId: ping
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+/// Requests with a `q` query parameter that reached the `ping` handler
+static PINGS: AtomicUsize = AtomicUsize::new(0);
+
+fn start(limits: HeaderLimits) -> Server {
+    start_with(|service| service.set_header_limits(limits))
+}
+
+fn start_with(configure: impl FnOnce(&mut AppService)) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("ping", |req: HandlerRequest| {
+            if req.get_query_param("q").is_some() {
+                PINGS.fetch_add(1, Ordering::SeqCst);
+            }
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({"pong": true})));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    configure(&mut service);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn ping(server: &Server, extra_headers: &str) -> (u16, String) {
+    get(server, "/ping", extra_headers)
+}
+
+fn get(server: &Server, target: &str, extra_headers: &str) -> (u16, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n{extra_headers}\r\n"),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, resp)
+}
+
+#[test]
+fn too_many_headers_get_431() {
+    let server = start(HeaderLimits {
+        max_headers: 4,
+        ..HeaderLimits::default()
+    });
+
+    let (status, resp) = ping(&server, "X-A: 1\r\nX-B: 2\r\n");
+    assert_eq!(status, 200, "{resp}");
+
+    let (status, resp) = ping(&server, "X-A: 1\r\nX-B: 2\r\nX-C: 3\r\nX-D: 4\r\n");
+    assert_eq!(status, 431, "{resp}");
+    assert!(resp.contains("application/problem+json"), "{resp}");
+    assert!(resp.contains("at most 4"), "{resp}");
+}
+
+#[test]
+fn oversized_headers_get_431() {
+    let server = start(HeaderLimits {
+        max_header_bytes: 256,
+        ..HeaderLimits::default()
+    });
+
+    let (status, resp) = ping(&server, &format!("X-Small: {}\r\n", "a".repeat(64)));
+    assert_eq!(status, 200, "{resp}");
+
+    let (status, resp) = ping(&server, &format!("X-Big: {}\r\n", "a".repeat(1024)));
+    assert_eq!(status, 431, "{resp}");
+    assert!(resp.contains("256 bytes"), "{resp}");
+}
+
+#[test]
+fn overlong_uri_gets_414_before_the_handler() {
+    let server = start_with(|service| service.set_max_uri_bytes(256));
+
+    let (status, resp) = get(&server, &format!("/ping?q={}", "a".repeat(64)), "");
+    assert_eq!(status, 200, "{resp}");
+
+    let before = PINGS.load(Ordering::SeqCst);
+    let (status, resp) = get(&server, &format!("/ping?q={}", "a".repeat(2048)), "");
+    assert_eq!(status, 414, "{resp}");
+    assert!(resp.contains("application/problem+json"), "{resp}");
+    assert!(resp.contains("at most 256"), "{resp}");
+    let (status, resp) = get(&server, &format!("/{}", "p".repeat(2048)), "");
+    assert_eq!(status, 414, "{resp}");
+    assert!(resp.contains("URI"), "{resp}");
+    assert_eq!(PINGS.load(Ordering::SeqCst), before);
+}
+
+#[test]
+fn http_config_sets_max_uri_bytes() {
+    let config: HttpConfig = serde_yaml::from_str("max_uri_bytes: 2048\n").unwrap();
+    assert_eq!(config.max_uri_bytes, Some(2048));
+    assert_eq!(HttpConfig::default().max_uri_bytes, None);
+}
+
+#[test]
+fn http_config_sets_header_limits() {
+    let config: HttpConfig =
+        serde_yaml::from_str("max_headers: 10\nmax_header_bytes: 2048\n").unwrap();
+    assert_eq!(
+        config.header_limits(),
+        HeaderLimits {
+            max_headers: 10,
+            max_header_bytes: 2048,
+        }
+    );
+    assert_eq!(
+        HttpConfig::default().header_limits(),
+        HeaderLimits::default()
+    );
+}
@@ -34,14 +34,15 @@ impl HealthTestServer {
             registry::register_from_spec(&mut dispatcher, &routes);
         }
         dispatcher.add_middleware(Arc::new(TracingMiddleware));
-        let service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            HashMap::new(),
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("examples/pet_store/static_site")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(HashMap::new())
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("examples/pet_store/static_site"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
         drop(listener);
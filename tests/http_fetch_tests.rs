@@ -90,6 +90,7 @@ fn fetch_get_http_returns_json_body() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 4096,
         extra_headers: Vec::new(),
+        tls: None,
     };
 
     let (status, bytes) = fetch_get(&url, &options).unwrap();
@@ -116,6 +117,7 @@ fn fetch_get_http_rejects_oversize_body() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 128,
         extra_headers: Vec::new(),
+        tls: None,
     };
     assert!(fetch_get(&url, &options).is_err());
     server.join().ok();
@@ -140,6 +142,7 @@ fn fetch_get_http_sends_extra_headers() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 1024,
         extra_headers: vec![("X-API-Key".to_string(), "validkey".to_string())],
+        tls: None,
     };
     let (status, bytes) = fetch_get(&url, &options).unwrap();
     assert_eq!(status, 200);
@@ -160,6 +163,7 @@ fn fetch_get_text_with_retry_succeeds_after_transient_failure() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 4096,
         extra_headers: Vec::new(),
+        tls: None,
     };
 
     let text = fetch_get_text_with_retry(&url, &options, 2);
@@ -174,6 +178,7 @@ fn fetch_get_text_with_retry_returns_none_when_all_attempts_fail() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 4096,
         extra_headers: Vec::new(),
+        tls: None,
     };
 
     assert!(fetch_get_text_with_retry(&url, &options, 2).is_none());
@@ -223,6 +228,7 @@ fn fetch_get_jwks_shaped_document_via_retry_helper() {
         timeout: Duration::from_millis(500),
         max_body_bytes: 256 * 1024,
         extra_headers: Vec::new(),
+        tls: None,
     };
 
     let body = fetch_get_text_with_retry(&url, &options, 2).expect("jwks body");
@@ -320,6 +326,7 @@ fn fetch_post_http_sends_json_body() {
         timeout: Duration::from_secs(2),
         max_body_bytes: 4096,
         extra_headers: vec![("content-type".to_string(), "application/json".to_string())],
+        tls: None,
     };
     let (status, body) =
         brrtrouter::http::fetch_post(&url, br#"{"user_id":"u1"}"#, &options).unwrap();
@@ -334,6 +341,7 @@ fn fetch_get_connect_error_on_dead_port() {
         timeout: Duration::from_millis(200),
         max_body_bytes: 1024,
         extra_headers: Vec::new(),
+        tls: None,
     };
     let result = fetch_get("http://127.0.0.1:1/closed", &options);
     assert!(result.is_err());
@@ -0,0 +1,102 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Request HTTP version: HTTP/1.0 connections close unless the client asks for keep-alive,
+//! HTTP/1.1 ones close when the client sends `Connection: close`, and requests older than
+//! `AppService::set_min_http_version` get `505`.
+
+use brrtrouter::dispatcher::Dispatcher;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, ConnectionConfig, HttpServer, HttpVersion, ServerHandle};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn start_service(min_version: HttpVersion) -> (ServerHandle, SocketAddr) {
+    may::config().set_stack_size(0x8000);
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full("examples/openapi.yaml").unwrap();
+    let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes)));
+    let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(Dispatcher::new()));
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .build()
+        .unwrap();
+    service.set_connection_config(ConnectionConfig {
+        keep_alive: true,
+        idle_timeout: Duration::from_secs(30),
+        max_requests_per_connection: None,
+    });
+    service.set_min_http_version(min_version);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    (handle, addr)
+}
+
+/// Send `GET /health` as `version` with `extra` header lines and return the lowercased
+/// response head.
+fn health(addr: SocketAddr, version: &str, extra: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    stream
+        .write_all(format!("GET /health {version}\r\nHost: localhost\r\n{extra}\r\n").as_bytes())
+        .unwrap();
+    let mut buf = Vec::new();
+    let mut tmp = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut tmp).unwrap();
+        assert!(n > 0, "connection closed before response");
+        buf.extend_from_slice(&tmp[..n]);
+    }
+    let text = String::from_utf8_lossy(&buf).to_ascii_lowercase();
+    text.split("\r\n\r\n").next().unwrap().to_string()
+}
+
+#[test]
+fn http_10_closes_by_default() {
+    let (handle, addr) = start_service(HttpVersion::Http10);
+    let head = health(addr, "HTTP/1.0", "");
+    assert!(head.contains(" 200 "), "{head}");
+    assert!(head.contains("connection: close"), "{head}");
+    assert!(!head.contains("keep-alive"), "{head}");
+    handle.stop();
+}
+
+#[test]
+fn http_10_keeps_alive_when_asked() {
+    let (handle, addr) = start_service(HttpVersion::Http10);
+    let head = health(addr, "HTTP/1.0", "Connection: keep-alive\r\n");
+    assert!(head.contains("connection: keep-alive"), "{head}");
+    assert!(head.contains("keep-alive: timeout=30"), "{head}");
+    handle.stop();
+}
+
+#[test]
+fn http_11_honours_connection_close() {
+    let (handle, addr) = start_service(HttpVersion::Http10);
+    let head = health(addr, "HTTP/1.1", "");
+    assert!(head.contains("connection: keep-alive"), "{head}");
+    let head = health(addr, "HTTP/1.1", "Connection: close\r\n");
+    assert!(head.contains("connection: close"), "{head}");
+    handle.stop();
+}
+
+#[test]
+fn versions_below_the_minimum_get_505() {
+    let (handle, addr) = start_service(HttpVersion::Http11);
+    let head = health(addr, "HTTP/1.0", "Connection: keep-alive\r\n");
+    assert!(head.contains(" 505 "), "{head}");
+    assert!(head.contains("connection: close"), "{head}");
+    let head = health(addr, "HTTP/1.1", "");
+    assert!(head.contains(" 200 "), "{head}");
+    handle.stop();
+}
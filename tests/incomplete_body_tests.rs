@@ -0,0 +1,209 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Truncated and stalled request bodies: a body shorter than its `Content-Length` is never
+//! parsed or handed to a handler, and a client that stops sending mid-body neither hangs the
+//! server nor gets its partial body processed. Both are answered with a problem and the
+//! connection is closed: `400` when the client ends the stream, `408` past the body read
+//! timeout.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Notes
+  version: "1.0"
+paths:
+  /notes:
+    post:
+      operationId: create_note
+      requestBody:
+        required: false
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    addr: SocketAddr,
+    handle: Option<ServerHandle>,
+    handled: Arc<AtomicUsize>,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(body_read_timeout: Option<Duration>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    let mut dispatcher = Dispatcher::new();
+    let count = handled.clone();
+    unsafe {
+        dispatcher.register_handler("create_note", move |req: HandlerRequest| {
+            count.fetch_add(1, Ordering::SeqCst);
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "ok": true })));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(spec_path)
+        .build()
+        .unwrap();
+    if let Some(timeout) = body_read_timeout {
+        service.set_body_read_timeout(timeout);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        addr,
+        handle: Some(handle),
+        handled,
+        _dir: dir,
+    }
+}
+
+/// Send a head declaring `declared` body bytes followed by `sent`, without finishing it
+fn send_partial(addr: &SocketAddr, declared: usize, sent: &[u8]) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let head = format!(
+        "POST /notes HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+         Content-Length: {declared}\r\n\r\n"
+    );
+    stream.write_all(head.as_bytes()).unwrap();
+    stream.write_all(sent).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+}
+
+/// Everything the server writes until it closes the connection; panics if it never does
+fn read_until_closed(mut stream: TcpStream) -> String {
+    let mut buf = Vec::new();
+    let mut tmp = [0u8; 1024];
+    loop {
+        match stream.read(&mut tmp) {
+            Ok(0) => break,
+            Ok(n) => buf.extend_from_slice(&tmp[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => break,
+            Err(e) => panic!("server kept the connection open: {e}"),
+        }
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// A problem response with `status` whose detail starts with `detail`, closing the connection
+fn assert_rejected(resp: &str, status: u16, detail: &str) {
+    assert!(resp.starts_with(&format!("HTTP/1.1 {status}")), "{resp}");
+    let lower = resp.to_ascii_lowercase();
+    assert!(lower.contains("connection: close"), "{resp}");
+    assert!(
+        lower.contains("content-type: application/problem+json"),
+        "{resp}"
+    );
+    let (_, body) = resp.split_once("\r\n\r\n").expect("response head");
+    let problem: serde_json::Value = serde_json::from_str(body).expect("problem body");
+    assert_eq!(problem["status"], status, "{resp}");
+    assert!(
+        problem["detail"]
+            .as_str()
+            .is_some_and(|d| d.starts_with(detail)),
+        "{resp}"
+    );
+}
+
+#[test]
+fn short_read_is_not_parsed() {
+    let server = start(None);
+    // The first 500 bytes are a complete JSON document padded with whitespace, so a
+    // truncated body would parse as valid if it were not checked against its length.
+    let mut sent = b"{\"text\":\"half\"}".to_vec();
+    sent.resize(500, b' ');
+    let stream = send_partial(&server.addr, 1000, &sent);
+    stream.shutdown(Shutdown::Write).unwrap();
+
+    let start = Instant::now();
+    let resp = read_until_closed(stream);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_rejected(&resp, 400, "Incomplete request body");
+    assert_eq!(server.handled.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn stalled_body_does_not_block_the_server() {
+    let server = start(None);
+    let stalled = send_partial(&server.addr, 1000, b"{\"text\":");
+
+    // Other connections are served while the stalled one waits for its body.
+    let body = "{\"text\":\"ok\"}";
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST /notes HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        ),
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+    assert_eq!(server.handled.load(Ordering::SeqCst), 1);
+
+    // The client giving up ends the request without its partial body being handled.
+    stalled.shutdown(Shutdown::Write).unwrap();
+    assert_rejected(&read_until_closed(stalled), 400, "Incomplete request body");
+    assert_eq!(server.handled.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn body_past_the_read_timeout_gets_408_and_closes() {
+    let timeout = Duration::from_millis(500);
+    let server = start(Some(timeout));
+    let mut stalled = send_partial(&server.addr, 1000, b"{\"text\":");
+
+    // Trickle a byte at a time until just past the deadline, then go quiet with the socket
+    // still open: the server checks the deadline when a read returns, and no byte is left
+    // unread when it closes.
+    let started = Instant::now();
+    while started.elapsed() <= timeout + Duration::from_millis(100) {
+        std::thread::sleep(Duration::from_millis(100));
+        stalled.write_all(b" ").unwrap();
+    }
+
+    assert_rejected(
+        &read_until_closed(stalled),
+        408,
+        "Request body not received in time",
+    );
+    assert_eq!(server.handled.load(Ordering::SeqCst), 0);
+}
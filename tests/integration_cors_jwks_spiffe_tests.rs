@@ -20,128 +20,15 @@ use brrtrouter::middleware::{CorsMiddleware, Middleware};
 use brrtrouter::router::ParamVec;
 use brrtrouter::security::{JwksBearerProvider, SecurityProvider, SecurityRequest, SpiffeProvider};
 use brrtrouter::spec::SecurityScheme;
+use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
 use http::Method;
 use may::sync::mpsc;
 use serde_json::json;
 use smallvec::smallvec;
-use std::io::{Read, Write};
-use std::net::TcpListener;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-// ============================================================================
-// Test Helpers
-// ============================================================================
-
-/// Start a mock JWKS server for testing
-/// Returns the URL to the JWKS endpoint
-fn start_mock_jwks_server(jwks: String) -> String {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let addr = listener.local_addr().unwrap();
-    let url = format!("http://127.0.0.1:{}/jwks.json", addr.port());
-
-    let jwks_clone = jwks;
-    thread::spawn(move || {
-        for stream in listener.incoming() {
-            match stream {
-                Ok(mut stream) => {
-                    let mut buf = [0u8; 2048];
-                    if stream.read(&mut buf).is_ok() {
-                        let resp = format!(
-                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                            jwks_clone.len(),
-                            jwks_clone
-                        );
-                        let _ = stream.write_all(resp.as_bytes());
-                        let _ = stream.flush();
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-    });
-
-    thread::sleep(Duration::from_millis(150));
-    url
-}
-
-/// Create a signed JWT token for JWKS testing
-fn make_signed_jwt_for_jwks(
-    secret: &[u8],
-    issuer: &str,
-    audience: &str,
-    kid: &str,
-    exp_secs: i64,
-) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        // RFC 9068: the JWKS provider only accepts access tokens typed "at+jwt"
-        typ: Some("at+jwt".to_string()),
-        ..Default::default()
-    };
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let claims = json!({
-        "iss": issuer,
-        "aud": audience,
-        "exp": now + exp_secs,
-        "iat": now
-    });
-
-    let encoding_key = EncodingKey::from_secret(secret);
-    jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap()
-}
-
-/// Create a signed SPIFFE JWT token
-fn make_signed_spiffe_jwt(
-    secret: &[u8],
-    spiffe_id: &str,
-    audience: &str,
-    kid: &str,
-    exp_secs: i64,
-) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        // RFC 9068: the JWKS provider only accepts access tokens typed "at+jwt"
-        typ: Some("at+jwt".to_string()),
-        ..Default::default()
-    };
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let claims = json!({
-        "sub": spiffe_id,
-        "aud": audience,
-        "exp": now + exp_secs,
-        "iat": now
-    });
-
-    let encoding_key = EncodingKey::from_secret(secret);
-    jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap()
-}
-
-/// Base64 URL-safe encoding without padding
-fn base64url_no_pad(data: &[u8]) -> String {
-    use base64::{engine::general_purpose, Engine as _};
-    general_purpose::URL_SAFE_NO_PAD.encode(data)
-}
-
 // ============================================================================
 // Test Category 1: CORS + JWKS Bearer Provider
 // ============================================================================
@@ -150,14 +37,8 @@ fn base64url_no_pad(data: &[u8]) -> String {
 fn test_cors_with_jwks_bearer_provider_preflight() {
     // Setup: Create JWKS provider and CORS middleware
     let secret = b"test-secret-key-for-jwks";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // JWKS provider created but not used in this test (only testing CORS preflight)
     let _jwks_provider = JwksBearerProvider::new(&jwks_url)
@@ -191,6 +72,12 @@ fn test_cors_with_jwks_bearer_provider_preflight() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should handle preflight before security validation
@@ -208,14 +95,8 @@ fn test_cors_with_jwks_bearer_provider_preflight() {
 fn test_cors_with_jwks_bearer_provider_authenticated_request() {
     // Setup: Create JWKS provider and CORS middleware
     let secret = b"test-secret-key-for-jwks";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let jwks_provider = JwksBearerProvider::new(&jwks_url)
         .issuer("https://auth.example.com")
@@ -227,13 +108,13 @@ fn test_cors_with_jwks_bearer_provider_authenticated_request() {
     let cors = CorsMiddleware::permissive();
 
     // Create a valid JWT token
-    let token = make_signed_jwt_for_jwks(
-        secret,
-        "https://auth.example.com",
-        "my-api",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .issuer("https://auth.example.com")
+        .audience("my-api")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test: Authenticated request should have CORS headers added
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
@@ -254,6 +135,12 @@ fn test_cors_with_jwks_bearer_provider_authenticated_request() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should not block the request (it's not a preflight)
@@ -284,14 +171,8 @@ fn test_cors_with_jwks_bearer_provider_authenticated_request() {
 fn test_cors_invalid_origin_before_jwks_validation() {
     // Setup: Create JWKS provider and restrictive CORS
     let secret = b"test-secret-key-for-jwks";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // JWKS provider created but not used in this test (only testing CORS origin rejection)
     let _jwks_provider = JwksBearerProvider::new(&jwks_url)
@@ -312,13 +193,13 @@ fn test_cors_invalid_origin_before_jwks_validation() {
     );
 
     // Create a valid JWT token
-    let token = make_signed_jwt_for_jwks(
-        secret,
-        "https://auth.example.com",
-        "my-api",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .issuer("https://auth.example.com")
+        .audience("my-api")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test: Invalid origin should be rejected by CORS before JWKS validation
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
@@ -339,6 +220,12 @@ fn test_cors_invalid_origin_before_jwks_validation() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should reject invalid origin
@@ -356,14 +243,8 @@ fn test_cors_invalid_origin_before_jwks_validation() {
 fn test_cors_with_spiffe_provider_preflight() {
     // Setup: Create SPIFFE provider and CORS middleware
     let secret = b"test-secret-key-for-spiffe";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let _spiffe_provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -397,6 +278,12 @@ fn test_cors_with_spiffe_provider_preflight() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should handle preflight before security validation
@@ -414,14 +301,8 @@ fn test_cors_with_spiffe_provider_preflight() {
 fn test_cors_with_spiffe_provider_authenticated_request() {
     // Setup: Create SPIFFE provider and CORS middleware
     let secret = b"test-secret-key-for-spiffe";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let spiffe_provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -434,13 +315,13 @@ fn test_cors_with_spiffe_provider_authenticated_request() {
     let cors = CorsMiddleware::permissive();
 
     // Create a valid SPIFFE JWT token
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test: Authenticated request should have CORS headers added
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
@@ -461,6 +342,12 @@ fn test_cors_with_spiffe_provider_authenticated_request() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should not block the request (it's not a preflight)
@@ -491,14 +378,8 @@ fn test_cors_with_spiffe_provider_authenticated_request() {
 fn test_cors_invalid_origin_before_spiffe_validation() {
     // Setup: Create SPIFFE provider and restrictive CORS
     let secret = b"test-secret-key-for-spiffe";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let _spiffe_provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -519,13 +400,13 @@ fn test_cors_invalid_origin_before_spiffe_validation() {
     );
 
     // Create a valid SPIFFE JWT token
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test: Invalid origin should be rejected by CORS before SPIFFE validation
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
@@ -546,6 +427,12 @@ fn test_cors_invalid_origin_before_spiffe_validation() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // CORS should reject invalid origin
@@ -563,14 +450,8 @@ fn test_cors_invalid_origin_before_spiffe_validation() {
 fn test_jwks_independent_usage() {
     // Test: JWKS provider should work without SPIFFE
     let secret = b"test-secret-key-for-jwks";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let jwks_provider = JwksBearerProvider::new(&jwks_url)
         .issuer("https://auth.example.com")
@@ -580,13 +461,13 @@ fn test_jwks_independent_usage() {
     thread::sleep(Duration::from_millis(200));
 
     // Create a non-SPIFFE JWT token (standard OAuth2 token)
-    let token = make_signed_jwt_for_jwks(
-        secret,
-        "https://auth.example.com",
-        "my-api",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .issuer("https://auth.example.com")
+        .audience("my-api")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test: JWKS provider should validate non-SPIFFE tokens
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
@@ -604,6 +485,12 @@ fn test_jwks_independent_usage() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let scheme = SecurityScheme::Http {
@@ -630,14 +517,8 @@ fn test_jwks_no_spiffe_dependency() {
     // This is a compile-time check - if this compiles, JWKS is independent
 
     let secret = b"test-secret-key-for-jwks";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // JWKS provider can be created and used without any SPIFFE imports
     let _jwks_provider = JwksBearerProvider::new(&jwks_url)
@@ -678,6 +559,12 @@ fn test_spiffe_requires_jwks_url() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let scheme = SecurityScheme::Http {
@@ -700,14 +587,8 @@ fn test_spiffe_requires_jwks_url() {
 fn test_spiffe_succeeds_with_jwks_url() {
     // Test: SPIFFE validation should succeed with JWKS URL configured
     let secret = b"test-secret-key-for-spiffe";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let spiffe_provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -718,13 +599,13 @@ fn test_spiffe_succeeds_with_jwks_url() {
     thread::sleep(Duration::from_millis(200));
 
     // Create a valid SPIFFE JWT token
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "test-kid",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("test-kid")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .expires_in(3600)
+        .claim_from_now("iat", 0)
+        .sign();
 
     let (tx, _rx) = mpsc::channel::<HandlerResponse>();
     let headers: HeaderVec = smallvec![(Arc::from("authorization"), format!("Bearer {}", token)),];
@@ -741,6 +622,12 @@ fn test_spiffe_succeeds_with_jwks_url() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let scheme = SecurityScheme::Http {
@@ -766,16 +653,9 @@ fn test_spiffe_succeeds_with_jwks_url() {
 fn test_spiffe_algorithm_mismatch_validation() {
     // Test: SPIFFE should validate algorithm mismatch (security requirement)
     let secret = b"test-secret-key-for-spiffe";
-    let k = base64url_no_pad(secret);
-
     // JWKS has HS256 key
-    let jwks = json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let spiffe_provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -828,6 +708,12 @@ fn test_spiffe_algorithm_mismatch_validation() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let scheme = SecurityScheme::Http {
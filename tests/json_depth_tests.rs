@@ -0,0 +1,154 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! JSON nesting limit: a body whose arrays and objects nest deeper than `http.max_json_depth`
+//! (default 64) is answered with `400` problem+json before it is parsed or validated, and
+//! never reaches the handler.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpConfig, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Depth
+  version: "1.0"
+paths:
+  /items:
+    post:
+      operationId: create_item
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+/// Requests that reached the `create_item` handler
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+fn start_with(configure: impl FnOnce(&mut AppService)) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("create_item", |req: HandlerRequest| {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "ok": true })));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    configure(&mut service);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn post(server: &Server, body: &str) -> (u16, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST /items HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        ),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, resp)
+}
+
+/// An object nesting `depth` objects: `{"a":{"a":...{}}}`
+fn nested(depth: usize) -> String {
+    format!(
+        "{}{{}}{}",
+        r#"{"a":"#.repeat(depth - 1),
+        "}".repeat(depth - 1)
+    )
+}
+
+#[test]
+fn over_deep_body_gets_400_before_the_handler() {
+    let server = start_with(|service| service.set_max_json_depth(8));
+
+    let (status, resp) = post(&server, &nested(8));
+    assert_eq!(status, 200, "{resp}");
+    // Brackets inside strings are not nesting.
+    let (status, resp) = post(&server, &json!({ "a": "[[[[[[[[[[{{{{{{{{{{" }).to_string());
+    assert_eq!(status, 200, "{resp}");
+
+    let before = CREATED.load(Ordering::SeqCst);
+    let (status, resp) = post(&server, &nested(9));
+    assert_eq!(status, 400, "{resp}");
+    assert!(resp.contains("application/problem+json"), "{resp}");
+    assert!(resp.contains("deeper than 8 levels"), "{resp}");
+    let (status, resp) = post(
+        &server,
+        &format!("{{\"a\":{}1{}}}", "[".repeat(8), "]".repeat(8)),
+    );
+    assert_eq!(status, 400, "{resp}");
+    assert_eq!(CREATED.load(Ordering::SeqCst), before);
+}
+
+#[test]
+fn default_limit_rejects_hostile_nesting() {
+    let server = start_with(|_| {});
+    // Far deeper than the parser or a 32 KiB coroutine stack could take
+    let (status, resp) = post(&server, &"[".repeat(100_000));
+    assert_eq!(status, 400, "{resp}");
+    assert!(resp.contains("deeper than 64 levels"), "{resp}");
+    let (status, resp) = post(&server, &nested(65));
+    assert_eq!(status, 400, "{resp}");
+}
+
+#[test]
+fn http_config_sets_max_json_depth() {
+    let config: HttpConfig = serde_yaml::from_str("max_json_depth: 16\n").unwrap();
+    assert_eq!(config.max_json_depth, Some(16));
+    assert_eq!(HttpConfig::default().max_json_depth, None);
+}
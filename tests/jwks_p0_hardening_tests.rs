@@ -1,11 +1,15 @@
 //! P0 JWT hardening tests for configured algorithms and unknown-key rotation.
 //!
 //! These tests cover the shared BRRTRouter behavior required for a hardened
-//! IdAM integration (P0): trusted algorithm allow-lists, immediate refresh after
-//! an unknown `kid`, and a cooldown that bounds attacker-triggered JWKS requests.
+//! IdAM integration (P0): trusted algorithm allow-lists, rejection of `alg: none`
+//! and of algorithms the key's type cannot verify, immediate refresh after
+//! an unknown `kid` (shared by concurrent misses), a cooldown that bounds
+//! attacker-triggered JWKS requests, and the sole-key fallback for tokens
+//! without a `kid`, plus dynamic token status and `jti` revocation on claims-cache hits.
 
 #![allow(clippy::expect_used, clippy::unwrap_used)]
 
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::TcpListener;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -14,9 +18,15 @@ use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64::Engine as _;
-use brrtrouter::dispatcher::HeaderVec;
-use brrtrouter::router::ParamVec;
-use brrtrouter::security::{JwksBearerProvider, JwtTokenStatus, SecurityProvider, SecurityRequest};
+use brrtrouter::dispatcher::{Dispatcher, HeaderVec};
+use brrtrouter::router::{ParamVec, Router};
+use brrtrouter::security::{
+    InMemoryRevocationChecker, JwksBearerProvider, JwtTokenStatus, SecurityProvider,
+    SecurityRequest,
+};
+use brrtrouter::server::app_config::AppConfig;
+use brrtrouter::server::security_setup::{check_jwks_algorithms, register_security_from_config};
+use brrtrouter::server::AppService;
 use brrtrouter::spec::SecurityScheme;
 use jsonwebtoken::{Algorithm, EncodingKey, Header};
 
@@ -48,17 +58,42 @@ fn token(secret: &[u8], kid: &str) -> String {
     jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
 }
 
+fn token_without_kid(secret: &[u8]) -> String {
+    let header = Header {
+        alg: Algorithm::HS256,
+        typ: Some("at+jwt".to_string()),
+        ..Header::default()
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let claims = serde_json::json!({
+        "sub": "test-subject",
+        "jti": "jti-no-kid",
+        "ver": 1,
+        "exp": now + 300,
+    });
+    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
+}
+
 fn jwks(secret: &[u8], kid: &str) -> String {
-    let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret);
-    serde_json::json!({
-        "keys": [{
-            "kty": "oct",
-            "alg": "HS256",
-            "kid": kid,
-            "k": encoded,
-        }]
-    })
-    .to_string()
+    jwks_keys(&[(secret, kid)])
+}
+
+fn jwks_keys(keys: &[(&[u8], &str)]) -> String {
+    let keys: Vec<_> = keys
+        .iter()
+        .map(|(secret, kid)| {
+            serde_json::json!({
+                "kty": "oct",
+                "alg": "HS256",
+                "kid": kid,
+                "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret),
+            })
+        })
+        .collect();
+    serde_json::json!({ "keys": keys }).to_string()
 }
 
 fn request(token: &str) -> (HeaderVec, ParamVec, HeaderVec) {
@@ -114,6 +149,133 @@ fn configured_asymmetric_allow_list_rejects_hmac_before_jwks_fetch() {
     assert!(!validate(&provider, &token(b"test-secret", "hmac-key")));
 }
 
+#[test]
+fn rs256_only_provider_rejects_hs256_even_with_a_matching_oct_key() {
+    let secret = b"shared-hmac-secret";
+    let (url, request_count) = start_jwks_server(jwks(secret, "oct-key"), jwks(secret, "oct-key"));
+
+    // Without an allowlist the oct key verifies the HS256 token.
+    let permissive = JwksBearerProvider::new(url.clone());
+    permissive.stop_background_refresh();
+    assert!(validate(&permissive, &token(secret, "oct-key")));
+    let fetched = request_count.load(Ordering::SeqCst);
+
+    let provider = JwksBearerProvider::new(url).allowed_algorithms(&[Algorithm::RS256]);
+    provider.stop_background_refresh();
+    assert!(!validate(&provider, &token(secret, "oct-key")));
+    // Rejected on the header alone: the JWKS was not consulted.
+    assert_eq!(request_count.load(Ordering::SeqCst), fetched);
+}
+
+#[test]
+fn allowed_algorithms_are_read_from_jwks_config() {
+    let config: brrtrouter::server::JwksConfig = serde_yaml::from_str(
+        "jwks_url: https://issuer.example/jwks.json\nallowed_algorithms: [RS256, EdDSA]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        config.allowed_algorithms,
+        Some(vec![Algorithm::RS256, Algorithm::EdDSA])
+    );
+}
+
+fn jwks_config(allowed_algorithms: &str) -> Result<AppConfig, serde_yaml::Error> {
+    serde_yaml::from_str(&format!(
+        "security:\n  jwks:\n    BearerAuth:\n      jwks_url: http://127.0.0.1:1/jwks.json\n      \
+         allowed_algorithms: {allowed_algorithms}\n"
+    ))
+}
+
+#[test]
+fn empty_allowed_algorithms_are_a_config_error_and_skip_the_provider() {
+    let config = jwks_config("[]").unwrap();
+    let err = check_jwks_algorithms(&config).unwrap_err();
+    assert_eq!(
+        err,
+        "security.jwks.BearerAuth: allowed_algorithms must contain at least one algorithm"
+    );
+
+    // Registration does not panic; the scheme is left without a provider (fails closed).
+    let mut service = AppService::builder()
+        .router(Router::new(Vec::new()))
+        .dispatcher(Dispatcher::new())
+        .security_schemes(HashMap::from([("BearerAuth".to_string(), bearer_scheme())]))
+        .spec_path("examples/openapi.yaml")
+        .build()
+        .unwrap();
+    register_security_from_config(&mut service, &config, None).unwrap();
+    assert!(!service.security_providers.contains_key("BearerAuth"));
+}
+
+#[test]
+fn unsupported_allowed_algorithms_are_a_config_error() {
+    // Names jsonwebtoken cannot verify never load.
+    let err = jwks_config("[RS256, ES512]").unwrap_err();
+    assert!(err.to_string().contains("ES512"), "{err}");
+
+    // Lists that load are checked against the supported set.
+    let config = jwks_config("[RS256, EdDSA]").unwrap();
+    assert_eq!(check_jwks_algorithms(&config), Ok(()));
+    assert!(JwksBearerProvider::check_allowed_algorithms(&[Algorithm::PS512]).is_ok());
+}
+
+#[test]
+fn alg_none_is_rejected() {
+    let secret = b"shared-hmac-secret";
+    let (url, _) = start_jwks_server(jwks(secret, "oct-key"), jwks(secret, "oct-key"));
+    let provider = JwksBearerProvider::new(url);
+    provider.stop_background_refresh();
+    let signed = token(secret, "oct-key");
+    assert!(validate(&provider, &signed));
+
+    // Same claims under an unsigned header, with and without the original signature.
+    let mut parts = signed.split('.');
+    let (_, payload, signature) = (parts.next(), parts.next().unwrap(), parts.next().unwrap());
+    for alg in ["none", "None", "NONE"] {
+        let header = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(
+            serde_json::json!({ "alg": alg, "typ": "at+jwt", "kid": "oct-key" }).to_string(),
+        );
+        assert!(
+            !validate(&provider, &format!("{header}.{payload}.")),
+            "{alg}"
+        );
+        assert!(
+            !validate(&provider, &format!("{header}.{payload}.{signature}")),
+            "{alg}"
+        );
+    }
+}
+
+#[test]
+fn hs256_token_is_rejected_against_an_rsa_key() {
+    let secret = b"shared-hmac-secret";
+    // The RSA modulus is public: an attacker can use it as an HMAC secret.
+    let modulus = [0xC3_u8; 256];
+    let jwks = serde_json::json!({ "keys": [
+        {
+            "kty": "oct",
+            "alg": "HS256",
+            "kid": "oct-key",
+            "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret),
+        },
+        {
+            "kty": "RSA",
+            "alg": "RS256",
+            "kid": "rsa-key",
+            "n": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(modulus),
+            "e": "AQAB",
+        },
+    ]})
+    .to_string();
+    let (url, _) = start_jwks_server(jwks.clone(), jwks);
+    let provider = JwksBearerProvider::new(url);
+    provider.stop_background_refresh();
+
+    assert!(validate(&provider, &token(secret, "oct-key")));
+    assert!(!validate(&provider, &token(&modulus, "rsa-key")));
+    assert!(!validate(&provider, &token(secret, "rsa-key")));
+}
+
 #[test]
 fn current_asymmetric_algorithm_set_can_be_configured() {
     let provider = JwksBearerProvider::new("http://127.0.0.1:1/jwks.json").allowed_algorithms(&[
@@ -146,6 +308,113 @@ fn unknown_kid_forces_refresh_before_normal_cache_ttl_expires() {
     assert_eq!(request_count.load(Ordering::SeqCst), 2);
 }
 
+#[test]
+fn added_kid_validates_after_one_forced_refresh() {
+    let old_secret = b"old-test-secret";
+    let new_secret = b"new-test-secret";
+    let (url, request_count) = start_jwks_server(
+        jwks(old_secret, "kid-old"),
+        jwks_keys(&[(old_secret, "kid-old"), (new_secret, "kid-new")]),
+    );
+    // Default cooldown: the first miss after startup may refresh at once.
+    let provider = JwksBearerProvider::new(url).cache_ttl(Duration::from_secs(300));
+    provider.stop_background_refresh();
+
+    assert!(validate(&provider, &token(old_secret, "kid-old")));
+    assert_eq!(request_count.load(Ordering::SeqCst), 1);
+    assert!(validate(&provider, &token(new_secret, "kid-new")));
+    assert_eq!(request_count.load(Ordering::SeqCst), 2);
+    // Both keys are cached now; neither needs another fetch.
+    assert!(validate(&provider, &token(old_secret, "kid-old")));
+    assert!(validate(&provider, &token(new_secret, "kid-new")));
+    assert_eq!(request_count.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn concurrent_kid_misses_share_one_refresh() {
+    let old_secret = b"herd-old-secret";
+    let new_secret = b"herd-new-secret";
+    let (url, request_count) = start_jwks_server(
+        jwks(old_secret, "kid-old"),
+        jwks_keys(&[(old_secret, "kid-old"), (new_secret, "kid-new")]),
+    );
+    let provider = Arc::new(
+        JwksBearerProvider::new(url)
+            .cache_ttl(Duration::from_secs(300))
+            .unknown_kid_refresh_cooldown(Duration::from_secs(60)),
+    );
+    provider.stop_background_refresh();
+    assert!(validate(&provider, &token(old_secret, "kid-old")));
+
+    // Every request racing on the rotated key waits for the one forced refresh instead of
+    // failing or fetching again.
+    let new_token = token(new_secret, "kid-new");
+    let barrier = Arc::new(std::sync::Barrier::new(8));
+    let results: Vec<bool> = (0..8)
+        .map(|_| {
+            let provider = Arc::clone(&provider);
+            let barrier = Arc::clone(&barrier);
+            let new_token = new_token.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                validate(&provider, &new_token)
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert!(results.iter().all(|ok| *ok), "{results:?}");
+    assert_eq!(request_count.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn token_without_kid_uses_the_sole_key() {
+    let secret = b"sole-key-secret";
+    let (url, _) = start_jwks_server(jwks(secret, "only-kid"), jwks(secret, "only-kid"));
+    let provider = JwksBearerProvider::new(url);
+    provider.stop_background_refresh();
+
+    let no_kid = token_without_kid(secret);
+    assert!(validate(&provider, &no_kid));
+    // The second validation is served from the claims cache.
+    assert!(validate(&provider, &no_kid));
+    let stats = provider.cache_stats();
+    assert_eq!((stats.misses, stats.hits), (1, 1));
+
+    // A token naming the same key gets its own cache entry.
+    assert!(validate(&provider, &token(secret, "only-kid")));
+    assert_eq!(provider.cache_stats().size, 2);
+
+    // Signed with another secret: the fallback key does not verify it.
+    assert!(!validate(&provider, &token_without_kid(b"other-secret")));
+}
+
+#[test]
+fn token_without_kid_is_ambiguous_with_several_keys() {
+    let first = b"first-of-two-secret";
+    let second = b"second-of-two-secret";
+    let both = jwks_keys(&[(first, "kid-a"), (second, "kid-b")]);
+    let (url, _) = start_jwks_server(both.clone(), both);
+    let provider = JwksBearerProvider::new(url);
+    provider.stop_background_refresh();
+
+    assert!(!validate(&provider, &token_without_kid(first)));
+    assert!(!validate(&provider, &token_without_kid(second)));
+    assert!(validate(&provider, &token(first, "kid-a")));
+}
+
+#[test]
+fn missing_kid_fallback_can_be_disabled() {
+    let secret = b"strict-kid-secret";
+    let (url, _) = start_jwks_server(jwks(secret, "only-kid"), jwks(secret, "only-kid"));
+    let provider = JwksBearerProvider::new(url).allow_missing_kid(false);
+    provider.stop_background_refresh();
+
+    assert!(!validate(&provider, &token_without_kid(secret)));
+    assert!(validate(&provider, &token(secret, "only-kid")));
+}
+
 #[test]
 fn unknown_kid_refreshes_are_rate_limited() {
     let secret = b"stable-test-secret";
@@ -225,3 +494,40 @@ fn dynamic_status_is_checked_once_before_claims_extraction() {
         .is_some());
     assert_eq!(checks.load(Ordering::SeqCst), 1);
 }
+
+#[test]
+fn revoked_jti_evicts_cached_claims_and_is_rejected() {
+    let secret = b"revocation-secret";
+    let stable_jwks = jwks(secret, "revocation-kid");
+    let (url, _) = start_jwks_server(stable_jwks.clone(), stable_jwks);
+    let revoked = Arc::new(InMemoryRevocationChecker::new());
+    let provider = JwksBearerProvider::new(url).revocation_checker(revoked.clone());
+    provider.stop_background_refresh();
+    let access_token = token(secret, "revocation-kid");
+
+    assert!(validate(&provider, &access_token));
+    assert_eq!(provider.cache_stats().size, 1);
+
+    revoked.revoke("jti-revocation-kid");
+    assert!(!validate(&provider, &access_token));
+    assert_eq!(provider.cache_stats().size, 0);
+    assert!(!validate(&provider, &access_token));
+    assert_eq!(provider.cache_stats().size, 0);
+
+    revoked.unrevoke("jti-revocation-kid");
+    assert!(validate(&provider, &access_token));
+}
+
+#[test]
+fn revoked_jti_is_rejected_without_being_cached() {
+    let secret = b"revoked-upfront-secret";
+    let stable_jwks = jwks(secret, "revoked-upfront-kid");
+    let (url, _) = start_jwks_server(stable_jwks.clone(), stable_jwks);
+    let revoked = Arc::new(InMemoryRevocationChecker::new());
+    revoked.revoke("jti-revoked-upfront-kid");
+    let provider = JwksBearerProvider::new(url).revocation_checker(revoked);
+    provider.stop_background_refresh();
+
+    assert!(!validate(&provider, &token(secret, "revoked-upfront-kid")));
+    assert_eq!(provider.cache_stats().size, 0);
+}
@@ -42,14 +42,15 @@ impl MetricsTestServer {
         let metrics = Arc::new(MetricsMiddleware::new());
         dispatcher.add_middleware(metrics.clone());
         dispatcher.add_middleware(Arc::new(TracingMiddleware));
-        let mut service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            schemes,
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("examples/pet_store/static_site")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let mut service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("examples/pet_store/static_site"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
         service.set_metrics_middleware(metrics);
 
         // Register a simple ApiKey provider so requests with X-API-Key: test123 are authorized
@@ -0,0 +1,169 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! JSON metrics snapshot: opt-in on a configurable path, reports the same counters as
+//! `/metrics` without counting itself, and leaves out excluded route templates.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::MetricsMiddleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Snapshot
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+  /internal/ping:
+    get:
+      operationId: ping
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(json_path: Option<&str>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in ["list_pets", "ping"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({ "ok": true })));
+            });
+        }
+    }
+    let metrics = Arc::new(MetricsMiddleware::new().with_excluded_paths(["/internal/ping"]));
+    dispatcher.add_middleware(metrics.clone());
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_metrics_middleware(metrics);
+    service.set_metrics_json_path(json_path.map(str::to_string));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, body) of `GET path`
+fn get(server: &Server, path: &str) -> (u16, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((resp.as_str(), ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, body.to_string())
+}
+
+fn snapshot(server: &Server, path: &str) -> Value {
+    let (status, body) = get(server, path);
+    assert_eq!(status, 200, "{body}");
+    serde_json::from_str(&body).unwrap()
+}
+
+#[test]
+fn snapshot_reports_counters_after_traffic() {
+    let server = start(Some("/metrics.json"));
+    for _ in 0..3 {
+        assert_eq!(get(&server, "/pets").0, 200);
+    }
+
+    let snap = snapshot(&server, "/metrics.json");
+    assert_eq!(snap["counters"]["requests_total"], 3, "{snap}");
+    assert_eq!(snap["gauges"]["active_requests"], 0);
+    let pets = &snap["paths"]["/pets"];
+    assert_eq!(pets["requests_total"], 3, "{snap}");
+    assert_eq!(pets["statuses"]["200"], 3, "{snap}");
+    assert_eq!(pets["duration_seconds"]["count"], 3);
+    let histogram = &snap["histograms"]["request_duration_seconds"];
+    assert_eq!(histogram["count"], 3);
+    let buckets = histogram["buckets"].as_array().unwrap();
+    assert_eq!(
+        buckets.last().unwrap(),
+        &json!({ "le": "+Inf", "count": 3 })
+    );
+}
+
+#[test]
+fn snapshot_does_not_count_itself_or_scrapes() {
+    let server = start(Some("/metrics.json"));
+    assert_eq!(get(&server, "/pets").0, 200);
+    let first = snapshot(&server, "/metrics.json");
+    assert_eq!(get(&server, "/metrics").0, 200);
+    let second = snapshot(&server, "/metrics.json");
+    assert_eq!(first["counters"]["requests_total"], 1);
+    assert_eq!(second["counters"]["requests_total"], 1);
+    assert_eq!(second["paths"]["/pets"], first["paths"]["/pets"]);
+    assert!(second["paths"].get("/metrics.json").is_none(), "{second}");
+}
+
+#[test]
+fn excluded_paths_are_left_out() {
+    let server = start(Some("/metrics.json"));
+    assert_eq!(get(&server, "/internal/ping").0, 200);
+    let snap = snapshot(&server, "/metrics.json");
+    assert!(snap["paths"].get("/internal/ping").is_none(), "{snap}");
+    assert_eq!(snap["counters"]["requests_total"], 0);
+
+    let (_, prometheus) = get(&server, "/metrics");
+    assert!(!prometheus.contains("/internal/ping"), "{prometheus}");
+}
+
+#[test]
+fn path_is_configurable_and_off_by_default() {
+    let server = start(Some("/internal/metrics"));
+    snapshot(&server, "/internal/metrics");
+    assert_eq!(get(&server, "/metrics.json").0, 404);
+
+    let server = start(None);
+    assert_eq!(get(&server, "/metrics.json").0, 404);
+}
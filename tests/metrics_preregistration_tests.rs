@@ -20,6 +20,19 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         x_service: None,
         x_brrtrouter_downstream_path: None,
         x_brrtrouter_impl: None,
+        auth_optional: false,
+        forward_claims: None,
+        tags: Vec::new(),
+        deprecated: false,
+        websocket: false,
+        links: Vec::new(),
+        declared_statuses: Vec::new(),
+        schema_overrides: Default::default(),
+        created_location: None,
+        response_headers: Default::default(),
+        upload_limits: Default::default(),
+        feature: None,
+        head_strategy: Default::default(),
         method,
         path_pattern: Arc::from(path),
         handler_name: Arc::from(handler),
@@ -30,6 +43,7 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         request_content_types: Vec::new(),
         response_schema: None,
         example: None,
+        examples: Vec::new(),
         responses: HashMap::new(),
         security: Vec::new(),
         example_name: "test_example".to_string(),
@@ -38,6 +52,8 @@ fn create_route_meta(method: Method, path: &str, handler: &str) -> RouteMeta {
         sse: false,
         estimated_request_body_bytes: None,
         x_brrtrouter_stack_size: None,
+        x_brrtrouter_timeout_ms: None,
+        x_slow_threshold_ms: None,
         cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
     }
 }
@@ -61,14 +77,13 @@ fn test_metrics_middleware_auto_preregisters_paths() {
     ));
 
     // Create the service
-    let mut service = AppService::new(
-        router,
-        dispatcher,
-        HashMap::new(),
-        PathBuf::from("test_spec.yaml"),
-        None,
-        None,
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(HashMap::new())
+        .spec_path(PathBuf::from("test_spec.yaml"))
+        .build()
+        .unwrap();
 
     // Create metrics middleware
     let metrics = Arc::new(MetricsMiddleware::new());
@@ -118,14 +133,13 @@ fn test_metrics_middleware_preregistration_with_base_path() {
         brrtrouter::dispatcher::Dispatcher::new(),
     ));
 
-    let mut service = AppService::new(
-        router,
-        dispatcher,
-        HashMap::new(),
-        PathBuf::from("test_spec.yaml"),
-        None,
-        None,
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(HashMap::new())
+        .spec_path(PathBuf::from("test_spec.yaml"))
+        .build()
+        .unwrap();
 
     let metrics = Arc::new(MetricsMiddleware::new());
     service.set_metrics_middleware(metrics.clone());
@@ -159,14 +173,13 @@ fn test_metrics_middleware_preregisters_parameterized_paths() {
         brrtrouter::dispatcher::Dispatcher::new(),
     ));
 
-    let mut service = AppService::new(
-        router,
-        dispatcher,
-        HashMap::new(),
-        PathBuf::from("test_spec.yaml"),
-        None,
-        None,
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(HashMap::new())
+        .spec_path(PathBuf::from("test_spec.yaml"))
+        .build()
+        .unwrap();
 
     let metrics = Arc::new(MetricsMiddleware::new());
     service.set_metrics_middleware(metrics.clone());
@@ -198,14 +211,13 @@ fn test_metrics_middleware_empty_router() {
         brrtrouter::dispatcher::Dispatcher::new(),
     ));
 
-    let mut service = AppService::new(
-        router,
-        dispatcher,
-        HashMap::new(),
-        PathBuf::from("test_spec.yaml"),
-        None,
-        None,
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(HashMap::new())
+        .spec_path(PathBuf::from("test_spec.yaml"))
+        .build()
+        .unwrap();
 
     let metrics = Arc::new(MetricsMiddleware::new());
     service.set_metrics_middleware(metrics.clone());
@@ -0,0 +1,194 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Config-driven middleware assembly (`middleware:` in `config.yaml`) and the behavior of the
+//! built-in `rate_limit`, `security_headers` and `compression` middleware it constructs.
+
+use brrtrouter::dispatcher::{HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::ids::RequestId;
+use brrtrouter::middleware::{
+    CompressionConfig, CompressionMiddleware, MetricsMiddleware, Middleware, RateLimitConfig,
+    RateLimitMiddleware, SecurityHeadersConfig, SecurityHeadersMiddleware,
+};
+use brrtrouter::router::ParamVec;
+use brrtrouter::server::{build_middleware_chain, AppConfig, MiddlewareConfigError};
+use http::Method;
+use may::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn request(headers: &[(&str, &str)]) -> HandlerRequest {
+    let (tx, _rx) = mpsc::channel::<HandlerResponse>();
+    let mut header_vec = HeaderVec::new();
+    for (k, v) in headers {
+        header_vec.push((Arc::from(*k), (*v).to_string()));
+    }
+    HandlerRequest {
+        request_id: RequestId::new(),
+        method: Method::GET,
+        path: "/items".into(),
+        handler_name: "list_items".into(),
+        path_params: ParamVec::new(),
+        query_params: ParamVec::new(),
+        headers: header_vec,
+        cookies: HeaderVec::new(),
+        body: None,
+        jwt_claims: None,
+        reply_tx: tx,
+        queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
+    }
+}
+
+fn config(yaml: &str) -> AppConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn chain_follows_config_order() {
+    let cfg = config(
+        r#"
+middleware:
+  - name: security_headers
+  - name: rate_limit
+    requests_per_window: 1
+    window_secs: 60
+  - name: compression
+"#,
+    );
+    let chain = build_middleware_chain(&cfg, &[], Arc::new(MetricsMiddleware::new())).unwrap();
+    assert_eq!(chain.len(), 3);
+
+    // Second request trips the rate limiter; the security headers entry still decorates it.
+    let req = request(&[]);
+    assert!(chain[1].before(&req).is_none());
+    let mut limited = chain[1]
+        .before(&req)
+        .expect("second request must be limited");
+    assert_eq!(limited.status, 429);
+    assert!(limited.get_header("retry-after").is_some());
+    chain[0].after(&req, &mut limited, Duration::ZERO);
+    assert_eq!(
+        limited.get_header("x-content-type-options"),
+        Some("nosniff")
+    );
+}
+
+#[test]
+fn unknown_middleware_fails_startup() {
+    let cfg = config("middleware:\n  - name: cors\n  - name: brotli\n");
+    let err = build_middleware_chain(&cfg, &[], Arc::new(MetricsMiddleware::new()))
+        .err()
+        .expect("unknown name must be rejected");
+    assert_eq!(
+        err,
+        MiddlewareConfigError::UnknownMiddleware {
+            index: 1,
+            name: "brotli".to_string(),
+        }
+    );
+    assert!(err.to_string().contains("rate_limit"));
+}
+
+#[test]
+fn rate_limit_keys_on_configured_header() {
+    let mw = RateLimitMiddleware::new(&RateLimitConfig {
+        requests_per_window: 1,
+        window_secs: 60,
+        key_header: Some("x-forwarded-for".to_string()),
+        max_tracked_keys: 100,
+    });
+    let alice = request(&[("x-forwarded-for", "10.0.0.1")]);
+    let bob = request(&[("x-forwarded-for", "10.0.0.2")]);
+
+    assert!(mw.before(&alice).is_none());
+    assert!(mw.before(&bob).is_none());
+    assert_eq!(mw.before(&alice).map(|r| r.status), Some(429));
+
+    // The proxy appends the real address last; a client-chosen prefix is not a new bucket.
+    let spoofed = request(&[("x-forwarded-for", "198.51.100.7, 10.0.0.1")]);
+    assert_eq!(mw.before(&spoofed).map(|r| r.status), Some(429));
+}
+
+#[test]
+fn rate_limit_shares_a_bucket_once_keys_are_exhausted() {
+    let mw = RateLimitMiddleware::new(&RateLimitConfig {
+        requests_per_window: 2,
+        window_secs: 60,
+        key_header: Some("x-api-key".to_string()),
+        max_tracked_keys: 2,
+    });
+    assert!(mw.before(&request(&[("x-api-key", "a")])).is_none());
+    assert!(mw.before(&request(&[("x-api-key", "b")])).is_none());
+
+    // Rotating keys in a full window all land in one bucket instead of growing the map.
+    assert!(mw.before(&request(&[("x-api-key", "c")])).is_none());
+    assert!(mw.before(&request(&[("x-api-key", "d")])).is_none());
+    assert_eq!(
+        mw.before(&request(&[("x-api-key", "e")])).map(|r| r.status),
+        Some(429)
+    );
+    // Tracked keys keep their own count.
+    assert!(mw.before(&request(&[("x-api-key", "a")])).is_none());
+}
+
+#[test]
+fn security_headers_do_not_override_handler_values() {
+    let mw = SecurityHeadersMiddleware::new(&SecurityHeadersConfig {
+        hsts_max_age_secs: Some(600),
+        hsts_include_subdomains: true,
+        ..SecurityHeadersConfig::default()
+    });
+    let req = request(&[]);
+    let mut res = HandlerResponse::json(200, serde_json::json!({}));
+    res.set_header("x-frame-options", "SAMEORIGIN".to_string());
+    mw.after(&req, &mut res, Duration::ZERO);
+
+    assert_eq!(res.get_header("x-frame-options"), Some("SAMEORIGIN"));
+    assert_eq!(res.get_header("referrer-policy"), Some("no-referrer"));
+    assert_eq!(
+        res.get_header("strict-transport-security"),
+        Some("max-age=600; includeSubDomains")
+    );
+}
+
+#[test]
+fn compression_negotiates_gzip_for_large_bodies_only() {
+    let mw = CompressionMiddleware::new(&CompressionConfig { min_size_bytes: 32 });
+    let big = serde_json::json!({ "items": vec!["value"; 16] });
+
+    let gzip_req = request(&[("accept-encoding", "gzip, br")]);
+    let mut res = HandlerResponse::json(200, big.clone());
+    mw.after(&gzip_req, &mut res, Duration::ZERO);
+    assert_eq!(res.get_header("content-encoding"), Some("gzip"));
+    assert_eq!(res.get_header("vary"), Some("Accept-Encoding"));
+
+    let mut small = HandlerResponse::json(200, serde_json::json!({"ok": true}));
+    mw.after(&gzip_req, &mut small, Duration::ZERO);
+    assert!(small.get_header("content-encoding").is_none());
+
+    let identity_req = request(&[("accept-encoding", "identity")]);
+    let mut res = HandlerResponse::json(200, big);
+    mw.after(&identity_req, &mut res, Duration::ZERO);
+    assert!(res.get_header("content-encoding").is_none());
+}
+
+#[test]
+fn compression_respects_an_explicit_gzip_refusal_over_the_wildcard() {
+    let mw = CompressionMiddleware::new(&CompressionConfig { min_size_bytes: 32 });
+    let big = serde_json::json!({ "items": vec!["value"; 16] });
+
+    let refused = request(&[("accept-encoding", "gzip;q=0, *")]);
+    let mut res = HandlerResponse::json(200, big.clone());
+    mw.after(&refused, &mut res, Duration::ZERO);
+    assert!(res.get_header("content-encoding").is_none());
+
+    let wildcard = request(&[("accept-encoding", "br;q=0, *")]);
+    let mut res = HandlerResponse::json(200, big);
+    mw.after(&wildcard, &mut res, Duration::ZERO);
+    assert_eq!(res.get_header("content-encoding"), Some("gzip"));
+}
@@ -36,6 +36,12 @@ fn create_test_request(method: Method, path: &str, headers: HeaderVec) -> Handle
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     }
 }
 
@@ -357,7 +363,7 @@ fn test_cors_preflight_denied_disallowed_method_returns_403() {
         .expect("denied preflight must short-circuit");
     assert_eq!(resp.status, 403);
     assert_eq!(
-        resp.body.get("error").and_then(serde_json::Value::as_str),
+        resp.body.get("detail").and_then(serde_json::Value::as_str),
         Some("CORS preflight request denied")
     );
     assert_eq!(resp.get_header("access-control-allow-origin"), None);
@@ -809,6 +815,12 @@ fn test_cors_metrics_sink_route_disabled() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
     assert!(cors.before(&req_get).is_none());
     assert_eq!(m.cors_route_disabled(), 1);
@@ -832,6 +844,12 @@ fn test_cors_metrics_sink_route_disabled() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
     assert!(cors.before(&req_opt).is_some());
     assert_eq!(m.cors_route_disabled(), 2);
@@ -1523,6 +1541,12 @@ fn test_cors_x_cors_false_disables_cors() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let resp = cors
@@ -1560,6 +1584,12 @@ fn test_cors_x_cors_false_disables_cors() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     // before() should not short-circuit (CORS disabled, so no validation)
@@ -1624,6 +1654,12 @@ fn test_cors_x_cors_inherit_uses_global_config() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let resp = cors
@@ -1661,6 +1697,12 @@ fn test_cors_x_cors_inherit_uses_global_config() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let mut resp2 = HandlerResponse::new(200, HeaderVec::new(), serde_json::Value::Null);
@@ -1727,6 +1769,12 @@ fn test_cors_x_cors_false_vs_inherit_distinction() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let mut resp_disabled = HandlerResponse::new(200, HeaderVec::new(), serde_json::Value::Null);
@@ -1751,6 +1799,12 @@ fn test_cors_x_cors_false_vs_inherit_distinction() {
         jwt_claims: None,
         reply_tx: mpsc::channel::<HandlerResponse>().0,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let mut resp_inherit = HandlerResponse::new(200, HeaderVec::new(), serde_json::Value::Null);
@@ -2041,3 +2095,71 @@ fn test_cors_same_origin_port_bug_fix() {
         "Host 'localhost:8080' vs Origin 'http://localhost:8080' should be same-origin"
     );
 }
+
+/// Records whether the body reaching `after` still contains `name`.
+struct BodyProbe(std::sync::Mutex<Option<bool>>);
+
+impl Middleware for BodyProbe {
+    fn after(&self, _req: &HandlerRequest, res: &mut HandlerResponse, _latency: Duration) {
+        *self.0.lock().unwrap() = Some(res.body.get("name").is_some());
+    }
+}
+
+#[test]
+fn test_response_transform_runs_before_middleware_after() {
+    use brrtrouter::middleware::FieldRedaction;
+
+    let _tracing = TestTracing::init();
+    let (routes, _slug) = load_spec("examples/openapi.yaml").unwrap();
+    let router = Router::new(routes.clone());
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        registry::register_from_spec(&mut dispatcher, &routes);
+    }
+    let probe = Arc::new(BodyProbe(std::sync::Mutex::new(None)));
+    dispatcher.add_middleware(probe.clone());
+    dispatcher.add_response_transform(Arc::new(
+        FieldRedaction::new(["name"]).unless_scope("pii:read"),
+    ));
+
+    let route_match = router.route(Method::GET, "/pets/12345").unwrap();
+    let resp = dispatcher
+        .dispatch(route_match, None, HeaderVec::new(), HeaderVec::new())
+        .unwrap();
+    assert_eq!(resp.status, 200);
+    assert!(resp.body.get("id").is_some());
+    assert!(resp.body.get("name").is_none());
+    assert_eq!(*probe.0.lock().unwrap(), Some(false));
+}
+
+#[test]
+fn test_field_redaction_respects_exempt_scope() {
+    use brrtrouter::middleware::{FieldRedaction, ResponseTransform};
+
+    let redaction = FieldRedaction::new(["ssn", "email"]).unless_scope("pii:read");
+    let body = serde_json::json!({
+        "id": 1,
+        "email": "a@example.com",
+        "contacts": [{"email": "b@example.com", "phone": "1"}],
+        "profile": {"ssn": "000-00-0000"}
+    });
+
+    let mut anonymous = body.clone();
+    let req = create_test_request(Method::GET, "/people/1", HeaderVec::new());
+    redaction.transform(&req, 200, &mut anonymous);
+    assert_eq!(
+        anonymous,
+        serde_json::json!({"id": 1, "contacts": [{"phone": "1"}], "profile": {}})
+    );
+
+    let mut scoped_req = create_test_request(Method::GET, "/people/1", HeaderVec::new());
+    scoped_req.jwt_claims = Some(serde_json::json!({"scp": ["pii:read"]}));
+    let mut privileged = body.clone();
+    redaction.transform(&scoped_req, 200, &mut privileged);
+    assert_eq!(privileged, body);
+
+    scoped_req.jwt_claims = Some(serde_json::json!({"scope": "read pii:write"}));
+    let mut wrong_scope = body;
+    redaction.transform(&scoped_req, 200, &mut wrong_scope);
+    assert!(wrong_scope.get("email").is_none());
+}
@@ -38,6 +38,7 @@ impl MultiResponseTestServer {
                 ResponseSpec {
                     schema: None,
                     example: None,
+                    examples: Vec::new(),
                 },
             );
             m.insert(201u16, inner);
@@ -48,6 +49,19 @@ impl MultiResponseTestServer {
             x_service: None,
             x_brrtrouter_downstream_path: None,
             x_brrtrouter_impl: None,
+            auth_optional: false,
+            forward_claims: None,
+            tags: Vec::new(),
+            deprecated: false,
+            websocket: false,
+            links: Vec::new(),
+            declared_statuses: Vec::new(),
+            schema_overrides: Default::default(),
+            created_location: None,
+            response_headers: Default::default(),
+            upload_limits: Default::default(),
+            feature: None,
+            head_strategy: Default::default(),
             method: Method::POST,
             path_pattern: Arc::from("/resp"),
             handler_name: Arc::from("h"),
@@ -57,6 +71,7 @@ impl MultiResponseTestServer {
             request_content_types: Vec::new(),
             response_schema: None,
             example: None,
+            examples: Vec::new(),
             responses,
             security: vec![],
             example_name: String::new(),
@@ -66,6 +81,8 @@ impl MultiResponseTestServer {
             sse: false,
             estimated_request_body_bytes: None,
             x_brrtrouter_stack_size: None,
+            x_brrtrouter_timeout_ms: None,
+            x_slow_threshold_ms: None,
             cors_policy: brrtrouter::middleware::RouteCorsPolicy::Inherit,
         };
 
@@ -79,14 +96,15 @@ impl MultiResponseTestServer {
         }
 
         // Include static and doc directories for comprehensive integration testing
-        let service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            HashMap::new(),
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("examples/pet_store/static_site")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(HashMap::new())
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("examples/pet_store/static_site"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
 
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
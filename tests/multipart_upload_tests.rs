@@ -0,0 +1,215 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Multipart uploads: `build_routes` reads per-field limits from the `multipart/form-data`
+//! schema, a part over `x-brrtrouter-max-bytes` is answered `413` and one of a type the field
+//! does not accept `415`, both before the handler runs; an accepted file reaches a typed
+//! handler as an [`UploadedFile`].
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, MultipartPart, TestClient};
+use brrtrouter::spec::UploadLimit;
+use brrtrouter::typed::{Handler, TypedHandlerRequest, UploadedFile};
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Photos
+  version: "1.0"
+paths:
+  /photos:
+    post:
+      operationId: upload_photo
+      requestBody:
+        required: true
+        content:
+          multipart/form-data:
+            schema:
+              type: object
+              properties:
+                title: { type: string }
+                photo:
+                  type: string
+                  format: binary
+                  x-brrtrouter-max-bytes: 16
+            encoding:
+              photo:
+                contentType: image/png, image/jpeg
+      responses:
+        "200": { description: OK }
+"#;
+
+const BOUNDARY: &str = "photo-boundary";
+
+/// Requests that reached the `upload_photo` handler
+static UPLOADED: AtomicUsize = AtomicUsize::new(0);
+
+struct UploadRequest;
+
+impl TryFrom<HandlerRequest> for UploadRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(_req: HandlerRequest) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+struct UploadPhoto;
+
+impl Handler for UploadPhoto {
+    type Request = UploadRequest;
+    type Response = Value;
+
+    fn handle(&self, req: TypedHandlerRequest<UploadRequest>) -> Value {
+        UPLOADED.fetch_add(1, Ordering::SeqCst);
+        let file = req.uploaded_file("photo").unwrap().unwrap();
+        let mut content = Vec::new();
+        file.reader().unwrap().read_to_end(&mut content).unwrap();
+        json!({
+            "filename": file.filename(),
+            "content_type": file.content_type(),
+            "len": file.len(),
+            "content": String::from_utf8_lossy(&content),
+            "missing": req.uploaded_file("other").is_none(),
+        })
+    }
+}
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_typed("upload_photo", UploadPhoto);
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+/// A multipart body with a `title` field and a `photo` file
+fn body(photo_type: &str, photo: &str) -> Vec<u8> {
+    format!(
+        "--{BOUNDARY}\r\n\
+         Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+         Cat\r\n\
+         --{BOUNDARY}\r\n\
+         Content-Disposition: form-data; name=\"photo\"; filename=\"cat.png\"\r\n\
+         Content-Type: {photo_type}\r\n\r\n\
+         {photo}\r\n\
+         --{BOUNDARY}--\r\n"
+    )
+    .into_bytes()
+}
+
+fn upload(client: &TestClient, photo_type: &str, photo: &str) -> brrtrouter::server::TestResponse {
+    client
+        .post("/photos")
+        .body(
+            &format!("multipart/form-data; boundary={BOUNDARY}"),
+            body(photo_type, photo),
+        )
+        .send()
+}
+
+#[test]
+fn build_routes_records_upload_limits() {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+    let limits = &routes[0].upload_limits;
+    assert_eq!(limits.len(), 1, "{limits:?}");
+    assert_eq!(
+        limits["photo"],
+        UploadLimit {
+            max_bytes: Some(16),
+            content_types: vec!["image/png".to_string(), "image/jpeg".to_string()],
+        }
+    );
+    assert!(limits["photo"].accepts_content_type("Image/PNG; x=1"));
+    assert!(!limits["photo"].accepts_content_type("image/gif"));
+}
+
+#[test]
+fn allowed_upload_reaches_the_handler() {
+    let (client, _dir) = client();
+    upload(&client, "image/png", "png bytes")
+        .assert_status(200)
+        .assert_json_at("/filename", json!("cat.png"))
+        .assert_json_at("/content_type", json!("image/png"))
+        .assert_json_at("/len", json!(9))
+        .assert_json_at("/content", json!("png bytes"))
+        .assert_json_at("/missing", json!(true));
+}
+
+#[test]
+fn oversize_upload_gets_413_before_the_handler() {
+    let (client, _dir) = client();
+    let before = UPLOADED.load(Ordering::SeqCst);
+    upload(&client, "image/png", &"x".repeat(17))
+        .assert_status(413)
+        .assert_header("content-type", "application/problem+json")
+        .assert_json_at("/field", json!("photo"))
+        .assert_json_at("/max_bytes", json!(16));
+    assert_eq!(UPLOADED.load(Ordering::SeqCst), before);
+}
+
+#[test]
+fn disallowed_content_type_gets_415_before_the_handler() {
+    let (client, _dir) = client();
+    let before = UPLOADED.load(Ordering::SeqCst);
+    upload(&client, "application/x-msdownload", "MZ")
+        .assert_status(415)
+        .assert_json_at("/field", json!("photo"))
+        .assert_json_at("/accepted", json!(["image/png", "image/jpeg"]));
+    assert_eq!(UPLOADED.load(Ordering::SeqCst), before);
+}
+
+#[test]
+fn large_upload_spills_to_a_file_the_handler_can_keep() {
+    let part = MultipartPart {
+        name: "photo".to_string(),
+        filename: Some("cat.png".to_string()),
+        content_type: Some("image/png".to_string()),
+        data: Bytes::from_static(b"0123456789"),
+    };
+
+    let in_memory = UploadedFile::from_part(&part, 10).unwrap();
+    assert!(in_memory.path().is_none());
+
+    let spilled = UploadedFile::from_part(&part, 4).unwrap();
+    let path = spilled.path().unwrap().to_path_buf();
+    assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "only the owner may read an upload");
+    }
+    drop(spilled);
+    assert!(
+        !path.exists(),
+        "a dropped upload removes its temporary file"
+    );
+
+    let kept = UploadedFile::from_part(&part, 4)
+        .unwrap()
+        .into_path()
+        .unwrap();
+    assert_eq!(std::fs::read(&kept).unwrap(), b"0123456789");
+    std::fs::remove_file(kept).unwrap();
+}
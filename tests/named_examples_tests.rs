@@ -0,0 +1,189 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Named response examples (`examples` map): default selection, `$ref` resolution,
+//! schema checks that only warn, and `?__example=<name>` arms in generated mock controllers.
+
+use brrtrouter::generator::{write_controller, FieldDef};
+use brrtrouter::spec::{build_routes, invalid_response_examples, RouteMeta};
+use oas3::OpenApiV3Spec;
+use serde_json::json;
+
+const SPEC: &str = r#"openapi: 3.1.0
+info:
+  title: Examples
+  version: '1.0'
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name: { type: string }
+        age: { type: integer }
+  examples:
+    Rex:
+      value: { name: Rex, age: 7 }
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        '200':
+          description: Ok
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+              examples:
+                broken:
+                  value: { age: old }
+                default:
+                  value: { name: Fluffy, age: 3 }
+                dog:
+                  $ref: '#/components/examples/Rex'
+        '404':
+          description: Missing
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  error: { type: string }
+              example: { error: 42 }
+  /owners:
+    get:
+      operationId: list_owners
+      responses:
+        '200':
+          description: Ok
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  name: { type: string }
+              examples:
+                alice:
+                  value: { name: Alice }
+                bob:
+                  value: { name: Bob }
+"#;
+
+fn routes() -> Vec<RouteMeta> {
+    let spec: OpenApiV3Spec = serde_yaml::from_str(SPEC).unwrap();
+    build_routes(&spec, "examples").unwrap()
+}
+
+fn route(routes: &[RouteMeta], handler: &str) -> RouteMeta {
+    routes
+        .iter()
+        .find(|r| r.handler_name.as_ref() == handler)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn default_named_example_is_preferred_and_refs_resolve() {
+    let routes = routes();
+    let pet = route(&routes, "get_pet");
+
+    assert_eq!(pet.example, Some(json!({"name": "Fluffy", "age": 3})));
+    let names: Vec<&str> = pet.examples.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["broken", "default", "dog"]);
+    let dog = pet.examples.iter().find(|(n, _)| n == "dog").unwrap();
+    assert_eq!(dog.1, json!({"name": "Rex", "age": 7}));
+
+    let spec = &pet.responses[&200]["application/json"];
+    assert_eq!(spec.examples, pet.examples);
+    assert_eq!(spec.example, pet.example);
+}
+
+#[test]
+fn first_named_example_is_the_default_without_one_named_default() {
+    let routes = routes();
+    let owners = route(&routes, "list_owners");
+    assert_eq!(owners.example, Some(json!({"name": "Alice"})));
+    assert_eq!(owners.examples.len(), 2);
+}
+
+#[test]
+fn examples_not_matching_their_schema_are_reported() {
+    let routes = routes();
+    let warnings = invalid_response_examples(&route(&routes, "get_pet"));
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings[0].contains("200 application/json: example 'broken'"));
+    assert!(warnings[1].contains("404 application/json: example 'example'"));
+    assert!(warnings[1].contains("#/error"), "{warnings:?}");
+
+    assert!(invalid_response_examples(&route(&routes, "list_owners")).is_empty());
+}
+
+#[test]
+fn mock_controller_selects_named_example_from_query() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("list_owners.rs");
+    let owners = route(&routes(), "list_owners");
+    let res = vec![FieldDef {
+        name: "name".to_string(),
+        original_name: "name".to_string(),
+        ty: "String".to_string(),
+        optional: true,
+        nullable: false,
+        value: "None".to_string(),
+    }];
+    write_controller(
+        &path,
+        "list_owners",
+        "ListOwnersController",
+        &res,
+        owners.example.clone(),
+        &owners.examples,
+        false,
+        true,
+        None,
+        None,
+        "GET".to_string(),
+        false,
+    )
+    .unwrap();
+
+    let code = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        code.contains(r#"_req.query_params.get("__example")"#),
+        "{code}"
+    );
+    assert!(code.contains("Some(\"alice\") => r###\"{\n  \"name\": \"Alice\"\n}\"###,"));
+    assert!(code.contains("Some(\"bob\") => r###\"{\n  \"name\": \"Bob\"\n}\"###,"));
+    assert!(code.contains("_ => r###\"{\n  \"name\": \"Alice\"\n}\"###,"));
+    assert!(code.contains("serde_json::from_str::<Response>(example)"));
+}
+
+#[test]
+fn mock_controller_without_named_examples_is_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("single.rs");
+    write_controller(
+        &path,
+        "single",
+        "SingleController",
+        &[],
+        Some(json!({"ok": true})),
+        &[],
+        false,
+        true,
+        None,
+        None,
+        "GET".to_string(),
+        false,
+    )
+    .unwrap();
+
+    let code = std::fs::read_to_string(&path).unwrap();
+    assert!(!code.contains("__example"));
+    assert!(code.contains("serde_json::from_str::<Response>(r###\"{\n  \"ok\": true\n}\"###)"));
+}
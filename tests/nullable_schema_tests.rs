@@ -0,0 +1,131 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Nullable fields: OpenAPI 3.1 type arrays (`type: [integer, "null"]`), a `oneOf` with a
+//! null schema and 3.0 `nullable: true` accept `null` in request bodies and handler
+//! responses. Required nullable fields must still be present; optional ones may be absent.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r##"
+openapi: 3.1.0
+info:
+  title: Nullable
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: echo_pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Pet" }
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [age, nickname]
+      properties:
+        age: { type: [integer, "null"] }
+        nickname:
+          oneOf:
+            - { type: string }
+            - { type: "null" }
+        breed: { type: string, nullable: true }
+        name: { type: string }
+"##;
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("echo_pet", |req: HandlerRequest| {
+            let body = req.body.clone().unwrap_or_default();
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn present_values_round_trip() {
+    let (client, _dir) = client();
+    let pet = json!({"age": 3, "nickname": "Rex", "breed": "collie", "name": "Rex"});
+    client
+        .post("/pets")
+        .json(pet.clone())
+        .send()
+        .assert_status(200)
+        .assert_json(&pet);
+}
+
+#[test]
+fn null_values_round_trip() {
+    let (client, _dir) = client();
+    let pet = json!({"age": null, "nickname": null, "breed": null});
+    client
+        .post("/pets")
+        .json(pet.clone())
+        .send()
+        .assert_status(200)
+        .assert_json(&pet);
+}
+
+#[test]
+fn optional_nullable_fields_may_be_absent() {
+    let (client, _dir) = client();
+    let pet = json!({"age": 1, "nickname": null});
+    client
+        .post("/pets")
+        .json(pet.clone())
+        .send()
+        .assert_status(200)
+        .assert_json(&pet);
+}
+
+#[test]
+fn required_nullable_fields_must_be_present() {
+    let (client, _dir) = client();
+    client
+        .post("/pets")
+        .json(json!({"nickname": null}))
+        .send()
+        .assert_status(400);
+}
+
+#[test]
+fn null_is_rejected_where_not_allowed() {
+    let (client, _dir) = client();
+    client
+        .post("/pets")
+        .json(json!({"age": "three", "nickname": null}))
+        .send()
+        .assert_status(400);
+    client
+        .post("/pets")
+        .json(json!({"age": 1, "nickname": null, "name": null}))
+        .send()
+        .assert_status(400);
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "arbitrary-precision")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! With the `arbitrary-precision` feature, JSON numbers keep their original text through
+//! request parsing, schema validation, dispatch and response serialization: a 20-digit
+//! integer and a decimal with trailing zeros come back byte-for-byte.
+//!
+//! Run with `cargo test --features arbitrary-precision --test number_precision_tests`.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::Value;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Numbers
+  version: "1.0"
+paths:
+  /payments:
+    post:
+      operationId: echo_payment
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Payment'
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Payment'
+components:
+  schemas:
+    Payment:
+      type: object
+      required: [id, amount]
+      properties:
+        id: { type: integer, format: bigint }
+        amount: { type: number, format: decimal }
+"#;
+
+const PAYLOAD: &str = r#"{"amount":1.50,"id":12345678901234567890123}"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("echo_payment", |req: HandlerRequest| {
+            let body = req.body.clone().unwrap_or(Value::Null);
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+#[test]
+fn value_round_trip_keeps_original_digits() {
+    let value: Value = serde_json::from_str(PAYLOAD).unwrap();
+    assert_eq!(value["id"].to_string(), "12345678901234567890123");
+    assert_eq!(value["amount"].to_string(), "1.50");
+    assert_eq!(serde_json::to_string(&value).unwrap(), PAYLOAD);
+
+    // Generated `format: bigint` fields are i128; they deserialize exactly from a Value.
+    let id: i128 = serde_json::from_value(value["id"].clone()).unwrap();
+    assert_eq!(id, 12_345_678_901_234_567_890_123);
+}
+
+#[test]
+fn request_and_response_preserve_precision() {
+    let server = start();
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST /payments HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{PAYLOAD}",
+            PAYLOAD.len()
+        ),
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+    let body = resp.split_once("\r\n\r\n").unwrap().1;
+    assert!(body.contains("12345678901234567890123"), "{body}");
+    assert!(body.contains("1.50"), "{body}");
+}
@@ -0,0 +1,172 @@
+//! OpenID Connect discovery tests for `JwksBearerProvider::from_oidc_discovery`.
+//!
+//! A mock identity provider serves both the discovery document and the JWKS it points to,
+//! so these tests cover the full path: discovery → issuer check → JWKS fetch → validation.
+//! Each test uses its own listener (and therefore its own issuer URL), so the process-wide
+//! discovery cache never leaks state between tests.
+
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine as _;
+use brrtrouter::dispatcher::HeaderVec;
+use brrtrouter::router::ParamVec;
+use brrtrouter::security::{
+    JwksBearerProvider, OidcDiscoveryError, SecurityProvider, SecurityRequest,
+};
+use brrtrouter::spec::SecurityScheme;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+
+const SECRET: &[u8] = b"oidc-discovery-secret";
+
+fn bearer_scheme() -> SecurityScheme {
+    SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: Some("JWT".to_string()),
+        description: None,
+    }
+}
+
+fn token(iss: &str) -> String {
+    let header = Header {
+        alg: Algorithm::HS256,
+        kid: Some("oidc-kid".to_string()),
+        typ: Some("at+jwt".to_string()),
+        ..Header::default()
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let claims = serde_json::json!({
+        "sub": "test-subject",
+        "iss": iss,
+        "exp": now + 300,
+    });
+    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+}
+
+fn validate(provider: &JwksBearerProvider, token: &str) -> bool {
+    let mut headers = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    let query = ParamVec::new();
+    let cookies = HeaderVec::new();
+    let request = SecurityRequest {
+        headers: &headers,
+        query: &query,
+        cookies: &cookies,
+    };
+    provider.validate(&bearer_scheme(), &[], &request)
+}
+
+/// Start a mock OIDC provider. `advertised_issuer` overrides the `issuer` member so tests can
+/// simulate a mix-up attack. Returns the issuer URL and a discovery-request counter.
+fn start_oidc_server(advertised_issuer: Option<&str>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let issuer = format!("http://{address}");
+    let discovery = serde_json::json!({
+        "issuer": advertised_issuer.unwrap_or(&issuer),
+        "jwks_uri": format!("{issuer}/.well-known/jwks.json"),
+        "id_token_signing_alg_values_supported": ["HS256"],
+    })
+    .to_string();
+    let jwks = serde_json::json!({
+        "keys": [{
+            "kty": "oct",
+            "alg": "HS256",
+            "kid": "oidc-kid",
+            "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(SECRET),
+        }]
+    })
+    .to_string();
+    let discovery_count = Arc::new(AtomicUsize::new(0));
+    let server_count = Arc::clone(&discovery_count);
+
+    thread::spawn(move || {
+        while let Ok((mut stream, _)) = listener.accept() {
+            let mut request_bytes = [0_u8; 2048];
+            let n = stream.read(&mut request_bytes).unwrap_or(0);
+            let request = String::from_utf8_lossy(&request_bytes[..n]);
+            let body = if request.starts_with("GET /.well-known/openid-configuration") {
+                server_count.fetch_add(1, Ordering::SeqCst);
+                &discovery
+            } else {
+                &jwks
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            let _ = stream.flush();
+        }
+    });
+
+    (issuer, discovery_count)
+}
+
+#[test]
+fn discovery_configures_issuer_and_jwks() {
+    let (issuer, _) = start_oidc_server(None);
+    let provider = JwksBearerProvider::from_oidc_discovery(&issuer).unwrap();
+
+    let doc = provider.oidc_discovery().unwrap();
+    assert_eq!(doc.issuer, issuer);
+    assert_eq!(doc.jwks_uri, format!("{issuer}/.well-known/jwks.json"));
+    assert!(validate(&provider, &token(&issuer)));
+    assert!(!validate(&provider, &token("https://other-issuer.example")));
+    provider.stop_background_refresh();
+}
+
+#[test]
+fn discovery_document_is_cached_per_issuer() {
+    let (issuer, discovery_count) = start_oidc_server(None);
+    let first = JwksBearerProvider::from_oidc_discovery(&issuer).unwrap();
+    let second = JwksBearerProvider::from_oidc_discovery(&issuer).unwrap();
+    first.stop_background_refresh();
+    second.stop_background_refresh();
+
+    assert_eq!(discovery_count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn discovery_rejects_issuer_mismatch() {
+    let (issuer, _) = start_oidc_server(Some("https://evil.example"));
+    let err = JwksBearerProvider::from_oidc_discovery(&issuer)
+        .err()
+        .expect("mix-up must be rejected");
+
+    assert_eq!(
+        err,
+        OidcDiscoveryError::IssuerMismatch {
+            expected: issuer,
+            discovered: "https://evil.example".to_string(),
+        }
+    );
+}
+
+#[test]
+fn discovery_rejects_insecure_issuer_without_fetching() {
+    let err = JwksBearerProvider::from_oidc_discovery("http://auth.example.com")
+        .err()
+        .expect("plain HTTP issuer must be rejected");
+
+    assert!(matches!(err, OidcDiscoveryError::InsecureUrl(_)));
+}
+
+#[test]
+fn discovery_reports_unreachable_provider() {
+    let err = JwksBearerProvider::from_oidc_discovery("http://127.0.0.1:1")
+        .err()
+        .expect("unreachable provider must fail");
+
+    assert!(matches!(err, OidcDiscoveryError::Fetch(_)));
+}
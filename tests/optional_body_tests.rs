@@ -0,0 +1,154 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Optional request bodies: an operation whose `requestBody` is not `required` runs without a
+//! body, still validates a body that is sent, and rejects one that is not valid JSON; its
+//! generated `Request` has only `Option` body fields.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::generator::{request_fields_with_options, TypeOptions};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::RouteMeta;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Notes
+  version: "1.0"
+paths:
+  /notes:
+    post:
+      operationId: create_note
+      requestBody:
+        required: false
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [text]
+              properties:
+                text: { type: string, minLength: 1 }
+                pinned: { type: boolean }
+      responses:
+        "200": { description: OK }
+  /notes/{id}:
+    put:
+      operationId: replace_note
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [text]
+              properties:
+                text: { type: string }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn start() -> (TestClient, Vec<RouteMeta>, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in ["create_note", "replace_note"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let body = json!({ "body": req.body.clone().unwrap_or(Value::Null) });
+                let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+            });
+        }
+    }
+    let client = TestClient::new(
+        AppService::builder()
+            .router(Router::new(routes.clone()))
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from(&spec_path))
+            .build()
+            .unwrap(),
+    );
+    (client, routes, dir)
+}
+
+#[test]
+fn optional_body_may_be_absent() {
+    let (client, _routes, _dir) = start();
+    client
+        .post("/notes")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "body": null }));
+}
+
+#[test]
+fn optional_body_is_validated_when_present() {
+    let (client, _routes, _dir) = start();
+    client
+        .post("/notes")
+        .json(json!({ "text": "milk" }))
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "body": { "text": "milk" } }));
+    client
+        .post("/notes")
+        .json(json!({ "text": "" }))
+        .send()
+        .assert_status(400);
+}
+
+#[test]
+fn malformed_body_is_rejected_even_when_optional() {
+    let (client, _routes, _dir) = start();
+    let resp = client
+        .post("/notes")
+        .body("application/json", "{\"text\": ")
+        .send();
+    resp.assert_status(400)
+        .assert_json_at("/detail", json!("Request body is not valid JSON"));
+}
+
+#[test]
+fn required_body_is_still_required() {
+    let (client, _routes, _dir) = start();
+    client.put("/notes/1").send().assert_status(400);
+    client
+        .put("/notes/1")
+        .json(json!({ "text": "eggs" }))
+        .send()
+        .assert_status(200);
+}
+
+#[test]
+fn generated_request_has_optional_body_fields() {
+    let (_client, routes, _dir) = start();
+    let fields = |handler: &str| {
+        let route = routes
+            .iter()
+            .find(|r| r.handler_name.as_ref() == handler)
+            .unwrap();
+        request_fields_with_options(route, TypeOptions::default())
+    };
+
+    let optional = fields("create_note");
+    assert!(optional.iter().all(|f| f.optional), "{optional:?}");
+    let text = optional.iter().find(|f| f.name == "text").unwrap();
+    assert_eq!(text.ty, "String");
+
+    let required = fields("replace_note");
+    let text = required.iter().find(|f| f.name == "text").unwrap();
+    assert!(!text.optional);
+    let id = required.iter().find(|f| f.name == "id").unwrap();
+    assert!(!id.optional);
+}
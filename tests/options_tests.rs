@@ -0,0 +1,165 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Automatic `OPTIONS` answers: a documented path without an OPTIONS operation gets
+//! `204` with `Allow`, an explicit OPTIONS operation still reaches its handler, and requests
+//! carrying `Origin` are left to CORS.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Options
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+    post:
+      operationId: add_pet
+      responses:
+        "201": { description: Created }
+  /pets/{id}:
+    parameters:
+      - name: id
+        in: path
+        required: true
+        schema: { type: string }
+    get:
+      operationId: get_pet
+      responses:
+        "200": { description: OK }
+  /users:
+    get:
+      operationId: list_users
+      responses:
+        "200": { description: OK }
+    options:
+      operationId: options_users
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in [
+            "list_pets",
+            "add_pet",
+            "get_pet",
+            "list_users",
+            "options_users",
+        ] {
+            dispatcher.register_handler(name, move |req: HandlerRequest| {
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({ "handler": name })));
+            });
+        }
+    }
+
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block, body)
+fn options(server: &Server, path: &str, extra_headers: &str) -> (u16, String, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("OPTIONS {path} HTTP/1.1\r\nHost: localhost\r\n{extra_headers}\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, head.to_ascii_lowercase(), body.to_string())
+}
+
+#[test]
+fn plain_options_lists_the_routed_methods() {
+    let server = start();
+
+    let (status, head, body) = options(&server, "/pets", "");
+    assert_eq!(status, 204, "{body}");
+    assert!(head.contains("allow: get, post, head, options"), "{head}");
+
+    let (status, head, _) = options(&server, "/pets/42", "");
+    assert_eq!(status, 204);
+    assert!(head.contains("allow: get, head, options"), "{head}");
+}
+
+#[test]
+fn undocumented_path_is_still_not_found() {
+    let server = start();
+    let (status, head, _) = options(&server, "/nowhere", "");
+    assert_eq!(status, 404);
+    assert!(!head.contains("allow:"), "{head}");
+}
+
+#[test]
+fn explicit_options_operation_reaches_its_handler() {
+    let server = start();
+    let (status, _, body) = options(&server, "/users", "");
+    assert_eq!(status, 200);
+    assert!(body.contains("options_users"), "{body}");
+}
+
+#[test]
+fn options_with_origin_is_not_auto_answered() {
+    let server = start();
+    let (status, head, _) = options(
+        &server,
+        "/pets",
+        "Origin: https://client.example\r\nAccess-Control-Request-Method: POST\r\n",
+    );
+    assert_eq!(status, 405);
+    assert!(head.contains("allow: get, post, head, options"), "{head}");
+}
@@ -0,0 +1,258 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Outbound HTTPS against a private CA: a mock endpoint serves a certificate signed by the
+//! test root in `tests/fixtures/tls`, which only [`OutboundTls`] trusts. Covers plain fetches,
+//! SPKI pinning, and the JWKS / remote API key providers.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use brrtrouter::dispatcher::HeaderVec;
+use brrtrouter::http::{
+    fetch_get, HttpFetchError, HttpFetchOptions, OutboundTls, OutboundTlsError,
+};
+use brrtrouter::router::ParamVec;
+use brrtrouter::security::{
+    JwksBearerProvider, RemoteApiKeyProvider, SecurityProvider, SecurityRequest,
+};
+use brrtrouter::server::OutboundTlsConfig;
+use brrtrouter::spec::SecurityScheme;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+/// `sha256/` pin of the leaf's SubjectPublicKeyInfo (see `tests/fixtures/tls/README.md`).
+const LEAF_PIN: &str = "sha256/n3HcAi1LjOLUIV+8bEUe0Gpp4SXOwkjmiWglc3o+Zbs=";
+/// Pin of the test root, which the mock server sends after the leaf.
+const CA_PIN: &str = "sha256/ERwhDKHrwd4r8iQIqs4Eeal86n5nzykTKGboMeIUQEU=";
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+fn custom_root() -> OutboundTls {
+    OutboundTls::builder()
+        .ca_bundle_file(fixture("ca.pem"))
+        .build()
+        .unwrap()
+}
+
+/// HTTPS server answering every request with `status` and `body`; when `api_key` is set,
+/// requests without `X-API-Key: <api_key>` get 401. Handshake failures are dropped silently.
+fn start_https_server(status: u16, body: String, api_key: Option<&'static str>) -> String {
+    // Full chain (leaf, then root) so an issuer pin has a certificate to match.
+    let certs: Vec<CertificateDer<'static>> = ["server.pem", "ca.pem"]
+        .into_iter()
+        .flat_map(|name| CertificateDer::pem_file_iter(fixture(name)).unwrap())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(fixture("server.key")).unwrap();
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for incoming in listener.incoming() {
+            let Ok(tcp) = incoming else {
+                break;
+            };
+            tcp.set_read_timeout(Some(Duration::from_secs(2))).ok();
+            let conn = rustls::ServerConnection::new(Arc::clone(&config)).unwrap();
+            let mut stream = rustls::StreamOwned::new(conn, tcp);
+            let mut buf = [0u8; 4096];
+            let Ok(n) = stream.read(&mut buf) else {
+                continue;
+            };
+            let req = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+            let (status, body) = match api_key {
+                Some(key) if !req.contains(&format!("x-api-key: {key}")) => (401, ""),
+                _ => (status, body.as_str()),
+            };
+            let resp = format!(
+                "HTTP/1.1 {status} OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(resp.as_bytes());
+            stream.conn.send_close_notify();
+            let _ = stream.flush();
+        }
+    });
+    format!("https://localhost:{}/resource", addr.port())
+}
+
+fn options(tls: Option<OutboundTls>) -> HttpFetchOptions {
+    HttpFetchOptions {
+        timeout: Duration::from_secs(2),
+        max_body_bytes: 4096,
+        extra_headers: Vec::new(),
+        tls: tls.map(Arc::new),
+    }
+}
+
+#[test]
+fn custom_root_accepts_the_private_ca() {
+    let url = start_https_server(200, r#"{"keys":[]}"#.to_string(), None);
+    let (status, body) = fetch_get(&url, &options(Some(custom_root()))).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(body, br#"{"keys":[]}"#);
+}
+
+#[test]
+fn custom_root_verifies_ip_address_names() {
+    let url = start_https_server(200, "ok".to_string(), None).replace("localhost", "127.0.0.1");
+    let (status, _) = fetch_get(&url, &options(Some(custom_root()))).unwrap();
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn system_trust_rejects_the_private_ca() {
+    let url = start_https_server(200, "ok".to_string(), None);
+    assert!(fetch_get(&url, &options(None)).is_err());
+}
+
+#[test]
+fn unrelated_root_rejects_the_server() {
+    let url = start_https_server(200, "ok".to_string(), None);
+    // The leaf is not a CA, so trusting it as a root must not validate the chain.
+    let tls = OutboundTls::builder()
+        .ca_bundle_file(fixture("server.pem"))
+        .build()
+        .unwrap();
+    let err = fetch_get(&url, &options(Some(tls))).unwrap_err();
+    assert!(matches!(err, HttpFetchError::Tls(_)), "{err:?}");
+}
+
+#[test]
+fn spki_pins_match_leaf_or_issuer() {
+    let url = start_https_server(200, "ok".to_string(), None);
+    for pin in [LEAF_PIN, CA_PIN] {
+        let tls = OutboundTls::builder()
+            .ca_bundle_file(fixture("ca.pem"))
+            .pin_spki_sha256(pin)
+            .build()
+            .unwrap();
+        let (status, _) = fetch_get(&url, &options(Some(tls))).unwrap();
+        assert_eq!(status, 200, "{pin}");
+    }
+}
+
+#[test]
+fn mismatched_spki_pin_rejects_the_server() {
+    let url = start_https_server(200, "ok".to_string(), None);
+    let tls = OutboundTls::builder()
+        .ca_bundle_file(fixture("ca.pem"))
+        .pin_spki_sha256("sha256/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=")
+        .build()
+        .unwrap();
+    let err = fetch_get(&url, &options(Some(tls))).unwrap_err();
+    assert!(matches!(err, HttpFetchError::Tls(_)), "{err:?}");
+}
+
+#[test]
+fn misconfiguration_fails_at_build_time() {
+    assert_eq!(
+        OutboundTls::builder().build().unwrap_err(),
+        OutboundTlsError::MissingCaBundle
+    );
+    assert!(matches!(
+        OutboundTls::builder()
+            .ca_bundle_file(fixture("missing.pem"))
+            .build(),
+        Err(OutboundTlsError::ReadCaBundle { .. })
+    ));
+    assert!(matches!(
+        OutboundTls::builder()
+            .ca_bundle_file(fixture("ca.pem"))
+            .pin_spki_sha256("sha256/not-base64!")
+            .build(),
+        Err(OutboundTlsError::InvalidPin(_))
+    ));
+
+    let from_config = OutboundTlsConfig {
+        ca_bundle: Some(fixture("ca.pem")),
+        spki_sha256: vec![LEAF_PIN.to_string()],
+    };
+    assert!(from_config.build().is_ok());
+    assert!(OutboundTlsConfig::default().build().is_err());
+}
+
+#[test]
+fn remote_api_key_provider_uses_the_custom_root() {
+    let url = start_https_server(200, String::new(), Some("validkey"));
+    let provider = RemoteApiKeyProvider::new(url)
+        .timeout_ms(2000)
+        .header_name("X-API-Key")
+        .tls(custom_root());
+    let scheme = SecurityScheme::ApiKey {
+        name: "X-API-Key".to_string(),
+        location: "header".to_string(),
+        description: None,
+    };
+
+    let mut headers: HeaderVec = HeaderVec::new();
+    headers.push((Arc::from("x-api-key"), "validkey".to_string()));
+    let req = SecurityRequest {
+        headers: &headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    assert!(provider.validate(&scheme, &[], &req));
+}
+
+#[test]
+fn jwks_bearer_provider_loads_keys_via_the_custom_root() {
+    use base64::Engine;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+
+    let secret = b"outbound-tls-jwks-secret";
+    let kid = "tls-kid";
+    let k = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret);
+    let jwks = format!(r#"{{"keys":[{{"kty":"oct","kid":"{kid}","k":"{k}","alg":"HS256"}}]}}"#);
+    let jwks_url = start_https_server(200, jwks, None);
+
+    let provider = JwksBearerProvider::new(&jwks_url)
+        .issuer("test-iss")
+        .audience("test-aud")
+        .tls(custom_root());
+
+    let header = Header {
+        kid: Some(kid.to_string()),
+        alg: Algorithm::HS256,
+        typ: Some("at+jwt".to_string()),
+        ..Default::default()
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let claims = serde_json::json!({ "iss": "test-iss", "aud": "test-aud", "exp": now + 3600 });
+    let token = jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap();
+
+    let mut headers: HeaderVec = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    let req = SecurityRequest {
+        headers: &headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    let scheme = SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: None,
+        description: None,
+    };
+    assert!(provider.validate(&scheme, &[], &req));
+    provider.stop_background_refresh();
+}
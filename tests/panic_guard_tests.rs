@@ -0,0 +1,169 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Handler panic policies: a handler that always panics trips its breaker after N calls
+//! (503 + `Retry-After`, visible in `/metrics`), and a respawn policy replaces the coroutine.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, PanicPolicy};
+use brrtrouter::middleware::MetricsMiddleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Panics
+  version: "1.0"
+paths:
+  /boom:
+    get:
+      operationId: boom
+      responses:
+        "200": { description: OK }
+  /flaky:
+    get:
+      operationId: flaky
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    dispatcher: Arc<arc_swap::ArcSwap<Dispatcher>>,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(flaky_calls: Arc<AtomicUsize>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("boom", |_req: HandlerRequest| {
+            panic!("poisoned state");
+        });
+        // Panics on its first two calls, then answers with the number of calls so far.
+        dispatcher.register_handler("flaky", move |req: HandlerRequest| {
+            let call = flaky_calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= 2 {
+                panic!("flaky call {call}");
+            }
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "call": call })));
+        });
+    }
+    dispatcher.set_panic_policy(
+        "boom",
+        PanicPolicy::trip_breaker(3, Duration::from_secs(60), Duration::from_secs(30)),
+    );
+    dispatcher.set_panic_policy("flaky", PanicPolicy::respawn(2, Duration::from_secs(60)));
+
+    let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher));
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .shared_dispatcher(dispatcher.clone())
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_metrics_middleware(Arc::new(MetricsMiddleware::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        dispatcher,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block, body)
+fn get(server: &Server, path: &str) -> (u16, String, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, head.to_ascii_lowercase(), body.to_string())
+}
+
+#[test]
+fn always_panicking_handler_trips_breaker_after_threshold() {
+    let server = start(Arc::new(AtomicUsize::new(0)));
+
+    for _ in 0..3 {
+        let (status, _, _) = get(&server, "/boom");
+        assert_eq!(status, 500);
+    }
+
+    // The breaker is open: answered without reaching the handler.
+    let (status, head, body) = get(&server, "/boom");
+    assert_eq!(status, 503, "{body}");
+    assert!(head.contains("retry-after: 30"), "{head}");
+
+    let stats = server.dispatcher.load().panic_guard_stats()["boom"];
+    assert!(stats.breaker_open);
+    assert_eq!(stats.panics, 3);
+    assert_eq!(stats.trips, 1);
+
+    let (_, _, metrics) = get(&server, "/metrics");
+    assert!(
+        metrics.contains("brrtrouter_handler_breaker_open{handler=\"boom\"} 1"),
+        "{metrics}"
+    );
+    assert!(metrics.contains("brrtrouter_handler_panics_total{handler=\"boom\"} 3"));
+    assert!(metrics.contains("brrtrouter_handler_breaker_trips_total{handler=\"boom\"} 1"));
+    assert!(metrics.contains("brrtrouter_handler_breaker_open{handler=\"flaky\"} 0"));
+}
+
+#[test]
+fn respawn_policy_replaces_the_handler_coroutine() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = start(calls.clone());
+
+    assert_eq!(get(&server, "/flaky").0, 500);
+    assert_eq!(get(&server, "/flaky").0, 500);
+
+    // The next request is served by a fresh coroutine; no breaker is involved.
+    let (status, _, body) = get(&server, "/flaky");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let stats = server.dispatcher.load().panic_guard_stats()["flaky"];
+    assert_eq!(stats.respawns, 1);
+    assert_eq!(stats.panics, 2);
+    assert!(!stats.breaker_open);
+
+    let (_, _, metrics) = get(&server, "/metrics");
+    assert!(metrics.contains("brrtrouter_handler_respawns_total{handler=\"flaky\"} 1"));
+}
@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Typed parameter accessors on `HandlerRequest`: `param_i64`, `param_bool`, `param_str` and
+//! `param_array` return declared parameters decoded as validation decoded them, schema
+//! defaults included, and name the parameter when asked for the wrong type.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::{json, Value};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Params
+  version: "1.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: integer } }
+        - { name: limit, in: query, schema: { type: integer, default: 20 } }
+        - { name: verbose, in: query, schema: { type: boolean, default: false } }
+        - { name: name, in: query, schema: { type: string } }
+        - name: tags
+          in: query
+          explode: false
+          schema:
+            type: array
+            items: { type: integer }
+            default: [1, 2]
+        - { name: x-trace, in: header, schema: { type: boolean } }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn echo_params(req: HandlerRequest) {
+    let body = json!({
+        "id": req.param_i64("id").unwrap(),
+        "limit": req.param_i64("limit").unwrap(),
+        "verbose": req.param_bool("verbose").unwrap(),
+        "name": req.param_str("name").unwrap(),
+        "tags": req.param_array("tags").unwrap(),
+        "trace": req.param_bool("x-trace").unwrap(),
+        "undeclared": req.param_str("other").unwrap(),
+        "wrong_type": req.param_str("id").map_err(|e| e.to_string()).err(),
+    });
+    let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+}
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("get_pet", echo_params);
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+fn params(client: &TestClient, target: &str) -> Value {
+    let res = client.get(target).header("X-Trace", "true").send();
+    res.assert_status(200);
+    res.body().clone()
+}
+
+#[test]
+fn param_i64_reads_coerced_integers_and_defaults() {
+    let (client, _dir) = client();
+    let sent = params(&client, "/pets/42?limit=5");
+    assert_eq!(sent["id"], json!(42));
+    assert_eq!(sent["limit"], json!(5));
+    // Omitted: the schema default
+    assert_eq!(params(&client, "/pets/42")["limit"], json!(20));
+}
+
+#[test]
+fn param_bool_reads_query_and_header_booleans() {
+    let (client, _dir) = client();
+    let sent = params(&client, "/pets/1?verbose=true");
+    assert_eq!(sent["verbose"], json!(true));
+    assert_eq!(sent["trace"], json!(true));
+    assert_eq!(params(&client, "/pets/1")["verbose"], json!(false));
+}
+
+#[test]
+fn param_str_reads_strings_and_rejects_other_types() {
+    let (client, _dir) = client();
+    let sent = params(&client, "/pets/1?name=Rex&other=x");
+    assert_eq!(sent["name"], json!("Rex"));
+    // Only declared parameters are decoded.
+    assert_eq!(sent["undeclared"], Value::Null);
+    assert_eq!(
+        sent["wrong_type"],
+        json!("parameter `id` is not a string: 1")
+    );
+    assert_eq!(params(&client, "/pets/1")["name"], Value::Null);
+}
+
+#[test]
+fn param_array_reads_items_decoded_per_schema() {
+    let (client, _dir) = client();
+    assert_eq!(
+        params(&client, "/pets/1?tags=3,4,5")["tags"],
+        json!([3, 4, 5])
+    );
+    assert_eq!(params(&client, "/pets/1")["tags"], json!([1, 2]));
+}
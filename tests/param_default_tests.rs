@@ -0,0 +1,116 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Parameter defaults: an omitted optional query or header parameter reaches the handler as
+//! its schema `default`, a sent value overrides it and is validated as usual, and a required
+//! parameter with a default still answers `400` when missing.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Defaults
+  version: "1.0"
+paths:
+  /items:
+    get:
+      operationId: list_items
+      parameters:
+        - name: limit
+          in: query
+          schema: { type: integer, minimum: 1, maximum: 100, default: 20 }
+        - name: tags
+          in: query
+          schema:
+            type: array
+            items: { type: string }
+            default: [new, sale]
+        - name: X-Region
+          in: header
+          schema: { type: string, default: eu }
+        - name: cursor
+          in: query
+          required: true
+          schema: { type: string, default: start }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn start() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_items", |req: HandlerRequest| {
+            let body = json!({
+                "limit": req.get_query_param("limit"),
+                "tags": req.get_query_params("tags").collect::<Vec<_>>(),
+                "region": req.get_header("x-region"),
+                "cursor": req.get_query_param("cursor"),
+            });
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn omitted_params_take_their_defaults() {
+    let (client, _dir) = start();
+    client
+        .get("/items?cursor=abc")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({
+            "limit": "20",
+            "tags": ["new", "sale"],
+            "region": "eu",
+            "cursor": "abc",
+        }));
+}
+
+#[test]
+fn sent_values_override_the_defaults() {
+    let (client, _dir) = start();
+    client
+        .get("/items?cursor=abc&limit=5&tags=old")
+        .header("X-Region", "us")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({
+            "limit": "5",
+            "tags": ["old"],
+            "region": "us",
+            "cursor": "abc",
+        }));
+}
+
+#[test]
+fn overrides_are_still_validated() {
+    let (client, _dir) = start();
+    client
+        .get("/items?cursor=abc&limit=500")
+        .send()
+        .assert_status(400);
+}
+
+#[test]
+fn required_params_with_a_default_are_still_required() {
+    let (client, _dir) = start();
+    client.get("/items").send().assert_status(400);
+}
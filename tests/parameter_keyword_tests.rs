@@ -0,0 +1,196 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Parameter schema keywords (`enum`, `const`, `pattern`, `minLength` / `maxLength`,
+//! `minimum` / `maximum` / `multipleOf`) enforced on path and query parameters, with numeric
+//! bounds checked after string → number coercion.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::Value;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Keywords
+  version: "1.0"
+paths:
+  /items/{code}:
+    get:
+      operationId: list_items
+      parameters:
+        - name: code
+          in: path
+          required: true
+          schema:
+            type: string
+            pattern: "^[a-z]+$"
+        - name: sort
+          in: query
+          schema:
+            type: string
+            enum: [asc, desc]
+        - name: format
+          in: query
+          schema:
+            const: json
+        - name: q
+          in: query
+          schema:
+            type: string
+            minLength: 2
+            maxLength: 5
+        - name: limit
+          in: query
+          schema:
+            type: integer
+            minimum: 1
+            maximum: 100
+        - name: step
+          in: query
+          schema:
+            type: number
+            multipleOf: 5
+        - name: ids
+          in: query
+          schema:
+            type: array
+            items:
+              type: integer
+              maximum: 10
+      responses:
+        "200":
+          description: ok
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_items", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, serde_json::json!({"ok": true})));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn get(server: &Server, uri: &str) -> (u16, Value) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {uri} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let body = resp
+        .split_once("\r\n\r\n")
+        .and_then(|(_, b)| serde_json::from_str(b).ok())
+        .unwrap_or(Value::Null);
+    (status, body)
+}
+
+fn assert_rejected(server: &Server, uri: &str, param: &str, location: &str) {
+    let (status, body) = get(server, uri);
+    assert_eq!(status, 400, "{uri}: {body}");
+    assert_eq!(body["parameter"], param, "{uri}");
+    assert_eq!(body["in"], location, "{uri}");
+    assert!(
+        body["errors"].as_array().is_some_and(|e| !e.is_empty()),
+        "{uri}: {body}"
+    );
+}
+
+#[test]
+fn valid_values_pass() {
+    let server = start();
+    let (status, body) = get(
+        &server,
+        "/items/abc?sort=desc&format=json&q=abc&limit=100&step=15&ids=1&ids=10",
+    );
+    assert_eq!(status, 200, "{body}");
+}
+
+#[test]
+fn enum_and_const() {
+    let server = start();
+    assert_rejected(&server, "/items/abc?sort=random", "sort", "query");
+    assert_rejected(&server, "/items/abc?format=xml", "format", "query");
+}
+
+#[test]
+fn pattern_on_path_parameter() {
+    let server = start();
+    assert_rejected(&server, "/items/ABC1", "code", "path");
+}
+
+#[test]
+fn min_and_max_length() {
+    let server = start();
+    assert_rejected(&server, "/items/abc?q=a", "q", "query");
+    assert_rejected(&server, "/items/abc?q=abcdef", "q", "query");
+}
+
+#[test]
+fn numeric_bounds_apply_after_coercion() {
+    let server = start();
+    // "100" and "15" only satisfy `type: integer/number` once coerced, so passing here
+    // shows coercion ran before the bounds were checked.
+    assert_eq!(get(&server, "/items/abc?limit=100").0, 200);
+    assert_rejected(&server, "/items/abc?limit=0", "limit", "query");
+    assert_rejected(&server, "/items/abc?limit=101", "limit", "query");
+    assert_rejected(&server, "/items/abc?limit=ten", "limit", "query");
+    assert_eq!(get(&server, "/items/abc?step=15").0, 200);
+    assert_rejected(&server, "/items/abc?step=7", "step", "query");
+}
+
+#[test]
+fn array_items_checked_across_repeated_keys() {
+    let server = start();
+    assert_eq!(get(&server, "/items/abc?ids=1,2").0, 200);
+    assert_rejected(&server, "/items/abc?ids=1&ids=11", "ids", "query");
+}
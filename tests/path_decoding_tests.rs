@@ -0,0 +1,178 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Path decoding: each segment is percent-decoded once after the path is split on `/`, so an
+//! encoded slash or brace is part of a parameter value rather than path structure; the raw
+//! text stays available to routers, handlers and the proxy, and `raw` turns decoding off.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::http::resolve_path_template;
+use brrtrouter::router::{ParamVec, PathDecoding, RouteMatch, Router};
+use brrtrouter::server::{AppService, TestClient};
+use http::Method;
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        "200": { description: OK }
+  /pets/{id}/toys/{toy}:
+    get:
+      operationId: get_toy
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+        - name: toy
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn load() -> (Vec<brrtrouter::spec::RouteMeta>, tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+    (routes, dir, spec_path)
+}
+
+fn route(decoding: PathDecoding, path: &str) -> Option<RouteMatch> {
+    let (routes, _dir, _) = load();
+    Router::new(routes)
+        .with_path_decoding(decoding)
+        .route(Method::GET, path)
+}
+
+#[test]
+fn encoded_slash_does_not_split_segments() {
+    let m = route(PathDecoding::Segment, "/pets/a%2Fb").unwrap();
+    assert_eq!(m.handler_name, "get_pet");
+    assert_eq!(m.get_path_param("id"), Some("a/b"));
+    assert_eq!(m.get_raw_path_param("id"), Some("a%2Fb"));
+
+    // Would be `get_toy` if `%2F` were decoded before splitting.
+    let m = route(PathDecoding::Segment, "/pets/a%2Ftoys%2Fb").unwrap();
+    assert_eq!(m.handler_name, "get_pet");
+    assert_eq!(m.get_path_param("id"), Some("a/toys/b"));
+
+    let m = route(PathDecoding::Segment, "/pets/%2E%2E").unwrap();
+    assert_eq!(m.get_path_param("id"), Some(".."));
+    assert_eq!(m.get_raw_path_param("id"), Some("%2E%2E"));
+}
+
+#[test]
+fn encoded_braces_are_plain_values() {
+    let m = route(PathDecoding::Segment, "/pets/%7Bid%7D").unwrap();
+    assert_eq!(m.handler_name, "get_pet");
+    assert_eq!(m.get_path_param("id"), Some("{id}"));
+    assert_eq!(m.get_raw_path_param("id"), Some("%7Bid%7D"));
+}
+
+#[test]
+fn encoded_value_is_decoded_exactly_once() {
+    let m = route(PathDecoding::Segment, "/pets/rex%2520").unwrap();
+    assert_eq!(m.get_path_param("id"), Some("rex%20"));
+    assert_eq!(m.get_raw_path_param("id"), Some("rex%2520"));
+}
+
+#[test]
+fn static_segments_match_after_decoding() {
+    let m = route(PathDecoding::Segment, "/p%65ts/1").unwrap();
+    assert_eq!(m.handler_name, "get_pet");
+    assert_eq!(m.get_path_param("id"), Some("1"));
+    assert!(m.raw_path_params.is_empty(), "{:?}", m.raw_path_params);
+    assert_eq!(m.get_raw_path_param("id"), Some("1"));
+
+    // Escapes that are not UTF-8 are matched as sent.
+    let m = route(PathDecoding::Segment, "/pets/%FF").unwrap();
+    assert_eq!(m.get_path_param("id"), Some("%FF"));
+}
+
+#[test]
+fn raw_values_run_parallel_to_decoded_ones() {
+    let m = route(PathDecoding::Segment, "/pets/1/toys/b%20all").unwrap();
+    assert_eq!(m.handler_name, "get_toy");
+    assert_eq!(m.get_path_param("toy"), Some("b all"));
+    assert_eq!(m.get_raw_path_param("toy"), Some("b%20all"));
+    assert_eq!(m.get_raw_path_param("id"), Some("1"));
+    assert_eq!(m.raw_path_params.len(), m.path_params.len());
+}
+
+#[test]
+fn raw_policy_matches_segments_as_sent() {
+    let m = route(PathDecoding::Raw, "/pets/a%2Fb").unwrap();
+    assert_eq!(m.get_path_param("id"), Some("a%2Fb"));
+    assert!(m.raw_path_params.is_empty());
+    assert!(route(PathDecoding::Raw, "/p%65ts/1").is_none());
+
+    let (routes, _dir, _) = load();
+    assert_eq!(
+        Router::new(routes.clone()).path_decoding(),
+        PathDecoding::Segment
+    );
+    let router = Router::new(routes).with_path_decoding(PathDecoding::Raw);
+    assert_eq!(router.path_decoding(), PathDecoding::Raw);
+}
+
+#[test]
+fn handlers_see_decoded_and_raw_values() {
+    may::config().set_stack_size(0x8000);
+    let (routes, _dir, spec_path) = load();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("get_pet", |req: HandlerRequest| {
+            let body = json!({
+                "id": req.get_path_param("id"),
+                "raw": req.get_raw_path_param("id"),
+            });
+            let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+        });
+    }
+    let client = TestClient::new(
+        AppService::builder()
+            .router(Router::new(routes))
+            .dispatcher(dispatcher)
+            .security_schemes(Default::default())
+            .spec_path(spec_path)
+            .build()
+            .unwrap(),
+    );
+
+    client
+        .get("/pets/a%2Fb")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "id": "a/b", "raw": "a%2Fb" }));
+    client
+        .get("/pets/rex")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "id": "rex", "raw": "rex" }));
+}
+
+#[test]
+fn raw_values_keep_downstream_paths_intact() {
+    let m = route(PathDecoding::Segment, "/pets/a%2F..%2Fadmin").unwrap();
+    assert_eq!(m.get_path_param("id"), Some("a/../admin"));
+    let raw: ParamVec = m.raw_path_params.clone();
+    assert_eq!(
+        resolve_path_template("/internal/pets/{id}", &raw, &ParamVec::new()),
+        "/internal/pets/a%2F..%2Fadmin"
+    );
+}
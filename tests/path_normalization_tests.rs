@@ -0,0 +1,177 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Duplicate slashes: `off` leaves `//pets` as sent, `normalize` routes it as `/pets`, and
+//! `redirect` sends the client to `/pets` with a 308 (or 301), keeping the query string.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::path_normalize::collapse_slashes;
+use brrtrouter::server::{
+    AppService, DuplicateSlashes, HttpServer, PathNormalization, ServerHandle, TestClient,
+};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+"#;
+
+fn service(normalization: PathNormalization) -> (AppService, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        // Both echo the path they were handed.
+        for name in ["list_pets", "get_pet"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let body = json!({
+                    "path": req.path,
+                    "id": req.get_path_param("id"),
+                });
+                let _ = req.reply_tx.send(HandlerResponse::json(200, body));
+            });
+        }
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_path_normalization(normalization);
+    (service, dir)
+}
+
+fn client(mode: DuplicateSlashes) -> (TestClient, tempfile::TempDir) {
+    let (service, dir) = service(PathNormalization::new(mode));
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn collapse_keeps_the_trailing_slash() {
+    assert_eq!(collapse_slashes("//pets").as_deref(), Some("/pets"));
+    assert_eq!(collapse_slashes("/pets//123").as_deref(), Some("/pets/123"));
+    assert_eq!(collapse_slashes("/pets///").as_deref(), Some("/pets/"));
+    assert_eq!(collapse_slashes("/pets/"), None);
+    assert_eq!(collapse_slashes("/Pets/123"), None);
+}
+
+#[test]
+fn off_leaves_the_path_as_sent() {
+    let (client, _dir) = client(DuplicateSlashes::Off);
+    client
+        .get("/pets//123")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/path", json!("/pets//123"));
+}
+
+#[test]
+fn normalize_routes_the_collapsed_path() {
+    let (client, _dir) = client(DuplicateSlashes::Normalize);
+    client
+        .get("//pets")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/path", json!("/pets"));
+    client
+        .get("/pets//123?verbose=true")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/path", json!("/pets/123"))
+        .assert_json_at("/id", json!("123"));
+}
+
+#[test]
+fn redirect_sends_the_client_to_the_collapsed_path() {
+    let (client, _dir) = client(DuplicateSlashes::Redirect);
+    client
+        .get("//pets")
+        .send()
+        .assert_status(308)
+        .assert_header("location", "/pets");
+    // The query string is kept byte for byte, slashes and escapes included.
+    client
+        .get("/pets//123?next=//x&q=a%20b")
+        .send()
+        .assert_status(308)
+        .assert_header("location", "/pets/123?next=//x&q=a%20b");
+    // Canonical paths are served as usual.
+    client
+        .get("/pets/123?next=//x")
+        .send()
+        .assert_status(200)
+        .assert_json_at("/path", json!("/pets/123"));
+}
+
+#[test]
+fn redirect_status_can_be_301() {
+    let (service, _dir) =
+        service(PathNormalization::new(DuplicateSlashes::Redirect).with_redirect_status(301));
+    TestClient::new(service)
+        .get("//pets")
+        .send()
+        .assert_status(301)
+        .assert_header("location", "/pets");
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+#[test]
+fn redirect_over_the_wire() {
+    let (service, dir) = service(PathNormalization::new(DuplicateSlashes::Redirect));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    let server = Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    };
+
+    let resp = send_request(
+        &server.addr,
+        "GET //pets?limit=2 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n",
+    );
+    let head = resp.split("\r\n\r\n").next().unwrap().to_ascii_lowercase();
+    assert!(head.starts_with("http/1.1 308"), "{resp}");
+    assert!(head.contains("\r\nlocation: /pets?limit=2\r\n"), "{resp}");
+}
@@ -0,0 +1,190 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Pretty-printed JSON responses: `AppService::set_pretty_json` indents handler bodies and
+//! problems, `?__pretty=1` does so per request only in dev mode, and `Content-Length` / gzip
+//! follow the indented bytes.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::{CompressionConfig, CompressionMiddleware};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pretty
+  version: "1.0"
+paths:
+  /pet:
+    get:
+      operationId: get_pet
+      responses:
+        "200": { description: OK }
+"#;
+
+fn pet() -> Value {
+    json!({"name": "Rex", "tags": ["good", "boy"], "owner": {"id": 7}})
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(compress: bool, configure: impl FnOnce(&mut AppService)) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("get_pet", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, pet()));
+        });
+    }
+    if compress {
+        dispatcher.add_middleware(Arc::new(CompressionMiddleware::new(&CompressionConfig {
+            min_size_bytes: 0,
+        })));
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    // Independent of BRRTR_PRETTY_JSON / BRRTR_DEV_MODE in the test environment.
+    service.set_pretty_json(false);
+    service.set_dev_mode(false);
+    configure(&mut service);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block, body); asserts `Content-Length` matches the body.
+fn get(server: &Server, path: &str) -> (u16, String, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let head = head.to_ascii_lowercase();
+    assert_eq!(content_length(&head), Some(body.len()), "{head}");
+    (status, head, body.to_string())
+}
+
+fn content_length(head: &str) -> Option<usize> {
+    head.lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+#[test]
+fn compact_by_default_and_query_ignored_outside_dev_mode() {
+    let server = start(false, |_| {});
+
+    let (status, _, body) = get(&server, "/pet");
+    assert_eq!(status, 200);
+    assert_eq!(body, serde_json::to_string(&pet()).unwrap());
+
+    let (_, _, body) = get(&server, "/pet?__pretty=1");
+    assert_eq!(body, serde_json::to_string(&pet()).unwrap());
+}
+
+#[test]
+fn config_indents_handler_bodies_and_problems() {
+    let server = start(false, |service| service.set_pretty_json(true));
+
+    let (status, head, body) = get(&server, "/pet");
+    assert_eq!(status, 200);
+    assert!(head.contains("content-type: application/json"), "{head}");
+    assert_eq!(body, serde_json::to_string_pretty(&pet()).unwrap());
+
+    let (status, head, body) = get(&server, "/nope");
+    assert_eq!(status, 404);
+    assert!(head.contains("application/problem+json"), "{head}");
+    assert!(body.starts_with("{\n  "), "{body}");
+    let problem: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(problem["status"], 404);
+}
+
+#[test]
+fn dev_mode_query_override_indents_one_request() {
+    let server = start(false, |service| service.set_dev_mode(true));
+
+    let (_, _, body) = get(&server, "/pet?__pretty=1");
+    assert_eq!(body, serde_json::to_string_pretty(&pet()).unwrap());
+
+    let (_, _, body) = get(&server, "/pet?__pretty=true");
+    assert_eq!(body, serde_json::to_string_pretty(&pet()).unwrap());
+
+    let (_, _, body) = get(&server, "/pet");
+    assert_eq!(body, serde_json::to_string(&pet()).unwrap());
+
+    let (_, _, body) = get(&server, "/pet?__pretty=0");
+    assert_eq!(body, serde_json::to_string(&pet()).unwrap());
+}
+
+#[test]
+fn gzip_encodes_the_indented_body() {
+    let server = start(true, |service| service.set_pretty_json(true));
+
+    let mut stream = TcpStream::connect(server.addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    stream
+        .write_all(
+            b"GET /pet HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n",
+        )
+        .unwrap();
+    let mut raw = Vec::new();
+    let _ = stream.read_to_end(&mut raw);
+
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8_lossy(&raw[..split]).to_ascii_lowercase();
+    let body = &raw[split + 4..];
+    assert!(head.contains("content-encoding: gzip"), "{head}");
+    assert_eq!(content_length(&head), Some(body.len()), "{head}");
+
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(body)
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, serde_json::to_string_pretty(&pet()).unwrap());
+}
@@ -0,0 +1,95 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+// The `jemalloc` feature installs the library's own global allocator.
+#![cfg(not(feature = "jemalloc"))]
+
+//! Allocation budget for parsing a request head
+//!
+//! A counting global allocator records heap allocations made on the current thread while
+//! [`parse_request_head`] runs. Every allocation left is data the handler keeps: the path,
+//! each header value, and the name and value of each cookie and query parameter.
+
+use brrtrouter::server::parse_request_head;
+use http::Method;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let out = f();
+    (out, ALLOCATIONS.with(Cell::get) - before)
+}
+
+const HEADERS: [(&str, &[u8]); 5] = [
+    ("Host", b"localhost:8080"),
+    ("User-Agent", b"curl/8.5.0"),
+    ("Accept", b"application/json"),
+    ("Authorization", b"Bearer token"),
+    ("Cookie", b"session=abc; theme=dark"),
+];
+
+const TARGET: &str = "/pets?limit=10&status=available";
+
+#[test]
+fn request_head_allocates_only_what_the_handler_keeps() {
+    // Warm up lazily initialised statics (interned header names, tracing callsites).
+    parse_request_head("GET", TARGET, HEADERS).unwrap();
+
+    let (parsed, allocations) =
+        allocations_during(|| parse_request_head("GET", TARGET, HEADERS).unwrap());
+
+    assert_eq!(parsed.method, Method::GET);
+    assert_eq!(parsed.path, "/pets");
+    assert_eq!(parsed.get_header("user-agent"), Some("curl/8.5.0"));
+    assert_eq!(parsed.get_cookie("theme"), Some("dark"));
+    assert_eq!(parsed.get_query_param("status"), Some("available"));
+    assert_eq!(parsed.body, None);
+
+    // path + 5 header values + 2 cookies (name, value) + 2 query params (name, value)
+    assert_eq!(allocations, 1 + 5 + 2 * 2 + 2 * 2);
+}
+
+#[test]
+fn uncommon_header_names_cost_one_allocation() {
+    parse_request_head("GET", "/", HEADERS).unwrap();
+    let custom = [("Host", &b"localhost"[..]), ("X-Tenant", &b"acme"[..])];
+
+    let (parsed, allocations) =
+        allocations_during(|| parse_request_head("GET", "/", custom).unwrap());
+
+    assert_eq!(parsed.get_header("x-tenant"), Some("acme"));
+    // path + 2 header values + the `x-tenant` name (lowercased on the stack)
+    assert_eq!(allocations, 1 + 2 + 1);
+}
+
+#[test]
+fn invalid_method_is_rejected() {
+    assert_eq!(
+        parse_request_head("GE T", "/", HEADERS).unwrap_err(),
+        "GE T"
+    );
+}
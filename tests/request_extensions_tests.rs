@@ -0,0 +1,133 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Request extensions: values a middleware inserts in `prepare` reach the handler with the
+//! request, one map per request.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::Middleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Extensions
+  version: "1.0"
+paths:
+  /whoami:
+    get:
+      operationId: whoami
+      responses:
+        "200": { description: OK }
+"#;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Tenant(String);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sequence(u64);
+
+#[derive(Default)]
+struct TenantMiddleware {
+    next: AtomicU64,
+}
+
+impl Middleware for TenantMiddleware {
+    fn prepare(&self, req: &mut HandlerRequest) {
+        let tenant = req.get_header("x-tenant").unwrap_or("public").to_string();
+        req.extensions_mut().insert(Tenant(tenant));
+        req.extensions_mut()
+            .insert(Sequence(self.next.fetch_add(1, Ordering::Relaxed)));
+    }
+}
+
+fn client() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.add_middleware(Arc::new(TenantMiddleware::default()));
+    unsafe {
+        dispatcher.register_handler("whoami", |req: HandlerRequest| {
+            let tenant = req.extensions().get::<Tenant>().map(|t| t.0.clone());
+            let sequence = req.extensions().get::<Sequence>().map(|s| s.0);
+            let _ = req.reply_tx.send(HandlerResponse::json(
+                200,
+                json!({"tenant": tenant, "sequence": sequence}),
+            ));
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(Default::default())
+        .spec_path(dir.path().join("openapi.yaml"))
+        .build()
+        .unwrap();
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn handler_reads_values_inserted_by_middleware() {
+    let (client, _dir) = client();
+    client
+        .get("/whoami")
+        .header("x-tenant", "acme")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({"tenant": "acme", "sequence": 0}));
+}
+
+#[test]
+fn each_request_carries_its_own_values() {
+    let (client, _dir) = client();
+    client
+        .get("/whoami")
+        .header("x-tenant", "acme")
+        .send()
+        .assert_json(&json!({"tenant": "acme", "sequence": 0}));
+    client
+        .get("/whoami")
+        .send()
+        .assert_json(&json!({"tenant": "public", "sequence": 1}));
+}
+
+#[test]
+fn extensions_start_empty() {
+    let (tx, _rx) = may::sync::mpsc::channel();
+    let mut req = HandlerRequest {
+        request_id: brrtrouter::ids::RequestId::new(),
+        method: http::Method::GET,
+        path: "/whoami".to_string(),
+        handler_name: "whoami".to_string(),
+        path_params: Default::default(),
+        query_params: Default::default(),
+        headers: Default::default(),
+        cookies: Default::default(),
+        body: None,
+        raw_body: None,
+        jwt_claims: None,
+        reply_tx: tx,
+        queue_guard: None,
+        peer_addr: None,
+        downstream_headers: Default::default(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+    };
+    assert!(req.extensions().get::<Tenant>().is_none());
+
+    req.extensions_mut().insert(Tenant("acme".to_string()));
+    let copy = req.clone();
+    req.extensions_mut().insert(Tenant("other".to_string()));
+    assert_eq!(
+        copy.extensions().get::<Tenant>(),
+        Some(&Tenant("acme".to_string()))
+    );
+}
@@ -0,0 +1,313 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Response caching: listed `GET` routes are served from the cache (`X-Cache: HIT`, `Age`)
+//! without invoking the handler until the TTL passes, keyed by path, query, `Accept` and the
+//! authenticated caller; `no-store` responses are never stored and auth runs on every hit.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderLookup};
+use brrtrouter::middleware::{ResponseCacheConfig, ResponseCacheMiddleware};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::SecurityScheme;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+components:
+  securitySchemes:
+    UserAuth:
+      type: apiKey
+      in: header
+      name: X-User
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      parameters:
+        - { name: limit, in: query, schema: { type: integer } }
+        - { name: trace, in: query, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /live:
+    get:
+      operationId: live
+      responses:
+        "200": { description: OK }
+  /me:
+    get:
+      operationId: me
+      security:
+        - UserAuth: []
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Fixture {
+    client: TestClient,
+    cache: Arc<ResponseCacheMiddleware>,
+    calls: Arc<AtomicUsize>,
+    _dir: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+fn fixture(config: ResponseCacheConfig, ttl: Option<Duration>) -> Fixture {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut dispatcher = Dispatcher::new();
+    for name in ["list_pets", "get_pet", "live", "me"] {
+        let calls = calls.clone();
+        unsafe {
+            dispatcher.register_handler(name, move |req: HandlerRequest| {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                let mut resp = HandlerResponse::json(
+                    200,
+                    json!({
+                        "call": n,
+                        "id": req.get_path_param("id"),
+                        "sub": req.auth_context.as_ref().and_then(|c| c.subject.clone()),
+                    }),
+                );
+                if req.handler_name == "live" {
+                    resp.set_header("cache-control", "max-age=0, No-Store".to_string());
+                }
+                let _ = req.reply_tx.send(resp);
+            });
+        }
+    }
+    let mut cache = ResponseCacheMiddleware::new(&config, &routes);
+    if let Some(ttl) = ttl {
+        cache = cache.with_ttl(ttl);
+    }
+    let cache = Arc::new(cache);
+    dispatcher.add_middleware(cache.clone());
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.register_security_provider("UserAuth", Arc::new(UserHeader));
+    service.resolve_security(&routes);
+    Fixture {
+        client: TestClient::new(service),
+        cache,
+        calls,
+        _dir: dir,
+    }
+}
+
+fn config(routes: &[&str]) -> ResponseCacheConfig {
+    ResponseCacheConfig {
+        routes: routes.iter().map(|r| r.to_string()).collect(),
+        ..ResponseCacheConfig::default()
+    }
+}
+
+/// Authenticates any `X-User` header, with its value as the subject
+struct UserHeader;
+
+impl SecurityProvider for UserHeader {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.headers.get("x-user").is_some()
+    }
+
+    fn extract_claims(&self, _scheme: &SecurityScheme, req: &SecurityRequest) -> Option<Value> {
+        Some(json!({ "sub": req.headers.get("x-user")? }))
+    }
+}
+
+#[test]
+fn second_request_is_a_hit_without_the_handler() {
+    let f = fixture(config(&["list_pets", "get_pet"]), None);
+
+    f.client
+        .get("/pets?limit=2")
+        .send()
+        .assert_status(200)
+        .assert_header("x-cache", "MISS")
+        .assert_json_at("/call", json!(1));
+    let hit = f.client.get("/pets?limit=2").send();
+    hit.assert_status(200)
+        .assert_header("x-cache", "HIT")
+        .assert_header("age", "0")
+        .assert_json_at("/call", json!(1));
+    assert_eq!(f.calls(), 1);
+
+    // Path parameters select the entry even though both share one route template.
+    f.client
+        .get("/pets/1")
+        .send()
+        .assert_header("x-cache", "MISS");
+    f.client
+        .get("/pets/2")
+        .send()
+        .assert_header("x-cache", "MISS")
+        .assert_json_at("/id", json!("2"));
+    f.client
+        .get("/pets/1")
+        .send()
+        .assert_header("x-cache", "HIT")
+        .assert_json_at("/id", json!("1"));
+    assert_eq!(f.calls(), 3);
+    assert_eq!(f.cache.len(), 3);
+}
+
+#[test]
+fn query_and_accept_select_the_entry() {
+    let f = fixture(config(&["list_pets"]), None);
+    f.client.get("/pets?limit=2").send();
+    f.client
+        .get("/pets?limit=3")
+        .send()
+        .assert_header("x-cache", "MISS");
+    f.client
+        .get("/pets?limit=2")
+        .header("accept", "application/xml")
+        .send()
+        .assert_header("x-cache", "MISS");
+    assert_eq!(f.calls(), 3);
+
+    // With `query_params: [limit]` other parameters share the entry.
+    let f = fixture(
+        ResponseCacheConfig {
+            query_params: Some(vec!["limit".to_string()]),
+            ..config(&["list_pets"])
+        },
+        None,
+    );
+    f.client.get("/pets?limit=2&trace=a").send();
+    f.client
+        .get("/pets?trace=b&limit=2")
+        .send()
+        .assert_header("x-cache", "HIT");
+    assert_eq!(f.calls(), 1);
+}
+
+#[test]
+fn entries_expire_after_the_ttl() {
+    let f = fixture(config(&["list_pets"]), Some(Duration::from_millis(200)));
+    f.client
+        .get("/pets")
+        .send()
+        .assert_json_at("/call", json!(1));
+    f.client.get("/pets").send().assert_header("x-cache", "HIT");
+
+    std::thread::sleep(Duration::from_millis(300));
+    f.client
+        .get("/pets")
+        .send()
+        .assert_header("x-cache", "MISS")
+        .assert_json_at("/call", json!(2));
+    f.client
+        .get("/pets")
+        .send()
+        .assert_header("x-cache", "HIT")
+        .assert_json_at("/call", json!(2));
+}
+
+#[test]
+fn max_entries_drops_the_least_recently_used() {
+    let f = fixture(
+        ResponseCacheConfig {
+            max_entries: 2,
+            ..config(&["get_pet"])
+        },
+        None,
+    );
+    for id in ["1", "2", "1", "3"] {
+        f.client.get(&format!("/pets/{id}")).send();
+    }
+    assert_eq!(f.cache.len(), 2);
+    // 2 was the least recently used when 3 came in.
+    f.client
+        .get("/pets/1")
+        .send()
+        .assert_header("x-cache", "HIT");
+    f.client
+        .get("/pets/2")
+        .send()
+        .assert_header("x-cache", "MISS");
+}
+
+#[test]
+fn no_store_responses_and_unlisted_routes_are_not_cached() {
+    let f = fixture(config(&["live"]), None);
+    f.client
+        .get("/live")
+        .send()
+        .assert_header("x-cache", "MISS");
+    f.client
+        .get("/live")
+        .send()
+        .assert_header("x-cache", "MISS")
+        .assert_json_at("/call", json!(2));
+    assert!(f.cache.is_empty());
+
+    let resp = f.client.get("/pets").send();
+    assert_eq!(resp.header("x-cache"), None);
+    f.client
+        .get("/pets")
+        .send()
+        .assert_json_at("/call", json!(4));
+}
+
+#[test]
+fn auth_runs_on_hits_and_callers_get_their_own_entries() {
+    let f = fixture(config(&["me"]), None);
+    f.client
+        .get("/me")
+        .header("x-user", "alice")
+        .send()
+        .assert_header("x-cache", "MISS");
+    f.client
+        .get("/me")
+        .header("x-user", "alice")
+        .send()
+        .assert_header("x-cache", "HIT")
+        .assert_json_at("/sub", json!("alice"));
+
+    // A cached response is no way around authentication.
+    let resp = f.client.get("/me").send();
+    resp.assert_status(401);
+    assert_eq!(resp.header("x-cache"), None);
+
+    f.client
+        .get("/me")
+        .header("x-user", "bob")
+        .send()
+        .assert_header("x-cache", "MISS")
+        .assert_json_at("/sub", json!("bob"));
+    assert_eq!(f.calls(), 2);
+}
@@ -0,0 +1,191 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Response header validation: declared response `headers` must be present when required and
+//! match their schema in `fail` / `warn` mode; undocumented headers are reported, not failed.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::router::Router;
+use brrtrouter::runtime_config::ResponseValidationMode;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::RouteMeta;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r##"
+openapi: 3.1.0
+info:
+  title: Limits
+  version: "1.0"
+components:
+  headers:
+    Trace:
+      schema: { type: string, pattern: "^[0-9a-f]+$" }
+paths:
+  /limited:
+    get:
+      operationId: limited
+      parameters:
+        - name: send
+          in: query
+          schema: { type: string }
+      responses:
+        "200":
+          description: OK
+          headers:
+            X-Rate-Limit:
+              required: true
+              schema: { type: integer, minimum: 0 }
+            X-Trace:
+              $ref: "#/components/headers/Trace"
+            Content-Type:
+              schema: { type: string }
+          content:
+            application/json:
+              schema: { type: object }
+"##;
+
+fn service() -> (AppService, Vec<RouteMeta>, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("limited", |req: HandlerRequest| {
+            let mut resp = HandlerResponse::json(200, json!({ "ok": true }));
+            // Lowercase names: declared headers match case-insensitively.
+            match req.get_query_param("send").unwrap_or("ok") {
+                "ok" => resp.set_header("x-rate-limit", "100".to_string()),
+                "bad" => resp.set_header("x-rate-limit", "lots".to_string()),
+                "extra" => {
+                    resp.set_header("x-rate-limit", "100".to_string());
+                    resp.set_header("x-trace", "abc123".to_string());
+                    resp.set_header("X-Debug", "1".to_string());
+                }
+                _ => {}
+            }
+            let _ = req.reply_tx.send(resp);
+        });
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    (service, routes, dir)
+}
+
+fn client(mode: ResponseValidationMode) -> (TestClient, tempfile::TempDir) {
+    let (mut service, _routes, dir) = service();
+    service.set_response_header_validation(mode);
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn declared_headers_are_extracted_from_the_spec() {
+    let (_service, routes, _dir) = service();
+    let headers = &routes[0].response_headers[&200];
+    let names: Vec<&str> = headers.iter().map(|h| h.name.as_str()).collect();
+    // Content-Type is ignored, as OpenAPI specifies; the `$ref` is resolved.
+    assert_eq!(names, ["X-Rate-Limit", "X-Trace"]);
+    assert!(headers[0].required);
+    assert!(!headers[1].required);
+    assert_eq!(headers[1].schema.as_ref().unwrap()["type"], "string");
+}
+
+#[test]
+fn missing_required_header_fails() {
+    let (client, _dir) = client(ResponseValidationMode::Fail);
+    client
+        .get("/limited?send=none")
+        .send()
+        .assert_status(500)
+        .assert_json_at("/detail", json!("Response header validation failed"))
+        .assert_json_at("/headers", json!(["X-Rate-Limit"]))
+        .assert_json_at("/errors/0/keyword", json!("required"))
+        .assert_json_at(
+            "/errors/0/detail",
+            json!("missing required header 'X-Rate-Limit'"),
+        );
+}
+
+#[test]
+fn wrongly_typed_header_fails() {
+    let (client, _dir) = client(ResponseValidationMode::Fail);
+    let resp = client.get("/limited?send=bad").send();
+    resp.assert_status(500)
+        .assert_json_at("/headers", json!(["X-Rate-Limit"]))
+        .assert_json_at("/errors/0/keyword", json!("type"));
+    let detail = resp.body()["errors"][0]["detail"].as_str().unwrap();
+    assert!(detail.starts_with("header 'X-Rate-Limit': "), "{detail}");
+}
+
+#[test]
+fn conforming_headers_pass_whatever_their_case() {
+    let (client, _dir) = client(ResponseValidationMode::Fail);
+    client
+        .get("/limited")
+        .send()
+        .assert_status(200)
+        .assert_header("x-rate-limit", "100");
+    // An undocumented header is only logged.
+    client
+        .get("/limited?send=extra")
+        .send()
+        .assert_status(200)
+        .assert_header("x-debug", "1");
+}
+
+#[test]
+fn warn_and_off_modes_send_the_response() {
+    for mode in [ResponseValidationMode::Warn, ResponseValidationMode::Off] {
+        let (client, _dir) = client(mode);
+        client
+            .get("/limited?send=bad")
+            .send()
+            .assert_status(200)
+            .assert_json(&json!({ "ok": true }));
+        client.get("/limited?send=none").send().assert_status(200);
+    }
+}
+
+#[test]
+fn undocumented_headers_are_reported_unless_allowed() {
+    let (mut service, routes, _dir) = service();
+    service.set_response_header_validation(ResponseValidationMode::Warn);
+    let headers: HeaderVec = [
+        ("X-RATE-LIMIT", "5"),
+        ("Content-Type", "application/json"),
+        ("X-Debug", "1"),
+        ("x-debug", "2"),
+        ("X-Internal", "yes"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (Arc::from(k), v.to_string()))
+    .collect();
+
+    let report =
+        service
+            .response_headers
+            .check(&service.validator_cache, &routes[0], 200, &headers);
+    assert!(report.errors.is_empty(), "{report:?}");
+    assert_eq!(report.undocumented, ["X-Debug", "X-Internal"]);
+
+    service.allow_response_headers(["x-internal"]);
+    let report =
+        service
+            .response_headers
+            .check(&service.validator_cache, &routes[0], 200, &headers);
+    assert_eq!(report.undocumented, ["X-Debug"]);
+
+    // Other statuses declare no headers: every non-standard one is undocumented.
+    let report =
+        service
+            .response_headers
+            .check(&service.validator_cache, &routes[0], 404, &headers);
+    assert_eq!(report.undocumented, ["X-RATE-LIMIT", "X-Debug"]);
+}
@@ -0,0 +1,173 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Response schema validation reaches array elements and nested objects: in `Fail` mode a
+//! violating handler body becomes a 500 problem whose `pointer` names the first failure; in
+//! `Warn` mode the body is sent unchanged.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::runtime_config::ResponseValidationMode;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Responses
+  version: "1.0"
+paths:
+  /items:
+    get:
+      operationId: list_items
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/Item'
+  /owner:
+    get:
+      operationId: get_owner
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: object
+                required: [name, address]
+                properties:
+                  name: { type: string }
+                  address:
+                    type: object
+                    properties:
+                      zip: { type: integer }
+components:
+  schemas:
+    Item:
+      type: object
+      required: [id, name]
+      properties:
+        id: { type: integer }
+        name: { type: string }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(mode: ResponseValidationMode, items: Value, owner: Value) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_items", move |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, items.clone()));
+        });
+        dispatcher.register_handler("get_owner", move |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, owner.clone()));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_response_validation(mode);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn get(server: &Server, path: &str) -> (u16, Value) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let status = resp
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let body = resp
+        .split_once("\r\n\r\n")
+        .and_then(|(_, b)| serde_json::from_str(b).ok())
+        .unwrap_or(Value::Null);
+    (status, body)
+}
+
+fn valid_owner() -> Value {
+    json!({"name": "ada", "address": {"zip": 12345}})
+}
+
+#[test]
+fn array_element_missing_required_field_fails() {
+    let server = start(
+        ResponseValidationMode::Fail,
+        json!([{"id": 1, "name": "ok"}, {"id": 2}]),
+        valid_owner(),
+    );
+    let (status, body) = get(&server, "/items");
+    assert_eq!(status, 500, "{body}");
+    assert_eq!(body["pointer"], "#/1");
+    assert!(body["errors"][0]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("name"));
+}
+
+#[test]
+fn nested_object_with_wrong_type_fails() {
+    let server = start(
+        ResponseValidationMode::Fail,
+        json!([]),
+        json!({"name": "ada", "address": {"zip": "not-a-number"}}),
+    );
+    let (status, body) = get(&server, "/owner");
+    assert_eq!(status, 500, "{body}");
+    assert_eq!(body["pointer"], "#/address/zip");
+
+    let (status, body) = get(&server, "/items");
+    assert_eq!(status, 200, "{body}");
+}
+
+#[test]
+fn warn_mode_sends_the_invalid_body() {
+    let items = json!([{"id": "x"}]);
+    let server = start(ResponseValidationMode::Warn, items.clone(), valid_owner());
+    let (status, body) = get(&server, "/items");
+    assert_eq!(status, 200);
+    assert_eq!(body, items);
+}
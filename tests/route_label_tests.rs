@@ -0,0 +1,113 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Route labels: concrete paths of one route share its template as the metrics `path` label,
+//! for parameterized, wildcard-style (`{+rest}`) and matrix (`;color=red`) routes alike.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::MetricsMiddleware;
+use brrtrouter::router::{route_label, Router};
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Labels
+  version: "1.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: integer } }
+      responses:
+        "200": { description: OK }
+  /files/{+rest}:
+    get:
+      operationId: get_file
+      responses:
+        "200": { description: OK }
+  /colors/{color}:
+    get:
+      operationId: get_color
+      parameters:
+        - name: color
+          in: path
+          required: true
+          style: matrix
+          schema: { type: string }
+      responses:
+        "200": { description: OK }
+"#;
+
+#[test]
+fn templates_reduce_to_plain_names() {
+    for (template, label) in [
+        ("/pets/{id}", "/pets/{id}"),
+        ("/pets/{id}/toys/{toy}", "/pets/{id}/toys/{toy}"),
+        ("/files/{+rest}", "/files/{rest}"),
+        ("/files/{rest*}", "/files/{rest}"),
+        ("/files/{/rest*}", "/files/{rest}"),
+        ("/colors/{;color}", "/colors/{color}"),
+        ("/search{?q,limit}", "/search{q,limit}"),
+        ("/logs/{day:10}", "/logs/{day}"),
+        ("/health", "/health"),
+        ("/pets/", "/pets/"),
+    ] {
+        assert_eq!(route_label(template), label, "{template}");
+    }
+}
+
+#[test]
+fn concrete_paths_share_their_route_label() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in ["get_pet", "get_file", "get_color"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({ "ok": true })));
+            });
+        }
+    }
+    let metrics = Arc::new(MetricsMiddleware::new());
+    dispatcher.add_middleware(metrics.clone());
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_metrics_middleware(metrics.clone());
+    let client = TestClient::new(service);
+
+    for path in [
+        "/pets/123",
+        "/pets/456",
+        "/files/report.pdf",
+        "/files/notes.txt",
+        "/colors/;color=red",
+        "/colors/;color=blue",
+    ] {
+        client.get(path).send().assert_status(200);
+    }
+
+    let stats = metrics.path_stats();
+    for label in ["/pets/{id}", "/files/{rest}", "/colors/{color}"] {
+        assert_eq!(stats[label].0, 2, "{label}: {stats:?}");
+    }
+    // Only the route labels: no concrete path and no raw template became a series.
+    assert_eq!(stats.len(), 3, "{stats:?}");
+    let statuses = metrics.status_stats();
+    assert_eq!(statuses[&("/files/{rest}".to_string(), 200)], 2);
+    assert_eq!(statuses.len(), 3, "{statuses:?}");
+}
@@ -0,0 +1,187 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Request span attributes: the `http_request` span carries the matched route's label
+//! (`http.route`, `<unmatched>` for 404s), the handler (`brrt.handler`) and the router lookup
+//! and validation times (`brrt.route_match_us`, `brrt.validation_us`).
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, UNMATCHED_ROUTE};
+use common::http::send_request;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Pets
+  version: "1.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: integer } }
+      responses:
+        "200": { description: OK }
+  /files/{+rest}:
+    get:
+      operationId: get_file
+      responses:
+        "200": { description: OK }
+"#;
+
+type Fields = BTreeMap<String, String>;
+
+/// Fields of every closed `http_request` span
+static CLOSED: Mutex<Vec<Fields>> = Mutex::new(Vec::new());
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+/// Collects the fields of `http_request` spans, recorded at creation or later.
+struct RequestSpans;
+
+impl<S> Layer<S> for RequestSpans
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "http_request" {
+            return;
+        }
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        if let Some(fields) = span.extensions_mut().remove::<Fields>() {
+            CLOSED.lock().unwrap().push(fields);
+        }
+    }
+}
+
+/// The closed `http_request` span whose `path` is `path`
+fn span_for(path: &str) -> Fields {
+    for _ in 0..50 {
+        if let Some(fields) = CLOSED
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|f| f.get("path").map(String::as_str) == Some(path))
+        {
+            return fields.clone();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    panic!(
+        "no http_request span for {path}: {:?}",
+        CLOSED.lock().unwrap()
+    );
+}
+
+#[test]
+fn request_span_carries_route_attributes() {
+    // Requests run on may worker threads: the layer must be installed globally.
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(RequestSpans))
+        .unwrap();
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        for name in ["get_pet", "get_file"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req
+                    .reply_tx
+                    .send(HandlerResponse::json(200, json!({ "ok": true })));
+            });
+        }
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+
+    let get = |path: &str| {
+        send_request(
+            &addr,
+            &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n"),
+        )
+    };
+    assert!(get("/pets/7").starts_with("HTTP/1.1 200"));
+    assert!(get("/pets/seven").starts_with("HTTP/1.1 400"));
+    assert!(get("/owners/7").starts_with("HTTP/1.1 404"));
+    assert!(get("/files/a.txt").starts_with("HTTP/1.1 200"));
+    handle.stop();
+
+    let matched = span_for("/pets/7");
+    assert_eq!(matched["http.route"], "/pets/{id}");
+    assert_eq!(matched["brrt.handler"], "get_pet");
+    for timing in ["brrt.route_match_us", "brrt.validation_us"] {
+        assert!(
+            matched[timing].parse::<u64>().is_ok(),
+            "{timing}: {matched:?}"
+        );
+    }
+
+    // Rejected by validation: still timed up to the rejection.
+    let rejected = span_for("/pets/seven");
+    assert_eq!(rejected["http.route"], "/pets/{id}");
+    assert!(rejected.contains_key("brrt.validation_us"), "{rejected:?}");
+
+    // The route label, as on the metrics `path` label
+    assert_eq!(span_for("/files/a.txt")["http.route"], "/files/{rest}");
+
+    let unmatched = span_for("/owners/7");
+    assert_eq!(unmatched["http.route"], UNMATCHED_ROUTE);
+    assert!(
+        unmatched.contains_key("brrt.route_match_us"),
+        "{unmatched:?}"
+    );
+    assert!(!unmatched.contains_key("brrt.handler"), "{unmatched:?}");
+    assert!(
+        !unmatched.contains_key("brrt.validation_us"),
+        "{unmatched:?}"
+    );
+}
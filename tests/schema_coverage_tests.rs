@@ -0,0 +1,142 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Operations without a schema: `spec::check_schemas` rejects them in strict mode
+//! (`validation.strict_schema`) and only warns otherwise.
+
+use brrtrouter::server::AppConfig;
+use brrtrouter::spec::{check_schemas, missing_schemas, RouteMeta, SchemaGap};
+
+const UNSCHEMATIZED: &str = r#"
+openapi: 3.1.0
+info:
+  title: Coverage
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: add_pet
+      requestBody:
+        required: true
+        content:
+          application/json: {}
+      responses:
+        "201":
+          description: Created
+          content:
+            application/json: {}
+        "204":
+          description: Nothing
+        "400":
+          description: Bad request
+    get:
+      operationId: list_pets
+      responses:
+        "200":
+          description: OK
+  /status:
+    get:
+      operationId: status
+      responses:
+        "200":
+          description: OK
+          content:
+            text/plain: {}
+"#;
+
+const SCHEMATIZED: &str = r#"
+openapi: 3.1.0
+info:
+  title: Coverage
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: add_pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "201":
+          description: Created
+          content:
+            application/json:
+              schema: { type: object }
+        "204":
+          description: Nothing
+"#;
+
+fn routes(spec: &str) -> Vec<RouteMeta> {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, spec).unwrap();
+    brrtrouter::load_spec(spec_path.to_str().unwrap())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn lists_bodies_without_schema() {
+    let missing = missing_schemas(&routes(UNSCHEMATIZED));
+    let by_handler: Vec<(&str, &[SchemaGap])> = missing
+        .iter()
+        .map(|route| (&*route.handler_name, route.gaps.as_slice()))
+        .collect();
+    assert!(by_handler.contains(&(
+        "add_pet",
+        &[
+            SchemaGap::RequestBody("application/json".to_string()),
+            SchemaGap::Response {
+                status: 201,
+                content_type: "application/json".to_string()
+            },
+        ][..]
+    )));
+    assert!(by_handler.contains(&("list_pets", &[SchemaGap::ResponseContent(200)][..])));
+    // `text/plain` and bodiless 204 / 4xx responses need no schema.
+    assert_eq!(missing.len(), 2, "{missing:?}");
+}
+
+#[test]
+fn strict_mode_rejects_body_without_schema() {
+    let err = check_schemas(&routes(UNSCHEMATIZED), true).unwrap_err();
+    assert_eq!(err.0.len(), 2);
+    let message = err.to_string();
+    assert!(
+        message.starts_with("2 operation(s) lack a schema"),
+        "{message}"
+    );
+    assert!(
+        message.contains(
+            "POST /pets (add_pet): request body application/json has no schema, \
+             response 201 application/json has no schema"
+        ),
+        "{message}"
+    );
+    assert!(
+        message.contains("GET /pets (list_pets): response 200 declares no content"),
+        "{message}"
+    );
+}
+
+#[test]
+fn lenient_mode_passes_body_without_schema() {
+    check_schemas(&routes(UNSCHEMATIZED), false).unwrap();
+}
+
+#[test]
+fn schematized_spec_passes_both_modes() {
+    let routes = routes(SCHEMATIZED);
+    assert!(missing_schemas(&routes).is_empty());
+    check_schemas(&routes, true).unwrap();
+    check_schemas(&routes, false).unwrap();
+}
+
+#[test]
+fn strict_schema_config() {
+    assert!(!AppConfig::default().strict_schema());
+    let config: AppConfig = serde_yaml::from_str("validation:\n  strict_schema: true\n").unwrap();
+    assert!(config.strict_schema());
+    assert!(serde_yaml::from_str::<AppConfig>("validation:\n  strict: true\n").is_err());
+}
@@ -0,0 +1,137 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Self-referential and mutually recursive `$ref` schemas: the generator boxes recursive
+//! fields instead of recursing forever, rejects cycles no finite value can satisfy, and
+//! runtime `$ref` expansion stays finite while still validating nested levels.
+
+use brrtrouter::generator::collect_component_schemas;
+use brrtrouter::spec::expand_schema_refs;
+use oas3::OpenApiV3Spec;
+use serde_json::json;
+
+const SPEC_HEADER: &str = r#"openapi: 3.1.0
+info:
+  title: Cycles
+  version: "1.0"
+paths: {}
+components:
+  schemas:
+"#;
+
+fn spec_with(schemas: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openapi.yaml");
+    std::fs::write(&path, format!("{SPEC_HEADER}{schemas}")).unwrap();
+    (dir, path)
+}
+
+fn field_type(
+    types: &std::collections::HashMap<String, brrtrouter::generator::TypeDefinition>,
+    ty: &str,
+    field: &str,
+) -> (String, bool) {
+    let f = types[ty].fields.iter().find(|f| f.name == field).unwrap();
+    (f.ty.clone(), f.optional)
+}
+
+#[test]
+fn linked_list_self_reference_is_boxed() {
+    let (_dir, path) = spec_with(
+        r#"    Node:
+      type: object
+      required: [value]
+      properties:
+        value: { type: string }
+        next: { $ref: '#/components/schemas/Node' }
+        children:
+          type: array
+          items: { $ref: '#/components/schemas/Node' }
+"#,
+    );
+    let types = collect_component_schemas(&path).unwrap();
+    assert_eq!(
+        field_type(&types, "Node", "next"),
+        ("Box<Node>".to_string(), true)
+    );
+    // Vec already allocates; no Box needed.
+    assert_eq!(field_type(&types, "Node", "children").0, "Vec<Node>");
+    assert_eq!(field_type(&types, "Node", "value").0, "String");
+}
+
+#[test]
+fn mutual_recursion_boxes_both_directions() {
+    let (_dir, path) = spec_with(
+        r#"    Author:
+      type: object
+      required: [name, latest]
+      properties:
+        name: { type: string }
+        latest: { $ref: '#/components/schemas/Book' }
+    Book:
+      type: object
+      required: [title]
+      properties:
+        title: { type: string }
+        author: { $ref: '#/components/schemas/Author' }
+    Review:
+      type: object
+      properties:
+        book: { $ref: '#/components/schemas/Book' }
+"#,
+    );
+    let types = collect_component_schemas(&path).unwrap();
+    assert_eq!(
+        field_type(&types, "Author", "latest"),
+        ("Box<Book>".to_string(), false)
+    );
+    assert_eq!(
+        field_type(&types, "Book", "author"),
+        ("Box<Author>".to_string(), true)
+    );
+    // Outside the cycle, references stay by value.
+    assert_eq!(field_type(&types, "Review", "book").0, "Book");
+}
+
+#[test]
+fn all_required_cycle_is_rejected_with_schema_names() {
+    let (_dir, path) = spec_with(
+        r#"    Chicken:
+      type: object
+      required: [egg]
+      properties:
+        egg: { $ref: '#/components/schemas/Egg' }
+    Egg:
+      type: object
+      required: [chicken]
+      properties:
+        chicken: { $ref: '#/components/schemas/Chicken' }
+"#,
+    );
+    let err = collect_component_schemas(&path).unwrap_err().to_string();
+    assert!(err.contains("Chicken -> Egg -> Chicken"), "{err}");
+}
+
+#[test]
+fn runtime_expansion_is_finite_and_validates_recursively() {
+    let spec: OpenApiV3Spec = serde_yaml::from_str(&format!(
+        "{SPEC_HEADER}{}",
+        r#"    Node:
+      type: object
+      required: [value]
+      properties:
+        value: { type: string }
+        next: { $ref: '#/components/schemas/Node' }
+"#
+    ))
+    .unwrap();
+    let mut schema = json!({ "$ref": "#/components/schemas/Node" });
+    expand_schema_refs(&spec, &mut schema);
+
+    assert_eq!(schema["x-ref-name"], "Node");
+    assert_eq!(schema["properties"]["next"]["$ref"], "#");
+    assert_eq!(schema["properties"]["next"]["x-ref-name"], "Node");
+
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    assert!(validator.is_valid(&json!({"value": "a", "next": {"value": "b"}})));
+    assert!(!validator.is_valid(&json!({"value": "a", "next": {"next": {"value": "c"}}})));
+}
@@ -0,0 +1,130 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Schema dialects: the same field validates by OpenAPI 3.0 rules under `openapi-3.0` and by
+//! JSON Schema 2020-12 rules under the default, and a spec using the other dialect's syntax
+//! fails to load with the keyword's location.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppConfig, AppService, TestClient};
+use brrtrouter::spec::{load_spec_full_with_dialect, SchemaDialect};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// `spec` with an `Order` schema whose `quantity` property is `quantity`.
+fn spec(quantity: &str) -> String {
+    format!(
+        r#"
+openapi: 3.1.0
+info:
+  title: Dialects
+  version: "1.0"
+paths:
+  /orders:
+    post:
+      operationId: create_order
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [quantity]
+              properties:
+                quantity: {quantity}
+                note: {{ type: string, nullable: true }}
+      responses:
+        "200": {{ description: OK }}
+"#
+    )
+}
+
+/// The 3.0 spelling of "a positive integer"
+const BOOLEAN_BOUND: &str = "{ type: integer, minimum: 0, exclusiveMinimum: true }";
+/// The 2020-12 spelling of "a positive integer, or null"
+const NUMERIC_BOUND: &str = r#"{ type: [integer, "null"], exclusiveMinimum: 0 }"#;
+
+fn load(quantity: &str, dialect: SchemaDialect) -> (anyhow::Result<TestClient>, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, spec(quantity)).unwrap();
+    let loaded = load_spec_full_with_dialect(spec_path.to_str().unwrap(), dialect).map(
+        |(routes, schemes, _slug)| {
+            let mut dispatcher = Dispatcher::new();
+            unsafe {
+                dispatcher.register_handler("create_order", |req: HandlerRequest| {
+                    let _ = req
+                        .reply_tx
+                        .send(HandlerResponse::json(200, json!({ "ok": true })));
+                });
+            }
+            TestClient::new(
+                AppService::builder()
+                    .router(Router::new(routes))
+                    .dispatcher(dispatcher)
+                    .security_schemes(schemes)
+                    .spec_path(PathBuf::from(&spec_path))
+                    .build()
+                    .unwrap(),
+            )
+        },
+    );
+    (loaded, dir)
+}
+
+fn status(client: &TestClient, body: serde_json::Value) -> u16 {
+    client
+        .post("/orders")
+        .body("application/json", body.to_string())
+        .send()
+        .status()
+}
+
+#[test]
+fn openapi_30_boolean_bound_is_exclusive() {
+    let (client, _dir) = load(BOOLEAN_BOUND, SchemaDialect::OpenApi30);
+    let client = client.unwrap();
+    assert_eq!(status(&client, json!({ "quantity": 1 })), 200);
+    assert_eq!(status(&client, json!({ "quantity": 0 })), 400);
+    assert_eq!(status(&client, json!({ "quantity": null })), 400);
+    assert_eq!(status(&client, json!({ "quantity": 1, "note": null })), 200);
+}
+
+#[test]
+fn draft_2020_12_numeric_bound_and_type_array() {
+    let (client, _dir) = load(NUMERIC_BOUND, SchemaDialect::Draft2020_12);
+    let client = client.unwrap();
+    assert_eq!(status(&client, json!({ "quantity": 1 })), 200);
+    assert_eq!(status(&client, json!({ "quantity": 0 })), 400);
+    assert_eq!(status(&client, json!({ "quantity": null })), 200);
+    assert_eq!(status(&client, json!({ "quantity": 1, "note": null })), 200);
+}
+
+#[test]
+fn boolean_bound_fails_loading_under_2020_12() {
+    let (loaded, _dir) = load(BOOLEAN_BOUND, SchemaDialect::Draft2020_12);
+    let err = loaded.err().unwrap().to_string();
+    assert!(err.contains("2020-12"), "{err}");
+    assert!(
+        err.contains("#/paths/~1orders/post/requestBody/content/application~1json/schema/properties/quantity/exclusiveMinimum"),
+        "{err}"
+    );
+}
+
+#[test]
+fn type_array_fails_loading_under_openapi_30() {
+    let (loaded, _dir) = load(NUMERIC_BOUND, SchemaDialect::OpenApi30);
+    let err = loaded.err().unwrap().to_string();
+    assert!(err.contains("openapi-3.0"), "{err}");
+    assert!(err.contains("properties/quantity/type"), "{err}");
+}
+
+#[test]
+fn dialect_is_read_from_config() {
+    let config: AppConfig =
+        serde_yaml::from_str("validation:\n  schema_dialect: openapi-3.0\n").unwrap();
+    assert_eq!(config.schema_dialect(), SchemaDialect::OpenApi30);
+    let config: AppConfig = serde_yaml::from_str("port: 8080\n").unwrap();
+    assert_eq!(config.schema_dialect(), SchemaDialect::Draft2020_12);
+}
@@ -0,0 +1,216 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `x-request-schema` / `x-response-schema` replace the declared schemas when validating one
+//! route, so an override can accept bodies the base schema rejects. Generated-code inputs
+//! (`request_schema`, `response_schema`) keep the declared schemas, and a malformed override
+//! fails spec loading.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::runtime_config::ResponseValidationMode;
+use brrtrouter::server::{AppService, TestClient};
+use serde_json::json;
+use std::path::PathBuf;
+
+const SPEC: &str = r##"
+openapi: 3.1.0
+info:
+  title: Overrides
+  version: "1.0"
+components:
+  schemas:
+    Item:
+      type: object
+      required: [name]
+      properties:
+        name: { type: string, maxLength: 5 }
+    LegacyItem:
+      type: object
+      properties:
+        name: { type: string, maxLength: 64 }
+paths:
+  /strict:
+    post:
+      operationId: strict
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Item" }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Item" }
+  /legacy:
+    post:
+      operationId: legacy
+      x-request-schema: { $ref: "#/components/schemas/LegacyItem" }
+      x-response-schema: { type: object }
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Item" }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Item" }
+  /by-status:
+    post:
+      operationId: by_status
+      x-response-schema:
+        "200": { $ref: "#/components/schemas/LegacyItem" }
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Item" }
+"##;
+
+fn load(spec: &str) -> anyhow::Result<Vec<brrtrouter::spec::RouteMeta>> {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, spec).unwrap();
+    brrtrouter::load_spec(spec_path.to_str().unwrap()).map(|(routes, _slug)| routes)
+}
+
+fn start() -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    // Every handler answers with a name the base `Item` schema is too strict for.
+    let long_name = |req: HandlerRequest| {
+        let _ = req
+            .reply_tx
+            .send(HandlerResponse::json(200, json!({ "name": "a long name" })));
+    };
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("strict", long_name);
+        dispatcher.register_handler("legacy", long_name);
+        dispatcher.register_handler("by_status", long_name);
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_response_validation(ResponseValidationMode::Fail);
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn request_override_loosens_the_declared_schema() {
+    let (client, _dir) = start();
+    for body in [json!({}), json!({ "name": "too long for Item" })] {
+        client
+            .post("/strict")
+            .json(body.clone())
+            .send()
+            .assert_status(400);
+        client
+            .post("/legacy")
+            .json(body)
+            .send()
+            .assert_status(200)
+            .assert_json(&json!({ "name": "a long name" }));
+    }
+    // The override still validates: `name` must be a string.
+    client
+        .post("/legacy")
+        .json(json!({ "name": 7 }))
+        .send()
+        .assert_status(400);
+}
+
+#[test]
+fn response_override_loosens_the_declared_schema() {
+    let (client, _dir) = start();
+    client
+        .post("/strict")
+        .json(json!({ "name": "ok" }))
+        .send()
+        .assert_status(500);
+    client
+        .post("/by-status")
+        .send()
+        .assert_status(200)
+        .assert_json(&json!({ "name": "a long name" }));
+}
+
+#[test]
+fn overrides_leave_the_declared_schemas_alone() {
+    let routes = load(SPEC).unwrap();
+    let legacy = routes
+        .iter()
+        .find(|r| &*r.handler_name == "legacy")
+        .unwrap();
+    assert_eq!(
+        legacy.request_schema.as_ref().unwrap()["properties"]["name"]["maxLength"],
+        5
+    );
+    assert_eq!(
+        legacy.request_validation_schema().unwrap()["properties"]["name"]["maxLength"],
+        64
+    );
+    assert_eq!(
+        legacy.schema_overrides.response(201),
+        Some(&json!({ "type": "object" }))
+    );
+    assert_eq!(legacy.schema_overrides.response(404), None);
+
+    let by_status = routes
+        .iter()
+        .find(|r| &*r.handler_name == "by_status")
+        .unwrap();
+    assert!(by_status.schema_overrides.response(200).is_some());
+    assert_eq!(by_status.schema_overrides.response(201), None);
+
+    let strict = routes
+        .iter()
+        .find(|r| &*r.handler_name == "strict")
+        .unwrap();
+    assert!(strict.schema_overrides.is_empty());
+}
+
+#[test]
+fn malformed_override_fails_loading() {
+    let spec = |extension: &str| {
+        format!(
+            r#"
+openapi: 3.1.0
+info: {{ title: Broken, version: "1.0" }}
+paths:
+  /items:
+    post:
+      operationId: create_item
+      {extension}
+      responses:
+        "200": {{ description: OK }}
+"#
+        )
+    };
+
+    let err = load(&spec("x-request-schema: { type: 12 }")).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("/items"), "{message}");
+    assert!(message.contains("x-request-schema"), "{message}");
+
+    let err = load(&spec("x-response-schema: { \"200\": \"object\" }")).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("x-response-schema.200"),
+        "{err:#}"
+    );
+
+    load(&spec("x-request-schema: true")).unwrap();
+}
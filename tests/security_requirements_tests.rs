@@ -0,0 +1,228 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `security` semantics: the schemes of one requirement object must all validate (AND), the
+//! objects of the list are alternatives (OR). A group whose only failure is a valid token
+//! lacking scopes answers 403; a group also missing a credential answers 401. Both the
+//! pre-resolved and the per-request lookup paths are checked.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::{AppService, TestClient, TestRequest};
+use brrtrouter::spec::SecurityScheme;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// `AppToken` sorts before `ServiceKey`, so the token is checked first in the AND groups.
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Requirements
+  version: "1.0"
+components:
+  securitySchemes:
+    AppToken:
+      type: http
+      scheme: bearer
+    ServiceKey:
+      type: apiKey
+      in: header
+      name: X-Service-Key
+    AdminKey:
+      type: apiKey
+      in: header
+      name: X-Admin-Key
+paths:
+  /both:
+    get:
+      operationId: both
+      security:
+        - AppToken: []
+          ServiceKey: []
+      responses:
+        "200": { description: OK }
+  /either:
+    get:
+      operationId: either
+      security:
+        - AppToken: []
+        - ServiceKey: []
+      responses:
+        "200": { description: OK }
+  /mixed:
+    get:
+      operationId: mixed
+      security:
+        - AppToken: [write]
+          ServiceKey: []
+        - AdminKey: []
+      responses:
+        "200": { description: OK }
+"#;
+
+/// `Bearer read` carries scope `read`, `Bearer write` carries `read` and `write`.
+struct TokenProvider;
+
+impl SecurityProvider for TokenProvider {
+    fn validate(&self, _scheme: &SecurityScheme, scopes: &[String], req: &SecurityRequest) -> bool {
+        let granted: &[&str] = match req.get_header("authorization") {
+            Some("Bearer read") => &["read"],
+            Some("Bearer write") => &["read", "write"],
+            _ => return false,
+        };
+        scopes.iter().all(|s| granted.contains(&s.as_str()))
+    }
+}
+
+/// Accepts `value` in `header`.
+struct KeyProvider {
+    header: &'static str,
+    value: &'static str,
+}
+
+impl SecurityProvider for KeyProvider {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.get_header(self.header) == Some(self.value)
+    }
+}
+
+fn client(preresolve: bool) -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let ok = |req: HandlerRequest| {
+        let _ = req
+            .reply_tx
+            .send(HandlerResponse::json(200, json!({"ok": true})));
+    };
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("both", ok);
+        dispatcher.register_handler("either", ok);
+        dispatcher.register_handler("mixed", ok);
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.register_security_provider("AppToken", Arc::new(TokenProvider));
+    service.register_security_provider(
+        "ServiceKey",
+        Arc::new(KeyProvider {
+            header: "x-service-key",
+            value: "svc",
+        }),
+    );
+    service.register_security_provider(
+        "AdminKey",
+        Arc::new(KeyProvider {
+            header: "x-admin-key",
+            value: "admin",
+        }),
+    );
+    if preresolve {
+        service.resolve_security(&routes);
+    }
+    (TestClient::new(service), dir)
+}
+
+fn with<'a>(request: TestRequest<'a>, token: Option<&str>, service_key: bool) -> TestRequest<'a> {
+    let request = match token {
+        Some(token) => request.bearer(token),
+        None => request,
+    };
+    if service_key {
+        request.header("x-service-key", "svc")
+    } else {
+        request
+    }
+}
+
+#[test]
+fn and_requires_every_scheme_of_the_object() {
+    for preresolve in [true, false] {
+        let (client, _dir) = client(preresolve);
+        for (token, key, status) in [
+            (None, false, 401),
+            (Some("read"), false, 401),
+            (None, true, 401),
+            (Some("wrong"), true, 401),
+            (Some("read"), true, 200),
+        ] {
+            let response = with(client.get("/both"), token, key).send();
+            assert_eq!(
+                response.status(),
+                status,
+                "preresolve={preresolve} token={token:?} key={key}"
+            );
+        }
+    }
+}
+
+#[test]
+fn or_accepts_any_object_of_the_list() {
+    for preresolve in [true, false] {
+        let (client, _dir) = client(preresolve);
+        for (token, key, status) in [
+            (None, false, 401),
+            (Some("wrong"), false, 401),
+            (Some("read"), false, 200),
+            (None, true, 200),
+            (Some("read"), true, 200),
+        ] {
+            let response = with(client.get("/either"), token, key).send();
+            assert_eq!(
+                response.status(),
+                status,
+                "preresolve={preresolve} token={token:?} key={key}"
+            );
+        }
+    }
+}
+
+#[test]
+fn mixed_requirements_pick_401_or_403_per_group() {
+    for preresolve in [true, false] {
+        let (client, _dir) = client(preresolve);
+        for (token, key, status) in [
+            (Some("write"), true, 200),
+            // Valid token lacking `write`, with the key: only scopes are missing.
+            (Some("read"), true, 403),
+            // Under-scoped token without the key: the AND group is missing a credential.
+            (Some("read"), false, 401),
+            (Some("write"), false, 401),
+            (None, true, 401),
+        ] {
+            let response = with(client.get("/mixed"), token, key).send();
+            assert_eq!(
+                response.status(),
+                status,
+                "preresolve={preresolve} token={token:?} key={key}"
+            );
+        }
+
+        with(client.get("/mixed"), Some("read"), true)
+            .send()
+            .assert_header(
+                "www-authenticate",
+                "Bearer error=\"insufficient_scope\", scope=\"write\"",
+            );
+        client
+            .get("/mixed")
+            .header("x-admin-key", "admin")
+            .send()
+            .assert_status(200);
+    }
+}
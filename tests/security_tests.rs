@@ -35,10 +35,10 @@
 //! - Pre-generated JWT tokens with known signatures
 //! - Remote validation server simulator
 
-use base64::Engine;
 use brrtrouter::middleware::TracingMiddleware;
-use brrtrouter::server::{HttpServer, ServerHandle};
+use brrtrouter::server::{DefaultProviderPolicy, HttpServer, ServerHandle};
 use brrtrouter::spec::SecurityScheme;
+use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
 use brrtrouter::{
     dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec},
     load_spec_full,
@@ -211,14 +211,15 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     service.register_security_provider(
         "ApiKeyAuth",
         Arc::new(ApiKeyProvider {
@@ -289,14 +290,15 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     service.register_security_provider("KeyOne", Arc::new(ApiKeyProvider { key: "one".into() }));
     service.register_security_provider("KeyTwo", Arc::new(ApiKeyProvider { key: "two".into() }));
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -364,14 +366,15 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     service.register_security_provider("BearerAuth", Arc::new(BearerJwtProvider::new("sig")));
     service.register_security_provider(
         "OAuth",
@@ -385,13 +388,6 @@ paths:
     (tracing, handle, addr)
 }
 
-fn make_token(scope: &str) -> String {
-    use base64::{engine::general_purpose, Engine as _};
-    let header = general_purpose::STANDARD.encode(r#"{"alg":"HS256","typ":"at+jwt"}"#);
-    let payload = general_purpose::STANDARD.encode(format!(r#"{{"scope":"{scope}"}}"#));
-    format!("{}.{}.{}", header, payload, "sig")
-}
-
 fn send_request(addr: &SocketAddr, req: &str) -> String {
     let mut stream = TcpStream::connect(addr).unwrap();
     stream.write_all(req.as_bytes()).unwrap();
@@ -552,16 +548,22 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     // Use default provider wiring with a test key
-    service.register_default_security_providers_from_env(Some("secret".into()));
+    service
+        .register_default_security_providers_from_env(
+            Some("secret".into()),
+            &DefaultProviderPolicy::lenient(),
+        )
+        .unwrap();
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     drop(listener);
@@ -584,51 +586,6 @@ fn test_api_key_auth_via_authorization_bearer() {
 
 // --- JWKS Bearer provider tests ---
 
-fn start_mock_jwks_server(body: String) -> String {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let addr = listener.local_addr().unwrap();
-    let url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    thread::spawn(move || {
-        if let Ok((mut stream, _)) = listener.accept() {
-            let mut buf = [0u8; 1024];
-            let _ = stream.read(&mut buf);
-            let resp = format!(
-                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-                body.len(),
-                body
-            );
-            let _ = stream.write_all(resp.as_bytes());
-        }
-    });
-    url
-}
-
-fn base64url_no_pad(data: &[u8]) -> String {
-    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
-}
-
-fn make_hs256_jwt(secret: &[u8], iss: &str, aud: &str, kid: &str, exp_secs: i64) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        typ: Some("at+jwt".to_string()),
-        ..Default::default()
-    };
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    let claims = json!({
-        "iss": iss,
-        "aud": aud,
-        "exp": now + exp_secs,
-        "scope": "read write"
-    });
-    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-}
-
 fn start_service_with_jwks(
     jwks_url: &str,
     iss: &str,
@@ -669,14 +626,15 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url.to_string())
         .issuer(iss.to_string())
         .audience(aud.to_string());
@@ -693,17 +651,17 @@ paths:
 fn test_bearer_jwks_success() {
     // Build HS256 oct key in JWKS
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
     let server = SecurityTestServer::from_jwks(&jwks_url, iss, aud);
     let req =
         format!("GET /header HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {token}\r\n\r\n");
@@ -716,18 +674,18 @@ fn test_bearer_jwks_success() {
 #[test]
 fn test_bearer_jwks_invalid_signature() {
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
     // token signed with different secret
-    let token = make_hs256_jwt(b"wrong", iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(b"wrong")
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
     let server = SecurityTestServer::from_jwks(&jwks_url, iss, aud);
     let req =
         format!("GET /header HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {token}\r\n\r\n");
@@ -804,14 +762,15 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
     let provider = brrtrouter::security::RemoteApiKeyProvider::new(verify_url.to_string())
         .header_name("X-API-Key")
         .timeout_ms(50)
@@ -889,7 +848,7 @@ fn test_bearer_header_and_oauth_cookie() {
     assert_eq!(status, 401);
 
     // Valid bearer header
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let req =
         format!("GET /header HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {token}\r\n\r\n");
     let resp = send_request(&server.addr(), &req);
@@ -897,7 +856,7 @@ fn test_bearer_header_and_oauth_cookie() {
     assert_eq!(status_ok, 200);
 
     // OAuth2 cookie with required scope
-    let token = make_token("read");
+    let token = TestJwt::fixed_signature("sig").scopes(["read"]).sign();
     let req = format!("GET /cookie HTTP/1.1\r\nHost: localhost\r\nCookie: auth={token}\r\n\r\n");
     let resp = send_request(&server.addr(), &req);
     let status_cookie = parse_status(&resp);
@@ -938,7 +897,7 @@ fn test_bearer_jwt_token_validation() {
     };
 
     // Test valid token with no scopes
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -960,7 +919,7 @@ fn test_bearer_jwt_invalid_signature() {
         description: None,
     };
 
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1059,7 +1018,9 @@ fn test_bearer_jwt_scope_validation() {
     };
 
     // Test token with read scope
-    let token = make_token("read write");
+    let token = TestJwt::fixed_signature("sig")
+        .scopes(["read", "write"])
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1091,7 +1052,7 @@ fn test_bearer_jwt_cookie_extraction() {
         description: None,
     };
 
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut cookies: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     cookies.push((Arc::from("auth_token"), token));
@@ -1113,7 +1074,7 @@ fn test_bearer_jwt_wrong_scheme() {
         description: None,
     };
 
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1134,7 +1095,7 @@ fn test_oauth2_provider_validation() {
         description: None,
     };
 
-    let token = make_token("read");
+    let token = TestJwt::fixed_signature("sig").scopes(["read"]).sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1155,7 +1116,7 @@ fn test_oauth2_provider_cookie() {
         description: None,
     };
 
-    let token = make_token("read");
+    let token = TestJwt::fixed_signature("sig").scopes(["read"]).sign();
     let mut cookies: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     cookies.push((Arc::from("oauth_token"), token));
@@ -1177,7 +1138,7 @@ fn test_oauth2_provider_wrong_scheme() {
         description: None,
     };
 
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1325,7 +1286,7 @@ fn test_case_insensitive_bearer_scheme() {
         description: None,
     };
 
-    let token = make_token("");
+    let token = TestJwt::fixed_signature("sig").sign();
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1347,7 +1308,7 @@ fn test_empty_token_scopes() {
         description: None,
     };
 
-    let token = make_token(""); // Empty scope
+    let token = TestJwt::fixed_signature("sig").sign(); // Empty scope
     let mut headers: HeaderVec = HeaderVec::new();
     // JSF P2: HeaderVec now uses Arc<str> for keys
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1368,17 +1329,17 @@ fn test_empty_token_scopes() {
 fn test_jwks_claims_cache_caching() {
     // Test that claims are cached and reused
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1412,18 +1373,18 @@ fn test_jwks_claims_cache_caching() {
 fn test_jwks_claims_cache_expiration_with_leeway() {
     // Test that cache respects leeway for expiration
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
     // Token expires in 5 seconds
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 5);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(5)
+        .scopes(["read", "write"])
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1454,21 +1415,86 @@ fn test_jwks_claims_cache_expiration_with_leeway() {
     assert!(provider.validate(&scheme, &[], &req));
 }
 
+/// Validate `token` against a fresh provider (own mock JWKS server) built by `configure`.
+fn jwks_accepts(
+    token: &str,
+    configure: impl FnOnce(
+        brrtrouter::security::JwksBearerProvider,
+    ) -> brrtrouter::security::JwksBearerProvider,
+) -> bool {
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", b"supersecret")])).unwrap();
+    let provider = configure(brrtrouter::security::JwksBearerProvider::new(
+        jwks_server.url(),
+    ));
+    provider.stop_background_refresh();
+    let scheme = SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: None,
+        description: None,
+    };
+    let mut headers: HeaderVec = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    let req = SecurityRequest {
+        headers: &headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    provider.validate(&scheme, &[], &req)
+}
+
+#[test]
+fn test_jwks_nbf_in_future_uses_leeway() {
+    let token = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .expires_in(300)
+        .claim_from_now("nbf", 10)
+        .sign();
+    // Issuer clock 10s ahead: accepted with the default 30s leeway...
+    assert!(jwks_accepts(&token, |p| p));
+    // ...rejected when the skew exceeds the leeway
+    assert!(!jwks_accepts(&token, |p| p.leeway(5)));
+
+    let past = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .expires_in(300)
+        .claim_from_now("nbf", -10)
+        .sign();
+    assert!(jwks_accepts(&past, |p| p.leeway(0)));
+}
+
+#[test]
+fn test_jwks_future_iat_is_lenient_by_default() {
+    let token = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .expires_in(300)
+        .claim_from_now("iat", 120)
+        .sign();
+    assert!(jwks_accepts(&token, |p| p));
+    assert!(!jwks_accepts(&token, |p| p.reject_future_iat(true)));
+    // Within leeway is still accepted when rejection is enabled
+    let skewed = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .expires_in(300)
+        .claim_from_now("iat", 10)
+        .sign();
+    assert!(jwks_accepts(&skewed, |p| p.reject_future_iat(true)));
+}
+
 #[test]
 fn test_jwks_cookie_support() {
     // Test that JwksBearerProvider supports cookie extraction
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1527,14 +1553,8 @@ fn test_jwks_url_127_subdomain_attack() {
 fn test_jwks_url_localhost_allowed() {
     // Test that localhost HTTP is allowed for testing
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     // Should not panic for localhost
     let _provider = brrtrouter::security::JwksBearerProvider::new(jwks_url);
 }
@@ -1564,17 +1584,17 @@ fn test_jwks_url_127_with_port() {
 fn test_jwks_cache_invalidation() {
     // Test cache invalidation methods
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1615,64 +1635,28 @@ fn test_jwks_cache_invalidation_does_not_clear_other_tokens() {
     // Test that invalidate_token() only invalidates the specific token,
     // not the entire cache (fixes thundering herd bug)
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let iss = "https://issuer.example";
     let aud = "my-audience";
 
     // Create two different tokens with unique subjects to ensure they're different
     // (JWT encoding may be non-deterministic, but adding unique claims guarantees different tokens)
-    let token1 = {
-        use jsonwebtoken::{Algorithm, EncodingKey, Header};
-        use serde_json::json;
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": now + 3600,
-            "sub": "user1",
-            "scope": "read write"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
-    let token2 = {
-        use jsonwebtoken::{Algorithm, EncodingKey, Header};
-        use serde_json::json;
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": now + 3600,
-            "sub": "user2",
-            "scope": "read write"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
+    let token1 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("sub", "user1")
+        .sign();
+    let token2 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("sub", "user2")
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1746,18 +1730,18 @@ fn test_jwks_cache_invalidation_does_not_clear_other_tokens() {
 fn test_jwks_extract_claims() {
     // Test that extract_claims() returns decoded JWT claims for BFF pattern
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let iss = "https://issuer.example";
     let aud = "my-audience";
-    let token = make_hs256_jwt(secret, iss, aud, "k1", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
 
     let provider = brrtrouter::security::JwksBearerProvider::new(jwks_url)
         .issuer(iss.to_string())
@@ -1830,14 +1814,8 @@ fn test_jwks_extract_claims() {
 fn test_jwks_cache_eviction() {
     // Test that LRU cache evicts entries when at capacity
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
 
@@ -1853,56 +1831,27 @@ fn test_jwks_cache_eviction() {
     };
 
     // Create 3 different tokens with unique jti (JWT ID) to ensure they're distinct
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-    let token1 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token1"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
-    let token2 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token2"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
-    let token3 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token3"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
+    let token1 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token1")
+        .sign();
+    let token2 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token2")
+        .sign();
+    let token3 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token3")
+        .sign();
 
     // Validate token1 - should cache
     let mut headers1: HeaderVec = HeaderVec::new();
@@ -1951,14 +1900,8 @@ fn test_jwks_cache_eviction() {
 fn test_jwks_cache_evictions_counter() {
     // Test that evictions counter correctly tracks LRU evictions
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
     let iss = "https://issuer.example";
     let aud = "my-audience";
 
@@ -1979,40 +1922,20 @@ fn test_jwks_cache_evictions_counter() {
 
     // Fill cache to capacity (2 entries) - no evictions yet
     // Generate unique tokens by including a unique jti (JWT ID) claim
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-    let token1 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token1"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
-    let token2 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token2"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
+    let token1 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token1")
+        .sign();
+    let token2 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token2")
+        .sign();
 
     let mut headers1: HeaderVec = HeaderVec::new();
     headers1.push((Arc::from("authorization"), format!("Bearer {token1}")));
@@ -2041,22 +1964,13 @@ fn test_jwks_cache_evictions_counter() {
     assert_eq!(stats.size, 2, "Cache should have 2 entries");
 
     // Insert token3 - should evict token1 (LRU)
-    let token3 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token3"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
+    let token3 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token3")
+        .sign();
     let mut headers3: HeaderVec = HeaderVec::new();
     headers3.push((Arc::from("authorization"), format!("Bearer {token3}")));
     let req3 = SecurityRequest {
@@ -2078,22 +1992,13 @@ fn test_jwks_cache_evictions_counter() {
     );
 
     // Insert token4 - should evict token2 (LRU, token3 is now most recent)
-    let token4 = {
-        let header = Header {
-            kid: Some("k1".to_string()),
-            alg: Algorithm::HS256,
-            typ: Some("at+jwt".to_string()),
-            ..Default::default()
-        };
-        let claims = json!({
-            "iss": iss,
-            "aud": aud,
-            "exp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64 + 3600,
-            "scope": "read write",
-            "jti": "token4"
-        });
-        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-    };
+    let token4 = TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(iss)
+        .audience(aud)
+        .scopes(["read", "write"])
+        .claim("jti", "token4")
+        .sign();
     let mut headers4: HeaderVec = HeaderVec::new();
     headers4.push((Arc::from("authorization"), format!("Bearer {token4}")));
     let req4 = SecurityRequest {
@@ -2583,20 +2488,11 @@ fn test_jwks_sub_second_cache_ttl_precision() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -2628,7 +2524,13 @@ fn test_jwks_sub_second_cache_ttl_precision() {
     // Make multiple validation calls in quick succession
     // If the bug existed, each call would see cache as expired (0s TTL)
     // and spawn a refresh thread, causing many HTTP requests
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -2692,20 +2594,11 @@ fn test_jwks_sub_second_cache_ttl_various_values() {
         let request_count_clone = request_count.clone();
 
         let secret = b"test-secret-key-32-bytes!!";
-        let k = base64url_no_pad(secret);
-        let jwks = json!({
-            "keys": [{
-                "kty": "oct",
-                "kid": "test-key",
-                "alg": "HS256",
-                "k": k
-            }]
-        });
+        let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
         let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-        let jwks_body = jwks.to_string();
 
         // Spawn server that counts requests
         std::thread::spawn(move || {
@@ -2733,7 +2626,13 @@ fn test_jwks_sub_second_cache_ttl_various_values() {
         std::thread::sleep(Duration::from_millis(millis + 50));
 
         // Make rapid validation calls - should not trigger constant refreshes
-        let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+        let token = TestJwt::hs256(secret)
+            .kid("test-key")
+            .issuer("https://issuer.example")
+            .audience("audience")
+            .expires_in(60)
+            .scopes(["read", "write"])
+            .sign();
         let scheme = SecurityScheme::Http {
             scheme: "bearer".to_string(),
             bearer_format: None,
@@ -2790,20 +2689,11 @@ fn test_jwks_sub_second_cache_ttl_timing_accuracy() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests (generous accept loop for slow CI)
     std::thread::spawn(move || {
@@ -2836,7 +2726,13 @@ fn test_jwks_sub_second_cache_ttl_timing_accuracy() {
         "Initial JWKS fetch did not complete within timeout"
     );
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -2938,20 +2834,11 @@ fn test_jwks_sub_second_cache_ttl_no_thread_storm() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -2981,7 +2868,13 @@ fn test_jwks_sub_second_cache_ttl_no_thread_storm() {
     // Wait for initial refresh
     std::thread::sleep(Duration::from_millis(150));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3210,20 +3103,11 @@ fn test_jwks_refresh_thread_storm_prevention() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -3259,7 +3143,13 @@ fn test_jwks_refresh_thread_storm_prevention() {
     );
 
     // Create a valid token for validation
-    let token = make_hs256_jwt(secret, iss, aud, "test-key", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3369,20 +3259,11 @@ fn test_jwks_refresh_atomic_claim_prevention() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -3418,7 +3299,13 @@ fn test_jwks_refresh_atomic_claim_prevention() {
     );
 
     // Create a valid token for validation
-    let token = make_hs256_jwt(secret, iss, aud, "test-key", 3600);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer(iss)
+        .audience(aud)
+        .expires_in(3600)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3519,20 +3406,11 @@ fn test_jwks_condition_variable_wakeup_on_refresh_completion() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server with small delay to simulate network latency
     std::thread::spawn(move || {
@@ -3566,7 +3444,13 @@ fn test_jwks_condition_variable_wakeup_on_refresh_completion() {
     // Clear cache by accessing internal state (simulate empty cache)
     // We'll trigger refresh by calling get_key_for which calls refresh_jwks_if_needed
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3684,13 +3568,13 @@ fn test_jwks_condition_variable_wakeup_on_refresh_failure() {
     // Stop background refresh
     provider.stop_background_refresh();
 
-    let token = make_hs256_jwt(
-        b"test-secret-key-32-bytes!!",
-        "https://issuer.example",
-        "audience",
-        "test-key",
-        60,
-    );
+    let token = TestJwt::hs256(b"test-secret-key-32-bytes!!")
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3767,20 +3651,11 @@ fn test_jwks_refresh_in_progress_flag_recovery() {
     // We test recovery by manually setting flag, then verifying system can clear it and continue
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server
     std::thread::spawn(move || {
@@ -3818,7 +3693,13 @@ fn test_jwks_refresh_in_progress_flag_recovery() {
     // If the refresh_in_progress flag was stuck, subsequent validations would hang
     // The key test is that the system doesn't get stuck, not that all validations succeed
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -3876,20 +3757,11 @@ fn test_jwks_empty_cache_multiple_waiters_all_woken() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server with delay
     std::thread::spawn(move || {
@@ -3923,7 +3795,13 @@ fn test_jwks_empty_cache_multiple_waiters_all_woken() {
     // Wait a bit to ensure background thread is fully stopped
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4027,20 +3905,11 @@ fn test_jwks_empty_cache_timeout_retry() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that delays first response (simulating slow network)
     // First request will take > 2s (condition variable timeout), subsequent requests will succeed
@@ -4087,7 +3956,13 @@ fn test_jwks_empty_cache_timeout_retry() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4182,20 +4057,11 @@ fn test_jwks_empty_cache_retry_after_failed_refresh() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that returns HTTP 500 error first (refresh fails), then valid response
     let request_num = Arc::new(std::sync::Mutex::new(0));
@@ -4239,7 +4105,13 @@ fn test_jwks_empty_cache_retry_after_failed_refresh() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4340,7 +4212,13 @@ fn test_jwks_empty_cache_no_retry_on_successful_empty_response() {
     std::thread::sleep(Duration::from_millis(100));
 
     let secret = b"test-secret-key-32-bytes!!";
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4398,20 +4276,11 @@ fn test_jwks_background_thread_refresh_interval() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4482,20 +4351,11 @@ fn test_jwks_background_thread_starts_on_creation() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4553,20 +4413,11 @@ fn test_jwks_background_thread_handles_shutdown_flag() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4635,20 +4486,11 @@ fn test_jwks_background_thread_ttl_change_detection() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4728,20 +4570,11 @@ fn test_jwks_thread_spawn_failure_recovery() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4775,7 +4608,13 @@ fn test_jwks_thread_spawn_failure_recovery() {
     // This tests the atomic claim mechanism and ensures the flag doesn't get stuck
     // Even if a thread spawn were to fail, the flag should be cleared and subsequent
     // attempts should succeed
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4854,20 +4693,11 @@ fn test_jwks_thread_spawn_failure_doesnt_deadlock() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server that counts requests
     std::thread::spawn(move || {
@@ -4897,7 +4727,13 @@ fn test_jwks_thread_spawn_failure_doesnt_deadlock() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -4988,20 +4824,11 @@ fn test_jwks_spawn_failure_wakeup_timing() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server with minimal delay
     std::thread::spawn(move || {
@@ -5033,7 +4860,13 @@ fn test_jwks_spawn_failure_wakeup_timing() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5125,20 +4958,11 @@ fn test_jwks_empty_cache_condition_variable_wait() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Spawn server with delay to ensure threads have time to wait
     std::thread::spawn(move || {
@@ -5170,7 +4994,13 @@ fn test_jwks_empty_cache_condition_variable_wait() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5290,20 +5120,11 @@ fn test_jwks_empty_cache_wakeup_on_completion() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Track when refresh completes
     let refresh_complete_time = Arc::new(std::sync::Mutex::new(Option::<Instant>::None));
@@ -5340,7 +5161,13 @@ fn test_jwks_empty_cache_wakeup_on_completion() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5433,20 +5260,11 @@ fn test_jwks_empty_cache_retry_claim_race() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Server returns invalid JSON first (refresh fails, cache stays empty), then valid JWKS
     let request_num = Arc::new(std::sync::Mutex::new(0));
@@ -5490,7 +5308,13 @@ fn test_jwks_empty_cache_retry_claim_race() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5569,20 +5393,11 @@ fn test_jwks_empty_cache_timeout_still_empty_check() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Server that returns 500 error on first request (refresh fails), then succeeds on retry
     // This ensures the refresh actually fails (not just slow), so retry happens
@@ -5628,7 +5443,13 @@ fn test_jwks_empty_cache_timeout_still_empty_check() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5721,20 +5542,11 @@ fn test_jwks_empty_cache_timeout_recovery() {
     let request_count_clone = request_count.clone();
 
     let secret = b"test-secret-key-32-bytes!!";
-    let k = base64url_no_pad(secret);
-    let jwks = json!({
-        "keys": [{
-            "kty": "oct",
-            "kid": "test-key",
-            "alg": "HS256",
-            "k": k
-        }]
-    });
+    let jwks_body = jwks([hs256_jwk("test-key", secret)]);
 
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let jwks_url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let jwks_body = jwks.to_string();
 
     // Server that returns 500 error on first request (refresh fails), then succeeds on retry
     // This ensures the refresh actually fails (not just slow), so retry happens
@@ -5780,7 +5592,13 @@ fn test_jwks_empty_cache_timeout_recovery() {
     provider.stop_background_refresh();
     std::thread::sleep(Duration::from_millis(100));
 
-    let token = make_hs256_jwt(secret, "https://issuer.example", "audience", "test-key", 60);
+    let token = TestJwt::hs256(secret)
+        .kid("test-key")
+        .issuer("https://issuer.example")
+        .audience("audience")
+        .expires_in(60)
+        .scopes(["read", "write"])
+        .sign();
     let scheme = SecurityScheme::Http {
         scheme: "bearer".to_string(),
         bearer_format: None,
@@ -5864,3 +5682,124 @@ fn test_jwks_empty_cache_timeout_recovery() {
 
     provider.stop_background_refresh();
 }
+
+// --- Optional authentication (x-auth-optional) tests ---
+
+/// Accepts `X-User: <name>` when name is non-empty and not "invalid"; exposes `{"sub": name}`.
+struct HeaderUserProvider;
+
+impl SecurityProvider for HeaderUserProvider {
+    fn validate(
+        &self,
+        _scheme: &SecurityScheme,
+        _scopes: &[String],
+        req: &SecurityRequest,
+    ) -> bool {
+        req.get_header("x-user")
+            .is_some_and(|u| !u.is_empty() && u != "invalid")
+    }
+
+    fn extract_claims(
+        &self,
+        _scheme: &SecurityScheme,
+        req: &SecurityRequest,
+    ) -> Option<serde_json::Value> {
+        req.get_header("x-user").map(|u| json!({ "sub": u }))
+    }
+}
+
+fn start_optional_auth_service() -> (TestTracing, ServerHandle, SocketAddr) {
+    may::config().set_stack_size(0x8000);
+    let tracing = TestTracing::init();
+    const SPEC: &str = r#"openapi: 3.1.0
+info:
+  title: Optional Auth API
+  version: '1.0'
+components:
+  securitySchemes:
+    UserAuth:
+      type: apiKey
+      in: header
+      name: X-User
+paths:
+  /feed:
+    get:
+      operationId: feed
+      x-auth-optional: true
+      security:
+        - UserAuth: []
+      responses:
+        '200': { description: OK }
+  /account:
+    get:
+      operationId: account
+      security:
+        - UserAuth: []
+      responses:
+        '200': { description: OK }
+"#;
+    let path = temp_files::create_temp_yaml(SPEC);
+    let (routes, schemes, _slug) = load_spec_full(path.to_str().unwrap()).unwrap();
+    let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes)));
+    let mut dispatcher = Dispatcher::new();
+    // SAFETY: Test context - handlers are simple closures for testing
+    unsafe {
+        for name in ["feed", "account"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                let _ = req.reply_tx.send(HandlerResponse {
+                    status: 200,
+                    headers: HeaderVec::new(),
+                    body: json!({ "authenticated": req.jwt_claims.is_some() }),
+                });
+            });
+        }
+    }
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .build()
+        .unwrap();
+    service.register_security_provider("UserAuth", Arc::new(HeaderUserProvider));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    (tracing, handle, addr)
+}
+
+fn optional_auth_get(addr: &SocketAddr, path: &str, user: Option<&str>) -> (u16, String) {
+    let user_header = user.map(|u| format!("X-User: {u}\r\n")).unwrap_or_default();
+    let resp = send_request(
+        addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{user_header}\r\n"),
+    );
+    let body = resp.split("\r\n\r\n").nth(1).unwrap_or("").to_string();
+    (parse_status(&resp), body)
+}
+
+#[test]
+fn test_optional_auth_present_absent_invalid() {
+    let (_tracing, handle, addr) = start_optional_auth_service();
+
+    let (status, body) = optional_auth_get(&addr, "/feed", Some("alice"));
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""authenticated":true"#), "{body}");
+
+    let (status, body) = optional_auth_get(&addr, "/feed", None);
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""authenticated":false"#), "{body}");
+
+    // A supplied-but-invalid credential is treated as anonymous, not rejected.
+    let (status, body) = optional_auth_get(&addr, "/feed", Some("invalid"));
+    assert_eq!(status, 200);
+    assert!(body.contains(r#""authenticated":false"#), "{body}");
+
+    // Routes without the extension keep enforcing authentication.
+    let (status, _) = optional_auth_get(&addr, "/account", None);
+    assert_eq!(status, 401);
+
+    handle.stop();
+}
@@ -0,0 +1,145 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `Server` header: the default value, a configured value and suppression apply alike to
+//! handler, static-file and error responses.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpConfig, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Server header
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start(http: Option<HttpConfig>) -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir(&static_dir).unwrap();
+    std::fs::write(static_dir.join("hello.txt"), "hello").unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({ "pets": [] })));
+        });
+    }
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .static_dir(static_dir)
+        .build()
+        .unwrap();
+    if let Some(http) = http {
+        service.set_server_header(http.server_header());
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// (status, lowercased header block) of `GET path`
+fn get(server: &Server, path: &str) -> (u16, String) {
+    let resp = send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    );
+    let head = resp
+        .split_once("\r\n\r\n")
+        .map_or(resp.as_str(), |(h, _)| h);
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, head.to_ascii_lowercase())
+}
+
+/// Handler, static file and 404 responses.
+fn responses(server: &Server) -> Vec<String> {
+    let mut heads = Vec::new();
+    for (path, expected) in [("/pets", 200), ("/hello.txt", 200), ("/nowhere", 404)] {
+        let (status, head) = get(server, path);
+        assert_eq!(status, expected, "{path}: {head}");
+        heads.push(head);
+    }
+    heads
+}
+
+#[test]
+fn default_server_header_is_brrtrouter() {
+    let server = start(None);
+    for head in responses(&server) {
+        assert!(head.contains("\r\nserver: brrtrouter\r\n"), "{head}");
+    }
+}
+
+#[test]
+fn configured_server_header_replaces_the_default() {
+    let server = start(Some(HttpConfig {
+        server_header: Some("acme-api".to_string()),
+        ..HttpConfig::default()
+    }));
+    for head in responses(&server) {
+        assert!(head.contains("\r\nserver: acme-api\r\n"), "{head}");
+        assert!(!head.contains("brrtrouter"), "{head}");
+    }
+}
+
+#[test]
+fn hidden_server_header_is_not_sent() {
+    let server = start(Some(HttpConfig {
+        server_header: Some("acme-api".to_string()),
+        hide_server_header: Some(true),
+        ..HttpConfig::default()
+    }));
+    for head in responses(&server) {
+        assert!(!head.contains("server: acme-api"), "{head}");
+        assert!(!head.contains("server: brrtrouter"), "{head}");
+    }
+}
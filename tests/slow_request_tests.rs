@@ -0,0 +1,136 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Slow-request detection: handler latency above the metrics threshold, or above a route's
+//! `x-slow-threshold-ms`, increments `brrtrouter_slow_requests_total{path}`; every route
+//! also gets a `brrtrouter_handler_duration_seconds` histogram.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::MetricsMiddleware;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Slow
+  version: "1.0"
+paths:
+  /report:
+    get:
+      operationId: build_report
+      x-slow-threshold-ms: 20
+      responses:
+        "200": { description: OK }
+  /export:
+    get:
+      operationId: build_export
+      responses:
+        "200": { description: OK }
+  /ping:
+    get:
+      operationId: ping
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    assert_eq!(
+        routes
+            .iter()
+            .find(|r| r.handler_name.as_ref() == "build_report")
+            .unwrap()
+            .x_slow_threshold_ms,
+        Some(20)
+    );
+
+    // The default threshold is far above the handlers' latency; only /report overrides it.
+    let metrics = Arc::new(MetricsMiddleware::new().with_slow_threshold(Duration::from_secs(10)));
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.add_middleware(metrics.clone());
+    unsafe {
+        for name in ["build_report", "build_export"] {
+            dispatcher.register_handler(name, |req: HandlerRequest| {
+                may::coroutine::sleep(Duration::from_millis(60));
+                let _ = req.reply_tx.send(HandlerResponse::json(200, json!({})));
+            });
+        }
+        dispatcher.register_handler("ping", |req: HandlerRequest| {
+            let _ = req.reply_tx.send(HandlerResponse::json(200, json!({})));
+        });
+    }
+
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_metrics_middleware(metrics);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn get(server: &Server, path: &str) -> String {
+    send_request(
+        &server.addr,
+        &format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+    )
+}
+
+#[test]
+fn route_threshold_counts_slow_requests() {
+    let server = start();
+    for path in ["/report", "/report", "/export", "/ping"] {
+        assert!(get(&server, path).starts_with("HTTP/1.1 200"), "{path}");
+    }
+
+    let metrics = get(&server, "/metrics");
+    assert!(
+        metrics.contains("brrtrouter_slow_requests_total{path=\"/report\"} 2"),
+        "{metrics}"
+    );
+    assert!(metrics.contains("brrtrouter_slow_requests_total{path=\"/export\"} 0"));
+    assert!(metrics.contains("brrtrouter_slow_requests_total{path=\"/ping\"} 0"));
+    assert!(metrics.contains("brrtrouter_handler_duration_seconds_count{path=\"/report\"} 2"));
+    assert!(metrics
+        .contains("brrtrouter_handler_duration_seconds_bucket{path=\"/report\",le=\"0.05\"} 0"));
+    assert!(metrics
+        .contains("brrtrouter_handler_duration_seconds_bucket{path=\"/report\",le=\"+Inf\"} 2"));
+}
@@ -92,3 +92,86 @@ fn security_explicit_requirement_preserved() {
     assert_eq!(explicit.len(), 1);
     assert!(explicit[0].0.contains_key("BearerAuth"));
 }
+
+#[test]
+fn security_policy_distinguishes_public_from_inherited() {
+    use brrtrouter::spec::{RouteSecurityPolicy, SchemeScopes};
+
+    let path = write_temp_yaml(FIXTURE);
+    let (routes, _) = load_spec(path.to_str().unwrap()).unwrap();
+    let policy = |handler: &str| {
+        routes
+            .iter()
+            .find(|r| r.handler_name.as_ref() == handler)
+            .unwrap()
+            .security_policy()
+    };
+
+    assert_eq!(policy("auth_login"), RouteSecurityPolicy::Public);
+    assert_eq!(
+        policy("admin_settings"),
+        RouteSecurityPolicy::Alternatives {
+            alternatives: vec![vec![SchemeScopes {
+                scheme: "BearerAuth".to_string(),
+                scopes: vec![],
+            }]],
+            allows_anonymous: false,
+        }
+    );
+}
+
+#[test]
+fn required_scopes_merge_or_alternatives() {
+    const SCOPED: &str = r#"openapi: 3.1.0
+info:
+  title: Scoped API
+  version: "1.0.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      security:
+        - OAuth: [pets:read]
+        - OAuth: [pets:admin]
+          ApiKey: []
+        - {}
+      responses:
+        "200":
+          description: OK
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+    OAuth:
+      type: oauth2
+      flows:
+        clientCredentials:
+          tokenUrl: https://auth.example.com/token
+          scopes:
+            pets:read: read
+            pets:admin: admin
+"#;
+    let path = write_temp_yaml(SCOPED);
+    let (routes, _) = load_spec(path.to_str().unwrap()).unwrap();
+    let route = &routes[0];
+
+    assert_eq!(
+        route.required_scopes("OAuth"),
+        Some(vec!["pets:read".to_string(), "pets:admin".to_string()])
+    );
+    assert_eq!(route.required_scopes("ApiKey"), Some(vec![]));
+    assert_eq!(route.required_scopes("BearerAuth"), None);
+    match route.security_policy() {
+        brrtrouter::spec::RouteSecurityPolicy::Alternatives {
+            alternatives,
+            allows_anonymous,
+        } => {
+            assert_eq!(alternatives.len(), 2);
+            assert_eq!(alternatives[1].len(), 2);
+            assert!(allows_anonymous);
+        }
+        other => panic!("expected alternatives, got {other:?}"),
+    }
+}
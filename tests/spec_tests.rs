@@ -197,6 +197,25 @@ paths:
     get:
       responses:
         '200': { description: OK }
+  /petStore/{storeId}/toy-box:
+    post:
+      responses:
+        '201': { description: Created }
+"#;
+
+const YAML_SYNTHESIZED_COLLISION: &str = r#"openapi: 3.1.0
+info:
+  title: Collide
+  version: '1.0.0'
+paths:
+  /pet-toys:
+    get:
+      responses:
+        '200': { description: OK }
+  /pet_toys:
+    get:
+      responses:
+        '200': { description: OK }
 "#;
 
 const YAML_UNSUPPORTED_METHOD: &str = r#"openapi: 3.1.0
@@ -212,20 +231,46 @@ paths:
 "#;
 
 #[test]
-fn test_missing_operation_id_exits() {
+fn test_missing_operation_id_synthesizes_stable_handler_names() {
+    let temp_path = write_temp_spec("spec_test_no_opid", "yaml", YAML_NO_OPID.as_bytes());
+    let names = || {
+        let (routes, _slug) = load_spec(temp_path.to_str().unwrap()).unwrap();
+        let mut names: Vec<String> = routes.iter().map(|r| r.handler_name.to_string()).collect();
+        names.sort();
+        names
+    };
+    let first = names();
+    assert_eq!(first, ["get_foo", "post_pet_store_by_store_id_toy_box"]);
+    assert_eq!(first, names());
+
+    let _ = std::fs::remove_file(&temp_path);
+}
+
+#[test]
+fn test_synthesized_handler_name_collision_is_an_error() {
     use std::process::Command;
 
-    let temp_path = write_temp_spec("spec_test_bad", "yaml", YAML_NO_OPID.as_bytes());
+    let spec: OpenApiV3Spec = serde_yaml::from_str(YAML_SYNTHESIZED_COLLISION).unwrap();
+    let err = brrtrouter::spec::build_routes(&spec, "collide")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("'get_pet_toys'"), "{err}");
+    assert!(err.contains("GET /pet-toys"), "{err}");
+    assert!(err.contains("GET /pet_toys"), "{err}");
 
-    let exe = env!("CARGO_BIN_EXE_spec_helper");
-    let output = Command::new(exe)
+    // Unlike explicit duplicates, the lenient duplicate-route mode does not excuse it.
+    let temp_path = write_temp_spec(
+        "spec_test_collide",
+        "yaml",
+        YAML_SYNTHESIZED_COLLISION.as_bytes(),
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_spec_helper"))
         .arg(&temp_path)
+        .env("BRRTR_DUPLICATE_ROUTES", "warn")
         .output()
         .expect("run spec_helper");
-    assert!(!output.status.success());
     assert_eq!(output.status.code(), Some(1));
 
-    // Manual cleanup
     let _ = std::fs::remove_file(&temp_path);
 }
 
@@ -298,3 +343,287 @@ fn test_sse_spec_loading() {
     // Manual cleanup
     let _ = std::fs::remove_file(&temp_path);
 }
+
+const YAML_DUPLICATE_OPERATION_ID: &str = r#"openapi: 3.1.0
+info:
+  title: Dupes
+  version: "1.0.0"
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+  /animals:
+    get:
+      operationId: list_pets
+      responses:
+        "200": { description: OK }
+"#;
+
+const YAML_SAME_TEMPLATE: &str = r#"openapi: 3.1.0
+info:
+  title: Templates
+  version: "1.0.0"
+paths:
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+  /pets/{petId}:
+    get:
+      operationId: fetch_pet
+      parameters:
+        - { name: petId, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+    delete:
+      operationId: delete_pet
+      parameters:
+        - { name: petId, in: path, required: true, schema: { type: string } }
+      responses:
+        "204": { description: Deleted }
+"#;
+
+#[test]
+fn test_duplicate_operation_id_is_rejected_naming_both_operations() {
+    let spec: OpenApiV3Spec = serde_yaml::from_str(YAML_DUPLICATE_OPERATION_ID).unwrap();
+    let err = brrtrouter::spec::build_routes(&spec, "dupes")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("handler 'list_pets'"), "{err}");
+    assert!(err.contains("GET /pets"), "{err}");
+    assert!(err.contains("GET /animals"), "{err}");
+}
+
+#[test]
+fn test_paths_compiling_to_the_same_template_are_rejected() {
+    let spec: OpenApiV3Spec = serde_yaml::from_str(YAML_SAME_TEMPLATE).unwrap();
+    let err = brrtrouter::spec::build_routes(&spec, "templates")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("GET /pets/{id}"), "{err}");
+    assert!(err.contains("GET /pets/{petId}"), "{err}");
+    assert!(err.contains("'get_pet' and 'fetch_pet'"), "{err}");
+    // Different methods on equivalent templates do not collide.
+    assert!(!err.contains("DELETE"), "{err}");
+}
+
+#[test]
+fn test_duplicate_routes_only_warn_in_lenient_mode() {
+    use brrtrouter::spec::{check_duplicate_routes, DuplicateRoutePolicy};
+
+    let spec: OpenApiV3Spec = serde_yaml::from_str(YAML_SPEC).unwrap();
+    let mut routes = brrtrouter::spec::build_routes(&spec, "lenient").unwrap();
+    assert!(check_duplicate_routes(&routes, DuplicateRoutePolicy::Error).is_ok());
+
+    let mut copy = routes[0].clone();
+    copy.path_pattern = "/elsewhere".into();
+    routes.push(copy);
+    assert!(check_duplicate_routes(&routes, DuplicateRoutePolicy::Error).is_err());
+    assert!(check_duplicate_routes(&routes, DuplicateRoutePolicy::Warn).is_ok());
+}
+
+const YAML_UNDEFINED_SCHEME: &str = r#"openapi: 3.1.0
+info:
+  title: Undefined scheme
+  version: '1.0.0'
+components:
+  securitySchemes:
+    ApiKey: { type: apiKey, in: header, name: X-API-Key }
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      security:
+        - ApiKye: []
+      responses:
+        '200': { description: OK }
+  /owners:
+    get:
+      operationId: list_owners
+      security:
+        - ApiKey: []
+      responses:
+        '200': { description: OK }
+"#;
+
+#[test]
+fn test_undefined_security_scheme_is_rejected_in_strict_mode() {
+    use brrtrouter::spec::{check_security_scheme_refs, UndefinedSchemePolicy};
+
+    let spec: OpenApiV3Spec = serde_yaml::from_str(YAML_UNDEFINED_SCHEME).unwrap();
+    // Strict is the default: loading fails, naming the operation and the scheme.
+    let err = brrtrouter::spec::build_routes(&spec, "undefined")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("GET /pets (handler 'list_pets'): scheme 'ApiKye'"),
+        "{err}"
+    );
+    assert!(
+        err.contains("BRRTR_UNDEFINED_SECURITY_SCHEMES=lenient"),
+        "{err}"
+    );
+    assert!(!err.contains("list_owners"), "{err}");
+
+    // The same routes checked against the original spec: lenient only logs.
+    let defined: OpenApiV3Spec = serde_yaml::from_str(&YAML_UNDEFINED_SCHEME.replace(
+        "    ApiKey: {",
+        "    ApiKye: { type: http, scheme: bearer }\n    ApiKey: {",
+    ))
+    .unwrap();
+    let routes = brrtrouter::spec::build_routes(&defined, "undefined").unwrap();
+    assert!(check_security_scheme_refs(&spec, &routes, UndefinedSchemePolicy::Strict).is_err());
+    assert!(check_security_scheme_refs(&spec, &routes, UndefinedSchemePolicy::Lenient).is_ok());
+    assert_eq!(
+        UndefinedSchemePolicy::default(),
+        UndefinedSchemePolicy::Strict
+    );
+}
+
+const YAML_LINKS: &str = r#"openapi: 3.1.0
+info:
+  title: Links
+  version: '1.0.0'
+components:
+  links:
+    FirstPet:
+      operationRef: '#/paths/~1pets~1%7Bid%7D/get'
+      parameters:
+        id: '$response.body#/0/id'
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      responses:
+        '200':
+          description: OK
+          links:
+            next:
+              operationId: list_pets
+              description: Next page
+              parameters:
+                cursor: '$response.body#/next_cursor'
+            first:
+              $ref: '#/components/links/FirstPet'
+            broken:
+              parameters:
+                id: '$response.body#/0/id'
+            ghost:
+              operationId: no_such_operation
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      responses:
+        '200':
+          description: OK
+          links:
+            self:
+              operationRef: '#/paths/~1pets~1{id}/get'
+              parameters:
+                id: '$request.path.id'
+            catalog:
+              operationRef: 'https://example.com/catalog.yaml#/paths/~1items/get'
+"#;
+
+#[test]
+fn test_response_links_are_retained_and_resolved() {
+    use brrtrouter::spec::LinkTarget;
+
+    let temp_path = write_temp_spec("spec_test_links", "yaml", YAML_LINKS.as_bytes());
+    let (routes, _slug) = load_spec(temp_path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(&temp_path);
+    let route = |handler: &str| routes.iter().find(|r| &*r.handler_name == handler).unwrap();
+
+    // Malformed (`broken`) and dangling (`ghost`) links are dropped; the rest are sorted.
+    let list = &route("list_pets").links;
+    let names: Vec<&str> = list.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["first", "next"]);
+
+    let first = &list[0];
+    assert_eq!(first.status, "200");
+    assert_eq!(
+        first.target,
+        LinkTarget::OperationRef("#/paths/~1pets~1%7Bid%7D/get".to_string())
+    );
+    assert_eq!(first.target_route, Some((Method::GET, "/pets/{id}".into())));
+    assert_eq!(
+        first.parameters,
+        [("id".to_string(), serde_json::json!("$response.body#/0/id"))]
+    );
+
+    let next = &list[1];
+    assert_eq!(
+        next.target,
+        LinkTarget::OperationId("list_pets".to_string())
+    );
+    assert_eq!(next.target_route, Some((Method::GET, "/pets".into())));
+    assert_eq!(next.description.as_deref(), Some("Next page"));
+
+    // External operationRefs are kept without a resolved target.
+    let get = &route("get_pet").links;
+    let names: Vec<&str> = get.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["catalog", "self"]);
+    assert_eq!(get[0].target_route, None);
+    assert_eq!(
+        get[1].target_route,
+        Some((Method::GET, "/pets/{id}".into()))
+    );
+}
+
+const YAML_DECLARED_STATUSES: &str = r#"openapi: 3.1.0
+info:
+  title: Declared statuses
+  version: "1.0"
+paths:
+  /pets:
+    post:
+      operationId: add_pet
+      responses:
+        "201": { description: Created }
+        "202": { description: Accepted }
+        "4XX": { description: Client error }
+  /pets/{id}:
+    delete:
+      operationId: delete_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "204": { description: Deleted }
+        default: { description: Error }
+    get:
+      operationId: get_pet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: string } }
+      responses:
+        "200": { description: OK }
+"#;
+
+#[test]
+fn test_declared_statuses_cover_exact_codes_ranges_and_default() {
+    let temp_path = write_temp_spec(
+        "spec_test_declared_statuses",
+        "yaml",
+        YAML_DECLARED_STATUSES.as_bytes(),
+    );
+    let (routes, _slug) = load_spec(temp_path.to_str().unwrap()).unwrap();
+    let _ = std::fs::remove_file(&temp_path);
+    let route = |handler: &str| routes.iter().find(|r| &*r.handler_name == handler).unwrap();
+
+    let add = route("add_pet");
+    assert_eq!(add.declared_statuses, ["201", "202", "4XX"]);
+    assert!(add.declares_status(201) && add.declares_status(404));
+    assert!(!add.declares_status(200) && !add.declares_status(204));
+    assert_eq!(add.typed_success_statuses(), [201, 202]);
+
+    let delete = route("delete_pet");
+    assert!(delete.declares_status(204) && delete.declares_status(201));
+    assert_eq!(delete.typed_success_statuses(), [204]);
+
+    // A lone 200 needs no wrapper enum: a plain `Response` already sends it.
+    assert!(route("get_pet").typed_success_statuses().is_empty());
+}
@@ -0,0 +1,171 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! `brrtrouter::spec::validate_spec`: every structural issue of a spec is reported at once,
+//! with JSON-pointer locations and error/warning severities.
+
+use brrtrouter::spec::{validate_spec, validate_spec_value};
+use brrtrouter::validator::{IssueSeverity, ValidationIssue};
+use std::path::Path;
+
+const BROKEN: &str = r#"openapi: 3.1.0
+info:
+  title: Broken
+  version: '1.0'
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        owner:
+          $ref: '#/components/schemas/Owner'
+paths:
+  /pets:
+    get:
+      responses:
+        '200':
+          description: OK
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+    post:
+      operationId: create_pet
+      responses:
+        '201':
+          description: Created
+          links:
+            orphan:
+              description: Points nowhere
+  /pets/{id}:
+    get:
+      operationId: get_pet
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        '200':
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+  /pets/{petId}:
+    delete:
+      operationId: get_pet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        '204': { description: Deleted }
+  /toys:
+    get:
+      operationId: list_toys
+      responses: {}
+"#;
+
+fn issues() -> Vec<ValidationIssue> {
+    let value: serde_json::Value = serde_yaml::from_str(BROKEN).unwrap();
+    validate_spec_value(&value)
+}
+
+fn find<'a>(issues: &'a [ValidationIssue], kind: &str) -> Vec<&'a ValidationIssue> {
+    issues.iter().filter(|i| i.kind == kind).collect()
+}
+
+#[test]
+fn reports_all_issues_with_pointer_locations() {
+    let issues = issues();
+
+    let missing_handler = find(&issues, "MissingHandler");
+    assert_eq!(missing_handler.len(), 1, "{issues:?}");
+    assert_eq!(missing_handler[0].location, "/paths/~1pets/get");
+
+    let duplicate = find(&issues, "DuplicateOperationId");
+    assert_eq!(duplicate.len(), 1, "{issues:?}");
+    assert_eq!(
+        duplicate[0].location,
+        "/paths/~1pets~1{petId}/delete/operationId"
+    );
+    assert!(duplicate[0].message.contains("/paths/~1pets~1{id}/get"));
+
+    let conflict = find(&issues, "ConflictingPathTemplates");
+    assert_eq!(conflict.len(), 1, "{issues:?}");
+    assert_eq!(conflict[0].location, "/paths/~1pets~1{petId}");
+
+    let unresolved = find(&issues, "UnresolvedRef");
+    assert_eq!(unresolved.len(), 1, "{issues:?}");
+    assert_eq!(
+        unresolved[0].location,
+        "/components/schemas/Pet/properties/owner/$ref"
+    );
+
+    let description = find(&issues, "MissingDescription");
+    assert_eq!(
+        description[0].location,
+        "/paths/~1pets~1{id}/get/responses/200/description"
+    );
+
+    let responses = find(&issues, "MissingResponses");
+    assert_eq!(responses[0].location, "/paths/~1toys/get/responses");
+}
+
+#[test]
+fn distinguishes_warnings_from_errors() {
+    let issues = issues();
+    let content = find(&issues, "MissingResponseContent");
+    assert_eq!(content.len(), 1, "{issues:?}");
+    assert_eq!(content[0].severity, IssueSeverity::Warning);
+    assert_eq!(content[0].location, "/paths/~1pets/post/responses/201");
+    assert!(!content[0].is_error());
+
+    // Handler names are synthesized for operations without an operationId.
+    let missing_handler = find(&issues, "MissingHandler");
+    assert_eq!(missing_handler[0].severity, IssueSeverity::Warning);
+
+    // Malformed links are ignored when loading, so they only warn.
+    let link = find(&issues, "MalformedLink");
+    assert_eq!(link.len(), 1, "{issues:?}");
+    assert_eq!(link[0].severity, IssueSeverity::Warning);
+    assert_eq!(
+        link[0].location,
+        "/paths/~1pets/post/responses/201/links/orphan"
+    );
+    assert!(link[0]
+        .message
+        .contains("neither operationId nor operationRef"));
+
+    // A 204 without content is fine.
+    assert!(!issues.iter().any(|i| i.location.contains("/responses/204")));
+
+    for kind in [
+        "DuplicateOperationId",
+        "ConflictingPathTemplates",
+        "UnresolvedRef",
+        "MissingResponses",
+    ] {
+        assert!(find(&issues, kind).iter().all(|i| i.is_error()), "{kind}");
+    }
+}
+
+#[test]
+fn pet_store_spec_has_no_errors() {
+    let issues = validate_spec(Path::new("examples/pet_store/doc/openapi.yaml")).unwrap();
+    let errors: Vec<_> = issues.iter().filter(|i| i.is_error()).collect();
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn syntax_errors_are_reported_as_an_issue() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openapi.yaml");
+    std::fs::write(&path, "openapi: [3.1.0\n").unwrap();
+    let issues = validate_spec(&path).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, "InvalidSyntax");
+    assert!(issues[0].is_error());
+
+    assert!(validate_spec(&dir.path().join("missing.yaml")).is_err());
+}
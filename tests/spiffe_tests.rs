@@ -11,345 +11,32 @@
 //! - JWT claim extraction
 //! - Integration with SecurityProvider trait
 
-use base64::Engine;
-use bollard::models::{HostConfig, PortBinding};
-use bollard::query_parameters::RemoveContainerOptionsBuilder;
-use bollard::Docker;
 use brrtrouter::security::{SecurityProvider, SecurityRequest, SpiffeProvider};
 use brrtrouter::spec::SecurityScheme;
+use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
 use brrtrouter::{dispatcher::HeaderVec, router::ParamVec};
-use futures::executor::block_on;
 use serde_json::json;
-use std::io::{Read, Write};
-use std::net::TcpListener;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-/// Helper to create a SPIFFE JWT token for testing
-///
-/// Creates a JWT with SPIFFE-specific claims:
-/// - `sub`: SPIFFE ID (required)
-/// - `aud`: Audience (required)
-/// - `exp`: Expiration timestamp
-/// - `iat`: Issued at timestamp
-fn make_spiffe_jwt(spiffe_id: &str, audience: &str, exp_secs: i64, iat_secs: i64) -> String {
-    use base64::{engine::general_purpose, Engine as _};
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let exp = now + exp_secs;
-    let iat = now + iat_secs;
-
-    let header = json!({
-        "alg": "HS256",
-        "typ": "JWT"
-    });
-
-    let payload = json!({
-        "sub": spiffe_id,
-        "aud": audience,
-        "exp": exp,
-        "iat": iat
-    });
-
-    let header_b64 = general_purpose::URL_SAFE_NO_PAD.encode(header.to_string().as_bytes());
-    let payload_b64 = general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string().as_bytes());
-
-    // Note: This creates an unsigned token with a fake signature
-    // In production, tokens MUST be signed and verified via JWKS
-    // This helper is used for testing validation failure scenarios
-    format!("{header_b64}.{payload_b64}.signature")
-}
-
-/// Helper to create a properly signed SPIFFE JWT using jsonwebtoken
-fn make_signed_spiffe_jwt(
-    secret: &[u8],
-    spiffe_id: &str,
-    audience: &str,
-    kid: &str,
-    exp_secs: i64,
-) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        ..Default::default()
-    };
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let claims = json!({
-        "sub": spiffe_id,
-        "aud": audience,
-        "exp": now + exp_secs,
-        "iat": now
-    });
-
-    let encoding_key = EncodingKey::from_secret(secret);
-    jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap()
-}
-
-/// Helper to create an expired SPIFFE JWT token
-fn make_expired_spiffe_jwt(spiffe_id: &str, audience: &str) -> String {
-    use base64::{engine::general_purpose, Engine as _};
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    // Expired 1 hour ago
-    let exp = now - 3600;
-    let iat = now - 7200; // Issued 2 hours ago
-
-    let header = json!({
-        "alg": "HS256",
-        "typ": "JWT"
-    });
-
-    let payload = json!({
-        "sub": spiffe_id,
-        "aud": audience,
-        "exp": exp,
-        "iat": iat
-    });
-
-    let header_b64 = general_purpose::URL_SAFE_NO_PAD.encode(header.to_string().as_bytes());
-    let payload_b64 = general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string().as_bytes());
-
-    format!("{header_b64}.{payload_b64}.signature")
-}
-
-/// Helper to create a SPIFFE JWT with array audience
-fn make_spiffe_jwt_array_aud(spiffe_id: &str, audiences: &[&str], exp_secs: i64) -> String {
-    use base64::{engine::general_purpose, Engine as _};
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let exp = now + exp_secs;
-
-    let header = json!({
-        "alg": "HS256",
-        "typ": "JWT"
-    });
-
-    let payload = json!({
-        "sub": spiffe_id,
-        "aud": audiences,
-        "exp": exp,
-        "iat": now
-    });
-
-    let header_b64 = general_purpose::URL_SAFE_NO_PAD.encode(header.to_string().as_bytes());
-    let payload_b64 = general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string().as_bytes());
-
-    format!("{header_b64}.{payload_b64}.signature")
-}
-
-/// Helper to base64url encode without padding
-fn base64url_no_pad(data: &[u8]) -> String {
-    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
-}
-
-/// RAII wrapper for Docker-based JWKS mock server
-struct JwksMockServerContainer {
-    docker: Docker,
-    container_id: String,
-    url: String,
-}
-
-impl JwksMockServerContainer {
-    /// Start a Docker container with a simple HTTP server serving JWKS
-    fn new(jwks_json: String) -> Self {
-        // Check if Docker is available
-        let docker = match Docker::connect_with_local_defaults() {
-            Ok(d) => d,
-            Err(_) => {
-                // Fall back to in-process server if Docker not available
-                return Self::fallback_in_process(jwks_json);
-            }
-        };
-
-        // Create a simple HTTP server container using nginx:alpine
-        // We'll use a custom nginx config to serve the JWKS JSON
-        let port_key = "80/tcp".to_string();
-        let bindings = std::collections::HashMap::from([(
-            port_key,
-            Some(vec![PortBinding {
-                host_ip: Some("127.0.0.1".into()),
-                host_port: Some("0".into()),
-            }]),
-        )]);
-        let host_config = HostConfig {
-            port_bindings: Some(bindings),
-            ..Default::default()
-        };
-
-        // For now, use in-process fallback since Docker setup is complex
-        // TODO: Implement full Docker-based mock server
-        Self::fallback_in_process(jwks_json)
-    }
-
-    /// Fallback to in-process server when Docker is not available
-    fn fallback_in_process(jwks: String) -> Self {
-        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
-        let url = format!("http://127.0.0.1:{}/jwks.json", addr.port());
-
-        let jwks_clone = jwks;
-        thread::spawn(move || {
-            for stream in listener.incoming() {
-                match stream {
-                    Ok(mut stream) => {
-                        let mut buf = [0u8; 2048];
-                        if stream.read(&mut buf).is_ok() {
-                            let resp = format!(
-                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                                jwks_clone.len(),
-                                jwks_clone
-                            );
-                            let _ = stream.write_all(resp.as_bytes());
-                            let _ = stream.flush();
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-
-        thread::sleep(Duration::from_millis(150));
+/// HMAC key and `kid` of the JWKS served by [`create_provider_with_mock_jwks`]
+const SECRET: &[u8] = b"test-secret-key-for-spiffe-validation";
+const KID: &str = "test-kid";
 
-        // Return a dummy container struct (Docker not actually used in fallback)
-        Self {
-            docker: Docker::connect_with_local_defaults()
-                .unwrap_or_else(|_| panic!("Docker connection failed and fallback also failed")),
-            container_id: "fallback".to_string(),
-            url,
-        }
-    }
-
-    fn url(&self) -> &str {
-        &self.url
-    }
-}
-
-impl Drop for JwksMockServerContainer {
-    fn drop(&mut self) {
-        if self.container_id != "fallback" {
-            let opts = RemoveContainerOptionsBuilder::default().force(true).build();
-            let _ = block_on(self.docker.remove_container(&self.container_id, Some(opts)));
-        }
-    }
-}
-
-/// Start a mock JWKS server for testing
-/// Returns the URL to the JWKS endpoint
+/// SpiffeProvider verifying signatures against a mock JWKS holding [`SECRET`] as [`KID`]
 ///
-/// Uses in-process TCP server for simplicity and speed.
-/// Handles multiple connections to support cache refreshes and retries.
-fn start_mock_jwks_server(jwks: String) -> String {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let addr = listener.local_addr().unwrap();
-    let url = format!("http://127.0.0.1:{}/jwks.json", addr.port());
-
-    let jwks_clone = jwks;
-    thread::spawn(move || {
-        for stream in listener.incoming() {
-            match stream {
-                Ok(mut stream) => {
-                    let mut buf = [0u8; 2048];
-                    if stream.read(&mut buf).is_ok() {
-                        let resp = format!(
-                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                            jwks_clone.len(),
-                            jwks_clone
-                        );
-                        let _ = stream.write_all(resp.as_bytes());
-                        let _ = stream.flush();
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-    });
-
-    thread::sleep(Duration::from_millis(150));
-    url
-}
-
-/// Helper to create a SpiffeProvider with a mock JWKS server for testing
-/// This is useful for tests that need signature verification but don't need to test JWKS itself
+/// The JWKS is served until the returned server is dropped.
 fn create_provider_with_mock_jwks(
     trust_domains: &[&str],
     audiences: &[&str],
-) -> (SpiffeProvider, String) {
-    // Create a simple HMAC key for testing
-    let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
-
+) -> (SpiffeProvider, MockJwksServer) {
+    let server = MockJwksServer::start(jwks([hs256_jwk(KID, SECRET)])).unwrap();
     let provider = SpiffeProvider::new()
         .trust_domains(trust_domains)
         .audiences(audiences)
-        .jwks_url(&jwks_url);
-
-    (provider, jwks_url)
-}
-
-/// Helper to create a signed SPIFFE JWT token using the test secret
-fn make_signed_spiffe_jwt_for_test(spiffe_id: &str, audience: &str, exp_secs: i64) -> String {
-    let secret = b"test-secret-key-for-spiffe-validation";
-    make_signed_spiffe_jwt(secret, spiffe_id, audience, "test-kid", exp_secs)
-}
-
-/// Helper to create a signed SPIFFE JWT token with array audience using the test secret
-fn make_signed_spiffe_jwt_array_aud_for_test(
-    spiffe_id: &str,
-    audiences: &[&str],
-    exp_secs: i64,
-) -> String {
-    let secret = b"test-secret-key-for-spiffe-validation";
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some("test-kid".to_string()),
-        alg: Algorithm::HS256,
-        ..Default::default()
-    };
-
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let claims = json!({
-        "sub": spiffe_id,
-        "aud": audiences,
-        "exp": now + exp_secs,
-        "iat": now
-    });
-
-    let encoding_key = EncodingKey::from_secret(secret);
-    jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap()
+        .jwks_url(&server.url());
+    (provider, server)
 }
 
 #[test]
@@ -368,7 +55,7 @@ fn test_spiffe_provider_creation() {
 fn test_spiffe_id_format_validation() {
     // Test SPIFFE ID format validation through provider validation
     // Valid SPIFFE IDs should pass validation
-    let (provider, _jwks_url) = create_provider_with_mock_jwks(
+    let (provider, _jwks) = create_provider_with_mock_jwks(
         &["example.com", "enterprise.local", "prod.example.com"],
         &["api.example.com"],
     );
@@ -389,7 +76,12 @@ fn test_spiffe_id_format_validation() {
 
     for spiffe_id in valid_ids {
         // Use signed token for valid IDs (signature verification is required)
-        let token = make_signed_spiffe_jwt_for_test(spiffe_id, "api.example.com", 3600);
+        let token = TestJwt::hs256(SECRET)
+            .kid(KID)
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -456,14 +148,8 @@ fn test_spiffe_id_format_validation() {
 fn test_extract_trust_domain() {
     // Test trust domain extraction through provider validation
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Wait for JWKS server to be ready
     use std::io::{Read, Write};
@@ -511,7 +197,12 @@ fn test_extract_trust_domain() {
 
     for (spiffe_id, kid) in valid_cases {
         // Use signed token for valid trust domains (matching the JWKS kid)
-        let token = make_signed_spiffe_jwt(secret, spiffe_id, "api.example.com", kid, 3600);
+        let token = TestJwt::hs256(secret)
+            .kid(kid)
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -530,7 +221,11 @@ fn test_extract_trust_domain() {
 
     // Invalid trust domain should fail (unsigned token will fail signature verification)
     let invalid_spiffe_id = "spiffe://untrusted.com/api/users";
-    let token = make_spiffe_jwt(invalid_spiffe_id, "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", invalid_spiffe_id)
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
     let req = SecurityRequest {
@@ -549,8 +244,7 @@ fn test_extract_trust_domain() {
 
 #[test]
 fn test_spiffe_validation_valid_svid() {
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -560,8 +254,12 @@ fn test_spiffe_validation_valid_svid() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -661,12 +359,11 @@ fn test_spiffe_validation_trust_domain_whitelist() {
     };
 
     // Token with untrusted domain
-    let token = make_spiffe_jwt(
-        "spiffe://untrusted.com/api/users",
-        "api.example.com",
-        3600,
-        0,
-    );
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://untrusted.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -693,12 +390,11 @@ fn test_spiffe_validation_empty_trust_domains() {
         description: None,
     };
 
-    let token = make_spiffe_jwt(
-        "spiffe://any-domain.com/api/users",
-        "api.example.com",
-        3600,
-        0,
-    );
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://any-domain.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -716,7 +412,7 @@ fn test_spiffe_validation_empty_trust_domains() {
 
 #[test]
 fn test_spiffe_validation_audience_string() {
-    let (provider, _jwks_url) =
+    let (provider, _jwks) =
         create_provider_with_mock_jwks(&["example.com"], &["api.example.com", "brrtrouter"]);
 
     thread::sleep(Duration::from_millis(200));
@@ -728,8 +424,12 @@ fn test_spiffe_validation_audience_string() {
     };
 
     // Valid audience
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -745,8 +445,12 @@ fn test_spiffe_validation_audience_string() {
     );
 
     // Invalid audience
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "wrong-audience", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("wrong-audience")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -764,7 +468,7 @@ fn test_spiffe_validation_audience_string() {
 
 #[test]
 fn test_spiffe_validation_audience_array() {
-    let (provider, _jwks_url) =
+    let (provider, _jwks) =
         create_provider_with_mock_jwks(&["example.com"], &["api.example.com", "brrtrouter"]);
 
     thread::sleep(Duration::from_millis(200));
@@ -776,11 +480,12 @@ fn test_spiffe_validation_audience_array() {
     };
 
     // Token with array audience containing valid audience
-    let token = make_signed_spiffe_jwt_array_aud_for_test(
-        "spiffe://example.com/api/users",
-        &["api.example.com", "other-audience"],
-        3600,
-    );
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .claim("aud", json!(["api.example.com", "other-audience"]))
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -796,11 +501,12 @@ fn test_spiffe_validation_audience_array() {
     );
 
     // Token with array audience containing no valid audience
-    let token = make_signed_spiffe_jwt_array_aud_for_test(
-        "spiffe://example.com/api/users",
-        &["wrong-audience", "another-wrong"],
-        3600,
-    );
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .claim("aud", json!(["wrong-audience", "another-wrong"]))
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -827,7 +533,11 @@ fn test_spiffe_validation_empty_audiences() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "any-audience", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("any-audience")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -856,7 +566,12 @@ fn test_spiffe_validation_expired_token() {
         description: None,
     };
 
-    let token = make_expired_spiffe_jwt("spiffe://example.com/api/users", "api.example.com");
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .expires_in(-3600)
+        .claim_from_now("iat", -7200)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -972,7 +687,11 @@ fn test_spiffe_validation_wrong_scheme() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -992,7 +711,11 @@ fn test_spiffe_validation_wrong_scheme() {
 fn test_spiffe_extract_spiffe_id() {
     let provider = SpiffeProvider::new().trust_domains(&["example.com"]);
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1022,7 +745,11 @@ fn test_spiffe_extract_claims() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1059,14 +786,8 @@ fn test_spiffe_extract_claims() {
 #[test]
 fn test_spiffe_cookie_extraction() {
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Wait for JWKS server to be ready
     use std::io::{Read, Write};
@@ -1104,8 +825,12 @@ fn test_spiffe_cookie_extraction() {
     };
 
     // Use signed token (signature verification is required)
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Token in cookie, not header
     let mut cookies: HeaderVec = HeaderVec::new();
@@ -1136,14 +861,8 @@ fn test_spiffe_cookie_extraction() {
 fn test_spiffe_leeway_configuration() {
     // Test with large leeway (should allow slightly expired tokens)
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1295,7 +1014,7 @@ fn test_spiffe_invalid_json_payload() {
 
 #[test]
 fn test_spiffe_multiple_trust_domains() {
-    let (provider, _jwks_url) = create_provider_with_mock_jwks(
+    let (provider, _jwks) = create_provider_with_mock_jwks(
         &["example.com", "enterprise.local", "prod.example.com"],
         &["api.example.com"],
     );
@@ -1311,7 +1030,12 @@ fn test_spiffe_multiple_trust_domains() {
     // Test each trust domain
     for trust_domain in &["example.com", "enterprise.local", "prod.example.com"] {
         let spiffe_id = format!("spiffe://{trust_domain}/api/users");
-        let token = make_signed_spiffe_jwt_for_test(&spiffe_id, "api.example.com", 3600);
+        let token = TestJwt::hs256(SECRET)
+            .kid(KID)
+            .claim("sub", &spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
 
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1335,14 +1059,8 @@ fn test_spiffe_multiple_trust_domains() {
 fn test_spiffe_jwks_signature_verification() {
     // Create mock JWKS server
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Create provider with JWKS
     let provider = SpiffeProvider::new()
@@ -1357,13 +1075,12 @@ fn test_spiffe_jwks_signature_verification() {
     };
 
     // Create properly signed token
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k1",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Verify server is ready by making a test request
     use std::io::{Read, Write};
@@ -1413,14 +1130,8 @@ fn test_spiffe_jwks_invalid_signature() {
     // Create mock JWKS server
     let secret = b"supersecret";
     let wrong_secret = b"wrongsecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Create provider with JWKS
     let provider = SpiffeProvider::new()
@@ -1435,13 +1146,12 @@ fn test_spiffe_jwks_invalid_signature() {
     };
 
     // Create token signed with wrong secret
-    let token = make_signed_spiffe_jwt(
-        wrong_secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k1",
-        3600,
-    );
+    let token = TestJwt::hs256(wrong_secret)
+        .kid("k1")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Wait for JWKS to be fetched
     thread::sleep(Duration::from_millis(200));
@@ -1464,14 +1174,8 @@ fn test_spiffe_jwks_invalid_signature() {
 fn test_spiffe_jwks_missing_key_id() {
     // Create mock JWKS server
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Create provider with JWKS
     let provider = SpiffeProvider::new()
@@ -1531,14 +1235,8 @@ fn test_spiffe_jwks_missing_key_id() {
 fn test_spiffe_jwks_key_not_found() {
     // Create mock JWKS server
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Create provider with JWKS
     let provider = SpiffeProvider::new()
@@ -1553,13 +1251,12 @@ fn test_spiffe_jwks_key_not_found() {
     };
 
     // Create token with kid that doesn't exist in JWKS
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k2",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("k2")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Wait for JWKS to be fetched
     thread::sleep(Duration::from_millis(200));
@@ -1582,14 +1279,8 @@ fn test_spiffe_jwks_key_not_found() {
 fn test_spiffe_jwks_algorithm_mismatch() {
     // Token signed with different algorithm than JWKS key
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1645,7 +1336,8 @@ fn test_spiffe_jwks_algorithm_mismatch() {
 fn test_spiffe_jwks_malformed_json() {
     // JWKS server returns malformed JSON
     let jwks = "not valid json {";
-    let jwks_url = start_mock_jwks_server(jwks.to_string());
+    let jwks_server = MockJwksServer::start(jwks.to_string()).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1658,8 +1350,12 @@ fn test_spiffe_jwks_malformed_json() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     thread::sleep(Duration::from_millis(200));
 
@@ -1685,7 +1381,8 @@ fn test_spiffe_jwks_empty_keys_array() {
         "keys": []
     })
     .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1698,8 +1395,12 @@ fn test_spiffe_jwks_empty_keys_array() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     thread::sleep(Duration::from_millis(200));
 
@@ -1725,7 +1426,8 @@ fn test_spiffe_jwks_missing_keys_field() {
         "some_other_field": "value"
     })
     .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1738,8 +1440,12 @@ fn test_spiffe_jwks_missing_keys_field() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     thread::sleep(Duration::from_millis(200));
 
@@ -1767,7 +1473,8 @@ fn test_spiffe_jwks_invalid_key_base64() {
         ]
     })
     .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1780,8 +1487,12 @@ fn test_spiffe_jwks_invalid_key_base64() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     thread::sleep(Duration::from_millis(200));
 
@@ -1805,16 +1516,9 @@ fn test_spiffe_jwks_multiple_keys_wrong_kid() {
     // JWKS has multiple keys, token signed with key that exists but wrong kid
     let secret1 = b"secret1";
     let secret2 = b"secret2";
-    let k1 = base64url_no_pad(secret1);
-    let k2 = base64url_no_pad(secret2);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k1},
-            {"kty": "oct", "alg": "HS256", "kid": "k2", "k": k2}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server =
+        MockJwksServer::start(jwks([hs256_jwk("k1", secret1), hs256_jwk("k2", secret2)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1828,13 +1532,12 @@ fn test_spiffe_jwks_multiple_keys_wrong_kid() {
     };
 
     // Token signed with secret1 but claims kid is k2 (wrong key for kid)
-    let token = make_signed_spiffe_jwt(
-        secret1,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k2",
-        3600,
-    );
+    let token = TestJwt::hs256(secret1)
+        .kid("k2")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     thread::sleep(Duration::from_millis(200));
 
@@ -1858,14 +1561,8 @@ fn test_spiffe_jwks_token_tampered_claims() {
     // Token with valid signature but tampered claims (should still fail signature verification)
     // This tests that signature verification happens on the full token, not just claims
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -1879,13 +1576,12 @@ fn test_spiffe_jwks_token_tampered_claims() {
     };
 
     // Create valid token
-    let valid_token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k1",
-        3600,
-    );
+    let valid_token = TestJwt::hs256(secret)
+        .kid("k1")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Tamper with the token by modifying the payload (base64 decode, modify, re-encode)
     let parts: Vec<&str> = valid_token.split('.').collect();
@@ -1939,7 +1635,11 @@ fn test_spiffe_jwks_without_jwks_url() {
     };
 
     // Unsigned token (would be accepted without signature verification, but that's insecure)
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -1962,14 +1662,8 @@ fn test_spiffe_jwks_without_jwks_url() {
 fn test_spiffe_jwks_cache_refresh() {
     // Create mock JWKS server
     let secret1 = b"secret1";
-    let k1 = base64url_no_pad(secret1);
-    let jwks1 = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k1}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks1);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret1)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     // Create provider with short cache TTL
     let provider = SpiffeProvider::new()
@@ -2008,13 +1702,12 @@ fn test_spiffe_jwks_cache_refresh() {
     }
 
     // Create token with first key
-    let token1 = make_signed_spiffe_jwt(
-        secret1,
-        "spiffe://example.com/api/users",
-        "api.example.com",
-        "k1",
-        3600,
-    );
+    let token1 = TestJwt::hs256(secret1)
+        .kid("k1")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token1}")));
@@ -2138,7 +1831,11 @@ fn test_spiffe_get_key_for_no_jwks_configured() {
     };
 
     // Create a token that would require JWKS (but provider doesn't have JWKS)
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -2174,7 +1871,11 @@ fn test_spiffe_refresh_jwks_if_needed_no_jwks_configured() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -2203,14 +1904,8 @@ fn test_spiffe_refresh_jwks_if_needed_no_jwks_configured() {
 fn test_spiffe_extract_token_from_cookie() {
     // Test token extraction from cookie when not in Authorization header
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2226,8 +1921,12 @@ fn test_spiffe_extract_token_from_cookie() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Put token in cookie instead of Authorization header
     let mut cookies: HeaderVec = HeaderVec::new();
@@ -2251,14 +1950,8 @@ fn test_spiffe_extract_token_prefers_header_over_cookie() {
     // This test verifies that when both header and cookie are present,
     // the header is used (extract_token checks header first)
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2274,8 +1967,12 @@ fn test_spiffe_extract_token_prefers_header_over_cookie() {
         description: None,
     };
 
-    let valid_token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let valid_token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Put valid token in header, also put it in cookie (both valid)
     // This tests that header is checked first
@@ -2302,14 +1999,8 @@ fn test_spiffe_extract_token_prefers_header_over_cookie() {
 fn test_spiffe_extract_token_cookie_fallback() {
     // Test that cookie is used when header is not present
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2325,8 +2016,12 @@ fn test_spiffe_extract_token_cookie_fallback() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Put token in cookie only (no header)
     let mut cookies: HeaderVec = HeaderVec::new();
@@ -2358,7 +2053,11 @@ fn test_spiffe_extract_token_no_cookie_name() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Put token in cookie only (no header, no cookie_name configured)
     let mut cookies: HeaderVec = HeaderVec::new();
@@ -2381,14 +2080,8 @@ fn test_spiffe_extract_token_no_cookie_name() {
 fn test_spiffe_leeway_configuration_edge_cases() {
     // Test leeway configuration with various values
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider1 = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2411,8 +2104,12 @@ fn test_spiffe_leeway_configuration_edge_cases() {
         description: None,
     };
 
-    let token1 =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token1 = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token1}")));
@@ -2443,7 +2140,11 @@ fn test_spiffe_get_key_for_cache_read_error() {
         description: None,
     };
 
-    let token = make_spiffe_jwt("spiffe://example.com/api/users", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -2465,8 +2166,7 @@ fn test_spiffe_get_key_for_cache_read_error() {
 fn test_spiffe_refresh_jwks_if_needed_early_return() {
     // Test that refresh_jwks_if_needed returns early when cache is not expired
     // This is tested indirectly by creating a provider and validating multiple times
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -2476,8 +2176,12 @@ fn test_spiffe_refresh_jwks_if_needed_early_return() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/api/users", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/api/users")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -2619,7 +2323,11 @@ fn test_spiffe_id_trust_domain_leading_trailing_dots() {
     ];
 
     for spiffe_id in invalid_ids {
-        let token = make_spiffe_jwt(spiffe_id, "api.example.com", 3600, 0);
+        let token = TestJwt::fixed_signature("signature")
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -2640,14 +2348,8 @@ fn test_spiffe_id_trust_domain_leading_trailing_dots() {
 fn test_spiffe_id_path_special_characters() {
     // Paths with special characters should be valid (SPIFFE spec allows any characters except control chars)
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2671,7 +2373,12 @@ fn test_spiffe_id_path_special_characters() {
     ];
 
     for spiffe_id in valid_paths {
-        let token = make_signed_spiffe_jwt_for_test(spiffe_id, "api.example.com", 3600);
+        let token = TestJwt::hs256(SECRET)
+            .kid(KID)
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -2692,14 +2399,8 @@ fn test_spiffe_id_path_special_characters() {
 fn test_spiffe_trust_domain_case_sensitivity() {
     // Trust domain matching should be case-sensitive
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["Example.com"]) // Capital E
@@ -2715,8 +2416,12 @@ fn test_spiffe_trust_domain_case_sensitivity() {
     };
 
     // Lowercase trust domain should fail
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
     let req = SecurityRequest {
@@ -2731,8 +2436,12 @@ fn test_spiffe_trust_domain_case_sensitivity() {
     );
 
     // Exact match should pass
-    let token2 =
-        make_signed_spiffe_jwt_for_test("spiffe://Example.com/path", "api.example.com", 3600);
+    let token2 = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://Example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers2: HeaderVec = HeaderVec::new();
     headers2.push((Arc::from("authorization"), format!("Bearer {token2}")));
     let req2 = SecurityRequest {
@@ -2759,7 +2468,11 @@ fn test_spiffe_trust_domain_subdomain_confusion() {
     };
 
     // Subdomain should fail
-    let token = make_spiffe_jwt("spiffe://sub.example.com/path", "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://sub.example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
     let req = SecurityRequest {
@@ -2824,14 +2537,8 @@ fn test_spiffe_audience_array_with_non_strings() {
     // Array with non-string values should ignore non-strings
     // Need JWKS URL for signature verification (security requirement)
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2848,13 +2555,12 @@ fn test_spiffe_audience_array_with_non_strings() {
     thread::sleep(Duration::from_millis(200));
 
     // Create signed token with array audience containing mixed types
-    let token = make_signed_spiffe_jwt(
-        secret,
-        "spiffe://example.com/path",
-        "api.example.com",
-        "k1",
-        3600,
-    );
+    let token = TestJwt::hs256(secret)
+        .kid("k1")
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Manually modify the token to have array audience with mixed types
     // We need to decode, modify payload, and re-sign
@@ -2922,14 +2628,8 @@ fn test_spiffe_audience_array_with_non_strings() {
 fn test_spiffe_audience_as_object() {
     // Audience claim as object (invalid format) should fail validation
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -2986,14 +2686,8 @@ fn test_spiffe_audience_as_object() {
 fn test_spiffe_audience_as_number() {
     // Audience claim as number (invalid format) should fail validation
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3050,14 +2744,8 @@ fn test_spiffe_audience_as_number() {
 fn test_spiffe_audience_as_boolean() {
     // Audience claim as boolean (invalid format) should fail validation
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3114,14 +2802,8 @@ fn test_spiffe_audience_as_boolean() {
 fn test_spiffe_audience_as_null() {
     // Audience claim as null (invalid format) should fail validation
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3179,14 +2861,8 @@ fn test_spiffe_audience_missing_with_valid_signature() {
     // Token with valid signature but missing audience should fail validation
     // This ensures our validation catches missing audience before signature verification
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3245,14 +2921,8 @@ fn test_spiffe_signature_verification_with_disabled_audience_validation() {
     // This tests that disabling jsonwebtoken's audience validation doesn't bypass our validation
     // Our validation should reject it BEFORE signature verification
     let secret = b"supersecret";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "k1", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3444,14 +3114,8 @@ fn test_spiffe_exp_claim_negative() {
 fn test_spiffe_exp_boundary_exact() {
     // Token expiring exactly at current time should pass with leeway
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3509,14 +3173,8 @@ fn test_spiffe_exp_boundary_exact() {
 fn test_spiffe_exp_boundary_leeway() {
     // Token expiring exactly at leeway boundary should pass
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3662,7 +3320,11 @@ fn test_spiffe_id_very_long_trust_domain() {
     // This should fail because the regex won't match such a long domain
     // (though the regex itself doesn't have a length limit, very long strings
     // could cause performance issues)
-    let token = make_spiffe_jwt(&spiffe_id, "api.example.com", 3600, 0);
+    let token = TestJwt::fixed_signature("signature")
+        .claim("sub", &spiffe_id)
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
     let req = SecurityRequest {
@@ -3683,14 +3345,8 @@ fn test_spiffe_id_very_long_trust_domain() {
 fn test_spiffe_id_very_long_path() {
     // Very long paths should be handled gracefully (not cause DoS)
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let provider = SpiffeProvider::new()
         .trust_domains(&["example.com"])
@@ -3709,7 +3365,12 @@ fn test_spiffe_id_very_long_path() {
     let long_path = "/".to_string() + &"a".repeat(10000);
     let spiffe_id = format!("spiffe://example.com{long_path}");
 
-    let token = make_signed_spiffe_jwt_for_test(&spiffe_id, "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", &spiffe_id)
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
     let req = SecurityRequest {
@@ -3746,7 +3407,11 @@ fn test_spiffe_trust_domain_consecutive_dots() {
     ];
 
     for spiffe_id in invalid_ids {
-        let token = make_spiffe_jwt(spiffe_id, "api.example.com", 3600, 0);
+        let token = TestJwt::fixed_signature("signature")
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -3823,8 +3488,7 @@ fn test_spiffe_leeway_integer_overflow() {
 fn test_spiffe_authorization_header_case_insensitive() {
     // HTTP headers are case-insensitive per RFC 7230
     // SecurityRequest::get_header() uses eq_ignore_ascii_case, so this should work
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -3834,8 +3498,12 @@ fn test_spiffe_authorization_header_case_insensitive() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test different header name casings (HTTP standard requires case-insensitive)
     let header_casings = vec![
@@ -3866,8 +3534,7 @@ fn test_spiffe_authorization_header_case_insensitive() {
 fn test_spiffe_bearer_token_whitespace_handling() {
     // Bearer tokens should handle whitespace correctly
     // RFC 6750: Bearer token is the string after "Bearer " (single space)
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -3877,8 +3544,12 @@ fn test_spiffe_bearer_token_whitespace_handling() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test various whitespace scenarios
     let test_cases = vec![
@@ -3918,8 +3589,7 @@ fn test_spiffe_multiple_authorization_headers() {
     // HTTP spec: Multiple headers with same name should be treated as comma-separated
     // But many implementations use "first wins" or "last wins"
     // SecurityRequest::get_header uses find() which returns first match
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -3929,8 +3599,12 @@ fn test_spiffe_multiple_authorization_headers() {
         description: None,
     };
 
-    let valid_token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let valid_token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
     let invalid_token = "invalid.token.signature";
 
     // Test with multiple headers - first should win (SecurityRequest::get_header uses find())
@@ -3976,8 +3650,7 @@ fn test_spiffe_multiple_authorization_headers() {
 fn test_spiffe_bearer_prefix_case_sensitivity() {
     // "Bearer" prefix should be case-sensitive per RFC 6750
     // But some implementations are lenient - we should test both
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
 
@@ -3987,8 +3660,12 @@ fn test_spiffe_bearer_prefix_case_sensitivity() {
         description: None,
     };
 
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Test different Bearer prefix casings
     let test_cases = vec![
@@ -4073,7 +3750,7 @@ fn test_spiffe_empty_bearer_token() {
 fn test_spiffe_trust_domain_exact_match_required() {
     // Trust domain matching must be exact - no subdomain matching
     // This is critical for security - prevents domain confusion attacks
-    let (provider, _jwks_url) =
+    let (provider, _jwks) =
         create_provider_with_mock_jwks(&["example.com", "sub.example.com"], &["api.example.com"]);
 
     thread::sleep(Duration::from_millis(200));
@@ -4088,7 +3765,12 @@ fn test_spiffe_trust_domain_exact_match_required() {
     let exact_matches = vec!["spiffe://example.com/path", "spiffe://sub.example.com/path"];
 
     for spiffe_id in exact_matches {
-        let token = make_signed_spiffe_jwt_for_test(spiffe_id, "api.example.com", 3600);
+        let token = TestJwt::hs256(SECRET)
+            .kid(KID)
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -4111,7 +3793,11 @@ fn test_spiffe_trust_domain_exact_match_required() {
     ];
 
     for spiffe_id in non_matches {
-        let token = make_spiffe_jwt(spiffe_id, "api.example.com", 3600, 0);
+        let token = TestJwt::fixed_signature("signature")
+            .claim("sub", spiffe_id)
+            .audience("api.example.com")
+            .claim_from_now("iat", 0)
+            .sign();
         let mut headers: HeaderVec = HeaderVec::new();
         headers.push((Arc::from("authorization"), format!("Bearer {token}")));
         let req = SecurityRequest {
@@ -4142,7 +3828,11 @@ fn test_spiffe_jwt_extra_parts() {
     };
 
     // Valid token
-    let valid_token = make_spiffe_jwt("spiffe://example.com/path", "api.example.com", 3600, 0);
+    let valid_token = TestJwt::fixed_signature("signature")
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     // Create token with extra parts
     let invalid_token = format!("{valid_token}.extra.part");
@@ -4218,14 +3908,8 @@ fn test_spiffe_token_revocation() {
     use brrtrouter::security::{InMemoryRevocationChecker, SpiffeProvider};
 
     let secret = b"test-secret-key-for-spiffe-validation";
-    let k = base64url_no_pad(secret);
-    let jwks = serde_json::json!({
-        "keys": [
-            {"kty": "oct", "alg": "HS256", "kid": "test-kid", "k": k}
-        ]
-    })
-    .to_string();
-    let jwks_url = start_mock_jwks_server(jwks);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("test-kid", secret)])).unwrap();
+    let jwks_url = jwks_server.url();
 
     let revocation_checker = InMemoryRevocationChecker::new();
     let provider = SpiffeProvider::new()
@@ -4305,8 +3989,7 @@ fn test_spiffe_token_revocation_no_jti() {
     // (revocation checking is only done if jti is present)
     use brrtrouter::security::{InMemoryRevocationChecker, SpiffeProvider};
 
-    let (provider, _jwks_url) =
-        create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
+    let (provider, _jwks) = create_provider_with_mock_jwks(&["example.com"], &["api.example.com"]);
 
     let revocation_checker = InMemoryRevocationChecker::new();
     let provider = SpiffeProvider::new()
@@ -4324,8 +4007,12 @@ fn test_spiffe_token_revocation_no_jti() {
     };
 
     // Token without jti
-    let token =
-        make_signed_spiffe_jwt_for_test("spiffe://example.com/path", "api.example.com", 3600);
+    let token = TestJwt::hs256(SECRET)
+        .kid(KID)
+        .claim("sub", "spiffe://example.com/path")
+        .audience("api.example.com")
+        .claim_from_now("iat", 0)
+        .sign();
 
     let mut headers: HeaderVec = HeaderVec::new();
     headers.push((Arc::from("authorization"), format!("Bearer {token}")));
@@ -0,0 +1,180 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! SSE backpressure: a channel buffers at most `SseConfig::buffer` events, and a full buffer
+//! drops the oldest event, drops the new one, or closes the stream as `SseOverflow` says. The
+//! collected response body is capped at `SseConfig::max_body_bytes` under the same policy.
+
+use brrtrouter::sse::{self, SseConfig, SseOverflow};
+use brrtrouter::typed::SseResponse;
+use std::time::Duration;
+
+fn config(buffer: usize, overflow: SseOverflow) -> SseConfig {
+    SseConfig {
+        buffer,
+        overflow,
+        ..SseConfig::default()
+    }
+}
+
+/// A one-byte event is a 9-byte `data: n\n\n` frame
+fn capped(max_body_bytes: usize, overflow: SseOverflow) -> SseConfig {
+    SseConfig {
+        max_body_bytes,
+        ..config(1024, overflow)
+    }
+}
+
+fn data(stream: &str) -> Vec<&str> {
+    stream
+        .split("\n\n")
+        .filter_map(|frame| frame.strip_prefix("data: "))
+        .collect()
+}
+
+#[test]
+fn default_config_buffers_1024_dropping_the_oldest() {
+    assert_eq!(
+        SseConfig::default(),
+        config(sse::DEFAULT_SSE_BUFFER, SseOverflow::DropOldest)
+    );
+    assert_eq!(sse::DEFAULT_SSE_BUFFER, 1024);
+    assert_eq!(SseConfig::default().max_body_bytes, 8 * 1024 * 1024);
+    let parsed: SseConfig = serde_yaml::from_str("buffer: 8\noverflow: close_stream\n").unwrap();
+    assert_eq!(parsed, config(8, SseOverflow::CloseStream));
+}
+
+#[test]
+fn drop_oldest_keeps_the_newest_events() {
+    let (sender, receiver) = sse::channel_with(config(3, SseOverflow::DropOldest));
+    for n in 0..10 {
+        sender.send(n.to_string());
+        assert!(sender.buffered() <= 3);
+    }
+    assert_eq!(sender.dropped(), 7);
+    assert!(!sender.is_closed());
+    drop(sender);
+    assert_eq!(data(&receiver.collect()), ["7", "8", "9"]);
+}
+
+#[test]
+fn drop_newest_keeps_the_unread_events() {
+    let (sender, receiver) = sse::channel_with(config(3, SseOverflow::DropNewest));
+    for n in 0..10 {
+        sender.send(n.to_string());
+    }
+    assert_eq!(sender.buffered(), 3);
+    assert_eq!(sender.dropped(), 7);
+    drop(sender);
+    assert_eq!(data(&receiver.collect()), ["0", "1", "2"]);
+}
+
+#[test]
+fn close_stream_ends_the_stream_and_discards_later_events() {
+    let (sender, receiver) = sse::channel_with(config(3, SseOverflow::CloseStream));
+    let other = sender.clone();
+    for n in 0..4 {
+        sender.send(n.to_string());
+    }
+    assert!(sender.is_closed() && other.is_closed());
+    assert_eq!(sender.buffered(), 0);
+    other.send("late");
+    assert_eq!(sender.dropped(), 5);
+    // The stream ends although senders are still alive.
+    assert_eq!(receiver.collect(), "");
+    drop((sender, other));
+
+    let (events, stream) = SseResponse::<String>::channel_with(config(1, SseOverflow::CloseStream));
+    events.send("a".to_string());
+    events.send("b".to_string());
+    assert!(events.is_closed());
+    assert_eq!(stream.collect().unwrap(), "");
+}
+
+/// The receiver only starts after most events were sent; `collect` itself drains as fast as it
+/// can, so this bounds the queue, not a slow socket.
+#[test]
+fn late_reader_keeps_the_buffer_bounded() {
+    const EVENTS: usize = 500;
+    let (sender, receiver) = sse::channel_with(config(8, SseOverflow::DropOldest));
+    let producer = std::thread::spawn(move || {
+        let mut most_buffered = 0;
+        for n in 0..EVENTS {
+            sender.send(n.to_string());
+            most_buffered = most_buffered.max(sender.buffered());
+            if n % 100 == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+        (most_buffered, sender.dropped())
+    });
+    // The reader starts late and then drains whatever is left.
+    std::thread::sleep(Duration::from_millis(50));
+    let stream = receiver.collect();
+    let (most_buffered, dropped) = producer.join().unwrap();
+
+    let received = data(&stream);
+    assert!(most_buffered <= 8, "buffer grew to {most_buffered}");
+    assert_eq!(received.len() as u64 + dropped, EVENTS as u64);
+    assert_eq!(received.last(), Some(&"499"));
+}
+
+#[test]
+fn drop_oldest_keeps_the_newest_events_within_the_body_cap() {
+    let (sender, receiver) = sse::channel_with(capped(27, SseOverflow::DropOldest));
+    for n in 0..10 {
+        sender.send(n.to_string());
+    }
+    drop(sender);
+    let stream = receiver.collect();
+    assert_eq!(data(&stream), ["7", "8", "9"]);
+    assert!(stream.len() <= 27);
+}
+
+#[test]
+fn drop_newest_stops_collecting_at_the_body_cap() {
+    let (sender, receiver) = sse::channel_with(capped(27, SseOverflow::DropNewest));
+    let other = sender.clone();
+    for n in 0..10 {
+        sender.send(n.to_string());
+    }
+    drop(sender);
+    assert_eq!(data(&receiver.collect()), ["0", "1", "2"]);
+    assert_eq!(other.dropped(), 7);
+}
+
+#[test]
+fn close_stream_at_the_body_cap_ends_an_endless_producer() {
+    let (sender, receiver) = sse::channel_with(capped(27, SseOverflow::CloseStream));
+    // Never stops on its own: only the closed stream ends it.
+    let producer = std::thread::spawn(move || {
+        let mut sent = 0u64;
+        while !sender.is_closed() {
+            sender.send("x");
+            sent += 1;
+        }
+        (sent, sender.dropped())
+    });
+    let stream = receiver.collect();
+    let (sent, dropped) = producer.join().unwrap();
+    // Empty unless the queue overflowed (and closed) before the body did
+    assert!(stream.len() <= 27, "{stream}");
+    assert_eq!(sent, dropped + data(&stream).len() as u64);
+
+    let (events, stream) =
+        SseResponse::<String>::channel_with(capped(16, SseOverflow::CloseStream));
+    events.send("a".to_string());
+    events.send("b".to_string());
+    drop(events);
+    assert_eq!(stream.collect().unwrap(), "");
+}
+
+#[test]
+fn typed_response_body_is_capped() {
+    // `data: "n"\n\n` is 11 bytes
+    let (events, stream) = SseResponse::<String>::channel_with(capped(22, SseOverflow::DropOldest));
+    for n in 0..5 {
+        events.send(n.to_string());
+    }
+    drop(events);
+    assert_eq!(stream.collect().unwrap(), "data: \"3\"\n\ndata: \"4\"\n\n");
+}
@@ -0,0 +1,181 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! SSE request correlation: a correlated stream opens with a `: request-id=` comment, logs
+//! every event in an `sse_event` span carrying the request id under one `sse_stream` span,
+//! closes that span when the stream ends or is dropped, and `text/event-stream` routes hand
+//! handlers the correlation of their request.
+
+mod tracing_util;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::sse::{self, SseCorrelation};
+use brrtrouter::typed::SseResponse;
+use opentelemetry::Value as OtelValue;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing_util::TestTracing;
+
+const REQUEST_ID: &str = "01J9ZK6W3Q8V4T2N5R7X0M1B2C";
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Feed
+  version: "1.0"
+paths:
+  /feed:
+    get:
+      operationId: feed
+      responses:
+        "200":
+          description: Feed events
+          content:
+            text/event-stream:
+              schema: { type: string }
+"#;
+
+/// Value of the span attribute `key`, if recorded
+fn attribute(span: &opentelemetry_sdk::trace::SpanData, key: &str) -> Option<OtelValue> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| kv.value.clone())
+}
+
+#[test]
+fn event_logs_carry_the_request_id() {
+    let tracing = TestTracing::init();
+    let correlation = SseCorrelation::new(REQUEST_ID).traceparent(TRACEPARENT);
+    let body = SseResponse::from_events([json!({ "n": 1 }), json!({ "n": 2 })])
+        .correlate(Some(&correlation))
+        .collect()
+        .unwrap();
+    assert_eq!(
+        body,
+        format!(": request-id={REQUEST_ID}\n\ndata: {{\"n\":1}}\n\ndata: {{\"n\":2}}\n\n")
+    );
+    tracing.force_flush();
+
+    let streams = tracing.spans_named("sse_stream");
+    assert_eq!(streams.len(), 1, "the stream span is closed once collected");
+    let stream = &streams[0];
+    assert_eq!(
+        attribute(stream, "request_id"),
+        Some(OtelValue::from(REQUEST_ID))
+    );
+    assert_eq!(
+        attribute(stream, "traceparent"),
+        Some(OtelValue::from(TRACEPARENT))
+    );
+
+    let events = tracing.spans_named("sse_event");
+    assert_eq!(events.len(), 2);
+    for event in &events {
+        assert_eq!(
+            attribute(event, "request_id"),
+            Some(OtelValue::from(REQUEST_ID))
+        );
+        assert_eq!(event.parent_span_id, stream.span_context.span_id());
+        assert_eq!(
+            event.span_context.trace_id(),
+            stream.span_context.trace_id()
+        );
+        assert!(
+            event
+                .events
+                .events
+                .iter()
+                .any(|e| e.name == "SSE event sent"),
+            "{:?}",
+            event.events
+        );
+    }
+}
+
+#[test]
+fn stream_span_closes_when_the_stream_is_dropped() {
+    let tracing = TestTracing::init();
+    let (sender, receiver) = sse::channel();
+    let receiver = receiver.correlate(Some(&SseCorrelation::new(REQUEST_ID)));
+    sender.send("never delivered");
+    tracing.force_flush();
+    assert!(tracing.spans_named("sse_stream").is_empty());
+
+    drop(receiver);
+    tracing.force_flush();
+    assert_eq!(tracing.spans_named("sse_stream").len(), 1);
+    assert!(tracing.spans_named("sse_event").is_empty());
+}
+
+#[test]
+fn request_id_comment_is_optional() {
+    let (sender, receiver) = sse::channel();
+    let correlation = SseCorrelation::new(REQUEST_ID).comment(false);
+    sender.send("tick");
+    drop(sender);
+    assert_eq!(
+        receiver.correlate(Some(&correlation)).collect(),
+        "data: tick\n\n"
+    );
+
+    // Line breaks in the id would end the comment early.
+    let (sender, receiver) = sse::channel();
+    drop(sender);
+    let correlation = SseCorrelation::new("abc\r\ndata: injected");
+    assert_eq!(
+        receiver.correlate(Some(&correlation)).collect(),
+        ": request-id=abc\n\n"
+    );
+}
+
+#[test]
+fn event_stream_routes_receive_their_correlation() {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("feed", |req: HandlerRequest| {
+            let correlation = req.extensions.get::<SseCorrelation>();
+            let same_id = correlation.map(|c| c.request_id() == req.request_id.to_string());
+            let (sender, receiver) = sse::channel();
+            sender.send(json!({ "same_id": same_id }).to_string());
+            drop(sender);
+            let body = receiver.correlate(correlation).collect();
+            let mut headers = HeaderVec::new();
+            headers.push((Arc::from("content-type"), "text/event-stream".to_string()));
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::new(200, headers, Value::String(body)));
+        });
+    }
+    let client = TestClient::new(
+        AppService::builder()
+            .router(Router::new(routes))
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from(&spec_path))
+            .build()
+            .unwrap(),
+    );
+
+    let resp = client
+        .get("/feed")
+        .header("X-Request-ID", REQUEST_ID)
+        .header("traceparent", TRACEPARENT)
+        .send();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.body(),
+        &Value::String(format!(
+            ": request-id={REQUEST_ID}\n\ndata: {{\"same_id\":true}}\n\n"
+        ))
+    );
+}
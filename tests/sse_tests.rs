@@ -40,14 +40,15 @@ impl SseTestServer {
             registry::register_from_spec(&mut dispatcher, &routes);
         }
         dispatcher.add_middleware(Arc::new(TracingMiddleware));
-        let mut service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            schemes,
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("examples/pet_store/static_site")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let mut service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(schemes)
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("examples/pet_store/static_site"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
 
         // Register ApiKey provider so /events (secured) can be accessed in test
         struct ApiKeyProvider {
@@ -0,0 +1,117 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! `BRRTR_STACK_GUARD=on`: handler coroutines record their peak stack use, and a handler
+//! that checks its [`StackBudget`] while recursing gets a 500 naming the variable to raise
+//! instead of overflowing. The same recursion succeeds under a raised stack.
+
+use brrtrouter::dispatcher::{
+    spawn_untyped_with_stack_size_and_name, stack_watermarks, Dispatcher, HandlerRequest,
+    HandlerResponse, HeaderVec, StackBudget, STACK_GUARD_ENV,
+};
+use brrtrouter::router::Router;
+use brrtrouter::spec::build_routes;
+use oas3::OpenApiV3Spec;
+use serde_json::json;
+
+const SPEC: &str = r#"openapi: 3.1.0
+info:
+  title: Stacks
+  version: '1.0'
+paths:
+  /small/{depth}:
+    get:
+      operationId: small_stack
+      parameters:
+        - { name: depth, in: path, required: true, schema: { type: integer } }
+      responses:
+        '200': { description: OK }
+  /large/{depth}:
+    get:
+      operationId: large_stack
+      parameters:
+        - { name: depth, in: path, required: true, schema: { type: integer } }
+      responses:
+        '200': { description: OK }
+"#;
+
+const SMALL_STACK: usize = 16 * 1024;
+const LARGE_STACK: usize = 256 * 1024;
+/// Each level keeps at least 512 bytes live, so this needs well over 32 KiB.
+const DEPTH: usize = 64;
+
+/// Recurse `depth` levels, checking the budget at each one.
+#[inline(never)]
+fn descend(depth: usize, budget: Option<&StackBudget>) -> usize {
+    if let Some(budget) = budget {
+        budget.check(4 * 1024);
+    }
+    let frame = std::hint::black_box([depth as u8; 512]);
+    if depth == 0 {
+        return usize::from(frame[0]);
+    }
+    descend(depth - 1, budget) + usize::from(std::hint::black_box(frame)[1])
+}
+
+fn recursive_handler(req: HandlerRequest) -> HandlerResponse {
+    let depth: usize = req.get_path_param("depth").unwrap().parse().unwrap();
+    let budget = req.extensions.get::<StackBudget>();
+    assert!(budget.is_some(), "BRRTR_STACK_GUARD=on stamps a budget");
+    let sum = descend(depth, budget);
+    HandlerResponse::json(200, json!({ "sum": sum }))
+}
+
+fn setup() -> (Router, Dispatcher) {
+    std::env::set_var(STACK_GUARD_ENV, "on");
+    may::config().set_stack_size(0x8000);
+    let spec: OpenApiV3Spec = serde_yaml::from_str(SPEC).unwrap();
+    let router = Router::new(build_routes(&spec, "stacks").unwrap());
+
+    let mut dispatcher = Dispatcher::new();
+    for (name, stack_size) in [("small_stack", SMALL_STACK), ("large_stack", LARGE_STACK)] {
+        let tx = unsafe {
+            spawn_untyped_with_stack_size_and_name(recursive_handler, stack_size, Some(name))
+        }
+        .unwrap();
+        dispatcher.handlers.insert(name.to_string(), tx);
+    }
+    (router, dispatcher)
+}
+
+fn get(router: &Router, dispatcher: &Dispatcher, path: &str) -> HandlerResponse {
+    let route_match = router.route(http::Method::GET, path).unwrap();
+    dispatcher
+        .dispatch(route_match, None, HeaderVec::new(), HeaderVec::new())
+        .unwrap()
+}
+
+#[test]
+fn exhausted_budget_answers_500_with_a_hint() {
+    let (router, dispatcher) = setup();
+
+    let resp = get(&router, &dispatcher, &format!("/small/{DEPTH}"));
+    assert_eq!(resp.status, 500);
+    let detail = resp.body["detail"].as_str().unwrap();
+    assert!(detail.contains("BRRTR_STACK_SIZE__SMALL_STACK"), "{detail}");
+    assert_eq!(resp.body["hint"], "raise BRRTR_STACK_SIZE__SMALL_STACK");
+    assert_eq!(resp.body["stack_size"], SMALL_STACK);
+
+    // The coroutine survived and still serves requests that fit.
+    let resp = get(&router, &dispatcher, "/small/2");
+    assert_eq!(resp.status, 200);
+}
+
+#[test]
+fn raised_stack_fits_the_recursion_and_records_its_peak() {
+    let (router, dispatcher) = setup();
+
+    let resp = get(&router, &dispatcher, &format!("/large/{DEPTH}"));
+    assert_eq!(resp.status, 200, "{:?}", resp.body);
+
+    let (_, size, peak) = stack_watermarks()
+        .into_iter()
+        .find(|(name, _, _)| name == "large_stack")
+        .unwrap();
+    assert_eq!(size, LARGE_STACK);
+    assert!(peak >= DEPTH * 512, "peak {peak}");
+    assert!(peak < LARGE_STACK, "peak {peak}");
+}
@@ -0,0 +1,166 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Startup summary: with JSON logging the server reports its spec path, address, resolved
+//! config and route table as one structured `tracing` event, with secrets redacted.
+
+use brrtrouter::otel::LogFormat;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppConfig, StartupSummary};
+use serde_json::{json, Value};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Vault
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKeyAuth:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /health-check:
+    get:
+      operationId: health_check
+      responses:
+        "200": { description: OK }
+  /secrets/{id}:
+    get:
+      operationId: get_secret
+      security:
+        - ApiKeyAuth: []
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: string }
+      responses:
+        "200": { description: OK }
+"#;
+
+const CONFIG: &str = r#"
+security:
+  api_keys:
+    ApiKeyAuth:
+      key: super-secret-api-key
+      header_name: X-API-Key
+  bearer:
+    signature: super-secret-signature
+cors:
+  allow_credentials: true
+http:
+  base_path: /vault
+"#;
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `f` under a JSON subscriber and return everything it logged.
+fn capture_json(f: impl FnOnce()) -> String {
+    let out = Captured::default();
+    let writer = out.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let bytes = out.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap()
+}
+
+fn summary() -> StartupSummary {
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, _slug) = brrtrouter::load_spec(spec_path.to_str().unwrap()).unwrap();
+    let config: AppConfig = serde_yaml::from_str(CONFIG).unwrap();
+    let router = Router::new(routes);
+    StartupSummary::new("openapi.yaml", "127.0.0.1:8081", &config, &router)
+}
+
+#[test]
+fn startup_is_one_structured_event() {
+    let summary = summary();
+    let log = capture_json(|| summary.log(LogFormat::Json));
+
+    let events: Vec<Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 1, "{log}");
+    let event = &events[0];
+    assert_eq!(event["target"], "brrtrouter::startup");
+    let fields = &event["fields"];
+    assert_eq!(fields["message"], "Server started");
+    assert_eq!(fields["spec_path"], "openapi.yaml");
+    assert_eq!(fields["addr"], "127.0.0.1:8081");
+    assert_eq!(fields["route_count"], 2);
+
+    let mut routes: Vec<Value> = serde_json::from_str(fields["routes"].as_str().unwrap()).unwrap();
+    routes.sort_by_key(|r| r["handler"].as_str().unwrap().to_string());
+    assert_eq!(
+        routes,
+        vec![
+            json!({
+                "method": "GET",
+                "path": "/secrets/{id}",
+                "handler": "get_secret",
+                "auth": [{ "ApiKeyAuth": [] }],
+            }),
+            json!({
+                "method": "GET",
+                "path": "/health-check",
+                "handler": "health_check",
+                "auth": [],
+            }),
+        ]
+    );
+
+    let config: Value = serde_json::from_str(fields["config"].as_str().unwrap()).unwrap();
+    assert_eq!(config["http"]["base_path"], "/vault");
+    assert_eq!(config["cors"]["allow_credentials"], true);
+    assert_eq!(
+        config["security"]["api_keys"]["ApiKeyAuth"]["header_name"],
+        "X-API-Key"
+    );
+}
+
+#[test]
+fn secrets_are_redacted() {
+    let summary = summary();
+    assert_eq!(
+        summary.config["security"]["api_keys"]["ApiKeyAuth"]["key"],
+        "<REDACTED>"
+    );
+    assert_eq!(
+        summary.config["security"]["bearer"]["signature"],
+        "<REDACTED>"
+    );
+
+    let log = capture_json(|| summary.log(LogFormat::Json));
+    assert!(!log.is_empty());
+    for secret in ["super-secret-api-key", "super-secret-signature"] {
+        assert!(!log.contains(secret), "{secret} leaked: {log}");
+        assert!(!summary.to_json().to_string().contains(secret));
+    }
+}
+
+#[test]
+fn pretty_format_emits_no_event() {
+    let summary = summary();
+    assert_eq!(capture_json(|| summary.log(LogFormat::Pretty)), "");
+}
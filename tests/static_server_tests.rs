@@ -33,14 +33,15 @@ impl StaticFileTestServer {
 
         // Important: Uses tests/staticdata for static files and includes doc directory
         // for comprehensive integration testing
-        let service = AppService::new(
-            router,
-            Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-            HashMap::new(),
-            PathBuf::from("examples/openapi.yaml"),
-            Some(PathBuf::from("tests/staticdata")),
-            Some(PathBuf::from("examples/pet_store/doc")),
-        );
+        let service = AppService::builder()
+            .shared_router(router)
+            .dispatcher(dispatcher)
+            .security_schemes(HashMap::new())
+            .spec_path(PathBuf::from("examples/openapi.yaml"))
+            .static_dir(PathBuf::from("tests/staticdata"))
+            .doc_dir(PathBuf::from("examples/pet_store/doc"))
+            .build()
+            .unwrap();
 
         let listener = TcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
@@ -104,6 +105,8 @@ fn test_js_served() {
     let (status, ct) = parse_parts(&resp);
     assert_eq!(status, 200);
     assert_eq!(ct, "application/javascript");
+    let head = resp.split("\r\n\r\n").next().unwrap().to_ascii_lowercase();
+    assert!(head.contains("x-content-type-options: nosniff"), "{head}");
 
     // Automatic cleanup!
 }
@@ -131,3 +134,28 @@ fn test_traversal_blocked() {
 
     // Automatic cleanup!
 }
+
+#[test]
+fn test_precompressed_gzip_served_when_accepted() {
+    let server = StaticFileTestServer::new();
+    let resp = send_request(
+        &server.addr(),
+        "GET /bundle.js HTTP/1.1\r\nHost: x\r\nAccept-Encoding: br, gzip\r\n\r\n",
+    );
+    let (status, ct) = parse_parts(&resp);
+    assert_eq!(status, 200);
+    assert_eq!(ct, "application/javascript");
+    let head = resp.split("\r\n\r\n").next().unwrap().to_ascii_lowercase();
+    // No `bundle.js.br` fixture: the gzip sibling is the best acceptable variant.
+    assert!(head.contains("content-encoding: gzip"), "{head}");
+    assert!(head.contains("vary: accept-encoding"), "{head}");
+
+    // Only brotli acceptable and no `.br` file: the original is served uncompressed.
+    let resp = send_request(
+        &server.addr(),
+        "GET /bundle.js HTTP/1.1\r\nHost: x\r\nAccept-Encoding: br\r\n\r\n",
+    );
+    let head = resp.split("\r\n\r\n").next().unwrap().to_ascii_lowercase();
+    assert!(!head.contains("content-encoding"), "{head}");
+    assert!(resp.ends_with("console.log('bundled');\n"));
+}
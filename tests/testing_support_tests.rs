@@ -0,0 +1,195 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! `brrtrouter::testing`: tokens from `TestJwt` validate against a `JwksBearerProvider`
+//! fetching keys from `MockJwksServer`, for both HS256 and RS256, and fixed-signature tokens
+//! against a `BearerJwtProvider`.
+
+use brrtrouter::dispatcher::HeaderVec;
+use brrtrouter::router::ParamVec;
+use brrtrouter::security::{
+    AuthOutcome, BearerJwtProvider, JwksBearerProvider, SecurityProvider, SecurityRequest,
+};
+use brrtrouter::spec::SecurityScheme;
+use brrtrouter::testing::{hs256_jwk, jwks, rsa_jwk, MockJwksServer, TestJwt};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+const ISSUER: &str = "https://issuer.example";
+const AUDIENCE: &str = "my-audience";
+const RS256_KEY: &[u8] = include_bytes!("fixtures/jwt/rs256.pem");
+const RS256_JWKS: &str = include_str!("fixtures/jwt/rs256.jwks.json");
+
+fn check(provider: &JwksBearerProvider, required: &[&str], token: &str) -> AuthOutcome {
+    let scheme = SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: None,
+        description: None,
+    };
+    let mut headers = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    let req = SecurityRequest {
+        headers: &headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    let required: Vec<String> = required.iter().map(|s| s.to_string()).collect();
+    provider.check(&scheme, &required, &req)
+}
+
+fn provider(server: &MockJwksServer) -> JwksBearerProvider {
+    JwksBearerProvider::new(server.url())
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+}
+
+fn get(server: &MockJwksServer) -> String {
+    let mut stream = TcpStream::connect(server.addr()).unwrap();
+    stream
+        .write_all(b"GET /jwks.json HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp).unwrap();
+    resp
+}
+
+#[test]
+fn hs256_token_validates_against_mock_jwks() {
+    let server = MockJwksServer::start(jwks([hs256_jwk("k1", b"supersecret")])).unwrap();
+    let provider = provider(&server);
+    let token = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .scopes(["pets:read", "pets:write"]);
+
+    assert_eq!(
+        check(&provider, &["pets:read"], &token.sign()),
+        AuthOutcome::Granted
+    );
+    assert_eq!(
+        check(&provider, &["pets:admin"], &token.sign()),
+        AuthOutcome::InsufficientScope {
+            missing: vec!["pets:admin".to_string()]
+        }
+    );
+    let forged = TestJwt::hs256(b"wrong")
+        .kid("k1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .sign();
+    assert_eq!(check(&provider, &[], &forged), AuthOutcome::Unauthenticated);
+    assert!(server.requests() >= 1);
+}
+
+#[test]
+fn rs256_token_validates_against_mock_jwks() {
+    let server = MockJwksServer::start(RS256_JWKS).unwrap();
+    let provider = provider(&server);
+    let token = TestJwt::rs256(RS256_KEY)
+        .unwrap()
+        .kid("rs1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .scopes(["read"])
+        .sign();
+    assert_eq!(check(&provider, &["read"], &token), AuthOutcome::Granted);
+
+    // Same key under a kid the JWKS does not publish
+    let unknown_kid = TestJwt::rs256(RS256_KEY)
+        .unwrap()
+        .kid("rs2")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .sign();
+    assert_eq!(
+        check(&provider, &[], &unknown_kid),
+        AuthOutcome::Unauthenticated
+    );
+    assert!(TestJwt::rs256(b"not a key").is_err());
+}
+
+#[test]
+fn expiry_and_claims_are_configurable() {
+    let server = MockJwksServer::start(jwks([hs256_jwk("k1", b"supersecret")])).unwrap();
+    let provider = provider(&server);
+    let base = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE);
+
+    let expired = base.clone().expires_in(-3600).sign();
+    assert_eq!(
+        check(&provider, &[], &expired),
+        AuthOutcome::Unauthenticated
+    );
+    let wrong_audience = base.clone().audience("someone-else").sign();
+    assert_eq!(
+        check(&provider, &[], &wrong_audience),
+        AuthOutcome::Unauthenticated
+    );
+
+    let claims = base.claim("tenant", "acme").claims();
+    assert_eq!(claims["tenant"], json!("acme"));
+    assert!(claims["exp"].is_i64());
+    assert_eq!(
+        TestJwt::hs256(b"s").no_expiry().claims(),
+        Value::Object(Default::default())
+    );
+}
+
+#[test]
+fn claims_can_be_relative_to_now() {
+    let server = MockJwksServer::start(jwks([hs256_jwk("k1", b"supersecret")])).unwrap();
+    let provider = provider(&server);
+    let not_yet = TestJwt::hs256(b"supersecret")
+        .kid("k1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .claim_from_now("nbf", 3600);
+    let nbf = not_yet.claims()["nbf"].as_i64().unwrap();
+    let exp = not_yet.claims()["exp"].as_i64().unwrap();
+    assert!((exp - nbf).abs() <= 1, "nbf {nbf}, exp {exp}");
+    assert_eq!(
+        check(&provider, &[], &not_yet.sign()),
+        AuthOutcome::Unauthenticated
+    );
+}
+
+#[test]
+fn fixed_signature_tokens_validate_against_bearer_jwt_provider() {
+    let token = TestJwt::fixed_signature("sig").scopes(["read"]).sign();
+    assert!(token.ends_with(".sig"), "{token}");
+    let scheme = SecurityScheme::Http {
+        scheme: "bearer".to_string(),
+        bearer_format: None,
+        description: None,
+    };
+    let mut headers = HeaderVec::new();
+    headers.push((Arc::from("authorization"), format!("Bearer {token}")));
+    let req = SecurityRequest {
+        headers: &headers,
+        query: &ParamVec::new(),
+        cookies: &HeaderVec::new(),
+    };
+    let read = ["read".to_string()];
+    assert!(BearerJwtProvider::new("sig").validate(&scheme, &read, &req));
+    assert!(!BearerJwtProvider::new("other").validate(&scheme, &read, &req));
+    assert!(!BearerJwtProvider::new("sig").validate(&scheme, &["write".to_string()], &req));
+}
+
+#[test]
+fn mock_jwks_server_counts_requests_and_rotates_keys() {
+    let first = jwks([hs256_jwk("k1", b"one")]);
+    let second = jwks([rsa_jwk("k2", "bW9kdWx1cw", "AQAB")]);
+    let server = MockJwksServer::start(first.clone()).unwrap();
+    assert!(server.url().ends_with("/jwks.json"));
+
+    assert!(get(&server).ends_with(&first));
+    server.set_jwks(second.clone());
+    let resp = get(&server);
+    assert!(resp.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(resp.ends_with(&second));
+    assert_eq!(server.requests(), 2);
+}
@@ -12,18 +12,17 @@
 
 use base64::Engine;
 use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderVec};
-use brrtrouter::http::{fetch_get, HttpFetchOptions};
 use brrtrouter::middleware::TracingMiddleware;
 use brrtrouter::router::Router;
 use brrtrouter::security::JwksBearerProvider;
 use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use brrtrouter::testing::{hs256_jwk, jwks, MockJwksServer, TestJwt};
 use serde_json::json;
 use std::io::{Read, Write};
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 mod tracing_util;
 use tracing_util::TestTracing;
@@ -35,148 +34,33 @@ use common::temp_files;
 // Helpers
 // ---------------------------------------------------------------------------
 
-fn base64url(data: &[u8]) -> String {
-    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
-}
-
-fn base64url_std(data: &[u8]) -> String {
-    base64::engine::general_purpose::STANDARD.encode(data)
-}
-
-/// Build a valid at+jwt token (HS256) signed with a secret key, scoped to a mock JWKS server.
-fn make_valid_at_jwt(secret: &[u8], kid: &str) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        typ: Some("at+jwt".to_string()),
-        ..Default::default()
-    };
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    let claims = json!({
-        "iss": "https://issuer.example",
-        "aud": "my-api",
-        "exp": now + 3600,
-        "scope": "read write"
-    });
-    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-}
-
-/// Build a JWT with a custom typ value.
-fn make_jwt_with_typ(secret: &[u8], typ: &str, kid: &str) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        typ: Some(typ.to_string()),
-        ..Default::default()
-    };
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    let claims = json!({
-        "iss": "https://issuer.example",
-        "aud": "my-api",
-        "exp": now + 3600,
-        "scope": "read write"
-    });
-    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
-}
+const ISSUER: &str = "https://issuer.example";
+const AUDIENCE: &str = "my-api";
 
-/// Build a JWT with NO typ claim.
-fn make_jwt_without_typ(secret: &[u8], kid: &str) -> String {
-    use jsonwebtoken::{Algorithm, EncodingKey, Header};
-    use serde_json::json;
-
-    let header = Header {
-        kid: Some(kid.to_string()),
-        alg: Algorithm::HS256,
-        ..Default::default()
-    };
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-    let claims = json!({
-        "iss": "https://issuer.example",
-        "aud": "my-api",
-        "exp": now + 3600,
-        "scope": "read write"
-    });
-    jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret)).unwrap()
+/// A token for the service from [`build_jwks_service`], signed with `secret` as key `k1`
+fn token(secret: &[u8]) -> TestJwt {
+    TestJwt::hs256(secret)
+        .kid("k1")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
+        .scopes(["read", "write"])
 }
 
 /// Build a raw JWT with a custom header JSON (bypass jsonwebtoken crate).
 /// Useful for testing malformed headers (numbers, objects, null bytes, etc.).
 fn make_raw_jwt(header_json: &str, payload_json: &str, signature: &str) -> String {
-    let h = base64url_std(header_json.as_bytes());
-    let p = base64url_std(payload_json.as_bytes());
+    let h = base64::engine::general_purpose::STANDARD.encode(header_json);
+    let p = base64::engine::general_purpose::STANDARD.encode(payload_json);
     format!("{}.{}.{}", h, p, signature)
 }
 
-fn start_mock_jwks_server_inner(jwks_json: &str) -> String {
-    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-    let addr = listener.local_addr().unwrap();
-    let url = format!("http://{}:{}/jwks.json", addr.ip(), addr.port());
-    let body = jwks_json.to_string();
-    eprintln!("DEBUG: mock jwks server listening at {}", url);
-    thread::spawn(move || {
-        // Handle unlimited connections in a loop (background refresh retries up to 2x)
-        let _listener = listener;
-        loop {
-            match _listener.accept() {
-                Ok((mut stream, _)) => {
-                    // Read the HTTP request to drain the socket
-                    let mut buf = [0u8; 4096];
-                    let n = stream.read(&mut buf).unwrap_or(0);
-                    let req_str = String::from_utf8_lossy(&buf[..n]).to_string();
-                    eprintln!(
-                        "DEBUG: mock jwks server received request ({} bytes): {}",
-                        n,
-                        req_str.lines().next().unwrap_or("")
-                    );
-                    let resp = format!(
-                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                        body.len(),
-                        body
-                    );
-                    let written = resp.as_bytes().len();
-                    let _ = stream.write_all(resp.as_bytes());
-                    let _ = stream.flush();
-                    eprintln!("DEBUG: mock jwks server sent {} bytes", written);
-                }
-                Err(_) => break, // listener dropped
-            }
-        }
-    });
-    url
-}
-
-/// Build a complete service with JWKS Bearer security using a mock JWKS server.
-/// Returns the test server with automatic cleanup.
-fn build_jwks_service(
-    jwks_json: &str,
-    _expected_typ: &str,
-) -> (TestTracing, ServerHandle, SocketAddr) {
+/// Build a complete service with JWKS Bearer security, trusting HS256 key `k1` = `secret`
+/// from a mock JWKS server that runs until the returned [`MockJwksServer`] is dropped.
+fn build_jwks_service(secret: &[u8]) -> (TestTracing, MockJwksServer, ServerHandle, SocketAddr) {
     may::config().set_stack_size(0x8000);
     let tracing = TestTracing::init();
 
-    let jwks_url = start_mock_jwks_server_inner(jwks_json);
-
-    // Verify the mock server responds before proceeding
-    eprintln!("DEBUG: Testing mock server at {}", jwks_url);
-    let (status, body) = fetch_get(&jwks_url, &HttpFetchOptions::default()).unwrap();
-    assert_eq!(status, 200);
-    let body = String::from_utf8(body).unwrap();
-    eprintln!("DEBUG: Mock server responded with: {}", body);
+    let jwks_server = MockJwksServer::start(jwks([hs256_jwk("k1", secret)])).unwrap();
 
     const SPEC: &str = r#"openapi: 3.1.0
 info:
@@ -212,20 +96,21 @@ paths:
         });
     }
     dispatcher.add_middleware(Arc::new(TracingMiddleware));
-    let mut service = AppService::new(
-        router,
-        Arc::new(arc_swap::ArcSwap::from_pointee(dispatcher)),
-        schemes,
-        PathBuf::from("examples/openapi.yaml"),
-        Some(PathBuf::from("examples/pet_store/static_site")),
-        Some(PathBuf::from("examples/pet_store/doc")),
-    );
-    let provider = JwksBearerProvider::new(&jwks_url)
+    let mut service = AppService::builder()
+        .shared_router(router)
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .static_dir(PathBuf::from("examples/pet_store/static_site"))
+        .doc_dir(PathBuf::from("examples/pet_store/doc"))
+        .build()
+        .unwrap();
+    let provider = JwksBearerProvider::new(jwks_server.url())
         // This legacy fixture deliberately exercises typ enforcement independently of the
         // production asymmetric-token profile. HMAC must therefore be opted in explicitly.
         .allowed_algorithms(&[jsonwebtoken::Algorithm::HS256])
-        .issuer("https://issuer.example")
-        .audience("my-api")
+        .issuer(ISSUER)
+        .audience(AUDIENCE)
         .leeway(30);
     service.register_security_provider("BearerAuth", Arc::new(provider));
 
@@ -234,7 +119,7 @@ paths:
     drop(listener);
     let handle = HttpServer(service).start(addr).unwrap();
     handle.wait_ready().unwrap();
-    (tracing, handle, addr)
+    (tracing, jwks_server, handle, addr)
 }
 
 fn send_raw_request(addr: &SocketAddr, req: &str) -> String {
@@ -338,18 +223,9 @@ fn make_authorization_header(token: &str) -> String {
 #[test]
 fn unit_valid_typ_atjwt_accepted() {
     let secret = b"test-secret-key-12345";
-    let jwks = serde_json::json!({
-        "keys": [{
-            "kty": "oct",
-            "alg": "HS256",
-            "kid": "k1",
-            "k": base64url(secret)
-        }]
-    })
-    .to_string();
-    let (_tracing, handle, addr) = build_jwks_service(&jwks, "at+jwt");
-
-    let token = make_valid_at_jwt(secret, "k1");
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
+
+    let token = token(secret).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -374,18 +250,9 @@ fn unit_valid_typ_atjwt_accepted() {
 #[test]
 fn unit_missing_typ_claim_rejected() {
     let secret = b"test-secret-key-12345";
-    let jwks = serde_json::json!({
-        "keys": [{
-            "kty": "oct",
-            "alg": "HS256",
-            "kid": "k1",
-            "k": base64url(secret)
-        }]
-    })
-    .to_string();
-    let (_tracing, handle, addr) = build_jwks_service(&jwks, "at+jwt");
-
-    let token = make_jwt_without_typ(secret, "k1");
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
+
+    let token = token(secret).typ(None).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -405,20 +272,9 @@ fn unit_missing_typ_claim_rejected() {
 #[test]
 fn unit_wrong_typ_jwt_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "jwt", "k1");
+    let token = token(secret).typ(Some("jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -438,20 +294,9 @@ fn unit_wrong_typ_jwt_rejected() {
 #[test]
 fn unit_wrong_typ_id_at_jwt_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "id+at+jwt", "k1");
+    let token = token(secret).typ(Some("id+at+jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -471,20 +316,9 @@ fn unit_wrong_typ_id_at_jwt_rejected() {
 #[test]
 fn unit_empty_typ_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "", "k1");
+    let token = token(secret).typ(Some("")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -500,20 +334,9 @@ fn unit_empty_typ_rejected() {
 #[test]
 fn unit_typ_case_sensitive_rejects_uppercase() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "AT+JWT", "k1");
+    let token = token(secret).typ(Some("AT+JWT")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -533,20 +356,9 @@ fn unit_typ_case_sensitive_rejects_uppercase() {
 #[test]
 fn unit_typ_rejects_whitespace() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, " at+jwt", "k1");
+    let token = token(secret).typ(Some(" at+jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -566,20 +378,9 @@ fn unit_typ_rejects_whitespace() {
 #[test]
 fn unit_typ_refresh_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "refresh", "k1");
+    let token = token(secret).typ(Some("refresh")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -599,20 +400,9 @@ fn unit_typ_refresh_rejected() {
 #[test]
 fn unit_error_message_includes_expected_actual_typ() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "self-issued", "k1");
+    let token = token(secret).typ(Some("self-issued")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -640,21 +430,10 @@ fn unit_error_message_includes_expected_actual_typ() {
 #[test]
 fn integration_login_service_issues_typ_atjwt() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Build a token that simulates what a login service would issue
-    let token = make_valid_at_jwt(secret, "k1");
+    let token = token(secret).sign();
 
     // Parse the token header to verify typ
     let parts: Vec<&str> = token.split('.').collect();
@@ -687,21 +466,10 @@ fn integration_login_service_issues_typ_atjwt() {
 #[test]
 fn integration_service_rejects_token_without_typ() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Send token without typ
-    let token = make_jwt_without_typ(secret, "k1");
+    let token = token(secret).typ(None).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -725,20 +493,9 @@ fn integration_service_rejects_token_without_typ() {
 #[test]
 fn integration_service_rejects_wrong_typ() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "jwt", "k1");
+    let token = token(secret).typ(Some("jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -762,20 +519,9 @@ fn integration_service_rejects_wrong_typ() {
 #[test]
 fn integration_typ_enforcement_with_hs256() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_valid_at_jwt(secret, "k1");
+    let token = token(secret).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -800,21 +546,10 @@ fn integration_typ_checked_before_signature() {
     let wrong_secret = b"wrong-secret-key-xyz";
 
     // Create a token with the correct typ but signed with wrong key
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // This token has typ="at+jwt" but signature is invalid
-    let token = make_valid_at_jwt(wrong_secret, "k1");
+    let token = token(wrong_secret).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -838,18 +573,7 @@ fn integration_typ_checked_before_signature() {
 #[test]
 fn security_refresh_token_not_jwt_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Send an opaque string (simulating a refresh token) as Bearer
     let opaque_refresh_token = "rT_abc123_def456ghi789";
@@ -871,20 +595,9 @@ fn security_refresh_token_not_jwt_rejected() {
 #[test]
 fn security_id_token_cannot_bypass_authz() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "id+at+jwt", "k1");
+    let token = token(secret).typ(Some("id+at+jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -905,21 +618,10 @@ fn security_typ_alone_does_not_grant_access() {
     let secret = b"test-secret-key-12345";
     let wrong_secret = b"totally-wrong-key";
 
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Token with correct typ but forged signature
-    let token = make_valid_at_jwt(wrong_secret, "k1");
+    let token = token(wrong_secret).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -938,20 +640,9 @@ fn security_typ_alone_does_not_grant_access() {
 #[test]
 fn security_no_info_leakage_in_typ_error() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "attacker-control", "k1");
+    let token = token(secret).typ(Some("attacker-control")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -977,18 +668,7 @@ fn security_no_info_leakage_in_typ_error() {
 fn edge_case_typ_as_number_rejected() {
     let secret = b"test-secret-key-12345";
 
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Create a raw JWT with typ as a number in the header
     let header_json = r#"{"alg":"HS256","typ":123,"kid":"k1"}"#;
@@ -1015,18 +695,7 @@ fn edge_case_typ_as_number_rejected() {
 #[test]
 fn edge_case_typ_as_object_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     let header_json = r#"{"alg":"HS256","typ":{"value":"at+jwt"},"kid":"k1"}"#;
     let payload_json =
@@ -1048,18 +717,7 @@ fn edge_case_typ_as_object_rejected() {
 #[test]
 fn edge_case_typ_as_array_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     let header_json = r#"{"alg":"HS256","typ":["at+jwt","refresh"],"kid":"k1"}"#;
     let payload_json =
@@ -1081,18 +739,7 @@ fn edge_case_typ_as_array_rejected() {
 #[test]
 fn edge_case_typ_with_null_bytes_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     let header_json = r#"{"alg":"HS256","typ":"at+\u0000jwt","kid":"k1"}"#;
     let payload_json =
@@ -1118,18 +765,7 @@ fn edge_case_typ_with_null_bytes_rejected() {
 #[test]
 fn edge_case_very_long_typ_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Create a 10KB typ value
     let long_typ = "x".repeat(10_000);
@@ -1157,20 +793,9 @@ fn edge_case_very_long_typ_rejected() {
 #[test]
 fn edge_case_typ_trailing_space_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "at+jwt ", "k1");
+    let token = token(secret).typ(Some("at+jwt ")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -1190,20 +815,9 @@ fn edge_case_typ_trailing_space_rejected() {
 #[test]
 fn edge_case_typ_mixed_case_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
-    let token = make_jwt_with_typ(secret, "At+Jwt", "k1");
+    let token = token(secret).typ(Some("At+Jwt")).sign();
     let req = format!(
         "GET /secure HTTP/1.1\r\nHost: localhost\r\n{}\r\n\r\n",
         make_authorization_header(&token)
@@ -1223,18 +837,7 @@ fn edge_case_typ_mixed_case_rejected() {
 #[test]
 fn edge_case_minimal_header_no_typ_rejected() {
     let secret = b"test-secret-key-12345";
-    let (_tracing, handle, addr) = build_jwks_service(
-        &serde_json::json!({
-            "keys": [{
-                "kty": "oct",
-                "alg": "HS256",
-                "kid": "k1",
-                "k": base64url(secret)
-            }]
-        })
-        .to_string(),
-        "at+jwt",
-    );
+    let (_tracing, _jwks, handle, addr) = build_jwks_service(secret);
 
     // Create raw JWT with only alg and kid, no typ
     let header_json = r#"{"alg":"HS256","kid":"k1"}"#;
@@ -0,0 +1,189 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Typed Server-Sent Events: a typed handler returning [`SseResponse`] streams named JSON
+//! events with heartbeats, a `text/event-stream`-only operation is SSE without `x-sse`, and
+//! events that break the declared event schema are logged but still sent.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use brrtrouter::typed::{Handler, SseEvent, SseResponse, TypedHandlerRequest, HEARTBEAT_FRAME};
+use common::http::send_request;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Ticks
+  version: "1.0"
+paths:
+  /ticks:
+    get:
+      operationId: ticks
+      responses:
+        "200":
+          description: Tick events
+          content:
+            text/event-stream:
+              schema:
+                type: object
+                required: [n]
+                properties:
+                  n: { type: integer, minimum: 0 }
+"#;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum TickEvent {
+    Tick { n: i64 },
+    Done { n: i64 },
+}
+
+impl SseEvent for TickEvent {
+    fn event_name(&self) -> Option<&str> {
+        match self {
+            TickEvent::Tick { .. } => Some("tick"),
+            TickEvent::Done { .. } => Some("done"),
+        }
+    }
+}
+
+struct NoParams;
+
+impl TryFrom<HandlerRequest> for NoParams {
+    type Error = anyhow::Error;
+
+    fn try_from(_req: HandlerRequest) -> Result<Self, Self::Error> {
+        Ok(NoParams)
+    }
+}
+
+#[derive(Clone)]
+struct Ticks;
+
+impl Handler for Ticks {
+    type Request = NoParams;
+    type Response = SseResponse<TickEvent>;
+
+    fn handle(&self, _req: TypedHandlerRequest<NoParams>) -> SseResponse<TickEvent> {
+        let (events, stream) = SseResponse::channel();
+        // SAFETY: the coroutine only sends on a channel it owns and then exits.
+        let producer = unsafe {
+            may::coroutine::Builder::new().spawn(move || {
+                for n in 0..3 {
+                    events.send(TickEvent::Tick { n });
+                    may::coroutine::sleep(Duration::from_millis(30));
+                }
+                // Breaks `minimum: 0`: logged in warn mode, still sent.
+                events.send(TickEvent::Done { n: -1 });
+            })
+        };
+        assert!(producer.is_ok());
+        stream.heartbeat(Duration::from_millis(5))
+    }
+}
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    assert!(routes[0].sse, "text/event-stream-only operation is SSE");
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_typed("ticks", Ticks);
+    }
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// `(event name, parsed data)` of every frame carrying data, in order.
+fn events(stream: &str) -> Vec<(String, Value)> {
+    stream
+        .split("\n\n")
+        .filter_map(|frame| {
+            let mut name = "message".to_string();
+            let mut data = None;
+            for line in frame.lines() {
+                if let Some(v) = line.strip_prefix("event: ") {
+                    name = v.to_string();
+                } else if let Some(v) = line.strip_prefix("data: ") {
+                    data = Some(serde_json::from_str(v).unwrap());
+                }
+            }
+            data.map(|d| (name, d))
+        })
+        .collect()
+}
+
+#[test]
+fn typed_events_are_streamed_as_named_json_frames() {
+    let server = start();
+    let resp = send_request(
+        &server.addr,
+        "GET /ticks HTTP/1.1\r\nHost: localhost\r\nAccept: text/event-stream\r\n\r\n",
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap();
+    let head = head.to_ascii_lowercase();
+    assert!(head.starts_with("http/1.1 200"), "{head}");
+    assert!(head.contains("content-type: text/event-stream"), "{head}");
+    assert!(head.contains("cache-control: no-cache"), "{head}");
+
+    assert_eq!(
+        events(body),
+        [
+            ("tick".to_string(), json!({ "n": 0 })),
+            ("tick".to_string(), json!({ "n": 1 })),
+            ("tick".to_string(), json!({ "n": 2 })),
+            ("done".to_string(), json!({ "n": -1 })),
+        ]
+    );
+    assert!(body.contains(HEARTBEAT_FRAME), "{body}");
+}
+
+#[test]
+fn from_events_without_names_sends_plain_messages() {
+    let stream = SseResponse::from_events([json!({ "a": 1 }), json!("two")]);
+    assert_eq!(
+        stream.collect().unwrap(),
+        "data: {\"a\":1}\n\ndata: \"two\"\n\n"
+    );
+}
@@ -58,6 +58,12 @@ fn test_from_handler_non_string_params() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let typed = TypedHandlerRequest::<Req>::from_handler(req).expect("conversion failed");
@@ -107,6 +113,12 @@ fn test_header_cookie_params() {
         jwt_claims: None,
         reply_tx: tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     };
 
     let typed = TypedHandlerRequest::<HeaderCookieReq>::from_handler(req).unwrap();
@@ -175,6 +187,12 @@ fn test_spawn_typed_success_and_error() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     })
     .unwrap();
     let resp = reply_rx.recv().unwrap();
@@ -196,6 +214,12 @@ fn test_spawn_typed_success_and_error() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     })
     .unwrap();
     let resp = reply_rx.recv().unwrap();
@@ -248,6 +272,12 @@ fn test_spawn_typed_http_json_status_without_panic() {
         jwt_claims: None,
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     })
     .unwrap();
     let resp = reply_rx.recv().unwrap();
@@ -297,6 +327,12 @@ fn test_spawn_typed_preserves_jwt_claims() {
         jwt_claims: Some(serde_json::json!({ "sub": "10" })),
         reply_tx,
         queue_guard: None,
+        peer_addr: None,
+        downstream_headers: HeaderVec::new(),
+        auth_context: None,
+        deadline: None,
+        extensions: Default::default(),
+        raw_body: None,
     })
     .unwrap();
     let resp = reply_rx.recv().unwrap();
@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Lenient handling of undefined security schemes: with
+//! `BRRTR_UNDEFINED_SECURITY_SCHEMES=lenient` a spec whose operation names a scheme missing
+//! from `components.securitySchemes` loads, and that requirement fails closed (`401`) instead
+//! of leaving the route open. Strict mode, the default, is covered in `spec_tests.rs`.
+//!
+//! Every test in this binary runs lenient, so setting the variable cannot race a strict one.
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::security::{SecurityProvider, SecurityRequest};
+use brrtrouter::server::{AppService, TestClient};
+use brrtrouter::spec::{SecurityScheme, UNDEFINED_SECURITY_SCHEMES_ENV};
+use serde_json::json;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Undefined
+  version: "1.0"
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /pets:
+    get:
+      operationId: list_pets
+      security:
+        - ApiKye: []
+      responses:
+        "200": { description: OK }
+  /owners:
+    get:
+      operationId: list_owners
+      security:
+        - ApiKye: []
+        - ApiKey: []
+      responses:
+        "200": { description: OK }
+"#;
+
+/// Accepts `X-API-Key: secret`
+struct HeaderKeyProvider;
+
+impl SecurityProvider for HeaderKeyProvider {
+    fn validate(&self, scheme: &SecurityScheme, _scopes: &[String], req: &SecurityRequest) -> bool {
+        match scheme {
+            SecurityScheme::ApiKey { name, .. } => {
+                req.get_header(&name.to_ascii_lowercase()) == Some("secret")
+            }
+            _ => false,
+        }
+    }
+}
+
+fn ok(req: HandlerRequest) {
+    let _ = req
+        .reply_tx
+        .send(HandlerResponse::json(200, json!({ "ok": true })));
+}
+
+fn client(pre_resolve: bool) -> (TestClient, tempfile::TempDir) {
+    may::config().set_stack_size(0x8000);
+    std::env::set_var(UNDEFINED_SECURITY_SCHEMES_ENV, "lenient");
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    assert!(!schemes.contains_key("ApiKye"));
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("list_pets", ok);
+        dispatcher.register_handler("list_owners", ok);
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes.clone()))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(&spec_path)
+        .build()
+        .unwrap();
+    service.register_security_provider("ApiKey", Arc::new(HeaderKeyProvider));
+    if pre_resolve {
+        // Must not panic on the undefined scheme: those routes keep the per-request check.
+        service.resolve_security(&routes);
+    }
+    (TestClient::new(service), dir)
+}
+
+#[test]
+fn lenient_route_with_only_an_undefined_scheme_fails_closed() {
+    for pre_resolve in [false, true] {
+        let (client, _dir) = client(pre_resolve);
+        client.get("/pets").send().assert_status(401);
+        client
+            .get("/pets")
+            .header("X-API-Key", "secret")
+            .send()
+            .assert_status(401);
+    }
+}
+
+#[test]
+fn lenient_route_still_accepts_its_defined_alternative() {
+    for pre_resolve in [false, true] {
+        let (client, _dir) = client(pre_resolve);
+        client.get("/owners").send().assert_status(401);
+        client
+            .get("/owners")
+            .header("X-API-Key", "secret")
+            .send()
+            .assert_status(200);
+    }
+}
@@ -0,0 +1,148 @@
+#![cfg(unix)]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Serving over a Unix domain socket (`HttpServer::start_unix`): requests over the socket,
+//! stale socket cleanup on start and socket removal on stop.
+
+use brrtrouter::dispatcher::Dispatcher;
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer};
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn service() -> AppService {
+    may::config().set_stack_size(0x8000);
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full("examples/openapi.yaml").unwrap();
+    let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes)));
+    let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(Dispatcher::new()));
+    AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from("examples/openapi.yaml"))
+        .build()
+        .unwrap()
+}
+
+fn get(path: &std::path::Path, uri: &str) -> String {
+    let mut stream = UnixStream::connect(path).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    write!(
+        stream,
+        "GET {uri} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut buf = Vec::new();
+    let mut tmp = [0u8; 1024];
+    loop {
+        match stream.read(&mut tmp) {
+            Ok(0) => break,
+            Ok(n) => buf.extend_from_slice(&tmp[..n]),
+            // Keep-alive servers may hold the socket open; a complete response is enough.
+            Err(_) => break,
+        }
+        let text = String::from_utf8_lossy(&buf);
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let len = head
+                .lines()
+                .filter_map(|l| l.split_once(':'))
+                .find(|(n, _)| n.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, v)| v.trim().parse::<usize>().ok());
+            if len.is_some_and(|len| body.len() >= len) {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&buf).to_string()
+}
+
+#[test]
+fn serves_health_over_unix_socket_and_cleans_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("brrtrouter.sock");
+
+    // Leave a stale socket file behind, as a crashed previous process would.
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let handle = HttpServer(service()).start_unix(&path).unwrap();
+    handle.wait_ready().unwrap();
+    assert_eq!(handle.unix_path(), Some(path.as_path()));
+
+    let response = get(&path, "/health");
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("\"status\""), "{response}");
+
+    handle.stop();
+    assert!(!path.exists(), "socket file must be removed on stop");
+}
+
+#[test]
+fn serves_as_soon_as_start_returns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ready.sock");
+    // No `wait_ready`: the relayed server is already listening when `start_unix` returns.
+    let handle = HttpServer(service()).start_unix(&path).unwrap();
+    let response = get(&path, "/health");
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    handle.stop();
+}
+
+#[test]
+fn closes_idle_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("idle.sock");
+    let mut service = service();
+    service.set_keep_alive(true, 1, 0);
+    let handle = HttpServer(service).start_unix(&path).unwrap();
+    handle.wait_ready().unwrap();
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut tmp = [0u8; 4096];
+    let n = stream.read(&mut tmp).unwrap();
+    let head = String::from_utf8_lossy(&tmp[..n]).to_string();
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert!(head.contains("Connection: keep-alive"), "{head}");
+
+    // Nothing more is sent: the relay closes the connection after the 1s idle timeout,
+    // well before the 5s read timeout.
+    let started = std::time::Instant::now();
+    loop {
+        match stream.read(&mut tmp) {
+            Ok(0) => break,
+            Ok(_) => continue,
+            Err(e) => panic!("connection not closed while idle: {e}"),
+        }
+    }
+    assert!(started.elapsed() < Duration::from_secs(4));
+
+    handle.stop();
+}
+
+#[test]
+fn refuses_live_socket_and_regular_files() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let live = dir.path().join("live.sock");
+    let _owner = UnixListener::bind(&live).unwrap();
+    let err = HttpServer(service()).start_unix(&live).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    assert!(live.exists());
+
+    let regular = dir.path().join("not-a-socket");
+    std::fs::write(&regular, b"keep me").unwrap();
+    let err = HttpServer(service()).start_unix(&regular).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read(&regular).unwrap(), b"keep me");
+}
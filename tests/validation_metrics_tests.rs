@@ -0,0 +1,173 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Rejected requests increment `brrtrouter_request_validation_failures_total`, labelled by
+//! route template and coarse failure category — never by field name or value.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::middleware::{MetricsMiddleware, ValidationFailureCategory};
+use brrtrouter::router::Router;
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::json;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Validation metrics
+  version: "1.0"
+paths:
+  /items/{id}:
+    post:
+      operationId: update_item
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema: { type: integer }
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              additionalProperties: false
+              required: [name, kind]
+              properties:
+                name: { type: string }
+                kind: { type: string, enum: [a, b] }
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    metrics: Arc<MetricsMiddleware>,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("update_item", |req: HandlerRequest| {
+            let _ = req
+                .reply_tx
+                .send(HandlerResponse::json(200, json!({"ok": true})));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    let metrics = Arc::new(MetricsMiddleware::new());
+    service.set_metrics_middleware(metrics.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        metrics,
+        _dir: dir,
+    }
+}
+
+fn post(server: &Server, path: &str, body: &str) -> u16 {
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST {path} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        ),
+    );
+    resp.split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+fn failures(server: &Server, category: ValidationFailureCategory) -> usize {
+    server
+        .metrics
+        .validation_failure_stats()
+        .get(&("/items/{id}".to_string(), category))
+        .copied()
+        .unwrap_or(0)
+}
+
+#[test]
+fn missing_required_field_increments_its_category() {
+    let server = start();
+    assert_eq!(post(&server, "/items/1", r#"{"name":"x"}"#), 400);
+
+    assert_eq!(
+        failures(&server, ValidationFailureCategory::MissingRequired),
+        1
+    );
+    assert_eq!(server.metrics.validation_failure_stats().len(), 1);
+
+    let resp = send_request(
+        &server.addr,
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert!(resp.contains(
+        "brrtrouter_request_validation_failures_total{path=\"/items/{id}\",category=\"missing_required\"} 1"
+    ));
+}
+
+#[test]
+fn each_category_is_counted_once_per_request() {
+    let server = start();
+    assert_eq!(post(&server, "/items/1", r#"{"name":"x","kind":"a"}"#), 200);
+    assert!(server.metrics.validation_failure_stats().is_empty());
+
+    // Wrong type, bad enum and an unknown property in one body.
+    assert_eq!(
+        post(&server, "/items/1", r#"{"name":1,"kind":"z","extra":true}"#),
+        400
+    );
+    assert_eq!(
+        failures(&server, ValidationFailureCategory::TypeMismatch),
+        1
+    );
+    assert_eq!(failures(&server, ValidationFailureCategory::Enum), 1);
+    assert_eq!(
+        failures(&server, ValidationFailureCategory::AdditionalProperty),
+        1
+    );
+
+    // Path parameters are validated too.
+    assert_eq!(
+        post(&server, "/items/abc", r#"{"name":"x","kind":"a"}"#),
+        400
+    );
+    assert_eq!(
+        failures(&server, ValidationFailureCategory::TypeMismatch),
+        2
+    );
+}
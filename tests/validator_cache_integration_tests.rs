@@ -122,14 +122,13 @@ fn test_validator_cache_with_app_service() {
     // Create service with cache enabled (default)
     let router = Arc::new(arc_swap::ArcSwap::from_pointee(Router::new(routes)));
     let dispatcher = Arc::new(arc_swap::ArcSwap::from_pointee(Dispatcher::new()));
-    let service = AppService::new(
-        router,
-        dispatcher,
-        security_schemes,
-        PathBuf::from(spec_path),
-        None,
-        None,
-    );
+    let service = AppService::builder()
+        .shared_router(router)
+        .shared_dispatcher(dispatcher)
+        .security_schemes(security_schemes)
+        .spec_path(PathBuf::from(spec_path))
+        .build()
+        .unwrap();
 
     // Verify cache is initialized
     assert_eq!(
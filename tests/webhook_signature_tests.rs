@@ -0,0 +1,141 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! Webhook signature verification: handlers see the request body exactly as sent
+//! (`HandlerRequest::raw_body`) and check its HMAC-SHA256 with
+//! `security::verify_hmac_sha256`; the re-serialized JSON body would not verify.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse, HeaderLookup};
+use brrtrouter::router::Router;
+use brrtrouter::security::{hmac_sha256, verify_hmac_sha256};
+use brrtrouter::server::{AppService, HttpServer, ServerHandle};
+use common::http::send_request;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+const SECRET: &[u8] = b"whsec_test";
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Webhooks
+  version: "1.0"
+paths:
+  /webhook:
+    post:
+      operationId: receive_webhook
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { type: object }
+      responses:
+        "200": { description: OK }
+"#;
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+
+    let mut dispatcher = Dispatcher::new();
+    unsafe {
+        dispatcher.register_handler("receive_webhook", |req: HandlerRequest| {
+            let signature = req.headers.get("x-hub-signature-256").unwrap_or("");
+            let raw = req.raw_body.clone().unwrap_or_default();
+            let reserialized = serde_json::to_vec(&req.body).unwrap();
+            let _ = req.reply_tx.send(HandlerResponse::json(
+                200,
+                json!({
+                    "verified": verify_hmac_sha256(SECRET, &raw, signature),
+                    "reserialized_verified": verify_hmac_sha256(SECRET, &reserialized, signature),
+                    "raw": String::from_utf8_lossy(&raw),
+                }),
+            ));
+        });
+    }
+
+    let service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let handle = HttpServer(service).start(addr).unwrap();
+    handle.wait_ready().unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+fn signature(payload: &str) -> String {
+    let mac = hmac_sha256(SECRET, payload.as_bytes());
+    let hex: String = mac.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// POST `payload` with `signature`; returns (status, JSON body).
+fn post(server: &Server, payload: &str, signature: &str) -> (u16, Value) {
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "POST /webhook HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             X-Hub-Signature-256: {signature}\r\nContent-Length: {}\r\n\r\n{payload}",
+            payload.len()
+        ),
+    );
+    let (head, body) = resp.split_once("\r\n\r\n").unwrap_or((&resp, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+// Key order and whitespace differ from what serde_json would produce.
+const PAYLOAD: &str = "{\"zeta\": 1,  \"action\" : \"opened\",\n \"ids\": [1, 2]}";
+
+#[test]
+fn signed_payload_verifies_against_raw_body() {
+    let server = start();
+    let (status, body) = post(&server, PAYLOAD, &signature(PAYLOAD));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["raw"], PAYLOAD);
+    assert_eq!(body["verified"], true);
+    assert_eq!(body["reserialized_verified"], false);
+}
+
+#[test]
+fn tampered_payload_does_not_verify() {
+    let server = start();
+    let tampered = PAYLOAD.replace("opened", "closed");
+    let (status, body) = post(&server, &tampered, &signature(PAYLOAD));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["verified"], false);
+}
@@ -0,0 +1,271 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, unsafe_code)]
+
+//! WebSocket routes (`x-websocket: true`): the opening handshake is answered with
+//! `101 Switching Protocols`, text and binary messages are echoed, pings get pongs, oversized
+//! messages close with `1009`, plain HTTP requests to the route get `426`, and every other
+//! route keeps working behind the WebSocket listener.
+
+mod common;
+
+use brrtrouter::dispatcher::{Dispatcher, HandlerRequest, HandlerResponse};
+use brrtrouter::router::Router;
+use brrtrouter::server::websocket::accept_key;
+use brrtrouter::server::{
+    AppService, HttpServer, ServerHandle, WebSocketChannel, WebSocketConfig, WebSocketRequest,
+};
+use common::http::send_request;
+use serde_json::json;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SPEC: &str = r#"
+openapi: 3.1.0
+info:
+  title: Echo
+  version: "1.0"
+paths:
+  /echo:
+    get:
+      operationId: echo
+      x-websocket: true
+      parameters:
+        - name: room
+          in: query
+          required: true
+          schema: { type: string }
+      responses:
+        "101":
+          description: Switching Protocols
+  /health-check:
+    get:
+      operationId: health_check
+      responses:
+        "200":
+          description: OK
+          content:
+            application/json:
+              schema: { type: object }
+"#;
+
+const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+
+struct Server {
+    handle: Option<ServerHandle>,
+    addr: SocketAddr,
+    _dir: tempfile::TempDir,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop();
+        }
+    }
+}
+
+fn echo(req: WebSocketRequest, mut ws: WebSocketChannel) {
+    let room = req.get_query_param("room").unwrap_or_default().to_string();
+    ws.send_text(&format!("joined {room}")).unwrap();
+    while let Ok(Some(message)) = ws.recv() {
+        if ws.send(&message).is_err() {
+            break;
+        }
+    }
+}
+
+fn start() -> Server {
+    may::config().set_stack_size(0x8000);
+    let dir = tempfile::tempdir().unwrap();
+    let spec_path = dir.path().join("openapi.yaml");
+    std::fs::write(&spec_path, SPEC).unwrap();
+    let (routes, schemes, _slug) = brrtrouter::load_spec_full(spec_path.to_str().unwrap()).unwrap();
+    assert!(routes.iter().any(|r| r.websocket));
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register_websocket_handler("echo", echo);
+    unsafe {
+        dispatcher.register_handler("health_check", |req: HandlerRequest| {
+            let client_ip = req.client_ip(&[]).map(|ip| ip.to_string());
+            let _ = req.reply_tx.send(HandlerResponse::json(
+                200,
+                json!({ "ok": true, "client_ip": client_ip }),
+            ));
+        });
+    }
+    let mut service = AppService::builder()
+        .router(Router::new(routes))
+        .dispatcher(dispatcher)
+        .security_schemes(schemes)
+        .spec_path(PathBuf::from(&spec_path))
+        .build()
+        .unwrap();
+    service.set_websocket(Some(WebSocketConfig {
+        max_message_bytes: 64,
+    }));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    // No `wait_ready`: both listeners accept once `start_with_websockets` returns.
+    let handle = HttpServer(service).start_with_websockets(addr).unwrap();
+    Server {
+        handle: Some(handle),
+        addr,
+        _dir: dir,
+    }
+}
+
+/// Open a WebSocket to `/echo?room=...` and return the stream after the `101` head.
+fn connect(addr: &SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    write!(
+        stream,
+        "GET /echo?room=lobby HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+         Connection: Upgrade\r\nSec-WebSocket-Key: {KEY}\r\nSec-WebSocket-Version: 13\r\n\r\n"
+    )
+    .unwrap();
+    let head = read_head(&mut stream).to_ascii_lowercase();
+    assert!(head.starts_with("http/1.1 101"), "{head}");
+    assert!(head.contains("upgrade: websocket"), "{head}");
+    assert!(
+        head.contains(&format!(
+            "sec-websocket-accept: {}",
+            accept_key(KEY).to_ascii_lowercase()
+        )),
+        "{head}"
+    );
+    stream
+}
+
+fn read_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+    String::from_utf8(head).unwrap()
+}
+
+/// Write one masked client frame.
+fn send_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) {
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(0x80 | n as u8),
+        n => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame).unwrap();
+}
+
+/// Read one unmasked server frame as `(opcode, payload)`.
+fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head).unwrap();
+    assert_eq!(head[0] & 0x80, 0x80, "server frames are unfragmented");
+    assert_eq!(head[1] & 0x80, 0, "server frames are unmasked");
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut ext = [0u8; 2];
+            stream.read_exact(&mut ext).unwrap();
+            u16::from_be_bytes(ext) as usize
+        }
+        n => n as usize,
+    };
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).unwrap();
+    (head[0] & 0x0f, payload)
+}
+
+fn close_code(payload: &[u8]) -> u16 {
+    u16::from_be_bytes([payload[0], payload[1]])
+}
+
+#[test]
+fn text_and_binary_messages_are_echoed() {
+    let server = start();
+    let mut ws = connect(&server.addr);
+    assert_eq!(read_frame(&mut ws), (0x1, b"joined lobby".to_vec()));
+
+    send_frame(&mut ws, 0x1, b"hello");
+    assert_eq!(read_frame(&mut ws), (0x1, b"hello".to_vec()));
+    send_frame(&mut ws, 0x2, &[0, 1, 2, 255]);
+    assert_eq!(read_frame(&mut ws), (0x2, vec![0, 1, 2, 255]));
+
+    send_frame(&mut ws, 0x9, b"are you there");
+    assert_eq!(read_frame(&mut ws), (0xA, b"are you there".to_vec()));
+
+    send_frame(&mut ws, 0x8, &1000u16.to_be_bytes());
+    let (opcode, payload) = read_frame(&mut ws);
+    assert_eq!(opcode, 0x8);
+    assert_eq!(close_code(&payload), 1000);
+}
+
+#[test]
+fn oversized_message_closes_with_1009() {
+    let server = start();
+    let mut ws = connect(&server.addr);
+    read_frame(&mut ws);
+
+    send_frame(&mut ws, 0x1, &[b'x'; 65]);
+    let (opcode, payload) = read_frame(&mut ws);
+    assert_eq!(opcode, 0x8);
+    assert_eq!(close_code(&payload), 1009);
+}
+
+#[test]
+fn upgrade_missing_required_query_param_is_rejected() {
+    let server = start();
+    let resp = send_request(
+        &server.addr,
+        &format!(
+            "GET /echo HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {KEY}\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        ),
+    );
+    assert!(resp.starts_with("HTTP/1.1 400"), "{resp}");
+}
+
+#[test]
+fn plain_http_to_websocket_route_is_426_and_other_routes_still_work() {
+    let server = start();
+    let resp = send_request(
+        &server.addr,
+        "GET /echo?room=lobby HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert!(resp.starts_with("HTTP/1.1 426"), "{resp}");
+    assert!(
+        resp.to_ascii_lowercase().contains("upgrade: websocket"),
+        "{resp}"
+    );
+
+    let resp = send_request(
+        &server.addr,
+        "GET /health-check HTTP/1.1\r\nHost: localhost\r\n\r\n",
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+    assert!(resp.contains("\"ok\":true"), "{resp}");
+}
+
+#[test]
+fn relayed_requests_carry_the_client_address() {
+    let server = start();
+    // The relay names the client in its authenticated preface; the same header in a request
+    // is ignored.
+    let resp = send_request(
+        &server.addr,
+        "GET /health-check HTTP/1.1\r\nHost: localhost\r\nx-brrtr-peer: 203.0.113.9\r\n\r\n",
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"), "{resp}");
+    assert!(resp.contains("\"client_ip\":\"127.0.0.1\""), "{resp}");
+}
@@ -121,6 +121,12 @@ fn test_worker_pool_shed_mode() {
             jwt_claims: None,
             reply_tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         };
 
         match pool.dispatch(req) {
@@ -218,6 +224,12 @@ fn test_worker_pool_block_mode() {
             jwt_claims: None,
             reply_tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         };
 
         match pool.dispatch(req) {
@@ -293,6 +305,12 @@ fn test_worker_pool_metrics() {
             jwt_claims: None,
             reply_tx,
             queue_guard: None,
+            peer_addr: None,
+            downstream_headers: HeaderVec::new(),
+            auth_context: None,
+            deadline: None,
+            extensions: Default::default(),
+            raw_body: None,
         };
 
         let _ = pool.dispatch(req);